@@ -97,6 +97,19 @@ pub fn set_aim(session: xr::Session, path: UserPath, pose: xr::Posef) {
     get_hand_data(path, &session).aim_pose.store(pose);
 }
 
+/// Sets the linear and angular velocity of the grip of a hand, relative to local space.
+pub fn set_velocity(
+    session: xr::Session,
+    path: UserPath,
+    linear: xr::Vector3f,
+    angular: xr::Vector3f,
+) {
+    let session = session.to_handle().unwrap();
+    get_hand_data(path, &session)
+        .velocity
+        .store((linear, angular));
+}
+
 #[track_caller]
 pub fn get_suggested_bindings(action: xr::Action, profile: xr::Path) -> Vec<String> {
     let action = xr::Action::to_handle(action).unwrap();
@@ -122,6 +135,23 @@ pub fn session_frame_state(session: xr::Session) -> FrameState {
     session.frame_state.load()
 }
 
+/// Moves the session to a new state, as if the runtime changed it (i.e. to show a system menu).
+pub fn set_session_state(session: xr::Session, state: xr::SessionState) {
+    let s = session.to_handle().unwrap();
+    s.state.store(state);
+    send_event(
+        &s.event_sender,
+        xr::EventDataSessionStateChanged {
+            ty: xr::EventDataSessionStateChanged::TYPE,
+            next: std::ptr::null(),
+            session,
+            state,
+            time: xr::Time::from_nanos(0),
+        },
+        None,
+    );
+}
+
 macro_rules! fn_unimplemented_impl {
     ($($param:ident),+) => {
         fn_unimplemented_impl!($($param),+  -> []);
@@ -245,7 +275,7 @@ pub extern "system" fn get_instance_proc_addr(
                 CreateActionSpace,
                 LocateSpace,
                 (EnumerateViewConfigurations),
-                (EnumerateEnvironmentBlendModes),
+                EnumerateEnvironmentBlendModes,
                 (GetViewConfigurationProperties),
                 (EnumerateViewConfigurationViews),
                 BeginFrame,
@@ -269,8 +299,8 @@ pub extern "system" fn get_instance_proc_addr(
                 AttachSessionActionSets,
                 GetCurrentInteractionProfile,
                 SyncActions,
-                (EnumerateBoundSourcesForAction),
-                (GetInputSourceLocalizedName)
+                EnumerateBoundSourcesForAction,
+                GetInputSourceLocalizedName
                 ]
 
                 other => {
@@ -291,20 +321,27 @@ extern "system" fn enumerate_instance_extension_properties(
     properties: *mut xr::ExtensionProperties,
 ) -> xr::Result {
     assert!(layer_name.is_null());
-    unsafe { *property_count_output = 1 };
+    const EXTENSIONS: &[&[u8]] = &[
+        xr::KHR_VULKAN_ENABLE_EXTENSION_NAME,
+        xr::BD_CONTROLLER_INTERACTION_EXTENSION_NAME,
+        xr::FB_TOUCH_CONTROLLER_PRO_EXTENSION_NAME,
+        xr::EXT_HAND_INTERACTION_EXTENSION_NAME,
+    ];
+    unsafe { *property_count_output = EXTENSIONS.len() as u32 };
     if property_capacity_input > 0 {
         let props =
             unsafe { std::slice::from_raw_parts_mut(properties, property_capacity_input as usize) };
-        props[0] = xr::ExtensionProperties {
-            ty: xr::ExtensionProperties::TYPE,
-            next: std::ptr::null_mut(),
-            extension_name: [0 as c_char; xr::MAX_EXTENSION_NAME_SIZE],
-            extension_version: 1,
-        };
-        let name = xr::KHR_VULKAN_ENABLE_EXTENSION_NAME;
-        let name =
-            unsafe { std::slice::from_raw_parts(name.as_ptr() as *const c_char, name.len()) };
-        props[0].extension_name[..name.len()].copy_from_slice(name);
+        for (prop, name) in props.iter_mut().zip(EXTENSIONS) {
+            *prop = xr::ExtensionProperties {
+                ty: xr::ExtensionProperties::TYPE,
+                next: std::ptr::null_mut(),
+                extension_name: [0 as c_char; xr::MAX_EXTENSION_NAME_SIZE],
+                extension_version: 1,
+            };
+            let name =
+                unsafe { std::slice::from_raw_parts(name.as_ptr() as *const c_char, name.len()) };
+            prop.extension_name[..name.len()].copy_from_slice(name);
+        }
     }
     xr::Result::SUCCESS
 }
@@ -400,6 +437,8 @@ struct HandData {
     profile: AtomicCell<xr::Path>,
     grip_pose: AtomicCell<xr::Posef>,
     aim_pose: AtomicCell<xr::Posef>,
+    /// Linear and angular velocity.
+    velocity: AtomicCell<(xr::Vector3f, xr::Vector3f)>,
 }
 
 impl Default for HandData {
@@ -409,6 +448,7 @@ impl Default for HandData {
             profile: Default::default(),
             grip_pose: xr::Posef::IDENTITY.into(),
             aim_pose: xr::Posef::IDENTITY.into(),
+            velocity: Default::default(),
         }
     }
 }
@@ -425,6 +465,8 @@ struct Session {
     state_synced: AtomicBool,
     should_render: AtomicBool,
     frame_state: AtomicCell<FrameState>,
+    /// Ending the session exits it, instead of going back to idle.
+    exit_requested: AtomicBool,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -585,6 +627,24 @@ impl Space {
             pose: ret,
         })
     }
+
+    /// The velocity of the hand at this space's offset from it, for a location returned by
+    /// get_pose_relative_to_local.
+    fn get_velocity_relative_to_local(&self, location: &xr::SpaceLocation) -> Option<(Vec3, Vec3)> {
+        if !location.location_flags.contains(*LOCATION_FLAGS_TRACKED) {
+            return None;
+        }
+        let session = self.session.upgrade()?;
+        let hand = self.hand.unwrap_or(UserPath::LeftHand);
+        let (linear, angular) = get_hand_data(hand, &session).velocity.load();
+        let linear = Vec3::new(linear.x, linear.y, linear.z);
+        let angular = Vec3::new(angular.x, angular.y, angular.z);
+
+        let target = pose_to_mat(location.pose);
+        let hand_pose = target * pose_to_mat(self.offset).inverse();
+        let lever = Vec3::from(target.translation - hand_pose.translation);
+        Some((linear + angular.cross(lever), angular))
+    }
 }
 
 struct ActionSet {
@@ -713,6 +773,7 @@ extern "system" fn create_session(
         state_synced: true.into(),
         should_render: false.into(),
         frame_state: FrameState::Ended.into(),
+        exit_requested: false.into(),
     });
 
     let tx = sess.event_sender.clone();
@@ -1038,6 +1099,7 @@ extern "system" fn path_to_string(
 
 extern "system" fn request_exit_session(session: xr::Session) -> xr::Result {
     let sess = get_handle!(session);
+    sess.exit_requested.store(true, Ordering::Relaxed);
     send_event(
         &sess.event_sender,
         xr::EventDataSessionStateChanged {
@@ -1054,13 +1116,19 @@ extern "system" fn request_exit_session(session: xr::Session) -> xr::Result {
 
 extern "system" fn end_session(session: xr::Session) -> xr::Result {
     let sess = get_handle!(session);
+    let state = if sess.exit_requested.load(Ordering::Relaxed) {
+        xr::SessionState::EXITING
+    } else {
+        xr::SessionState::IDLE
+    };
+    sess.state.store(state);
     send_event(
         &sess.event_sender,
         xr::EventDataSessionStateChanged {
             ty: xr::EventDataSessionStateChanged::TYPE,
             next: std::ptr::null(),
             session,
-            state: xr::SessionState::EXITING,
+            state,
             time: xr::Time::from_nanos(0),
         },
         None,
@@ -1336,6 +1404,105 @@ extern "system" fn get_current_interaction_profile(
     xr::Result::SUCCESS
 }
 
+extern "system" fn enumerate_bound_sources_for_action(
+    session: xr::Session,
+    info: *const xr::BoundSourcesForActionEnumerateInfo,
+    capacity: u32,
+    count: *mut u32,
+    sources: *mut xr::Path,
+) -> xr::Result {
+    let session = get_handle!(session);
+    let Some(instance) = session.instance.upgrade() else {
+        return xr::Result::ERROR_INSTANCE_LOST;
+    };
+    let info = unsafe { info.as_ref() }.unwrap();
+    let action = get_handle!(info.action);
+
+    // Only the bindings for the current profile of each hand are bound.
+    let suggested = action.suggested.lock().unwrap();
+    let mut bound = Vec::new();
+    for hand in [UserPath::LeftHand, UserPath::RightHand] {
+        let profile = get_hand_data(hand, &session).profile.load();
+        for path in suggested.get(&profile).into_iter().flatten() {
+            let in_hand = instance
+                .get_path_value(*path)
+                .ok()
+                .flatten()
+                .is_some_and(|p| p.starts_with(hand.to_path()));
+            if in_hand && !bound.contains(path) {
+                bound.push(*path);
+            }
+        }
+    }
+
+    unsafe { count.write(bound.len() as u32) };
+    if capacity > 0 {
+        if capacity < bound.len() as u32 {
+            return xr::Result::ERROR_SIZE_INSUFFICIENT;
+        }
+        let out = unsafe { std::slice::from_raw_parts_mut(sources, bound.len()) };
+        out.copy_from_slice(&bound);
+    }
+
+    xr::Result::SUCCESS
+}
+
+extern "system" fn get_input_source_localized_name(
+    session: xr::Session,
+    info: *const xr::InputSourceLocalizedNameGetInfo,
+    capacity: u32,
+    count: *mut u32,
+    buffer: *mut c_char,
+) -> xr::Result {
+    let session = get_handle!(session);
+    let Some(instance) = session.instance.upgrade() else {
+        return xr::Result::ERROR_INSTANCE_LOST;
+    };
+    let info = unsafe { info.as_ref() }.unwrap();
+    let Ok(Some(path)) = instance.get_path_value(info.source_path) else {
+        return xr::Result::ERROR_PATH_INVALID;
+    };
+    let Some((user, component)) = path.split_once("/input/") else {
+        return xr::Result::ERROR_PATH_UNSUPPORTED;
+    };
+
+    let mut parts = Vec::new();
+    if info
+        .which_components
+        .contains(xr::InputSourceLocalizedNameFlags::USER_PATH)
+    {
+        parts.push(match UserPath::from_path(user) {
+            Some(UserPath::LeftHand) => "Left Hand",
+            Some(UserPath::RightHand) => "Right Hand",
+            None => user,
+        });
+    }
+    if info
+        .which_components
+        .contains(xr::InputSourceLocalizedNameFlags::INTERACTION_PROFILE)
+    {
+        parts.push("Fake Controller");
+    }
+    if info
+        .which_components
+        .contains(xr::InputSourceLocalizedNameFlags::COMPONENT)
+    {
+        parts.push(component);
+    }
+
+    let buf = [parts.join(" ").as_bytes(), &[0]].concat();
+    unsafe { count.write(buf.len() as u32) };
+    if capacity > 0 {
+        if capacity < buf.len() as u32 {
+            return xr::Result::ERROR_SIZE_INSUFFICIENT;
+        }
+        let out = unsafe { std::slice::from_raw_parts_mut(buffer as *mut u8, buf.len()) };
+        out.copy_from_slice(&buf);
+    }
+
+    xr::Result::SUCCESS
+}
+
 extern "system" fn locate_space(
     space: xr::Space,
     base_space: xr::Space,
@@ -1357,29 +1524,50 @@ extern "system" fn locate_space(
         pose: xr::Posef::IDENTITY,
     };
 
+    let mut velo = None;
     if !next.is_null() {
         let header = next as *mut xr::BaseOutStructure;
         unsafe {
             if *&raw mut (*header).ty == xr::SpaceVelocity::TYPE {
-                let velo = next as *mut xr::SpaceVelocity;
-                velo.write(xr::SpaceVelocity {
+                let v = next as *mut xr::SpaceVelocity;
+                v.write(xr::SpaceVelocity {
                     ty: xr::SpaceVelocity::TYPE,
-                    next: *&raw mut (*velo).next,
+                    next: *&raw mut (*v).next,
                     velocity_flags: xr::SpaceVelocityFlags::EMPTY,
                     linear_velocity: Default::default(),
                     angular_velocity: Default::default(),
                 });
-                out_loc.next = velo as _;
+                velo = Some(v);
             }
         }
     }
     if base_space == *LOCAL {
         match space.get_pose_relative_to_local() {
             Ok(loc) => {
-                out_loc = loc;
+                out_loc.location_flags = loc.location_flags;
+                out_loc.pose = loc.pose;
             }
             Err(e) => return e,
         };
+        // Velocities are only reported relative to local space.
+        if let Some((velo, (linear, angular))) =
+            velo.zip(space.get_velocity_relative_to_local(&out_loc))
+        {
+            unsafe {
+                (*velo).velocity_flags =
+                    xr::SpaceVelocityFlags::LINEAR_VALID | xr::SpaceVelocityFlags::ANGULAR_VALID;
+                (*velo).linear_velocity = xr::Vector3f {
+                    x: linear.x,
+                    y: linear.y,
+                    z: linear.z,
+                };
+                (*velo).angular_velocity = xr::Vector3f {
+                    x: angular.x,
+                    y: angular.y,
+                    z: angular.z,
+                };
+            }
+        }
     } else {
         let base_space = get_handle!(base_space);
         let base_loc = match base_space.get_pose_relative_to_local() {
@@ -1408,6 +1596,9 @@ extern "system" fn locate_space(
 
     xr::Result::SUCCESS
 }
+/// The second is VK_FORMAT_R16G16B16A16_SFLOAT, for fallbacks from high bit depth formats.
+const SWAPCHAIN_FORMATS: [i64; 2] = [0, 97];
+
 extern "system" fn create_swapchain(
     _session: xr::Session,
     info: *const xr::SwapchainCreateInfo,
@@ -1417,7 +1608,7 @@ extern "system" fn create_swapchain(
     if info.width == 0 || info.height == 0 {
         return xr::Result::ERROR_VALIDATION_FAILURE;
     }
-    if info.format != 0 {
+    if !SWAPCHAIN_FORMATS.contains(&info.format) {
         return xr::Result::ERROR_SWAPCHAIN_FORMAT_UNSUPPORTED;
     }
     let swap = Arc::new(Swapchain {
@@ -1438,13 +1629,33 @@ extern "system" fn enumerate_swapchain_formats(
     capacity: u32,
     output: *mut u32,
     formats: *mut i64,
+) -> xr::Result {
+    unsafe {
+        output.write(SWAPCHAIN_FORMATS.len() as u32);
+    }
+    if capacity as usize >= SWAPCHAIN_FORMATS.len() {
+        let formats = unsafe { std::slice::from_raw_parts_mut(formats, capacity as usize) };
+        formats[..SWAPCHAIN_FORMATS.len()].copy_from_slice(&SWAPCHAIN_FORMATS);
+    }
+
+    xr::Result::SUCCESS
+}
+
+extern "system" fn enumerate_environment_blend_modes(
+    _instance: xr::Instance,
+    _system_id: xr::SystemId,
+    _view_configuration_type: xr::ViewConfigurationType,
+    capacity: u32,
+    output: *mut u32,
+    modes: *mut xr::EnvironmentBlendMode,
 ) -> xr::Result {
     unsafe {
         output.write(1);
     }
     if capacity >= 1 {
-        let formats = unsafe { std::slice::from_raw_parts_mut(formats, capacity as usize) };
-        formats[0] = 0;
+        unsafe {
+            modes.write(xr::EnvironmentBlendMode::OPAQUE);
+        }
     }
 
     xr::Result::SUCCESS
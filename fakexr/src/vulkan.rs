@@ -39,7 +39,7 @@ pub extern "system" fn get_instance_proc_addr(
     let name = unsafe { CStr::from_ptr(name) };
 
     if instance.is_null() {
-        get_fn![name => CreateInstance]
+        get_fn![name => CreateInstance, EnumerateInstanceExtensionProperties]
     } else {
         get_fn![name =>
             GetPhysicalDeviceQueueFamilyProperties,
@@ -94,6 +94,25 @@ extern "system" fn create_instance(
     vk::Result::SUCCESS
 }
 
+/// The runtime's instance extensions, minus VK_missing.
+extern "system" fn enumerate_instance_extension_properties(
+    _: *const c_char,
+    property_count: *mut u32,
+    properties: *mut vk::ExtensionProperties,
+) -> vk::Result {
+    const EXTS: [&CStr; 2] = [c"VK_foo", c"VK_bar"];
+    unsafe { *property_count = EXTS.len() as u32 };
+    if !properties.is_null() {
+        let properties = unsafe { std::slice::from_raw_parts_mut(properties, EXTS.len()) };
+        for (props, ext) in properties.iter_mut().zip(EXTS) {
+            *props = vk::ExtensionProperties::default()
+                .extension_name(ext)
+                .unwrap();
+        }
+    }
+    vk::Result::SUCCESS
+}
+
 extern "system" fn destroy_instance(instance: vk::Instance, _: *const vk::AllocationCallbacks<'_>) {
     drop(unsafe { Box::from_raw(instance.as_raw() as *mut Instance) });
 }
@@ -153,41 +172,54 @@ pub(crate) mod xr {
     use openxr_sys as xr;
     use std::ffi::{c_char, CStr};
 
-    pub extern "system" fn get_vulkan_instance_extensions_k_h_r(
-        _: xr::Instance,
-        _: xr::SystemId,
+    fn write_extensions(
+        exts: &CStr,
         buffer_capacity_input: u32,
         buffer_count_output: *mut u32,
         buffer: *mut c_char,
     ) -> xr::Result {
-        static EXTS: &CStr = c"VK_foo VK_bar";
-        static LEN: usize = EXTS.count_bytes() + 1;
-
+        let len = exts.count_bytes() + 1;
         if !buffer_count_output.is_null() {
-            unsafe { *buffer_count_output = LEN as u32 };
+            unsafe { *buffer_count_output = len as u32 };
         }
-        if buffer_capacity_input >= LEN as u32 {
+        if buffer_capacity_input >= len as u32 {
             let buf =
                 unsafe { std::slice::from_raw_parts_mut(buffer, buffer_capacity_input as usize) };
-            let bytes = EXTS.to_bytes_with_nul();
+            let bytes = exts.to_bytes_with_nul();
             let bytes = unsafe { std::slice::from_raw_parts(bytes.as_ptr() as _, bytes.len()) };
-            buf[..LEN].copy_from_slice(bytes);
+            buf[..len].copy_from_slice(bytes);
         } else if buffer_capacity_input > 0 {
             return xr::Result::ERROR_SIZE_INSUFFICIENT;
         }
         xr::Result::SUCCESS
     }
 
+    pub extern "system" fn get_vulkan_instance_extensions_k_h_r(
+        _: xr::Instance,
+        _: xr::SystemId,
+        buffer_capacity_input: u32,
+        buffer_count_output: *mut u32,
+        buffer: *mut c_char,
+    ) -> xr::Result {
+        // VK_missing isn't available from the fake Vulkan implementation.
+        write_extensions(
+            c"VK_foo VK_missing VK_bar",
+            buffer_capacity_input,
+            buffer_count_output,
+            buffer,
+        )
+    }
+
     pub extern "system" fn get_vulkan_device_extensions_k_h_r(
-        instance: xr::Instance,
-        system_id: xr::SystemId,
+        _: xr::Instance,
+        _: xr::SystemId,
         buffer_capacity_input: u32,
         buffer_count_output: *mut u32,
         buffer: *mut c_char,
     ) -> xr::Result {
-        get_vulkan_instance_extensions_k_h_r(
-            instance,
-            system_id,
+        // Runtimes may list an extension more than once.
+        write_extensions(
+            c"VK_foo VK_bar VK_foo",
             buffer_capacity_input,
             buffer_count_output,
             buffer,
@@ -139,6 +139,7 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
         "IVRChaperone",
         "IVRApplications",
         "IVRSettings",
+        "IVRNotifications",
     ];
 
     for interface in INTERFACES {
@@ -1,16 +1,32 @@
+use crate::openxr_data::RealOpenXrData;
 use openvr as vr;
-use std::ffi::c_char;
+use std::ffi::{c_char, CStr};
+use std::sync::Arc;
 
-#[derive(Default, macros::InterfaceImpl)]
+#[derive(macros::InterfaceImpl)]
 #[interface = "IVRApplications"]
 #[versions(007)]
 pub struct Applications {
     vtables: Vtables,
+    openxr: Arc<RealOpenXrData>,
+}
+
+impl Applications {
+    pub fn new(openxr: Arc<RealOpenXrData>) -> Self {
+        Self {
+            vtables: Default::default(),
+            openxr,
+        }
+    }
 }
 
 impl vr::IVRApplications007_Interface for Applications {
     fn GetCurrentSceneProcessId(&self) -> u32 {
-        todo!()
+        if self.openxr.has_scene_focus() {
+            std::process::id()
+        } else {
+            0
+        }
     }
     fn LaunchInternalProcess(
         &self,
@@ -22,15 +38,24 @@ impl vr::IVRApplications007_Interface for Applications {
     }
     fn GetSceneApplicationStateNameFromEnum(
         &self,
-        _: vr::EVRSceneApplicationState,
+        state: vr::EVRSceneApplicationState,
     ) -> *const c_char {
-        todo!()
+        #[allow(unreachable_patterns)]
+        let name: &'static CStr = match state {
+            vr::EVRSceneApplicationState::None => c"None",
+            vr::EVRSceneApplicationState::Starting => c"Starting",
+            vr::EVRSceneApplicationState::Quitting => c"Quitting",
+            vr::EVRSceneApplicationState::Running => c"Running",
+            vr::EVRSceneApplicationState::Waiting => c"Waiting",
+            _ => c"Unknown",
+        };
+        name.as_ptr()
     }
     fn PerformApplicationPrelaunchCheck(&self, _: *const c_char) -> vr::EVRApplicationError {
         todo!()
     }
     fn GetSceneApplicationState(&self) -> vr::EVRSceneApplicationState {
-        todo!()
+        self.openxr.scene_application_state()
     }
     fn GetStartingApplication(&self, _: *mut c_char, _: u32) -> vr::EVRApplicationError {
         todo!()
@@ -4,6 +4,7 @@ use crate::{
     compositor::Compositor,
     input::Input,
     misc_unknown::UnknownInterfaces,
+    notifications::Notifications,
     openxr_data::{OpenXrData, RealOpenXrData},
     overlay::OverlayMan,
     overlayview::OverlayView,
@@ -134,6 +135,7 @@ impl IVRClientCore003_Interface for ClientCore {
             application_type,
             vr::EVRApplicationType::Scene // Standard apps
             | vr::EVRApplicationType::Background // Proton
+            | vr::EVRApplicationType::Overlay
         ) {
             error!("Unsupported application type: {application_type:?}");
             return vr::EVRInitError::Init_InvalidApplicationType;
@@ -163,7 +165,8 @@ impl IVRClientCore003_Interface for ClientCore {
         match OpenXrData::new(&Injector {
             store: self.interface_store.clone(),
         }) {
-            Ok(data) => {
+            Ok(mut data) => {
+                data.overlay_app = application_type == vr::EVRApplicationType::Overlay;
                 let data = Arc::new(data);
                 if let Some(path) = manifest_path {
                     data.input
@@ -181,6 +184,12 @@ impl IVRClientCore003_Interface for ClientCore {
         }
     }
     fn Cleanup(&self) {
+        // The overlay frame thread calls into other interfaces, so it has to be stopped before
+        // they're dropped.
+        let overlays = self.interface_store.lock().unwrap().get::<OverlayMan>();
+        if let Some(overlays) = overlays {
+            overlays.stop_overlay_frames();
+        }
         self.interface_store.lock().unwrap().clear();
 
         let mut openxr = self.openxr.write().unwrap();
@@ -219,11 +228,16 @@ impl IVRClientCore003_Interface for ClientCore {
             })
             .or_else(|| self.try_interface(interface, |_| Input::new(openxr.clone())))
             .or_else(|| self.try_interface(interface, |_| RenderModels::default()))
-            .or_else(|| self.try_interface(interface, |_| OverlayMan::new(openxr.clone())))
+            .or_else(|| {
+                self.try_interface(interface, |injector| {
+                    OverlayMan::new(openxr.clone(), injector)
+                })
+            })
             .or_else(|| self.try_interface(interface, |_| Chaperone::new(openxr.clone())))
-            .or_else(|| self.try_interface(interface, |_| Applications::default()))
+            .or_else(|| self.try_interface(interface, |_| Applications::new(openxr.clone())))
             .or_else(|| self.try_interface(interface, |_| OverlayView::default()))
-            .or_else(|| self.try_interface(interface, |_| Screenshots::default()))
+            .or_else(|| self.try_interface(interface, Screenshots::new))
+            .or_else(|| self.try_interface(interface, Notifications::new))
             .or_else(|| self.try_interface(interface, |_| Settings::default()))
             .or_else(|| self.try_interface(interface, |_| UnknownInterfaces::default()))
             .unwrap_or_else(|| {
@@ -244,6 +258,7 @@ impl IVRClientCore003_Interface for ClientCore {
                 Applications::supported_versions(),
                 OverlayView::supported_versions(),
                 Screenshots::supported_versions(),
+                Notifications::supported_versions(),
                 UnknownInterfaces::supported_versions(),
             ]
             .concat()
@@ -378,6 +393,34 @@ mod tests {
             .Init(vr::EVRApplicationType::Scene, std::ptr::null());
     }
 
+    #[test]
+    fn old_compositor_versions() {
+        let core = ClientCore::new(c"IVRClientCore_003").unwrap();
+        core.clone()
+            .Init(vr::EVRApplicationType::Scene, std::ptr::null());
+
+        // Every version in the headers back to openvr 1.0.4, which older games still ask for.
+        for version in [
+            c"IVRCompositor_022",
+            c"IVRCompositor_021",
+            c"IVRCompositor_020",
+            c"IVRCompositor_019",
+            c"IVRCompositor_018",
+        ] {
+            assert_eq!(
+                core.IsInterfaceVersionValid(version.as_ptr()),
+                vr::EVRInitError::None,
+                "{version:?}"
+            );
+            assert!(
+                !core
+                    .GetGenericInterface(version.as_ptr(), std::ptr::null_mut())
+                    .is_null(),
+                "{version:?}"
+            );
+        }
+    }
+
     #[test]
     fn inject() {
         let core = ClientCore::new(c"IVRClientCore_003").unwrap();
@@ -0,0 +1,110 @@
+//! Scales the color of the game's image in the compositor, i.e. to dim the display at night,
+//! without the game having to support it. Set by the config, or through the same IVRSettings keys
+//! as SteamVR's display brightness and color settings.
+
+use crate::config::Config;
+use log::info;
+use openxr as xr;
+use serde::Deserialize;
+use std::sync::{Mutex, OnceLock};
+
+const STEAMVR_SECTION: &str = "steamvr";
+const BRIGHTNESS_KEY: &str = "analogGain";
+const RED_KEY: &str = "hmdDisplayColorGainR";
+const GREEN_KEY: &str = "hmdDisplayColorGainG";
+const BLUE_KEY: &str = "hmdDisplayColorGainB";
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ColorGain {
+    /// Multiplies all channels.
+    #[serde(default = "default_gain")]
+    pub brightness: f32,
+    #[serde(default = "default_gain")]
+    pub red: f32,
+    #[serde(default = "default_gain")]
+    pub green: f32,
+    #[serde(default = "default_gain")]
+    pub blue: f32,
+}
+
+fn default_gain() -> f32 {
+    1.0
+}
+
+impl Default for ColorGain {
+    fn default() -> Self {
+        Self {
+            brightness: 1.0,
+            red: 1.0,
+            green: 1.0,
+            blue: 1.0,
+        }
+    }
+}
+
+impl ColorGain {
+    /// The color scale for the projection layer, or None if the image is left as is.
+    pub fn scale(&self) -> Option<xr::Color4f> {
+        (*self != Self::default()).then(|| xr::Color4f {
+            r: self.brightness * self.red,
+            g: self.brightness * self.green,
+            b: self.brightness * self.blue,
+            a: 1.0,
+        })
+    }
+
+    fn channel(&mut self, section: &str, key: &str) -> Option<&mut f32> {
+        if section != STEAMVR_SECTION {
+            return None;
+        }
+        match key {
+            BRIGHTNESS_KEY => Some(&mut self.brightness),
+            RED_KEY => Some(&mut self.red),
+            GREEN_KEY => Some(&mut self.green),
+            BLUE_KEY => Some(&mut self.blue),
+            _ => None,
+        }
+    }
+}
+
+fn current() -> &'static Mutex<ColorGain> {
+    static GAIN: OnceLock<Mutex<ColorGain>> = OnceLock::new();
+    GAIN.get_or_init(|| Mutex::new(Config::get().color_gain.unwrap_or_default()))
+}
+
+pub fn get() -> ColorGain {
+    *current().lock().unwrap()
+}
+
+/// Returns None if the setting isn't a color gain.
+pub fn get_setting(section: &str, key: &str) -> Option<f32> {
+    current().lock().unwrap().channel(section, key).copied()
+}
+
+/// Returns false if the setting isn't a color gain.
+pub fn set_setting(section: &str, key: &str, value: f32) -> bool {
+    let mut gain = current().lock().unwrap();
+    let Some(channel) = gain.channel(section, key) else {
+        return false;
+    };
+    *channel = value.max(0.0);
+    info!("Color gain changed: {:?}", *gain);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config() {
+        let gain: ColorGain =
+            serde_json::from_str(r#"{ "brightness": 0.5, "blue": 0.8 }"#).unwrap();
+        let scale = gain.scale().unwrap();
+        assert_eq!((scale.r, scale.g, scale.b, scale.a), (0.5, 0.5, 0.4, 1.0));
+        assert!(serde_json::from_str::<ColorGain>("{}")
+            .unwrap()
+            .scale()
+            .is_none());
+    }
+}
@@ -1,27 +1,51 @@
 use crate::{
     clientcore::{Injected, Injector},
-    graphics_backends::{supported_backends_enum, GraphicsBackend, SupportedBackend},
+    color_gain,
+    config::Config,
+    dynamic_resolution::{DynamicResolution, FrameLoad},
+    graphics_backends::{
+        supported_backends_enum, GpuSpan, GraphicsBackend, InteropTexture, SupportedBackend,
+        VulkanData,
+    },
     input::Input,
+    mirror::{Mirror, MirrorEye, MirrorImage},
     openxr_data::{self, FrameStream, OpenXrData, SessionCreateInfo, SessionData},
     overlay::OverlayMan,
     system::System,
     tracy_span, AtomicF64,
 };
 
-use log::{debug, info, trace};
+use glam::{Quat, Vec3, Vec4};
+use log::{debug, info, trace, warn};
 use openvr as vr;
 use openxr as xr;
+use std::collections::VecDeque;
 use std::mem::offset_of;
 use std::sync::{
-    atomic::{AtomicU32, Ordering},
-    Arc, Mutex, Once,
+    atomic::{AtomicBool, AtomicU32, Ordering},
+    mpsc, Arc, Condvar, Mutex, Once, OnceLock,
 };
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use std::{ffi::c_char, ops::Deref};
 
 #[derive(Default)]
 pub struct CompositorSessionData(Mutex<Option<DynFrameController>>);
 
+impl CompositorSessionData {
+    /// A backend for overlays drawn by xrizer itself, if the game has submitted a frame already.
+    pub fn new_overlay_backend(&self) -> Option<SupportedBackend> {
+        #[macros::any_graphics(DynFrameController)]
+        fn new_backend<G: GraphicsBackend + 'static>(
+            ctrl: &FrameController<G>,
+        ) -> SupportedBackend {
+            ctrl.backend.new_overlay_backend().into()
+        }
+
+        let lock = self.0.lock().unwrap();
+        Some(lock.as_ref()?.with_any_graphics::<new_backend>(()))
+    }
+}
+
 #[derive(macros::InterfaceImpl)]
 #[interface = "IVRCompositor"]
 #[versions(028, 027, 026, 022, 021, 020, 019, 018)]
@@ -33,10 +57,74 @@ pub struct Compositor {
     /// Stores the backend data in between session restarts.
     tmp_backend: Mutex<Option<AnyTempBackendData>>,
     overlays: Injected<OverlayMan>,
-    metrics: FrameMetrics,
+    metrics: Arc<FrameMetrics>,
     timing_mode: Mutex<vr::EVRCompositorTimingMode>,
     frame_state: Mutex<FrameState>,
     focused: Once,
+    fades: Mutex<Fades>,
+    /// Set by ForceInterleavedReprojectionOn: each frame is shown twice, halving the frame rate.
+    interleaved_reprojection: AtomicBool,
+    /// Started on the first present if threaded presenting is enabled.
+    presenter: OnceLock<Option<Presenter>>,
+    /// Wait for frames on a pacing thread in frame controllers created from now on.
+    frame_pacing: AtomicBool,
+}
+
+/// A value animated towards a target, for FadeToColor and FadeGrid.
+#[derive(Copy, Clone, Debug)]
+struct Fade {
+    from: Vec4,
+    to: Vec4,
+    start: Instant,
+    seconds: f32,
+}
+
+impl Fade {
+    fn new(value: Vec4) -> Self {
+        Self {
+            from: value,
+            to: value,
+            start: Instant::now(),
+            seconds: 0.0,
+        }
+    }
+
+    fn current(&self, now: Instant) -> Vec4 {
+        if self.seconds <= 0.0 {
+            return self.to;
+        }
+        let t = now.saturating_duration_since(self.start).as_secs_f32() / self.seconds;
+        self.from.lerp(self.to, t.min(1.0))
+    }
+
+    /// Starts fading from the current value.
+    fn start(&mut self, to: Vec4, seconds: f32, now: Instant) {
+        *self = Self {
+            from: self.current(now),
+            to,
+            start: now,
+            seconds,
+        };
+    }
+}
+
+struct Fades {
+    /// Drawn over the scene.
+    color: Fade,
+    /// Only reported back to the game, we don't draw a background behind the scene.
+    background: Fade,
+    /// Alpha of the grid in w.
+    grid: Fade,
+}
+
+impl Default for Fades {
+    fn default() -> Self {
+        Self {
+            color: Fade::new(Vec4::ZERO),
+            background: Fade::new(Vec4::ZERO),
+            grid: Fade::new(Vec4::ZERO),
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -72,6 +160,645 @@ struct FrameMetrics {
     system_start: Instant,
     index: AtomicU32,
     time: AtomicF64,
+    record: Mutex<FrameRecord>,
+    stats: Mutex<CumulativeStats>,
+}
+
+/// How many presented frames GetFrameTiming(s) can look back on.
+const FRAME_HISTORY: usize = 128;
+/// How often WaitGetPoses returns while the session is stopped.
+const STANDBY_FRAME_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Timings of a frame, in milliseconds since WaitGetPoses was called for it.
+#[derive(Default, Clone, Copy)]
+struct FrameTimes {
+    index: u32,
+    system_time: f64,
+    /// Since WaitGetPoses was called for the previous frame.
+    interval_ms: f32,
+    /// Spent blocked in xrWaitFrame.
+    wait_ms: f32,
+    display_period_ms: f32,
+    poses_ready_ms: f32,
+    /// When the last eye was submitted.
+    frame_ready_ms: f32,
+    present_start_ms: f32,
+    /// Spent in xrEndFrame.
+    present_ms: f32,
+    /// How many display frames this frame was shown for.
+    presents: u32,
+    /// Filled in a few frames later, once the GPU is done with the frame.
+    gpu: Option<GpuFrameTimes>,
+}
+
+/// GPU timings of a frame, measured by timestamps around copying the eyes to the swapchain.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct GpuFrameTimes {
+    /// From the end of the previous frame's copies to the end of this frame's. This includes time
+    /// the GPU spent idle, i.e. waiting for the game's CPU work.
+    total_ms: f32,
+    /// The game's rendering: the total minus our copies.
+    pre_submit_ms: f32,
+    /// Spent copying the eyes.
+    copy_ms: f32,
+}
+
+impl GpuFrameTimes {
+    fn new(previous_end_ms: f64, eyes: [GpuSpan; 2]) -> Self {
+        let end_ms = eyes.iter().map(|eye| eye.end_ms).fold(f64::MIN, f64::max);
+        let copy_ms: f64 = eyes.iter().map(|eye| eye.end_ms - eye.start_ms).sum();
+        let total_ms = (end_ms - previous_end_ms).max(copy_ms);
+        Self {
+            total_ms: total_ms as f32,
+            pre_submit_ms: (total_ms - copy_ms) as f32,
+            copy_ms: copy_ms as f32,
+        }
+    }
+}
+
+/// Frames whose eye copies are waiting for their GPU timestamps.
+#[derive(Default)]
+struct GpuTiming {
+    /// Frame indices and the swapchain images they were copied to, oldest first.
+    pending: VecDeque<(u32, usize)>,
+    /// The index of the last frame read back, and when its copies ended.
+    last: Option<(u32, f64)>,
+}
+
+impl GpuTiming {
+    fn frame_copied(&mut self, index: u32, image_index: usize) {
+        // Earlier copies to the same image have been overwritten.
+        self.pending.retain(|(_, image)| *image != image_index);
+        self.pending.push_back((index, image_index));
+    }
+
+    /// Reads back the frames that the GPU is done with, in order.
+    fn poll(&mut self, backend: &impl GraphicsBackend) -> Vec<(u32, GpuFrameTimes)> {
+        let mut times = Vec::new();
+        while let Some(&(index, image_index)) = self.pending.front() {
+            let Some(eyes) = backend.eye_copy_times(image_index) else {
+                break;
+            };
+            self.pending.pop_front();
+
+            // Without the previous frame there's nothing to measure from.
+            if let Some((_, previous_end)) = self.last.filter(|(last, _)| last + 1 == index) {
+                times.push((index, GpuFrameTimes::new(previous_end, eyes)));
+            }
+            let end = eyes.iter().map(|eye| eye.end_ms).fold(f64::MIN, f64::max);
+            self.last = Some((index, end));
+        }
+        times
+    }
+}
+
+/// Totals since the compositor was created, for GetCumulativeStats.
+#[derive(Default, Clone, Copy)]
+struct CumulativeStats {
+    /// Display frames, including repeated ones.
+    presents: u32,
+    /// Frames the game submitted that weren't ended.
+    dropped: u32,
+    /// Display frames that showed an older frame again, either for interleaved reprojection or
+    /// because the game missed the display.
+    reprojected: u32,
+    /// Frames the game submitted that were ended.
+    submits: u32,
+    app_cpu_ms: f64,
+    app_gpu_ms: f64,
+    compositor_cpu_ms: f64,
+    compositor_gpu_ms: f64,
+    target_frame_ms: f64,
+}
+
+#[derive(Default)]
+struct FrameRecord {
+    /// When WaitGetPoses was called for the current frame.
+    start: Option<Instant>,
+    current: FrameTimes,
+    /// Presented frames, most recent first.
+    history: VecDeque<FrameTimes>,
+}
+
+impl FrameMetrics {
+    fn start_frame(&self, called: Instant) {
+        let mut record = self.record.lock().unwrap();
+        let interval_ms = record.start.map_or(0.0, |last| ms_between(last, called));
+        record.start = Some(called);
+        record.current = FrameTimes {
+            interval_ms,
+            ..Default::default()
+        };
+    }
+
+    /// Calls `f` with the timings of the current frame and the milliseconds since it started.
+    fn record(&self, f: impl FnOnce(&mut FrameTimes, f32)) {
+        let now = Instant::now();
+        let mut record = self.record.lock().unwrap();
+        let since_start = record.start.map_or(0.0, |start| ms_between(start, now));
+        f(&mut record.current, since_start)
+    }
+
+    /// Returns the index of the presented frame.
+    fn frame_presented(&self) -> u32 {
+        let index = self.index.fetch_add(1, Ordering::Relaxed) + 1;
+        let time = self.system_start.elapsed().as_secs_f64();
+        self.time.store(time);
+
+        let mut record = self.record.lock().unwrap();
+        let frame = FrameTimes {
+            index,
+            system_time: time,
+            presents: 1,
+            ..record.current
+        };
+        record.history.truncate(FRAME_HISTORY - 1);
+        record.history.push_front(frame);
+        index
+    }
+
+    /// Updates a presented frame, if it's still remembered, returning its new timings.
+    fn update_frame(&self, index: u32, f: impl FnOnce(&mut FrameTimes)) -> Option<FrameTimes> {
+        let mut record = self.record.lock().unwrap();
+        let frame = record
+            .history
+            .iter_mut()
+            .find(|frame| frame.index == index)?;
+        f(frame);
+        Some(*frame)
+    }
+
+    fn frame_ended(&self, frame: &FrameTimes, repeat: bool) {
+        let shown = if repeat { 2 } else { 1 };
+        // The runtime keeps showing the previous frame for display periods the game missed.
+        let missed = if frame.display_period_ms > 0.0 {
+            ((frame.interval_ms / frame.display_period_ms).round() as u32).saturating_sub(shown)
+        } else {
+            0
+        };
+
+        let mut stats = self.stats.lock().unwrap();
+        stats.submits += 1;
+        stats.presents += shown + missed;
+        stats.reprojected += shown - 1 + missed;
+        stats.app_cpu_ms += (frame.interval_ms - frame.wait_ms).max(0.0) as f64;
+        stats.compositor_cpu_ms += frame.present_ms as f64;
+        stats.target_frame_ms += frame.display_period_ms as f64;
+    }
+
+    fn frame_dropped(&self) {
+        self.stats.lock().unwrap().dropped += 1;
+    }
+
+    fn gpu_times_read(&self, times: &GpuFrameTimes) {
+        let mut stats = self.stats.lock().unwrap();
+        stats.app_gpu_ms += times.pre_submit_ms as f64;
+        stats.compositor_gpu_ms += times.copy_ms as f64;
+    }
+
+    fn stats(&self) -> CumulativeStats {
+        *self.stats.lock().unwrap()
+    }
+
+    /// Up to `count` of the most recently presented frames, oldest first.
+    fn frames(&self, count: usize) -> Vec<FrameTimes> {
+        let record = self.record.lock().unwrap();
+        record.history.iter().take(count).rev().copied().collect()
+    }
+
+    /// Frames further back than we remember get the oldest one we have.
+    fn frame(&self, frames_ago: u32) -> FrameTimes {
+        let record = self.record.lock().unwrap();
+        record
+            .history
+            .get(frames_ago as usize)
+            .or(record.history.back())
+            .copied()
+            .unwrap_or(record.current)
+    }
+}
+
+fn ms_between(earlier: Instant, later: Instant) -> f32 {
+    later.saturating_duration_since(earlier).as_secs_f32() * 1000.0
+}
+
+/// The size the app has set on a Compositor_FrameTiming, if it's large enough for us.
+fn frame_timing_size(timing: *const vr::Compositor_FrameTiming) -> Option<usize> {
+    if timing.is_null() || !timing.is_aligned() {
+        return None;
+    }
+
+    let size = unsafe { (&raw const (*timing).m_nSize).read() } as usize;
+    fn ptr_size<T>(_: *const T) -> usize {
+        std::mem::size_of::<T>()
+    }
+    (size
+        >= offset_of!(vr::Compositor_FrameTiming, m_HmdPose)
+            + ptr_size(unsafe { &raw const (*timing).m_HmdPose }))
+    .then_some(size)
+}
+
+/// # Safety
+///
+/// `timing` must point to a Compositor_FrameTiming at least as large as [`frame_timing_size`]
+/// requires.
+unsafe fn write_frame_timing(timing: *mut vr::Compositor_FrameTiming, frame: &FrameTimes) {
+    // We're using raw pointers here because the Compositor_FrameTiming struct can be a
+    // varaible size, so we don't want to create a reference to a struct with an incorrect
+    // (to us) size, because that would be Undefined Behavior.
+    macro_rules! set {
+        ($member:ident, $value:expr) => {{
+            let ptr = &raw mut (*timing).$member;
+            ptr.write_unaligned($value)
+        }};
+    }
+
+    set!(m_nFrameIndex, frame.index);
+    set!(m_nNumFramePresents, frame.presents.max(1));
+    set!(m_nNumMisPresented, 0);
+    set!(m_nReprojectionFlags, 0);
+    set!(m_flSystemTimeInSeconds, frame.system_time);
+
+    // Without GPU timestamps, and for the runtime's compositor which we can't see, nothing was
+    // measured, so these are left at 0.
+    let gpu = frame.gpu.unwrap_or_default();
+    set!(m_flPreSubmitGpuMs, gpu.pre_submit_ms);
+    set!(m_flPostSubmitGpuMs, 0.0);
+    set!(m_flTotalRenderGpuMs, gpu.total_ms);
+    set!(m_flCompositorRenderGpuMs, gpu.copy_ms);
+    set!(m_flCompositorRenderCpuMs, 0.0);
+    set!(m_flCompositorIdleCpuMs, 0.0);
+
+    set!(m_flClientFrameIntervalMs, frame.interval_ms);
+    set!(m_flPresentCallCpuMs, frame.present_ms);
+    set!(m_flWaitForPresentCpuMs, frame.wait_ms);
+    set!(m_flSubmitFrameMs, frame.present_start_ms);
+
+    // Frame times are relative to the call to WaitGetPoses.
+    set!(m_flWaitGetPosesCalledMs, 0.0);
+    set!(m_flNewPosesReadyMs, frame.poses_ready_ms);
+    set!(m_flNewFrameReadyMs, frame.frame_ready_ms); // second call to IVRCompositor::Submit
+    set!(m_flCompositorUpdateStartMs, 0.0);
+    set!(m_flCompositorUpdateEndMs, 0.0);
+    set!(m_flCompositorRenderStartMs, 0.0);
+}
+
+/// # Safety
+///
+/// `stats` must point to at least `size` writable bytes.
+unsafe fn write_cumulative_stats(
+    stats: *mut vr::Compositor_CumulativeStats,
+    size: usize,
+    totals: &CumulativeStats,
+) {
+    // Older versions of the struct are shorter, so only the members that fit are written.
+    fn ptr_size<T>(_: *mut T) -> usize {
+        std::mem::size_of::<T>()
+    }
+    macro_rules! set {
+        ($member:ident, $value:expr) => {{
+            let ptr = &raw mut (*stats).$member;
+            if offset_of!(vr::Compositor_CumulativeStats, $member) + ptr_size(ptr) <= size {
+                ptr.write_unaligned($value)
+            }
+        }};
+    }
+
+    // Startup, loading and timeout stats aren't tracked, so they stay zero.
+    stats.cast::<u8>().write_bytes(0, size);
+    set!(m_nPid, std::process::id());
+    set!(m_nNumFramePresents, totals.presents);
+    set!(m_nNumDroppedFrames, totals.dropped);
+    set!(m_nNumReprojectedFrames, totals.reprojected);
+    set!(m_nNumFrameSubmits, totals.submits);
+    set!(m_flSumCompositorCPUTimeMS, totals.compositor_cpu_ms);
+    set!(m_flSumCompositorGPUTimeMS, totals.compositor_gpu_ms);
+    set!(m_flSumTargetFrameTimes, totals.target_frame_ms);
+    set!(m_flSumApplicationCPUTimeMS, totals.app_cpu_ms);
+    set!(m_flSumApplicationGPUTimeMS, totals.app_gpu_ms);
+}
+
+/// Ends frames on its own thread, so PostPresentHandoff can return to the game before the runtime
+/// is done with the frame.
+struct Presenter {
+    jobs: mpsc::Sender<Present>,
+    /// How many frames are queued or being ended.
+    pending: Arc<(Mutex<u32>, Condvar)>,
+}
+
+impl Presenter {
+    fn spawn() -> Option<Self> {
+        let (jobs, receiver) = mpsc::channel::<Present>();
+        let pending = Arc::new((Mutex::new(0), Condvar::new()));
+        let thread_pending = pending.clone();
+        std::thread::Builder::new()
+            .name("xrizer-present".into())
+            .spawn(move || {
+                for present in receiver {
+                    // If ending the frame panics, this thread is gone and later frames are
+                    // presented inline, but whoever waits for this one must not hang.
+                    let _done = PresentDone(&thread_pending);
+                    present.run();
+                }
+            })
+            .inspect_err(|e| warn!("Couldn't spawn present thread, presenting inline: {e}"))
+            .ok()?;
+        info!("Presenting frames on a separate thread");
+        Some(Self { jobs, pending })
+    }
+
+    fn present(&self, present: Present) {
+        *self.pending.0.lock().unwrap() += 1;
+        if let Err(mpsc::SendError(present)) = self.jobs.send(present) {
+            // The thread is gone, so nothing will pick this up.
+            *self.pending.0.lock().unwrap() -= 1;
+            present.run();
+        }
+    }
+
+    /// Blocks until every frame handed off has been ended.
+    fn wait(&self) {
+        let (pending, done) = &*self.pending;
+        let _pending = done
+            .wait_while(pending.lock().unwrap(), |pending| *pending > 0)
+            .unwrap();
+    }
+}
+
+/// Counts a handed off frame as ended when dropped.
+struct PresentDone<'a>(&'a (Mutex<u32>, Condvar));
+
+impl Drop for PresentDone<'_> {
+    fn drop(&mut self) {
+        let (pending, done) = self.0;
+        *pending.lock().unwrap() -= 1;
+        done.notify_all();
+    }
+}
+
+/// What it takes to end a frame after the game has handed it off.
+struct Present {
+    openxr: Arc<OpenXrData<Compositor>>,
+    system: Arc<System>,
+    overlays: Option<Arc<OverlayMan>>,
+    metrics: Arc<FrameMetrics>,
+    display_time: xr::Time,
+    fade_color: Vec4,
+    /// Show the frame twice, for interleaved reprojection.
+    repeat: bool,
+    index: u32,
+}
+
+impl Present {
+    fn run(self) {
+        tracy_span!();
+        #[macros::any_graphics(DynFrameController)]
+        fn end_frame<G: GraphicsBackend + 'static>(
+            ctrl: &mut FrameController<G>,
+            session_data: &SessionData,
+            system: &System,
+            display_time: xr::Time,
+            overlays: Option<&OverlayMan>,
+            fade_color: Vec4,
+            frame_index: u32,
+            repeat: bool,
+        ) -> Option<Vec<(u32, GpuFrameTimes)>>
+        where
+            for<'b> &'b crate::overlay::AnySwapchainMap:
+                TryInto<&'b crate::overlay::SwapchainMap<G::Api>, Error: std::fmt::Display>,
+            for<'b> &'b openxr_data::GraphicalSession:
+                TryInto<&'b openxr_data::Session<G::Api>, Error: std::fmt::Display>,
+            <G::Api as xr::Graphics>::Format: PartialEq,
+        {
+            // The session may have been restarted since the frame was handed off.
+            if !ctrl.frame_begun {
+                debug!("frame {frame_index} wasn't begun - not presenting it");
+                return None;
+            }
+            let mut gpu_times = ctrl.end_frame(
+                session_data,
+                system,
+                display_time,
+                overlays,
+                fade_color,
+                Some(frame_index),
+            );
+            if repeat {
+                gpu_times.extend(ctrl.repeat_frame(session_data, system, overlays, fade_color));
+            }
+            Some(gpu_times)
+        }
+
+        let start = Instant::now();
+        let gpu_times = {
+            let session_data = self.openxr.session_data.get();
+            let mut frame_lock = session_data.comp_data.0.lock().unwrap();
+            let Some(ctrl) = frame_lock.as_mut() else {
+                debug!("no frame controller - not presenting frame");
+                self.metrics.frame_dropped();
+                return;
+            };
+            ctrl.with_any_graphics_mut::<end_frame>((
+                &session_data,
+                &self.system,
+                self.display_time,
+                self.overlays.as_deref(),
+                self.fade_color,
+                self.index,
+                self.repeat,
+            ))
+        };
+        let Some(gpu_times) = gpu_times else {
+            self.metrics.frame_dropped();
+            return;
+        };
+        let present_ms = ms_between(start, Instant::now());
+
+        let frame = self.metrics.update_frame(self.index, |frame| {
+            frame.present_ms = present_ms;
+            if self.repeat {
+                frame.presents += 1;
+            }
+        });
+        if let Some(frame) = &frame {
+            self.metrics.frame_ended(frame, self.repeat);
+        }
+        for (index, times) in gpu_times {
+            self.metrics
+                .update_frame(index, |frame| frame.gpu = Some(times));
+            self.metrics.gpu_times_read(&times);
+        }
+        if let Some((dynamic_resolution, frame)) = DynamicResolution::get().zip(frame) {
+            dynamic_resolution.frame_presented(FrameLoad {
+                interval_ms: frame.interval_ms,
+                wait_ms: frame.wait_ms,
+                display_period_ms: frame.display_period_ms * frame.presents as f32,
+            });
+        }
+    }
+}
+
+/// Calls xrWaitFrame on its own thread as soon as the previous frame has ended, so WaitGetPoses
+/// only has to pick up the latched frame state instead of blocking in the runtime with the frame
+/// controller locked.
+struct FramePacer {
+    pacing: Arc<Pacing>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+#[derive(Default)]
+struct Pacing {
+    state: Mutex<PacingState>,
+    changed: Condvar,
+}
+
+#[derive(Default)]
+enum PacingState {
+    #[default]
+    Idle,
+    /// The next frame should be waited for.
+    Requested,
+    Waiting,
+    Latched(xr::Result<xr::FrameState>),
+    Stopped,
+}
+
+impl FramePacer {
+    /// Hands the waiter back if the thread couldn't be started.
+    fn spawn(waiter: xr::FrameWaiter) -> Result<Self, xr::FrameWaiter> {
+        let pacing = Arc::<Pacing>::default();
+        let thread_pacing = pacing.clone();
+        // The waiter is sent over once the thread is running, so it isn't lost if spawning fails.
+        let (send, receive) = mpsc::sync_channel(1);
+        let thread = std::thread::Builder::new()
+            .name("xrizer-pacing".into())
+            .spawn(move || {
+                if let Ok(waiter) = receive.recv() {
+                    thread_pacing.run(waiter);
+                }
+            });
+        match thread {
+            Ok(thread) => {
+                if send.send(waiter).is_err() {
+                    unreachable!("frame pacing thread exited before receiving the waiter");
+                }
+                info!("Waiting for frames on a separate thread");
+                Ok(Self {
+                    pacing,
+                    thread: Some(thread),
+                })
+            }
+            Err(e) => {
+                warn!("Couldn't spawn frame pacing thread, waiting inline: {e}");
+                Err(waiter)
+            }
+        }
+    }
+}
+
+impl Drop for FramePacer {
+    fn drop(&mut self) {
+        *self.pacing.state.lock().unwrap() = PacingState::Stopped;
+        self.pacing.changed.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Pacing {
+    fn run(&self, mut waiter: xr::FrameWaiter) {
+        loop {
+            {
+                let mut state = self
+                    .changed
+                    .wait_while(self.state.lock().unwrap(), |state| {
+                        matches!(state, PacingState::Idle | PacingState::Latched(_))
+                    })
+                    .unwrap();
+                if matches!(*state, PacingState::Stopped) {
+                    return;
+                }
+                *state = PacingState::Waiting;
+            }
+
+            let frame_state = {
+                tracy_span!("wait frame");
+                waiter.wait()
+            };
+
+            let mut state = self.state.lock().unwrap();
+            if matches!(*state, PacingState::Stopped) {
+                return;
+            }
+            *state = PacingState::Latched(frame_state);
+            self.changed.notify_all();
+        }
+    }
+
+    /// Starts waiting for the next frame, unless that's already happening.
+    fn request(&self) {
+        let mut state = self.state.lock().unwrap();
+        if matches!(*state, PacingState::Idle) {
+            *state = PacingState::Requested;
+            self.changed.notify_all();
+        }
+    }
+
+    /// Blocks until the next frame has been waited for.
+    fn take(&self) -> xr::Result<xr::FrameState> {
+        self.request();
+        let mut state = self
+            .changed
+            .wait_while(self.state.lock().unwrap(), |state| {
+                matches!(state, PacingState::Requested | PacingState::Waiting)
+            })
+            .unwrap();
+        match std::mem::take(&mut *state) {
+            PacingState::Latched(frame_state) => frame_state,
+            PacingState::Stopped => {
+                *state = PacingState::Stopped;
+                Err(xr::sys::Result::ERROR_SESSION_NOT_RUNNING)
+            }
+            PacingState::Idle | PacingState::Requested | PacingState::Waiting => unreachable!(),
+        }
+    }
+}
+
+/// Where xrWaitFrame is called from.
+enum Waiter {
+    Direct(xr::FrameWaiter),
+    Paced(FramePacer),
+}
+
+impl Waiter {
+    fn new(waiter: xr::FrameWaiter, paced: bool) -> Self {
+        if !paced {
+            return Self::Direct(waiter);
+        }
+        FramePacer::spawn(waiter).map_or_else(Self::Direct, Self::Paced)
+    }
+
+    fn wait(&mut self) -> xr::Result<xr::FrameState> {
+        match self {
+            Self::Direct(waiter) => waiter.wait(),
+            Self::Paced(pacer) => pacer.pacing.take(),
+        }
+    }
+
+    fn pacing(&self) -> Option<Arc<Pacing>> {
+        match self {
+            Self::Direct(_) => None,
+            Self::Paced(pacer) => Some(pacer.pacing.clone()),
+        }
+    }
+
+    /// Lets the pacing thread wait for the next frame while the game is busy with other things.
+    fn frame_ended(&self) {
+        if let Self::Paced(pacer) = self {
+            pacer.pacing.request();
+        }
+    }
 }
 
 struct TempBackendData<G: GraphicsBackend> {
@@ -93,33 +820,239 @@ impl Compositor {
                 system_start: Instant::now(),
                 index: 0.into(),
                 time: 0.0.into(),
-            },
+                record: Default::default(),
+                stats: Default::default(),
+            }
+            .into(),
             timing_mode: vr::EVRCompositorTimingMode::Implicit.into(),
             frame_state: FrameState::Submitted.into(),
             focused: Once::new(),
+            fades: Mutex::default(),
+            interleaved_reprojection: false.into(),
+            presenter: OnceLock::new(),
+            frame_pacing: Config::get().frame_pacing_thread().into(),
+        }
+    }
+
+    /// Blocks until a frame being ended on the present thread is done, so the next one isn't
+    /// started before it. Must not be called while holding the session data, which the present
+    /// thread needs.
+    fn wait_for_present(&self) {
+        if let Some(Some(presenter)) = self.presenter.get() {
+            tracy_span!();
+            presenter.wait();
         }
     }
 
     fn maybe_wait_frame(&self, session_data: &SessionData) {
         tracy_span!();
-        let mut frame_lock = { session_data.comp_data.0.lock().unwrap() };
+        #[macros::any_graphics(DynFrameController)]
+        fn frame_pacing<G: GraphicsBackend + 'static>(
+            ctrl: &FrameController<G>,
+        ) -> Option<Arc<Pacing>> {
+            ctrl.waiter.pacing()
+        }
+
+        #[macros::any_graphics(DynFrameController)]
+        fn wait_frame<G: GraphicsBackend + 'static>(
+            ctrl: &mut FrameController<G>,
+            paced: Option<xr::FrameState>,
+        ) -> (xr::Time, xr::Duration) {
+            match paced {
+                Some(frame_state) => ctrl.frame_waited(frame_state),
+                None => ctrl.wait_frame(),
+            }
+        }
+
+        let pacing = session_data
+            .comp_data
+            .0
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|ctrl| ctrl.with_any_graphics::<frame_pacing>(()));
         self.frame_state
             .lock()
             .unwrap()
             .advance_to(FrameState::Waited);
+        // The frame controller isn't locked while waiting on the pacing thread, so other threads
+        // calling into the compositor don't have to wait for the runtime's frame timing.
+        let paced = pacing.map(|pacing| {
+            tracy_span!("wait paced frame");
+            // A wait started before the session was stopped fails, so wait again now that it's
+            // running.
+            pacing
+                .take()
+                .or_else(|e| match e {
+                    xr::sys::Result::ERROR_SESSION_NOT_RUNNING => pacing.take(),
+                    e => Err(e),
+                })
+                .unwrap()
+        });
+
+        let mut frame_lock = session_data.comp_data.0.lock().unwrap();
         let Some(ctrl) = frame_lock.as_mut() else {
             debug!("no frame controller - not starting frame");
             return;
         };
 
+        let (display_time, display_period) = ctrl.with_any_graphics_mut::<wait_frame>(paced);
+        self.openxr.display_time.set(display_time);
+        self.metrics.record(|frame, _| {
+            frame.display_period_ms = display_period.as_nanos() as f32 / 1_000_000.0;
+        });
+    }
+
+    /// Submit, with the layer of an array texture picked by SubmitWithArrayIndex.
+    fn submit_texture(
+        &self,
+        eye: vr::EVREye,
+        texture: *const vr::Texture_t,
+        bounds: *const vr::VRTextureBounds_t,
+        submit_flags: vr::EVRSubmitFlags,
+        array_index: Option<u32>,
+    ) -> vr::EVRCompositorError {
+        if self.openxr.in_standby() {
+            return vr::EVRCompositorError::DoNotHaveFocus;
+        }
+
+        let bounds = unsafe { bounds.as_ref() }
+            .copied()
+            .unwrap_or(vr::VRTextureBounds_t {
+                uMin: 0.0,
+                vMin: 0.0,
+                uMax: 1.0,
+                vMax: 1.0,
+            });
+
+        // Superhot passes crazy bounds on startup.
+        if !bounds.valid() {
+            return vr::EVRCompositorError::InvalidBounds;
+        }
+
+        let Some(texture) = (unsafe { texture.as_ref() }) else {
+            return vr::EVRCompositorError::InvalidTexture;
+        };
+
+        if texture.eType == vr::ETextureType::DirectX {
+            // Only textures created by DXVK can be submitted, through the Vulkan image behind them.
+            let Some(interop) = InteropTexture::new(texture.handle) else {
+                return vr::EVRCompositorError::InvalidTexture;
+            };
+            // The texture passed on isn't part of a struct with depth anymore.
+            let mut submit_flags =
+                vr::EVRSubmitFlags(submit_flags.0 & !vr::EVRSubmitFlags::TextureWithDepth.0);
+            let pose = submitted_pose(texture, submit_flags);
+            if pose.is_none() {
+                submit_flags.0 &= !vr::EVRSubmitFlags::TextureWithPose.0;
+            }
+            return interop.with_vulkan_texture(texture.eColorSpace, |texture| {
+                let texture = vr::VRTextureWithPose_t {
+                    _base: *texture,
+                    mDeviceToAbsoluteTracking: pose.unwrap_or(xr::Posef::IDENTITY).into(),
+                };
+                let texture = (&raw const texture).cast();
+                self.submit_texture(eye, texture, &bounds, submit_flags, array_index)
+            });
+        }
+
+        if !self.focused.is_completed() {
+            return vr::EVRCompositorError::DoNotHaveFocus;
+        }
+
+        self.wait_for_present();
+        if *self.frame_state.lock().unwrap() == FrameState::Waited {
+            // Explicit timing games are supposed to begin the frame with SubmitExplicitTimingData,
+            // otherwise there's no swapchain image to copy to.
+            crate::warn_once!("Texture submitted before SubmitExplicitTimingData, beginning frame");
+            self.maybe_begin_frame(&self.openxr.session_data.get());
+        }
+
+        let mut session_lock = self.openxr.session_data.get();
+        let mut frame_lock = session_lock.comp_data.0.lock().unwrap();
+
+        let ctrl = match frame_lock.as_mut() {
+            Some(ctrl) => ctrl,
+            None => {
+                drop(frame_lock);
+                drop(session_lock);
+
+                info!("Received game texture, restarting session with new data");
+                self.initialize_real_session(texture, bounds, submit_flags);
+
+                session_lock = self.openxr.session_data.get();
+                frame_lock = session_lock.comp_data.0.lock().unwrap();
+                frame_lock.as_mut().unwrap()
+            }
+        };
+
+        #[macros::any_graphics(DynFrameController)]
+        fn submit<G: GraphicsBackend + 'static>(
+            ctrl: &mut FrameController<G>,
+            session_data: &SessionData,
+            eye: vr::EVREye,
+            texture: &vr::Texture_t,
+            bounds: vr::VRTextureBounds_t,
+            flags: vr::EVRSubmitFlags,
+            array_index: Option<u32>,
+            depth: Option<vr::VRTextureDepthInfo_t>,
+            pose: Option<xr::Posef>,
+        ) -> xr::Result<(), vr::EVRCompositorError>
+        where
+            for<'d> &'d openxr_data::GraphicalSession:
+                TryInto<&'d openxr_data::Session<G::Api>, Error: std::fmt::Display>,
+            <G::Api as xr::Graphics>::Format: Eq + std::fmt::Debug,
+        {
+            let real_texture = G::get_texture(texture);
+            ctrl.submit_impl(
+                session_data,
+                eye,
+                real_texture,
+                submitted_color_space(texture),
+                bounds,
+                flags,
+                array_index,
+                depth,
+                pose,
+            )
+        }
+
+        let depth = self
+            .openxr
+            .enabled_extensions
+            .khr_composition_layer_depth
+            .then(|| submitted_depth(texture, submit_flags))
+            .flatten();
+        if let Err(e) = ctrl.with_any_graphics_mut::<submit>((
+            &session_lock,
+            eye,
+            texture,
+            bounds,
+            submit_flags,
+            array_index,
+            depth,
+            submitted_pose(texture, submit_flags),
+        )) {
+            return e;
+        }
+        self.metrics
+            .record(|frame, since_start| frame.frame_ready_ms = since_start);
+        vr::EVRCompositorError::None
+    }
+
+    /// Forgets about the current frame after the session was ended, so frames start over once it's
+    /// running again.
+    fn session_stopped(&self) {
         #[macros::any_graphics(DynFrameController)]
-        fn wait_frame<G: GraphicsBackend + 'static>(ctrl: &mut FrameController<G>) -> xr::Time {
-            ctrl.wait_frame()
+        fn session_stopped<G: GraphicsBackend + 'static>(ctrl: &mut FrameController<G>) {
+            ctrl.session_stopped()
         }
 
-        self.openxr
-            .display_time
-            .set(ctrl.with_any_graphics_mut::<wait_frame>(()));
+        *self.frame_state.lock().unwrap() = FrameState::Submitted;
+        let session_data = self.openxr.session_data.get();
+        if let Some(ctrl) = session_data.comp_data.0.lock().unwrap().as_mut() {
+            ctrl.with_any_graphics_mut::<session_stopped>(());
+        }
     }
 
     fn maybe_begin_frame(&self, session_data: &SessionData) {
@@ -142,21 +1075,56 @@ impl Compositor {
         ctrl.with_any_graphics_mut::<begin_frame>(());
     }
 
-    fn initialize_real_session(&self, texture: &vr::Texture_t, bounds: vr::VRTextureBounds_t) {
+    /// Reads back the eyes of the next submitted frame for a screenshot. Returns false if no frames
+    /// are being submitted.
+    pub fn request_screenshot(&self, callback: ScreenshotCallback) -> bool {
+        let session_data = self.openxr.session_data.get();
+        let mut frame_lock = session_data.comp_data.0.lock().unwrap();
+        let Some(ctrl) = frame_lock.as_mut() else {
+            return false;
+        };
+
+        #[macros::any_graphics(DynFrameController)]
+        fn set_screenshot<G: GraphicsBackend + 'static>(
+            ctrl: &mut FrameController<G>,
+            callback: ScreenshotCallback,
+        ) {
+            ctrl.screenshot = Some(callback);
+        }
+
+        ctrl.with_any_graphics_mut::<set_screenshot>(callback);
+        true
+    }
+
+    fn initialize_real_session(
+        &self,
+        texture: &vr::Texture_t,
+        bounds: vr::VRTextureBounds_t,
+        submit_flags: vr::EVRSubmitFlags,
+    ) {
         info!("Creating real backend for texture type {:?}", texture.eType);
         let backend = SupportedBackend::new(texture, bounds);
 
         #[macros::any_graphics(SupportedBackend)]
         fn swapchain_info<G: GraphicsBackend>(
-            backend: G,
+            mut backend: G,
+            instance: &xr::Instance,
+            system_id: xr::SystemId,
             texture: &vr::Texture_t,
             bounds: vr::VRTextureBounds_t,
+            submit_flags: vr::EVRSubmitFlags,
         ) -> AnyTempBackendData
         where
             AnyTempBackendData: From<TempBackendData<G>>,
         {
+            backend.check_runtime_device(instance, system_id);
             let b_texture = G::get_texture(texture);
-            let info = backend.swapchain_info_for_texture(b_texture, bounds, texture.eColorSpace);
+            let info = backend.swapchain_info_for_texture(
+                b_texture,
+                bounds,
+                submitted_color_space(texture),
+                submit_flags,
+            );
             TempBackendData {
                 backend,
                 swapchain_create_info: Some(info),
@@ -164,7 +1132,72 @@ impl Compositor {
             .into()
         }
         *self.tmp_backend.lock().unwrap() =
-            Some(backend.with_any_graphics_owned::<swapchain_info>((texture, bounds)));
+            Some(backend.with_any_graphics_owned::<swapchain_info>((
+                &self.openxr.instance,
+                self.openxr.system_id,
+                texture,
+                bounds,
+                submit_flags,
+            )));
+
+        self.openxr.restart_session();
+    }
+
+    /// Overlay apps never submit eye textures, so their session is started using the graphics
+    /// device of the first overlay texture instead. The session won't have a projection swapchain.
+    pub fn initialize_overlay_session(&self, texture: &vr::Texture_t) {
+        if self
+            .openxr
+            .session_data
+            .get()
+            .comp_data
+            .0
+            .lock()
+            .unwrap()
+            .is_some()
+        {
+            return;
+        }
+
+        info!(
+            "Creating overlay backend for texture type {:?}",
+            texture.eType
+        );
+        let bounds = vr::VRTextureBounds_t {
+            uMin: 0.0,
+            vMin: 0.0,
+            uMax: 1.0,
+            vMax: 1.0,
+        };
+        let backend = match texture.eType {
+            // The frames are ended on another thread, where the app's context isn't current, so
+            // GL overlays are copied into a Vulkan session of our own instead.
+            vr::ETextureType::OpenGL => SupportedBackend::Vulkan(VulkanData::new_temporary(
+                &self.openxr.instance,
+                self.openxr.system_id,
+            )),
+            _ => SupportedBackend::new(texture, bounds),
+        };
+
+        #[macros::any_graphics(SupportedBackend)]
+        fn overlay_data<G: GraphicsBackend>(
+            mut backend: G,
+            instance: &xr::Instance,
+            system_id: xr::SystemId,
+        ) -> AnyTempBackendData
+        where
+            AnyTempBackendData: From<TempBackendData<G>>,
+        {
+            backend.check_runtime_device(instance, system_id);
+            TempBackendData {
+                backend,
+                swapchain_create_info: None,
+            }
+            .into()
+        }
+        *self.tmp_backend.lock().unwrap() = Some(backend.with_any_graphics_owned::<overlay_data>(
+            (&self.openxr.instance, self.openxr.system_id),
+        ));
 
         self.openxr.restart_session();
     }
@@ -241,6 +1274,7 @@ impl openxr_data::Compositor for Compositor {
             session_data: &SessionData,
             waiter: xr::FrameWaiter,
             stream: FrameStream,
+            frame_pacing: bool,
         ) -> DynFrameController
         where
             for<'a> &'a openxr_data::GraphicalSession:
@@ -251,7 +1285,7 @@ impl openxr_data::Compositor for Compositor {
         {
             FrameController::new(
                 session_data,
-                waiter,
+                Waiter::new(waiter, frame_pacing),
                 stream.try_into().unwrap_or_else(|_| unreachable!()),
                 data.backend,
                 data.swapchain_create_info,
@@ -264,6 +1298,7 @@ impl openxr_data::Compositor for Compositor {
                 session_data,
                 waiter,
                 stream,
+                self.frame_pacing.load(Ordering::Relaxed),
             )),
         );
 
@@ -340,6 +1375,18 @@ impl vr::IVRCompositor028_Interface for Compositor {
             return vr::EVRCompositorError::None;
         }
 
+        let frame_state = *self.frame_state.lock().unwrap();
+        match frame_state {
+            FrameState::Waited => {}
+            // Beginning again would discard the frame being rendered.
+            FrameState::Begun => return vr::EVRCompositorError::None,
+            FrameState::Submitted => {
+                debug!("SubmitExplicitTimingData called before WaitGetPoses");
+                return vr::EVRCompositorError::RequestFailed;
+            }
+        }
+
+        self.wait_for_present();
         let session_data = self.openxr.session_data.get();
         self.maybe_begin_frame(&session_data);
         vr::EVRCompositorError::None
@@ -355,11 +1402,9 @@ impl vr::IVRCompositor028_Interface for Compositor {
         buffer: *mut std::ffi::c_char,
         buffer_size: u32,
     ) -> u32 {
-        let exts = self
-            .openxr
-            .instance
-            .vulkan_legacy_device_extensions(self.openxr.system_id)
-            .unwrap();
+        let exts =
+            VulkanData::required_device_extensions(&self.openxr.instance, self.openxr.system_id)
+                .unwrap();
         log::debug!("required device extensions: {exts}");
         fill_vk_extensions_buffer(exts, buffer, buffer_size)
     }
@@ -369,11 +1414,9 @@ impl vr::IVRCompositor028_Interface for Compositor {
         buffer: *mut std::ffi::c_char,
         buffer_size: u32,
     ) -> u32 {
-        let exts = self
-            .openxr
-            .instance
-            .vulkan_legacy_instance_extensions(self.openxr.system_id)
-            .unwrap();
+        let exts =
+            VulkanData::required_instance_extensions(&self.openxr.instance, self.openxr.system_id)
+                .unwrap();
         log::debug!("required instance extensions: {exts}");
         fill_vk_extensions_buffer(exts, buffer, buffer_size)
     }
@@ -432,8 +1475,10 @@ impl vr::IVRCompositor028_Interface for Compositor {
     fn ForceReconnectProcess(&self) {
         todo!()
     }
-    fn ForceInterleavedReprojectionOn(&self, _: bool) {
-        crate::warn_unimplemented!("ForceInterleavedReprojectionOn");
+    fn ForceInterleavedReprojectionOn(&self, bOverride: bool) {
+        debug!("interleaved reprojection forced: {bOverride}");
+        self.interleaved_reprojection
+            .store(bOverride, Ordering::Relaxed);
     }
     fn ShouldAppRenderWithLowResources(&self) -> bool {
         // TODO
@@ -452,25 +1497,36 @@ impl vr::IVRCompositor028_Interface for Compositor {
         todo!()
     }
     fn CanRenderScene(&self) -> bool {
-        true
+        self.openxr.has_scene_focus()
     }
     fn GetLastFrameRenderer(&self) -> u32 {
         todo!()
     }
     fn GetCurrentSceneFocusProcess(&self) -> u32 {
-        todo!()
+        if self.openxr.has_scene_focus() {
+            std::process::id()
+        } else {
+            0
+        }
     }
     fn IsFullscreen(&self) -> bool {
         true
     }
     fn CompositorQuit(&self) {
-        todo!()
+        // The runtime is the compositor, so ending the session is the closest we can get. The
+        // app is sent a quit event once the runtime stops it.
+        info!("App asked the compositor to quit");
+        self.openxr.request_quit();
     }
     fn CompositorGoToBack(&self) {
         crate::warn_unimplemented!("CompositorGoToBack");
     }
     fn CompositorBringToFront(&self) {
-        crate::warn_unimplemented!("CompositorBringToFront");
+        // The runtime decides which app is shown, and OpenXR has no way to ask for focus.
+        debug!(
+            "CompositorBringToFront: scene focus {}",
+            self.openxr.has_scene_focus()
+        );
     }
     fn ClearSkyboxOverride(&self) {
         if let Some(overlays) = self.overlays.get() {
@@ -484,7 +1540,7 @@ impl vr::IVRCompositor028_Interface for Compositor {
     ) -> vr::EVRCompositorError {
         let overlays = self
             .overlays
-            .force(|_| OverlayMan::new(self.openxr.clone()));
+            .force(|injector| OverlayMan::new(self.openxr.clone(), injector));
         if pTextures.is_null() {
             return vr::EVRCompositorError::RequestFailed;
         }
@@ -515,9 +1571,16 @@ impl vr::IVRCompositor028_Interface for Compositor {
         vr::EVRCompositorError::None
     }
     fn GetCurrentGridAlpha(&self) -> f32 {
-        0.0
-    }
-    fn FadeGrid(&self, _fSeconds: f32, bFadeGridIn: bool) {
+        self.fades.lock().unwrap().grid.current(Instant::now()).w
+    }
+    fn FadeGrid(&self, fSeconds: f32, bFadeGridIn: bool) {
+        let alpha = if bFadeGridIn { 1.0 } else { 0.0 };
+        self.fades.lock().unwrap().grid.start(
+            Vec4::new(0.0, 0.0, 0.0, alpha),
+            fSeconds,
+            Instant::now(),
+        );
+
         #[macros::any_graphics(DynFrameController)]
         fn set_fade_grid<G: GraphicsBackend + 'static>(
             ctrl: &mut FrameController<G>,
@@ -536,136 +1599,131 @@ impl vr::IVRCompositor028_Interface for Compositor {
             .iter_mut()
             .for_each(|ctrl| ctrl.with_any_graphics_mut::<set_fade_grid>(bFadeGridIn));
     }
-    fn GetCurrentFadeColor(&self, _bBackground: bool) -> vr::HmdColor_t {
-        todo!()
+    fn GetCurrentFadeColor(&self, bBackground: bool) -> vr::HmdColor_t {
+        let fades = self.fades.lock().unwrap();
+        let fade = if bBackground {
+            &fades.background
+        } else {
+            &fades.color
+        };
+        let [r, g, b, a] = fade.current(Instant::now()).to_array();
+        vr::HmdColor_t { r, g, b, a }
     }
     fn FadeToColor(
         &self,
-        _fSeconds: f32,
-        _fRed: f32,
-        _fGreen: f32,
-        _fBlue: f32,
-        _fAlpha: f32,
-        _bBackground: bool,
+        fSeconds: f32,
+        fRed: f32,
+        fGreen: f32,
+        fBlue: f32,
+        fAlpha: f32,
+        bBackground: bool,
     ) {
-        crate::warn_unimplemented!("FadeToColor");
+        debug!(
+            "fading {} to ({fRed}, {fGreen}, {fBlue}, {fAlpha}) over {fSeconds}s",
+            if bBackground { "background" } else { "scene" }
+        );
+        let mut fades = self.fades.lock().unwrap();
+        let fade = if bBackground {
+            &mut fades.background
+        } else {
+            &mut fades.color
+        };
+        fade.start(
+            Vec4::new(fRed, fGreen, fBlue, fAlpha),
+            fSeconds,
+            Instant::now(),
+        );
     }
-    fn GetCumulativeStats(
-        &self,
-        _pStats: *mut vr::Compositor_CumulativeStats,
-        _nStatsSizeInBytes: u32,
-    ) {
-        todo!()
+    fn GetCumulativeStats(&self, stats: *mut vr::Compositor_CumulativeStats, size: u32) {
+        if stats.is_null() {
+            return;
+        }
+        let size = (size as usize).min(std::mem::size_of::<vr::Compositor_CumulativeStats>());
+        unsafe { write_cumulative_stats(stats, size, &self.metrics.stats()) };
     }
     fn GetFrameTimeRemaining(&self) -> f32 {
         crate::warn_unimplemented!("GetFrameTimeRemaining");
         0.0
     }
-    fn GetFrameTimings(&self, _pTiming: *mut vr::Compositor_FrameTiming, _nFrames: u32) -> u32 {
-        todo!()
-    }
-    fn GetFrameTiming(&self, timing: *mut vr::Compositor_FrameTiming, _frames_ago: u32) -> bool {
-        if timing.is_null() || !timing.is_aligned() {
-            return false;
-        }
+    fn GetFrameTimings(&self, timings: *mut vr::Compositor_FrameTiming, frames: u32) -> u32 {
+        let Some(size) = frame_timing_size(timings) else {
+            return 0;
+        };
 
-        let size = unsafe { (&raw const (*timing).m_nSize).read() } as usize;
-        fn ptr_size<T>(_: *const T) -> usize {
-            std::mem::size_of::<T>()
+        // Only the first entry has its size set, the rest are assumed to be the same.
+        let frames = self.metrics.frames(frames as usize);
+        for (idx, frame) in frames.iter().enumerate() {
+            unsafe {
+                let timing = timings.byte_add(idx * size);
+                (&raw mut (*timing).m_nSize).write_unaligned(size as u32);
+                write_frame_timing(timing, frame);
+            }
         }
-        if size
-            < offset_of!(vr::Compositor_FrameTiming, m_HmdPose)
-                + ptr_size(unsafe { &raw const (*timing).m_HmdPose })
-        {
+        frames.len() as u32
+    }
+    fn GetFrameTiming(&self, timing: *mut vr::Compositor_FrameTiming, frames_ago: u32) -> bool {
+        if frame_timing_size(timing).is_none() {
             return false;
         }
-        // We're using raw pointers here because the Compositor_FrameTiming struct can be a
-        // varaible size, so we don't want to create a reference to a struct with an incorrect
-        // (to us) size, because that would be Undefined Behavior.
-        macro_rules! set {
-            ($member:ident, $value:expr) => {{
-                let ptr = &raw mut (*timing).$member;
-                ptr.write_unaligned($value)
-            }};
-        }
-
-        unsafe {
-            // TODO: These values are copy/pasted from OpenComposite, determine if real values are
-            // necessary/better
-            set!(m_nFrameIndex, self.metrics.index.load(Ordering::Relaxed));
-            set!(m_nNumFramePresents, 1);
-            set!(m_nNumMisPresented, 0);
-            set!(m_nReprojectionFlags, 0);
-            set!(m_flSystemTimeInSeconds, self.metrics.time.load());
-            set!(m_flPreSubmitGpuMs, 8.0);
-            set!(m_flPostSubmitGpuMs, 1.0);
-            set!(m_flTotalRenderGpuMs, 9.0);
-
-            set!(m_flCompositorRenderGpuMs, 1.5);
-            set!(m_flCompositorRenderCpuMs, 3.0);
-            set!(m_flCompositorIdleCpuMs, 0.1);
-
-            set!(m_flClientFrameIntervalMs, 11.1);
-            set!(m_flPresentCallCpuMs, 0.0);
-            set!(m_flWaitForPresentCpuMs, 0.0);
-            set!(m_flSubmitFrameMs, 0.0);
-
-            set!(m_flWaitGetPosesCalledMs, 0.0);
-            set!(m_flNewPosesReadyMs, 0.0);
-            set!(m_flNewFrameReadyMs, 0.0); // second call to IVRCompositor::Submit
-            set!(m_flCompositorUpdateStartMs, 0.0);
-            set!(m_flCompositorUpdateEndMs, 0.0);
-            set!(m_flCompositorRenderStartMs, 0.0);
-        }
 
+        unsafe { write_frame_timing(timing, &self.metrics.frame(frames_ago)) };
         true
     }
     fn PostPresentHandoff(&self) {
         #[macros::any_graphics(DynFrameController)]
-        fn end_frame<G: GraphicsBackend + 'static>(
-            ctrl: &mut FrameController<G>,
-            session_data: &SessionData,
-            system: &System,
-            display_time: xr::Time,
-            overlays: Option<&OverlayMan>,
-        ) where
-            for<'b> &'b crate::overlay::AnySwapchainMap:
-                TryInto<&'b crate::overlay::SwapchainMap<G::Api>, Error: std::fmt::Display>,
-        {
-            ctrl.end_frame(session_data, system, display_time, overlays)
+        fn can_present_off_thread<G: GraphicsBackend + 'static>(_: &FrameController<G>) -> bool {
+            G::can_present_off_thread()
         }
 
         if *self.frame_state.lock().unwrap() != FrameState::Begun {
             return;
         }
 
-        let session_data = self.openxr.session_data.get();
-        let mut frame_lock = session_data.comp_data.0.lock().unwrap();
-        let Some(ctrl) = frame_lock.as_mut() else {
-            debug!("no frame controller - not presenting frame");
-            return;
+        let threaded = {
+            let session_data = self.openxr.session_data.get();
+            let frame_lock = session_data.comp_data.0.lock().unwrap();
+            let Some(ctrl) = frame_lock.as_ref() else {
+                debug!("no frame controller - not presenting frame");
+                return;
+            };
+            ctrl.with_any_graphics::<can_present_off_thread>(())
         };
         trace!("presenting frame");
-        let system = self.system.force(|i| System::new(self.openxr.clone(), i));
-        let display_time = self.openxr.display_time.get();
-        let overlays = self.overlays.get();
-
-        ctrl.with_any_graphics_mut::<end_frame>((
-            &session_data,
-            &system,
-            display_time,
-            overlays.as_deref(),
-        ));
 
+        self.metrics
+            .record(|frame, since_start| frame.present_start_ms = since_start);
         self.frame_state
             .lock()
             .unwrap()
             .advance_to(FrameState::Submitted);
 
-        self.metrics.index.fetch_add(1, Ordering::Relaxed);
-        self.metrics
-            .time
-            .store(self.metrics.system_start.elapsed().as_secs_f64());
+        let present = Present {
+            openxr: self.openxr.clone(),
+            system: self.system.force(|i| System::new(self.openxr.clone(), i)),
+            overlays: self.overlays.get(),
+            metrics: self.metrics.clone(),
+            display_time: self.openxr.display_time.get(),
+            fade_color: self.fades.lock().unwrap().color.current(Instant::now()),
+            repeat: self.interleaved_reprojection.load(Ordering::Relaxed),
+            index: self.metrics.frame_presented(),
+        };
+        let presenter = threaded
+            .then(|| {
+                self.presenter
+                    .get_or_init(|| {
+                        Config::get()
+                            .threaded_present()
+                            .then(Presenter::spawn)
+                            .flatten()
+                    })
+                    .as_ref()
+            })
+            .flatten();
+        match presenter {
+            Some(presenter) => presenter.present(present),
+            None => present.run(),
+        }
+
         #[cfg(feature = "tracing")]
         {
             tracy_client::frame_mark();
@@ -676,13 +1734,17 @@ impl vr::IVRCompositor028_Interface for Compositor {
     }
     fn SubmitWithArrayIndex(
         &self,
-        _eEye: vr::EVREye,
-        _pTexture: *const vr::Texture_t,
-        _unTextureArrayIndex: u32,
-        _pBounds: *const vr::VRTextureBounds_t,
-        _nSubmitFlags: vr::EVRSubmitFlags,
+        eye: vr::EVREye,
+        texture: *const vr::Texture_t,
+        array_index: u32,
+        bounds: *const vr::VRTextureBounds_t,
+        mut submit_flags: vr::EVRSubmitFlags,
     ) -> vr::EVRCompositorError {
-        todo!()
+        // OpenGL textures aren't known to be arrays otherwise.
+        if unsafe { texture.as_ref() }.is_some_and(|t| t.eType == vr::ETextureType::OpenGL) {
+            submit_flags.0 |= vr::EVRSubmitFlags::GlArrayTexture.0;
+        }
+        self.submit_texture(eye, texture, bounds, submit_flags, Some(array_index))
     }
 
     fn Submit(
@@ -692,81 +1754,7 @@ impl vr::IVRCompositor028_Interface for Compositor {
         bounds: *const vr::VRTextureBounds_t,
         submit_flags: vr::EVRSubmitFlags,
     ) -> vr::EVRCompositorError {
-        let bounds = unsafe { bounds.as_ref() }
-            .copied()
-            .unwrap_or(vr::VRTextureBounds_t {
-                uMin: 0.0,
-                vMin: 0.0,
-                uMax: 1.0,
-                vMax: 1.0,
-            });
-
-        // Superhot passes crazy bounds on startup.
-        if !bounds.valid() {
-            return vr::EVRCompositorError::InvalidBounds;
-        }
-
-        let Some(texture) = (unsafe { texture.as_ref() }) else {
-            return vr::EVRCompositorError::InvalidTexture;
-        };
-
-        if !self.focused.is_completed() {
-            return vr::EVRCompositorError::DoNotHaveFocus;
-        }
-
-        let mut session_lock = self.openxr.session_data.get();
-        let mut frame_lock = session_lock.comp_data.0.lock().unwrap();
-
-        let ctrl = match frame_lock.as_mut() {
-            Some(ctrl) => ctrl,
-            None => {
-                drop(frame_lock);
-                drop(session_lock);
-
-                info!("Received game texture, restarting session with new data");
-                self.initialize_real_session(texture, bounds);
-
-                session_lock = self.openxr.session_data.get();
-                frame_lock = session_lock.comp_data.0.lock().unwrap();
-                frame_lock.as_mut().unwrap()
-            }
-        };
-
-        #[macros::any_graphics(DynFrameController)]
-        fn submit<G: GraphicsBackend + 'static>(
-            ctrl: &mut FrameController<G>,
-            session_data: &SessionData,
-            eye: vr::EVREye,
-            texture: &vr::Texture_t,
-            bounds: vr::VRTextureBounds_t,
-            flags: vr::EVRSubmitFlags,
-        ) -> xr::Result<(), vr::EVRCompositorError>
-        where
-            for<'d> &'d openxr_data::GraphicalSession:
-                TryInto<&'d openxr_data::Session<G::Api>, Error: std::fmt::Display>,
-            <G::Api as xr::Graphics>::Format: Eq + std::fmt::Debug,
-        {
-            let real_texture = G::get_texture(texture);
-            ctrl.submit_impl(
-                session_data,
-                eye,
-                real_texture,
-                texture.eColorSpace,
-                bounds,
-                flags,
-            )
-        }
-
-        if let Err(e) = ctrl.with_any_graphics_mut::<submit>((
-            &session_lock,
-            eye,
-            texture,
-            bounds,
-            submit_flags,
-        )) {
-            return e;
-        }
-        vr::EVRCompositorError::None
+        self.submit_texture(eye, texture, bounds, submit_flags, None)
     }
 
     fn GetLastPoseForTrackedDeviceIndex(
@@ -816,11 +1804,11 @@ impl vr::IVRCompositor028_Interface for Compositor {
         game_pose_count: u32,
     ) -> vr::EVRCompositorError {
         tracy_span!("WaitGetPoses impl");
+        let called = Instant::now();
         // This should be called every frame - we must regularly poll events
         self.openxr.poll_events();
         self.focused.call_once(|| {});
         {
-            let session_data = self.openxr.session_data.get();
             let timing_mode = *self.timing_mode.lock().unwrap();
             if matches!(
                 timing_mode,
@@ -830,12 +1818,39 @@ impl vr::IVRCompositor028_Interface for Compositor {
             {
                 self.PostPresentHandoff();
             }
+            self.wait_for_present();
+
+            // Reloading bindings restarts the session, which has to happen between frames.
+            if let Some(input) = self.input.get() {
+                input.reload_bindings_if_changed();
+            }
+
+            if self.openxr.end_stopped_session() {
+                self.session_stopped();
+            }
+            if self.openxr.in_standby() {
+                // There's no frame timing to wait for, so the game is throttled instead.
+                std::thread::sleep(STANDBY_FRAME_INTERVAL);
+                self.GetLastPoses(
+                    render_pose_array,
+                    render_pose_count,
+                    game_pose_array,
+                    game_pose_count,
+                );
+                return vr::EVRCompositorError::DoNotHaveFocus;
+            }
 
+            let session_data = self.openxr.session_data.get();
             if *self.frame_state.lock().unwrap() == FrameState::Waited {
                 // discard frame
                 self.maybe_begin_frame(&session_data);
             }
+            self.metrics.start_frame(called);
+            let wait_start = Instant::now();
             self.maybe_wait_frame(&session_data);
+            self.metrics.record(|frame, _| {
+                frame.wait_ms = ms_between(wait_start, Instant::now());
+            });
 
             if timing_mode == vr::EVRCompositorTimingMode::Implicit {
                 self.maybe_begin_frame(&session_data);
@@ -846,14 +1861,29 @@ impl vr::IVRCompositor028_Interface for Compositor {
         }
         if let Some(input) = self.input.get() {
             input.frame_start_update();
+            if let Some((lines, overlays)) = input.take_bindings_view().zip(self.overlays.get()) {
+                overlays.show_bindings_view(&self.openxr.session_data.get(), &lines);
+            }
+            if let Some(overlays) = self.overlays.get() {
+                let session_data = self.openxr.session_data.get();
+                overlays.update_dashboard(&session_data, input.dashboard_summon_held());
+                overlays.update_pointer(&session_data, &input);
+                overlays.update_keyboard(&session_data);
+            }
+        }
+        if let Some(face_tracker) = &self.openxr.session_data.get().face_tracker {
+            face_tracker.update(self.openxr.display_time.get());
         }
 
-        self.GetLastPoses(
+        let ret = self.GetLastPoses(
             render_pose_array,
             render_pose_count,
             game_pose_array,
             game_pose_count,
-        )
+        );
+        self.metrics
+            .record(|frame, since_start| frame.poses_ready_ms = since_start);
+        ret
     }
 
     fn GetTrackingSpace(&self) -> vr::ETrackingUniverseOrigin {
@@ -887,8 +1917,122 @@ impl vr::IVRCompositor021On022 for Compositor {
 struct SubmittedEye {
     extent: xr::Extent2Di,
     flip_vertically: bool,
+    flip_horizontally: bool,
+    depth: Option<SubmittedDepth>,
+    /// The HMD pose the game rendered the eye with.
+    pose: Option<xr::Posef>,
+}
+
+/// A depth buffer copied to the depth swapchain.
+#[derive(Copy, Clone)]
+struct SubmittedDepth {
+    extent: xr::Extent2Di,
+    /// The range of depth values, and the distances they correspond to.
+    min_depth: f32,
+    max_depth: f32,
+    near_z: f32,
+    far_z: f32,
+}
+
+/// The distances of the near and far planes of a projection matrix like GetProjectionMatrix
+/// returns. For reversed depth, the near distance is larger than the far distance, as
+/// XR_KHR_composition_layer_depth expects.
+fn depth_planes(projection: &vr::HmdMatrix44_t) -> (f32, f32) {
+    let [_, _, [_, _, a, b], _] = projection.m;
+    (b / a, b / (a + 1.0))
+}
+
+/// The depth buffer submitted along with a texture, if there is one.
+fn submitted_depth(
+    texture: &vr::Texture_t,
+    flags: vr::EVRSubmitFlags,
+) -> Option<vr::VRTextureDepthInfo_t> {
+    if (flags & vr::EVRSubmitFlags::TextureWithDepth).0 == 0 {
+        return None;
+    }
+    let texture: *const vr::Texture_t = texture;
+    // SAFETY: The flags tell us what struct the texture is a part of.
+    let depth = if (flags & vr::EVRSubmitFlags::TextureWithPose).0 > 0 {
+        unsafe { &*texture.cast::<vr::VRTextureWithPoseAndDepth_t>() }.depth
+    } else {
+        unsafe { &*texture.cast::<vr::VRTextureWithDepth_t>() }.depth
+    };
+    (!depth.handle.is_null()).then_some(depth)
 }
 
+/// The color space to treat a submitted texture as being in.
+fn submitted_color_space(texture: &vr::Texture_t) -> vr::EColorSpace {
+    Config::get()
+        .force_color_space()
+        .map(Into::into)
+        .unwrap_or(texture.eColorSpace)
+}
+
+/// The HMD pose a texture was rendered with, if the game submitted one.
+fn submitted_pose(texture: &vr::Texture_t, flags: vr::EVRSubmitFlags) -> Option<xr::Posef> {
+    if (flags & vr::EVRSubmitFlags::TextureWithPose).0 == 0 {
+        return None;
+    }
+    let texture: *const vr::Texture_t = texture;
+    // SAFETY: The flags tell us what struct the texture is a part of.
+    let pose: xr::Posef = unsafe { &*texture.cast::<vr::VRTextureWithPose_t>() }
+        .mDeviceToAbsoluteTracking
+        .into();
+    // Some games set the flag without filling in the pose.
+    let xr::Quaternionf { x, y, z, w } = pose.orientation;
+    let valid = Quat::from_xyzw(x, y, z, w).is_normalized()
+        && [pose.position.x, pose.position.y, pose.position.z]
+            .iter()
+            .all(|p| p.is_finite());
+    valid.then_some(pose)
+}
+
+/// Places an eye, given relative to the head, at the given head pose.
+fn eye_pose_from_head(head: xr::Posef, eye: xr::Posef) -> xr::Posef {
+    let quat = |q: xr::Quaternionf| Quat::from_xyzw(q.x, q.y, q.z, q.w);
+    let vec = |v: xr::Vector3f| Vec3::new(v.x, v.y, v.z);
+
+    let head_rotation = quat(head.orientation);
+    let orientation = head_rotation * quat(eye.orientation);
+    let position = vec(head.position) + head_rotation * vec(eye.position);
+    xr::Posef {
+        orientation: xr::Quaternionf {
+            x: orientation.x,
+            y: orientation.y,
+            z: orientation.z,
+            w: orientation.w,
+        },
+        position: xr::Vector3f {
+            x: position.x,
+            y: position.y,
+            z: position.z,
+        },
+    }
+}
+
+/// The fade color as an sRGB RGBA8 pixel.
+fn fade_pixel(color: Vec4) -> [u8; 4] {
+    let srgb = |c: f32| {
+        let c = c.clamp(0.0, 1.0);
+        if c <= 0.0031308 {
+            c * 12.92
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        }
+    };
+    let byte = |c: f32| (c * 255.0).round() as u8;
+    [
+        byte(srgb(color.x)),
+        byte(srgb(color.y)),
+        byte(srgb(color.z)),
+        byte(color.w.clamp(0.0, 1.0)),
+    ]
+}
+
+/// Called with both eyes of a frame side by side and the extent of the left eye, or None if the
+/// frame couldn't be read back.
+pub type ScreenshotCallback = Box<dyn FnOnce(Option<(MirrorImage, xr::Extent2Di)>) + Send>;
+
 struct SwapchainData<G: xr::Graphics> {
     swapchain: xr::Swapchain<G>,
     info: xr::SwapchainCreateInfo<G>,
@@ -897,7 +2041,7 @@ struct SwapchainData<G: xr::Graphics> {
 
 struct FrameController<G: GraphicsBackend> {
     stream: xr::FrameStream<G::Api>,
-    waiter: xr::FrameWaiter,
+    waiter: Waiter,
     swapchain_data: Option<SwapchainData<G::Api>>,
     image_index: usize,
     image_acquired: bool,
@@ -906,8 +2050,31 @@ struct FrameController<G: GraphicsBackend> {
     app_fade_grid: bool,
     eyes_submitted: [Option<SubmittedEye>; 2],
     submitting_null: bool,
+    /// Between xrBeginFrame and xrEndFrame.
+    frame_begun: bool,
+    depth_swapchain_data: Option<SwapchainData<G::Api>>,
+    /// Acquired on the first depth submission of a frame.
+    depth_image_index: Option<usize>,
+    screenshot: Option<ScreenshotCallback>,
+    fade_data: Option<FadeData<G>>,
+    gpu_timing: GpuTiming,
+    backend: G,
+}
+
+/// The fade color is drawn with a quad in front of the view, filled with the color.
+struct FadeData<G: GraphicsBackend> {
+    swapchain: xr::Swapchain<G::Api>,
     backend: G,
+    /// The contents of the last released image.
+    pixel: [u8; 4],
 }
+
+const FADE_EXTENT: xr::Extent2Di = xr::Extent2Di {
+    width: 4,
+    height: 4,
+};
+/// In meters, one meter in front of the view, so it covers any field of view.
+const FADE_QUAD_SIZE: f32 = 20.0;
 supported_backends_enum!(enum DynFrameController: FrameController);
 
 impl<G: GraphicsBackend> FrameController<G> {
@@ -945,6 +2112,10 @@ impl<G: GraphicsBackend> FrameController<G> {
                 )
             });
 
+        if let Some(foveation) = &session_data.foveation {
+            foveation.apply(&swapchain);
+        }
+
         let images = swapchain
             .enumerate_images()
             .expect("Failed to enumerate swapchain images");
@@ -960,7 +2131,7 @@ impl<G: GraphicsBackend> FrameController<G> {
 
     fn new(
         session_data: &SessionData,
-        waiter: xr::FrameWaiter,
+        waiter: Waiter,
         stream: xr::FrameStream<G::Api>,
         mut backend: G,
         create_info: Option<xr::SwapchainCreateInfo<G::Api>>,
@@ -995,6 +2166,12 @@ impl<G: GraphicsBackend> FrameController<G> {
             app_fade_grid: false,
             eyes_submitted: Default::default(),
             submitting_null: false,
+            frame_begun: false,
+            depth_swapchain_data: None,
+            depth_image_index: None,
+            screenshot: None,
+            fade_data: None,
+            gpu_timing: GpuTiming::default(),
             backend,
         }
     }
@@ -1042,16 +2219,29 @@ impl<G: GraphicsBackend> FrameController<G> {
         self.image_acquired = true;
     }
 
-    fn wait_frame(&mut self) -> xr::Time {
+    fn wait_frame(&mut self) -> (xr::Time, xr::Duration) {
         let frame_state = {
             tracy_span!("wait frame");
             self.waiter.wait().unwrap()
         };
+        self.frame_waited(frame_state)
+    }
+
+    fn frame_waited(&mut self, frame_state: xr::FrameState) -> (xr::Time, xr::Duration) {
         self.should_render = frame_state.should_render && !self.app_suspend_render;
-        frame_state.predicted_display_time
+        (
+            frame_state.predicted_display_time,
+            frame_state.predicted_display_period,
+        )
+    }
+
+    fn session_stopped(&mut self) {
+        self.frame_begun = false;
+        self.should_render = false;
     }
 
     fn begin_frame(&mut self) {
+        self.release_depth_image();
         if self.image_acquired {
             tracy_span!("release old swapchain image");
             self.swapchain_data
@@ -1070,11 +2260,13 @@ impl<G: GraphicsBackend> FrameController<G> {
             tracy_span!("begin frame");
             self.stream.begin().expect("Couldn't begin frame");
         }
+        self.frame_begun = true;
         self.eyes_submitted = [None; 2];
         self.submitting_null = false;
         trace!("frame begin");
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn submit_impl(
         &mut self,
         session_data: &SessionData,
@@ -1083,6 +2275,9 @@ impl<G: GraphicsBackend> FrameController<G> {
         color_space: vr::EColorSpace,
         bounds: vr::VRTextureBounds_t,
         submit_flags: vr::EVRSubmitFlags,
+        array_index: Option<u32>,
+        depth: Option<vr::VRTextureDepthInfo_t>,
+        pose: Option<xr::Posef>,
     ) -> Result<(), vr::EVRCompositorError>
     where
         <G::Api as xr::Graphics>::Format: Eq,
@@ -1097,9 +2292,9 @@ impl<G: GraphicsBackend> FrameController<G> {
 
         self.eyes_submitted[eye as usize] = if self.should_render {
             // Make sure our image dimensions haven't changed.
-            let new_info = self
-                .backend
-                .swapchain_info_for_texture(texture, bounds, color_space);
+            let new_info =
+                self.backend
+                    .swapchain_info_for_texture(texture, bounds, color_space, submit_flags);
 
             is_valid_swapchain_info(&new_info)
                 .then(|| {
@@ -1115,16 +2310,23 @@ impl<G: GraphicsBackend> FrameController<G> {
                         self.recreate_swapchain(session_data, new_info);
                     }
 
+                    let extent = self.backend.copy_texture_to_swapchain(
+                        eye,
+                        texture,
+                        color_space,
+                        bounds,
+                        self.image_index,
+                        submit_flags,
+                        array_index,
+                    );
                     SubmittedEye {
-                        extent: self.backend.copy_texture_to_swapchain(
-                            eye,
-                            texture,
-                            color_space,
-                            bounds,
-                            self.image_index,
-                            submit_flags,
-                        ),
+                        extent,
                         flip_vertically: bounds.vertically_flipped(),
+                        flip_horizontally: bounds.horizontally_flipped(),
+                        pose,
+                        depth: depth.and_then(|depth| {
+                            self.submit_depth(session_data, eye, &depth, bounds, extent)
+                        }),
                     }
                 })
                 .or_else(|| {
@@ -1138,28 +2340,223 @@ impl<G: GraphicsBackend> FrameController<G> {
 
         trace!("submitted {eye:?}");
         if self.eyes_submitted.iter().all(|eye| eye.is_some()) {
+            self.take_screenshot();
+            self.update_mirror();
             let mut swapchain_data = self.swapchain_data.as_mut();
             if let Some(data) = &mut swapchain_data {
                 trace!("releasing image");
                 data.swapchain.release_image().unwrap();
             }
             self.image_acquired = false;
+            self.release_depth_image();
         }
 
         Ok(())
     }
 
+    /// The given eyes, if they were submitted with a texture this frame.
+    fn submitted_eyes(&self, eyes: &[vr::EVREye]) -> Option<Vec<MirrorEye>> {
+        if !self.should_render || self.submitting_null || self.swapchain_data.is_none() {
+            return None;
+        }
+
+        eyes.iter()
+            .map(|&eye| {
+                let submitted = self.eyes_submitted[eye as usize]?;
+                Some(MirrorEye {
+                    eye,
+                    extent: submitted.extent,
+                    flip_vertically: submitted.flip_vertically,
+                    flip_horizontally: submitted.flip_horizontally,
+                })
+            })
+            .collect()
+    }
+
+    fn take_screenshot(&mut self) {
+        let Some(callback) = self.screenshot.take() else {
+            return;
+        };
+
+        let image = self
+            .submitted_eyes(&[vr::EVREye::Left, vr::EVREye::Right])
+            .and_then(|eyes| {
+                let image = self.backend.read_eyes(self.image_index, &eyes)?;
+                Some((image, eyes[0].extent))
+            });
+        callback(image);
+    }
+
+    /// Fills the fade swapchain with the fade color. Returns whether the fade should be drawn.
+    fn update_fade(&mut self, session_data: &SessionData, color: Vec4) -> bool
+    where
+        for<'b> &'b openxr_data::GraphicalSession:
+            TryInto<&'b openxr_data::Session<G::Api>, Error: std::fmt::Display>,
+        <G::Api as xr::Graphics>::Format: PartialEq,
+    {
+        let pixel = fade_pixel(color);
+        if pixel[3] == 0 {
+            return false;
+        }
+
+        if self.fade_data.is_none() {
+            let mut backend = self.backend.new_overlay_backend();
+            let mut info = backend.swapchain_info_for_pixels(FADE_EXTENT);
+            session_data.check_format::<G>(&mut info);
+            let swapchain = match session_data.create_swapchain(&info) {
+                Ok(swapchain) => swapchain,
+                Err(e) => {
+                    crate::warn_once!("Failed to create fade swapchain: {e}");
+                    return false;
+                }
+            };
+            let images = swapchain
+                .enumerate_images()
+                .expect("Couldn't enumerate fade swapchain images");
+            backend.store_swapchain_images(images, info.format);
+            self.fade_data = Some(FadeData {
+                swapchain,
+                backend,
+                pixel: [0; 4],
+            });
+        }
+
+        let fade = self.fade_data.as_mut().unwrap();
+        if fade.pixel != pixel {
+            let pixels = pixel.repeat((FADE_EXTENT.width * FADE_EXTENT.height) as usize);
+            let idx = fade.swapchain.acquire_image().unwrap();
+            fade.swapchain.wait_image(xr::Duration::INFINITE).unwrap();
+            fade.backend
+                .copy_pixels_to_swapchain(&pixels, FADE_EXTENT, idx as usize);
+            fade.swapchain.release_image().unwrap();
+            fade.pixel = pixel;
+        }
+        true
+    }
+
+    /// Copies the submitted eyes to the mirror window, if it's open.
+    fn update_mirror(&mut self) {
+        let Some(mirror) = Mirror::get() else {
+            return;
+        };
+        let Some(eyes) = self.submitted_eyes(mirror.eyes().eyes()) else {
+            return;
+        };
+        if let Some(image) = self.backend.read_mirror_image(self.image_index, &eyes) {
+            mirror.show(image);
+        }
+    }
+
+    /// Copies the depth buffer submitted along with an eye to the depth swapchain.
+    fn submit_depth(
+        &mut self,
+        session_data: &SessionData,
+        eye: vr::EVREye,
+        depth: &vr::VRTextureDepthInfo_t,
+        bounds: vr::VRTextureBounds_t,
+        extent: xr::Extent2Di,
+    ) -> Option<SubmittedDepth>
+    where
+        for<'b> &'b openxr_data::GraphicalSession:
+            TryInto<&'b openxr_data::Session<G::Api>, Error: std::fmt::Display>,
+        <G::Api as xr::Graphics>::Format: Eq + std::fmt::Debug,
+    {
+        let info = self.backend.swapchain_info_for_depth(depth, extent)?;
+        if !self
+            .depth_swapchain_data
+            .as_ref()
+            .is_some_and(|data| is_usable_swapchain(&data.info, data.initial_format, &info))
+        {
+            if !session_data.supports_format::<G>(info.format) {
+                crate::warn_once!(
+                    "Runtime doesn't support depth format {:?}",
+                    G::to_nice_format(info.format)
+                );
+                return None;
+            }
+            let swapchain = session_data
+                .create_swapchain(&info)
+                .inspect_err(|e| warn!("Failed to create depth swapchain: {e}"))
+                .ok()?;
+            let images = swapchain
+                .enumerate_images()
+                .expect("Failed to enumerate depth swapchain images");
+            self.backend.store_depth_swapchain_images(images);
+            debug!(
+                "Created new depth swapchain: {}x{}, format = {:?}",
+                info.width,
+                info.height,
+                G::to_nice_format(info.format)
+            );
+            self.depth_swapchain_data = Some(SwapchainData {
+                swapchain,
+                initial_format: info.format,
+                info,
+            });
+            // Depth already copied this frame went to the old swapchain.
+            self.depth_image_index = None;
+            for eye in self.eyes_submitted.iter_mut().flatten() {
+                eye.depth = None;
+            }
+        }
+
+        let swapchain = &mut self.depth_swapchain_data.as_mut().unwrap().swapchain;
+        let image_index = match self.depth_image_index {
+            Some(index) => index,
+            None => {
+                let index = swapchain
+                    .acquire_image()
+                    .expect("Failed to acquire depth swapchain image")
+                    as usize;
+                swapchain
+                    .wait_image(xr::Duration::INFINITE)
+                    .expect("Failed to wait for depth swapchain image");
+                *self.depth_image_index.insert(index)
+            }
+        };
+        self.backend
+            .copy_depth_to_swapchain(eye, depth, bounds, image_index);
+
+        let (near_z, far_z) = depth_planes(&depth.mProjection);
+        Some(SubmittedDepth {
+            extent,
+            min_depth: depth.vRange.v[0],
+            max_depth: depth.vRange.v[1],
+            near_z,
+            far_z,
+        })
+    }
+
+    fn release_depth_image(&mut self) {
+        if self.depth_image_index.take().is_some() {
+            self.depth_swapchain_data
+                .as_mut()
+                .expect("Depth image is acquired, yet we have no depth swapchain?")
+                .swapchain
+                .release_image()
+                .unwrap();
+        }
+    }
+
     fn end_frame(
         &mut self,
         session_data: &SessionData,
         system: &System,
         display_time: xr::Time,
         overlays: Option<&OverlayMan>,
-    ) where
+        fade_color: Vec4,
+        frame_index: Option<u32>,
+    ) -> Vec<(u32, GpuFrameTimes)>
+    where
         for<'b> &'b crate::overlay::AnySwapchainMap:
             TryInto<&'b crate::overlay::SwapchainMap<G::Api>, Error: std::fmt::Display>,
+        for<'b> &'b openxr_data::GraphicalSession:
+            TryInto<&'b openxr_data::Session<G::Api>, Error: std::fmt::Display>,
+        <G::Api as xr::Graphics>::Format: PartialEq,
     {
         let mut proj_layer_views = Vec::new();
+        // Chained to the projection views, so they have to outlive them.
+        let mut depth_infos = Vec::new();
 
         if self.should_render
             && !self.submitting_null
@@ -1170,34 +2567,59 @@ impl<G: GraphicsBackend> FrameController<G> {
                 .as_ref()
                 .expect("Swapchain data unexpectedly invalid on submit");
 
-            let crate::system::ViewData { flags, views } =
-                system.get_views(session_data.current_origin_as_reference_space());
+            let crate::system::ViewData {
+                flags,
+                views,
+                insets,
+            } = system.get_views(session_data.current_origin_as_reference_space());
+            let mut inset_views = Vec::new();
+            // Where the eyes are relative to the head, for placing them at submitted poses.
+            let eye_offsets = self
+                .eyes_submitted
+                .iter()
+                .any(|eye| eye.is_some_and(|eye| eye.pose.is_some()))
+                .then(|| system.get_views(xr::ReferenceSpaceType::VIEW).views);
             proj_layer_views = views
                 .into_iter()
                 .enumerate()
                 .map(|(eye_index, view)| {
-                    let pose = xr::Posef {
-                        orientation: if flags.contains(xr::ViewStateFlags::ORIENTATION_VALID) {
-                            view.pose.orientation
-                        } else {
-                            xr::Quaternionf::IDENTITY
-                        },
-                        position: if flags.contains(xr::ViewStateFlags::POSITION_VALID) {
-                            view.pose.position
-                        } else {
-                            xr::Vector3f::default()
-                        },
+                    let submitted = self.eyes_submitted[eye_index]
+                        .unwrap_or_else(|| panic!("Eye {eye_index} has not been submitted!"));
+                    let pose = if let Some((head, offsets)) =
+                        submitted.pose.zip(eye_offsets.as_ref())
+                    {
+                        eye_pose_from_head(head, offsets[eye_index].pose)
+                    } else {
+                        xr::Posef {
+                            orientation: if flags.contains(xr::ViewStateFlags::ORIENTATION_VALID) {
+                                view.pose.orientation
+                            } else {
+                                xr::Quaternionf::IDENTITY
+                            },
+                            position: if flags.contains(xr::ViewStateFlags::POSITION_VALID) {
+                                view.pose.position
+                            } else {
+                                xr::Vector3f::default()
+                            },
+                        }
                     };
 
                     let SubmittedEye {
                         extent,
                         flip_vertically,
-                    } = self.eyes_submitted[eye_index]
-                        .unwrap_or_else(|| panic!("Eye {eye_index} has not been submitted!"));
+                        flip_horizontally,
+                        depth,
+                        ..
+                    } = submitted;
+                    // The copied image keeps the orientation the game rendered it in, so flipped
+                    // bounds are undone by flipping the view's FOV instead.
                     let mut fov = view.fov;
                     if flip_vertically {
                         std::mem::swap(&mut fov.angle_up, &mut fov.angle_down);
                     }
+                    if flip_horizontally {
+                        std::mem::swap(&mut fov.angle_left, &mut fov.angle_right);
+                    }
 
                     let sub_image = xr::SwapchainSubImage::new()
                         .swapchain(&swapchain_data.swapchain)
@@ -1207,40 +2629,275 @@ impl<G: GraphicsBackend> FrameController<G> {
                             offset: xr::Offset2Di::default(),
                         });
 
-                    xr::CompositionLayerProjectionView::new()
+                    if let Some(insets) = &insets {
+                        let mut inset_fov = insets[eye_index].fov;
+                        if flip_vertically {
+                            std::mem::swap(&mut inset_fov.angle_up, &mut inset_fov.angle_down);
+                        }
+                        if flip_horizontally {
+                            std::mem::swap(&mut inset_fov.angle_left, &mut inset_fov.angle_right);
+                        }
+                        let (inset_fov, rect) = inset_view(fov, inset_fov, extent);
+                        inset_views.push(
+                            xr::CompositionLayerProjectionView::new()
+                                .fov(inset_fov)
+                                .pose(pose)
+                                .sub_image(
+                                    xr::SwapchainSubImage::new()
+                                        .swapchain(&swapchain_data.swapchain)
+                                        .image_array_index(eye_index as u32)
+                                        .image_rect(rect),
+                                ),
+                        );
+                    }
+
+                    let view = xr::CompositionLayerProjectionView::new()
                         .fov(fov)
                         .pose(pose)
-                        .sub_image(sub_image)
-                })
-                .collect()
-        }
+                        .sub_image(sub_image);
 
+                    let Some((depth, depth_swapchain)) =
+                        depth.zip(self.depth_swapchain_data.as_ref())
+                    else {
+                        return view;
+                    };
+                    let depth_info = Box::new(xr::sys::CompositionLayerDepthInfoKHR {
+                        ty: xr::StructureType::COMPOSITION_LAYER_DEPTH_INFO_KHR,
+                        next: std::ptr::null(),
+                        sub_image: xr::sys::SwapchainSubImage {
+                            swapchain: depth_swapchain.swapchain.as_raw(),
+                            image_rect: xr::Rect2Di {
+                                extent: depth.extent,
+                                offset: xr::Offset2Di::default(),
+                            },
+                            image_array_index: eye_index as u32,
+                        },
+                        min_depth: depth.min_depth,
+                        max_depth: depth.max_depth,
+                        near_z: depth.near_z,
+                        far_z: depth.far_z,
+                    });
+                    let mut raw = view.into_raw();
+                    raw.next = (&raw const *depth_info).cast();
+                    depth_infos.push(depth_info);
+                    // SAFETY: The depth info lives until the frame is submitted.
+                    unsafe { xr::CompositionLayerProjectionView::from_raw(raw) }
+                })
+                .collect();
+            proj_layer_views.extend(inset_views);
+        }
+
+        let passthrough = session_data.passthrough.frame();
+        let color_scale = color_gain::get().scale().filter(|_| {
+            let supported = session_data
+                .session
+                .instance()
+                .exts()
+                .khr_composition_layer_color_scale_bias
+                .is_some();
+            if !supported {
+                crate::warn_once!(
+                    "Color gain is set, but the runtime doesn't support XR_KHR_composition_layer_color_scale_bias"
+                );
+            }
+            supported
+        });
+        let color_scale_bias;
         let mut proj_layer = None;
         if !proj_layer_views.is_empty() {
             trace!("projection layer present");
-            proj_layer = Some(
-                xr::CompositionLayerProjection::new()
-                    .space(session_data.tracking_space())
-                    .views(&proj_layer_views),
+            let mut layer = xr::CompositionLayerProjection::new()
+                .layer_flags(if passthrough.blend_alpha {
+                    xr::CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA
+                } else {
+                    xr::CompositionLayerFlags::EMPTY
+                })
+                .space(session_data.tracking_space())
+                .views(&proj_layer_views);
+            if let Some(color_scale) = color_scale {
+                color_scale_bias = xr::sys::CompositionLayerColorScaleBiasKHR {
+                    ty: xr::StructureType::COMPOSITION_LAYER_COLOR_SCALE_BIAS_KHR,
+                    next: std::ptr::null(),
+                    color_scale,
+                    color_bias: Default::default(),
+                };
+                let mut raw = layer.into_raw();
+                raw.next = (&raw const color_scale_bias).cast();
+                // SAFETY: The color scale lives until the frame is submitted.
+                layer = unsafe { xr::CompositionLayerProjection::from_raw(raw) };
+            }
+            proj_layer = Some(layer);
+        }
+
+        let mut fade_layer = None;
+        if self.update_fade(session_data, fade_color) {
+            let fade = self.fade_data.as_ref().unwrap();
+            fade_layer = Some(
+                xr::CompositionLayerQuad::new()
+                    .space(&session_data.view_space)
+                    .layer_flags(
+                        xr::CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA
+                            | xr::CompositionLayerFlags::UNPREMULTIPLIED_ALPHA,
+                    )
+                    .eye_visibility(xr::EyeVisibility::BOTH)
+                    .sub_image(
+                        xr::SwapchainSubImage::new()
+                            .swapchain(&fade.swapchain)
+                            .image_array_index(0)
+                            .image_rect(xr::Rect2Di {
+                                offset: xr::Offset2Di::default(),
+                                extent: FADE_EXTENT,
+                            }),
+                    )
+                    .pose(xr::Posef {
+                        position: xr::Vector3f {
+                            x: 0.0,
+                            y: 0.0,
+                            z: -1.0,
+                        },
+                        orientation: xr::Quaternionf::IDENTITY,
+                    })
+                    .size(xr::Extent2Df {
+                        width: FADE_QUAD_SIZE,
+                        height: FADE_QUAD_SIZE,
+                    }),
             );
         }
 
         let mut layers: Vec<&xr::CompositionLayerBase<_>> = Vec::new();
+        // Passthrough is drawn below everything else.
+        if let Some(l) = passthrough.layer.as_ref() {
+            // SAFETY: CompositionLayerBase is a transparent wrapper around the header that every
+            // composition layer starts with, and the layer outlives the submission.
+            layers.push(unsafe {
+                &*(l as *const xr::sys::CompositionLayerPassthroughFB)
+                    .cast::<xr::CompositionLayerBase<_>>()
+            });
+        }
         if let Some(l) = proj_layer.as_ref() {
             layers.push(l);
         }
+        // The fade covers the scene, but not overlays.
+        if let Some(l) = fade_layer.as_ref() {
+            layers.push(l);
+        }
         let overlay_layers;
         if let Some(overlay_man) = overlays {
-            overlay_layers = overlay_man.get_layers(session_data, self.app_fade_grid);
+            // Show the skybox during loading, when the game isn't giving us anything to show.
+            let render_skybox = self.app_fade_grid || proj_layer.is_none();
+            overlay_layers = overlay_man.get_layers(session_data, render_skybox);
             layers.extend(overlay_layers.iter().map(Deref::deref));
         }
 
-        self.stream
-            .end(display_time, xr::EnvironmentBlendMode::OPAQUE, &layers)
-            .unwrap();
-
-        trace!("frame submitted");
+        self.stream
+            .end(display_time, passthrough.blend_mode, &layers)
+            .unwrap();
+        self.frame_begun = false;
+        self.waiter.frame_ended();
+
+        trace!("frame submitted");
+
+        if let Some(index) = frame_index.filter(|_| proj_layer.is_some()) {
+            self.gpu_timing.frame_copied(index, self.image_index);
+        }
+        self.gpu_timing.poll(&self.backend)
+    }
+
+    /// Shows the last frame again at the next display time, leaving the runtime to reproject it.
+    fn repeat_frame(
+        &mut self,
+        session_data: &SessionData,
+        system: &System,
+        overlays: Option<&OverlayMan>,
+        fade_color: Vec4,
+    ) -> Vec<(u32, GpuFrameTimes)>
+    where
+        for<'b> &'b crate::overlay::AnySwapchainMap:
+            TryInto<&'b crate::overlay::SwapchainMap<G::Api>, Error: std::fmt::Display>,
+        for<'b> &'b openxr_data::GraphicalSession:
+            TryInto<&'b openxr_data::Session<G::Api>, Error: std::fmt::Display>,
+        <G::Api as xr::Graphics>::Format: PartialEq,
+    {
+        trace!("repeating frame");
+        let frame_state = {
+            tracy_span!("wait frame");
+            self.waiter.wait()
+        };
+        let display_time = match frame_state {
+            Ok(frame_state) => self.frame_waited(frame_state).0,
+            Err(e) => {
+                warn!("Failed to wait for repeated frame: {e}");
+                return Vec::new();
+            }
+        };
+        {
+            tracy_span!("begin frame");
+            if let Err(e) = self.stream.begin() {
+                warn!("Failed to begin repeated frame: {e}");
+                return Vec::new();
+            }
+        }
+        self.frame_begun = true;
+        // The submitted eyes and the views they were rendered with are left over from the last
+        // frame, so ending the frame submits the same projection layer.
+        self.end_frame(
+            session_data,
+            system,
+            display_time,
+            overlays,
+            fade_color,
+            None,
+        )
+    }
+}
+
+/// The game only renders the outer views of a quad view configuration (XR_VARJO_quad_views), so
+/// the inset views show the part of each eye's image they cover. `fov` is what the whole image
+/// covers. Returns the inset's FOV, snapped to whole pixels, and its rect in the image.
+fn inset_view(fov: xr::Fovf, inset: xr::Fovf, extent: xr::Extent2Di) -> (xr::Fovf, xr::Rect2Di) {
+    let [left, right, up, down] = [
+        fov.angle_left,
+        fov.angle_right,
+        fov.angle_up,
+        fov.angle_down,
+    ]
+    .map(f32::tan);
+    let (width, height) = (extent.width as f32, extent.height as f32);
+    let x = |angle: f32| ((angle.tan() - left) / (right - left)).clamp(0.0, 1.0) * width;
+    let y = |angle: f32| ((up - angle.tan()) / (up - down)).clamp(0.0, 1.0) * height;
+    // Grows the inset to whole pixels, without adding pixels for rounding errors.
+    let floor = |pixel: f32| (pixel + 1e-3).floor() as i32;
+    let ceil = |pixel: f32| (pixel - 1e-3).ceil() as i32;
+    let (x0, x1) = (floor(x(inset.angle_left)), ceil(x(inset.angle_right)));
+    let (y0, y1) = (floor(y(inset.angle_up)), ceil(y(inset.angle_down)));
+    if x1 <= x0 || y1 <= y0 {
+        // Still needs a view, so show the whole image.
+        return (
+            fov,
+            xr::Rect2Di {
+                offset: xr::Offset2Di::default(),
+                extent,
+            },
+        );
     }
+
+    let angle_x = |x: i32| (left + (right - left) * x as f32 / width).atan();
+    let angle_y = |y: i32| (up - (up - down) * y as f32 / height).atan();
+    (
+        xr::Fovf {
+            angle_left: angle_x(x0),
+            angle_right: angle_x(x1),
+            angle_up: angle_y(y0),
+            angle_down: angle_y(y1),
+        },
+        xr::Rect2Di {
+            offset: xr::Offset2Di { x: x0, y: y0 },
+            extent: xr::Extent2Di {
+                width: x1 - x0,
+                height: y1 - y0,
+            },
+        },
+    )
 }
 
 pub fn is_usable_swapchain<G: xr::Graphics>(
@@ -1274,6 +2931,7 @@ mod tests {
     use std::ffi::CStr;
     use std::mem::MaybeUninit;
     use std::thread_local;
+    use std::time::Duration;
     use vr::EVRCompositorError::*;
     use vr::IVRCompositor028_Interface;
 
@@ -1332,6 +2990,9 @@ mod tests {
         fn to_nice_format(format: <Self::Api as openxr::Graphics>::Format) -> Self::NiceFormat {
             VulkanData::to_nice_format(format)
         }
+        fn fallback_formats(format: u32) -> Vec<u32> {
+            VulkanData::fallback_formats(format)
+        }
         fn session_create_info(&self) -> <Self::Api as openxr::Graphics>::SessionCreateInfo {
             self.vk.session_create_info()
         }
@@ -1345,6 +3006,7 @@ mod tests {
             _: Self::OpenVrTexture,
             _: openvr::VRTextureBounds_t,
             _: openvr::EColorSpace,
+            _: openvr::EVRSubmitFlags,
         ) -> openxr::SwapchainCreateInfo<Self::Api> {
             xr::SwapchainCreateInfo {
                 create_flags: xr::SwapchainCreateFlags::EMPTY,
@@ -1375,6 +3037,7 @@ mod tests {
             _bounds: openvr::VRTextureBounds_t,
             _image_index: usize,
             _submit_flags: openvr::EVRSubmitFlags,
+            _array_index: Option<u32>,
         ) -> openxr::Extent2Di {
             xr::Extent2Di::default()
         }
@@ -1387,6 +3050,32 @@ mod tests {
         ) -> openxr::Extent2Di {
             xr::Extent2Di::default()
         }
+
+        fn new_overlay_backend(&self) -> Self {
+            Self {
+                vk: self.vk.clone(),
+                swapchain_format: None,
+            }
+        }
+
+        fn swapchain_info_for_pixels(
+            &self,
+            extent: xr::Extent2Di,
+        ) -> openxr::SwapchainCreateInfo<Self::Api> {
+            xr::SwapchainCreateInfo {
+                create_flags: xr::SwapchainCreateFlags::EMPTY,
+                usage_flags: xr::SwapchainUsageFlags::EMPTY,
+                format: SWAPCHAIN_FORMAT.get(),
+                sample_count: 1,
+                width: extent.width as u32,
+                height: extent.height as u32,
+                face_count: 1,
+                array_size: 1,
+                mip_count: 1,
+            }
+        }
+
+        fn copy_pixels_to_swapchain(&mut self, _: &[u8], _: xr::Extent2Di, _: usize) {}
     }
 
     impl FakeGraphicsData {
@@ -1538,6 +3227,20 @@ mod tests {
             ),
             InvalidBounds
         );
+        assert_ne!(
+            comp.Submit(
+                vr::EVREye::Right,
+                std::ptr::null(),
+                &vr::VRTextureBounds_t {
+                    uMin: 1.0,
+                    vMin: 0.0,
+                    uMax: 0.5,
+                    vMax: 1.0
+                },
+                vr::EVRSubmitFlags::Default
+            ),
+            InvalidBounds
+        );
     }
 
     #[test]
@@ -1623,6 +3326,135 @@ mod tests {
         assert!(!f.comp.GetFrameTiming(timing.as_mut_ptr(), 1));
     }
 
+    #[test]
+    fn frame_timing_history() {
+        let f = Fixture::new();
+        for _ in 0..3 {
+            assert_eq!(f.wait_get_poses(), None);
+            assert_eq!(f.submit(vr::EVREye::Left), None);
+            assert_eq!(f.submit(vr::EVREye::Right), None);
+        }
+        // Presents the last frame.
+        assert_eq!(f.wait_get_poses(), None);
+
+        let get_timing = |frames_ago| {
+            let mut timing = vr::Compositor_FrameTiming {
+                m_nSize: std::mem::size_of::<vr::Compositor_FrameTiming>() as u32,
+                ..Default::default()
+            };
+            assert!(f.comp.GetFrameTiming(&mut timing, frames_ago));
+            timing
+        };
+
+        let last = get_timing(0);
+        assert!({ last.m_flNewPosesReadyMs } <= { last.m_flNewFrameReadyMs });
+        assert!({ last.m_flNewFrameReadyMs } <= { last.m_flSubmitFrameMs });
+        assert!({ last.m_flClientFrameIntervalMs } > 0.0);
+
+        let previous = get_timing(1);
+        assert_eq!({ previous.m_nFrameIndex } + 1, { last.m_nFrameIndex });
+        assert!({ previous.m_flSystemTimeInSeconds } <= { last.m_flSystemTimeInSeconds });
+        // Frames that aren't remembered get the oldest one.
+        let oldest = get_timing(20);
+        assert!({ oldest.m_nFrameIndex } >= 1);
+        assert!({ oldest.m_nFrameIndex } <= { previous.m_nFrameIndex });
+    }
+
+    #[test]
+    fn interleaved_reprojection_repeats_frames() {
+        let f = Fixture::new();
+        f.comp.ForceInterleavedReprojectionOn(true);
+        for _ in 0..2 {
+            assert_eq!(f.wait_get_poses(), None);
+            assert_eq!(f.submit(vr::EVREye::Left), None);
+            assert_eq!(f.submit(vr::EVREye::Right), None);
+        }
+        assert_eq!(f.wait_get_poses(), None);
+
+        let mut timing = vr::Compositor_FrameTiming {
+            m_nSize: std::mem::size_of::<vr::Compositor_FrameTiming>() as u32,
+            ..Default::default()
+        };
+        assert!(f.comp.GetFrameTiming(&mut timing, 0));
+        assert_eq!({ timing.m_nNumFramePresents }, 2);
+
+        f.comp.ForceInterleavedReprojectionOn(false);
+        assert_eq!(f.submit(vr::EVREye::Left), None);
+        assert_eq!(f.submit(vr::EVREye::Right), None);
+        assert_eq!(f.wait_get_poses(), None);
+        assert!(f.comp.GetFrameTiming(&mut timing, 0));
+        assert_eq!({ timing.m_nNumFramePresents }, 1);
+    }
+
+    #[test]
+    fn get_frame_timings() {
+        let f = Fixture::new();
+        for _ in 0..3 {
+            assert_eq!(f.wait_get_poses(), None);
+            assert_eq!(f.submit(vr::EVREye::Left), None);
+            assert_eq!(f.submit(vr::EVREye::Right), None);
+        }
+        assert_eq!(f.wait_get_poses(), None);
+
+        // Only the first entry needs its size set.
+        let mut timings = [vr::Compositor_FrameTiming::default(); 10];
+        timings[0].m_nSize = std::mem::size_of::<vr::Compositor_FrameTiming>() as u32;
+        let count = f.comp.GetFrameTimings(timings.as_mut_ptr(), 10) as usize;
+        assert!((2..10).contains(&count), "{count}");
+
+        // Oldest first.
+        let indices: Vec<u32> = timings[..count].iter().map(|t| t.m_nFrameIndex).collect();
+        assert!(indices.windows(2).all(|w| w[0] + 1 == w[1]), "{indices:?}");
+        assert_eq!({ timings[count - 1].m_nSize }, { timings[0].m_nSize });
+        assert_eq!({ timings[count].m_nFrameIndex }, 0);
+
+        let mut last = vr::Compositor_FrameTiming {
+            m_nSize: std::mem::size_of::<vr::Compositor_FrameTiming>() as u32,
+            ..Default::default()
+        };
+        assert!(f.comp.GetFrameTiming(&mut last, 0));
+        assert_eq!({ last.m_nFrameIndex }, indices[count - 1]);
+
+        // Asking for fewer frames gives the most recent ones.
+        let mut timings = [vr::Compositor_FrameTiming::default(); 1];
+        timings[0].m_nSize = std::mem::size_of::<vr::Compositor_FrameTiming>() as u32;
+        assert_eq!(f.comp.GetFrameTimings(timings.as_mut_ptr(), 1), 1);
+        assert_eq!({ timings[0].m_nFrameIndex }, indices[count - 1]);
+    }
+
+    #[test]
+    fn cumulative_stats() {
+        let f = Fixture::new();
+        f.comp.ForceInterleavedReprojectionOn(true);
+        for _ in 0..3 {
+            assert_eq!(f.wait_get_poses(), None);
+            assert_eq!(f.submit(vr::EVREye::Left), None);
+            assert_eq!(f.submit(vr::EVREye::Right), None);
+        }
+        assert_eq!(f.wait_get_poses(), None);
+
+        let mut stats = vr::Compositor_CumulativeStats::default();
+        f.comp.GetCumulativeStats(
+            &mut stats,
+            std::mem::size_of::<vr::Compositor_CumulativeStats>() as u32,
+        );
+        assert_eq!({ stats.m_nPid }, std::process::id());
+        assert_eq!({ stats.m_nNumFrameSubmits }, 3);
+        assert!({ stats.m_nNumFramePresents } >= 6);
+        assert!({ stats.m_nNumReprojectedFrames } >= 3);
+        assert_eq!({ stats.m_nNumDroppedFrames }, 0);
+
+        // Older, shorter versions of the struct only get the members that fit.
+        let mut stats = vr::Compositor_CumulativeStats {
+            m_nNumFrameSubmits: 1234,
+            ..Default::default()
+        };
+        let old_size = std::mem::offset_of!(vr::Compositor_CumulativeStats, m_nNumFrameSubmits);
+        f.comp.GetCumulativeStats(&mut stats, old_size as u32);
+        assert!({ stats.m_nNumFramePresents } >= 6);
+        assert_eq!({ stats.m_nNumFrameSubmits }, 1234);
+    }
+
     #[test]
     fn zero_dims_texture() {
         let f = Fixture::new();
@@ -1731,6 +3563,29 @@ mod tests {
         assert_eq!(data.info.format, 0);
     }
 
+    #[test]
+    fn high_bit_depth_fallback_format() {
+        let f = Fixture::new();
+        SWAPCHAIN_FORMAT.set(ash::vk::Format::A2B10G10R10_UNORM_PACK32.as_raw() as u32);
+        assert_eq!(f.wait_get_poses(), None);
+        assert_eq!(f.submit(vr::EVREye::Left), None);
+        assert_eq!(f.submit(vr::EVREye::Right), None);
+        let data = f.comp.openxr.session_data.get();
+        let lock = data.comp_data.0.lock().unwrap();
+        let DynFrameController::Fake(ctrl) = lock.as_ref().unwrap() else {
+            panic!("Frame controller was not set up or not faked!");
+        };
+        let data = ctrl
+            .swapchain_data
+            .as_ref()
+            .expect("Swapchain data is missing");
+        // The runtime doesn't support 10 bit formats, but does support FP16.
+        assert_eq!(
+            data.info.format,
+            ash::vk::Format::R16G16B16A16_SFLOAT.as_raw() as u32
+        );
+    }
+
     #[test]
     fn explicit_timing() {
         let f = Fixture::new();
@@ -1754,6 +3609,131 @@ mod tests {
         f.check_frame_state(fakexr::FrameState::Ended);
     }
 
+    #[test]
+    fn explicit_timing_threaded_present() {
+        let f = Fixture::new();
+        f.ensure_real_session(false);
+        assert!(f.comp.presenter.set(Presenter::spawn()).is_ok());
+
+        f.comp.SetExplicitTimingMode(
+            vr::EVRCompositorTimingMode::Explicit_ApplicationPerformsPostPresentHandoff,
+        );
+        for _ in 0..2 {
+            assert_eq!(f.wait_get_poses(), None);
+            assert_eq!(f.comp.SubmitExplicitTimingData(), None);
+            assert_eq!(f.submit(vr::EVREye::Left), None);
+            assert_eq!(f.submit(vr::EVREye::Right), None);
+            f.comp.PostPresentHandoff();
+            f.comp.wait_for_present();
+            f.check_frame_state(fakexr::FrameState::Ended);
+        }
+    }
+
+    #[test]
+    fn threaded_present_dropped_after_session_restart() {
+        let f = Fixture::new();
+        f.ensure_real_session(false);
+        // Keep the handed off frame queued instead of presenting it.
+        let (jobs, queued) = mpsc::channel();
+        let pending: Arc<(Mutex<u32>, Condvar)> = Default::default();
+        let presenter = Presenter {
+            jobs,
+            pending: pending.clone(),
+        };
+        assert!(f.comp.presenter.set(Some(presenter)).is_ok());
+
+        assert_eq!(f.submit(vr::EVREye::Left), None);
+        assert_eq!(f.submit(vr::EVREye::Right), None);
+        f.comp.PostPresentHandoff();
+        let present = queued.try_recv().expect("Frame wasn't handed off");
+
+        f.comp.openxr.restart_session();
+        // The new session has no frame begun, so the old frame can't be ended in it.
+        present.run();
+        *pending.0.lock().unwrap() -= 1;
+        assert_eq!(f.comp.metrics.stats().dropped, 1);
+
+        assert_eq!(f.wait_get_poses(), None);
+        f.check_frame_state(fakexr::FrameState::Begun);
+    }
+
+    #[test]
+    fn frame_pacing_thread() {
+        let f = Fixture::new();
+        f.comp.frame_pacing.store(true, Ordering::Relaxed);
+        f.ensure_real_session(false);
+        {
+            let data = f.comp.openxr.session_data.get();
+            let lock = data.comp_data.0.lock().unwrap();
+            let DynFrameController::Fake(ctrl) = lock.as_ref().unwrap() else {
+                panic!("Frame controller was not set up or not faked!");
+            };
+            assert!(matches!(ctrl.waiter, Waiter::Paced(_)));
+        }
+
+        for _ in 0..3 {
+            assert_eq!(f.wait_get_poses(), None);
+            f.check_frame_state(fakexr::FrameState::Begun);
+            assert_eq!(f.submit(vr::EVREye::Left), None);
+            assert_eq!(f.submit(vr::EVREye::Right), None);
+        }
+    }
+
+    #[test]
+    fn submit_with_array_index() {
+        let f = Fixture::new();
+        f.ensure_real_session(false);
+        assert_eq!(f.wait_get_poses(), None);
+        for (eye, index) in [(vr::EVREye::Left, 0), (vr::EVREye::Right, 1)] {
+            assert_eq!(
+                f.comp.SubmitWithArrayIndex(
+                    eye,
+                    &FakeGraphicsData::texture(&f.vk),
+                    index,
+                    std::ptr::null(),
+                    vr::EVRSubmitFlags::Default,
+                ),
+                None
+            );
+        }
+        assert_eq!(
+            f.comp.SubmitWithArrayIndex(
+                vr::EVREye::Left,
+                &FakeGraphicsData::texture(&f.vk),
+                0,
+                std::ptr::null(),
+                vr::EVRSubmitFlags::Default,
+            ),
+            AlreadySubmitted
+        );
+        assert_eq!(f.wait_get_poses(), None);
+    }
+
+    #[test]
+    fn standby() {
+        let f = Fixture::new();
+        f.ensure_real_session(false);
+        let session = f.comp.openxr.session_data.get().session.as_raw();
+        assert_eq!(f.submit(vr::EVREye::Left), None);
+        assert_eq!(f.submit(vr::EVREye::Right), None);
+
+        // The frame in flight is ended before the session is.
+        fakexr::set_session_state(session, xr::SessionState::STOPPING);
+        assert_eq!(f.wait_get_poses(), DoNotHaveFocus);
+        f.check_frame_state(fakexr::FrameState::Ended);
+        assert!(f.comp.openxr.in_standby());
+        assert_eq!(f.submit(vr::EVREye::Left), DoNotHaveFocus);
+        assert_eq!(f.wait_get_poses(), DoNotHaveFocus);
+
+        fakexr::set_session_state(session, xr::SessionState::READY);
+        assert_eq!(f.wait_get_poses(), None);
+        assert!(!f.comp.openxr.in_standby());
+        f.check_frame_state(fakexr::FrameState::Begun);
+        assert_eq!(f.submit(vr::EVREye::Left), None);
+        assert_eq!(f.submit(vr::EVREye::Right), None);
+        assert_eq!(f.wait_get_poses(), None);
+    }
+
     #[test]
     fn explicit_timing_no_submit() {
         let f = Fixture::new();
@@ -1796,6 +3776,45 @@ mod tests {
         f.check_frame_state(fakexr::FrameState::Waited);
     }
 
+    #[test]
+    fn explicit_timing_data_once_per_frame() {
+        let f = Fixture::new();
+        f.comp.SetExplicitTimingMode(
+            vr::EVRCompositorTimingMode::Explicit_ApplicationPerformsPostPresentHandoff,
+        );
+        f.ensure_real_session(true);
+
+        assert_eq!(f.comp.SubmitExplicitTimingData(), RequestFailed);
+        f.check_frame_state(fakexr::FrameState::Ended);
+
+        assert_eq!(f.wait_get_poses(), None);
+        assert_eq!(f.comp.SubmitExplicitTimingData(), None);
+        f.check_frame_state(fakexr::FrameState::Begun);
+        assert_eq!(f.submit(vr::EVREye::Left), None);
+        // Calling it again doesn't restart the frame.
+        assert_eq!(f.comp.SubmitExplicitTimingData(), None);
+        assert_eq!(f.submit(vr::EVREye::Right), None);
+        f.comp.PostPresentHandoff();
+        f.check_frame_state(fakexr::FrameState::Ended);
+    }
+
+    #[test]
+    fn explicit_timing_submit_without_timing_data() {
+        let f = Fixture::new();
+        f.comp.SetExplicitTimingMode(
+            vr::EVRCompositorTimingMode::Explicit_ApplicationPerformsPostPresentHandoff,
+        );
+        f.ensure_real_session(true);
+
+        assert_eq!(f.wait_get_poses(), None);
+        f.check_frame_state(fakexr::FrameState::Waited);
+        assert_eq!(f.submit(vr::EVREye::Left), None);
+        f.check_frame_state(fakexr::FrameState::Begun);
+        assert_eq!(f.submit(vr::EVREye::Right), None);
+        f.comp.PostPresentHandoff();
+        f.check_frame_state(fakexr::FrameState::Ended);
+    }
+
     #[test]
     fn explicit_timing_unfocused() {
         let f = Fixture::new();
@@ -1812,4 +3831,194 @@ mod tests {
         assert_eq!(f.submit(vr::EVREye::Left), None);
         assert_eq!(f.submit(vr::EVREye::Right), None);
     }
+
+    #[test]
+    fn depth_planes_from_projection() {
+        let projection = |a: f32, b: f32| vr::HmdMatrix44_t {
+            m: [
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, a, b],
+                [0.0, 0.0, -1.0, 0.0],
+            ],
+        };
+        let (near, far) = (0.1, 100.0);
+        let assert_planes = |(a, b): (f32, f32), (expected_near, expected_far): (f32, f32)| {
+            let (near_z, far_z) = depth_planes(&projection(a, b));
+            assert!(
+                (near_z - expected_near).abs() < 1e-4,
+                "{near_z} != {expected_near}"
+            );
+            assert!(
+                (far_z - expected_far).abs() < 1e-2,
+                "{far_z} != {expected_far}"
+            );
+        };
+
+        // What GetProjectionMatrix returns.
+        assert_planes((far / (near - far), far * near / (near - far)), (near, far));
+        // Reversed depth: 0 is the far plane.
+        assert_planes(
+            (near / (far - near), far * near / (far - near)),
+            (far, near),
+        );
+    }
+
+    #[test]
+    fn fade_interpolation() {
+        let start = Instant::now();
+        let mut fade = Fade::new(Vec4::ZERO);
+        fade.start(Vec4::new(0.0, 0.0, 0.0, 1.0), 2.0, start);
+        assert_eq!(fade.current(start).w, 0.0);
+        assert_eq!(fade.current(start + Duration::from_secs(1)).w, 0.5);
+        assert_eq!(fade.current(start + Duration::from_secs(5)).w, 1.0);
+
+        // Fading back starts from wherever the previous fade got to.
+        fade.start(Vec4::ZERO, 1.0, start + Duration::from_secs(1));
+        assert_eq!(fade.current(start + Duration::from_secs(1)).w, 0.5);
+        assert_eq!(fade.current(start + Duration::from_secs(2)).w, 0.0);
+
+        // Instant fades
+        fade.start(Vec4::ONE, 0.0, start);
+        assert_eq!(fade.current(start), Vec4::ONE);
+    }
+
+    #[test]
+    fn gpu_frame_times() {
+        let span = |start_ms, end_ms| GpuSpan { start_ms, end_ms };
+        let times = GpuFrameTimes::new(100.0, [span(108.0, 108.5), span(110.0, 111.0)]);
+        assert_eq!(
+            times,
+            GpuFrameTimes {
+                total_ms: 11.0,
+                pre_submit_ms: 9.5,
+                copy_ms: 1.5,
+            }
+        );
+
+        // The copies can't take longer than the whole frame.
+        let times = GpuFrameTimes::new(110.0, [span(108.0, 109.0), span(109.0, 110.0)]);
+        assert_eq!(times.total_ms, 2.0);
+        assert_eq!(times.pre_submit_ms, 0.0);
+    }
+
+    #[test]
+    fn inset_view_rect() {
+        let rect_of = |rect: xr::Rect2Di| {
+            (
+                rect.offset.x,
+                rect.offset.y,
+                rect.extent.width,
+                rect.extent.height,
+            )
+        };
+        let fov = xr::Fovf {
+            angle_left: -std::f32::consts::FRAC_PI_4,
+            angle_right: std::f32::consts::FRAC_PI_4,
+            angle_up: std::f32::consts::FRAC_PI_4,
+            angle_down: -std::f32::consts::FRAC_PI_4,
+        };
+        let half = 0.5f32.atan();
+        let inset = xr::Fovf {
+            angle_left: -half,
+            angle_right: half,
+            angle_up: half,
+            angle_down: -half,
+        };
+        let extent = xr::Extent2Di {
+            width: 100,
+            height: 100,
+        };
+        let (inset_fov, rect) = inset_view(fov, inset, extent);
+        assert_eq!(rect_of(rect), (25, 25, 50, 50));
+        assert!((inset_fov.angle_right - half).abs() < 1e-5);
+        assert!((inset_fov.angle_down + half).abs() < 1e-5);
+
+        // Flipped images have their FOVs flipped the same way.
+        let flip = |fov: xr::Fovf| xr::Fovf {
+            angle_up: fov.angle_down,
+            angle_down: fov.angle_up,
+            ..fov
+        };
+        let (inset_fov, rect) = inset_view(flip(fov), flip(inset), extent);
+        assert_eq!(rect_of(rect), (25, 25, 50, 50));
+        assert!((inset_fov.angle_up + half).abs() < 1e-5);
+
+        // Insets outside of the image show all of it.
+        let outside = xr::Fovf {
+            angle_left: 1.0,
+            angle_right: 1.2,
+            ..inset
+        };
+        let (inset_fov, rect) = inset_view(fov, outside, extent);
+        assert_eq!(rect_of(rect), (0, 0, 100, 100));
+        assert_eq!(inset_fov.angle_left, fov.angle_left);
+    }
+
+    #[test]
+    fn fade_pixel_is_srgb() {
+        assert_eq!(
+            fade_pixel(Vec4::new(0.0, 1.0, 0.5, 0.5)),
+            [0, 255, 188, 128]
+        );
+        assert_eq!(fade_pixel(Vec4::new(-1.0, 2.0, 0.0, 0.0)), [0, 255, 0, 0]);
+    }
+
+    #[test]
+    fn submitted_pose_placement() {
+        let head = xr::Posef {
+            // 90 degrees around y
+            orientation: xr::Quaternionf {
+                x: 0.0,
+                y: std::f32::consts::FRAC_1_SQRT_2,
+                z: 0.0,
+                w: std::f32::consts::FRAC_1_SQRT_2,
+            },
+            position: xr::Vector3f {
+                x: 1.0,
+                y: 1.5,
+                z: 0.0,
+            },
+        };
+        let texture = vr::VRTextureWithPose_t {
+            _base: vr::Texture_t {
+                handle: std::ptr::null_mut(),
+                eType: vr::ETextureType::Vulkan,
+                eColorSpace: vr::EColorSpace::Auto,
+            },
+            mDeviceToAbsoluteTracking: head.into(),
+        };
+        let base = &texture._base;
+        assert!(submitted_pose(base, vr::EVRSubmitFlags::Default).is_none());
+        let pose = submitted_pose(base, vr::EVRSubmitFlags::TextureWithPose).unwrap();
+
+        // The left eye, looking down -z from the head, ends up looking down -x.
+        let eye = xr::Posef {
+            orientation: xr::Quaternionf::IDENTITY,
+            position: xr::Vector3f {
+                x: -0.03,
+                y: 0.0,
+                z: 0.0,
+            },
+        };
+        let placed = eye_pose_from_head(pose, eye);
+        let position = Vec3::new(placed.position.x, placed.position.y, placed.position.z);
+        assert!(
+            position.abs_diff_eq(Vec3::new(1.0, 1.5, 0.03), 1e-5),
+            "{position}"
+        );
+        let xr::Quaternionf { x, y, z, w } = placed.orientation;
+        assert!(
+            (Quat::from_xyzw(x, y, z, w) * Vec3::NEG_Z).abs_diff_eq(Vec3::NEG_X, 1e-5),
+            "{:?}",
+            placed.orientation
+        );
+
+        // An empty pose is ignored.
+        let texture = vr::VRTextureWithPose_t {
+            mDeviceToAbsoluteTracking: vr::HmdMatrix34_t { m: [[0.0; 4]; 3] },
+            ..texture
+        };
+        assert!(submitted_pose(&texture._base, vr::EVRSubmitFlags::TextureWithPose).is_none());
+    }
 }
@@ -0,0 +1,759 @@
+use crate::color_gain::ColorGain;
+use crate::dynamic_resolution::DynamicResolutionConfig;
+use crate::face_tracking::FaceTrackingConfig;
+use crate::foveation::FoveationConfig;
+use crate::input::{FingerSmoothingConfig, GestureBinding, PoseFilterConfig};
+use crate::mirror::MirrorConfig;
+use crate::openxr_data::Hand;
+use crate::osc_trackers::OscTrackersConfig;
+use crate::overlay::DashboardConfig;
+use glam::{EulerRot, Mat4, Quat, Vec3};
+use log::{error, info};
+use openvr as vr;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// User configuration, read from `$XDG_CONFIG_HOME/xrizer/config.json` (or the file pointed to by
+/// `XRIZER_CONFIG`).
+#[derive(Deserialize, Default)]
+pub struct Config {
+    /// Keyed by interaction profile path (i.e. /interaction_profiles/valve/index_controller)
+    #[serde(default)]
+    pose_offsets: HashMap<String, ProfilePoseOffsets>,
+    /// Trackers received over OSC, disabled if missing.
+    #[serde(default)]
+    pub osc_trackers: Option<OscTrackersConfig>,
+    /// Face tracking sent over OSC, disabled if missing.
+    #[serde(default)]
+    pub face_tracking: Option<FaceTrackingConfig>,
+    /// Desktop window mirroring the headset view, disabled if missing.
+    #[serde(default)]
+    pub mirror_window: Option<MirrorConfig>,
+    /// Smoothing for controller poses, disabled if missing.
+    #[serde(default)]
+    pub pose_filter: Option<PoseFilterConfig>,
+    /// Smoothing for the fingers of skeletons estimated from controller inputs.
+    #[serde(default)]
+    pub finger_smoothing: FingerSmoothingConfig,
+    /// Keyed by interaction profile path, or "default" for all profiles.
+    #[serde(default)]
+    haptics: HashMap<String, HapticsConfig>,
+    /// SteamVR controller type (i.e. knuckles) to report all controllers as.
+    #[serde(default)]
+    emulate_controller: Option<String>,
+    /// Keyed by "trigger" or "grip".
+    #[serde(default)]
+    click_thresholds: HashMap<String, ClickThresholds>,
+    /// Keyed by interaction profile path, or "default" for all profiles.
+    #[serde(default)]
+    thumbsticks: HashMap<String, ThumbstickConfig>,
+    /// Hand tracking gestures that press boolean actions.
+    #[serde(default)]
+    gestures: Vec<GestureBinding>,
+    /// Copy bindings only defined for the left hand over to the right hand.
+    #[serde(default)]
+    mirror_left_to_right: bool,
+    /// Reported to games instead of the detected skeletal tracking level.
+    #[serde(default)]
+    skeletal_tracking_level: Option<SkeletalTrackingLevel>,
+    /// Multiplier for the number of pixels in the recommended render target size, like SteamVR's
+    /// resolution slider.
+    #[serde(default)]
+    supersample_scale: Option<f32>,
+    /// Scaling of the recommended resolution with the game's performance, disabled if missing.
+    #[serde(default)]
+    pub dynamic_resolution: Option<DynamicResolutionConfig>,
+    /// Fixed foveation of the eye swapchain, disabled if missing.
+    #[serde(default)]
+    pub foveation: Option<FoveationConfig>,
+    /// Scaling of the game image's color, i.e. to dim the display at night.
+    #[serde(default)]
+    pub color_gain: Option<ColorGain>,
+    /// Strength of the sharpening applied to the game's image, from 0 to 1, disabled if missing.
+    #[serde(default)]
+    sharpening: Option<f32>,
+    /// Pass depth buffers submitted by games on to the runtime.
+    #[serde(default)]
+    submit_depth: bool,
+    /// Treat submitted textures as being in this color space, regardless of what the game says.
+    #[serde(default)]
+    force_color_space: Option<ColorSpace>,
+    /// End frames on a separate thread, so PostPresentHandoff returns right away.
+    #[serde(default)]
+    threaded_present: bool,
+    /// Call xrWaitFrame ahead of time on a separate thread, instead of in WaitGetPoses.
+    #[serde(default)]
+    frame_pacing_thread: bool,
+    /// Use the XR_VARJO_quad_views view configuration if the runtime supports it.
+    #[serde(default)]
+    quad_views: bool,
+    /// Show the real world behind games that render a transparent background, on headsets that
+    /// support it. Games can also toggle this through the xrizer/passthrough setting.
+    #[serde(default)]
+    passthrough: bool,
+    /// How to summon the dashboard, where dashboard overlays are shown.
+    #[serde(default)]
+    pub dashboard: DashboardConfig,
+    /// Per game overrides, keyed by Steam app key (i.e. steam.app.620980) or executable name.
+    #[serde(default)]
+    apps: HashMap<String, AppConfig>,
+}
+
+#[derive(Deserialize, Default)]
+struct AppConfig {
+    #[serde(default)]
+    haptics: HashMap<String, HapticsConfig>,
+    #[serde(default)]
+    emulate_controller: Option<String>,
+    #[serde(default)]
+    click_thresholds: HashMap<String, ClickThresholds>,
+    #[serde(default)]
+    thumbsticks: HashMap<String, ThumbstickConfig>,
+    #[serde(default)]
+    gestures: Vec<GestureBinding>,
+    #[serde(default)]
+    mirror_left_to_right: Option<bool>,
+    #[serde(default)]
+    skeletal_tracking_level: Option<SkeletalTrackingLevel>,
+    #[serde(default)]
+    supersample_scale: Option<f32>,
+    #[serde(default)]
+    submit_depth: Option<bool>,
+    #[serde(default)]
+    force_color_space: Option<ColorSpace>,
+    #[serde(default)]
+    threaded_present: Option<bool>,
+    #[serde(default)]
+    frame_pacing_thread: Option<bool>,
+    #[serde(default)]
+    quad_views: Option<bool>,
+    #[serde(default)]
+    passthrough: Option<bool>,
+    #[serde(default)]
+    sharpening: Option<f32>,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SkeletalTrackingLevel {
+    /// Finger poses are guessed from buttons and triggers.
+    Estimated,
+    /// Some fingers are tracked, i.e. by finger curl sensors.
+    Partial,
+    /// Every finger is tracked, i.e. by hand tracking.
+    Full,
+}
+
+impl From<SkeletalTrackingLevel> for vr::EVRSkeletalTrackingLevel {
+    fn from(level: SkeletalTrackingLevel) -> Self {
+        match level {
+            SkeletalTrackingLevel::Estimated => Self::Estimated,
+            SkeletalTrackingLevel::Partial => Self::Partial,
+            SkeletalTrackingLevel::Full => Self::Full,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorSpace {
+    /// Colors are sRGB encoded.
+    Gamma,
+    /// Colors are stored as they are.
+    Linear,
+}
+
+impl From<ColorSpace> for vr::EColorSpace {
+    fn from(color_space: ColorSpace) -> Self {
+        match color_space {
+            ColorSpace::Gamma => Self::Gamma,
+            ColorSpace::Linear => Self::Linear,
+        }
+    }
+}
+
+/// The analog values at which a click is generated for inputs without a click component, unless
+/// the bindings set their own.
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq)]
+pub struct ClickThresholds {
+    pub click: Option<f32>,
+    pub release: Option<f32>,
+}
+
+/// Remapping applied to haptic vibrations before they're passed to the runtime.
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq)]
+pub struct HapticsConfig {
+    amplitude_scale: Option<f32>,
+    /// In seconds
+    min_duration: Option<f32>,
+    /// In Hz
+    min_frequency: Option<f32>,
+    /// In Hz
+    max_frequency: Option<f32>,
+}
+
+impl HapticsConfig {
+    /// Fields set in `other` take priority.
+    fn merge(self, other: Self) -> Self {
+        Self {
+            amplitude_scale: other.amplitude_scale.or(self.amplitude_scale),
+            min_duration: other.min_duration.or(self.min_duration),
+            min_frequency: other.min_frequency.or(self.min_frequency),
+            max_frequency: other.max_frequency.or(self.max_frequency),
+        }
+    }
+
+    /// Returns the remapped amplitude, duration (in seconds) and frequency.
+    pub fn apply(&self, amplitude: f32, duration: f32, frequency: f32) -> (f32, f32, f32) {
+        let amplitude = (amplitude * self.amplitude_scale.unwrap_or(1.0)).clamp(0.0, 1.0);
+        // A zero duration means the shortest pulse the runtime can do, so leave it alone.
+        let duration = match self.min_duration {
+            Some(min) if duration > 0.0 => duration.max(min),
+            _ => duration,
+        };
+        // Likewise a zero frequency is left to the runtime.
+        let frequency = if frequency > 0.0 {
+            frequency
+                .max(self.min_frequency.unwrap_or(0.0))
+                .min(self.max_frequency.unwrap_or(f32::INFINITY))
+        } else {
+            frequency
+        };
+        (amplitude, duration, frequency)
+    }
+}
+
+/// Processing applied to two axis inputs before their values are passed to the game.
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq)]
+pub struct ThumbstickConfig {
+    /// Deflections smaller than this are ignored.
+    deadzone: Option<f32>,
+    /// Deflections within this distance of the edge count as fully deflected.
+    outer_deadzone: Option<f32>,
+    curve: Option<ThumbstickCurve>,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ThumbstickCurve {
+    Linear,
+    /// Raises the deflection to the given power, for finer control near the center.
+    Power(f32),
+}
+
+impl ThumbstickConfig {
+    /// Fields set in `other` take priority.
+    fn merge(self, other: Self) -> Self {
+        Self {
+            deadzone: other.deadzone.or(self.deadzone),
+            outer_deadzone: other.outer_deadzone.or(self.outer_deadzone),
+            curve: other.curve.or(self.curve),
+        }
+    }
+
+    /// Remaps the deflection, keeping its direction.
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        if *self == Self::default() {
+            return (x, y);
+        }
+        let magnitude = (x * x + y * y).sqrt();
+        let inner = self.deadzone.unwrap_or(0.0);
+        let outer = 1.0 - self.outer_deadzone.unwrap_or(0.0);
+        if magnitude <= inner || outer <= inner {
+            return (0.0, 0.0);
+        }
+        let scaled = ((magnitude - inner) / (outer - inner)).min(1.0);
+        let curved = match self.curve {
+            Some(ThumbstickCurve::Power(exponent)) => scaled.powf(exponent),
+            Some(ThumbstickCurve::Linear) | None => scaled,
+        };
+        (x / magnitude * curved, y / magnitude * curved)
+    }
+}
+
+/// The app key SteamVR uses for the running app, if it was launched through Steam.
+pub fn steam_app_key() -> Option<String> {
+    std::env::var("SteamAppId")
+        .or_else(|_| std::env::var("SteamGameId"))
+        .ok()
+        .filter(|id| !id.is_empty() && id != "0")
+        .map(|id| format!("steam.app.{id}"))
+}
+
+fn exe_name() -> Option<String> {
+    std::env::current_exe()
+        .ok()?
+        .file_name()?
+        .to_str()
+        .map(str::to_owned)
+}
+
+#[derive(Deserialize, Default)]
+struct ProfilePoseOffsets {
+    #[serde(default)]
+    left: HandPoseOffsets,
+    #[serde(default)]
+    right: HandPoseOffsets,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+pub struct HandPoseOffsets {
+    #[serde(default)]
+    pub grip: PoseOffset,
+    #[serde(default)]
+    pub aim: PoseOffset,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+pub struct PoseOffset {
+    /// In meters
+    #[serde(default)]
+    position: [f32; 3],
+    /// XYZ euler angles, in degrees
+    #[serde(default)]
+    rotation: [f32; 3],
+}
+
+impl PoseOffset {
+    pub fn matrix(&self) -> Mat4 {
+        let [x, y, z] = self.rotation.map(f32::to_radians);
+        Mat4::from_rotation_translation(
+            Quat::from_euler(EulerRot::XYZ, x, y, z),
+            Vec3::from_array(self.position),
+        )
+    }
+}
+
+impl Config {
+    pub fn get() -> &'static Self {
+        static CONFIG: OnceLock<Config> = OnceLock::new();
+        CONFIG.get_or_init(Self::load)
+    }
+
+    fn path() -> Option<PathBuf> {
+        if let Ok(path) = std::env::var("XRIZER_CONFIG") {
+            return Some(path.into());
+        }
+
+        let config_dir = std::env::var("XDG_CONFIG_HOME")
+            .or_else(|_| std::env::var("HOME").map(|h| h + "/.config"))
+            .ok()?;
+        Some(PathBuf::from(config_dir).join("xrizer/config.json"))
+    }
+
+    fn load() -> Self {
+        // Tests shouldn't be affected by whatever config is on the machine running them.
+        if cfg!(test) {
+            return Self::default();
+        }
+
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                error!("Failed to read config ({}): {e}", path.display());
+                return Self::default();
+            }
+        };
+
+        match serde_json::from_slice(&data) {
+            Ok(config) => {
+                info!("Loaded config from {}", path.display());
+                config
+            }
+            Err(e) => {
+                error!("Failed to parse config ({}): {e}", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    fn app(&self) -> Option<&AppConfig> {
+        if self.apps.is_empty() {
+            return None;
+        }
+        steam_app_key()
+            .and_then(|key| self.apps.get(&key))
+            .or_else(|| self.apps.get(&exe_name()?))
+    }
+
+    pub fn haptics(&self, profile_path: &str) -> HapticsConfig {
+        let app_haptics = self.app().map(|app| &app.haptics);
+        [Some(&self.haptics), app_haptics]
+            .into_iter()
+            .flatten()
+            .flat_map(|haptics| [haptics.get("default"), haptics.get(profile_path)])
+            .flatten()
+            .fold(HapticsConfig::default(), |acc, config| acc.merge(*config))
+    }
+
+    pub fn thumbsticks(&self, profile_path: &str) -> ThumbstickConfig {
+        let app_thumbsticks = self.app().map(|app| &app.thumbsticks);
+        [Some(&self.thumbsticks), app_thumbsticks]
+            .into_iter()
+            .flatten()
+            .flat_map(|sticks| [sticks.get("default"), sticks.get(profile_path)])
+            .flatten()
+            .fold(ThumbstickConfig::default(), |acc, config| {
+                acc.merge(*config)
+            })
+    }
+
+    /// For "trigger" or "grip". Per game settings take priority.
+    pub fn click_thresholds(&self, input: &str) -> ClickThresholds {
+        let app = self.app().and_then(|app| app.click_thresholds.get(input));
+        let global = self.click_thresholds.get(input);
+        ClickThresholds {
+            click: app.and_then(|t| t.click).or(global.and_then(|t| t.click)),
+            release: app
+                .and_then(|t| t.release)
+                .or(global.and_then(|t| t.release)),
+        }
+    }
+
+    /// Both the global and per game gesture bindings apply.
+    pub fn gestures(&self) -> impl Iterator<Item = &GestureBinding> {
+        self.gestures
+            .iter()
+            .chain(self.app().into_iter().flat_map(|app| &app.gestures))
+    }
+
+    pub fn mirror_left_to_right(&self) -> bool {
+        self.app()
+            .and_then(|app| app.mirror_left_to_right)
+            .unwrap_or(self.mirror_left_to_right)
+    }
+
+    pub fn skeletal_tracking_level(&self) -> Option<SkeletalTrackingLevel> {
+        self.app()
+            .and_then(|app| app.skeletal_tracking_level)
+            .or(self.skeletal_tracking_level)
+    }
+
+    pub fn supersample_scale(&self) -> f32 {
+        self.app()
+            .and_then(|app| app.supersample_scale)
+            .or(self.supersample_scale)
+            .unwrap_or(1.0)
+    }
+
+    pub fn submit_depth(&self) -> bool {
+        self.app()
+            .and_then(|app| app.submit_depth)
+            .unwrap_or(self.submit_depth)
+    }
+
+    pub fn threaded_present(&self) -> bool {
+        self.app()
+            .and_then(|app| app.threaded_present)
+            .unwrap_or(self.threaded_present)
+    }
+
+    pub fn frame_pacing_thread(&self) -> bool {
+        self.app()
+            .and_then(|app| app.frame_pacing_thread)
+            .unwrap_or(self.frame_pacing_thread)
+    }
+
+    pub fn quad_views(&self) -> bool {
+        self.app()
+            .and_then(|app| app.quad_views)
+            .unwrap_or(self.quad_views)
+    }
+
+    pub fn passthrough(&self) -> bool {
+        self.app()
+            .and_then(|app| app.passthrough)
+            .unwrap_or(self.passthrough)
+    }
+
+    /// None if sharpening is off. A strength of 0 in `apps` turns it off for that game.
+    pub fn sharpening(&self) -> Option<f32> {
+        self.app()
+            .and_then(|app| app.sharpening)
+            .or(self.sharpening)
+            .filter(|strength| *strength > 0.0)
+            .map(|strength| strength.min(1.0))
+    }
+
+    pub fn force_color_space(&self) -> Option<ColorSpace> {
+        self.app()
+            .and_then(|app| app.force_color_space)
+            .or(self.force_color_space)
+    }
+
+    pub fn emulated_controller(&self) -> Option<&str> {
+        self.app()
+            .and_then(|app| app.emulate_controller.as_deref())
+            .or(self.emulate_controller.as_deref())
+    }
+
+    pub fn pose_offsets(&self, profile_path: &str, hand: Hand) -> HandPoseOffsets {
+        self.pose_offsets
+            .get(profile_path)
+            .map(|offsets| match hand {
+                Hand::Left => offsets.left,
+                Hand::Right => offsets.right,
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClickThresholds, ColorSpace, Config, SkeletalTrackingLevel};
+    use crate::input::{Gesture, GestureBinding};
+    use crate::mirror::MirrorEyes;
+    use crate::openxr_data::Hand;
+    use crate::osc_trackers::OscTrackersConfig;
+    use glam::{Mat4, Quat, Vec3};
+
+    #[test]
+    fn pose_offsets() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "pose_offsets": {
+                    "/interaction_profiles/valve/index_controller": {
+                        "left": {
+                            "grip": { "position": [0.0, 0.01, -0.02] },
+                            "aim": { "rotation": [90.0, 0.0, 0.0] }
+                        }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let profile = "/interaction_profiles/valve/index_controller";
+        let left = config.pose_offsets(profile, Hand::Left);
+        assert_eq!(
+            left.grip.matrix(),
+            Mat4::from_translation(Vec3::new(0.0, 0.01, -0.02))
+        );
+        assert!(left.aim.matrix().abs_diff_eq(
+            Mat4::from_quat(Quat::from_rotation_x(90.0_f32.to_radians())),
+            1e-6
+        ));
+
+        let right = config.pose_offsets(profile, Hand::Right);
+        assert_eq!(right.grip.matrix(), Mat4::IDENTITY);
+        assert_eq!(right.aim.matrix(), Mat4::IDENTITY);
+
+        let other = config.pose_offsets("/interaction_profiles/htc/vive_controller", Hand::Left);
+        assert_eq!(other.grip.matrix(), Mat4::IDENTITY);
+    }
+
+    #[test]
+    fn haptics() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "haptics": {
+                    "default": { "amplitude_scale": 2.0, "max_frequency": 200.0 },
+                    "/interaction_profiles/valve/index_controller": { "min_duration": 0.05 }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let index = config.haptics("/interaction_profiles/valve/index_controller");
+        assert_eq!(index.apply(0.25, 0.01, 320.0), (0.5, 0.05, 200.0));
+        assert_eq!(index.apply(0.75, 0.0, 0.0), (1.0, 0.0, 0.0));
+
+        let vive = config.haptics("/interaction_profiles/htc/vive_controller");
+        assert_eq!(vive.apply(0.25, 0.01, 100.0), (0.5, 0.01, 100.0));
+    }
+
+    #[test]
+    fn thumbsticks() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "thumbsticks": {
+                    "default": { "deadzone": 0.2, "outer_deadzone": 0.2 },
+                    "/interaction_profiles/valve/index_controller": { "curve": { "power": 2.0 } }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let vive = config.thumbsticks("/interaction_profiles/htc/vive_controller");
+        assert_eq!(vive.apply(0.1, -0.1), (0.0, 0.0));
+        assert_eq!(vive.apply(0.0, 0.9), (0.0, 1.0));
+        let (x, y) = vive.apply(-0.5, 0.0);
+        assert!((x + 0.5).abs() < 1e-6 && y == 0.0);
+
+        let index = config.thumbsticks("/interaction_profiles/valve/index_controller");
+        let (x, y) = index.apply(0.0, 0.5);
+        assert!(x == 0.0 && (y - 0.25).abs() < 1e-6);
+
+        let none = Config::default().thumbsticks("/interaction_profiles/valve/index_controller");
+        assert_eq!(none.apply(0.1, 0.2), (0.1, 0.2));
+    }
+
+    #[test]
+    fn click_thresholds() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "click_thresholds": {
+                    "trigger": { "click": 0.6, "release": 0.5 }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.click_thresholds("trigger"),
+            ClickThresholds {
+                click: Some(0.6),
+                release: Some(0.5)
+            }
+        );
+        assert_eq!(config.click_thresholds("grip"), ClickThresholds::default());
+    }
+
+    #[test]
+    fn gestures() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "gestures": [
+                    { "action": "/actions/main/in/menu", "gesture": "thumbs_up", "hand": "left" },
+                    { "action": "/actions/main/in/fire", "gesture": "finger_gun" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let gestures: Vec<_> = config.gestures().collect();
+        assert_eq!(
+            *gestures[0],
+            GestureBinding {
+                action: "/actions/main/in/menu".into(),
+                gesture: Gesture::ThumbsUp,
+                hand: Some(Hand::Left),
+            }
+        );
+        assert_eq!(gestures[1].gesture, Gesture::FingerGun);
+        assert_eq!(gestures[1].hand, None);
+    }
+
+    #[test]
+    fn skeletal_tracking_level() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "skeletal_tracking_level": "partial"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.skeletal_tracking_level(),
+            Some(SkeletalTrackingLevel::Partial)
+        );
+        assert_eq!(Config::default().skeletal_tracking_level(), None);
+    }
+
+    #[test]
+    fn supersample_scale() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "supersample_scale": 1.5
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.supersample_scale(), 1.5);
+        assert_eq!(Config::default().supersample_scale(), 1.0);
+    }
+
+    #[test]
+    fn dynamic_resolution() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "dynamic_resolution": { "min_scale": 0.6 }
+            }"#,
+        )
+        .unwrap();
+        let dynamic = config.dynamic_resolution.unwrap();
+        assert_eq!(dynamic.min_scale, 0.6);
+        assert_eq!(dynamic.max_scale, 1.0);
+        assert!(Config::default().dynamic_resolution.is_none());
+    }
+
+    #[test]
+    fn foveation() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "foveation": { "level": "medium", "dynamic": true }
+            }"#,
+        )
+        .unwrap();
+        let foveation = config.foveation.unwrap();
+        assert_eq!(foveation.level, crate::foveation::FoveationLevel::Medium);
+        assert!(foveation.dynamic);
+        assert!(Config::default().foveation.is_none());
+    }
+
+    #[test]
+    fn submit_depth() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "submit_depth": true
+            }"#,
+        )
+        .unwrap();
+        assert!(config.submit_depth());
+        assert!(!Config::default().submit_depth());
+    }
+
+    #[test]
+    fn force_color_space() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "force_color_space": "linear"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.force_color_space(), Some(ColorSpace::Linear));
+        assert_eq!(Config::default().force_color_space(), None);
+    }
+
+    #[test]
+    fn mirror_window() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "mirror_window": { "eyes": "left" }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.mirror_window.as_ref().map(|mirror| mirror.eyes),
+            Some(MirrorEyes::Left)
+        );
+
+        let config: Config = serde_json::from_str(r#"{ "mirror_window": {} }"#).unwrap();
+        assert_eq!(
+            config.mirror_window.as_ref().map(|mirror| mirror.eyes),
+            Some(MirrorEyes::Both)
+        );
+        assert!(Config::default().mirror_window.is_none());
+    }
+
+    #[test]
+    fn sharpening() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "sharpening": 0.5
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.sharpening(), Some(0.5));
+
+        let config: Config = serde_json::from_str(r#"{ "sharpening": 2.0 }"#).unwrap();
+        assert_eq!(config.sharpening(), Some(1.0));
+        let config: Config = serde_json::from_str(r#"{ "sharpening": 0.0 }"#).unwrap();
+        assert_eq!(config.sharpening(), None);
+        assert_eq!(Config::default().sharpening(), None);
+    }
+}
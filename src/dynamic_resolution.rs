@@ -0,0 +1,217 @@
+//! Scales the recommended render resolution with how well the game keeps up with the display, for
+//! games that don't have a dynamic resolution of their own.
+
+use crate::config::Config;
+use log::info;
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+/// Frames to wait after changing the scale before changing it again, since games may have to
+/// reallocate their render targets, and it takes a while for the load to settle.
+const COOLDOWN_FRAMES: u32 = 90;
+/// How much of each frame's load goes into the average.
+const LOAD_SMOOTHING: f32 = 0.05;
+/// How much the scale changes at once.
+const STEP: f32 = 0.1;
+/// How far the load can be above or below the target before the scale changes.
+const LOAD_ABOVE_TARGET: f32 = 0.1;
+const LOAD_BELOW_TARGET: f32 = 0.15;
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct DynamicResolutionConfig {
+    /// The lowest multiplier for the recommended pixel count.
+    #[serde(default = "default_min_scale")]
+    pub min_scale: f32,
+    /// The highest multiplier for the recommended pixel count.
+    #[serde(default = "default_max_scale")]
+    pub max_scale: f32,
+    /// How much of each display period the game should be busy for.
+    #[serde(default = "default_target_load")]
+    pub target_load: f32,
+}
+
+fn default_min_scale() -> f32 {
+    0.5
+}
+
+fn default_max_scale() -> f32 {
+    1.0
+}
+
+fn default_target_load() -> f32 {
+    0.8
+}
+
+/// Timings of a presented frame.
+#[derive(Clone, Copy, Debug)]
+pub struct FrameLoad {
+    /// Since the previous frame started.
+    pub interval_ms: f32,
+    /// Spent blocked in xrWaitFrame. The runtime throttles games there, including on their GPU
+    /// work, so this is how much time the game had to spare.
+    pub wait_ms: f32,
+    pub display_period_ms: f32,
+}
+
+struct Controller {
+    config: DynamicResolutionConfig,
+    scale: f32,
+    /// Average fraction of the display period the game was busy for since the last change.
+    load: Option<f32>,
+    frames_since_change: u32,
+}
+
+impl Controller {
+    fn new(config: DynamicResolutionConfig) -> Self {
+        Self {
+            config,
+            scale: config.max_scale.max(config.min_scale),
+            load: None,
+            frames_since_change: 0,
+        }
+    }
+
+    /// Returns true if the scale changed.
+    fn frame_presented(&mut self, frame: FrameLoad) -> bool {
+        if frame.display_period_ms <= 0.0 {
+            return false;
+        }
+
+        let load = ((frame.interval_ms - frame.wait_ms) / frame.display_period_ms).max(0.0);
+        let load = *self.load.insert(match self.load {
+            Some(average) => average + (load - average) * LOAD_SMOOTHING,
+            None => load,
+        });
+        self.frames_since_change = self.frames_since_change.saturating_add(1);
+        if self.frames_since_change < COOLDOWN_FRAMES {
+            return false;
+        }
+
+        let target = self.config.target_load;
+        let scale = if load > target + LOAD_ABOVE_TARGET {
+            self.scale - STEP
+        } else if load < target - LOAD_BELOW_TARGET {
+            self.scale + STEP
+        } else {
+            return false;
+        };
+        let scale = scale.clamp(self.config.min_scale, self.config.max_scale);
+        if scale == self.scale {
+            return false;
+        }
+
+        self.scale = scale;
+        self.load = None;
+        self.frames_since_change = 0;
+        true
+    }
+}
+
+pub struct DynamicResolution {
+    controller: Mutex<Controller>,
+    changed: AtomicBool,
+}
+
+impl DynamicResolution {
+    /// Returns None if dynamic resolution isn't enabled.
+    pub fn get() -> Option<&'static Self> {
+        static DYNAMIC_RESOLUTION: OnceLock<Option<DynamicResolution>> = OnceLock::new();
+        DYNAMIC_RESOLUTION
+            .get_or_init(|| {
+                Config::get().dynamic_resolution.map(|config| {
+                    info!("Dynamic resolution enabled ({config:?})");
+                    Self {
+                        controller: Controller::new(config).into(),
+                        changed: false.into(),
+                    }
+                })
+            })
+            .as_ref()
+    }
+
+    /// The multiplier for the recommended pixel count.
+    pub fn scale(&self) -> f32 {
+        self.controller.lock().unwrap().scale
+    }
+
+    pub fn frame_presented(&self, frame: FrameLoad) {
+        let mut controller = self.controller.lock().unwrap();
+        if controller.frame_presented(frame) {
+            info!(
+                "Changing dynamic resolution scale to {:.1}",
+                controller.scale
+            );
+            self.changed.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Whether the scale changed since the last call.
+    pub fn take_changed(&self) -> bool {
+        self.changed.swap(false, Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> DynamicResolutionConfig {
+        serde_json::from_str("{}").unwrap()
+    }
+
+    /// Presents frames with the given load until the scale changes, returning how many it took.
+    fn frames_until_change(controller: &mut Controller, load: f32) -> Option<u32> {
+        let frame = FrameLoad {
+            interval_ms: 10.0,
+            wait_ms: 10.0 * (1.0 - load),
+            display_period_ms: 10.0,
+        };
+        (1..=1000).find(|_| controller.frame_presented(frame))
+    }
+
+    #[test]
+    fn defaults() {
+        assert_eq!(
+            config(),
+            DynamicResolutionConfig {
+                min_scale: 0.5,
+                max_scale: 1.0,
+                target_load: 0.8,
+            }
+        );
+        assert_eq!(Controller::new(config()).scale, 1.0);
+    }
+
+    #[test]
+    fn scales_down_when_overloaded() {
+        let mut controller = Controller::new(config());
+        assert_eq!(
+            frames_until_change(&mut controller, 1.0),
+            Some(COOLDOWN_FRAMES)
+        );
+        assert!((controller.scale - 0.9).abs() < 1e-6);
+
+        // Stops at the minimum.
+        while frames_until_change(&mut controller, 1.0).is_some() {}
+        assert!((controller.scale - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn scales_up_with_headroom() {
+        let mut controller = Controller::new(DynamicResolutionConfig {
+            max_scale: 1.5,
+            ..config()
+        });
+        controller.scale = 1.0;
+        assert!(frames_until_change(&mut controller, 0.3).is_some());
+        assert!((controller.scale - 1.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn keeps_scale_near_target() {
+        let mut controller = Controller::new(config());
+        assert_eq!(frames_until_change(&mut controller, 0.8), None);
+        assert_eq!(controller.scale, 1.0);
+    }
+}
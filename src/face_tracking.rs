@@ -0,0 +1,326 @@
+//! Face tracking through XR_FB_face_tracking, sent to VRChat (or anything else listening) over OSC
+//! as [VRCFaceTracking](https://github.com/benaclejames/VRCFaceTracking) v2 avatar parameters.
+
+use crate::config::Config;
+use crate::osc::BundleWriter;
+use log::{error, info, warn};
+use openxr as xr;
+use serde::Deserialize;
+use std::net::UdpSocket;
+use std::sync::Mutex;
+
+#[derive(Deserialize)]
+pub struct FaceTrackingConfig {
+    /// Where to send the OSC parameters.
+    #[serde(default = "default_address")]
+    address: String,
+}
+
+fn default_address() -> String {
+    "127.0.0.1:9000".into()
+}
+
+/// XR_FACE_EXPRESSION_COUNT_FB
+const EXPRESSION_COUNT: usize = 63;
+/// XR_FACE_CONFIDENCE_COUNT_FB
+const CONFIDENCE_COUNT: usize = 2;
+const PARAMETER_PREFIX: &str = "/avatar/parameters/FT/v2/";
+/// Parameters that haven't changed by more than this aren't resent.
+const SEND_THRESHOLD: f32 = 0.005;
+
+/// Indices into the XR_FB_face_tracking expression weights.
+#[allow(dead_code)]
+mod fb {
+    pub const BROW_LOWERER_L: usize = 0;
+    pub const BROW_LOWERER_R: usize = 1;
+    pub const CHEEK_PUFF_L: usize = 2;
+    pub const CHEEK_PUFF_R: usize = 3;
+    pub const CHEEK_RAISER_L: usize = 4;
+    pub const CHEEK_RAISER_R: usize = 5;
+    pub const CHEEK_SUCK_L: usize = 6;
+    pub const CHEEK_SUCK_R: usize = 7;
+    pub const CHIN_RAISER_B: usize = 8;
+    pub const CHIN_RAISER_T: usize = 9;
+    pub const DIMPLER_L: usize = 10;
+    pub const DIMPLER_R: usize = 11;
+    pub const EYES_CLOSED_L: usize = 12;
+    pub const EYES_CLOSED_R: usize = 13;
+    pub const EYES_LOOK_DOWN_L: usize = 14;
+    pub const EYES_LOOK_DOWN_R: usize = 15;
+    pub const EYES_LOOK_LEFT_L: usize = 16;
+    pub const EYES_LOOK_LEFT_R: usize = 17;
+    pub const EYES_LOOK_RIGHT_L: usize = 18;
+    pub const EYES_LOOK_RIGHT_R: usize = 19;
+    pub const EYES_LOOK_UP_L: usize = 20;
+    pub const EYES_LOOK_UP_R: usize = 21;
+    pub const INNER_BROW_RAISER_L: usize = 22;
+    pub const INNER_BROW_RAISER_R: usize = 23;
+    pub const JAW_DROP: usize = 24;
+    pub const JAW_SIDEWAYS_LEFT: usize = 25;
+    pub const JAW_SIDEWAYS_RIGHT: usize = 26;
+    pub const JAW_THRUST: usize = 27;
+    pub const LID_TIGHTENER_L: usize = 28;
+    pub const LID_TIGHTENER_R: usize = 29;
+    pub const LIP_CORNER_DEPRESSOR_L: usize = 30;
+    pub const LIP_CORNER_DEPRESSOR_R: usize = 31;
+    pub const LIP_CORNER_PULLER_L: usize = 32;
+    pub const LIP_CORNER_PULLER_R: usize = 33;
+    pub const LIP_FUNNELER_LB: usize = 34;
+    pub const LIP_FUNNELER_LT: usize = 35;
+    pub const LIP_FUNNELER_RB: usize = 36;
+    pub const LIP_FUNNELER_RT: usize = 37;
+    pub const LIP_PRESSOR_L: usize = 38;
+    pub const LIP_PRESSOR_R: usize = 39;
+    pub const LIP_PUCKER_L: usize = 40;
+    pub const LIP_PUCKER_R: usize = 41;
+    pub const LIP_STRETCHER_L: usize = 42;
+    pub const LIP_STRETCHER_R: usize = 43;
+    pub const LIP_SUCK_LB: usize = 44;
+    pub const LIP_SUCK_LT: usize = 45;
+    pub const LIP_SUCK_RB: usize = 46;
+    pub const LIP_SUCK_RT: usize = 47;
+    pub const LIP_TIGHTENER_L: usize = 48;
+    pub const LIP_TIGHTENER_R: usize = 49;
+    pub const LIPS_TOWARD: usize = 50;
+    pub const LOWER_LIP_DEPRESSOR_L: usize = 51;
+    pub const LOWER_LIP_DEPRESSOR_R: usize = 52;
+    pub const MOUTH_LEFT: usize = 53;
+    pub const MOUTH_RIGHT: usize = 54;
+    pub const NOSE_WRINKLER_L: usize = 55;
+    pub const NOSE_WRINKLER_R: usize = 56;
+    pub const OUTER_BROW_RAISER_L: usize = 57;
+    pub const OUTER_BROW_RAISER_R: usize = 58;
+    pub const UPPER_LID_RAISER_L: usize = 59;
+    pub const UPPER_LID_RAISER_R: usize = 60;
+    pub const UPPER_LIP_RAISER_L: usize = 61;
+    pub const UPPER_LIP_RAISER_R: usize = 62;
+}
+
+/// Unified expressions that map directly onto a single FB expression.
+const DIRECT_PARAMETERS: &[(&str, usize)] = &[
+    ("BrowLowererLeft", fb::BROW_LOWERER_L),
+    ("BrowLowererRight", fb::BROW_LOWERER_R),
+    ("BrowInnerUpLeft", fb::INNER_BROW_RAISER_L),
+    ("BrowInnerUpRight", fb::INNER_BROW_RAISER_R),
+    ("BrowOuterUpLeft", fb::OUTER_BROW_RAISER_L),
+    ("BrowOuterUpRight", fb::OUTER_BROW_RAISER_R),
+    ("CheekPuffLeft", fb::CHEEK_PUFF_L),
+    ("CheekPuffRight", fb::CHEEK_PUFF_R),
+    ("CheekSquintLeft", fb::CHEEK_RAISER_L),
+    ("CheekSquintRight", fb::CHEEK_RAISER_R),
+    ("CheekSuckLeft", fb::CHEEK_SUCK_L),
+    ("CheekSuckRight", fb::CHEEK_SUCK_R),
+    ("EyeSquintLeft", fb::LID_TIGHTENER_L),
+    ("EyeSquintRight", fb::LID_TIGHTENER_R),
+    ("EyeWideLeft", fb::UPPER_LID_RAISER_L),
+    ("EyeWideRight", fb::UPPER_LID_RAISER_R),
+    ("JawOpen", fb::JAW_DROP),
+    ("JawLeft", fb::JAW_SIDEWAYS_LEFT),
+    ("JawRight", fb::JAW_SIDEWAYS_RIGHT),
+    ("JawForward", fb::JAW_THRUST),
+    ("MouthClosed", fb::LIPS_TOWARD),
+    ("MouthRaiserLower", fb::CHIN_RAISER_B),
+    ("MouthRaiserUpper", fb::CHIN_RAISER_T),
+    ("MouthDimpleLeft", fb::DIMPLER_L),
+    ("MouthDimpleRight", fb::DIMPLER_R),
+    ("MouthFrownLeft", fb::LIP_CORNER_DEPRESSOR_L),
+    ("MouthFrownRight", fb::LIP_CORNER_DEPRESSOR_R),
+    ("MouthCornerPullLeft", fb::LIP_CORNER_PULLER_L),
+    ("MouthCornerPullRight", fb::LIP_CORNER_PULLER_R),
+    ("MouthPressLeft", fb::LIP_PRESSOR_L),
+    ("MouthPressRight", fb::LIP_PRESSOR_R),
+    ("MouthStretchLeft", fb::LIP_STRETCHER_L),
+    ("MouthStretchRight", fb::LIP_STRETCHER_R),
+    ("MouthTightenerLeft", fb::LIP_TIGHTENER_L),
+    ("MouthTightenerRight", fb::LIP_TIGHTENER_R),
+    ("MouthLowerDownLeft", fb::LOWER_LIP_DEPRESSOR_L),
+    ("MouthLowerDownRight", fb::LOWER_LIP_DEPRESSOR_R),
+    ("MouthUpperUpLeft", fb::UPPER_LIP_RAISER_L),
+    ("MouthUpperUpRight", fb::UPPER_LIP_RAISER_R),
+    ("MouthUpperLeft", fb::MOUTH_LEFT),
+    ("MouthLowerLeft", fb::MOUTH_LEFT),
+    ("MouthUpperRight", fb::MOUTH_RIGHT),
+    ("MouthLowerRight", fb::MOUTH_RIGHT),
+    ("LipFunnelLowerLeft", fb::LIP_FUNNELER_LB),
+    ("LipFunnelUpperLeft", fb::LIP_FUNNELER_LT),
+    ("LipFunnelLowerRight", fb::LIP_FUNNELER_RB),
+    ("LipFunnelUpperRight", fb::LIP_FUNNELER_RT),
+    ("LipPuckerUpperLeft", fb::LIP_PUCKER_L),
+    ("LipPuckerLowerLeft", fb::LIP_PUCKER_L),
+    ("LipPuckerUpperRight", fb::LIP_PUCKER_R),
+    ("LipPuckerLowerRight", fb::LIP_PUCKER_R),
+    ("LipSuckLowerLeft", fb::LIP_SUCK_LB),
+    ("LipSuckUpperLeft", fb::LIP_SUCK_LT),
+    ("LipSuckLowerRight", fb::LIP_SUCK_RB),
+    ("LipSuckUpperRight", fb::LIP_SUCK_RT),
+    ("NoseSneerLeft", fb::NOSE_WRINKLER_L),
+    ("NoseSneerRight", fb::NOSE_WRINKLER_R),
+];
+
+/// Converts FB expression weights into (parameter name, value) pairs.
+fn expression_parameters(
+    weights: &[f32; EXPRESSION_COUNT],
+    eyes_valid: bool,
+) -> impl Iterator<Item = (&'static str, f32)> + '_ {
+    let direct = DIRECT_PARAMETERS
+        .iter()
+        .map(|&(name, index)| (name, weights[index]));
+
+    let eyes = eyes_valid.then(|| {
+        [
+            ("EyeLidLeft", 1.0 - weights[fb::EYES_CLOSED_L]),
+            ("EyeLidRight", 1.0 - weights[fb::EYES_CLOSED_R]),
+            (
+                "EyeLeftX",
+                weights[fb::EYES_LOOK_RIGHT_L] - weights[fb::EYES_LOOK_LEFT_L],
+            ),
+            (
+                "EyeLeftY",
+                weights[fb::EYES_LOOK_UP_L] - weights[fb::EYES_LOOK_DOWN_L],
+            ),
+            (
+                "EyeRightX",
+                weights[fb::EYES_LOOK_RIGHT_R] - weights[fb::EYES_LOOK_LEFT_R],
+            ),
+            (
+                "EyeRightY",
+                weights[fb::EYES_LOOK_UP_R] - weights[fb::EYES_LOOK_DOWN_R],
+            ),
+        ]
+    });
+
+    direct.chain(eyes.into_iter().flatten())
+}
+
+pub struct FaceTracker {
+    // Keeps the session alive for as long as the tracker exists.
+    _session: xr::Session<xr::AnyGraphics>,
+    handle: xr::sys::FaceTrackerFB,
+    fp: xr::raw::FaceTrackingFB,
+    socket: UdpSocket,
+    last_sent: Mutex<Vec<(&'static str, f32)>>,
+}
+
+impl FaceTracker {
+    /// Returns None if face tracking isn't enabled in the config or isn't supported.
+    pub fn new(session: &xr::Session<xr::AnyGraphics>) -> Option<Self> {
+        let config = Config::get().face_tracking.as_ref()?;
+        let Some(fp) = session.instance().exts().fb_face_tracking else {
+            warn!("Face tracking is enabled, but XR_FB_face_tracking isn't supported");
+            return None;
+        };
+
+        let create_info = xr::sys::FaceTrackerCreateInfoFB {
+            ty: xr::sys::FaceTrackerCreateInfoFB::TYPE,
+            next: std::ptr::null(),
+            face_expression_set: xr::sys::FaceExpressionSetFB::DEFAULT,
+        };
+        let mut handle = xr::sys::FaceTrackerFB::NULL;
+        let result =
+            unsafe { (fp.create_face_tracker)(session.as_raw(), &create_info, &mut handle) };
+        if result != xr::sys::Result::SUCCESS {
+            error!("Failed to create face tracker: {result}");
+            return None;
+        }
+
+        let socket = UdpSocket::bind("0.0.0.0:0")
+            .and_then(|socket| socket.connect(&config.address).map(|_| socket))
+            .inspect_err(|e| error!("Couldn't set up face tracking socket: {e}"));
+        let Ok(socket) = socket else {
+            unsafe { (fp.destroy_face_tracker)(handle) };
+            return None;
+        };
+        info!("Sending face tracking to {}", config.address);
+
+        Some(Self {
+            _session: session.clone(),
+            handle,
+            fp,
+            socket,
+            last_sent: Mutex::default(),
+        })
+    }
+
+    pub fn update(&self, time: xr::Time) {
+        let mut weights = [0.0; EXPRESSION_COUNT];
+        let mut confidences = [0.0; CONFIDENCE_COUNT];
+        let info = xr::sys::FaceExpressionInfoFB {
+            ty: xr::sys::FaceExpressionInfoFB::TYPE,
+            next: std::ptr::null(),
+            time,
+        };
+        let mut expressions = xr::sys::FaceExpressionWeightsFB {
+            ty: xr::sys::FaceExpressionWeightsFB::TYPE,
+            next: std::ptr::null_mut(),
+            weight_count: EXPRESSION_COUNT as u32,
+            weights: weights.as_mut_ptr(),
+            confidence_count: CONFIDENCE_COUNT as u32,
+            confidences: confidences.as_mut_ptr(),
+            status: xr::sys::FaceExpressionStatusFB {
+                is_valid: false.into(),
+                is_eye_following_blendshapes_valid: false.into(),
+            },
+            time,
+        };
+        let result =
+            unsafe { (self.fp.get_face_expression_weights)(self.handle, &info, &mut expressions) };
+        if result != xr::sys::Result::SUCCESS {
+            crate::warn_once!("Failed to get face expression weights: {result}");
+            return;
+        }
+        if !bool::from(expressions.status.is_valid) {
+            return;
+        }
+
+        let eyes_valid = expressions.status.is_eye_following_blendshapes_valid.into();
+        let mut last_sent = self.last_sent.lock().unwrap();
+        let mut bundle = BundleWriter::new();
+        for (name, value) in expression_parameters(&weights, eyes_valid) {
+            match last_sent.iter_mut().find(|(n, _)| *n == name) {
+                Some((_, last)) if (*last - value).abs() < SEND_THRESHOLD => continue,
+                Some((_, last)) => *last = value,
+                None => last_sent.push((name, value)),
+            }
+            bundle.message(&format!("{PARAMETER_PREFIX}{name}"), &[value]);
+        }
+
+        if !bundle.is_empty() {
+            if let Err(e) = self.socket.send(&bundle.finish()) {
+                crate::warn_once!("Failed to send face tracking parameters: {e}");
+            }
+        }
+    }
+}
+
+impl Drop for FaceTracker {
+    fn drop(&mut self) {
+        unsafe { (self.fp.destroy_face_tracker)(self.handle) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parameters() {
+        let mut weights = [0.0; EXPRESSION_COUNT];
+        weights[fb::JAW_DROP] = 0.75;
+        weights[fb::EYES_CLOSED_L] = 1.0;
+        weights[fb::EYES_LOOK_LEFT_R] = 0.5;
+
+        let params: Vec<_> = expression_parameters(&weights, true).collect();
+        let get = |name| params.iter().find(|(n, _)| *n == name).unwrap().1;
+        assert_eq!(get("JawOpen"), 0.75);
+        assert_eq!(get("EyeLidLeft"), 0.0);
+        assert_eq!(get("EyeLidRight"), 1.0);
+        assert_eq!(get("EyeRightX"), -0.5);
+        assert_eq!(get("MouthClosed"), 0.0);
+
+        assert!(
+            expression_parameters(&weights, false).all(|(name, _)| !name.starts_with("Eye")
+                || name.starts_with("EyeSquint")
+                || name.starts_with("EyeWide"))
+        );
+    }
+}
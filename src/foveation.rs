@@ -0,0 +1,133 @@
+//! Fixed foveated rendering through XR_FB_foveation, applied to the eye swapchain by the runtime.
+//! This needs no cooperation from the game, which keeps rendering at full resolution: it's up to
+//! the runtime what it saves, i.e. streaming runtimes may encode the periphery at lower quality.
+
+use crate::config::Config;
+use log::{error, info, warn};
+use openxr as xr;
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FoveationLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl From<FoveationLevel> for xr::sys::FoveationLevelFB {
+    fn from(level: FoveationLevel) -> Self {
+        match level {
+            FoveationLevel::Low => Self::LOW,
+            FoveationLevel::Medium => Self::MEDIUM,
+            FoveationLevel::High => Self::HIGH,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct FoveationConfig {
+    pub level: FoveationLevel,
+    /// Moves the full quality area down (negative) or up, in the runtime's units.
+    #[serde(default)]
+    pub vertical_offset: f32,
+    /// Let the runtime lower the level while there's enough headroom.
+    #[serde(default)]
+    pub dynamic: bool,
+}
+
+/// A foveation profile, applied to swapchains as they're created.
+pub struct Foveation {
+    // Keeps the session alive for as long as the profile exists.
+    _session: xr::Session<xr::AnyGraphics>,
+    profile: xr::sys::FoveationProfileFB,
+    fp: xr::raw::FoveationFB,
+    update_fp: xr::raw::SwapchainUpdateStateFB,
+}
+
+impl Foveation {
+    /// Returns None if foveation isn't enabled in the config or isn't supported.
+    pub fn new(session: &xr::Session<xr::AnyGraphics>) -> Option<Self> {
+        let config = Config::get().foveation?;
+        let exts = session.instance().exts();
+        let (Some(fp), Some(update_fp), Some(_)) = (
+            exts.fb_foveation,
+            exts.fb_swapchain_update_state,
+            exts.fb_foveation_configuration,
+        ) else {
+            warn!("Foveation is enabled, but XR_FB_foveation isn't supported");
+            return None;
+        };
+
+        let level_info = xr::sys::FoveationLevelProfileCreateInfoFB {
+            ty: xr::sys::FoveationLevelProfileCreateInfoFB::TYPE,
+            next: std::ptr::null_mut(),
+            level: config.level.into(),
+            vertical_offset: config.vertical_offset,
+            dynamic: if config.dynamic {
+                xr::sys::FoveationDynamicFB::LEVEL_ENABLED
+            } else {
+                xr::sys::FoveationDynamicFB::DISABLED
+            },
+        };
+        let create_info = xr::sys::FoveationProfileCreateInfoFB {
+            ty: xr::sys::FoveationProfileCreateInfoFB::TYPE,
+            next: (&raw const level_info).cast_mut().cast(),
+        };
+        let mut profile = xr::sys::FoveationProfileFB::NULL;
+        let result =
+            unsafe { (fp.create_foveation_profile)(session.as_raw(), &create_info, &mut profile) };
+        if result != xr::sys::Result::SUCCESS {
+            error!("Failed to create foveation profile: {result}");
+            return None;
+        }
+        info!("Foveation enabled ({config:?})");
+
+        Some(Self {
+            _session: session.clone(),
+            profile,
+            fp,
+            update_fp,
+        })
+    }
+
+    pub fn apply<G: xr::Graphics>(&self, swapchain: &xr::Swapchain<G>) {
+        let state = xr::sys::SwapchainStateFoveationFB {
+            ty: xr::sys::SwapchainStateFoveationFB::TYPE,
+            next: std::ptr::null_mut(),
+            flags: xr::sys::SwapchainStateFoveationFlagsFB::EMPTY,
+            profile: self.profile,
+        };
+        let result = unsafe {
+            (self.update_fp.update_swapchain)(swapchain.as_raw(), (&raw const state).cast())
+        };
+        if result != xr::sys::Result::SUCCESS {
+            crate::warn_once!("Failed to apply foveation to swapchain: {result}");
+        }
+    }
+}
+
+impl Drop for Foveation {
+    fn drop(&mut self) {
+        unsafe { (self.fp.destroy_foveation_profile)(self.profile) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config() {
+        let config: FoveationConfig = serde_json::from_str(r#"{ "level": "high" }"#).unwrap();
+        assert_eq!(
+            config,
+            FoveationConfig {
+                level: FoveationLevel::High,
+                vertical_offset: 0.0,
+                dynamic: false,
+            }
+        );
+        assert!(serde_json::from_str::<FoveationConfig>("{}").is_err());
+    }
+}
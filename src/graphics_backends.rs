@@ -1,8 +1,13 @@
+mod dxvk;
 mod gl;
+mod gl_interop;
 mod vulkan;
 
+use crate::mirror::{MirrorEye, MirrorImage};
 use derive_more::{From, TryInto};
+pub use dxvk::InteropTexture;
 pub use gl::GlData;
+pub use gl_interop::GlInterop;
 use openvr as vr;
 use openxr as xr;
 pub use vulkan::VulkanData;
@@ -14,6 +19,18 @@ pub trait GraphicsBackend: Into<SupportedBackend> {
 
     fn to_nice_format(format: <Self::Api as xr::Graphics>::Format) -> Self::NiceFormat;
 
+    /// Formats to use instead of the given one if the runtime doesn't support it, best first.
+    /// These should hold the same contents without losing precision.
+    fn fallback_formats(
+        _format: <Self::Api as xr::Graphics>::Format,
+    ) -> Vec<<Self::Api as xr::Graphics>::Format> {
+        Vec::new()
+    }
+
+    /// Called before creating a session with the game's graphics, so the backend can switch to
+    /// a device the runtime can use if the game's device isn't one.
+    fn check_runtime_device(&mut self, _instance: &xr::Instance, _system_id: xr::SystemId) {}
+
     fn session_create_info(&self) -> <Self::Api as xr::Graphics>::SessionCreateInfo;
 
     fn get_texture(texture: &vr::Texture_t) -> Self::OpenVrTexture;
@@ -23,6 +40,7 @@ pub trait GraphicsBackend: Into<SupportedBackend> {
         texture: Self::OpenVrTexture,
         bounds: vr::VRTextureBounds_t,
         color_space: vr::EColorSpace,
+        submit_flags: vr::EVRSubmitFlags,
     ) -> xr::SwapchainCreateInfo<Self::Api>;
 
     fn store_swapchain_images(
@@ -31,6 +49,8 @@ pub trait GraphicsBackend: Into<SupportedBackend> {
         format: <Self::Api as xr::Graphics>::Format,
     );
 
+    /// `array_index` is the layer of an array texture picked by SubmitWithArrayIndex.
+    #[allow(clippy::too_many_arguments)]
     fn copy_texture_to_swapchain(
         &self,
         eye: vr::EVREye,
@@ -39,6 +59,7 @@ pub trait GraphicsBackend: Into<SupportedBackend> {
         bounds: vr::VRTextureBounds_t,
         image_index: usize,
         submit_flags: vr::EVRSubmitFlags,
+        array_index: Option<u32>,
     ) -> xr::Extent2Di;
 
     fn copy_overlay_to_swapchain(
@@ -47,6 +68,96 @@ pub trait GraphicsBackend: Into<SupportedBackend> {
         bounds: vr::VRTextureBounds_t,
         image_index: usize,
     ) -> xr::Extent2Di;
+
+    /// Swapchain info for a cube map with a face the size of the given texture, or None if this
+    /// backend can't copy textures to cube maps.
+    fn swapchain_info_for_cube(
+        &self,
+        _texture: Self::OpenVrTexture,
+        _color_space: vr::EColorSpace,
+    ) -> Option<xr::SwapchainCreateInfo<Self::Api>> {
+        None
+    }
+
+    /// Copies each texture to its face of the swapchain image, in the +X, -X, +Y, -Y, +Z, -Z
+    /// order of cube map layers.
+    fn copy_textures_to_cube(&mut self, _textures: [Self::OpenVrTexture; 6], _image_index: usize) {}
+
+    /// Swapchain info for a depth buffer submitted along with a texture, or None if this backend
+    /// can't pass it on.
+    fn swapchain_info_for_depth(
+        &self,
+        _depth: &vr::VRTextureDepthInfo_t,
+        _extent: xr::Extent2Di,
+    ) -> Option<xr::SwapchainCreateInfo<Self::Api>> {
+        None
+    }
+
+    fn store_depth_swapchain_images(
+        &mut self,
+        _images: Vec<<Self::Api as xr::Graphics>::SwapchainImage>,
+    ) {
+    }
+
+    fn copy_depth_to_swapchain(
+        &self,
+        _eye: vr::EVREye,
+        _depth: &vr::VRTextureDepthInfo_t,
+        _bounds: vr::VRTextureBounds_t,
+        _image_index: usize,
+    ) {
+    }
+
+    /// Copies the given eyes of a swapchain image side by side into an image for the mirror
+    /// window. The copy finishes in the background, so this returns the image from the previous
+    /// call, if there is one.
+    fn read_mirror_image(
+        &mut self,
+        _image_index: usize,
+        _eyes: &[MirrorEye],
+    ) -> Option<MirrorImage> {
+        None
+    }
+
+    /// Like [`Self::read_mirror_image`], but waits for the copy to finish and returns it.
+    fn read_eyes(&mut self, _image_index: usize, _eyes: &[MirrorEye]) -> Option<MirrorImage> {
+        None
+    }
+
+    /// When copying each eye to the given swapchain image last started and finished on the GPU.
+    /// None if the copies haven't finished yet, or if this backend can't measure them.
+    fn eye_copy_times(&self, _image_index: usize) -> Option<[GpuSpan; 2]> {
+        None
+    }
+
+    /// Whether frames can be ended on a thread other than the one the game submits from.
+    fn can_present_off_thread() -> bool {
+        true
+    }
+
+    /// Creates a backend using the same device/context as this one, for overlays whose contents
+    /// come from the CPU instead of a game texture.
+    fn new_overlay_backend(&self) -> Self;
+
+    fn swapchain_info_for_pixels(
+        &self,
+        extent: xr::Extent2Di,
+    ) -> xr::SwapchainCreateInfo<Self::Api>;
+
+    /// Uploads tightly packed, top-down sRGB RGBA8 pixels to the swapchain image.
+    fn copy_pixels_to_swapchain(
+        &mut self,
+        pixels: &[u8],
+        extent: xr::Extent2Di,
+        image_index: usize,
+    );
+}
+
+/// A span of GPU work, in milliseconds on the GPU's clock.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GpuSpan {
+    pub start_ms: f64,
+    pub end_ms: f64,
 }
 
 #[derive(macros::Backends, TryInto, From)]
@@ -0,0 +1,228 @@
+//! Access to the Vulkan images behind D3D11 textures created by DXVK, through its interop
+//! interfaces (see dxgi_interfaces.h in DXVK).
+
+use ash::vk::{self, Handle};
+use log::warn;
+use openvr as vr;
+use std::ffi::c_void;
+
+#[repr(C)]
+struct Guid {
+    data1: u32,
+    data2: u16,
+    data3: u16,
+    data4: [u8; 8],
+}
+
+/// 5546cf8c-77e7-4341-b05d-8d4d5000e77d
+const IID_IDXGIVK_INTEROP_SURFACE: Guid = Guid {
+    data1: 0x5546cf8c,
+    data2: 0x77e7,
+    data3: 0x4341,
+    data4: [0xb0, 0x5d, 0x8d, 0x4d, 0x50, 0x00, 0xe7, 0x7d],
+};
+
+type This = *mut c_void;
+
+#[repr(C)]
+struct IUnknownVtbl {
+    query_interface: unsafe extern "system" fn(This, *const Guid, *mut This) -> i32,
+    add_ref: unsafe extern "system" fn(This) -> u32,
+    release: unsafe extern "system" fn(This) -> u32,
+}
+
+#[repr(C)]
+struct InteropSurfaceVtbl {
+    base: IUnknownVtbl,
+    get_device: unsafe extern "system" fn(This, *mut This) -> i32,
+    get_vulkan_image_info: unsafe extern "system" fn(
+        This,
+        *mut vk::Image,
+        *mut vk::ImageLayout,
+        *mut vk::ImageCreateInfo,
+    ) -> i32,
+}
+
+#[repr(C)]
+struct InteropDeviceVtbl {
+    base: IUnknownVtbl,
+    get_vulkan_handles: unsafe extern "system" fn(
+        This,
+        *mut vk::Instance,
+        *mut vk::PhysicalDevice,
+        *mut vk::Device,
+    ),
+    get_submission_queue: unsafe extern "system" fn(This, *mut vk::Queue, *mut u32),
+    transition_surface_layout: unsafe extern "system" fn(
+        This,
+        This,
+        *const vk::ImageSubresourceRange,
+        vk::ImageLayout,
+        vk::ImageLayout,
+    ),
+    flush_rendering_commands: unsafe extern "system" fn(This),
+    lock_submission_queue: unsafe extern "system" fn(This),
+    release_submission_queue: unsafe extern "system" fn(This),
+}
+
+/// Calls a method of a COM object.
+macro_rules! com_call {
+    ($this:expr, $vtbl:ty, $method:ident $(, $arg:expr)*) => {{
+        let this: This = $this;
+        let vtbl = &**this.cast::<*const $vtbl>();
+        (vtbl.$method)(this $(, $arg)*)
+    }};
+}
+
+/// A D3D11 texture created by DXVK, along with the Vulkan image backing it.
+pub struct InteropTexture {
+    surface: This,
+    device: This,
+    layout: vk::ImageLayout,
+    subresources: vk::ImageSubresourceRange,
+    data: vr::VRVulkanTextureData_t,
+}
+
+impl InteropTexture {
+    /// Returns None if the texture wasn't created by DXVK.
+    pub fn new(texture: *mut c_void) -> Option<Self> {
+        if texture.is_null() {
+            return None;
+        }
+
+        let mut surface: This = std::ptr::null_mut();
+        // SAFETY: The handle of a DirectX texture is an ID3D11Texture2D, which is a COM object.
+        let hr = unsafe {
+            com_call!(
+                texture,
+                IUnknownVtbl,
+                query_interface,
+                &IID_IDXGIVK_INTEROP_SURFACE,
+                &mut surface
+            )
+        };
+        if hr < 0 || surface.is_null() {
+            warn!("Submitted D3D11 texture doesn't support DXVK interop ({hr:#x})");
+            return None;
+        }
+
+        let mut device: This = std::ptr::null_mut();
+        let mut image = vk::Image::null();
+        let mut layout = vk::ImageLayout::UNDEFINED;
+        let mut info = vk::ImageCreateInfo::default();
+        let (mut instance, mut physical_device, mut vk_device, mut queue, mut queue_family) = (
+            vk::Instance::null(),
+            vk::PhysicalDevice::null(),
+            vk::Device::null(),
+            vk::Queue::null(),
+            0,
+        );
+        unsafe {
+            let hr = com_call!(surface, InteropSurfaceVtbl, get_device, &mut device);
+            if hr < 0 || device.is_null() {
+                warn!("Couldn't get DXVK interop device ({hr:#x})");
+                com_call!(surface, IUnknownVtbl, release);
+                return None;
+            }
+            com_call!(
+                surface,
+                InteropSurfaceVtbl,
+                get_vulkan_image_info,
+                &mut image,
+                &mut layout,
+                &mut info
+            );
+            com_call!(
+                device,
+                InteropDeviceVtbl,
+                get_vulkan_handles,
+                &mut instance,
+                &mut physical_device,
+                &mut vk_device
+            );
+            com_call!(
+                device,
+                InteropDeviceVtbl,
+                get_submission_queue,
+                &mut queue,
+                &mut queue_family
+            );
+        }
+
+        Some(Self {
+            surface,
+            device,
+            layout,
+            subresources: vk::ImageSubresourceRange {
+                aspect_mask: vk::ImageAspectFlags::COLOR,
+                base_mip_level: 0,
+                level_count: info.mip_levels,
+                base_array_layer: 0,
+                layer_count: info.array_layers,
+            },
+            data: vr::VRVulkanTextureData_t {
+                m_nImage: image.as_raw(),
+                m_pDevice: vk_device.as_raw() as _,
+                m_pPhysicalDevice: physical_device.as_raw() as _,
+                m_pInstance: instance.as_raw() as _,
+                m_pQueue: queue.as_raw() as _,
+                m_nQueueFamilyIndex: queue_family,
+                m_nWidth: info.extent.width,
+                m_nHeight: info.extent.height,
+                m_nFormat: info.format.as_raw() as _,
+                m_nSampleCount: info.samples.as_raw(),
+            },
+        })
+    }
+
+    /// Calls `f` with a Vulkan texture referring to the image, while it's ready to be copied from
+    /// and DXVK isn't using its queue.
+    pub fn with_vulkan_texture<R>(
+        &self,
+        color_space: vr::EColorSpace,
+        f: impl FnOnce(&vr::Texture_t) -> R,
+    ) -> R {
+        let texture = vr::Texture_t {
+            handle: (&raw const self.data).cast_mut().cast(),
+            eType: vr::ETextureType::Vulkan,
+            eColorSpace: color_space,
+        };
+
+        unsafe {
+            com_call!(
+                self.device,
+                InteropDeviceVtbl,
+                transition_surface_layout,
+                self.surface,
+                &self.subresources,
+                self.layout,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL
+            );
+            com_call!(self.device, InteropDeviceVtbl, flush_rendering_commands);
+            com_call!(self.device, InteropDeviceVtbl, lock_submission_queue);
+        }
+        let ret = f(&texture);
+        unsafe {
+            com_call!(self.device, InteropDeviceVtbl, release_submission_queue);
+            com_call!(
+                self.device,
+                InteropDeviceVtbl,
+                transition_surface_layout,
+                self.surface,
+                &self.subresources,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                self.layout
+            );
+        }
+        ret
+    }
+}
+
+impl Drop for InteropTexture {
+    fn drop(&mut self) {
+        unsafe {
+            com_call!(self.device, IUnknownVtbl, release);
+            com_call!(self.surface, IUnknownVtbl, release);
+        }
+    }
+}
@@ -28,27 +28,46 @@ struct SessionCreateInfo(xr::opengl::SessionCreateInfo);
 unsafe impl Send for SessionCreateInfo {}
 unsafe impl Sync for SessionCreateInfo {}
 
-impl GlData {
-    pub(crate) fn new() -> Self {
-        let glx = Glx::load_with(|func| {
-            let func = unsafe { CString::from_vec_unchecked(func.as_bytes().to_vec()) };
-            GLX.get(&func)
+/// Loads GLX, along with the GL functions the first time around.
+fn load_glx() -> Glx {
+    let glx = Glx::load_with(|func| {
+        let func = unsafe { CString::from_vec_unchecked(func.as_bytes().to_vec()) };
+        GLX.get(&func)
+    });
+
+    static ONCE: Once = Once::new();
+    ONCE.call_once(|| {
+        gl::load_with(|f| {
+            let f = unsafe { CString::from_vec_unchecked(f.as_bytes().to_vec()) };
+            unsafe { glx.GetProcAddress(f.as_ptr().cast()) }.cast()
         });
 
-        static ONCE: Once = Once::new();
-        ONCE.call_once(|| {
-            gl::load_with(|f| {
-                let f = unsafe { CString::from_vec_unchecked(f.as_bytes().to_vec()) };
-                unsafe { glx.GetProcAddress(f.as_ptr().cast()) }.cast()
-            });
-
-            if log::log_enabled!(log::Level::Debug) {
-                unsafe {
-                    gl::DebugMessageCallback(Some(debug_message), std::ptr::null());
-                    gl::Enable(gl::DEBUG_OUTPUT);
-                }
+        if log::log_enabled!(log::Level::Debug) {
+            unsafe {
+                gl::DebugMessageCallback(Some(debug_message), std::ptr::null());
+                gl::Enable(gl::DEBUG_OUTPUT);
             }
-        });
+        }
+    });
+    glx
+}
+
+/// Looks up a GL function the gl crate doesn't know about, such as extension functions.
+/// Also makes sure the functions it does know about are loaded.
+pub(super) fn get_proc_address(name: &CStr) -> *const c_void {
+    let glx = load_glx();
+    unsafe { glx.GetProcAddress(name.as_ptr().cast()) }.cast()
+}
+
+/// The GLX context current on this thread as an address, or 0 if there is none.
+pub(super) fn current_context() -> usize {
+    let glx = load_glx();
+    unsafe { glx.GetCurrentContext() as usize }
+}
+
+impl GlData {
+    pub(crate) fn new() -> Self {
+        let glx = load_glx();
 
         // Grab the session info on creation - this makes us resilient against session restarts,
         // which could result in us trying to grab the context from a different thread
@@ -128,6 +147,11 @@ impl GraphicsBackend for GlData {
         format
     }
 
+    fn can_present_off_thread() -> bool {
+        // The game's context is only current on its own thread.
+        false
+    }
+
     fn session_create_info(&self) -> <Self::Api as openxr::Graphics>::SessionCreateInfo {
         // SAFETY: SessionCreateInfo should be Copy anyway but doesn't work right
         // https://github.com/Ralith/openxrs/issues/183
@@ -155,27 +179,24 @@ impl GraphicsBackend for GlData {
         texture: Self::OpenVrTexture,
         bounds: vr::VRTextureBounds_t,
         color_space: vr::EColorSpace,
+        submit_flags: vr::EVRSubmitFlags,
     ) -> xr::SwapchainCreateInfo<Self::Api> {
-        let mut fmt = 0;
-        unsafe {
-            gl::BindTexture(gl::TEXTURE_2D, texture);
-            gl::GetTexLevelParameteriv(gl::TEXTURE_2D, 0, gl::TEXTURE_INTERNAL_FORMAT, &mut fmt);
-        }
-        let xr::Rect2Di { extent, .. } = texture_rect_from_bounds(texture, bounds);
+        let source = SourceInfo::new(texture, submit_flags);
+        let xr::Rect2Di { extent, .. } = source.rect_from_bounds(bounds);
 
         let fmt = match color_space {
-            vr::EColorSpace::Linear => fmt,
-            vr::EColorSpace::Gamma | vr::EColorSpace::Auto => match fmt as gl::types::GLenum {
-                gl::RGBA8 => gl::SRGB8_ALPHA8 as gl::types::GLint,
-                gl::RGB8 => gl::SRGB8 as gl::types::GLint,
-                _ => fmt,
+            vr::EColorSpace::Linear => source.format,
+            vr::EColorSpace::Gamma | vr::EColorSpace::Auto => match source.format {
+                gl::RGBA8 => gl::SRGB8_ALPHA8,
+                gl::RGB8 => gl::SRGB8,
+                fmt => fmt,
             },
         };
 
         xr::SwapchainCreateInfo {
             create_flags: xr::SwapchainCreateFlags::EMPTY,
             usage_flags: xr::SwapchainUsageFlags::TRANSFER_DST,
-            format: fmt as u32,
+            format: fmt,
             sample_count: 1,
             width: extent.width as u32,
             height: extent.height as u32,
@@ -192,28 +213,30 @@ impl GraphicsBackend for GlData {
         _color_space: vr::EColorSpace,
         bounds: vr::VRTextureBounds_t,
         image_index: usize,
-        _submit_flags: vr::EVRSubmitFlags,
+        submit_flags: vr::EVRSubmitFlags,
+        array_index: Option<u32>,
     ) -> xr::Extent2Di {
         let swapchain_texture = self.images[image_index];
 
-        let xr::Rect2Di { extent, offset } = texture_rect_from_bounds(texture, bounds);
-
-        let mut fmt = 0;
-        unsafe {
-            gl::BindTexture(gl::TEXTURE_2D, texture);
-            gl::GetTexLevelParameteriv(gl::TEXTURE_2D, 0, gl::TEXTURE_INTERNAL_FORMAT, &mut fmt);
-            gl::BindTexture(gl::TEXTURE_2D, 0);
-        }
+        let source = SourceInfo::new(texture, submit_flags);
+        let xr::Rect2Di { extent, offset } = source.rect_from_bounds(bounds);
+        // Array textures have the image of each eye in their matching layer, unless the game
+        // picked one.
+        let layer = match source.kind {
+            SourceKind::ArrayTexture => array_index.map_or(eye as i32, |index| index as i32),
+            SourceKind::Texture | SourceKind::Renderbuffer => 0,
+        };
 
-        if self.format == fmt as u32 {
+        // Renderbuffers are usually multisampled, which requires resolving them with a blit.
+        if self.format == source.format && source.kind != SourceKind::Renderbuffer {
             unsafe {
                 gl::CopyImageSubData(
                     texture,
-                    gl::TEXTURE_2D,
+                    source.kind.target(),
                     0, // level
                     offset.x,
                     offset.y,
-                    0, // z
+                    layer,
                     swapchain_texture,
                     gl::TEXTURE_2D_ARRAY,
                     0, // x
@@ -228,13 +251,28 @@ impl GraphicsBackend for GlData {
         } else {
             unsafe {
                 gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.read_fbo);
-                gl::FramebufferTexture2D(
-                    gl::READ_FRAMEBUFFER,
-                    gl::COLOR_ATTACHMENT0,
-                    gl::TEXTURE_2D,
-                    texture,
-                    0,
-                );
+                match source.kind {
+                    SourceKind::Texture => gl::FramebufferTexture2D(
+                        gl::READ_FRAMEBUFFER,
+                        gl::COLOR_ATTACHMENT0,
+                        gl::TEXTURE_2D,
+                        texture,
+                        0,
+                    ),
+                    SourceKind::ArrayTexture => gl::FramebufferTextureLayer(
+                        gl::READ_FRAMEBUFFER,
+                        gl::COLOR_ATTACHMENT0,
+                        texture,
+                        0,
+                        layer,
+                    ),
+                    SourceKind::Renderbuffer => gl::FramebufferRenderbuffer(
+                        gl::READ_FRAMEBUFFER,
+                        gl::COLOR_ATTACHMENT0,
+                        gl::RENDERBUFFER,
+                        texture,
+                    ),
+                }
                 gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, self.draw_fbo);
                 gl::FramebufferTextureLayer(
                     gl::DRAW_FRAMEBUFFER,
@@ -280,33 +318,146 @@ impl GraphicsBackend for GlData {
             vr::EVRSubmitFlags::Default,
         )
     }
+
+    fn new_overlay_backend(&self) -> Self {
+        Self::new()
+    }
+
+    fn swapchain_info_for_pixels(
+        &self,
+        extent: xr::Extent2Di,
+    ) -> xr::SwapchainCreateInfo<Self::Api> {
+        xr::SwapchainCreateInfo {
+            create_flags: xr::SwapchainCreateFlags::EMPTY,
+            usage_flags: xr::SwapchainUsageFlags::TRANSFER_DST,
+            format: gl::SRGB8_ALPHA8,
+            sample_count: 1,
+            width: extent.width as u32,
+            height: extent.height as u32,
+            face_count: 1,
+            array_size: 1,
+            mip_count: 1,
+        }
+    }
+
+    fn copy_pixels_to_swapchain(
+        &mut self,
+        pixels: &[u8],
+        extent: xr::Extent2Di,
+        image_index: usize,
+    ) {
+        let row_len = extent.width as usize * 4;
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.images[image_index]);
+            // GL textures start at the bottom row, so the rows need to be flipped.
+            for (row, data) in pixels.chunks_exact(row_len).enumerate() {
+                gl::TexSubImage2D(
+                    gl::TEXTURE_2D,
+                    0,
+                    0,
+                    extent.height - 1 - row as i32,
+                    extent.width,
+                    1,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    data.as_ptr().cast(),
+                );
+            }
+        }
+    }
 }
 
-fn texture_rect_from_bounds(
-    texture: glx::types::GLuint,
-    bounds: vr::VRTextureBounds_t,
-) -> xr::Rect2Di {
-    let [mut height, mut width] = Default::default();
-    unsafe {
-        gl::BindTexture(gl::TEXTURE_2D, texture);
-        gl::GetTexLevelParameteriv(gl::TEXTURE_2D, 0, gl::TEXTURE_WIDTH, &mut width);
-        gl::GetTexLevelParameteriv(gl::TEXTURE_2D, 0, gl::TEXTURE_HEIGHT, &mut height);
-        gl::BindTexture(gl::TEXTURE_2D, 0);
+/// What the handle of a submitted texture refers to, according to the submit flags.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SourceKind {
+    Texture,
+    ArrayTexture,
+    Renderbuffer,
+}
+
+impl SourceKind {
+    fn new(submit_flags: vr::EVRSubmitFlags) -> Self {
+        if (submit_flags & vr::EVRSubmitFlags::GlRenderBuffer).0 > 0 {
+            Self::Renderbuffer
+        } else if (submit_flags & vr::EVRSubmitFlags::GlArrayTexture).0 > 0 {
+            Self::ArrayTexture
+        } else {
+            Self::Texture
+        }
     }
-    let width_min = bounds.uMin * width as f32;
-    let width_max = bounds.uMax * width as f32;
-    let height_min = bounds.vMin * height as f32;
-    let height_max = bounds.vMax * height as f32;
-
-    xr::Rect2Di {
-        extent: xr::Extent2Di {
-            width: (width_max - width_min).abs() as i32,
-            height: (height_max - height_min).abs() as i32,
-        },
-        offset: xr::Offset2Di {
-            x: width_min.min(width_max) as i32,
-            y: height_min.min(height_max) as i32,
-        },
+
+    fn target(self) -> gl::types::GLenum {
+        match self {
+            Self::Texture => gl::TEXTURE_2D,
+            Self::ArrayTexture => gl::TEXTURE_2D_ARRAY,
+            Self::Renderbuffer => gl::RENDERBUFFER,
+        }
+    }
+}
+
+pub(super) struct SourceInfo {
+    kind: SourceKind,
+    pub format: u32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl SourceInfo {
+    pub fn new(texture: gl::types::GLuint, submit_flags: vr::EVRSubmitFlags) -> Self {
+        let kind = SourceKind::new(submit_flags);
+        let [mut format, mut width, mut height] = Default::default();
+        unsafe {
+            match kind {
+                SourceKind::Renderbuffer => {
+                    gl::BindRenderbuffer(gl::RENDERBUFFER, texture);
+                    for (param, value) in [
+                        (gl::RENDERBUFFER_INTERNAL_FORMAT, &mut format),
+                        (gl::RENDERBUFFER_WIDTH, &mut width),
+                        (gl::RENDERBUFFER_HEIGHT, &mut height),
+                    ] {
+                        gl::GetRenderbufferParameteriv(gl::RENDERBUFFER, param, value);
+                    }
+                    gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+                }
+                SourceKind::Texture | SourceKind::ArrayTexture => {
+                    let target = kind.target();
+                    gl::BindTexture(target, texture);
+                    for (param, value) in [
+                        (gl::TEXTURE_INTERNAL_FORMAT, &mut format),
+                        (gl::TEXTURE_WIDTH, &mut width),
+                        (gl::TEXTURE_HEIGHT, &mut height),
+                    ] {
+                        gl::GetTexLevelParameteriv(target, 0, param, value);
+                    }
+                    gl::BindTexture(target, 0);
+                }
+            }
+        }
+
+        Self {
+            kind,
+            format: format as u32,
+            width,
+            height,
+        }
+    }
+
+    fn rect_from_bounds(&self, bounds: vr::VRTextureBounds_t) -> xr::Rect2Di {
+        let width_min = (bounds.uMin * self.width as f32).round();
+        let width_max = (bounds.uMax * self.width as f32).round();
+        let height_min = (bounds.vMin * self.height as f32).round();
+        let height_max = (bounds.vMax * self.height as f32).round();
+
+        xr::Rect2Di {
+            extent: xr::Extent2Di {
+                width: (width_max - width_min).abs() as i32,
+                height: (height_max - height_min).abs() as i32,
+            },
+            offset: xr::Offset2Di {
+                x: width_min.min(width_max) as i32,
+                y: height_min.min(height_max) as i32,
+            },
+        }
     }
 }
 
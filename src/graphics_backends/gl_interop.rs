@@ -0,0 +1,443 @@
+//! Copies OpenGL textures into Vulkan images, so overlays from GL apps can be shown in a Vulkan
+//! session. The image's memory is exported from Vulkan and imported into GL through
+//! GL_EXT_memory_object_fd, where the app's texture is copied into it.
+
+use super::gl::{current_context, get_proc_address, SourceInfo};
+use super::vulkan::find_memory_type;
+use super::VulkanData;
+use ash::vk::{self, Handle};
+use gl::types::{GLenum, GLint, GLsizei, GLuint, GLuint64};
+use log::warn;
+use openvr as vr;
+use std::ffi::{c_void, CStr};
+use std::mem::transmute;
+use std::sync::{LazyLock, Mutex};
+
+const GL_TEXTURE_TILING_EXT: GLenum = 0x9580;
+const GL_OPTIMAL_TILING_EXT: GLenum = 0x9584;
+const GL_HANDLE_TYPE_OPAQUE_FD_EXT: GLenum = 0x9586;
+
+/// The GL_EXT_memory_object(_fd) functions, which the gl crate doesn't have.
+struct MemoryObjectFns {
+    create: unsafe extern "system" fn(GLsizei, *mut GLuint),
+    delete: unsafe extern "system" fn(GLsizei, *const GLuint),
+    import_fd: unsafe extern "system" fn(GLuint, GLuint64, GLenum, GLint),
+    tex_storage_2d:
+        unsafe extern "system" fn(GLenum, GLsizei, GLenum, GLsizei, GLsizei, GLuint, GLuint64),
+}
+
+impl MemoryObjectFns {
+    /// Must be called with the app's context current.
+    fn load() -> Option<Self> {
+        let load = |name: &CStr| Some(get_proc_address(name)).filter(|f| !f.is_null());
+        let create = load(c"glCreateMemoryObjectsEXT")?;
+        // GLX hands out functions whether or not the driver implements them.
+        let supported = unsafe {
+            let mut count = 0;
+            gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+            (0..count as GLuint).any(|idx| {
+                let ext = gl::GetStringi(gl::EXTENSIONS, idx);
+                !ext.is_null() && CStr::from_ptr(ext.cast()) == c"GL_EXT_memory_object_fd"
+            })
+        };
+        if !supported {
+            return None;
+        }
+
+        unsafe {
+            Some(Self {
+                create: transmute::<*const c_void, _>(create),
+                delete: transmute::<*const c_void, _>(load(c"glDeleteMemoryObjectsEXT")?),
+                import_fd: transmute::<*const c_void, _>(load(c"glImportMemoryFdEXT")?),
+                tex_storage_2d: transmute::<*const c_void, _>(load(c"glTexStorageMem2DEXT")?),
+            })
+        }
+    }
+}
+
+static MEMORY_OBJECT: LazyLock<Option<MemoryObjectFns>> = LazyLock::new(|| {
+    let fns = MemoryObjectFns::load();
+    if fns.is_none() {
+        warn!("GL_EXT_memory_object_fd is unsupported, OpenGL overlays can't be shown");
+    }
+    fns
+});
+
+/// GL objects that were left behind without their context current. They're deleted by the next
+/// copy made with that context current.
+static ORPHANED: Mutex<Vec<GlObjects>> = Mutex::new(Vec::new());
+
+struct GlObjects {
+    /// The context the objects were created in.
+    context: usize,
+    memory_object: GLuint,
+    texture: GLuint,
+}
+
+impl GlObjects {
+    /// Must be called with `context` current.
+    fn delete(&self, fns: &MemoryObjectFns) {
+        unsafe {
+            gl::DeleteTextures(1, &self.texture);
+            (fns.delete)(1, &self.memory_object);
+        }
+    }
+}
+
+/// A Vulkan image along with the GL texture sharing its memory.
+struct SharedImage {
+    image: vk::Image,
+    memory: vk::DeviceMemory,
+    gl: GlObjects,
+    gl_format: GLenum,
+    format: vk::Format,
+    width: u32,
+    height: u32,
+}
+
+/// Copies GL textures into a Vulkan image on the overlay compositor's device. The image is
+/// recreated whenever the textures change size or format.
+pub struct GlInterop {
+    instance: ash::Instance,
+    physical_device: vk::PhysicalDevice,
+    device: ash::Device,
+    queue: vk::Queue,
+    queue_family_index: u32,
+    image: Option<SharedImage>,
+}
+
+impl GlInterop {
+    pub fn new(vk: &VulkanData) -> Self {
+        Self {
+            instance: vk.instance.clone(),
+            physical_device: vk.physical_device,
+            device: vk.device.clone(),
+            queue: vk.queue,
+            queue_family_index: vk.queue_family_index,
+            image: None,
+        }
+    }
+
+    /// Copies a GL texture into the Vulkan image, then calls `f` with a Vulkan texture referring
+    /// to it. Must be called with the app's context current. Returns None if the texture can't be
+    /// shared.
+    pub fn copy<R>(
+        &mut self,
+        texture: GLuint,
+        color_space: vr::EColorSpace,
+        f: impl FnOnce(&vr::Texture_t) -> R,
+    ) -> Option<R> {
+        let fns = MEMORY_OBJECT.as_ref()?;
+        let context = current_context();
+        ORPHANED.lock().unwrap().retain(|objects| {
+            let current = objects.context == context;
+            if current {
+                objects.delete(fns);
+            }
+            !current
+        });
+
+        let info = SourceInfo::new(texture, vr::EVRSubmitFlags::Default);
+        let (width, height) = (info.width as u32, info.height as u32);
+        if self.image.as_ref().is_none_or(|image| {
+            (image.gl_format, image.width, image.height) != (info.format, width, height)
+        }) {
+            if let Some(image) = self.image.take() {
+                self.destroy(fns, image);
+            }
+            self.image = self.create(fns, info.format, width, height);
+        }
+        let image = self.image.as_ref()?;
+
+        unsafe {
+            gl::CopyImageSubData(
+                texture,
+                gl::TEXTURE_2D,
+                0,
+                0,
+                0,
+                0,
+                image.gl.texture,
+                gl::TEXTURE_2D,
+                0,
+                0,
+                0,
+                0,
+                width as i32,
+                height as i32,
+                1,
+            );
+            // Vulkan reads the image right away, and there are no semaphores to wait on.
+            gl::Finish();
+        }
+
+        let data = vr::VRVulkanTextureData_t {
+            m_nImage: image.image.as_raw(),
+            m_pDevice: self.device.handle().as_raw() as _,
+            m_pPhysicalDevice: self.physical_device.as_raw() as _,
+            m_pInstance: self.instance.handle().as_raw() as _,
+            m_pQueue: self.queue.as_raw() as _,
+            m_nQueueFamilyIndex: self.queue_family_index,
+            m_nWidth: width,
+            m_nHeight: height,
+            m_nFormat: image.format.as_raw() as _,
+            m_nSampleCount: 1,
+        };
+        let texture = vr::Texture_t {
+            handle: (&raw const data).cast_mut().cast(),
+            eType: vr::ETextureType::Vulkan,
+            eColorSpace: color_space,
+        };
+        Some(f(&texture))
+    }
+
+    fn create(
+        &self,
+        fns: &MemoryObjectFns,
+        gl_format: GLenum,
+        width: u32,
+        height: u32,
+    ) -> Option<SharedImage> {
+        let format = match gl_format {
+            gl::RGBA8 => vk::Format::R8G8B8A8_UNORM,
+            gl::SRGB8_ALPHA8 => vk::Format::R8G8B8A8_SRGB,
+            _ => {
+                crate::warn_once!("Can't share OpenGL overlay texture with format {gl_format:#x}");
+                return None;
+            }
+        };
+        if width == 0 || height == 0 {
+            return None;
+        }
+        let get_memory_fd = unsafe {
+            self.instance
+                .get_device_proc_addr(self.device.handle(), c"vkGetMemoryFdKHR".as_ptr())
+        };
+        if get_memory_fd.is_none() {
+            crate::warn_once!(
+                "VK_KHR_external_memory_fd isn't enabled, OpenGL overlays can't be shown"
+            );
+            return None;
+        }
+
+        let mut image = SharedImage {
+            image: vk::Image::null(),
+            memory: vk::DeviceMemory::null(),
+            gl: GlObjects {
+                context: current_context(),
+                memory_object: 0,
+                texture: 0,
+            },
+            gl_format,
+            format,
+            width,
+            height,
+        };
+        match self.share(fns, &mut image) {
+            Some(()) => Some(image),
+            None => {
+                self.destroy(fns, image);
+                None
+            }
+        }
+    }
+
+    /// Creates the Vulkan image and its GL texture. Whatever was created is left in `image`, to
+    /// be destroyed on failure.
+    fn share(&self, fns: &MemoryObjectFns, image: &mut SharedImage) -> Option<()> {
+        let handle_type = vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD;
+        let (fd, size) = unsafe {
+            image.image = self
+                .device
+                .create_image(
+                    &vk::ImageCreateInfo::default()
+                        .push_next(
+                            &mut vk::ExternalMemoryImageCreateInfo::default()
+                                .handle_types(handle_type),
+                        )
+                        .image_type(vk::ImageType::TYPE_2D)
+                        .format(image.format)
+                        .extent(vk::Extent3D {
+                            width: image.width,
+                            height: image.height,
+                            depth: 1,
+                        })
+                        .mip_levels(1)
+                        .array_layers(1)
+                        .samples(vk::SampleCountFlags::TYPE_1)
+                        .tiling(vk::ImageTiling::OPTIMAL)
+                        .usage(
+                            vk::ImageUsageFlags::SAMPLED
+                                | vk::ImageUsageFlags::COLOR_ATTACHMENT
+                                | vk::ImageUsageFlags::TRANSFER_SRC
+                                | vk::ImageUsageFlags::TRANSFER_DST,
+                        )
+                        .initial_layout(vk::ImageLayout::UNDEFINED),
+                    None,
+                )
+                .inspect_err(|e| warn!("Failed to create shared image: {e}"))
+                .ok()?;
+
+            let requirements = self.device.get_image_memory_requirements(image.image);
+            let memory_type = find_memory_type(
+                &self.instance,
+                self.physical_device,
+                requirements,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            )?;
+            image.memory = self
+                .device
+                .allocate_memory(
+                    &vk::MemoryAllocateInfo::default()
+                        .allocation_size(requirements.size)
+                        .memory_type_index(memory_type)
+                        .push_next(
+                            &mut vk::ExportMemoryAllocateInfo::default().handle_types(handle_type),
+                        ),
+                    None,
+                )
+                .inspect_err(|e| warn!("Failed to allocate shared memory: {e}"))
+                .ok()?;
+            self.device
+                .bind_image_memory(image.image, image.memory, 0)
+                .inspect_err(|e| warn!("Failed to bind shared memory: {e}"))
+                .ok()?;
+            self.transition_for_copies(image.image)?;
+
+            let fd = ash::khr::external_memory_fd::Device::new(&self.instance, &self.device)
+                .get_memory_fd(
+                    &vk::MemoryGetFdInfoKHR::default()
+                        .memory(image.memory)
+                        .handle_type(handle_type),
+                )
+                .inspect_err(|e| warn!("Failed to export shared memory: {e}"))
+                .ok()?;
+            (fd, requirements.size)
+        };
+
+        unsafe {
+            // Don't blame the app's errors on the import.
+            while gl::GetError() != gl::NO_ERROR {}
+
+            (fns.create)(1, &mut image.gl.memory_object);
+            // The memory object owns the fd from here on.
+            (fns.import_fd)(
+                image.gl.memory_object,
+                size,
+                GL_HANDLE_TYPE_OPAQUE_FD_EXT,
+                fd,
+            );
+            gl::GenTextures(1, &mut image.gl.texture);
+            gl::BindTexture(gl::TEXTURE_2D, image.gl.texture);
+            gl::TexParameteri(
+                gl::TEXTURE_2D,
+                GL_TEXTURE_TILING_EXT,
+                GL_OPTIMAL_TILING_EXT as GLint,
+            );
+            (fns.tex_storage_2d)(
+                gl::TEXTURE_2D,
+                1,
+                image.gl_format,
+                image.width as GLsizei,
+                image.height as GLsizei,
+                image.gl.memory_object,
+                0,
+            );
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+
+            match gl::GetError() {
+                gl::NO_ERROR => Some(()),
+                e => {
+                    warn!("Failed to import shared memory into OpenGL ({e:#x})");
+                    None
+                }
+            }
+        }
+    }
+
+    /// Moves the image to the layout the overlay copy expects, where it stays.
+    fn transition_for_copies(&self, image: vk::Image) -> Option<()> {
+        unsafe {
+            let pool = self
+                .device
+                .create_command_pool(
+                    &vk::CommandPoolCreateInfo::default()
+                        .flags(vk::CommandPoolCreateFlags::TRANSIENT)
+                        .queue_family_index(self.queue_family_index),
+                    None,
+                )
+                .inspect_err(|e| warn!("Failed to create command pool: {e}"))
+                .ok()?;
+            let result = (|| {
+                let cmd = self.device.allocate_command_buffers(
+                    &vk::CommandBufferAllocateInfo::default()
+                        .command_pool(pool)
+                        .level(vk::CommandBufferLevel::PRIMARY)
+                        .command_buffer_count(1),
+                )?[0];
+                self.device.begin_command_buffer(
+                    cmd,
+                    &vk::CommandBufferBeginInfo::default()
+                        .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT),
+                )?;
+                self.device.cmd_pipeline_barrier(
+                    cmd,
+                    vk::PipelineStageFlags::TOP_OF_PIPE,
+                    vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &[vk::ImageMemoryBarrier::default()
+                        .image(image)
+                        .old_layout(vk::ImageLayout::UNDEFINED)
+                        .new_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+                        .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                        .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                        .subresource_range(vk::ImageSubresourceRange {
+                            aspect_mask: vk::ImageAspectFlags::COLOR,
+                            base_mip_level: 0,
+                            level_count: 1,
+                            base_array_layer: 0,
+                            layer_count: 1,
+                        })],
+                );
+                self.device.end_command_buffer(cmd)?;
+                self.device.queue_submit(
+                    self.queue,
+                    &[vk::SubmitInfo::default().command_buffers(&[cmd])],
+                    vk::Fence::null(),
+                )?;
+                self.device.queue_wait_idle(self.queue)
+            })();
+            self.device.destroy_command_pool(pool, None);
+            result
+                .inspect_err(|e| warn!("Failed to transition shared image: {e}"))
+                .ok()
+        }
+    }
+
+    /// The GL objects can only be deleted with the context they were created in current,
+    /// otherwise they're left for the next copy made with it.
+    fn destroy(&self, fns: &MemoryObjectFns, image: SharedImage) {
+        unsafe {
+            // The overlay copy may still be reading from it.
+            let _ = self.device.queue_wait_idle(self.queue);
+            self.device.destroy_image(image.image, None);
+            self.device.free_memory(image.memory, None);
+        }
+        if image.gl.context == current_context() {
+            image.gl.delete(fns);
+        } else {
+            warn!("OpenGL context isn't current, deleting overlay texture later");
+            ORPHANED.lock().unwrap().push(image.gl);
+        }
+    }
+}
+
+impl Drop for GlInterop {
+    fn drop(&mut self) {
+        if let (Some(image), Some(fns)) = (self.image.take(), MEMORY_OBJECT.as_ref()) {
+            self.destroy(fns, image);
+        }
+    }
+}
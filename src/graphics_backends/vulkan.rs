@@ -1,11 +1,13 @@
-use super::GraphicsBackend;
+use super::{GpuSpan, GraphicsBackend};
+use crate::config::Config;
+use crate::mirror::{MirrorEye, MirrorImage};
 use ash::vk::{self, Handle};
-use log::warn;
+use log::{info, warn};
 use openvr as vr;
 use openxr as xr;
 use std::collections::HashSet;
 use std::ffi::{c_char, CString};
-use std::sync::{LazyLock, Mutex};
+use std::sync::{LazyLock, Mutex, Once, OnceLock};
 
 struct RealSessionData {
     images: Vec<vk::Image>,
@@ -13,6 +15,221 @@ struct RealSessionData {
     pool: vk::CommandPool,
     bufs: Vec<vk::CommandBuffer>,
     overlay_pipeline: Option<PipelineData>,
+    timestamps: Option<TimestampData>,
+    /// Created on the first eye copy, so overlay swapchains don't get one.
+    sharpen: OnceLock<Option<SharpenData>>,
+}
+
+/// Timestamp queries taken before and after copying each eye, four per swapchain image.
+struct TimestampData {
+    pool: vk::QueryPool,
+    /// Nanoseconds per tick.
+    period: f64,
+    valid_mask: u64,
+}
+
+impl TimestampData {
+    fn query(image_index: usize, eye: vr::EVREye) -> u32 {
+        (4 * image_index + 2 * eye as usize) as u32
+    }
+}
+
+struct DepthSwapchainData {
+    images: Vec<vk::Image>,
+    pool: vk::CommandPool,
+    bufs: Vec<vk::CommandBuffer>,
+}
+
+/// For reading back eyes on the CPU: the eyes are blitted to an sRGB image (converting the format),
+/// which is then copied to a buffer the CPU can read.
+struct ReadbackData {
+    extent: vk::Extent2D,
+    image: vk::Image,
+    image_memory: vk::DeviceMemory,
+    buffer: vk::Buffer,
+    buffer_memory: vk::DeviceMemory,
+    pool: vk::CommandPool,
+    buf: vk::CommandBuffer,
+    fence: vk::Fence,
+    /// Whether a copy was submitted that hasn't been read back yet.
+    pending: bool,
+}
+
+impl ReadbackData {
+    fn size(&self) -> usize {
+        self.extent.width as usize * self.extent.height as usize * 4
+    }
+
+    fn read_back(&mut self, device: &ash::Device) -> Option<MirrorImage> {
+        if !std::mem::take(&mut self.pending) {
+            return None;
+        }
+
+        unsafe {
+            device
+                .wait_for_fences(&[self.fence], true, u64::MAX)
+                .unwrap();
+            device.reset_fences(&[self.fence]).unwrap();
+            let ptr = device
+                .map_memory(
+                    self.buffer_memory,
+                    0,
+                    self.size() as u64,
+                    vk::MemoryMapFlags::empty(),
+                )
+                .unwrap();
+            let pixels = std::slice::from_raw_parts(ptr.cast::<u8>(), self.size()).to_vec();
+            device.unmap_memory(self.buffer_memory);
+
+            Some(MirrorImage {
+                width: self.extent.width,
+                height: self.extent.height,
+                pixels,
+            })
+        }
+    }
+
+    fn destroy(self, device: &ash::Device) {
+        unsafe {
+            if self.pending {
+                device
+                    .wait_for_fences(&[self.fence], true, u64::MAX)
+                    .unwrap();
+            }
+            device.destroy_fence(self.fence, None);
+            device.destroy_command_pool(self.pool, None);
+            device.destroy_buffer(self.buffer, None);
+            device.free_memory(self.buffer_memory, None);
+            device.destroy_image(self.image, None);
+            device.free_memory(self.image_memory, None);
+        }
+    }
+}
+
+/// The game's own device, if the runtime can't use it: either it's on another GPU than the one the
+/// runtime renders on, or the game's queue can't do graphics work. Eyes are then read back on the
+/// game's device and uploaded to the swapchain on a device created for the runtime.
+struct GameDevice {
+    device: ash::Device,
+    physical_device: vk::PhysicalDevice,
+    queue: vk::Queue,
+    pool: vk::CommandPool,
+    buf: vk::CommandBuffer,
+    fence: vk::Fence,
+    /// Recreated when the submitted eye changes size or format.
+    eyes: [Option<BridgedEye>; 2],
+}
+
+/// Where an eye is copied to on its way from the game's device to the runtime's.
+struct BridgedEye {
+    extent: vk::Extent3D,
+    format: vk::Format,
+    size: u64,
+    /// On the game's device.
+    readback: vk::Buffer,
+    readback_memory: vk::DeviceMemory,
+    /// On the runtime's device.
+    upload: vk::Buffer,
+    upload_memory: vk::DeviceMemory,
+    /// The uploaded eye in the game's format, copied to the swapchain like a game texture would be.
+    image: vk::Image,
+    image_memory: vk::DeviceMemory,
+    /// Signaled once the runtime's device is done with the upload.
+    fence: vk::Fence,
+}
+
+impl BridgedEye {
+    fn destroy(self, game_device: &ash::Device, device: &ash::Device) {
+        unsafe {
+            device
+                .wait_for_fences(&[self.fence], true, u64::MAX)
+                .unwrap();
+            device.destroy_fence(self.fence, None);
+            device.destroy_image(self.image, None);
+            device.free_memory(self.image_memory, None);
+            device.destroy_buffer(self.upload, None);
+            device.free_memory(self.upload_memory, None);
+            game_device.destroy_buffer(self.readback, None);
+            game_device.free_memory(self.readback_memory, None);
+        }
+    }
+}
+
+impl GameDevice {
+    fn destroy(self, device: &ash::Device) {
+        unsafe {
+            self.device.queue_wait_idle(self.queue).unwrap();
+        }
+        for eye in self.eyes.into_iter().flatten() {
+            eye.destroy(&self.device, device);
+        }
+        unsafe {
+            self.device.destroy_fence(self.fence, None);
+            self.device.destroy_command_pool(self.pool, None);
+        }
+    }
+}
+
+/// Contrast adaptive sharpening of the eyes: a compute pass reads the game's image into an
+/// intermediate image, which is blitted to the swapchain instead of copying the game's image.
+struct SharpenData {
+    strength: f32,
+    pipeline: vk::Pipeline,
+    layout: vk::PipelineLayout,
+    set_layout: vk::DescriptorSetLayout,
+    pool: vk::DescriptorPool,
+    sampler: vk::Sampler,
+    /// One per eye command buffer, as a set can't be updated while a submitted copy uses it.
+    sets: Vec<vk::DescriptorSet>,
+    /// The view of the game's image each eye command buffer last read, destroyed once the command
+    /// buffer is recorded again.
+    game_views: Mutex<Vec<vk::ImageView>>,
+    /// Recreated when the eye changes size.
+    targets: Mutex<[Option<SharpenTarget>; 2]>,
+}
+
+struct SharpenTarget {
+    extent: vk::Extent3D,
+    image: vk::Image,
+    memory: vk::DeviceMemory,
+    view: vk::ImageView,
+}
+
+impl SharpenTarget {
+    fn destroy(&self, device: &ash::Device) {
+        unsafe {
+            device.destroy_image_view(self.view, None);
+            device.destroy_image(self.image, None);
+            device.free_memory(self.memory, None);
+        }
+    }
+}
+
+impl SharpenData {
+    fn destroy(&self, device: &ash::Device) {
+        for target in self.targets.lock().unwrap().iter().flatten() {
+            target.destroy(device);
+        }
+        unsafe {
+            for view in self.game_views.lock().unwrap().iter() {
+                device.destroy_image_view(*view, None);
+            }
+            device.destroy_pipeline(self.pipeline, None);
+            device.destroy_pipeline_layout(self.layout, None);
+            device.destroy_descriptor_pool(self.pool, None);
+            device.destroy_descriptor_set_layout(self.set_layout, None);
+            device.destroy_sampler(self.sampler, None);
+        }
+    }
+}
+
+/// What [`VulkanData::record_sharpen`] needs for one eye.
+struct SharpenPass {
+    set: vk::DescriptorSet,
+    target: vk::Image,
+    /// Converts what the shader samples to the color space the swapchain expects, for games whose
+    /// image format doesn't match their color space. See sharpen.comp.
+    transfer: u32,
 }
 
 pub struct VulkanData {
@@ -23,22 +240,38 @@ pub struct VulkanData {
     pub queue: vk::Queue,
     pub queue_family_index: u32,
     real_data: Option<RealSessionData>,
+    depth_data: Option<DepthSwapchainData>,
+    mirror_data: Option<ReadbackData>,
+    game_device: Option<Mutex<GameDevice>>,
 }
 
 impl Drop for VulkanData {
     fn drop(&mut self) {
         unsafe {
             self.device.device_wait_idle().unwrap();
+            if let Some(data) = &self.depth_data {
+                self.device.destroy_command_pool(data.pool, None);
+            }
+        }
+        if let Some(data) = self.mirror_data.take() {
+            data.destroy(&self.device);
         }
         match &self.real_data {
             // Temporary session - we created these handles, so let's destroy them
-            None => unsafe {
+            None if self.game_device.is_none() => unsafe {
                 self.device.destroy_device(None);
                 self.instance.destroy_instance(None);
             },
+            None => {}
             // Real session - the handles come from the app, only destroy the command pool we created
             Some(data) => unsafe {
                 self.device.destroy_command_pool(data.pool, None);
+                if let Some(timestamps) = &data.timestamps {
+                    self.device.destroy_query_pool(timestamps.pool, None);
+                }
+                if let Some(sharpen) = data.sharpen.get().and_then(Option::as_ref) {
+                    sharpen.destroy(&self.device);
+                }
                 if let Some(data) = &data.overlay_pipeline {
                     self.device.destroy_pipeline(data.pipeline, None);
                     self.device.destroy_pipeline_layout(data.layout, None);
@@ -48,6 +281,13 @@ impl Drop for VulkanData {
                 }
             },
         }
+        // The game's instance is used with a device of our own.
+        if let Some(game_device) = self.game_device.take() {
+            game_device.into_inner().unwrap().destroy(&self.device);
+            unsafe {
+                self.device.destroy_device(None);
+            }
+        }
     }
 }
 
@@ -61,6 +301,90 @@ impl GraphicsBackend for VulkanData {
         vk::Format::from_raw(format as _)
     }
 
+    fn fallback_formats(format: u32) -> Vec<u32> {
+        fallback_formats(vk::Format::from_raw(format as _))
+            .iter()
+            .map(|format| format.as_raw() as _)
+            .collect()
+    }
+
+    fn check_runtime_device(&mut self, xr_instance: &xr::Instance, system_id: xr::SystemId) {
+        if self.game_device.is_some() {
+            return;
+        }
+        let runtime_device = match unsafe {
+            xr_instance.vulkan_graphics_device(system_id, self.instance.handle().as_raw() as _)
+        } {
+            Ok(device) => vk::PhysicalDevice::from_raw(device as _),
+            Err(e) => {
+                warn!("Couldn't get the runtime's Vulkan device: {e}");
+                return;
+            }
+        };
+        let graphics_queue = unsafe {
+            self.instance
+                .get_physical_device_queue_family_properties(self.physical_device)
+        }
+        .get(self.queue_family_index as usize)
+        .is_some_and(|family| family.queue_flags.contains(vk::QueueFlags::GRAPHICS));
+        if runtime_device == self.physical_device && graphics_queue {
+            return;
+        }
+
+        if runtime_device != self.physical_device {
+            warn!("Game renders on another GPU than the runtime, copying eyes through the CPU");
+        } else {
+            warn!(
+                "Game submits on a queue family without graphics support ({}), copying eyes through the CPU",
+                self.queue_family_index
+            );
+        }
+        let (device, queue, queue_family_index) =
+            match create_runtime_device(&self.instance, runtime_device, xr_instance, system_id) {
+                Ok(device) => device,
+                Err(e) => {
+                    warn!("Failed to create Vulkan device for the runtime: {e}");
+                    return;
+                }
+            };
+
+        let game_device = unsafe {
+            let pool = self
+                .device
+                .create_command_pool(
+                    &vk::CommandPoolCreateInfo::default()
+                        .flags(vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER)
+                        .queue_family_index(self.queue_family_index),
+                    None,
+                )
+                .unwrap();
+            let buf = self
+                .device
+                .allocate_command_buffers(
+                    &vk::CommandBufferAllocateInfo::default()
+                        .command_pool(pool)
+                        .level(vk::CommandBufferLevel::PRIMARY)
+                        .command_buffer_count(1),
+                )
+                .unwrap()[0];
+            let fence = self
+                .device
+                .create_fence(&vk::FenceCreateInfo::default(), None)
+                .unwrap();
+            GameDevice {
+                device: std::mem::replace(&mut self.device, device),
+                physical_device: std::mem::replace(&mut self.physical_device, runtime_device),
+                queue: std::mem::replace(&mut self.queue, queue),
+                pool,
+                buf,
+                fence,
+                eyes: Default::default(),
+            }
+        };
+        self.queue_family_index = queue_family_index;
+        self.game_device = Some(game_device.into());
+    }
+
     fn session_create_info(&self) -> <Self::Api as openxr::Graphics>::SessionCreateInfo {
         let queue_families = unsafe {
             self.instance
@@ -93,27 +417,8 @@ impl GraphicsBackend for VulkanData {
     }
     fn store_swapchain_images(&mut self, images: Vec<u64>, format: u32) {
         let images: Vec<vk::Image> = images.into_iter().map(vk::Image::from_raw).collect();
-        let pool = unsafe {
-            self.device
-                .create_command_pool(
-                    &vk::CommandPoolCreateInfo::default()
-                        .flags(vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER)
-                        .queue_family_index(self.queue_family_index),
-                    None,
-                )
-                .unwrap()
-        };
-        let bufs = unsafe {
-            self.device
-                .allocate_command_buffers(
-                    &vk::CommandBufferAllocateInfo::default()
-                        .command_pool(pool)
-                        .level(vk::CommandBufferLevel::PRIMARY)
-                        // We have to copy 2 eyes per swapchain image
-                        .command_buffer_count(images.len() as u32 * 2),
-                )
-                .unwrap()
-        };
+        let (pool, bufs) = self.create_eye_command_buffers(images.len());
+        let timestamps = self.create_timestamp_data(images.len());
 
         if let Some(data) = self.real_data.replace(RealSessionData {
             images,
@@ -121,9 +426,18 @@ impl GraphicsBackend for VulkanData {
             pool,
             bufs,
             overlay_pipeline: Default::default(),
+            timestamps,
+            sharpen: OnceLock::new(),
         }) {
             unsafe {
                 self.device.destroy_command_pool(data.pool, None);
+                if let Some(timestamps) = data.timestamps {
+                    self.device.destroy_query_pool(timestamps.pool, None);
+                }
+                if let Some(sharpen) = data.sharpen.get().and_then(Option::as_ref) {
+                    self.device.queue_wait_idle(self.queue).unwrap();
+                    sharpen.destroy(&self.device);
+                }
             }
         }
     }
@@ -133,13 +447,16 @@ impl GraphicsBackend for VulkanData {
         texture: *const vr::VRVulkanTextureData_t,
         bounds: vr::VRTextureBounds_t,
         color_space: vr::EColorSpace,
+        _submit_flags: vr::EVRSubmitFlags,
     ) -> xr::SwapchainCreateInfo<Self::Api> {
         let texture = unsafe { texture.as_ref() }.unwrap();
         let (extent, _) = texture_extent_from_bounds(texture, bounds);
         xr::SwapchainCreateInfo {
             create_flags: xr::SwapchainCreateFlags::EMPTY,
+            // Read back for the mirror window and screenshots.
             usage_flags: xr::SwapchainUsageFlags::COLOR_ATTACHMENT
-                | xr::SwapchainUsageFlags::TRANSFER_DST,
+                | xr::SwapchainUsageFlags::TRANSFER_DST
+                | xr::SwapchainUsageFlags::TRANSFER_SRC,
             format: get_colorspace_corrected_format(
                 vk::Format::from_raw(texture.m_nFormat as _),
                 color_space,
@@ -154,6 +471,9 @@ impl GraphicsBackend for VulkanData {
         }
     }
 
+    // OpenXR only composites images from swapchains allocated by the runtime, and no extension
+    // lets us wrap a VkImage created by the game instead, even on the same device. The game
+    // renders into its own images, so every submitted eye is copied.
     fn copy_texture_to_swapchain(
         &self,
         eye: vr::EVREye,
@@ -162,6 +482,7 @@ impl GraphicsBackend for VulkanData {
         bounds: vr::VRTextureBounds_t,
         image_index: usize,
         submit_flags: vr::EVRSubmitFlags,
+        array_index: Option<u32>,
     ) -> xr::Extent2Di {
         let (texture, array_data) =
             if (submit_flags & vr::EVRSubmitFlags::VulkanTextureWithArrayData).0 > 0 {
@@ -178,7 +499,88 @@ impl GraphicsBackend for VulkanData {
         let (extent, offset) = texture_extent_from_bounds(texture, bounds);
         log::trace!("{:?} extent: {:?} | bounds: {:?}", eye, extent, bounds);
 
-        self.record_commands(buf, || unsafe {
+        let game_format = get_colorspace_corrected_format(
+            vk::Format::from_raw(texture.m_nFormat as _),
+            color_space,
+        );
+        let mut game_image = vk::Image::from_raw(texture.m_nImage);
+        let mut game_image_format = vk::Format::from_raw(texture.m_nFormat as _);
+        let mut game_layer = array_index
+            .or(array_data.map(|d| d.m_unArrayIndex))
+            .unwrap_or(0);
+        let mut game_offset = offset;
+        let mut game_device = self.game_device.as_ref().map(|d| d.lock().unwrap());
+        let bridged = match game_device.as_deref_mut() {
+            Some(game_device) => {
+                let Some(bridged) = self.bridge_eye(
+                    game_device,
+                    eye,
+                    texture,
+                    game_layer,
+                    offset,
+                    extent,
+                    game_format,
+                ) else {
+                    return xr::Extent2Di {
+                        width: extent.width as _,
+                        height: extent.height as _,
+                    };
+                };
+                game_image = bridged.image;
+                game_image_format = game_format;
+                game_layer = 0;
+                game_offset = vk::Offset3D::default();
+                Some(bridged)
+            }
+            None => None,
+        };
+
+        let sharpen = data
+            .sharpen
+            .get_or_init(|| self.create_sharpen_data(data.bufs.len()))
+            .as_ref()
+            .filter(|_| {
+                if texture.m_nSampleCount > 1 {
+                    crate::warn_once!("Multisampled eyes can't be sharpened.");
+                    return false;
+                }
+                true
+            })
+            .and_then(|sharpen| {
+                let pass = self.prepare_sharpen(
+                    sharpen,
+                    2 * image_index + eye as usize,
+                    eye,
+                    game_image,
+                    game_image_format,
+                    game_format,
+                    game_layer,
+                    extent,
+                )?;
+                Some((sharpen, pass))
+            });
+
+        let timestamps = data
+            .timestamps
+            .as_ref()
+            .map(|t| (t.pool, TimestampData::query(image_index, eye)));
+        let fence = bridged.map_or(vk::Fence::null(), |bridged| bridged.fence);
+        self.record_commands_with_fence(buf, fence, || unsafe {
+            // Written once the game's rendering before this is done.
+            if let Some((pool, query)) = timestamps {
+                self.device.cmd_reset_query_pool(buf, pool, query, 2);
+                self.device.cmd_write_timestamp(
+                    buf,
+                    vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                    pool,
+                    query,
+                );
+            }
+
+            if let Some(bridged) = bridged {
+                self.record_upload(buf, bridged);
+            }
+
             // transition swapchain image to TRANSFER_DST
             let swapchain_res = vk::ImageSubresourceRange {
                 aspect_mask: vk::ImageAspectFlags::COLOR,
@@ -214,15 +616,12 @@ impl GraphicsBackend for VulkanData {
                 layer_count: 1,
             };
 
-            let game_image = vk::Image::from_raw(texture.m_nImage);
-            let game_layer = array_data.map(|d| d.m_unArrayIndex).unwrap_or(0);
-
             let copy = vk::ImageResolve {
                 src_subresource: vk::ImageSubresourceLayers {
                     base_array_layer: game_layer,
                     ..subresource
                 },
-                src_offset: offset,
+                src_offset: game_offset,
                 dst_subresource: vk::ImageSubresourceLayers {
                     base_array_layer: eye as u32,
                     ..subresource
@@ -231,11 +630,28 @@ impl GraphicsBackend for VulkanData {
                 extent,
             };
 
-            let game_format = get_colorspace_corrected_format(
-                vk::Format::from_raw(texture.m_nFormat as _),
-                color_space,
-            );
-            if texture.m_nSampleCount > 1 {
+            if let Some((sharpen, pass)) = &sharpen {
+                self.record_sharpen(buf, sharpen, pass, game_offset, extent);
+                let end_img_offset = vk::Offset3D {
+                    x: extent.width as _,
+                    y: extent.height as _,
+                    z: 1,
+                };
+                self.device.cmd_blit_image(
+                    buf,
+                    pass.target,
+                    vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                    swapchain_image,
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    &[vk::ImageBlit {
+                        src_subresource: subresource,
+                        src_offsets: [vk::Offset3D::default(), end_img_offset],
+                        dst_subresource: copy.dst_subresource,
+                        dst_offsets: [copy.dst_offset, end_img_offset],
+                    }],
+                    vk::Filter::NEAREST,
+                );
+            } else if texture.m_nSampleCount > 1 {
                 self.device.cmd_resolve_image(
                     buf,
                     game_image,
@@ -299,6 +715,15 @@ impl GraphicsBackend for VulkanData {
                     ..Default::default()
                 }],
             );
+
+            if let Some((pool, query)) = timestamps {
+                self.device.cmd_write_timestamp(
+                    buf,
+                    vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                    pool,
+                    query + 1,
+                );
+            }
         });
 
         xr::Extent2Di {
@@ -307,6 +732,29 @@ impl GraphicsBackend for VulkanData {
         }
     }
 
+    fn eye_copy_times(&self, image_index: usize) -> Option<[GpuSpan; 2]> {
+        let timestamps = self.real_data.as_ref()?.timestamps.as_ref()?;
+        let mut ticks = [0u64; 4];
+        // Not waiting, so this fails with NOT_READY if any copy hasn't finished.
+        unsafe {
+            self.device.get_query_pool_results(
+                timestamps.pool,
+                TimestampData::query(image_index, vr::EVREye::Left),
+                &mut ticks,
+                vk::QueryResultFlags::TYPE_64,
+            )
+        }
+        .ok()?;
+
+        let ms =
+            |tick: u64| (tick & timestamps.valid_mask) as f64 * timestamps.period / 1_000_000.0;
+        let span = |start: u64, end: u64| GpuSpan {
+            start_ms: ms(start),
+            end_ms: ms(end),
+        };
+        Some([span(ticks[0], ticks[1]), span(ticks[2], ticks[3])])
+    }
+
     fn copy_overlay_to_swapchain(
         &mut self,
         texture: *const vr::VRVulkanTextureData_t,
@@ -456,63 +904,1566 @@ impl GraphicsBackend for VulkanData {
             height: extent.height as _,
         }
     }
-}
-impl VulkanData {
-    pub fn record_commands(&self, buf: vk::CommandBuffer, cmds: impl FnOnce()) {
-        unsafe {
-            self.device
-                .begin_command_buffer(
-                    buf,
-                    &vk::CommandBufferBeginInfo::default()
-                        .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT),
-                )
-                .unwrap();
-        }
-
-        cmds();
-
-        unsafe {
-            self.device.end_command_buffer(buf).unwrap();
 
-            self.device
-                .queue_submit(
-                    self.queue,
-                    &[vk::SubmitInfo::default().command_buffers(&[buf])],
-                    vk::Fence::null(),
-                )
-                .unwrap();
+    fn swapchain_info_for_cube(
+        &self,
+        texture: *const vr::VRVulkanTextureData_t,
+        color_space: vr::EColorSpace,
+    ) -> Option<xr::SwapchainCreateInfo<Self::Api>> {
+        let texture = unsafe { texture.as_ref() }?;
+        if texture.m_nSampleCount > 1 {
+            crate::warn_once!("Multisampled textures can't be copied to a cube map.");
+            return None;
         }
+        // Cube faces are square.
+        let size = texture.m_nWidth.max(texture.m_nHeight);
+        Some(xr::SwapchainCreateInfo {
+            create_flags: xr::SwapchainCreateFlags::EMPTY,
+            usage_flags: xr::SwapchainUsageFlags::COLOR_ATTACHMENT
+                | xr::SwapchainUsageFlags::TRANSFER_DST,
+            format: get_colorspace_corrected_format(
+                vk::Format::from_raw(texture.m_nFormat as _),
+                color_space,
+            )
+            .as_raw() as _,
+            sample_count: 1,
+            width: size,
+            height: size,
+            face_count: 6,
+            array_size: 1,
+            mip_count: 1,
+        })
     }
 
-    pub fn new(data: &vr::VRVulkanTextureData_t) -> Self {
-        let entry = new_entry();
-        let instance = unsafe {
-            ash::Instance::load(
-                entry.static_fn(),
-                vk::Instance::from_raw(data.m_pInstance as _),
-            )
+    fn copy_textures_to_cube(
+        &mut self,
+        textures: [*const vr::VRVulkanTextureData_t; 6],
+        image_index: usize,
+    ) {
+        let data = self.real_data.as_ref().unwrap();
+        let image = data.images[image_index];
+        let buf = data.bufs[2 * image_index];
+        let faces = vk::ImageSubresourceRange {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            layer_count: 6,
         };
-        let device = unsafe {
-            ash::Device::load(
-                instance.fp_v1_0(),
-                vk::Device::from_raw(data.m_pDevice as _),
-            )
+        let Some(first) = (unsafe { textures[0].as_ref() }) else {
+            return;
         };
+        let size = first.m_nWidth.max(first.m_nHeight) as i32;
+
+        self.record_commands(buf, || unsafe {
+            self.device.cmd_pipeline_barrier(
+                buf,
+                vk::PipelineStageFlags::TOP_OF_PIPE,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[vk::ImageMemoryBarrier {
+                    src_access_mask: vk::AccessFlags::empty(),
+                    dst_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                    old_layout: vk::ImageLayout::UNDEFINED,
+                    new_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    image,
+                    subresource_range: faces,
+                    ..Default::default()
+                }],
+            );
+
+            // Blitting scales faces that aren't the size of the first one, and converts formats.
+            for (face, texture) in textures.iter().enumerate() {
+                let Some(texture) = texture.as_ref() else {
+                    continue;
+                };
+                self.device.cmd_blit_image(
+                    buf,
+                    vk::Image::from_raw(texture.m_nImage),
+                    vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                    image,
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    &[vk::ImageBlit {
+                        src_subresource: vk::ImageSubresourceLayers {
+                            aspect_mask: vk::ImageAspectFlags::COLOR,
+                            mip_level: 0,
+                            base_array_layer: 0,
+                            layer_count: 1,
+                        },
+                        src_offsets: [
+                            vk::Offset3D::default(),
+                            vk::Offset3D {
+                                x: texture.m_nWidth as _,
+                                y: texture.m_nHeight as _,
+                                z: 1,
+                            },
+                        ],
+                        dst_subresource: vk::ImageSubresourceLayers {
+                            aspect_mask: vk::ImageAspectFlags::COLOR,
+                            mip_level: 0,
+                            base_array_layer: face as u32,
+                            layer_count: 1,
+                        },
+                        dst_offsets: [
+                            vk::Offset3D::default(),
+                            vk::Offset3D {
+                                x: size,
+                                y: size,
+                                z: 1,
+                            },
+                        ],
+                    }],
+                    vk::Filter::LINEAR,
+                );
+            }
+
+            self.device.cmd_pipeline_barrier(
+                buf,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[vk::ImageMemoryBarrier {
+                    src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                    dst_access_mask: vk::AccessFlags::empty(),
+                    old_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    new_layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+                    image,
+                    subresource_range: faces,
+                    ..Default::default()
+                }],
+            );
+        });
+    }
+
+    fn swapchain_info_for_depth(
+        &self,
+        depth: &vr::VRTextureDepthInfo_t,
+        extent: xr::Extent2Di,
+    ) -> Option<xr::SwapchainCreateInfo<Self::Api>> {
+        let texture = unsafe { depth.handle.cast::<vr::VRVulkanTextureData_t>().as_ref() }?;
+        if self.game_device.is_some() {
+            crate::warn_once!(
+                "Depth buffers can't be passed on when copying eyes between devices."
+            );
+            return None;
+        }
+        if texture.m_nSampleCount > 1 {
+            crate::warn_once!("Multisampled depth buffers can't be passed on to the runtime.");
+            return None;
+        }
+        Some(xr::SwapchainCreateInfo {
+            create_flags: xr::SwapchainCreateFlags::EMPTY,
+            usage_flags: xr::SwapchainUsageFlags::DEPTH_STENCIL_ATTACHMENT
+                | xr::SwapchainUsageFlags::TRANSFER_DST,
+            format: texture.m_nFormat as _,
+            sample_count: 1,
+            width: extent.width as u32,
+            height: extent.height as u32,
+            face_count: 1,
+            array_size: 2,
+            mip_count: 1,
+        })
+    }
+
+    fn store_depth_swapchain_images(&mut self, images: Vec<u64>) {
+        let images: Vec<vk::Image> = images.into_iter().map(vk::Image::from_raw).collect();
+        let (pool, bufs) = self.create_eye_command_buffers(images.len());
+        if let Some(data) = self
+            .depth_data
+            .replace(DepthSwapchainData { images, pool, bufs })
+        {
+            unsafe {
+                self.device.destroy_command_pool(data.pool, None);
+            }
+        }
+    }
+
+    fn copy_depth_to_swapchain(
+        &self,
+        eye: vr::EVREye,
+        depth: &vr::VRTextureDepthInfo_t,
+        bounds: vr::VRTextureBounds_t,
+        image_index: usize,
+    ) {
+        let texture = unsafe { &*depth.handle.cast::<vr::VRVulkanTextureData_t>() };
+        let data = self.depth_data.as_ref().unwrap();
+        let swapchain_image = data.images[image_index];
+        let buf = data.bufs[2 * image_index + eye as usize];
+        let (extent, offset) = texture_extent_from_bounds(texture, bounds);
+
+        let format = vk::Format::from_raw(texture.m_nFormat as _);
+        let swapchain_res = vk::ImageSubresourceRange {
+            aspect_mask: depth_aspect(format),
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: eye as u32,
+            layer_count: 1,
+        };
+        let subresource = vk::ImageSubresourceLayers {
+            aspect_mask: vk::ImageAspectFlags::DEPTH,
+            mip_level: 0,
+            base_array_layer: 0,
+            layer_count: 1,
+        };
+
+        self.record_commands(buf, || unsafe {
+            // The whole layer is overwritten, so its previous contents don't matter.
+            self.device.cmd_pipeline_barrier(
+                buf,
+                vk::PipelineStageFlags::TOP_OF_PIPE,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[vk::ImageMemoryBarrier {
+                    src_access_mask: vk::AccessFlags::empty(),
+                    dst_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                    old_layout: vk::ImageLayout::UNDEFINED,
+                    new_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    image: swapchain_image,
+                    subresource_range: swapchain_res,
+                    ..Default::default()
+                }],
+            );
+
+            self.device.cmd_copy_image(
+                buf,
+                vk::Image::from_raw(texture.m_nImage),
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                swapchain_image,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &[vk::ImageCopy {
+                    src_subresource: subresource,
+                    src_offset: offset,
+                    dst_subresource: vk::ImageSubresourceLayers {
+                        base_array_layer: eye as u32,
+                        ..subresource
+                    },
+                    dst_offset: vk::Offset3D::default(),
+                    extent,
+                }],
+            );
+
+            self.device.cmd_pipeline_barrier(
+                buf,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[vk::ImageMemoryBarrier {
+                    src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                    dst_access_mask: vk::AccessFlags::empty(),
+                    old_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    new_layout: vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL,
+                    image: swapchain_image,
+                    subresource_range: swapchain_res,
+                    ..Default::default()
+                }],
+            );
+        });
+    }
+
+    fn new_overlay_backend(&self) -> Self {
+        Self {
+            _entry: new_entry(),
+            instance: self.instance.clone(),
+            physical_device: self.physical_device,
+            device: self.device.clone(),
+            queue: self.queue,
+            queue_family_index: self.queue_family_index,
+            real_data: Default::default(),
+            depth_data: Default::default(),
+            mirror_data: Default::default(),
+            game_device: None,
+        }
+    }
+
+    fn swapchain_info_for_pixels(
+        &self,
+        extent: xr::Extent2Di,
+    ) -> xr::SwapchainCreateInfo<Self::Api> {
+        xr::SwapchainCreateInfo {
+            create_flags: xr::SwapchainCreateFlags::EMPTY,
+            usage_flags: xr::SwapchainUsageFlags::COLOR_ATTACHMENT
+                | xr::SwapchainUsageFlags::TRANSFER_DST,
+            format: vk::Format::R8G8B8A8_SRGB.as_raw() as _,
+            sample_count: 1,
+            width: extent.width as u32,
+            height: extent.height as u32,
+            face_count: 1,
+            array_size: 1,
+            mip_count: 1,
+        }
+    }
+
+    fn copy_pixels_to_swapchain(
+        &mut self,
+        pixels: &[u8],
+        extent: xr::Extent2Di,
+        image_index: usize,
+    ) {
+        let data = self.real_data.as_ref().unwrap();
+        let swapchain_image = data.images[image_index];
+        let buf = data.bufs[image_index];
+
+        let staging = unsafe {
+            self.device
+                .create_buffer(
+                    &vk::BufferCreateInfo::default()
+                        .size(pixels.len() as u64)
+                        .usage(vk::BufferUsageFlags::TRANSFER_SRC)
+                        .sharing_mode(vk::SharingMode::EXCLUSIVE),
+                    None,
+                )
+                .unwrap()
+        };
+        let requirements = unsafe { self.device.get_buffer_memory_requirements(staging) };
+        let memory = self
+            .allocate_memory(
+                requirements,
+                vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+            )
+            .expect("No host visible memory for overlay pixels");
+        unsafe {
+            self.device.bind_buffer_memory(staging, memory, 0).unwrap();
+            let ptr = self
+                .device
+                .map_memory(memory, 0, pixels.len() as u64, vk::MemoryMapFlags::empty())
+                .unwrap();
+            std::ptr::copy_nonoverlapping(pixels.as_ptr(), ptr.cast(), pixels.len());
+            self.device.unmap_memory(memory);
+        }
+
+        let swapchain_res = vk::ImageSubresourceRange {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            layer_count: 1,
+        };
+        self.record_commands(buf, || unsafe {
+            // The whole image gets overwritten, so the old contents can be discarded.
+            self.device.cmd_pipeline_barrier(
+                buf,
+                vk::PipelineStageFlags::TOP_OF_PIPE,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[vk::ImageMemoryBarrier {
+                    src_access_mask: vk::AccessFlags::empty(),
+                    dst_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                    old_layout: vk::ImageLayout::UNDEFINED,
+                    new_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    image: swapchain_image,
+                    subresource_range: swapchain_res,
+                    ..Default::default()
+                }],
+            );
+
+            self.device.cmd_copy_buffer_to_image(
+                buf,
+                staging,
+                swapchain_image,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &[vk::BufferImageCopy {
+                    buffer_offset: 0,
+                    buffer_row_length: 0,
+                    buffer_image_height: 0,
+                    image_subresource: vk::ImageSubresourceLayers {
+                        aspect_mask: vk::ImageAspectFlags::COLOR,
+                        mip_level: 0,
+                        base_array_layer: 0,
+                        layer_count: 1,
+                    },
+                    image_offset: vk::Offset3D::default(),
+                    image_extent: vk::Extent3D {
+                        width: extent.width as u32,
+                        height: extent.height as u32,
+                        depth: 1,
+                    },
+                }],
+            );
+
+            self.device.cmd_pipeline_barrier(
+                buf,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[vk::ImageMemoryBarrier {
+                    src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                    dst_access_mask: vk::AccessFlags::empty(),
+                    old_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    new_layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+                    image: swapchain_image,
+                    subresource_range: swapchain_res,
+                    ..Default::default()
+                }],
+            );
+        });
+
+        // The staging buffer has to outlive the copy. This only happens when the pixels change,
+        // so just waiting for it is fine.
+        unsafe {
+            self.device.queue_wait_idle(self.queue).unwrap();
+            self.device.destroy_buffer(staging, None);
+            self.device.free_memory(memory, None);
+        }
+    }
+
+    fn read_mirror_image(&mut self, image_index: usize, eyes: &[MirrorEye]) -> Option<MirrorImage> {
+        let previous = self
+            .mirror_data
+            .as_mut()
+            .and_then(|data| data.read_back(&self.device));
+
+        let Some(extent) = readback_extent(eyes) else {
+            return previous;
+        };
+        if self
+            .mirror_data
+            .as_ref()
+            .is_none_or(|data| data.extent != extent)
+        {
+            if let Some(data) = self.mirror_data.take() {
+                data.destroy(&self.device);
+            }
+            self.mirror_data = Some(self.create_readback_data(extent)?);
+        }
+
+        self.record_readback(self.mirror_data.as_ref().unwrap(), image_index, eyes);
+        self.mirror_data.as_mut().unwrap().pending = true;
+
+        previous
+    }
+
+    fn read_eyes(&mut self, image_index: usize, eyes: &[MirrorEye]) -> Option<MirrorImage> {
+        let mut data = self.create_readback_data(readback_extent(eyes)?)?;
+        self.record_readback(&data, image_index, eyes);
+        data.pending = true;
+        let image = data.read_back(&self.device);
+        data.destroy(&self.device);
+        image
+    }
+}
+impl VulkanData {
+    /// Reads an eye back on the game's device and copies it to the eye's upload buffer, which
+    /// [`Self::record_upload`] copies to the eye's image on the runtime's device. None if the eye
+    /// can't be copied between devices.
+    #[allow(clippy::too_many_arguments)]
+    fn bridge_eye<'a>(
+        &self,
+        game_device: &'a mut GameDevice,
+        eye: vr::EVREye,
+        texture: &vr::VRVulkanTextureData_t,
+        layer: u32,
+        offset: vk::Offset3D,
+        extent: vk::Extent3D,
+        format: vk::Format,
+    ) -> Option<&'a BridgedEye> {
+        if texture.m_nSampleCount > 1 {
+            crate::warn_once!("Multisampled textures can't be copied between devices.");
+            return None;
+        }
+        let Some(texel_size) = texel_size(format) else {
+            crate::warn_once!("Textures in {format:?} can't be copied between devices.");
+            return None;
+        };
+
+        let slot = &mut game_device.eyes[eye as usize];
+        if slot
+            .as_ref()
+            .is_none_or(|bridged| bridged.extent != extent || bridged.format != format)
+        {
+            if let Some(bridged) = slot.take() {
+                bridged.destroy(&game_device.device, &self.device);
+            }
+            *slot = Some(self.create_bridged_eye(
+                &game_device.device,
+                game_device.physical_device,
+                extent,
+                format,
+                texel_size,
+            )?);
+        }
+        let bridged = slot.as_ref().unwrap();
+
+        let buf = game_device.buf;
+        let device = &game_device.device;
+        unsafe {
+            device
+                .begin_command_buffer(
+                    buf,
+                    &vk::CommandBufferBeginInfo::default()
+                        .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT),
+                )
+                .unwrap();
+            device.cmd_copy_image_to_buffer(
+                buf,
+                vk::Image::from_raw(texture.m_nImage),
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                bridged.readback,
+                &[vk::BufferImageCopy {
+                    buffer_offset: 0,
+                    buffer_row_length: 0,
+                    buffer_image_height: 0,
+                    image_subresource: vk::ImageSubresourceLayers {
+                        aspect_mask: vk::ImageAspectFlags::COLOR,
+                        mip_level: 0,
+                        base_array_layer: layer,
+                        layer_count: 1,
+                    },
+                    image_offset: offset,
+                    image_extent: extent,
+                }],
+            );
+            device.cmd_pipeline_barrier(
+                buf,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::HOST,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[vk::BufferMemoryBarrier {
+                    src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                    dst_access_mask: vk::AccessFlags::HOST_READ,
+                    src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                    dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                    buffer: bridged.readback,
+                    offset: 0,
+                    size: vk::WHOLE_SIZE,
+                    ..Default::default()
+                }],
+                &[],
+            );
+            device.end_command_buffer(buf).unwrap();
+            device
+                .queue_submit(
+                    game_device.queue,
+                    &[vk::SubmitInfo::default().command_buffers(&[buf])],
+                    game_device.fence,
+                )
+                .unwrap();
+            device
+                .wait_for_fences(&[game_device.fence], true, u64::MAX)
+                .unwrap();
+            device.reset_fences(&[game_device.fence]).unwrap();
+
+            // The last upload of this eye has to be done before it's overwritten.
+            self.device
+                .wait_for_fences(&[bridged.fence], true, u64::MAX)
+                .unwrap();
+            self.device.reset_fences(&[bridged.fence]).unwrap();
+
+            let src = device
+                .map_memory(
+                    bridged.readback_memory,
+                    0,
+                    bridged.size,
+                    vk::MemoryMapFlags::empty(),
+                )
+                .unwrap();
+            let dst = self
+                .device
+                .map_memory(
+                    bridged.upload_memory,
+                    0,
+                    bridged.size,
+                    vk::MemoryMapFlags::empty(),
+                )
+                .unwrap();
+            std::ptr::copy_nonoverlapping(
+                src.cast::<u8>(),
+                dst.cast::<u8>(),
+                bridged.size as usize,
+            );
+            self.device.unmap_memory(bridged.upload_memory);
+            device.unmap_memory(bridged.readback_memory);
+        }
+
+        Some(bridged)
+    }
+
+    fn create_bridged_eye(
+        &self,
+        game_device: &ash::Device,
+        game_physical_device: vk::PhysicalDevice,
+        extent: vk::Extent3D,
+        format: vk::Format,
+        texel_size: u64,
+    ) -> Option<BridgedEye> {
+        let size = extent.width as u64 * extent.height as u64 * texel_size;
+        let (readback, readback_memory) = create_host_buffer(
+            &self.instance,
+            game_physical_device,
+            game_device,
+            size,
+            vk::BufferUsageFlags::TRANSFER_DST,
+        )?;
+        let destroy_readback = || unsafe {
+            game_device.destroy_buffer(readback, None);
+            game_device.free_memory(readback_memory, None);
+        };
+        let Some((upload, upload_memory)) = create_host_buffer(
+            &self.instance,
+            self.physical_device,
+            &self.device,
+            size,
+            vk::BufferUsageFlags::TRANSFER_SRC,
+        ) else {
+            destroy_readback();
+            return None;
+        };
+
+        unsafe {
+            let image = self
+                .device
+                .create_image(
+                    &vk::ImageCreateInfo::default()
+                        .image_type(vk::ImageType::TYPE_2D)
+                        .format(format)
+                        .extent(extent)
+                        .mip_levels(1)
+                        .array_layers(1)
+                        .samples(vk::SampleCountFlags::TYPE_1)
+                        .tiling(vk::ImageTiling::OPTIMAL)
+                        // Sampled when sharpening.
+                        .usage(
+                            vk::ImageUsageFlags::TRANSFER_SRC
+                                | vk::ImageUsageFlags::TRANSFER_DST
+                                | vk::ImageUsageFlags::SAMPLED,
+                        )
+                        .sharing_mode(vk::SharingMode::EXCLUSIVE)
+                        .initial_layout(vk::ImageLayout::UNDEFINED),
+                    None,
+                )
+                .unwrap();
+            let Some(image_memory) = self.allocate_memory(
+                self.device.get_image_memory_requirements(image),
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            ) else {
+                self.device.destroy_image(image, None);
+                self.device.destroy_buffer(upload, None);
+                self.device.free_memory(upload_memory, None);
+                destroy_readback();
+                return None;
+            };
+            self.device
+                .bind_image_memory(image, image_memory, 0)
+                .unwrap();
+            let fence = self
+                .device
+                .create_fence(
+                    &vk::FenceCreateInfo::default().flags(vk::FenceCreateFlags::SIGNALED),
+                    None,
+                )
+                .unwrap();
+
+            Some(BridgedEye {
+                extent,
+                format,
+                size,
+                readback,
+                readback_memory,
+                upload,
+                upload_memory,
+                image,
+                image_memory,
+                fence,
+            })
+        }
+    }
+
+    /// Records copying a bridged eye from its upload buffer to its image, leaving the image ready
+    /// to be copied from like a game texture.
+    fn record_upload(&self, buf: vk::CommandBuffer, bridged: &BridgedEye) {
+        let range = vk::ImageSubresourceRange {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            layer_count: 1,
+        };
+        unsafe {
+            self.device.cmd_pipeline_barrier(
+                buf,
+                vk::PipelineStageFlags::TOP_OF_PIPE,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[vk::ImageMemoryBarrier {
+                    src_access_mask: vk::AccessFlags::empty(),
+                    dst_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                    old_layout: vk::ImageLayout::UNDEFINED,
+                    new_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    image: bridged.image,
+                    subresource_range: range,
+                    ..Default::default()
+                }],
+            );
+            self.device.cmd_copy_buffer_to_image(
+                buf,
+                bridged.upload,
+                bridged.image,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &[vk::BufferImageCopy {
+                    buffer_offset: 0,
+                    buffer_row_length: 0,
+                    buffer_image_height: 0,
+                    image_subresource: vk::ImageSubresourceLayers {
+                        aspect_mask: vk::ImageAspectFlags::COLOR,
+                        mip_level: 0,
+                        base_array_layer: 0,
+                        layer_count: 1,
+                    },
+                    image_offset: vk::Offset3D::default(),
+                    image_extent: bridged.extent,
+                }],
+            );
+            self.device.cmd_pipeline_barrier(
+                buf,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[vk::ImageMemoryBarrier {
+                    src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                    dst_access_mask: vk::AccessFlags::TRANSFER_READ,
+                    old_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    new_layout: vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                    image: bridged.image,
+                    subresource_range: range,
+                    ..Default::default()
+                }],
+            );
+        }
+    }
+
+    /// Records and submits the copy of the eyes to the readback buffer.
+    fn record_readback(&self, data: &ReadbackData, image_index: usize, eyes: &[MirrorEye]) {
+        let extent = data.extent;
+        let swapchain_image = self.real_data.as_ref().unwrap().images[image_index];
+        let swapchain_res = vk::ImageSubresourceRange {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            layer_count: 2,
+        };
+        let readback_res = vk::ImageSubresourceRange {
+            layer_count: 1,
+            ..swapchain_res
+        };
+        let buf = data.buf;
+        self.record_commands_with_fence(buf, data.fence, || unsafe {
+            self.device.cmd_pipeline_barrier(
+                buf,
+                vk::PipelineStageFlags::ALL_COMMANDS,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[
+                    vk::ImageMemoryBarrier {
+                        src_access_mask: vk::AccessFlags::MEMORY_WRITE,
+                        dst_access_mask: vk::AccessFlags::TRANSFER_READ,
+                        old_layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+                        new_layout: vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                        image: swapchain_image,
+                        subresource_range: swapchain_res,
+                        ..Default::default()
+                    },
+                    vk::ImageMemoryBarrier {
+                        src_access_mask: vk::AccessFlags::empty(),
+                        dst_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                        old_layout: vk::ImageLayout::UNDEFINED,
+                        new_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                        image: data.image,
+                        subresource_range: readback_res,
+                        ..Default::default()
+                    },
+                ],
+            );
+
+            let mut x = 0;
+            let blits: Vec<vk::ImageBlit> = eyes
+                .iter()
+                .map(|eye| {
+                    let (width, height) = (eye.extent.width, eye.extent.height);
+                    let (top, bottom) = if eye.flip_vertically {
+                        (height, 0)
+                    } else {
+                        (0, height)
+                    };
+                    let (left, right) = if eye.flip_horizontally {
+                        (width, 0)
+                    } else {
+                        (0, width)
+                    };
+                    let blit = vk::ImageBlit {
+                        src_subresource: vk::ImageSubresourceLayers {
+                            aspect_mask: vk::ImageAspectFlags::COLOR,
+                            mip_level: 0,
+                            base_array_layer: eye.eye as u32,
+                            layer_count: 1,
+                        },
+                        src_offsets: [
+                            vk::Offset3D {
+                                x: left,
+                                y: top,
+                                z: 0,
+                            },
+                            vk::Offset3D {
+                                x: right,
+                                y: bottom,
+                                z: 1,
+                            },
+                        ],
+                        dst_subresource: vk::ImageSubresourceLayers {
+                            aspect_mask: vk::ImageAspectFlags::COLOR,
+                            mip_level: 0,
+                            base_array_layer: 0,
+                            layer_count: 1,
+                        },
+                        dst_offsets: [
+                            vk::Offset3D { x, y: 0, z: 0 },
+                            vk::Offset3D {
+                                x: x + width,
+                                y: height,
+                                z: 1,
+                            },
+                        ],
+                    };
+                    x += width;
+                    blit
+                })
+                .collect();
+            self.device.cmd_blit_image(
+                buf,
+                swapchain_image,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                data.image,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &blits,
+                vk::Filter::NEAREST,
+            );
+
+            self.device.cmd_pipeline_barrier(
+                buf,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[
+                    vk::ImageMemoryBarrier {
+                        src_access_mask: vk::AccessFlags::TRANSFER_READ,
+                        dst_access_mask: vk::AccessFlags::empty(),
+                        old_layout: vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                        new_layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+                        image: swapchain_image,
+                        subresource_range: swapchain_res,
+                        ..Default::default()
+                    },
+                    vk::ImageMemoryBarrier {
+                        src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                        dst_access_mask: vk::AccessFlags::TRANSFER_READ,
+                        old_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                        new_layout: vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                        image: data.image,
+                        subresource_range: readback_res,
+                        ..Default::default()
+                    },
+                ],
+            );
+
+            self.device.cmd_copy_image_to_buffer(
+                buf,
+                data.image,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                data.buffer,
+                &[vk::BufferImageCopy {
+                    buffer_offset: 0,
+                    buffer_row_length: 0,
+                    buffer_image_height: 0,
+                    image_subresource: vk::ImageSubresourceLayers {
+                        aspect_mask: vk::ImageAspectFlags::COLOR,
+                        mip_level: 0,
+                        base_array_layer: 0,
+                        layer_count: 1,
+                    },
+                    image_offset: vk::Offset3D::default(),
+                    image_extent: extent.into(),
+                }],
+            );
+
+            self.device.cmd_pipeline_barrier(
+                buf,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::HOST,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[vk::BufferMemoryBarrier {
+                    src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                    dst_access_mask: vk::AccessFlags::HOST_READ,
+                    src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                    dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                    buffer: data.buffer,
+                    offset: 0,
+                    size: vk::WHOLE_SIZE,
+                    ..Default::default()
+                }],
+                &[],
+            );
+        });
+    }
+
+    /// Returns None if sharpening is off or the game's queue can't run it.
+    fn create_sharpen_data(&self, command_buffer_count: usize) -> Option<SharpenData> {
+        let strength = Config::get().sharpening()?;
+        let compute_queue = unsafe {
+            self.instance
+                .get_physical_device_queue_family_properties(self.physical_device)
+        }
+        .get(self.queue_family_index as usize)
+        .is_some_and(|family| family.queue_flags.contains(vk::QueueFlags::COMPUTE));
+        if !compute_queue {
+            warn!("Game's queue can't run compute shaders, not sharpening eyes");
+            return None;
+        }
+        info!("Sharpening eyes (strength {strength})");
+
+        let bindings = [
+            vk::DescriptorSetLayoutBinding::default()
+                .binding(0)
+                .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                .descriptor_count(1)
+                .stage_flags(vk::ShaderStageFlags::COMPUTE),
+            vk::DescriptorSetLayoutBinding::default()
+                .binding(1)
+                .descriptor_type(vk::DescriptorType::STORAGE_IMAGE)
+                .descriptor_count(1)
+                .stage_flags(vk::ShaderStageFlags::COMPUTE),
+        ];
+        let count = command_buffer_count as u32;
+        unsafe {
+            let set_layout = self
+                .device
+                .create_descriptor_set_layout(
+                    &vk::DescriptorSetLayoutCreateInfo::default().bindings(&bindings),
+                    None,
+                )
+                .unwrap();
+            let pool = self
+                .device
+                .create_descriptor_pool(
+                    &vk::DescriptorPoolCreateInfo::default()
+                        .max_sets(count)
+                        .pool_sizes(&[
+                            vk::DescriptorPoolSize {
+                                ty: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+                                descriptor_count: count,
+                            },
+                            vk::DescriptorPoolSize {
+                                ty: vk::DescriptorType::STORAGE_IMAGE,
+                                descriptor_count: count,
+                            },
+                        ]),
+                    None,
+                )
+                .unwrap();
+            let set_layouts = vec![set_layout; command_buffer_count];
+            let sets = self
+                .device
+                .allocate_descriptor_sets(
+                    &vk::DescriptorSetAllocateInfo::default()
+                        .descriptor_pool(pool)
+                        .set_layouts(&set_layouts),
+                )
+                .unwrap();
+
+            // offset, extent, strength, transfer
+            let params = vk::PushConstantRange {
+                stage_flags: vk::ShaderStageFlags::COMPUTE,
+                offset: 0,
+                size: std::mem::size_of::<[u32; 6]>() as u32,
+            };
+            let layout = self
+                .device
+                .create_pipeline_layout(
+                    &vk::PipelineLayoutCreateInfo::default()
+                        .set_layouts(std::slice::from_ref(&set_layout))
+                        .push_constant_ranges(&[params]),
+                    None,
+                )
+                .unwrap();
+
+            let bytes = &include_bytes!(concat!(env!("OUT_DIR"), "/comp_sharpen.spv"))[..];
+            let module = self
+                .device
+                .create_shader_module(
+                    &vk::ShaderModuleCreateInfo::default()
+                        .code(&ash::util::read_spv(&mut std::io::Cursor::new(bytes)).unwrap()),
+                    None,
+                )
+                .unwrap();
+            let pipeline = self
+                .device
+                .create_compute_pipelines(
+                    vk::PipelineCache::null(),
+                    &[vk::ComputePipelineCreateInfo::default()
+                        .stage(
+                            vk::PipelineShaderStageCreateInfo::default()
+                                .stage(vk::ShaderStageFlags::COMPUTE)
+                                .module(module)
+                                .name(c"main"),
+                        )
+                        .layout(layout)],
+                    None,
+                )
+                .unwrap()[0];
+            self.device.destroy_shader_module(module, None);
+
+            let sampler = self
+                .device
+                .create_sampler(&vk::SamplerCreateInfo::default(), None)
+                .unwrap();
+
+            Some(SharpenData {
+                strength,
+                pipeline,
+                layout,
+                set_layout,
+                pool,
+                sampler,
+                sets,
+                game_views: Mutex::new(vec![vk::ImageView::null(); command_buffer_count]),
+                targets: Default::default(),
+            })
+        }
+    }
+
+    fn create_sharpen_target(&self, extent: vk::Extent3D) -> Option<SharpenTarget> {
+        unsafe {
+            let image = self
+                .device
+                .create_image(
+                    &vk::ImageCreateInfo::default()
+                        .image_type(vk::ImageType::TYPE_2D)
+                        .format(vk::Format::R16G16B16A16_SFLOAT)
+                        .extent(extent)
+                        .mip_levels(1)
+                        .array_layers(1)
+                        .samples(vk::SampleCountFlags::TYPE_1)
+                        .tiling(vk::ImageTiling::OPTIMAL)
+                        .usage(vk::ImageUsageFlags::STORAGE | vk::ImageUsageFlags::TRANSFER_SRC)
+                        .sharing_mode(vk::SharingMode::EXCLUSIVE)
+                        .initial_layout(vk::ImageLayout::UNDEFINED),
+                    None,
+                )
+                .unwrap();
+            let Some(memory) = self.allocate_memory(
+                self.device.get_image_memory_requirements(image),
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            ) else {
+                self.device.destroy_image(image, None);
+                return None;
+            };
+            self.device.bind_image_memory(image, memory, 0).unwrap();
+            let view = self
+                .device
+                .create_image_view(
+                    &vk::ImageViewCreateInfo::default()
+                        .image(image)
+                        .view_type(vk::ImageViewType::TYPE_2D)
+                        .format(vk::Format::R16G16B16A16_SFLOAT)
+                        .subresource_range(vk::ImageSubresourceRange {
+                            aspect_mask: vk::ImageAspectFlags::COLOR,
+                            base_mip_level: 0,
+                            level_count: 1,
+                            base_array_layer: 0,
+                            layer_count: 1,
+                        }),
+                    None,
+                )
+                .unwrap();
+
+            Some(SharpenTarget {
+                extent,
+                image,
+                memory,
+                view,
+            })
+        }
+    }
+
+    /// Points the descriptor set of the eye command buffer at the game's image and the eye's
+    /// intermediate image. None if the intermediate image couldn't be created, in which case the
+    /// eye is copied as usual.
+    #[allow(clippy::too_many_arguments)]
+    fn prepare_sharpen(
+        &self,
+        sharpen: &SharpenData,
+        command_buffer: usize,
+        eye: vr::EVREye,
+        game_image: vk::Image,
+        game_image_format: vk::Format,
+        game_format: vk::Format,
+        game_layer: u32,
+        extent: vk::Extent3D,
+    ) -> Option<SharpenPass> {
+        let mut targets = sharpen.targets.lock().unwrap();
+        let target = &mut targets[eye as usize];
+        if target.as_ref().is_some_and(|t| t.extent != extent) {
+            // The other eye's copies may still be using it.
+            unsafe { self.device.queue_wait_idle(self.queue).unwrap() };
+            target.take().unwrap().destroy(&self.device);
+        }
+        if target.is_none() {
+            *target = self.create_sharpen_target(extent);
+        }
+        let target = target.as_ref()?;
+
+        let game_view = unsafe {
+            self.device
+                .create_image_view(
+                    &vk::ImageViewCreateInfo::default()
+                        .image(game_image)
+                        .view_type(vk::ImageViewType::TYPE_2D)
+                        .format(game_image_format)
+                        .subresource_range(vk::ImageSubresourceRange {
+                            aspect_mask: vk::ImageAspectFlags::COLOR,
+                            base_mip_level: 0,
+                            level_count: 1,
+                            base_array_layer: game_layer,
+                            layer_count: 1,
+                        }),
+                    None,
+                )
+                .unwrap()
+        };
+        let old_view = std::mem::replace(
+            &mut sharpen.game_views.lock().unwrap()[command_buffer],
+            game_view,
+        );
+        let set = sharpen.sets[command_buffer];
+        unsafe {
+            self.device.destroy_image_view(old_view, None);
+            self.device.update_descriptor_sets(
+                &[
+                    vk::WriteDescriptorSet::default()
+                        .dst_set(set)
+                        .dst_binding(0)
+                        .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                        .image_info(&[vk::DescriptorImageInfo {
+                            sampler: sharpen.sampler,
+                            image_view: game_view,
+                            image_layout: vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                        }]),
+                    vk::WriteDescriptorSet::default()
+                        .dst_set(set)
+                        .dst_binding(1)
+                        .descriptor_type(vk::DescriptorType::STORAGE_IMAGE)
+                        .image_info(&[vk::DescriptorImageInfo {
+                            sampler: vk::Sampler::null(),
+                            image_view: target.view,
+                            image_layout: vk::ImageLayout::GENERAL,
+                        }]),
+                ],
+                &[],
+            );
+        }
+
+        // The intermediate image is linear, and blitting it encodes it again for sRGB swapchains.
+        let transfer = match (is_srgb(game_image_format), is_srgb(game_format)) {
+            (false, true) => 1,
+            (true, false) => 2,
+            _ => 0,
+        };
+        Some(SharpenPass {
+            set,
+            target: target.image,
+            transfer,
+        })
+    }
+
+    /// Records sharpening the game's image into the eye's intermediate image, leaving it ready to
+    /// be blitted to the swapchain.
+    fn record_sharpen(
+        &self,
+        buf: vk::CommandBuffer,
+        sharpen: &SharpenData,
+        pass: &SharpenPass,
+        offset: vk::Offset3D,
+        extent: vk::Extent3D,
+    ) {
+        let range = vk::ImageSubresourceRange {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            layer_count: 1,
+        };
+        let params = [
+            offset.x as u32,
+            offset.y as u32,
+            extent.width,
+            extent.height,
+            sharpen.strength.to_bits(),
+            pass.transfer,
+        ];
+        unsafe {
+            // The game only made its image ready for transfers, and the previous frame's blit
+            // has to be done reading the intermediate image.
+            self.device.cmd_pipeline_barrier(
+                buf,
+                vk::PipelineStageFlags::ALL_COMMANDS,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::DependencyFlags::empty(),
+                &[vk::MemoryBarrier {
+                    src_access_mask: vk::AccessFlags::MEMORY_WRITE,
+                    dst_access_mask: vk::AccessFlags::SHADER_READ,
+                    ..Default::default()
+                }],
+                &[],
+                &[vk::ImageMemoryBarrier {
+                    src_access_mask: vk::AccessFlags::TRANSFER_READ,
+                    dst_access_mask: vk::AccessFlags::SHADER_WRITE,
+                    old_layout: vk::ImageLayout::UNDEFINED,
+                    new_layout: vk::ImageLayout::GENERAL,
+                    image: pass.target,
+                    subresource_range: range,
+                    ..Default::default()
+                }],
+            );
+            self.device
+                .cmd_bind_pipeline(buf, vk::PipelineBindPoint::COMPUTE, sharpen.pipeline);
+            self.device.cmd_bind_descriptor_sets(
+                buf,
+                vk::PipelineBindPoint::COMPUTE,
+                sharpen.layout,
+                0,
+                &[pass.set],
+                &[],
+            );
+            self.device.cmd_push_constants(
+                buf,
+                sharpen.layout,
+                vk::ShaderStageFlags::COMPUTE,
+                0,
+                params.align_to().1,
+            );
+            self.device
+                .cmd_dispatch(buf, extent.width.div_ceil(8), extent.height.div_ceil(8), 1);
+            self.device.cmd_pipeline_barrier(
+                buf,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[vk::ImageMemoryBarrier {
+                    src_access_mask: vk::AccessFlags::SHADER_WRITE,
+                    dst_access_mask: vk::AccessFlags::TRANSFER_READ,
+                    old_layout: vk::ImageLayout::GENERAL,
+                    new_layout: vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                    image: pass.target,
+                    subresource_range: range,
+                    ..Default::default()
+                }],
+            );
+        }
+    }
+
+    fn allocate_memory(
+        &self,
+        requirements: vk::MemoryRequirements,
+        wanted: vk::MemoryPropertyFlags,
+    ) -> Option<vk::DeviceMemory> {
+        allocate_memory(
+            &self.instance,
+            self.physical_device,
+            &self.device,
+            requirements,
+            wanted,
+        )
+    }
+
+    fn create_readback_data(&self, extent: vk::Extent2D) -> Option<ReadbackData> {
+        unsafe {
+            let image = self
+                .device
+                .create_image(
+                    &vk::ImageCreateInfo::default()
+                        .image_type(vk::ImageType::TYPE_2D)
+                        .format(vk::Format::R8G8B8A8_SRGB)
+                        .extent(extent.into())
+                        .mip_levels(1)
+                        .array_layers(1)
+                        .samples(vk::SampleCountFlags::TYPE_1)
+                        .tiling(vk::ImageTiling::OPTIMAL)
+                        .usage(
+                            vk::ImageUsageFlags::TRANSFER_SRC | vk::ImageUsageFlags::TRANSFER_DST,
+                        )
+                        .sharing_mode(vk::SharingMode::EXCLUSIVE)
+                        .initial_layout(vk::ImageLayout::UNDEFINED),
+                    None,
+                )
+                .unwrap();
+            let Some(image_memory) = self.allocate_memory(
+                self.device.get_image_memory_requirements(image),
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            ) else {
+                self.device.destroy_image(image, None);
+                return None;
+            };
+            self.device
+                .bind_image_memory(image, image_memory, 0)
+                .unwrap();
+
+            let buffer = self
+                .device
+                .create_buffer(
+                    &vk::BufferCreateInfo::default()
+                        .size(extent.width as u64 * extent.height as u64 * 4)
+                        .usage(vk::BufferUsageFlags::TRANSFER_DST)
+                        .sharing_mode(vk::SharingMode::EXCLUSIVE),
+                    None,
+                )
+                .unwrap();
+            let Some(buffer_memory) = self.allocate_memory(
+                self.device.get_buffer_memory_requirements(buffer),
+                vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+            ) else {
+                self.device.destroy_buffer(buffer, None);
+                self.device.destroy_image(image, None);
+                self.device.free_memory(image_memory, None);
+                return None;
+            };
+            self.device
+                .bind_buffer_memory(buffer, buffer_memory, 0)
+                .unwrap();
+
+            let pool = self
+                .device
+                .create_command_pool(
+                    &vk::CommandPoolCreateInfo::default()
+                        .flags(vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER)
+                        .queue_family_index(self.queue_family_index),
+                    None,
+                )
+                .unwrap();
+            let buf = self
+                .device
+                .allocate_command_buffers(
+                    &vk::CommandBufferAllocateInfo::default()
+                        .command_pool(pool)
+                        .level(vk::CommandBufferLevel::PRIMARY)
+                        .command_buffer_count(1),
+                )
+                .unwrap()[0];
+            let fence = self
+                .device
+                .create_fence(&vk::FenceCreateInfo::default(), None)
+                .unwrap();
+
+            Some(ReadbackData {
+                extent,
+                image,
+                image_memory,
+                buffer,
+                buffer_memory,
+                pool,
+                buf,
+                fence,
+                pending: false,
+            })
+        }
+    }
+
+    /// Creates a command pool with two command buffers for each swapchain image, as we have to
+    /// copy 2 eyes per swapchain image.
+    fn create_eye_command_buffers(
+        &self,
+        image_count: usize,
+    ) -> (vk::CommandPool, Vec<vk::CommandBuffer>) {
+        let pool = unsafe {
+            self.device
+                .create_command_pool(
+                    &vk::CommandPoolCreateInfo::default()
+                        .flags(vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER)
+                        .queue_family_index(self.queue_family_index),
+                    None,
+                )
+                .unwrap()
+        };
+        let bufs = unsafe {
+            self.device
+                .allocate_command_buffers(
+                    &vk::CommandBufferAllocateInfo::default()
+                        .command_pool(pool)
+                        .level(vk::CommandBufferLevel::PRIMARY)
+                        .command_buffer_count(image_count as u32 * 2),
+                )
+                .unwrap()
+        };
+        (pool, bufs)
+    }
+
+    /// Creates the queries for timing eye copies, or returns None if the queue doesn't support
+    /// timestamps.
+    fn create_timestamp_data(&self, image_count: usize) -> Option<TimestampData> {
+        let families = unsafe {
+            self.instance
+                .get_physical_device_queue_family_properties(self.physical_device)
+        };
+        let valid_bits = families
+            .get(self.queue_family_index as usize)?
+            .timestamp_valid_bits;
+        if valid_bits == 0 {
+            return None;
+        }
+        let period = unsafe {
+            self.instance
+                .get_physical_device_properties(self.physical_device)
+        }
+        .limits
+        .timestamp_period;
+
+        let pool = unsafe {
+            self.device.create_query_pool(
+                &vk::QueryPoolCreateInfo::default()
+                    .query_type(vk::QueryType::TIMESTAMP)
+                    .query_count(image_count as u32 * 4),
+                None,
+            )
+        }
+        .inspect_err(|e| warn!("Failed to create timestamp query pool: {e}"))
+        .ok()?;
+
+        Some(TimestampData {
+            pool,
+            period: period as f64,
+            valid_mask: u64::MAX >> (64 - valid_bits.min(64)),
+        })
+    }
+
+    pub fn record_commands(&self, buf: vk::CommandBuffer, cmds: impl FnOnce()) {
+        self.record_commands_with_fence(buf, vk::Fence::null(), cmds);
+    }
+
+    /// Like [`Self::record_commands`], signaling `fence` once the commands complete.
+    fn record_commands_with_fence(
+        &self,
+        buf: vk::CommandBuffer,
+        fence: vk::Fence,
+        cmds: impl FnOnce(),
+    ) {
+        unsafe {
+            self.device
+                .begin_command_buffer(
+                    buf,
+                    &vk::CommandBufferBeginInfo::default()
+                        .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT),
+                )
+                .unwrap();
+        }
+
+        cmds();
+
+        unsafe {
+            self.device.end_command_buffer(buf).unwrap();
+
+            self.device
+                .queue_submit(
+                    self.queue,
+                    &[vk::SubmitInfo::default().command_buffers(&[buf])],
+                    fence,
+                )
+                .unwrap();
+        }
+    }
+
+    pub fn new(data: &vr::VRVulkanTextureData_t) -> Self {
+        let entry = new_entry();
+        let instance = unsafe {
+            ash::Instance::load(
+                entry.static_fn(),
+                vk::Instance::from_raw(data.m_pInstance as _),
+            )
+        };
+        let device = unsafe {
+            ash::Device::load(
+                instance.fp_v1_0(),
+                vk::Device::from_raw(data.m_pDevice as _),
+            )
+        };
+
+        Self {
+            _entry: entry,
+            instance,
+            physical_device: vk::PhysicalDevice::from_raw(data.m_pPhysicalDevice as _),
+            device,
+            queue: vk::Queue::from_raw(data.m_pQueue as _),
+            queue_family_index: data.m_nQueueFamilyIndex,
+            real_data: Default::default(),
+            depth_data: Default::default(),
+            mirror_data: Default::default(),
+            game_device: None,
+        }
+    }
+
+    /// The instance extensions games have to enable for the runtime. Any the Vulkan implementation
+    /// doesn't have are left out, asking for them would only make creating the instance fail.
+    pub fn required_instance_extensions(
+        xr_instance: &xr::Instance,
+        system_id: xr::SystemId,
+    ) -> xr::Result<String> {
+        log_graphics_requirements(xr_instance, system_id);
+        let exts = xr_instance.vulkan_legacy_instance_extensions(system_id)?;
+        let available: Option<HashSet<String>> =
+            unsafe { new_entry().enumerate_instance_extension_properties(None) }
+                .inspect_err(|e| warn!("Couldn't enumerate Vulkan instance extensions: {e}"))
+                .ok()
+                .map(|props| {
+                    props
+                        .iter()
+                        .filter_map(|props| props.extension_name_as_c_str().ok())
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .collect()
+                });
+        Ok(extension_list(&exts, |ext| {
+            let supported = available
+                .as_ref()
+                .is_none_or(|available| available.contains(ext));
+            if !supported {
+                warn!("Runtime wants instance extension {ext}, which Vulkan doesn't have");
+            }
+            supported
+        }))
+    }
+
+    /// The device extensions games have to enable for the runtime.
+    pub fn required_device_extensions(
+        xr_instance: &xr::Instance,
+        system_id: xr::SystemId,
+    ) -> xr::Result<String> {
+        log_graphics_requirements(xr_instance, system_id);
+        let exts = xr_instance.vulkan_legacy_device_extensions(system_id)?;
+        Ok(extension_list(&exts, |_| true))
+    }
+
+    pub fn new_temporary(xr_instance: &xr::Instance, system_id: xr::SystemId) -> Self {
+        let entry = new_entry();
 
-        Self {
-            _entry: entry,
-            instance,
-            physical_device: vk::PhysicalDevice::from_raw(data.m_pPhysicalDevice as _),
-            device,
-            queue: vk::Queue::from_raw(data.m_pQueue as _),
-            queue_family_index: data.m_nQueueFamilyIndex,
-            real_data: Default::default(),
-        }
-    }
-
-    pub fn new_temporary(xr_instance: &xr::Instance, system_id: xr::SystemId) -> Self {
-        let entry = new_entry();
-
         let inst_exts = xr_instance
             .vulkan_legacy_instance_extensions(system_id)
             .unwrap();
@@ -542,42 +2493,9 @@ impl VulkanData {
                 .vulkan_graphics_device(system_id, instance.handle().as_raw() as _)
                 .expect("Failed to get temporary Vulkan physical device") as _
         });
-        let dev_exts = xr_instance
-            .vulkan_legacy_device_extensions(system_id)
-            .unwrap();
-        let dev_exts: Vec<CString> = dev_exts
-            .split_ascii_whitespace()
-            .map(|ext| CString::new(ext).unwrap())
-            .collect();
-        let dev_exts: Vec<*const c_char> = dev_exts.iter().map(|ext| ext.as_ptr()).collect();
-
-        // find whatever graphics queue family
-        let queue_family_index =
-            unsafe { instance.get_physical_device_queue_family_properties(physical_device) }
-                .into_iter()
-                .enumerate()
-                .find_map(|(idx, family)| {
-                    (family.queue_flags.contains(vk::QueueFlags::GRAPHICS)).then_some(idx)
-                })
-                .unwrap() as u32;
-
-        let device = unsafe {
-            instance
-                .create_device(
-                    physical_device,
-                    &vk::DeviceCreateInfo::default()
-                        .queue_create_infos(std::slice::from_ref(
-                            &vk::DeviceQueueCreateInfo::default()
-                                .queue_family_index(queue_family_index)
-                                .queue_priorities(&[1.0]),
-                        ))
-                        .enabled_extension_names(&dev_exts),
-                    None,
-                )
-                .expect("Could not create temporary vulkan device")
-        };
-
-        let queue = unsafe { device.get_device_queue(queue_family_index, 0) };
+        let (device, queue, queue_family_index) =
+            create_runtime_device(&instance, physical_device, xr_instance, system_id)
+                .expect("Could not create temporary vulkan device");
 
         Self {
             _entry: entry,
@@ -587,6 +2505,9 @@ impl VulkanData {
             queue,
             queue_family_index,
             real_data: Default::default(),
+            depth_data: Default::default(),
+            mirror_data: Default::default(),
+            game_device: None,
         }
     }
 }
@@ -851,6 +2772,170 @@ impl PipelineData {
 }
 
 #[inline]
+fn allocate_memory(
+    instance: &ash::Instance,
+    physical_device: vk::PhysicalDevice,
+    device: &ash::Device,
+    requirements: vk::MemoryRequirements,
+    wanted: vk::MemoryPropertyFlags,
+) -> Option<vk::DeviceMemory> {
+    let memory_type = find_memory_type(instance, physical_device, requirements, wanted)?;
+    unsafe {
+        device
+            .allocate_memory(
+                &vk::MemoryAllocateInfo::default()
+                    .allocation_size(requirements.size)
+                    .memory_type_index(memory_type),
+                None,
+            )
+            .inspect_err(|e| warn!("Failed to allocate memory: {e}"))
+            .ok()
+    }
+}
+
+pub(super) fn find_memory_type(
+    instance: &ash::Instance,
+    physical_device: vk::PhysicalDevice,
+    requirements: vk::MemoryRequirements,
+    wanted: vk::MemoryPropertyFlags,
+) -> Option<u32> {
+    let memory_props = unsafe { instance.get_physical_device_memory_properties(physical_device) };
+    memory_props
+        .memory_types_as_slice()
+        .iter()
+        .enumerate()
+        .find_map(|(idx, ty)| {
+            (requirements.memory_type_bits & (1 << idx) != 0 && ty.property_flags.contains(wanted))
+                .then_some(idx as u32)
+        })
+}
+
+/// A buffer the CPU can map, for moving eyes between devices.
+fn create_host_buffer(
+    instance: &ash::Instance,
+    physical_device: vk::PhysicalDevice,
+    device: &ash::Device,
+    size: u64,
+    usage: vk::BufferUsageFlags,
+) -> Option<(vk::Buffer, vk::DeviceMemory)> {
+    unsafe {
+        let buffer = device
+            .create_buffer(
+                &vk::BufferCreateInfo::default()
+                    .size(size)
+                    .usage(usage)
+                    .sharing_mode(vk::SharingMode::EXCLUSIVE),
+                None,
+            )
+            .ok()?;
+        let Some(memory) = allocate_memory(
+            instance,
+            physical_device,
+            device,
+            device.get_buffer_memory_requirements(buffer),
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+        ) else {
+            device.destroy_buffer(buffer, None);
+            return None;
+        };
+        device.bind_buffer_memory(buffer, memory, 0).unwrap();
+        Some((buffer, memory))
+    }
+}
+
+/// Creates a device on the given physical device with the extensions the runtime needs, and
+/// returns it with a graphics queue and its family.
+fn create_runtime_device(
+    instance: &ash::Instance,
+    physical_device: vk::PhysicalDevice,
+    xr_instance: &xr::Instance,
+    system_id: xr::SystemId,
+) -> Result<(ash::Device, vk::Queue, u32), vk::Result> {
+    let dev_exts = xr_instance
+        .vulkan_legacy_device_extensions(system_id)
+        .unwrap();
+    let dev_exts: Vec<CString> = dev_exts
+        .split_ascii_whitespace()
+        .map(|ext| CString::new(ext).unwrap())
+        .collect();
+    let dev_exts: Vec<*const c_char> = dev_exts.iter().map(|ext| ext.as_ptr()).collect();
+
+    // find whatever graphics queue family
+    let queue_family_index =
+        unsafe { instance.get_physical_device_queue_family_properties(physical_device) }
+            .into_iter()
+            .enumerate()
+            .find_map(|(idx, family)| {
+                (family.queue_flags.contains(vk::QueueFlags::GRAPHICS)).then_some(idx)
+            })
+            .ok_or(vk::Result::ERROR_FEATURE_NOT_PRESENT)? as u32;
+
+    let device = unsafe {
+        instance.create_device(
+            physical_device,
+            &vk::DeviceCreateInfo::default()
+                .queue_create_infos(std::slice::from_ref(
+                    &vk::DeviceQueueCreateInfo::default()
+                        .queue_family_index(queue_family_index)
+                        .queue_priorities(&[1.0]),
+                ))
+                .enabled_extension_names(&dev_exts),
+            None,
+        )
+    }?;
+
+    let queue = unsafe { device.get_device_queue(queue_family_index, 0) };
+    Ok((device, queue, queue_family_index))
+}
+
+/// Logs the Vulkan versions the runtime supports, the first time games ask for extensions. OpenVR
+/// can't pass them on, but they explain session creation failing with newer versions.
+fn log_graphics_requirements(xr_instance: &xr::Instance, system_id: xr::SystemId) {
+    static LOGGED: Once = Once::new();
+    LOGGED.call_once(
+        || match xr_instance.graphics_requirements::<xr::Vulkan>(system_id) {
+            Ok(reqs) => info!(
+                "Runtime supports Vulkan {} to {}",
+                reqs.min_api_version_supported, reqs.max_api_version_supported
+            ),
+            Err(e) => warn!("Couldn't get Vulkan graphics requirements: {e}"),
+        },
+    );
+}
+
+/// Joins the extensions in a space separated list that `keep` returns true for, without
+/// duplicates.
+fn extension_list(exts: &str, mut keep: impl FnMut(&str) -> bool) -> String {
+    let mut seen = HashSet::new();
+    exts.split_ascii_whitespace()
+        .filter(|ext| seen.insert(*ext) && keep(*ext))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Bytes per texel of the color formats games submit, for copying eyes through buffers.
+fn texel_size(format: vk::Format) -> Option<u64> {
+    match format {
+        vk::Format::R8G8B8A8_UNORM
+        | vk::Format::R8G8B8A8_SRGB
+        | vk::Format::B8G8R8A8_UNORM
+        | vk::Format::B8G8R8A8_SRGB
+        | vk::Format::A2B10G10R10_UNORM_PACK32
+        | vk::Format::A2R10G10B10_UNORM_PACK32
+        | vk::Format::B10G11R11_UFLOAT_PACK32 => Some(4),
+        vk::Format::R16G16B16A16_UNORM | vk::Format::R16G16B16A16_SFLOAT => Some(8),
+        vk::Format::R32G32B32A32_SFLOAT => Some(16),
+        _ => None,
+    }
+}
+
+fn is_srgb(format: vk::Format) -> bool {
+    matches!(
+        format,
+        vk::Format::R8G8B8A8_SRGB | vk::Format::B8G8R8A8_SRGB | vk::Format::BC3_SRGB_BLOCK
+    )
+}
+
 fn get_colorspace_corrected_format(format: vk::Format, color_space: vr::EColorSpace) -> vk::Format {
     static UNSUPPORTED: LazyLock<Mutex<HashSet<vk::Format>>> = LazyLock::new(Mutex::default);
     // https://github.com/ValveSoftware/openvr/wiki/Vulkan#image-formats
@@ -859,6 +2944,13 @@ fn get_colorspace_corrected_format(format: vk::Format, color_space: vr::EColorSp
             vk::Format::R8G8B8A8_UNORM | vk::Format::R8G8B8A8_SRGB => vk::Format::R8G8B8A8_SRGB,
             vk::Format::B8G8R8A8_UNORM | vk::Format::B8G8R8A8_SRGB => vk::Format::B8G8R8A8_SRGB,
             vk::Format::BC3_SRGB_BLOCK => format,
+            // There are no sRGB variants of these, and Auto means linear for anything but 8 bits
+            // per component anyway.
+            vk::Format::A2B10G10R10_UNORM_PACK32
+            | vk::Format::A2R10G10B10_UNORM_PACK32
+            | vk::Format::B10G11R11_UFLOAT_PACK32
+            | vk::Format::R16G16B16A16_UNORM
+            | vk::Format::R16G16B16A16_SFLOAT => format,
             _ => {
                 if UNSUPPORTED.lock().unwrap().insert(format) {
                     warn!("Unhandled texture format: {format:?}");
@@ -866,7 +2958,57 @@ fn get_colorspace_corrected_format(format: vk::Format, color_space: vr::EColorSp
                 format
             }
         },
-        vr::EColorSpace::Linear => todo!("Linear colorspace not implemented yet"),
+        // The colors are already what they should be, so the image can be used as it is.
+        vr::EColorSpace::Linear => format,
+    }
+}
+
+/// Formats with at least the precision of `format`, for runtimes that don't support it. The copy
+/// to the swapchain blits between formats, so the component order doesn't matter.
+fn fallback_formats(format: vk::Format) -> &'static [vk::Format] {
+    use vk::Format as F;
+    match format {
+        F::R8G8B8A8_SRGB => &[F::B8G8R8A8_SRGB],
+        F::B8G8R8A8_SRGB => &[F::R8G8B8A8_SRGB],
+        F::R8G8B8A8_UNORM => &[F::B8G8R8A8_UNORM],
+        F::B8G8R8A8_UNORM => &[F::R8G8B8A8_UNORM],
+        F::A2B10G10R10_UNORM_PACK32 => &[
+            F::A2R10G10B10_UNORM_PACK32,
+            F::R16G16B16A16_UNORM,
+            F::R16G16B16A16_SFLOAT,
+        ],
+        F::A2R10G10B10_UNORM_PACK32 => &[
+            F::A2B10G10R10_UNORM_PACK32,
+            F::R16G16B16A16_UNORM,
+            F::R16G16B16A16_SFLOAT,
+        ],
+        F::B10G11R11_UFLOAT_PACK32 => &[F::R16G16B16A16_SFLOAT],
+        _ => &[],
+    }
+}
+
+/// The size of the eyes side by side, or None if it's empty.
+fn readback_extent(eyes: &[MirrorEye]) -> Option<vk::Extent2D> {
+    let extent = vk::Extent2D {
+        width: eyes.iter().map(|eye| eye.extent.width as u32).sum(),
+        height: eyes
+            .iter()
+            .map(|eye| eye.extent.height as u32)
+            .max()
+            .unwrap_or(0),
+    };
+    (extent.width > 0 && extent.height > 0).then_some(extent)
+}
+
+/// Barriers on depth/stencil images have to cover both aspects.
+fn depth_aspect(format: vk::Format) -> vk::ImageAspectFlags {
+    match format {
+        vk::Format::D16_UNORM_S8_UINT
+        | vk::Format::D24_UNORM_S8_UINT
+        | vk::Format::D32_SFLOAT_S8_UINT => {
+            vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL
+        }
+        _ => vk::ImageAspectFlags::DEPTH,
     }
 }
 
@@ -874,10 +3016,12 @@ fn texture_extent_from_bounds(
     texture: &vr::VRVulkanTextureData_t,
     bounds: vr::VRTextureBounds_t,
 ) -> (vk::Extent3D, vk::Offset3D) {
-    let width_min = bounds.uMin * texture.m_nWidth as f32;
-    let width_max = bounds.uMax * texture.m_nWidth as f32;
-    let height_min = bounds.vMin * texture.m_nHeight as f32;
-    let height_max = bounds.vMax * texture.m_nHeight as f32;
+    // Rounding the edges (rather than truncating the extent) keeps sub-rects of a texture shared
+    // between eyes from overlapping or leaving a gap.
+    let width_min = (bounds.uMin * texture.m_nWidth as f32).round();
+    let width_max = (bounds.uMax * texture.m_nWidth as f32).round();
+    let height_min = (bounds.vMin * texture.m_nHeight as f32).round();
+    let height_max = (bounds.vMax * texture.m_nHeight as f32).round();
 
     (
         vk::Extent3D {
@@ -1,24 +1,36 @@
 mod action_manifest;
+mod active_sets;
 mod custom_bindings;
+mod force_feedback;
+mod gestures;
+mod hot_reload;
 mod legacy;
+mod origins;
+mod pose_filter;
 mod profiles;
 mod skeletal;
 
 #[cfg(test)]
 mod tests;
 
-use profiles::MainAxisType;
+pub use gestures::{Gesture, GestureBinding};
+pub use pose_filter::PoseFilterConfig;
+use profiles::PoseComponent;
 pub use profiles::{InteractionProfile, Profiles};
+pub use skeletal::FingerSmoothingConfig;
 use skeletal::FingerState;
 use skeletal::SkeletalInputActionData;
 
 use crate::{
+    config::Config,
     openxr_data::{self, Hand, OpenXrData, SessionData},
+    osc_trackers::{self, OscTrackers},
     tracy_span, AtomicF32,
 };
 use custom_bindings::{BindingData, GrabActions};
+use glam::{Mat4, Quat, Vec3};
 use legacy::{setup_legacy_bindings, LegacyActionData};
-use log::{debug, info, trace, warn};
+use log::{debug, error, info, trace, warn};
 use openvr::{self as vr, space_relation_to_openvr_pose};
 use openxr as xr;
 use slotmap::{new_key_type, Key, KeyData, SecondaryMap, SlotMap};
@@ -29,6 +41,10 @@ use std::mem::ManuallyDrop;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex, OnceLock, RwLock};
 
+/// Gamepads are reported as a device of their own, after the OSC trackers.
+pub const GAMEPAD_DEVICE_INDEX: vr::TrackedDeviceIndex_t =
+    osc_trackers::FIRST_TRACKER_INDEX + osc_trackers::MAX_TRACKERS as u32;
+
 new_key_type! {
     struct InputSourceKey;
     struct ActionKey;
@@ -44,15 +60,41 @@ pub struct Input<C: openxr_data::Compositor> {
     input_source_map: RwLock<SlotMap<InputSourceKey, CString>>,
     left_hand_key: InputSourceKey,
     right_hand_key: InputSourceKey,
+    gamepad_key: InputSourceKey,
     action_map: RwLock<SlotMap<ActionKey, Action>>,
     set_map: RwLock<SlotMap<ActionSetKey, String>>,
     loaded_actions_path: OnceLock<PathBuf>,
+    /// Contents of the last action manifest that loaded successfully, to fall back to if an
+    /// edited one fails to load.
+    loaded_manifest: Mutex<Vec<u8>>,
+    bindings_watcher: hot_reload::BindingsWatcher,
     cached_poses: Mutex<CachedSpaces>,
     legacy_state: legacy::LegacyState,
-    skeletal_tracking_level: RwLock<vr::EVRSkeletalTrackingLevel>,
+    skeletal_tracking_level: [RwLock<vr::EVRSkeletalTrackingLevel>; 2],
     profile_map: HashMap<xr::Path, &'static profiles::ProfileProperties>,
+    /// Reported instead of the properties of the actual controllers, if controller emulation is
+    /// configured.
+    emulated_properties: Option<&'static profiles::ProfileProperties>,
     estimated_finger_state: [Mutex<FingerState>; 2],
     events: Mutex<VecDeque<InputEvent>>,
+    /// Binding list requested through ShowBindingsForActionSet/ShowActionOrigins, for the
+    /// compositor to show.
+    pending_bindings_view: Mutex<Option<Vec<String>>>,
+    active_sets: RwLock<active_sets::ActiveSets>,
+    /// Indexed by hand, then by whether the pose is seated.
+    pose_filters: Mutex<[[pose_filter::PoseFilter; 2]; 2]>,
+    gestures: Mutex<gestures::GestureState>,
+    /// Whether chords masked each digital action, by action and restricted device.
+    chord_masks: Mutex<HashMap<(vr::VRActionHandle_t, vr::VRInputValueHandle_t), ChordMask>>,
+}
+
+#[derive(Default)]
+struct ChordMask {
+    /// Sync this mask was last updated for.
+    packet_num: u32,
+    masked: bool,
+    /// Whether the action was masked at the sync before that.
+    was_masked: bool,
 }
 
 struct InputEvent {
@@ -94,6 +136,7 @@ impl<C: openxr_data::Compositor> Input<C> {
         let mut map = SlotMap::with_key();
         let left_hand_key = map.insert(c"/user/hand/left".into());
         let right_hand_key = map.insert(c"/user/hand/right".into());
+        let gamepad_key = map.insert(c"/user/gamepad".into());
         let profile_map = Profiles::get()
             .profiles_iter()
             .map(|profile| {
@@ -114,17 +157,31 @@ impl<C: openxr_data::Compositor> Input<C> {
             action_map: Default::default(),
             set_map: Default::default(),
             loaded_actions_path: OnceLock::new(),
+            loaded_manifest: Mutex::default(),
+            bindings_watcher: hot_reload::BindingsWatcher::new(),
             left_hand_key,
             right_hand_key,
+            gamepad_key,
             cached_poses: Mutex::default(),
             legacy_state: Default::default(),
-            skeletal_tracking_level: RwLock::new(vr::EVRSkeletalTrackingLevel::Estimated),
+            skeletal_tracking_level: [
+                RwLock::new(vr::EVRSkeletalTrackingLevel::Estimated),
+                RwLock::new(vr::EVRSkeletalTrackingLevel::Estimated),
+            ],
             profile_map,
+            emulated_properties: Profiles::get()
+                .emulated()
+                .map(|(_, profile)| profile.properties()),
             estimated_finger_state: [
                 Mutex::new(FingerState::new()),
                 Mutex::new(FingerState::new()),
             ],
             events: Mutex::default(),
+            pending_bindings_view: Mutex::default(),
+            active_sets: RwLock::default(),
+            pose_filters: Mutex::default(),
+            gestures: Mutex::default(),
+            chord_masks: Mutex::default(),
         }
     }
 
@@ -135,11 +192,51 @@ impl<C: openxr_data::Compositor> Input<C> {
             match InputSourceKey::from(KeyData::from_ffi(handle)) {
                 x if x == self.left_hand_key => Some(self.openxr.left_hand.subaction_path),
                 x if x == self.right_hand_key => Some(self.openxr.right_hand.subaction_path),
+                x if x == self.gamepad_key => Some(self.openxr.gamepad.subaction_path),
                 _ => None,
             }
         }
     }
 
+    /// The OSC tracker an input source handle refers to, if it's a tracker's device path.
+    fn tracker_from_handle(
+        &self,
+        handle: vr::VRInputValueHandle_t,
+    ) -> Option<vr::TrackedDeviceIndex_t> {
+        let map = self.input_source_map.read().unwrap();
+        let path = map.get(InputSourceKey::from(KeyData::from_ffi(handle)))?;
+        OscTrackers::index_for_device_path(path)
+    }
+
+    /// The profile of the device with the given subaction path, or of the first device that has
+    /// one if the path is null.
+    fn profile_for_subaction(
+        &self,
+        subaction: xr::Path,
+    ) -> Option<&'static dyn InteractionProfile> {
+        [
+            &self.openxr.left_hand,
+            &self.openxr.right_hand,
+            &self.openxr.gamepad,
+        ]
+        .into_iter()
+        .filter(|device| subaction == xr::Path::NULL || device.subaction_path == subaction)
+        .find_map(|device| *device.profile.lock().unwrap())
+    }
+
+    fn input_source_handle(&self, path: &CStr) -> vr::VRInputValueHandle_t {
+        let guard = self.input_source_map.read().unwrap();
+        match guard.iter().find(|(_, src)| src.as_c_str() == path) {
+            Some((key, _)) => key.data().as_ffi(),
+            None => {
+                drop(guard);
+                let mut guard = self.input_source_map.write().unwrap();
+                let key = guard.insert(path.into());
+                key.data().as_ffi()
+            }
+        }
+    }
+
     fn state_from_bindings_left_right(
         &self,
         action: vr::VRActionHandle_t,
@@ -194,11 +291,12 @@ impl<C: openxr_data::Compositor> Input<C> {
             .try_get_bindings(action, interaction_profile)
             .ok()?;
         let extra_data = loaded_actions.try_get_extra(action).ok()?;
+        let packet_num = self.legacy_state.packet_num();
 
         let mut best_state: Option<xr::ActionState<bool>> = None;
 
         for x in bindings.iter() {
-            let Ok(Some(state)) = x.state(&session, extra_data, subaction) else {
+            let Ok(Some(state)) = x.state(&session, extra_data, subaction, packet_num) else {
                 continue;
             };
 
@@ -215,6 +313,124 @@ impl<C: openxr_data::Compositor> Input<C> {
 
         best_state.map(|x| (x, restrict_to_device))
     }
+
+    /// Returns true if the action is masked by a held chord on the given device (or either hand).
+    fn masked_by_chord(
+        &self,
+        action: vr::VRActionHandle_t,
+        restrict_to_device: vr::VRInputValueHandle_t,
+    ) -> bool {
+        let Some(subaction) = self.subaction_path_from_handle(restrict_to_device) else {
+            return false;
+        };
+        let session = self.openxr.session_data.get();
+        let Some(loaded_actions) = session.input_data.get_loaded_actions() else {
+            return false;
+        };
+
+        let hands = if subaction == xr::Path::NULL {
+            vec![
+                self.openxr.left_hand.subaction_path,
+                self.openxr.right_hand.subaction_path,
+            ]
+        } else {
+            vec![subaction]
+        };
+
+        hands.into_iter().any(|hand| {
+            let Ok(interaction_profile) = session.session.current_interaction_profile(hand) else {
+                return false;
+            };
+            let Ok(bindings) = loaded_actions.try_get_bindings(action, interaction_profile) else {
+                return false;
+            };
+            bindings
+                .iter()
+                .any(|x| x.masked(&session, hand).unwrap_or(false))
+        })
+    }
+
+    /// Held bindings time presses from the sync they start on, so they're updated on every sync
+    /// whether or not the app reads their action.
+    fn update_held_bindings(&self, data: &SessionData, loaded_actions: &LoadedActions) {
+        let packet_num = self.legacy_state.packet_num();
+        for subaction in [
+            self.openxr.left_hand.subaction_path,
+            self.openxr.right_hand.subaction_path,
+        ] {
+            let Ok(interaction_profile) = data.session.current_interaction_profile(subaction)
+            else {
+                continue;
+            };
+            let Some(bindings) = loaded_actions
+                .per_profile_bindings
+                .get(&interaction_profile)
+            else {
+                continue;
+            };
+            for (action, bindings) in bindings {
+                let Some(extra_data) = loaded_actions.extra_actions.get(action) else {
+                    continue;
+                };
+                for binding in bindings {
+                    if matches!(binding, BindingData::Held(..)) {
+                        let _ = binding.state(data, extra_data, subaction, packet_num);
+                    }
+                }
+            }
+        }
+    }
+
+    fn scroll_state_from_bindings(
+        &self,
+        action: vr::VRActionHandle_t,
+        restrict_to_device: vr::VRInputValueHandle_t,
+    ) -> Option<(xr::ActionState<xr::Vector2f>, vr::VRInputValueHandle_t)> {
+        let subaction = self.subaction_path_from_handle(restrict_to_device)?;
+        if subaction == xr::Path::NULL {
+            let left = self.scroll_state_from_bindings(action, self.left_hand_key.0.as_ffi());
+            let right = self.scroll_state_from_bindings(action, self.right_hand_key.0.as_ffi());
+            let moving = |x: &Option<(xr::ActionState<xr::Vector2f>, _)>| {
+                x.as_ref()
+                    .is_some_and(|(s, _)| s.current_state.x != 0.0 || s.current_state.y != 0.0)
+            };
+            return if moving(&left) || (left.is_some() && !moving(&right)) {
+                left
+            } else {
+                right
+            };
+        }
+
+        let session = self.openxr.session_data.get();
+        let Ok(loaded_actions) = session.input_data.loaded_actions.get()?.read() else {
+            return None;
+        };
+
+        let interaction_profile = session
+            .session
+            .current_interaction_profile(subaction)
+            .ok()?;
+        let bindings = loaded_actions
+            .try_get_bindings(action, interaction_profile)
+            .ok()?;
+        let extra_data = loaded_actions.try_get_extra(action).ok()?;
+        let packet_num = self.legacy_state.packet_num();
+
+        bindings
+            .iter()
+            .filter_map(|x| {
+                x.scroll_state(&session, extra_data, subaction, packet_num)
+                    .ok()
+                    .flatten()
+            })
+            .max_by(|a, b| {
+                let len = |s: &xr::ActionState<xr::Vector2f>| {
+                    s.current_state.x.abs() + s.current_state.y.abs()
+                };
+                len(a).total_cmp(&len(b))
+            })
+            .map(|x| (x, restrict_to_device))
+    }
 }
 
 #[derive(Default)]
@@ -222,6 +438,8 @@ pub struct InputSessionData {
     loaded_actions: OnceLock<RwLock<LoadedActions>>,
     legacy_actions: OnceLock<LegacyActionData>,
     estimated_skeleton_actions: OnceLock<SkeletalInputActionData>,
+    force_feedback: OnceLock<Option<force_feedback::ForceFeedback>>,
+    gesture_trackers: OnceLock<Option<gestures::GestureTrackers>>,
 }
 
 impl InputSessionData {
@@ -230,12 +448,32 @@ impl InputSessionData {
         self.loaded_actions.get().map(|l| l.read().unwrap())
     }
 
+    fn get_force_feedback(
+        &self,
+        session: &xr::Session<xr::AnyGraphics>,
+        hand_tracking_supported: bool,
+    ) -> Option<&force_feedback::ForceFeedback> {
+        self.force_feedback
+            .get_or_init(|| force_feedback::ForceFeedback::new(session, hand_tracking_supported))
+            .as_ref()
+    }
+
+    fn get_gesture_trackers(
+        &self,
+        session: &xr::Session<xr::AnyGraphics>,
+        hand_tracking_supported: bool,
+    ) -> Option<&gestures::GestureTrackers> {
+        self.gesture_trackers
+            .get_or_init(|| gestures::GestureTrackers::new(session, hand_tracking_supported))
+            .as_ref()
+    }
+
     pub(crate) fn interaction_profile_changed(&self) {
         if let Some(legacy) = self.legacy_actions.get() {
             // If the interaction profile changes the offsets must be updated too
-            // Delete the current raw spaces so they can be recreated later
-            legacy.left_spaces.reset_raw();
-            legacy.right_spaces.reset_raw();
+            // Delete the current spaces so they can be recreated later
+            legacy.left_spaces.reset_spaces();
+            legacy.right_spaces.reset_spaces();
         }
     }
 }
@@ -249,6 +487,11 @@ enum ActionData {
         action: xr::Action<xr::Vector2f>,
         last_value: (AtomicF32, AtomicF32),
     },
+    /// OpenXR has no three component inputs, so only x and y can be bound and z is always 0.
+    Vector3 {
+        action: xr::Action<xr::Vector2f>,
+        last_value: (AtomicF32, AtomicF32),
+    },
     Pose,
     Skeleton {
         hand: Hand,
@@ -257,11 +500,30 @@ enum ActionData {
     Haptic(xr::Action<xr::Haptic>),
 }
 
+impl ActionData {
+    /// The OpenXR action, for actions that are bound to inputs.
+    fn raw_action(&self) -> Option<xr::sys::Action> {
+        match self {
+            ActionData::Bool(action) => Some(action.as_raw()),
+            ActionData::Vector1 { action, .. } => Some(action.as_raw()),
+            ActionData::Vector2 { action, .. } | ActionData::Vector3 { action, .. } => {
+                Some(action.as_raw())
+            }
+            ActionData::Haptic(action) => Some(action.as_raw()),
+            ActionData::Pose | ActionData::Skeleton { .. } => None,
+        }
+    }
+}
+
 #[derive(Default)]
 struct ExtraActionData {
     pub toggle_action: Option<xr::Action<bool>>,
+    pub held_action: Option<xr::Action<bool>>,
+    pub double_action: Option<xr::Action<bool>>,
+    pub repeat_action: Option<xr::Action<bool>>,
     pub analog_action: Option<xr::Action<f32>>,
     pub vector2_action: Option<xr::Action<xr::Vector2f>>,
+    pub touch_action: Option<xr::Action<bool>>,
     pub grab_action: Option<GrabActions>,
 }
 
@@ -278,6 +540,8 @@ enum BoundPoseType {
     Raw,
     /// Not sure why games still use this, but having it be equivalent to raw seems to work fine.
     Gdc2015,
+    /// One of the other pose components, offset from raw by the interaction profile.
+    Component(PoseComponent),
 }
 
 macro_rules! get_action_from_handle {
@@ -347,19 +611,37 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
     }
     fn ShowBindingsForActionSet(
         &self,
-        _: *mut vr::VRActiveActionSet_t,
-        _: u32,
-        _: u32,
+        sets: *mut vr::VRActiveActionSet_t,
+        set_size: u32,
+        set_count: u32,
         _: vr::VRInputValueHandle_t,
     ) -> vr::EVRInputError {
-        todo!()
+        if sets.is_null() || set_size as usize != std::mem::size_of::<vr::VRActiveActionSet_t>() {
+            return vr::EVRInputError::InvalidParam;
+        }
+        let sets = unsafe { std::slice::from_raw_parts(sets, set_count as usize) };
+        let mut lines = Vec::new();
+        for set in sets {
+            match self.bindings_view_lines(set.ulActionSet, None) {
+                Ok(set_lines) => lines.extend(set_lines),
+                Err(e) => return e,
+            }
+        }
+        *self.pending_bindings_view.lock().unwrap() = Some(lines);
+        vr::EVRInputError::None
     }
     fn ShowActionOrigins(
         &self,
-        _: vr::VRActionSetHandle_t,
-        _: vr::VRActionHandle_t,
+        set: vr::VRActionSetHandle_t,
+        action: vr::VRActionHandle_t,
     ) -> vr::EVRInputError {
-        todo!()
+        match self.bindings_view_lines(set, Some(action)) {
+            Ok(lines) => {
+                *self.pending_bindings_view.lock().unwrap() = Some(lines);
+                vr::EVRInputError::None
+            }
+            Err(e) => e,
+        }
     }
     fn GetActionBindingInfo(
         &self,
@@ -396,6 +678,7 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
         let index = match key {
             x if x == self.left_hand_key => Hand::Left as u32,
             x if x == self.right_hand_key => Hand::Right as u32,
+            x if x == self.gamepad_key => GAMEPAD_DEVICE_INDEX,
             _ => {
                 unsafe {
                     info.write(Default::default());
@@ -415,22 +698,48 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
     }
     fn GetOriginLocalizedName(
         &self,
-        _: vr::VRInputValueHandle_t,
-        _: *mut c_char,
-        _: u32,
-        _: i32,
+        origin: vr::VRInputValueHandle_t,
+        name_array: *mut c_char,
+        name_array_size: u32,
+        string_sections_to_include: i32,
     ) -> vr::EVRInputError {
-        crate::warn_unimplemented!("GetOriginLocalizedName");
+        let key = InputSourceKey::from(KeyData::from_ffi(origin));
+        let Some(path) = self.input_source_map.read().unwrap().get(key).cloned() else {
+            return vr::EVRInputError::InvalidHandle;
+        };
+
+        let name = CString::new(self.origin_localized_name(&path, string_sections_to_include))
+            .unwrap_or_default();
+        let name =
+            unsafe { std::slice::from_raw_parts(name.as_ptr(), name.to_bytes_with_nul().len()) };
+        if name_array.is_null() || (name_array_size as usize) < name.len() {
+            return vr::EVRInputError::BufferTooSmall;
+        }
+
+        let buf = unsafe { std::slice::from_raw_parts_mut(name_array, name.len()) };
+        buf.copy_from_slice(name);
         vr::EVRInputError::None
     }
     fn GetActionOrigins(
         &self,
         _: vr::VRActionSetHandle_t,
-        _: vr::VRActionHandle_t,
-        _: *mut vr::VRInputValueHandle_t,
-        _: u32,
+        action: vr::VRActionHandle_t,
+        origins_out: *mut vr::VRInputValueHandle_t,
+        origin_out_count: u32,
     ) -> vr::EVRInputError {
-        crate::warn_unimplemented!("GetActionOrigins");
+        if origins_out.is_null() {
+            return vr::EVRInputError::InvalidParam;
+        }
+        let origins = match self.action_origins(action) {
+            Ok(origins) => origins,
+            Err(e) => return e,
+        };
+
+        let out = unsafe { std::slice::from_raw_parts_mut(origins_out, origin_out_count as usize) };
+        out.fill(vr::k_ulInvalidInputValueHandle);
+        for (out, origin) in out.iter_mut().zip(origins) {
+            *out = origin;
+        }
         vr::EVRInputError::None
     }
     fn TriggerHapticVibrationAction(
@@ -442,8 +751,16 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
         amplitude: f32,
         restrict_to_device: vr::VRInputValueHandle_t,
     ) -> vr::EVRInputError {
+        let action_handle = action;
         get_action_from_handle!(self, action, session_data, action);
         let Some(subaction_path) = self.subaction_path_from_handle(restrict_to_device) else {
+            if let (ActionData::Haptic(_), Some(trackers), Some(index)) = (
+                &action,
+                OscTrackers::get(),
+                self.tracker_from_handle(restrict_to_device),
+            ) {
+                trackers.trigger_haptic(index, amplitude, duration_seconds, frequency);
+            }
             return vr::EVRInputError::None;
         };
 
@@ -455,12 +772,65 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
             warn!("start_seconds_from_now: {start_seconds_from_now}")
         }
 
+        for (hand, info) in [
+            (Hand::Left, &self.openxr.left_hand),
+            (Hand::Right, &self.openxr.right_hand),
+        ] {
+            if subaction_path != xr::Path::NULL && subaction_path != info.subaction_path {
+                continue;
+            }
+            let Ok(profile) = session_data
+                .session
+                .current_interaction_profile(info.subaction_path)
+            else {
+                continue;
+            };
+            let has_force_feedback =
+                loaded
+                    .try_get_bindings(action_handle, profile)
+                    .is_ok_and(|bindings| {
+                        bindings.iter().any(
+                        |b| matches!(b, BindingData::ForceFeedback(p) if *p == info.subaction_path),
+                    )
+                    });
+            if !has_force_feedback {
+                continue;
+            }
+            if let Some(force_feedback) = session_data
+                .input_data
+                .get_force_feedback(&session_data.session, self.openxr.hand_tracking_supported)
+            {
+                force_feedback.apply(
+                    hand,
+                    amplitude,
+                    std::time::Duration::from_secs_f32(duration_seconds.max(0.0)),
+                );
+            }
+        }
+
+        // Both hands should be using the same profile when the device isn't restricted.
+        let hand_info = if subaction_path == self.openxr.right_hand.subaction_path {
+            &self.openxr.right_hand
+        } else if subaction_path == self.openxr.gamepad.subaction_path {
+            &self.openxr.gamepad
+        } else {
+            &self.openxr.left_hand
+        };
+        let haptics = hand_info
+            .profile
+            .lock()
+            .unwrap()
+            .map(|profile| Config::get().haptics(profile.profile_path()))
+            .unwrap_or_default();
+        let (amplitude, duration_seconds, frequency) =
+            haptics.apply(amplitude, duration_seconds, frequency);
+
         action
             .apply_feedback(
                 &session_data.session,
                 subaction_path,
                 &xr::HapticVibration::new()
-                    .amplitude(amplitude.clamp(0.0, 1.0))
+                    .amplitude(amplitude)
                     .frequency(frequency)
                     .duration(xr::Duration::from_nanos((duration_seconds * 1e9) as _)),
             )
@@ -470,45 +840,98 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
     }
     fn DecompressSkeletalBoneData(
         &self,
-        _: *const std::os::raw::c_void,
-        _: u32,
-        _: vr::EVRSkeletalTransformSpace,
-        _: *mut vr::VRBoneTransform_t,
-        _: u32,
+        compressed_buffer: *const std::os::raw::c_void,
+        compressed_buffer_size: u32,
+        transform_space: vr::EVRSkeletalTransformSpace,
+        transform_array: *mut vr::VRBoneTransform_t,
+        transform_array_count: u32,
     ) -> vr::EVRInputError {
-        todo!()
+        if compressed_buffer.is_null() || transform_array.is_null() {
+            return vr::EVRInputError::InvalidParam;
+        }
+        if transform_array_count != skeletal::HandSkeletonBone::Count as u32 {
+            return vr::EVRInputError::InvalidBoneCount;
+        }
+
+        let data = unsafe {
+            std::slice::from_raw_parts(
+                compressed_buffer as *const u8,
+                compressed_buffer_size as usize,
+            )
+        };
+        let transforms = unsafe {
+            std::slice::from_raw_parts_mut(transform_array, transform_array_count as usize)
+        };
+
+        match skeletal::decompress_bones(data, transform_space, transforms) {
+            Some(()) => vr::EVRInputError::None,
+            None => vr::EVRInputError::InvalidCompressedData,
+        }
     }
     fn GetSkeletalBoneDataCompressed(
         &self,
-        _: vr::VRActionHandle_t,
-        _: vr::EVRSkeletalMotionRange,
-        _: *mut std::os::raw::c_void,
-        _: u32,
-        _: *mut u32,
+        handle: vr::VRActionHandle_t,
+        motion_range: vr::EVRSkeletalMotionRange,
+        compressed_data: *mut std::os::raw::c_void,
+        compressed_size: u32,
+        required_compressed_size: *mut u32,
     ) -> vr::EVRInputError {
-        todo!()
+        let hand = {
+            get_action_from_handle!(self, handle, session_data, action);
+            let ActionData::Skeleton { hand, .. } = action else {
+                return vr::EVRInputError::WrongType;
+            };
+            *hand
+        };
+
+        let mut transforms =
+            [vr::VRBoneTransform_t::default(); skeletal::HandSkeletonBone::Count as usize];
+        let ret = self.GetSkeletalBoneData(
+            handle,
+            vr::EVRSkeletalTransformSpace::Parent,
+            motion_range,
+            transforms.as_mut_ptr(),
+            transforms.len() as u32,
+        );
+        if ret != vr::EVRInputError::None {
+            return ret;
+        }
+
+        let data = skeletal::compress_bones(hand, &transforms);
+        if let Some(required) = unsafe { required_compressed_size.as_mut() } {
+            *required = data.len() as u32;
+        }
+
+        if compressed_data.is_null() || (compressed_size as usize) < data.len() {
+            return vr::EVRInputError::BufferTooSmall;
+        }
+
+        let buf = unsafe { std::slice::from_raw_parts_mut(compressed_data as *mut u8, data.len()) };
+        buf.copy_from_slice(&data);
+        vr::EVRInputError::None
     }
     fn GetSkeletalSummaryData(
         &self,
         action: vr::VRActionHandle_t,
-        _: vr::EVRSummaryType,
+        summary_type: vr::EVRSummaryType,
         data: *mut vr::VRSkeletalSummaryData_t,
     ) -> vr::EVRInputError {
-        crate::warn_unimplemented!("GetSkeletalSummaryData");
-        get_action_from_handle!(self, action, session_data, _action);
-        unsafe {
-            data.write(vr::VRSkeletalSummaryData_t {
-                flFingerSplay: [0.2; 4],
-                flFingerCurl: [0.0; 5],
-            })
-        }
+        let Some(data) = (unsafe { data.as_mut() }) else {
+            return vr::EVRInputError::InvalidParam;
+        };
+        get_action_from_handle!(self, action, session_data, action);
+        let ActionData::Skeleton { hand, hand_tracker } = action else {
+            return vr::EVRInputError::WrongType;
+        };
+
+        *data = self.get_summary_data(&session_data, summary_type, hand_tracker.as_ref(), *hand);
         vr::EVRInputError::None
     }
     fn GetSkeletalBoneData(
         &self,
         handle: vr::VRActionHandle_t,
         transform_space: vr::EVRSkeletalTransformSpace,
-        _motion_range: vr::EVRSkeletalMotionRange,
+        motion_range: vr::EVRSkeletalMotionRange,
         transform_array: *mut vr::VRBoneTransform_t,
         transform_array_count: u32,
     ) -> vr::EVRInputError {
@@ -525,18 +948,14 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
             return vr::EVRInputError::WrongType;
         };
 
-        if let Some(hand_tracker) = hand_tracker.as_ref() {
-            self.get_bones_from_hand_tracking(
-                &self.openxr,
-                &session_data,
-                transform_space,
-                hand_tracker,
-                *hand,
-                transforms,
-            )
-        } else {
-            self.get_estimated_bones(&session_data, transform_space, *hand, transforms);
-        }
+        self.get_bones(
+            &session_data,
+            transform_space,
+            motion_range,
+            hand_tracker.as_ref(),
+            *hand,
+            transforms,
+        );
 
         vr::EVRInputError::None
     }
@@ -555,14 +974,23 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
             vr::ETrackedDeviceProperty::ControllerType_String,
         );
 
-        unsafe {
-            // Make sure knuckles are always Partial
+        let tracked = *self.skeletal_tracking_level[*hand as usize - 1]
+            .read()
+            .unwrap();
+        let detected = match tracked {
+            // The last skeleton came from real hand tracking.
+            vr::EVRSkeletalTrackingLevel::Full => tracked,
+            // Knuckles track the curl of every finger, which is more than an estimate.
             // TODO: Remove in favor of using XR_EXT_hand_tracking_data_source
-            if controller_type == Some(c"knuckles") {
-                *level = vr::EVRSkeletalTrackingLevel::Partial;
-            } else {
-                *level = *self.skeletal_tracking_level.read().unwrap();
-            }
+            _ if controller_type == Some(c"knuckles") => vr::EVRSkeletalTrackingLevel::Partial,
+            _ => tracked,
+        };
+
+        unsafe {
+            *level = Config::get()
+                .skeletal_tracking_level()
+                .map(Into::into)
+                .unwrap_or(detected);
         }
         vr::EVRInputError::None
     }
@@ -595,20 +1023,57 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
     }
     fn GetBoneName(
         &self,
-        _: vr::VRActionHandle_t,
-        _: vr::BoneIndex_t,
-        _: *mut c_char,
-        _: u32,
+        handle: vr::VRActionHandle_t,
+        bone_index: vr::BoneIndex_t,
+        bone_name: *mut c_char,
+        bone_name_size: u32,
     ) -> vr::EVRInputError {
-        todo!()
+        get_action_from_handle!(self, handle, session_data, action);
+        let ActionData::Skeleton { hand, .. } = action else {
+            return vr::EVRInputError::WrongType;
+        };
+
+        let Some(bone) = usize::try_from(bone_index)
+            .ok()
+            .and_then(skeletal::HandSkeletonBone::from_index)
+        else {
+            return vr::EVRInputError::InvalidBoneIndex;
+        };
+
+        let name = CString::new(bone.name(*hand)).unwrap();
+        let name =
+            unsafe { std::slice::from_raw_parts(name.as_ptr(), name.to_bytes_with_nul().len()) };
+        if bone_name.is_null() || (bone_name_size as usize) < name.len() {
+            return vr::EVRInputError::BufferTooSmall;
+        }
+
+        let buf = unsafe { std::slice::from_raw_parts_mut(bone_name, name.len()) };
+        buf.copy_from_slice(name);
+        vr::EVRInputError::None
     }
     fn GetBoneHierarchy(
         &self,
-        _: vr::VRActionHandle_t,
-        _: *mut vr::BoneIndex_t,
-        _: u32,
+        handle: vr::VRActionHandle_t,
+        parent_indices: *mut vr::BoneIndex_t,
+        index_array_count: u32,
     ) -> vr::EVRInputError {
-        todo!()
+        get_action_from_handle!(self, handle, session_data, action);
+        if !matches!(action, ActionData::Skeleton { .. }) {
+            return vr::EVRInputError::WrongType;
+        }
+
+        if parent_indices.is_null() || index_array_count != skeletal::HandSkeletonBone::Count as u32
+        {
+            return vr::EVRInputError::InvalidParam;
+        }
+
+        let parents =
+            unsafe { std::slice::from_raw_parts_mut(parent_indices, index_array_count as usize) };
+        for (parent, bone) in parents.iter_mut().zip(skeletal::HandSkeletonBone::ALL) {
+            *parent = bone.parent().map(|p| p as vr::BoneIndex_t).unwrap_or(-1);
+        }
+
+        vr::EVRInputError::None
     }
     fn GetBoneCount(&self, handle: vr::VRActionHandle_t, count: *mut u32) -> vr::EVRInputError {
         get_action_from_handle!(self, handle, session_data, action);
@@ -674,143 +1139,41 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
         action_data_size: u32,
         restrict_to_device: vr::VRInputValueHandle_t,
     ) -> vr::EVRInputError {
-        assert_eq!(
-            action_data_size as usize,
-            std::mem::size_of::<vr::InputPoseActionData_t>()
-        );
-
-        if log::log_enabled!(log::Level::Trace) {
-            let action_map = self.action_map.read().unwrap();
-            let action_key = ActionKey::from(KeyData::from_ffi(action));
-            let input_map = self.input_source_map.read().unwrap();
-            let input_key = InputSourceKey::from(KeyData::from_ffi(restrict_to_device));
-            trace!(
-                "getting pose for {:?} (restrict: {:?})",
-                action_map.get(action_key).map(|a| &a.path),
-                input_map.get(input_key)
-            );
-        }
-
-        let data = self.openxr.session_data.get();
-        let Some(loaded) = data.input_data.get_loaded_actions() else {
-            return vr::EVRInputError::InvalidHandle;
-        };
-
-        macro_rules! no_data {
-            () => {{
-                unsafe {
-                    action_data.write(Default::default());
-                }
-                return vr::EVRInputError::None;
-            }};
-        }
-        let subaction_path = get_subaction_path!(self, restrict_to_device, action_data);
-        let (active_origin, hand) = match loaded.try_get_action(action) {
-            Ok(ActionData::Pose) => {
-                let (mut hand, interaction_profile) = match subaction_path {
-                    x if x == self.openxr.left_hand.subaction_path => (
-                        Some(Hand::Left),
-                        Some(self.openxr.left_hand.profile_path.load()),
-                    ),
-                    x if x == self.openxr.right_hand.subaction_path => (
-                        Some(Hand::Right),
-                        Some(self.openxr.right_hand.profile_path.load()),
-                    ),
-                    x if x == xr::Path::NULL => (None, None),
-                    _ => unreachable!(),
-                };
-
-                let get_first_bound_hand_profile = || {
-                    loaded
-                        .try_get_pose(action, self.openxr.left_hand.profile_path.load())
-                        .or_else(|_| {
-                            loaded.try_get_pose(action, self.openxr.right_hand.profile_path.load())
-                        })
-                        .ok()
-                };
-
-                let Some(bound) = interaction_profile
-                    .and_then(|p| loaded.try_get_pose(action, p).ok())
-                    .or_else(get_first_bound_hand_profile)
-                else {
-                    match hand {
-                        Some(hand) => {
-                            trace!("action has no bindings for the {hand:?} hand's interaction profile");
-                        }
-                        None => {
-                            trace!("action has no bindings for either hand's interaction profile");
-                        }
-                    }
-
-                    no_data!()
-                };
-
-                let origin = hand.is_some().then_some(restrict_to_device);
-                let pose_type = match hand {
-                    Some(Hand::Left) => bound.left,
-                    Some(Hand::Right) => bound.right,
-                    None => {
-                        hand = Some(Hand::Left);
-                        bound.left.or_else(|| {
-                            hand = Some(Hand::Right);
-                            bound.right
-                        })
-                    }
-                };
-
-                let Some(ty) = pose_type else {
-                    trace!("action has no bindings for the hand {:?}", hand);
-                    no_data!()
-                };
-
-                let hand = hand.unwrap();
-                let origin = origin.unwrap_or_else(|| match hand {
-                    Hand::Left => self.left_hand_key.data().as_ffi(),
-                    Hand::Right => self.right_hand_key.data().as_ffi(),
-                });
-
-                match ty {
-                    BoundPoseType::Raw | BoundPoseType::Gdc2015 => (origin, hand),
-                }
-            }
-            Ok(ActionData::Skeleton { hand, .. }) => {
-                if subaction_path != xr::Path::NULL {
-                    return vr::EVRInputError::InvalidDevice;
-                }
-                (0, *hand)
-            }
-            Ok(_) => return vr::EVRInputError::WrongType,
-            Err(e) => return e,
-        };
-
-        drop(loaded);
-        drop(data);
-        unsafe {
-            action_data.write(vr::InputPoseActionData_t {
-                bActive: true,
-                activeOrigin: active_origin,
-                pose: self.get_controller_pose(hand, Some(origin)).expect("wtf"),
-            })
-        }
-
-        vr::EVRInputError::None
-    }
+        self.get_pose_action_data(
+            action,
+            origin,
+            action_data,
+            action_data_size,
+            restrict_to_device,
+            None,
+        )
+    }
 
     fn GetPoseActionDataRelativeToNow(
         &self,
         action: vr::VRActionHandle_t,
         origin: vr::ETrackingUniverseOrigin,
-        _seconds_from_now: f32,
+        seconds_from_now: f32,
         action_data: *mut vr::InputPoseActionData_t,
         action_data_size: u32,
         restrict_to_device: vr::VRInputValueHandle_t,
     ) -> vr::EVRInputError {
-        self.GetPoseActionDataForNextFrame(
+        // Without a way to get the runtime's current time, the next frame's display time is the
+        // closest estimate of it.
+        let now = self.openxr.now().unwrap_or_else(|| {
+            crate::warn_once!("Runtime can't convert the current time, using the display time");
+            self.openxr.display_time.get()
+        });
+        let time = Some(xr::Time::from_nanos(
+            now.as_nanos() + (seconds_from_now * 1e9) as i64,
+        ));
+        self.get_pose_action_data(
             action,
             origin,
             action_data,
             action_data_size,
             restrict_to_device,
+            time,
         )
     }
 
@@ -828,6 +1191,10 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
 
         let mut out = WriteOnDrop::new(action_data);
         get_action_from_handle!(self, handle, session_data, action, loaded);
+        let Some(restrict_to_device) = self.restrict_to_active_sets(handle, restrict_to_device)
+        else {
+            return vr::EVRInputError::None;
+        };
         let subaction_path = get_subaction_path!(self, restrict_to_device, action_data);
 
         let mut active_hand = restrict_to_device;
@@ -875,11 +1242,38 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
                     delta,
                 )
             }
-            ActionData::Vector2 { action, last_value } => {
-                let state = action.state(&session_data.session, subaction_path).unwrap();
+            ActionData::Vector2 {
+                action,
+                last_value: (last_x, last_y),
+            }
+            | ActionData::Vector3 {
+                action,
+                last_value: (last_x, last_y),
+            } => {
+                let mut state = action.state(&session_data.session, subaction_path).unwrap();
+                let thumbsticks = self
+                    .profile_for_subaction(subaction_path)
+                    .map(|profile| Config::get().thumbsticks(profile.profile_path()))
+                    .unwrap_or_default();
+                (state.current_state.x, state.current_state.y) =
+                    thumbsticks.apply(state.current_state.x, state.current_state.y);
+
+                // Scroll bindings take over while they're producing movement.
+                if let Some((scroll_state, scroll_source)) =
+                    self.scroll_state_from_bindings(handle, restrict_to_device)
+                {
+                    if !state.is_active
+                        || scroll_state.current_state.x != 0.0
+                        || scroll_state.current_state.y != 0.0
+                    {
+                        state = scroll_state;
+                        active_hand = scroll_source;
+                    }
+                }
+
                 let delta = xr::Vector2f {
-                    x: state.current_state.x - last_value.0.swap(state.current_state.x),
-                    y: state.current_state.y - last_value.1.swap(state.current_state.y),
+                    x: state.current_state.x - last_x.swap(state.current_state.x),
+                    y: state.current_state.y - last_y.swap(state.current_state.y),
                 };
                 (state, delta)
             }
@@ -914,6 +1308,10 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
         let mut out = WriteOnDrop::new(action_data);
 
         get_action_from_handle!(self, handle, session_data, action);
+        let Some(restrict_to_device) = self.restrict_to_active_sets(handle, restrict_to_device)
+        else {
+            return vr::EVRInputError::None;
+        };
         let subaction_path = get_subaction_path!(self, restrict_to_device, action_data);
         let ActionData::Bool(action) = &action else {
             return vr::EVRInputError::WrongType;
@@ -933,6 +1331,38 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
             }
         }
 
+        if let Some((gesture_state, gesture_hand)) =
+            self.state_from_gestures(handle, restrict_to_device)
+        {
+            if !state.current_state
+                && (gesture_state.current_state || gesture_state.changed_since_last_sync)
+                || !state.is_active
+            {
+                state = gesture_state;
+                active_hand = gesture_hand;
+            }
+        }
+
+        let masked = state.current_state && self.masked_by_chord(handle, restrict_to_device);
+        let was_masked = {
+            let packet_num = self.legacy_state.packet_num();
+            let mut masks = self.chord_masks.lock().unwrap();
+            let mask = masks.entry((handle, restrict_to_device)).or_default();
+            if mask.packet_num != packet_num {
+                mask.was_masked = mask.masked && mask.packet_num == packet_num.wrapping_sub(1);
+                mask.packet_num = packet_num;
+            }
+            mask.masked = masked;
+            mask.was_masked
+        };
+        if masked || was_masked {
+            // The change has to be relative to what was reported at the last sync, which was
+            // false if the action was masked.
+            let last_reported = state.current_state != state.changed_since_last_sync && !was_masked;
+            state.current_state &= !masked;
+            state.changed_since_last_sync = state.current_state != last_reported;
+        }
+
         *out.value = vr::InputDigitalActionData_t {
             bActive: state.is_active,
             bState: state.current_state,
@@ -962,13 +1392,6 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
         let active_sets =
             unsafe { std::slice::from_raw_parts(active_sets, active_set_count as usize) };
 
-        if active_sets
-            .iter()
-            .any(|set| set.ulRestrictedToDevice != vr::k_ulInvalidInputValueHandle)
-        {
-            crate::warn_once!("Per device action set restriction is not implemented yet.");
-        }
-
         let data = self.openxr.session_data.get();
         let Some(actions) = data.input_data.get_loaded_actions() else {
             return vr::EVRInputError::InvalidParam;
@@ -1000,6 +1423,9 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
             tracy_span!("xrSyncActions");
             data.session.sync_actions(&sync_sets).unwrap();
         }
+        self.update_active_sets(&data, &actions, &set_map, active_sets);
+        self.update_held_bindings(&data, &actions);
+        self.update_gestures(&data);
 
         vr::EVRInputError::None
     }
@@ -1011,18 +1437,7 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
     ) -> vr::EVRInputError {
         let path = unsafe { CStr::from_ptr(input_source_path) };
 
-        let ret = {
-            let guard = self.input_source_map.read().unwrap();
-            match guard.iter().find(|(_, src)| src.as_c_str() == path) {
-                Some((key, _)) => key.data().as_ffi(),
-                None => {
-                    drop(guard);
-                    let mut guard = self.input_source_map.write().unwrap();
-                    let key = guard.insert(path.into());
-                    key.data().as_ffi()
-                }
-            }
-        };
+        let ret = self.input_source_handle(path);
         if let Some(handle) = unsafe { handle.as_mut() } {
             debug!("requested handle for path {path:?}: {ret}");
             *handle = ret;
@@ -1164,6 +1579,24 @@ impl<C: openxr_data::Compositor> Input<C> {
                 .get_controller_pose(Hand::Right, origin)
                 .unwrap_or_default();
         }
+
+        if let Some(trackers) = OscTrackers::get() {
+            let data = self.openxr.session_data.get();
+            let display_time = self.openxr.display_time.get();
+            for (index, pose) in poses
+                .iter_mut()
+                .enumerate()
+                .skip(osc_trackers::FIRST_TRACKER_INDEX as usize)
+                .take(osc_trackers::MAX_TRACKERS)
+            {
+                *pose = trackers.get_pose(
+                    index as vr::TrackedDeviceIndex_t,
+                    &data,
+                    display_time,
+                    origin.unwrap_or(data.current_origin),
+                );
+            }
+        }
     }
 
     fn get_hmd_pose(&self, origin: Option<vr::ETrackingUniverseOrigin>) -> vr::TrackedDevicePose_t {
@@ -1181,6 +1614,37 @@ impl<C: openxr_data::Compositor> Input<C> {
             .unwrap()
     }
 
+    /// Where a controller is pointing, along -Z. Returns None if legacy actions haven't been set
+    /// up yet.
+    pub fn get_aim_pose(
+        &self,
+        hand: Hand,
+        origin: vr::ETrackingUniverseOrigin,
+    ) -> Option<vr::TrackedDevicePose_t> {
+        let mut pose = self.get_controller_pose(hand, Some(origin))?;
+        if let Some(offset) = self.pose_component_offset(hand, PoseComponent::Tip) {
+            offset_pose(&mut pose, offset);
+        }
+        Some(pose)
+    }
+
+    /// The offset of a pose component from the raw pose, for the hand's current profile.
+    fn pose_component_offset(&self, hand: Hand, component: PoseComponent) -> Option<Mat4> {
+        let profile = match hand {
+            Hand::Left => &self.openxr.left_hand.profile,
+            Hand::Right => &self.openxr.right_hand.profile,
+        };
+        let profile = (*profile.lock().unwrap())?;
+        let mut offset = profile.offset_pose_component(hand, component);
+        if component == PoseComponent::Tip {
+            offset *= Config::get()
+                .pose_offsets(profile.profile_path(), hand)
+                .aim
+                .matrix();
+        }
+        Some(offset)
+    }
+
     /// Returns None if legacy actions haven't been set up yet.
     pub fn get_controller_pose(
         &self,
@@ -1190,19 +1654,212 @@ impl<C: openxr_data::Compositor> Input<C> {
         tracy_span!();
         let mut spaces = self.cached_poses.lock().unwrap();
         let data = self.openxr.session_data.get();
-        spaces.get_pose_impl(
-            &self.openxr,
-            &data,
-            self.openxr.display_time.get(),
-            Some(hand),
-            origin.unwrap_or(data.current_origin),
-        )
+        let origin = origin.unwrap_or(data.current_origin);
+        let time = self.openxr.display_time.get();
+        let pose = spaces.get_pose_impl(&self.openxr, &data, time, Some(hand), origin)?;
+        let Some(config) = &Config::get().pose_filter else {
+            return Some(pose);
+        };
+        let seated = origin == vr::ETrackingUniverseOrigin::Seated;
+        let mut filters = self.pose_filters.lock().unwrap();
+        let filter = &mut filters[hand as usize - 1][seated as usize];
+        Some(filter.apply(config, time, pose))
+    }
+
+    /// Gets the pose at the given time, or the next frame's display time if there is none.
+    fn get_pose_action_data(
+        &self,
+        action: vr::VRActionHandle_t,
+        origin: vr::ETrackingUniverseOrigin,
+        action_data: *mut vr::InputPoseActionData_t,
+        action_data_size: u32,
+        restrict_to_device: vr::VRInputValueHandle_t,
+        time: Option<xr::Time>,
+    ) -> vr::EVRInputError {
+        assert_eq!(
+            action_data_size as usize,
+            std::mem::size_of::<vr::InputPoseActionData_t>()
+        );
+
+        if log::log_enabled!(log::Level::Trace) {
+            let action_map = self.action_map.read().unwrap();
+            let action_key = ActionKey::from(KeyData::from_ffi(action));
+            let input_map = self.input_source_map.read().unwrap();
+            let input_key = InputSourceKey::from(KeyData::from_ffi(restrict_to_device));
+            trace!(
+                "getting pose for {:?} (restrict: {:?})",
+                action_map.get(action_key).map(|a| &a.path),
+                input_map.get(input_key)
+            );
+        }
+
+        let data = self.openxr.session_data.get();
+        let Some(loaded) = data.input_data.get_loaded_actions() else {
+            return vr::EVRInputError::InvalidHandle;
+        };
+
+        macro_rules! no_data {
+            () => {{
+                unsafe {
+                    action_data.write(Default::default());
+                }
+                return vr::EVRInputError::None;
+            }};
+        }
+        let Some(restrict_to_device) = self.restrict_to_active_sets(action, restrict_to_device)
+        else {
+            no_data!()
+        };
+        let subaction_path = get_subaction_path!(self, restrict_to_device, action_data);
+        let (active_origin, hand, component) = match loaded.try_get_action(action) {
+            Ok(ActionData::Pose) => {
+                let (mut hand, interaction_profile) = match subaction_path {
+                    x if x == self.openxr.left_hand.subaction_path => (
+                        Some(Hand::Left),
+                        Some(self.openxr.left_hand.profile_path.load()),
+                    ),
+                    x if x == self.openxr.right_hand.subaction_path => (
+                        Some(Hand::Right),
+                        Some(self.openxr.right_hand.profile_path.load()),
+                    ),
+                    x if x == xr::Path::NULL => (None, None),
+                    // Gamepads don't have poses.
+                    _ => no_data!(),
+                };
+
+                let get_first_bound_hand_profile = || {
+                    loaded
+                        .try_get_pose(action, self.openxr.left_hand.profile_path.load())
+                        .or_else(|_| {
+                            loaded.try_get_pose(action, self.openxr.right_hand.profile_path.load())
+                        })
+                        .ok()
+                };
+
+                let Some(bound) = interaction_profile
+                    .and_then(|p| loaded.try_get_pose(action, p).ok())
+                    .or_else(get_first_bound_hand_profile)
+                else {
+                    match hand {
+                        Some(hand) => {
+                            trace!("action has no bindings for the {hand:?} hand's interaction profile");
+                        }
+                        None => {
+                            trace!("action has no bindings for either hand's interaction profile");
+                        }
+                    }
+
+                    no_data!()
+                };
+
+                let origin = hand.is_some().then_some(restrict_to_device);
+                let pose_type = match hand {
+                    Some(Hand::Left) => bound.left,
+                    Some(Hand::Right) => bound.right,
+                    None => {
+                        hand = Some(Hand::Left);
+                        bound.left.or_else(|| {
+                            hand = Some(Hand::Right);
+                            bound.right
+                        })
+                    }
+                };
+
+                let Some(ty) = pose_type else {
+                    trace!("action has no bindings for the hand {:?}", hand);
+                    no_data!()
+                };
+
+                let hand = hand.unwrap();
+                let origin = origin.unwrap_or_else(|| match hand {
+                    Hand::Left => self.left_hand_key.data().as_ffi(),
+                    Hand::Right => self.right_hand_key.data().as_ffi(),
+                });
+
+                let component = match ty {
+                    BoundPoseType::Raw | BoundPoseType::Gdc2015 => None,
+                    BoundPoseType::Component(component) => Some(component),
+                };
+                (origin, hand, component)
+            }
+            Ok(ActionData::Skeleton { hand, .. }) => {
+                if subaction_path != xr::Path::NULL {
+                    return vr::EVRInputError::InvalidDevice;
+                }
+                (0, *hand, None)
+            }
+            Ok(_) => return vr::EVRInputError::WrongType,
+            Err(e) => return e,
+        };
+
+        drop(loaded);
+        drop(data);
+        let palm_handgrip = (component == Some(PoseComponent::Handgrip))
+            .then(|| self.get_palm_handgrip_pose(hand, origin, time))
+            .flatten();
+        let pose = palm_handgrip.unwrap_or_else(|| {
+            let mut pose = match time {
+                Some(time) => self.get_controller_pose_at(hand, origin, time),
+                None => self.get_controller_pose(hand, Some(origin)),
+            }
+            .expect("wtf");
+            if let Some(offset) = component.and_then(|c| self.pose_component_offset(hand, c)) {
+                offset_pose(&mut pose, offset);
+            }
+            pose
+        });
+        unsafe {
+            action_data.write(vr::InputPoseActionData_t {
+                bActive: true,
+                activeOrigin: active_origin,
+                pose,
+            })
+        }
+
+        vr::EVRInputError::None
+    }
+
+    /// Like get_controller_pose, but for any time. These poses aren't cached.
+    fn get_controller_pose_at(
+        &self,
+        hand: Hand,
+        origin: vr::ETrackingUniverseOrigin,
+        time: xr::Time,
+    ) -> Option<vr::TrackedDevicePose_t> {
+        let data = self.openxr.session_data.get();
+        CachedSpaces::default().get_pose_impl(&self.openxr, &data, time, Some(hand), origin)
+    }
+
+    /// Returns None if XR_EXT_palm_pose isn't available or legacy actions haven't been set up yet.
+    fn get_palm_handgrip_pose(
+        &self,
+        hand: Hand,
+        origin: vr::ETrackingUniverseOrigin,
+        time: Option<xr::Time>,
+    ) -> Option<vr::TrackedDevicePose_t> {
+        let data = self.openxr.session_data.get();
+        let legacy = data.input_data.legacy_actions.get()?;
+        let spaces = match hand {
+            Hand::Left => &legacy.left_spaces,
+            Hand::Right => &legacy.right_spaces,
+        };
+        let handgrip = spaces.try_get_or_init_handgrip(&self.openxr, &data, legacy)?;
+        let (loc, velo) = handgrip
+            .relate(
+                data.get_space_for_origin(origin),
+                time.unwrap_or_else(|| self.openxr.display_time.get()),
+            )
+            .ok()?;
+        Some(space_relation_to_openvr_pose(loc, velo))
     }
 
     pub fn frame_start_update(&self) {
         tracy_span!();
         std::mem::take(&mut *self.cached_poses.lock().unwrap());
         let data = self.openxr.session_data.get();
+        if let Some(Some(force_feedback)) = data.input_data.force_feedback.get() {
+            force_feedback.update();
+        }
         if let Some(loaded) = data.input_data.loaded_actions.get() {
             // If the game has loaded actions, we shouldn't need to sync the state because the game
             // should be doing it itself with UpdateActionState. However, some games (Tea for God)
@@ -1226,6 +1883,7 @@ impl<C: openxr_data::Compositor> Input<C> {
                     .unwrap();
 
                 self.legacy_state.on_action_sync();
+                self.update_gestures(&data);
             }
             None => {
                 // If we haven't created our legacy actions yet but we're getting our per frame
@@ -1251,6 +1909,7 @@ impl<C: openxr_data::Compositor> Input<C> {
                     &self.openxr.instance,
                     self.openxr.left_hand.subaction_path,
                     self.openxr.right_hand.subaction_path,
+                    self.openxr.gamepad.subaction_path,
                 );
                 setup_legacy_bindings(&self.openxr.instance, &data.session, &legacy);
                 data.input_data
@@ -1267,7 +1926,14 @@ impl<C: openxr_data::Compositor> Input<C> {
             Hand::Right => &self.openxr.right_hand,
         };
         let profile = hand.profile_path.load();
-        self.profile_map.get(&profile).map(|v| &**v)
+        let properties = *self.profile_map.get(&profile)?;
+        // Only report the emulated controller when there's a real one behind it.
+        Some(self.emulated_properties.unwrap_or(properties))
+    }
+
+    fn get_gamepad_profile_data(&self) -> Option<&profiles::ProfileProperties> {
+        let profile = self.openxr.gamepad.profile_path.load();
+        self.profile_map.get(&profile).copied()
     }
 
     pub fn get_controller_string_tracked_property(
@@ -1275,34 +1941,8 @@ impl<C: openxr_data::Compositor> Input<C> {
         hand: Hand,
         property: vr::ETrackedDeviceProperty,
     ) -> Option<&'static CStr> {
-        self.get_profile_data(hand).and_then(|data| {
-            match property {
-                // Audica likes to apply controller specific tweaks via this property
-                vr::ETrackedDeviceProperty::ControllerType_String => {
-                    Some(data.openvr_controller_type)
-                }
-                // I Expect You To Die 3 identifies controllers with this property -
-                // why it couldn't just use ControllerType instead is beyond me...
-                // Because some controllers have different model names for each hand......
-                vr::ETrackedDeviceProperty::ModelNumber_String => Some(*data.model.get(hand)),
-                // Resonite won't recognize controllers without this
-                vr::ETrackedDeviceProperty::RenderModelName_String => {
-                    Some(*data.render_model_name.get(hand))
-                }
-                vr::ETrackedDeviceProperty::RegisteredDeviceType_String => {
-                    Some(*data.registered_device_type.get(hand))
-                }
-                vr::ETrackedDeviceProperty::TrackingSystemName_String => {
-                    Some(data.tracking_system_name)
-                }
-                // Required for controllers to be acknowledged in I Expect You To Die 3
-                vr::ETrackedDeviceProperty::SerialNumber_String => {
-                    Some(*data.serial_number.get(hand))
-                }
-                vr::ETrackedDeviceProperty::ManufacturerName_String => Some(data.manufacturer_name),
-                _ => None,
-            }
-        })
+        self.get_profile_data(hand)
+            .and_then(|data| string_property(data, hand, property))
     }
 
     pub fn get_controller_int_tracked_property(
@@ -1310,25 +1950,8 @@ impl<C: openxr_data::Compositor> Input<C> {
         hand: Hand,
         property: vr::ETrackedDeviceProperty,
     ) -> Option<i32> {
-        self.get_profile_data(hand).and_then(|data| match property {
-            vr::ETrackedDeviceProperty::Axis0Type_Int32 => match data.main_axis {
-                MainAxisType::Thumbstick => Some(vr::EVRControllerAxisType::Joystick as _),
-                MainAxisType::Trackpad => Some(vr::EVRControllerAxisType::TrackPad as _),
-            },
-            vr::ETrackedDeviceProperty::Axis1Type_Int32 => {
-                Some(vr::EVRControllerAxisType::Trigger as _)
-            }
-            vr::ETrackedDeviceProperty::Axis2Type_Int32 => {
-                // This is actually the grip, and gets recognized as such
-                Some(vr::EVRControllerAxisType::Trigger as _)
-            }
-            // TODO: report knuckles trackpad?
-            vr::ETrackedDeviceProperty::Axis3Type_Int32
-            | vr::ETrackedDeviceProperty::Axis4Type_Int32 => {
-                Some(vr::EVRControllerAxisType::None as _)
-            }
-            _ => None,
-        })
+        self.get_profile_data(hand)
+            .and_then(|data| int_property(data, property))
     }
 
     pub fn get_controller_uint_tracked_property(
@@ -1336,17 +1959,81 @@ impl<C: openxr_data::Compositor> Input<C> {
         hand: Hand,
         property: vr::ETrackedDeviceProperty,
     ) -> Option<u64> {
-        self.get_profile_data(hand).and_then(|data| match property {
-            vr::ETrackedDeviceProperty::SupportedButtons_Uint64 => Some(data.legacy_buttons_mask),
+        self.get_profile_data(hand)
+            .and_then(|data| uint_property(data, property))
+    }
+
+    /// Takes the binding list the application last asked to show, if it hasn't been shown yet.
+    pub fn take_bindings_view(&self) -> Option<Vec<String>> {
+        self.pending_bindings_view.lock().unwrap().take()
+    }
+
+    pub fn gamepad_connected(&self) -> bool {
+        self.openxr.gamepad.connected()
+    }
+
+    pub fn get_gamepad_string_tracked_property(
+        &self,
+        property: vr::ETrackedDeviceProperty,
+    ) -> Option<&'static CStr> {
+        // Gamepad properties are the same for both "hands".
+        self.get_gamepad_profile_data()
+            .and_then(|data| string_property(data, Hand::Left, property))
+    }
+
+    pub fn get_gamepad_int_tracked_property(
+        &self,
+        property: vr::ETrackedDeviceProperty,
+    ) -> Option<i32> {
+        self.get_gamepad_profile_data()
+            .and_then(|data| int_property(data, property))
+    }
+
+    pub fn get_gamepad_uint_tracked_property(
+        &self,
+        property: vr::ETrackedDeviceProperty,
+    ) -> Option<u64> {
+        self.get_gamepad_profile_data()
+            .and_then(|data| uint_property(data, property))
+    }
+
+    pub fn get_controller_float_tracked_property(
+        &self,
+        hand: Hand,
+        property: vr::ETrackedDeviceProperty,
+    ) -> Option<f32> {
+        match property {
+            vr::ETrackedDeviceProperty::DeviceBatteryPercentage_Float => {
+                self.get_battery_level(hand)
+            }
             _ => None,
-        })
+        }
+    }
+
+    pub fn get_controller_bool_tracked_property(
+        &self,
+        hand: Hand,
+        property: vr::ETrackedDeviceProperty,
+    ) -> Option<bool> {
+        match property {
+            vr::ETrackedDeviceProperty::DeviceProvidesBatteryStatus_Bool => {
+                self.get_profile_data(hand)?;
+                Some(self.get_battery_level(hand).is_some())
+            }
+            _ => None,
+        }
     }
 
     pub fn post_session_restart(&self, data: &SessionData) {
         // This function is called while a write lock is called on the session, and as such should
         // not use self.openxr.session_data.get().
         if let Some(path) = self.loaded_actions_path.get() {
-            self.load_action_manifest(data, path).unwrap();
+            if let Err(e) = self.load_action_manifest(data, path) {
+                error!("Failed to reload action manifest ({e:?}), keeping the previous one");
+                if let Err(e) = self.reload_previous_action_manifest(data) {
+                    error!("Failed to load previous action manifest: {e:?}");
+                }
+            }
         }
     }
 
@@ -1380,6 +2067,60 @@ impl<C: openxr_data::Compositor> Input<C> {
     }
 }
 
+fn string_property(
+    data: &profiles::ProfileProperties,
+    hand: Hand,
+    property: vr::ETrackedDeviceProperty,
+) -> Option<&'static CStr> {
+    match property {
+        // Audica likes to apply controller specific tweaks via this property
+        vr::ETrackedDeviceProperty::ControllerType_String => Some(data.openvr_controller_type),
+        // I Expect You To Die 3 identifies controllers with this property -
+        // why it couldn't just use ControllerType instead is beyond me...
+        // Because some controllers have different model names for each hand......
+        vr::ETrackedDeviceProperty::ModelNumber_String => Some(*data.model.get(hand)),
+        // Resonite won't recognize controllers without this
+        vr::ETrackedDeviceProperty::RenderModelName_String => {
+            Some(*data.render_model_name.get(hand))
+        }
+        vr::ETrackedDeviceProperty::RegisteredDeviceType_String => {
+            Some(*data.registered_device_type.get(hand))
+        }
+        vr::ETrackedDeviceProperty::TrackingSystemName_String => Some(data.tracking_system_name),
+        // Required for controllers to be acknowledged in I Expect You To Die 3
+        vr::ETrackedDeviceProperty::SerialNumber_String => Some(*data.serial_number.get(hand)),
+        vr::ETrackedDeviceProperty::ManufacturerName_String => Some(data.manufacturer_name),
+        _ => None,
+    }
+}
+
+fn int_property(
+    data: &profiles::ProfileProperties,
+    property: vr::ETrackedDeviceProperty,
+) -> Option<i32> {
+    match property {
+        vr::ETrackedDeviceProperty::Axis0Type_Int32
+        | vr::ETrackedDeviceProperty::Axis1Type_Int32
+        | vr::ETrackedDeviceProperty::Axis2Type_Int32
+        | vr::ETrackedDeviceProperty::Axis3Type_Int32
+        | vr::ETrackedDeviceProperty::Axis4Type_Int32 => {
+            let axis = property as usize - vr::ETrackedDeviceProperty::Axis0Type_Int32 as usize;
+            Some(data.legacy_axes[axis].axis_type(&data.main_axis) as _)
+        }
+        _ => None,
+    }
+}
+
+fn uint_property(
+    data: &profiles::ProfileProperties,
+    property: vr::ETrackedDeviceProperty,
+) -> Option<u64> {
+    match property {
+        vr::ETrackedDeviceProperty::SupportedButtons_Uint64 => Some(data.legacy_buttons_mask),
+        _ => None,
+    }
+}
+
 #[derive(Default)]
 struct CachedSpaces {
     seated: CachedPoses,
@@ -1445,6 +2186,44 @@ impl CachedSpaces {
     }
 }
 
+/// Moves a pose by an offset in its own space, adjusting the linear velocity for the angular
+/// velocity around the original pose.
+fn offset_pose(pose: &mut vr::TrackedDevicePose_t, offset: Mat4) {
+    if !pose.bPoseIsValid {
+        return;
+    }
+    let xr_pose = xr::Posef::from(pose.mDeviceToAbsoluteTracking);
+    let base = Mat4::from_rotation_translation(
+        Quat::from_xyzw(
+            xr_pose.orientation.x,
+            xr_pose.orientation.y,
+            xr_pose.orientation.z,
+            xr_pose.orientation.w,
+        ),
+        Vec3::new(xr_pose.position.x, xr_pose.position.y, xr_pose.position.z),
+    );
+    let (_, rotation, translation) = (base * offset).to_scale_rotation_translation();
+    pose.mDeviceToAbsoluteTracking = xr::Posef {
+        orientation: xr::Quaternionf {
+            x: rotation.x,
+            y: rotation.y,
+            z: rotation.z,
+            w: rotation.w,
+        },
+        position: xr::Vector3f {
+            x: translation.x,
+            y: translation.y,
+            z: translation.z,
+        },
+    }
+    .into();
+
+    let lever = base.transform_vector3(offset.w_axis.truncate());
+    let angular = Vec3::from_array(pose.vAngularVelocity.v);
+    let linear = Vec3::from_array(pose.vVelocity.v) + angular.cross(lever);
+    pose.vVelocity = linear.into();
+}
+
 struct LoadedActions {
     sets: SecondaryMap<ActionSetKey, xr::ActionSet>,
     actions: SecondaryMap<ActionKey, ActionData>,
@@ -1,10 +1,11 @@
 use super::{
-    custom_bindings::DpadDirection,
+    custom_bindings::{DoublePressOutput, DpadDirection},
     legacy::LegacyActionData,
-    profiles::{PathTranslation, Profiles},
+    profiles::{InteractionProfile, PathTranslation, PoseComponent, Profiles},
     skeletal::SkeletalInputActionData,
     ActionData, ActionKey, BoundPoseType, Input,
 };
+use crate::config::{steam_app_key, Config};
 use crate::openxr_data::{self, Hand, SessionData};
 use helpers::{BindingsLoadContext, BindingsProfileLoadContext, DpadActivatorData, DpadHapticData};
 use log::{debug, error, info, trace, warn};
@@ -15,11 +16,11 @@ use serde::{
     Deserialize,
 };
 use slotmap::{SecondaryMap, SlotMap};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::env::current_dir;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::RwLock;
-use std::{cell::LazyCell, env::current_dir};
 
 mod helpers;
 
@@ -40,6 +41,26 @@ fn action_map_to_secondary<T>(
 }
 
 impl<C: openxr_data::Compositor> Input<C> {
+    fn read_action_manifest(manifest_path: &Path) -> Result<Vec<u8>, vr::EVRInputError> {
+        std::fs::read(manifest_path).map_err(|e| {
+            error!("Failed to read manifest {}: {e}", manifest_path.display());
+            vr::EVRInputError::InvalidParam
+        })
+    }
+
+    fn parse_action_manifest_data(data: &[u8]) -> Result<ActionManifest, vr::EVRInputError> {
+        serde_json::from_slice(data).map_err(|e| {
+            error!("Failed to parse action manifest: {e}");
+            vr::EVRInputError::InvalidParam
+        })
+    }
+
+    pub(super) fn parse_action_manifest(
+        manifest_path: &Path,
+    ) -> Result<ActionManifest, vr::EVRInputError> {
+        Self::parse_action_manifest_data(&Self::read_action_manifest(manifest_path)?)
+    }
+
     pub(super) fn load_action_manifest(
         &self,
         session_data: &SessionData,
@@ -55,15 +76,33 @@ impl<C: openxr_data::Compositor> Input<C> {
                 .unwrap(),
         }
 
-        let data = std::fs::read(manifest_path).map_err(|e| {
-            error!("Failed to read manifest {}: {e}", manifest_path.display());
-            vr::EVRInputError::InvalidParam
-        })?;
+        let data = Self::read_action_manifest(manifest_path)?;
+        let manifest = Self::parse_action_manifest_data(&data)?;
+        self.apply_action_manifest(session_data, manifest_path, manifest)?;
+        *self.loaded_manifest.lock().unwrap() = data;
+        Ok(())
+    }
 
-        let manifest: ActionManifest = serde_json::from_slice(&data).map_err(|e| {
-            error!("Failed to parse action manifest: {e}");
-            vr::EVRInputError::InvalidParam
-        })?;
+    /// Loads the last action manifest that loaded successfully again, for when an edited one
+    /// fails to load after the session was already restarted.
+    pub(super) fn reload_previous_action_manifest(
+        &self,
+        session_data: &SessionData,
+    ) -> Result<(), vr::EVRInputError> {
+        let manifest_path = self.loaded_actions_path.get().unwrap();
+        let data = self.loaded_manifest.lock().unwrap().clone();
+        let manifest = Self::parse_action_manifest_data(&data)?;
+        self.apply_action_manifest(session_data, manifest_path, manifest)
+    }
+
+    fn apply_action_manifest(
+        &self,
+        session_data: &SessionData,
+        manifest_path: &Path,
+        manifest: ActionManifest,
+    ) -> Result<(), vr::EVRInputError> {
+        self.bindings_watcher.clear();
+        self.bindings_watcher.watch(manifest_path);
 
         // TODO: support non english localization?
         let english = manifest
@@ -85,6 +124,8 @@ impl<C: openxr_data::Compositor> Input<C> {
             manifest.actions,
             self.openxr.left_hand.subaction_path,
             self.openxr.right_hand.subaction_path,
+            self.openxr.gamepad.subaction_path,
+            self.openxr.hand_tracking_supported,
         )?;
         debug!("Loaded {} actions.", actions.len());
 
@@ -95,6 +136,7 @@ impl<C: openxr_data::Compositor> Input<C> {
                 &self.openxr.instance,
                 self.openxr.left_hand.subaction_path,
                 self.openxr.right_hand.subaction_path,
+                self.openxr.gamepad.subaction_path,
             )
         });
 
@@ -128,6 +170,7 @@ impl<C: openxr_data::Compositor> Input<C> {
         );
 
         self.load_bindings(
+            &session_data.session,
             manifest_path.parent().unwrap(),
             manifest.default_bindings,
             &mut binding_context,
@@ -206,6 +249,8 @@ impl<C: openxr_data::Compositor> Input<C> {
                     .unwrap_or_else(|_| unreachable!());
             }
         }
+        // The blocked actions are keyed by the old actions.
+        *self.active_sets.write().unwrap() = Default::default();
         Ok(())
     }
 }
@@ -216,7 +261,7 @@ impl<C: openxr_data::Compositor> Input<C> {
  */
 
 #[derive(Deserialize)]
-struct ActionManifest {
+pub(super) struct ActionManifest {
     default_bindings: Vec<DefaultBindings>,
     #[serde(default)] // optional apparently
     action_sets: Vec<ActionSetJson>,
@@ -237,10 +282,37 @@ pub(super) enum ControllerType {
     ViveController,
     Knuckles,
     OculusTouch,
+    HolographicController,
+    Gamepad,
     #[serde(untagged)]
     Unknown(String),
 }
 
+impl ControllerType {
+    /// The name SteamVR uses for this controller type.
+    fn name(&self) -> &str {
+        match self {
+            Self::ViveController => "vive_controller",
+            Self::Knuckles => "knuckles",
+            Self::OculusTouch => "oculus_touch",
+            Self::HolographicController => "holographic_controller",
+            Self::Gamepad => "gamepad",
+            Self::Unknown(other) => other,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "vive_controller" => Self::ViveController,
+            "knuckles" => Self::Knuckles,
+            "oculus_touch" => Self::OculusTouch,
+            "holographic_controller" => Self::HolographicController,
+            "gamepad" => Self::Gamepad,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+}
+
 #[derive(Deserialize)]
 struct ActionSetJson {
     #[serde(rename = "name")]
@@ -253,6 +325,7 @@ enum ActionType {
     Boolean(ActionDataCommon),
     Vector1(ActionDataCommon),
     Vector2(ActionDataCommon),
+    Vector3(ActionDataCommon),
     Vibration(ActionDataCommon),
     Pose(ActionDataCommon),
     Skeleton(SkeletonData),
@@ -265,13 +338,36 @@ struct ActionDataCommon {
 
 #[derive(Deserialize)]
 struct SkeletonData {
-    #[serde(deserialize_with = "parse_skeleton")]
-    skeleton: Hand,
+    /// Some manifests leave this out, in which case the hand is inferred from the action name
+    /// or its bindings.
+    #[serde(default, deserialize_with = "parse_skeleton")]
+    skeleton: Option<Hand>,
     #[serde(flatten)]
     data: ActionDataCommon,
 }
 
-fn parse_skeleton<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Hand, D::Error> {
+impl SkeletonData {
+    fn hand(&self) -> Hand {
+        if let Some(hand) = self.skeleton {
+            return hand;
+        }
+
+        let name = self.data.name.to_lowercase();
+        let name = name.rsplit_once('/').map(|(_, n)| n).unwrap_or(&name);
+        let hand = if name.contains("right") {
+            Hand::Right
+        } else {
+            Hand::Left
+        };
+        warn!(
+            "Skeleton action {} has no skeleton path, assuming {hand:?} hand",
+            self.data.name
+        );
+        hand
+    }
+}
+
+fn parse_skeleton<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Option<Hand>, D::Error> {
     let path: &str = Deserialize::deserialize(d)?;
     let Some(hand) = path.strip_prefix("/skeleton/hand") else {
         return Err(D::Error::invalid_value(
@@ -281,8 +377,8 @@ fn parse_skeleton<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Hand, D::Err
     };
 
     match hand {
-        "/left" => Ok(Hand::Left),
-        "/right" => Ok(Hand::Right),
+        "/left" => Ok(Some(Hand::Left)),
+        "/right" => Ok(Some(Hand::Right)),
         _ => Err(D::Error::invalid_value(
             Unexpected::Str(hand),
             &r#""/left" or "/right""#,
@@ -343,6 +439,8 @@ fn load_actions(
     actions: Vec<ActionType>,
     left_hand: xr::Path,
     right_hand: xr::Path,
+    gamepad: xr::Path,
+    hand_tracking: bool,
 ) -> Result<HashMap<String, super::ActionData>, vr::EVRInputError> {
     let mut ret = HashMap::with_capacity(actions.len());
     let mut long_name_idx = 0;
@@ -402,7 +500,7 @@ fn load_actions(
                 })
         }
 
-        let paths = &[left_hand, right_hand];
+        let paths = &[left_hand, right_hand, gamepad];
         macro_rules! create_action {
             ($ty:ty, $data:expr) => {
                 create_action::<$ty>(instance, &$data, sets, english, paths, &mut long_name_idx)
@@ -426,20 +524,23 @@ fn load_actions(
                     last_value: Default::default(),
                 },
             ),
+            ActionType::Vector3(data) => (
+                &data.name,
+                Vector3 {
+                    action: create_action!(xr::Vector2f, data),
+                    last_value: Default::default(),
+                },
+            ),
             ActionType::Pose(data) => (&data.name, Pose),
-            ActionType::Skeleton(SkeletonData { skeleton, data }) => {
+            ActionType::Skeleton(skeleton_data) => {
+                let skeleton = &skeleton_data.hand();
+                let data = &skeleton_data.data;
                 trace!("Creating skeleton action {}", data.name.to_lowercase());
-                let hand_tracker = match session.create_hand_tracker(match skeleton {
-                    Hand::Left => xr::Hand::LEFT,
-                    Hand::Right => xr::Hand::RIGHT,
-                }) {
-                    Ok(t) => Some(t),
-                    Err(
-                        xr::sys::Result::ERROR_EXTENSION_NOT_PRESENT
-                        | xr::sys::Result::ERROR_FEATURE_UNSUPPORTED,
-                    ) => None,
-                    Err(other) => panic!("Creating hand tracker failed: {other:?}"),
-                };
+                // Without hand tracking support we fall back to estimating the skeleton
+                // from the controller inputs.
+                let hand_tracker = hand_tracking
+                    .then(|| create_hand_tracker(session, *skeleton))
+                    .flatten();
 
                 (
                     &data.name,
@@ -456,6 +557,23 @@ fn load_actions(
     Ok(ret)
 }
 
+fn create_hand_tracker(
+    session: &xr::Session<xr::AnyGraphics>,
+    hand: Hand,
+) -> Option<xr::HandTracker> {
+    match session.create_hand_tracker(match hand {
+        Hand::Left => xr::Hand::LEFT,
+        Hand::Right => xr::Hand::RIGHT,
+    }) {
+        Ok(t) => Some(t),
+        Err(
+            xr::sys::Result::ERROR_EXTENSION_NOT_PRESENT
+            | xr::sys::Result::ERROR_FEATURE_UNSUPPORTED,
+        ) => None,
+        Err(other) => panic!("Creating hand tracker failed: {other:?}"),
+    }
+}
+
 /**
  * Structure for binding files
  */
@@ -471,6 +589,7 @@ struct ActionSetBinding {
     poses: Option<Vec<PoseBinding>>,
     haptics: Option<Vec<SimpleActionBinding>>,
     skeleton: Option<Vec<SkeletonActionBinding>>,
+    chords: Option<Vec<ChordBinding>>,
 }
 
 #[repr(transparent)]
@@ -534,7 +653,15 @@ fn parse_pose_binding<'de, D: serde::Deserializer<'de>>(
     let pose = match pose {
         "raw" => BoundPoseType::Raw,
         "gdc2015" => BoundPoseType::Gdc2015,
-        other => return Err(D::Error::unknown_variant(other, &["raw", "gdc2015"])),
+        "tip" => BoundPoseType::Component(PoseComponent::Tip),
+        "base" => BoundPoseType::Component(PoseComponent::Base),
+        "handgrip" => BoundPoseType::Component(PoseComponent::Handgrip),
+        other => {
+            return Err(D::Error::unknown_variant(
+                other,
+                &["raw", "gdc2015", "tip", "base", "handgrip"],
+            ))
+        }
     };
 
     Ok((hand, pose))
@@ -546,6 +673,13 @@ struct SimpleActionBinding {
     path: String,
 }
 
+#[derive(Deserialize)]
+struct ChordBinding {
+    output: LowercaseActionPath,
+    /// Pairs of input paths and components, i.e. ["/user/hand/left/input/grip", "click"]
+    inputs: Vec<(String, String)>,
+}
+
 #[derive(Deserialize)]
 struct SkeletonActionBinding {
     output: LowercaseActionPath,
@@ -556,8 +690,8 @@ struct SkeletonActionBinding {
 fn path_to_skeleton<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Hand, D::Error> {
     let path: &str = Deserialize::deserialize(d)?;
     match path {
-        "/user/hand/left/input/skeleton/left" => Ok(Hand::Left),
-        "/user/hand/right/input/skeleton/right" => Ok(Hand::Right),
+        "/user/hand/left/input/skeleton/left" | "/skeleton/hand/left" => Ok(Hand::Left),
+        "/user/hand/right/input/skeleton/right" | "/skeleton/hand/right" => Ok(Hand::Right),
         other => Err(D::Error::invalid_value(
             Unexpected::Str(other),
             &"/user/hand/left/input/skeleton/left or /user/hand/right/input/skeleton/right",
@@ -584,6 +718,12 @@ enum ActionBinding {
         path: String,
         inputs: ButtonInput,
     },
+    /// Not a SteamVR mode: pulses the click output on and off while the button is held.
+    AutoRepeat {
+        path: String,
+        inputs: ButtonInput,
+        parameters: Option<AutoRepeatParameters>,
+    },
     Dpad {
         path: String,
         inputs: DpadInput,
@@ -592,7 +732,6 @@ enum ActionBinding {
     Trigger {
         path: String,
         inputs: TriggerInput,
-        #[allow(unused)]
         parameters: Option<ClickThresholdParams>,
     },
     ScalarConstant {
@@ -604,7 +743,6 @@ enum ActionBinding {
     ForceSensor {
         path: String,
         inputs: ForceSensorInput,
-        #[allow(unused)]
         parameters: Option<ForceSensorParameters>,
     },
     Grab {
@@ -614,10 +752,8 @@ enum ActionBinding {
         parameters: Option<GrabParameters>,
     },
     Scroll {
-        #[allow(unused)]
         path: String,
         inputs: ScrollInput,
-        #[allow(unused)]
         parameters: Option<ScrollParameters>,
     },
     Trackpad(Vector2Mode),
@@ -654,6 +790,7 @@ struct ButtonInput {
     touch: Option<ActionBindingOutput>,
     click: Option<ActionBindingOutput>,
     double: Option<ActionBindingOutput>,
+    held: Option<ActionBindingOutput>,
 }
 
 #[derive(Deserialize)]
@@ -678,6 +815,19 @@ struct ButtonParameters {
     #[allow(unused)]
     #[serde(flatten)]
     click_threshold: ClickThresholdParams,
+    /// Seconds the button must be held down before the held output activates.
+    long_press_delay: Option<FromString<f32>>,
+    /// Seconds after a release in which another press counts as a double press.
+    double_press_delay: Option<FromString<f32>>,
+    /// If set, the click output of a button with a double press output fires immediately
+    /// instead of waiting for the double press window to pass.
+    immediate_single_press: Option<FromString<bool>>,
+}
+
+#[derive(Deserialize)]
+struct AutoRepeatParameters {
+    /// Presses per second while the button is held.
+    repeat_rate: Option<FromString<f32>>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -737,6 +887,8 @@ struct ForceSensorInput {
 struct ForceSensorParameters {
     #[allow(unused)]
     haptic_amplitude: Option<String>,
+    #[serde(flatten)]
+    click_threshold: ClickThresholdParams,
 }
 
 #[derive(Deserialize)]
@@ -759,10 +911,8 @@ struct ScrollInput {
 
 #[derive(Deserialize)]
 struct ScrollParameters {
-    #[allow(unused)]
     scroll_mode: Option<String>,
-    #[allow(unused)]
-    smooth_scroll_multiplier: Option<String>, // float
+    smooth_scroll_multiplier: Option<FromString<f32>>,
 }
 
 #[derive(Deserialize)]
@@ -778,14 +928,76 @@ struct Vector2Input {
     touch: Option<ActionBindingOutput>,
 }
 
+fn steam_dir() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("XRIZER_STEAM_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+
+    let home = PathBuf::from(std::env::var("HOME").ok()?);
+    [".steam/steam", ".local/share/Steam"]
+        .into_iter()
+        .map(|dir| home.join(dir))
+        .find(|dir| dir.is_dir())
+}
+
+/// Finds bindings the user saved for this app and controller type through SteamVR's binding UI.
+/// SteamVR keeps these in Steam's config directory, in a folder named after the app key.
+/// If there are several for the same controller type, the most recently modified one wins.
+fn find_user_bindings(controller_type: &ControllerType) -> Option<PathBuf> {
+    let dir = steam_dir()?.join("config").join(steam_app_key()?);
+    let prefix = controller_type.name();
+
+    std::fs::read_dir(&dir)
+        .ok()?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name();
+            let name = name.to_str()?.to_lowercase();
+            if !name.starts_with(prefix) || !name.ends_with(".json") {
+                return None;
+            }
+            let modified = entry.metadata().and_then(|m| m.modified()).ok()?;
+            Some((modified, entry.path()))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
 impl<C: openxr_data::Compositor> Input<C> {
     #[allow(clippy::too_many_arguments)]
     fn load_bindings(
         &self,
+        session: &xr::Session<xr::AnyGraphics>,
         parent_path: &Path,
         bindings: Vec<DefaultBindings>,
         context: &mut BindingsLoadContext,
     ) {
+        // When emulating another controller, its bindings are used for every profile. Gamepads
+        // aren't controllers, so their bindings are kept as they are.
+        let emulated = Profiles::get().emulated().map(|(ty, _)| ty).filter(|ty| {
+            let found = bindings.iter().any(|b| b.controller_type == **ty);
+            if !found {
+                warn!("Emulating {ty:?}, but the app has no bindings for it");
+            }
+            found
+        });
+        let bindings = match emulated {
+            Some(ty) => {
+                info!("Emulating {ty:?}, ignoring bindings for other controllers");
+                bindings
+                    .into_iter()
+                    .filter(|b| {
+                        b.controller_type == *ty || b.controller_type == ControllerType::Gamepad
+                    })
+                    .collect()
+            }
+            None => bindings,
+        };
+
+        let mut loaded_profiles = HashSet::new();
+        // Every set of controller bindings we manage to load, for profiles the app has no bindings
+        // for.
+        let mut available = Vec::new();
         let mut it: Box<dyn Iterator<Item = DefaultBindings>> = Box::new(bindings.into_iter());
         while let Some(DefaultBindings {
             binding_url,
@@ -800,18 +1012,37 @@ impl<C: openxr_data::Compositor> Input<C> {
                         current_dir().unwrap().join("xrizer")
                     }
                     .join(format!("{controller_type:?}.json").to_lowercase());
-                let bindings_path = match custom_path.exists() {
-                    true => custom_path,
-                    false => parent_path.join(binding_url),
+                let bindings_path = if custom_path.exists() {
+                    custom_path
+                } else if let Some(user_path) = find_user_bindings(&controller_type) {
+                    info!(
+                        "Using user bindings for {controller_type:?} from {}",
+                        user_path.display()
+                    );
+                    user_path
+                } else {
+                    parent_path.join(binding_url)
                 };
                 debug!(
                     "Reading bindings for {controller_type:?} (at {})",
                     bindings_path.display()
                 );
+                self.bindings_watcher.watch(&bindings_path);
 
-                let data = std::fs::read(bindings_path)
+                let mut data = std::fs::read(bindings_path)
                     .inspect_err(|e| error!("Couldn't load bindings for {controller_type:?}: {e}"))
                     .ok()?;
+                if Config::get().mirror_left_to_right() {
+                    data = serde_json::from_slice(&data)
+                        .and_then(|mut value| {
+                            mirror_left_to_right(&mut value);
+                            serde_json::to_vec(&value)
+                        })
+                        .inspect_err(|e| {
+                            error!("Failed to parse bindings for {controller_type:?}: {e}")
+                        })
+                        .ok()?;
+                }
 
                 let Bindings { bindings } = serde_json::from_slice(&data)
                     .inspect_err(|e| {
@@ -826,19 +1057,28 @@ impl<C: openxr_data::Compositor> Input<C> {
                     info!("Ignoring bindings for unknown profile {other}")
                 }
                 ref other => {
-                    let profiles = Profiles::get()
+                    let profiles: Vec<_> = Profiles::get()
                         .list
                         .iter()
-                        .filter_map(|(ty, p)| (*ty == *other).then_some(*p));
-                    let bindings = LazyCell::new(load_bindings);
-                    for profile in profiles {
-                        if let Some(bindings) = bindings.as_ref() {
+                        .filter_map(|(ty, p)| {
+                            let emulating = emulated.is_some()
+                                && *other != ControllerType::Gamepad
+                                && *ty != ControllerType::Gamepad;
+                            (emulating || *ty == *other).then_some(*p)
+                        })
+                        .collect();
+                    if let Some(bindings) = (!profiles.is_empty()).then(load_bindings).flatten() {
+                        for profile in profiles {
                             if let Some(mut context) =
                                 context.for_profile(&self.openxr, profile, other)
                             {
-                                self.load_bindings_for_profile(bindings, &mut context);
+                                self.load_bindings_for_profile(session, &bindings, &mut context);
+                                loaded_profiles.insert(profile.profile_path());
                             }
                         }
+                        if *other != ControllerType::Gamepad {
+                            available.push((other.clone(), bindings));
+                        }
                     }
                 }
             }
@@ -852,11 +1092,41 @@ impl<C: openxr_data::Compositor> Input<C> {
                 }
             }));
         }
+
+        for (ty, profile) in Profiles::get().list.iter() {
+            if *ty == ControllerType::Gamepad || loaded_profiles.contains(profile.profile_path()) {
+                continue;
+            }
+            // Use the bindings of whichever controller has the most inputs in common with this
+            // one, preferring the ones listed first in the manifest.
+            let Some((controller_type, bindings)) =
+                available.iter().rev().max_by_key(|(available_ty, _)| {
+                    Profiles::get()
+                        .list
+                        .iter()
+                        .filter(|(other, _)| other == available_ty)
+                        .map(|(_, other)| shared_inputs(*profile, *other))
+                        .max()
+                        .unwrap_or(0)
+                })
+            else {
+                return;
+            };
+            info!(
+                "No bindings for {}, using {controller_type:?} bindings",
+                profile.profile_path()
+            );
+            if let Some(mut context) = context.for_profile(&self.openxr, *profile, controller_type)
+            {
+                self.load_bindings_for_profile(session, bindings, &mut context);
+            }
+        }
     }
 
     #[allow(clippy::too_many_arguments)]
     fn load_bindings_for_profile(
         &self,
+        session: &xr::Session<xr::AnyGraphics>,
         bindings: &HashMap<String, ActionSetBinding>,
         context: &mut BindingsProfileLoadContext,
     ) {
@@ -876,6 +1146,10 @@ impl<C: openxr_data::Compositor> Input<C> {
         let profile_path = stp(profile.profile_path());
         let legal_paths = profile.legal_paths();
         let translate_map = profile.translate_map();
+        // Bindings written for another controller type (i.e. when emulating one) may use a
+        // trackpad where this profile has a thumbstick, or the other way around.
+        let foreign_bindings =
+            Profiles::get().controller_type(profile) != Some(context.controller_type);
         let path_translator = |path: &str| {
             let mut translated = path.to_string();
             for PathTranslation { from, to, stop } in translate_map {
@@ -886,6 +1160,15 @@ impl<C: openxr_data::Compositor> Input<C> {
                     }
                 }
             }
+            if foreign_bindings && !legal_paths.contains(&translated) {
+                if let Some(swapped) = [("trackpad", "thumbstick"), ("thumbstick", "trackpad")]
+                    .into_iter()
+                    .map(|(from, to)| translated.replace(from, to))
+                    .find(|swapped| legal_paths.contains(swapped))
+                {
+                    translated = swapped;
+                }
+            }
             trace!("translated {path} to {translated}");
             if !legal_paths.contains(&translated) {
                 Err(InvalidActionPath(format!(
@@ -913,7 +1196,8 @@ impl<C: openxr_data::Compositor> Input<C> {
             }
 
             if let Some(bindings) = &bindings.skeleton {
-                handle_skeleton_bindings(context, bindings);
+                let hand_tracking = self.openxr.hand_tracking_supported;
+                handle_skeleton_bindings(context, session, hand_tracking, bindings);
             }
 
             handle_sources(
@@ -923,8 +1207,14 @@ impl<C: openxr_data::Compositor> Input<C> {
                 &set,
                 &bindings.sources,
             );
+
+            if let Some(chords) = &bindings.chords {
+                handle_chord_bindings(path_translator, context, action_set_name, &set, chords);
+            }
         }
 
+        context.add_chord_masks();
+
         let info_action_binding = *legacy_bindings.trigger_click.first().unwrap_or_else(|| {
             panic!(
                 "Missing trigger_click binding for {}",
@@ -944,7 +1234,9 @@ impl<C: openxr_data::Compositor> Input<C> {
                 {
                     Bool(action) => xr::Binding::new(action, path),
                     Vector1 { action, .. } => xr::Binding::new(action, path),
-                    Vector2 { action, .. } => xr::Binding::new(action, path),
+                    Vector2 { action, .. } | Vector3 { action, .. } => {
+                        xr::Binding::new(action, path)
+                    }
                     Haptic(action) => xr::Binding::new(action, path),
                     Skeleton { .. } | Pose { .. } => unreachable!(),
                 }
@@ -969,6 +1261,56 @@ impl<C: openxr_data::Compositor> Input<C> {
     }
 }
 
+/// Copies the sources bound to left hand inputs over to the same inputs of the right hand,
+/// unless the right hand already has bindings for them.
+fn mirror_left_to_right(bindings: &mut serde_json::Value) {
+    let Some(sets) = bindings
+        .get_mut("bindings")
+        .and_then(serde_json::Value::as_object_mut)
+    else {
+        return;
+    };
+    for sources in sets
+        .values_mut()
+        .filter_map(|set| set.get_mut("sources")?.as_array_mut())
+    {
+        let path_of = |source: &serde_json::Value| {
+            source
+                .get("path")
+                .and_then(serde_json::Value::as_str)
+                .map(str::to_lowercase)
+        };
+        let bound: HashSet<String> = sources.iter().filter_map(path_of).collect();
+        let mirrored: Vec<_> = sources
+            .iter()
+            .filter_map(|source| {
+                let path = path_of(source)?;
+                let right = format!(
+                    "/user/hand/right/{}",
+                    path.strip_prefix("/user/hand/left/")?
+                );
+                if bound.contains(&right) {
+                    return None;
+                }
+                let mut source = source.clone();
+                source["path"] = right.into();
+                Some(source)
+            })
+            .collect();
+        sources.extend(mirrored);
+    }
+}
+
+/// How many of the profile's inputs and outputs the other profile has as well.
+fn shared_inputs(profile: &dyn InteractionProfile, other: &dyn InteractionProfile) -> usize {
+    let other_paths = other.legal_paths();
+    profile
+        .legal_paths()
+        .iter()
+        .filter(|path| other_paths.contains(path))
+        .count()
+}
+
 /// Returns a tuple of a parent action index and a path for its bindng
 fn handle_dpad_binding(
     string_to_path: impl Fn(&str) -> Option<xr::Path>,
@@ -987,7 +1329,6 @@ fn handle_dpad_binding(
 ) {
     // Would love to use the dpad extension here, but it doesn't seem to
     // support touch trackpad dpads.
-    // TODO: actually take the deadzone and overlap into account
 
     // Workaround weird closure lifetime quirks.
     const fn constrain<F>(f: F) -> F
@@ -1038,7 +1379,13 @@ fn handle_dpad_binding(
     );
 
     for (action_name, direction) in bound_actions {
-        context.add_custom_dpad_binding(parent_path, action_name, direction, &created_actions);
+        context.add_custom_dpad_binding(
+            parent_path,
+            action_name,
+            direction,
+            &created_actions,
+            parameters,
+        );
     }
 
     let activator_binding = created_actions
@@ -1116,7 +1463,7 @@ fn handle_sources(
                     context.add_custom_toggle_binding(output, &translated);
                 }
             }
-            ActionBinding::Button {
+            ActionBinding::AutoRepeat {
                 path,
                 inputs,
                 parameters,
@@ -1124,6 +1471,106 @@ fn handle_sources(
                 bind_button_touch!(path, inputs);
 
                 if let Some(ActionBindingOutput { output }) = &inputs.click {
+                    let Ok(translated) = path_translator(&format!("{path}/click"))
+                        .inspect_err(translate_warn(output))
+                    else {
+                        continue;
+                    };
+
+                    let rate = parameters
+                        .as_ref()
+                        .and_then(|x| x.repeat_rate.as_ref())
+                        .map(|x| x.0);
+                    context.add_repeat_binding(
+                        output,
+                        &translated,
+                        action_set_name,
+                        action_set,
+                        rate,
+                    );
+                }
+            }
+            ActionBinding::Button {
+                path,
+                inputs,
+                parameters,
+            } => {
+                bind_button_touch!(path, inputs);
+
+                let held_delay = parameters
+                    .as_ref()
+                    .and_then(|x| x.long_press_delay.as_ref())
+                    .map(|x| x.0);
+                let held_path = inputs
+                    .held
+                    .as_ref()
+                    .and_then(|ActionBindingOutput { output }| {
+                        let translated = path_translator(&format!("{path}/click"))
+                            .inspect_err(translate_warn(output))
+                            .ok()?;
+                        context.add_held_binding(
+                            output,
+                            &translated,
+                            action_set_name,
+                            action_set,
+                            held_delay,
+                            false,
+                        );
+                        Some(translated)
+                    });
+
+                let double_press_delay = parameters
+                    .as_ref()
+                    .and_then(|x| x.double_press_delay.as_ref())
+                    .map(|x| x.0);
+                let double_path =
+                    inputs
+                        .double
+                        .as_ref()
+                        .and_then(|ActionBindingOutput { output }| {
+                            let translated = path_translator(&format!("{path}/click"))
+                                .inspect_err(translate_warn(output))
+                                .ok()?;
+                            context.add_double_press_binding(
+                                output,
+                                &translated,
+                                action_set_name,
+                                action_set,
+                                double_press_delay,
+                                DoublePressOutput::Double,
+                            );
+                            Some(translated)
+                        });
+
+                if let (Some(ActionBindingOutput { output }), Some(held_path)) =
+                    (&inputs.click, &held_path)
+                {
+                    // The held output takes over long presses, so click only covers short ones.
+                    context.add_held_binding(
+                        output,
+                        held_path,
+                        action_set_name,
+                        action_set,
+                        held_delay,
+                        true,
+                    );
+                } else if let (Some(ActionBindingOutput { output }), Some(double_path)) =
+                    (&inputs.click, &double_path)
+                {
+                    // Keep the first press of a double press from also registering as a click.
+                    let delayed = !parameters
+                        .as_ref()
+                        .and_then(|x| x.immediate_single_press.as_ref())
+                        .is_some_and(|x| x.0);
+                    context.add_double_press_binding(
+                        output,
+                        double_path,
+                        action_set_name,
+                        action_set,
+                        double_press_delay,
+                        DoublePressOutput::Single { delayed },
+                    );
+                } else if let Some(ActionBindingOutput { output }) = &inputs.click {
                     let parameters = parameters.as_ref();
                     let target = parameters
                         .and_then(|x| x.force_input.as_ref())
@@ -1183,10 +1630,6 @@ fn handle_sources(
                         context.add_custom_button_binding(output, &translated, parameters)
                     }
                 }
-
-                if let Some(ActionBindingOutput { output }) = &inputs.double {
-                    warn!("Double click binding for {output} currently unsupported.");
-                }
             }
             ActionBinding::Dpad {
                 path,
@@ -1216,7 +1659,7 @@ fn handle_sources(
             ActionBinding::Trigger {
                 path,
                 inputs: TriggerInput { pull, touch, click },
-                ..
+                parameters,
             } => {
                 let suffixes_and_outputs = [("pull", pull), ("touch", touch), ("click", click)]
                     .into_iter()
@@ -1228,6 +1671,32 @@ fn handle_sources(
                         continue;
                     };
 
+                    // A boolean action on the trigger pull needs our own click thresholds,
+                    // rather than whatever the runtime uses for float to bool conversion.
+                    if suffix == "pull"
+                        && !translated.ends_with("/click")
+                        && matches!(
+                            context.actions.get(output.as_str()),
+                            Some(ActionData::Bool(_))
+                        )
+                    {
+                        let float_name_with_as = context.get_or_create_analog_extra_action(
+                            output,
+                            action_set_name,
+                            action_set,
+                        );
+                        context.push_binding(
+                            float_name_with_as,
+                            context.instance.string_to_path(&translated).unwrap(),
+                        );
+                        context.add_custom_threshold_binding(
+                            output,
+                            &translated,
+                            parameters.as_ref(),
+                        );
+                        continue;
+                    }
+
                     context.try_get_bool_binding(output.to_string(), translated);
                 }
             }
@@ -1258,7 +1727,7 @@ fn handle_sources(
                     ForceSensorInput {
                         force: ActionBindingOutput { output },
                     },
-                ..
+                parameters,
             } => {
                 let Ok(translated) =
                     path_translator(&format!("{path}/force")).inspect_err(translate_warn(output))
@@ -1266,6 +1735,28 @@ fn handle_sources(
                     continue;
                 };
 
+                // Same as the trigger pull: a boolean action needs our own click thresholds.
+                if matches!(
+                    context.actions.get(output.as_str()),
+                    Some(ActionData::Bool(_))
+                ) {
+                    let float_name_with_as = context.get_or_create_analog_extra_action(
+                        output,
+                        action_set_name,
+                        action_set,
+                    );
+                    context.push_binding(
+                        float_name_with_as,
+                        context.instance.string_to_path(&translated).unwrap(),
+                    );
+                    context.add_custom_threshold_binding(
+                        output,
+                        &translated,
+                        parameters.as_ref().map(|p| &p.click_threshold),
+                    );
+                    continue;
+                }
+
                 context.try_get_float_binding(output.to_string(), translated);
             }
             ActionBinding::Grab {
@@ -1309,8 +1800,48 @@ fn handle_sources(
                     context.instance.string_to_path(&translated_value).unwrap(),
                 );
             }
-            ActionBinding::Scroll { inputs, .. } => {
-                warn!("Got scroll binding for input {}, but these are currently unimplemented, skipping", inputs.scroll.output);
+            ActionBinding::Scroll {
+                path,
+                inputs:
+                    ScrollInput {
+                        scroll: ActionBindingOutput { output },
+                    },
+                parameters,
+            } => {
+                let Ok(translated) = path_translator(path).inspect_err(translate_warn(output))
+                else {
+                    continue;
+                };
+
+                if !context.find_action(output) {
+                    continue;
+                }
+
+                let v2_name =
+                    context.get_or_create_v2_extra_action(output, action_set_name, action_set);
+                trace!("suggesting {translated} for {output} (scroll)");
+                context.push_binding(
+                    v2_name,
+                    context.instance.string_to_path(&translated).unwrap(),
+                );
+
+                // Trackpads can be touched at the origin, so their position alone can't tell
+                // when a swipe starts.
+                if translated.ends_with("/trackpad") {
+                    if let Ok(touch_path) = path_translator(&format!("{translated}/touch")) {
+                        let touch_name = context.get_or_create_touch_extra_action(
+                            output,
+                            action_set_name,
+                            action_set,
+                        );
+                        context.push_binding(
+                            touch_name,
+                            context.instance.string_to_path(&touch_path).unwrap(),
+                        );
+                    }
+                }
+
+                context.add_custom_scroll_binding(output, &translated, parameters.as_ref());
             }
             ActionBinding::Trackpad(data) | ActionBinding::Joystick(data) => {
                 let Vector2Mode { path, inputs } = data;
@@ -1354,8 +1885,48 @@ fn handle_sources(
     }
 }
 
+fn handle_chord_bindings(
+    path_translator: impl Fn(&str) -> Result<String, InvalidActionPath>,
+    context: &mut BindingsProfileLoadContext,
+    action_set_name: &str,
+    action_set: &xr::ActionSet,
+    chords: &[ChordBinding],
+) {
+    for ChordBinding { output, inputs } in chords {
+        if !context.find_action(output) {
+            continue;
+        }
+
+        if !matches!(context.actions[&output.0], ActionData::Bool(_)) {
+            warn!("Chord output {output} is not a boolean action, ignoring");
+            continue;
+        }
+
+        let Some(translated) = inputs
+            .iter()
+            .map(|(path, component)| {
+                path_translator(&format!("{path}/{component}")).inspect_err(translate_warn(output))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .ok()
+        else {
+            continue;
+        };
+
+        if translated.is_empty() {
+            warn!("Chord for {output} has no inputs, ignoring");
+            continue;
+        }
+
+        trace!("binding chord {translated:?} to {output}");
+        context.add_chord_binding(output, &translated, action_set_name, action_set);
+    }
+}
+
 fn handle_skeleton_bindings(
-    context: &BindingsProfileLoadContext,
+    context: &mut BindingsProfileLoadContext,
+    session: &xr::Session<xr::AnyGraphics>,
+    hand_tracking: bool,
     bindings: &[SkeletonActionBinding],
 ) {
     for SkeletonActionBinding { output, path } in bindings {
@@ -1364,9 +1935,19 @@ fn handle_skeleton_bindings(
             continue;
         };
 
-        match &context.actions[&output.0] {
-            super::ActionData::Skeleton { hand, .. } => assert_eq!(hand, path),
-            _ => panic!("Expected skeleton action for skeleton binding {output}"),
+        match context.actions.get_mut(&output.0).unwrap() {
+            super::ActionData::Skeleton { hand, hand_tracker } => {
+                if hand != path {
+                    // The bindings know better than our guess from the action name, but the
+                    // hand tracker was made for the other hand.
+                    warn!("Skeleton action {output} is bound to {path:?} hand, but was declared for {hand:?} hand");
+                    *hand = *path;
+                    *hand_tracker = hand_tracking
+                        .then(|| create_hand_tracker(session, *path))
+                        .flatten();
+                }
+            }
+            _ => warn!("Expected skeleton action for skeleton binding {output}, ignoring"),
         }
     }
 }
@@ -1378,6 +1959,14 @@ fn handle_haptic_bindings(
     bindings: &[SimpleActionBinding],
 ) {
     for SimpleActionBinding { output, path } in bindings {
+        // Not a real input path, so there's nothing to translate or bind in OpenXR.
+        if path.ends_with("/output/force_feedback") {
+            if context.find_action(output) {
+                context.add_force_feedback_binding(output, path);
+            }
+            continue;
+        }
+
         let Ok(translated) = path_translator(path).inspect_err(translate_warn(output)) else {
             continue;
         };
@@ -1424,3 +2013,45 @@ fn handle_pose_bindings(context: &mut BindingsProfileLoadContext, bindings: &[Po
         trace!("bound {:?} to pose {output} for hand {hand:?}", *pose_ty);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::mirror_left_to_right;
+    use serde_json::json;
+
+    #[test]
+    fn mirrors_left_hand_sources() {
+        let mut bindings = json!({
+            "bindings": {
+                "/actions/main": {
+                    "sources": [
+                        {
+                            "mode": "button",
+                            "path": "/user/hand/left/input/a",
+                            "inputs": { "click": { "output": "/actions/main/in/jump" } }
+                        },
+                        {
+                            "mode": "trigger",
+                            "path": "/user/hand/left/input/trigger",
+                            "inputs": { "pull": { "output": "/actions/main/in/fire" } }
+                        },
+                        {
+                            "mode": "button",
+                            "path": "/user/hand/right/input/trigger",
+                            "inputs": { "click": { "output": "/actions/main/in/use" } }
+                        }
+                    ]
+                }
+            }
+        });
+        mirror_left_to_right(&mut bindings);
+
+        let sources = bindings["bindings"]["/actions/main"]["sources"]
+            .as_array()
+            .unwrap();
+        // The right trigger already has its own binding.
+        assert_eq!(sources.len(), 4);
+        assert_eq!(sources[3]["path"], "/user/hand/right/input/a");
+        assert_eq!(sources[3]["inputs"], sources[0]["inputs"]);
+    }
+}
@@ -1,23 +1,29 @@
+use crate::config::{ClickThresholds, Config};
 use crate::input::action_manifest::{
-    ButtonParameters, ControllerType, GrabParameters, LoadedActionDataMap, LowercaseActionPath,
+    ButtonParameters, ClickThresholdParams, ControllerType, GrabParameters, LoadedActionDataMap,
+    LowercaseActionPath, ScrollParameters,
 };
 use crate::input::custom_bindings::{
-    BindingData, DpadActions, DpadData, DpadDirection, GrabActions, GrabBindingData,
+    BindingData, ChordData, DoublePressData, DoublePressOutput, DpadActions, DpadData,
+    DpadDirection, GrabActions, GrabBindingData, HeldData, RepeatData, ScrollData, ScrollMode,
     ThresholdBindingData,
 };
 use crate::input::legacy::LegacyActions;
 use crate::input::skeletal::SkeletalInputActionData;
-use crate::input::ActionData::{Bool, Vector1, Vector2};
+use crate::input::ActionData::{Bool, Vector1, Vector2, Vector3};
 use crate::input::{ActionData, BoundPose, ExtraActionData, InteractionProfile};
 use crate::openxr_data;
 use crate::openxr_data::OpenXrData;
-use log::{info, trace, warn};
+use log::{debug, info, trace, warn};
 use openxr as xr;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 pub(super) struct BindingsLoadContext<'a> {
     pub action_sets: &'a HashMap<String, xr::ActionSet>,
     pub actions: LoadedActionDataMap,
+    /// Names of the actions declared in the action manifest, as opposed to ones we create.
+    manifest_actions: HashSet<String>,
     pub extra_actions: HashMap<String, ExtraActionData>,
     pub per_profile_bindings: HashMap<xr::Path, HashMap<String, Vec<BindingData>>>,
     pub per_profile_pose_bindings: HashMap<xr::Path, HashMap<String, BoundPose>>,
@@ -36,6 +42,7 @@ impl<'a> BindingsLoadContext<'a> {
     ) -> Self {
         BindingsLoadContext {
             action_sets,
+            manifest_actions: actions.keys().cloned().collect(),
             actions,
             extra_actions: Default::default(),
             per_profile_bindings: Default::default(),
@@ -55,6 +62,13 @@ impl BindingsLoadContext<'_> {
         controller_type: &'a ControllerType,
     ) -> Option<BindingsProfileLoadContext<'a>> {
         let instance = &openxr.instance;
+        if !profile.is_supported(instance.exts()) {
+            debug!(
+                "Skipping {} for {controller_type:?}, it's not supported by the runtime",
+                profile.profile_path()
+            );
+            return None;
+        }
         let Ok(interaction_profile) = instance.string_to_path(profile.profile_path()) else {
             warn!("Controller type {controller_type:?} has no OpenXR path supported?");
             return None;
@@ -78,6 +92,7 @@ impl BindingsLoadContext<'_> {
             controller_type,
             action_sets: self.action_sets,
             actions: &mut self.actions,
+            manifest_actions: &self.manifest_actions,
             extra_actions: &mut self.extra_actions,
             bindings_parsed,
             pose_bindings,
@@ -87,6 +102,7 @@ impl BindingsLoadContext<'_> {
             instance,
             hands,
             bindings: Vec::new(),
+            chords: Vec::new(),
         })
     }
 }
@@ -96,6 +112,7 @@ pub(super) struct BindingsProfileLoadContext<'a> {
     pub controller_type: &'a ControllerType,
     pub action_sets: &'a HashMap<String, xr::ActionSet>,
     pub actions: &'a mut LoadedActionDataMap,
+    manifest_actions: &'a HashSet<String>,
     extra_actions: &'a mut HashMap<String, ExtraActionData>,
     bindings_parsed: &'a mut HashMap<String, Vec<BindingData>>,
     pub pose_bindings: &'a mut HashMap<String, BoundPose>,
@@ -105,6 +122,8 @@ pub(super) struct BindingsProfileLoadContext<'a> {
     pub instance: &'a xr::Instance,
     pub hands: [xr::Path; 2],
     pub bindings: Vec<(String, xr::Path)>,
+    /// Chords loaded for this profile, along with their output and the paths they're bound to.
+    chords: Vec<(String, Arc<ChordData>, Vec<xr::Path>)>,
 }
 
 pub(super) struct DpadActivatorData {
@@ -204,7 +223,11 @@ impl BindingsProfileLoadContext<'_> {
     }
 
     pub fn try_get_v2_binding(&mut self, action_path: String, input_path: String) {
-        self.try_get_binding(action_path, input_path, action_match!(Vector2 { .. }));
+        self.try_get_binding(
+            action_path,
+            input_path,
+            action_match!(Vector2 { .. } | Vector3 { .. }),
+        );
     }
 
     pub fn add_custom_toggle_binding(&mut self, output: &LowercaseActionPath, translated: &str) {
@@ -218,14 +241,130 @@ impl BindingsProfileLoadContext<'_> {
         }
     }
 
+    pub fn add_force_feedback_binding(&mut self, output: &LowercaseActionPath, path: &str) {
+        if let Some(binding_hand) = parse_hand_from_path(self.instance, path) {
+            self.bindings_parsed
+                .entry(output.to_lowercase())
+                .or_default()
+                .push(BindingData::ForceFeedback(binding_hand));
+        } else {
+            warn!("Binding on {path} has unknown hand path, it will be ignored")
+        }
+    }
+
+    /// Binds a long press (or, if `short_press` is set, a press released before the delay) of
+    /// the button at `translated` to `output`.
+    pub fn add_held_binding(
+        &mut self,
+        output: &LowercaseActionPath,
+        translated: &str,
+        action_set_name: &str,
+        action_set: &xr::ActionSet,
+        delay: Option<f32>,
+        short_press: bool,
+    ) {
+        if !self.find_action(output) {
+            return;
+        }
+        let Some(binding_hand) = parse_hand_from_path(self.instance, translated) else {
+            warn!("Binding on {translated} has unknown hand path, it will be ignored");
+            return;
+        };
+
+        let as_name = self.get_or_create_held_extra_action(output, action_set_name, action_set);
+        trace!("suggesting {translated} for {output} (held)");
+        self.push_binding(as_name, self.instance.string_to_path(translated).unwrap());
+
+        self.bindings_parsed
+            .entry(output.to_lowercase())
+            .or_default()
+            .push(BindingData::Held(
+                HeldData::new(delay, short_press),
+                binding_hand,
+            ));
+    }
+
+    /// Binds a double press of the button at `translated` to `output`, or the single presses
+    /// of a button that also has a double press output.
+    pub fn add_double_press_binding(
+        &mut self,
+        output: &LowercaseActionPath,
+        translated: &str,
+        action_set_name: &str,
+        action_set: &xr::ActionSet,
+        window: Option<f32>,
+        press: DoublePressOutput,
+    ) {
+        if !self.find_action(output) {
+            return;
+        }
+        let Some(binding_hand) = parse_hand_from_path(self.instance, translated) else {
+            warn!("Binding on {translated} has unknown hand path, it will be ignored");
+            return;
+        };
+
+        let as_name = self.get_or_create_double_extra_action(output, action_set_name, action_set);
+        trace!("suggesting {translated} for {output} ({press:?})");
+        self.push_binding(as_name, self.instance.string_to_path(translated).unwrap());
+
+        self.bindings_parsed
+            .entry(output.to_lowercase())
+            .or_default()
+            .push(BindingData::DoublePress(
+                DoublePressData::new(window, press),
+                binding_hand,
+            ));
+    }
+
+    /// Binds repeated presses of `output`, at `rate` per second, for as long as the button at
+    /// `translated` is held.
+    pub fn add_repeat_binding(
+        &mut self,
+        output: &LowercaseActionPath,
+        translated: &str,
+        action_set_name: &str,
+        action_set: &xr::ActionSet,
+        rate: Option<f32>,
+    ) {
+        if !self.find_action(output) {
+            return;
+        }
+        let Some(binding_hand) = parse_hand_from_path(self.instance, translated) else {
+            warn!("Binding on {translated} has unknown hand path, it will be ignored");
+            return;
+        };
+
+        let as_name = self.get_or_create_repeat_extra_action(output, action_set_name, action_set);
+        trace!("suggesting {translated} for {output} (auto repeat)");
+        self.push_binding(as_name, self.instance.string_to_path(translated).unwrap());
+
+        self.bindings_parsed
+            .entry(output.to_lowercase())
+            .or_default()
+            .push(BindingData::Repeat(RepeatData::new(rate), binding_hand));
+    }
+
     pub fn add_custom_button_binding(
         &mut self,
         output: &LowercaseActionPath,
         translated: &str,
         parameters: Option<&ButtonParameters>,
+    ) {
+        self.add_custom_threshold_binding(
+            output,
+            translated,
+            parameters.map(|x| &x.click_threshold),
+        );
+    }
+
+    pub fn add_custom_threshold_binding(
+        &mut self,
+        output: &LowercaseActionPath,
+        translated: &str,
+        thresholds: Option<&ClickThresholdParams>,
     ) {
         if let Some(binding_hand) = parse_hand_from_path(self.instance, translated) {
-            let thresholds = parameters.map(|x| &x.click_threshold);
+            let configured = configured_click_thresholds(translated);
             self.bindings_parsed
                 .entry(output.to_lowercase())
                 .or_default()
@@ -233,10 +372,12 @@ impl BindingsProfileLoadContext<'_> {
                     ThresholdBindingData::new(
                         thresholds
                             .and_then(|x| x.click_activate_threshold.as_ref())
-                            .map(|x| x.0),
+                            .map(|x| x.0)
+                            .or(configured.click),
                         thresholds
                             .and_then(|x| x.click_deactivate_threshold.as_ref())
-                            .map(|x| x.0),
+                            .map(|x| x.0)
+                            .or(configured.release),
                     ),
                     binding_hand,
                 ));
@@ -248,6 +389,122 @@ impl BindingsProfileLoadContext<'_> {
         }
     }
 
+    pub fn add_chord_binding(
+        &mut self,
+        output: &LowercaseActionPath,
+        translated: &[String],
+        action_set_name: &str,
+        action_set: &xr::ActionSet,
+    ) {
+        let Some(binding_hand) = parse_hand_from_path(self.instance, &translated[0]) else {
+            warn!(
+                "Chord on {} has unknown hand path, it will be ignored",
+                translated[0]
+            );
+            return;
+        };
+
+        let mut inputs = Vec::with_capacity(translated.len());
+        let mut paths = Vec::with_capacity(translated.len());
+        for input in translated {
+            let Some(hand) = parse_hand_from_path(self.instance, input) else {
+                warn!("Chord on {input} has unknown hand path, it will be ignored");
+                return;
+            };
+            let path = self.instance.string_to_path(input).unwrap();
+
+            // Share chord inputs that use the same action set and same bound path
+            let key = format!("{input}-{action_set_name}-chord");
+            let action = self.actions.entry(key.clone()).or_insert_with(|| {
+                let clean_path = input.replace("/", "_");
+                let name = format!("xrizer-chord-{clean_path}");
+                let localized = format!("XRizer chord input ({input})");
+                Bool(
+                    action_set
+                        .create_action(&name, &localized, &self.hands)
+                        .unwrap(),
+                )
+            });
+            let Bool(action) = action else {
+                unreachable!();
+            };
+
+            inputs.push((action.clone(), hand));
+            paths.push(path);
+            self.bindings.push((key, path));
+        }
+
+        let chord = Arc::new(ChordData::new(inputs));
+        self.bindings_parsed
+            .entry(output.to_lowercase())
+            .or_default()
+            .push(BindingData::Chord(chord.clone(), binding_hand));
+        self.chords.push((output.to_lowercase(), chord, paths));
+    }
+
+    /// Masks the actions bound directly to the buttons of each chord, so they don't activate
+    /// while the chord is held. Must be called after all bindings for the profile are loaded.
+    pub fn add_chord_masks(&mut self) {
+        for (output, chord, paths) in &self.chords {
+            for (action, path) in &self.bindings {
+                if action == output
+                    || !paths.contains(path)
+                    || !self.manifest_actions.contains(action)
+                    || !matches!(self.actions.get(action), Some(Bool(_)))
+                {
+                    continue;
+                }
+
+                let Some(hand) = self
+                    .instance
+                    .path_to_string(*path)
+                    .ok()
+                    .and_then(|p| parse_hand_from_path(self.instance, &p))
+                else {
+                    continue;
+                };
+
+                trace!("masking {action} while chord for {output} is held");
+                self.bindings_parsed
+                    .entry(action.clone())
+                    .or_default()
+                    .push(BindingData::ChordMask(chord.clone(), hand));
+            }
+        }
+    }
+
+    pub fn add_custom_scroll_binding(
+        &mut self,
+        output: &LowercaseActionPath,
+        translated: &str,
+        parameters: Option<&ScrollParameters>,
+    ) {
+        let Some(binding_hand) = parse_hand_from_path(self.instance, translated) else {
+            info!("Binding on {translated} has unknown hand path, it will be ignored");
+            return;
+        };
+
+        let mode = match parameters.and_then(|x| x.scroll_mode.as_deref()) {
+            None | Some("discrete") => ScrollMode::Discrete,
+            Some("smooth") => ScrollMode::Smooth,
+            Some(other) => {
+                warn!("Unknown scroll mode {other} for {output}, using discrete");
+                ScrollMode::Discrete
+            }
+        };
+        let multiplier = parameters
+            .and_then(|x| x.smooth_scroll_multiplier.as_ref())
+            .map(|x| x.0);
+
+        self.bindings_parsed
+            .entry(output.to_lowercase())
+            .or_default()
+            .push(BindingData::Scroll(
+                ScrollData::new(mode, multiplier, translated.ends_with("/trackpad")),
+                binding_hand,
+            ));
+    }
+
     pub fn add_custom_grab_binding(
         &mut self,
         output: &LowercaseActionPath,
@@ -289,6 +546,7 @@ impl BindingsProfileLoadContext<'_> {
             Option<DpadActivatorData>,
             Option<DpadHapticData>,
         ),
+        parameters: Option<&crate::input::action_manifest::DpadParameters>,
     ) {
         if let Some(binding_hand) = parse_hand_from_path(self.instance, parent_path) {
             let (parent_action, click_or_touch, haptic) = created_actions;
@@ -305,11 +563,16 @@ impl BindingsProfileLoadContext<'_> {
                 .entry(action_name.to_string())
                 .or_default()
                 .push(BindingData::Dpad(
-                    DpadData {
+                    DpadData::new(
                         dpad_actions,
                         direction,
-                        last_state: false.into(),
-                    },
+                        parameters.map_or(DpadData::DEFAULT_DEADZONE, |p| {
+                            p.deadzone_pct.0 as f32 / 100.0
+                        }),
+                        parameters.map_or(DpadData::DEFAULT_OVERLAP, |p| {
+                            p.overlap_pct.0 as f32 / 100.0
+                        }),
+                    ),
                     binding_hand,
                 ));
         } else {
@@ -351,6 +614,96 @@ impl BindingsProfileLoadContext<'_> {
         as_name
     }
 
+    pub fn get_or_create_held_extra_action(
+        &mut self,
+        output: &LowercaseActionPath,
+        action_set_name: &str,
+        action_set: &xr::ActionSet,
+    ) -> String {
+        let name_only = output.rsplit_once('/').unwrap().1;
+        let held_name = format!("{name_only}_held");
+        let as_name = format!("{}/{}", action_set_name, held_name);
+
+        let mut extra_data = self
+            .extra_actions
+            .remove(&output.to_lowercase())
+            .unwrap_or_default();
+
+        if extra_data.held_action.is_none() {
+            let localized = format!("{name_only} held");
+            let action = action_set
+                .create_action(&held_name, &localized, &self.hands)
+                .unwrap();
+
+            self.actions.insert(as_name.clone(), Bool(action.clone()));
+
+            extra_data.held_action = Some(action);
+        }
+        self.extra_actions.insert(output.to_lowercase(), extra_data);
+
+        as_name
+    }
+
+    pub fn get_or_create_double_extra_action(
+        &mut self,
+        output: &LowercaseActionPath,
+        action_set_name: &str,
+        action_set: &xr::ActionSet,
+    ) -> String {
+        let name_only = output.rsplit_once('/').unwrap().1;
+        let double_name = format!("{name_only}_dbl");
+        let as_name = format!("{}/{}", action_set_name, double_name);
+
+        let mut extra_data = self
+            .extra_actions
+            .remove(&output.to_lowercase())
+            .unwrap_or_default();
+
+        if extra_data.double_action.is_none() {
+            let localized = format!("{name_only} double press");
+            let action = action_set
+                .create_action(&double_name, &localized, &self.hands)
+                .unwrap();
+
+            self.actions.insert(as_name.clone(), Bool(action.clone()));
+
+            extra_data.double_action = Some(action);
+        }
+        self.extra_actions.insert(output.to_lowercase(), extra_data);
+
+        as_name
+    }
+
+    pub fn get_or_create_repeat_extra_action(
+        &mut self,
+        output: &LowercaseActionPath,
+        action_set_name: &str,
+        action_set: &xr::ActionSet,
+    ) -> String {
+        let name_only = output.rsplit_once('/').unwrap().1;
+        let repeat_name = format!("{name_only}_rpt");
+        let as_name = format!("{}/{}", action_set_name, repeat_name);
+
+        let mut extra_data = self
+            .extra_actions
+            .remove(&output.to_lowercase())
+            .unwrap_or_default();
+
+        if extra_data.repeat_action.is_none() {
+            let localized = format!("{name_only} auto repeat");
+            let action = action_set
+                .create_action(&repeat_name, &localized, &self.hands)
+                .unwrap();
+
+            self.actions.insert(as_name.clone(), Bool(action.clone()));
+
+            extra_data.repeat_action = Some(action);
+        }
+        self.extra_actions.insert(output.to_lowercase(), extra_data);
+
+        as_name
+    }
+
     pub fn get_or_create_analog_extra_action(
         &mut self,
         output: &LowercaseActionPath,
@@ -419,6 +772,36 @@ impl BindingsProfileLoadContext<'_> {
         float_name_with_as
     }
 
+    pub fn get_or_create_touch_extra_action(
+        &mut self,
+        output: &LowercaseActionPath,
+        action_set_name: &str,
+        action_set: &xr::ActionSet,
+    ) -> String {
+        let name_only = output.rsplit_once('/').unwrap().1;
+        let touch_name = format!("{name_only}_tch");
+        let as_name = format!("{}/{}", action_set_name, touch_name);
+
+        let mut extra_data = self
+            .extra_actions
+            .remove(&output.to_lowercase())
+            .unwrap_or_default();
+
+        if extra_data.touch_action.is_none() {
+            let localized = format!("{name_only} touch");
+            let action = action_set
+                .create_action(&touch_name, &localized, &self.hands)
+                .unwrap();
+
+            self.actions.insert(as_name.clone(), Bool(action.clone()));
+
+            extra_data.touch_action = Some(action);
+        }
+        self.extra_actions.insert(output.to_lowercase(), extra_data);
+
+        as_name
+    }
+
     pub fn get_or_create_grab_action_pair(
         &mut self,
         output: &LowercaseActionPath,
@@ -602,3 +985,15 @@ impl BindingsProfileLoadContext<'_> {
         )
     }
 }
+
+/// The thresholds from the config for the trigger or grip, which `path` may be on.
+fn configured_click_thresholds(path: &str) -> ClickThresholds {
+    let input = if path.contains("/input/trigger/") {
+        "trigger"
+    } else if path.contains("/input/squeeze/") {
+        "grip"
+    } else {
+        return ClickThresholds::default();
+    };
+    Config::get().click_thresholds(input)
+}
@@ -0,0 +1,276 @@
+use super::{origins::enumerate_bound_sources, ActionKey, ActionSetKey, Input, LoadedActions};
+use crate::openxr_data::{self, SessionData};
+use log::debug;
+use openvr as vr;
+use openxr as xr;
+use slotmap::{Key, KeyData, SecondaryMap, SlotMap};
+use std::collections::{HashMap, HashSet};
+
+/// Both hands and the gamepad.
+const DEVICE_COUNT: usize = 3;
+
+/// An action set activated with UpdateActionState.
+#[derive(Clone, PartialEq)]
+struct RequestedSet {
+    key: ActionSetKey,
+    /// Index into the devices, or None if the set isn't restricted to a device.
+    device: Option<usize>,
+    priority: i32,
+}
+
+/// The devices each action may read its state from, according to the priorities and device
+/// restrictions of the active action sets.
+#[derive(Default)]
+pub(super) struct ActiveSets {
+    requested: Vec<RequestedSet>,
+    profiles: Option<[xr::Path; DEVICE_COUNT]>,
+    /// Only actions that are blocked on any device are present.
+    blocked: SecondaryMap<ActionKey, [bool; DEVICE_COUNT]>,
+}
+
+impl<C: openxr_data::Compositor> Input<C> {
+    fn device_info(&self) -> [(&openxr_data::HandInfo, vr::VRInputValueHandle_t); DEVICE_COUNT] {
+        [
+            (&self.openxr.left_hand, self.left_hand_key.data().as_ffi()),
+            (&self.openxr.right_hand, self.right_hand_key.data().as_ffi()),
+            (&self.openxr.gamepad, self.gamepad_key.data().as_ffi()),
+        ]
+    }
+
+    /// Works out which actions are blocked on which devices, after the action sets have been
+    /// synced. This is only redone when the requested sets or the interaction profiles change.
+    pub(super) fn update_active_sets(
+        &self,
+        data: &SessionData,
+        loaded: &LoadedActions,
+        set_map: &SlotMap<ActionSetKey, String>,
+        sets: &[vr::VRActiveActionSet_t],
+    ) {
+        let devices = self.device_info();
+        let requested: Vec<_> = sets
+            .iter()
+            .map(|set| RequestedSet {
+                key: ActionSetKey::from(KeyData::from_ffi(set.ulActionSet)),
+                device: match set.ulRestrictedToDevice {
+                    vr::k_ulInvalidInputValueHandle => None,
+                    handle => {
+                        let device = devices.iter().position(|(_, h)| *h == handle);
+                        if device.is_none() {
+                            crate::warn_once!(
+                                "Action sets can only be restricted to hands or the gamepad."
+                            );
+                        }
+                        device
+                    }
+                },
+                priority: set.nPriority,
+            })
+            .collect();
+        let profiles = devices.map(|(info, _)| info.profile_path.load());
+
+        let mut active_sets = self.active_sets.write().unwrap();
+        if active_sets.requested == requested && active_sets.profiles == Some(profiles) {
+            return;
+        }
+        active_sets.blocked = if requested
+            .iter()
+            .all(|set| set.device.is_none() && set.priority == requested[0].priority)
+        {
+            // Nothing to block, so skip enumerating the bound sources.
+            SecondaryMap::new()
+        } else {
+            self.blocked_actions(data, loaded, set_map, &requested)
+        };
+        active_sets.requested = requested;
+        active_sets.profiles = Some(profiles);
+    }
+
+    fn blocked_actions(
+        &self,
+        data: &SessionData,
+        loaded: &LoadedActions,
+        set_map: &SlotMap<ActionSetKey, String>,
+        requested: &[RequestedSet],
+    ) -> SecondaryMap<ActionKey, [bool; DEVICE_COUNT]> {
+        let action_map = self.action_map.read().unwrap();
+        let set_of_action = |key: ActionKey| {
+            let path = &action_map.get(key)?.path;
+            requested.iter().map(|set| set.key).find(|set| {
+                set_map.get(*set).is_some_and(|name| {
+                    path.strip_prefix(name.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+                })
+            })
+        };
+
+        let mut blocked: SecondaryMap<ActionKey, [bool; DEVICE_COUNT]> = SecondaryMap::new();
+        for (device, (info, _)) in self.device_info().into_iter().enumerate() {
+            // The priority of each set on this device, if it's active on it at all.
+            let mut priorities = HashMap::new();
+            for set in requested
+                .iter()
+                .filter(|set| set.device.is_none_or(|d| d == device))
+            {
+                let priority = priorities.entry(set.key).or_insert(set.priority);
+                *priority = set.priority.max(*priority);
+            }
+
+            // The inputs of this device each action is bound to, grouped by priority.
+            let mut by_priority: Vec<(i32, ActionKey, HashSet<String>)> = Vec::new();
+            for (key, action) in loaded.actions.iter() {
+                let Some(set) = set_of_action(key) else {
+                    continue;
+                };
+                let Some(priority) = priorities.get(&set) else {
+                    blocked.entry(key).unwrap().or_default()[device] = true;
+                    continue;
+                };
+                let Some(raw_action) = action.raw_action() else {
+                    continue;
+                };
+                let sources =
+                    enumerate_bound_sources(&self.openxr.instance, &data.session, raw_action)
+                        .inspect_err(|e| debug!("Couldn't enumerate bound sources: {e}"))
+                        .unwrap_or_default();
+                let inputs = sources
+                    .into_iter()
+                    .filter_map(|source| self.openxr.instance.path_to_string(source).ok())
+                    .filter_map(|source| {
+                        let rest = source.strip_prefix(info.path_name())?.strip_prefix('/')?;
+                        Some(format!("{}/{}", info.path_name(), input_of(rest)))
+                    })
+                    .collect();
+                by_priority.push((*priority, key, inputs));
+            }
+            by_priority.sort_by_key(|(priority, ..)| std::cmp::Reverse(*priority));
+
+            // Inputs used by a set take precedence over all sets with a lower priority. OpenXR
+            // combines the state of all of an action's bindings, so an action loses all of them
+            // on this device if any of its inputs are taken.
+            let mut taken = HashSet::new();
+            for group in by_priority.chunk_by(|(a, ..), (b, ..)| a == b) {
+                for (_, key, inputs) in group {
+                    if !inputs.is_disjoint(&taken) {
+                        blocked.entry(*key).unwrap().or_default()[device] = true;
+                    }
+                }
+                for (_, _, inputs) in group {
+                    taken.extend(inputs.iter().cloned());
+                }
+            }
+        }
+
+        blocked
+    }
+
+    /// Narrows the device an action's state is requested for to the devices the active action
+    /// sets allow it to use. Returns None if none of them are allowed.
+    pub(super) fn restrict_to_active_sets(
+        &self,
+        action: vr::VRActionHandle_t,
+        restrict_to_device: vr::VRInputValueHandle_t,
+    ) -> Option<vr::VRInputValueHandle_t> {
+        let active_sets = self.active_sets.read().unwrap();
+        let Some(blocked) = active_sets
+            .blocked
+            .get(ActionKey::from(KeyData::from_ffi(action)))
+        else {
+            return Some(restrict_to_device);
+        };
+
+        let devices = self.device_info();
+        if restrict_to_device == vr::k_ulInvalidInputValueHandle {
+            // There's no way to get the combined state of only some of the devices, so unless
+            // both hands are allowed, the first allowed device is used.
+            if !blocked[0] && !blocked[1] {
+                return Some(restrict_to_device);
+            }
+            return devices
+                .iter()
+                .zip(blocked)
+                .find(|(_, blocked)| !**blocked)
+                .map(|((_, handle), _)| *handle);
+        }
+        match devices
+            .iter()
+            .position(|(_, handle)| *handle == restrict_to_device)
+        {
+            Some(device) if blocked[device] => None,
+            _ => Some(restrict_to_device),
+        }
+    }
+}
+
+/// The input a source belongs to, i.e. "input/trigger" for "input/trigger/click".
+fn input_of(source: &str) -> &str {
+    match source.match_indices('/').nth(1) {
+        Some((idx, _)) => &source[..idx],
+        None => source,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::profiles::knuckles::Knuckles;
+    use crate::input::tests::Fixture;
+    use fakexr::UserPath::*;
+    use openvr as vr;
+
+    #[test]
+    fn priority_and_device_restriction() {
+        let f = Fixture::new();
+        let default = f.get_action_set_handle(c"/actions/default");
+        let tool = f.get_action_set_handle(c"/actions/tool");
+        let fire = f.get_action_handle(c"/actions/default/in/fire");
+        let jump = f.get_action_handle(c"/actions/default/in/jump");
+        let tool_use = f.get_action_handle(c"/actions/tool/in/use");
+        let left = f.get_input_source_handle(c"/user/hand/left");
+        let right = f.get_input_source_handle(c"/user/hand/right");
+        f.load_actions(c"actions_priority.json");
+
+        f.set_interaction_profile(&Knuckles, LeftHand);
+        f.set_interaction_profile(&Knuckles, RightHand);
+        for action in [fire, jump, tool_use] {
+            let raw = f.get_action::<bool>(action);
+            fakexr::set_action_state(raw, true.into(), LeftHand);
+            fakexr::set_action_state(raw, true.into(), RightHand);
+        }
+
+        // The tool set only takes over the trigger of the left hand.
+        f.sync_sets(&mut [
+            vr::VRActiveActionSet_t {
+                ulActionSet: default,
+                ..Default::default()
+            },
+            vr::VRActiveActionSet_t {
+                ulActionSet: tool,
+                ulRestrictedToDevice: left,
+                nPriority: 1,
+                ..Default::default()
+            },
+        ]);
+
+        let active = |action, device| {
+            let state = f.get_bool_state_hand(action, device).unwrap();
+            state.bActive && state.bState
+        };
+        assert!(active(tool_use, left));
+        assert!(!active(tool_use, right));
+        assert!(!active(fire, left));
+        assert!(active(fire, right));
+        assert!(active(jump, left));
+        assert!(active(jump, right));
+        let state = f.get_bool_state(fire).unwrap();
+        assert!(state.bActive);
+        assert_eq!(state.activeOrigin, right);
+
+        // Without the tool set, the default set gets the trigger back.
+        f.sync(vr::VRActiveActionSet_t {
+            ulActionSet: default,
+            ..Default::default()
+        });
+        assert!(active(fire, left));
+        assert!(active(fire, right));
+        assert!(!active(tool_use, left));
+    }
+}
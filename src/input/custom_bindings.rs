@@ -3,8 +3,10 @@ use crate::openxr_data::SessionData;
 use log::error;
 use openxr as xr;
 use openxr::{Haptic, HapticVibration};
-use std::f32::consts::{FRAC_PI_4, PI};
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Copy)]
 pub(super) enum DpadDirection {
@@ -25,14 +27,58 @@ pub(super) struct DpadData {
     pub dpad_actions: DpadActions,
     pub direction: DpadDirection,
     pub last_state: AtomicBool,
+    /// Radius of the center zone, as a fraction of the full range.
+    pub deadzone: f32,
+    /// How far each direction's wedge extends into its neighbors, as a fraction of its own size.
+    pub overlap: f32,
 }
 
 impl DpadData {
-    const CENTER_ZONE: f32 = 0.5;
+    pub const DEFAULT_DEADZONE: f32 = 0.5;
+    pub const DEFAULT_OVERLAP: f32 = 0.5;
 
     // Thresholds for force-activated dpads, experimentally chosen to match SteamVR
     const DPAD_CLICK_THRESHOLD: f32 = 0.33;
     const DPAD_RELEASE_THRESHOLD: f32 = 0.2;
+
+    pub fn new(
+        dpad_actions: DpadActions,
+        direction: DpadDirection,
+        deadzone: f32,
+        overlap: f32,
+    ) -> Self {
+        Self {
+            dpad_actions,
+            direction,
+            last_state: false.into(),
+            deadzone: deadzone.clamp(0.0, 1.0),
+            overlap: overlap.clamp(0.0, 1.0),
+        }
+    }
+
+    fn in_bounds(&self, xr::Vector2f { x, y }: xr::Vector2f) -> bool {
+        // convert to polar coordinates
+        let radius = x.hypot(y);
+        let angle = y.atan2(x);
+
+        let center_angle = match self.direction {
+            DpadDirection::North => FRAC_PI_2,
+            DpadDirection::East => 0.0,
+            DpadDirection::South => -FRAC_PI_2,
+            DpadDirection::West => PI,
+            DpadDirection::Center => return radius < self.deadzone,
+        };
+
+        // Without overlap, each direction gets a pi/2 wedge.
+        let half_width = FRAC_PI_4 * (1.0 + self.overlap);
+        let mut distance = (angle - center_angle).abs();
+        if distance > PI {
+            distance = 2.0 * PI - distance;
+        }
+
+        radius >= self.deadzone && distance <= half_width
+    }
+
     fn state<G>(
         &self,
         session: &xr::Session<G>,
@@ -71,30 +117,7 @@ impl DpadData {
             return Ok(None);
         }
 
-        // convert to polar coordinates
-        let xr::Vector2f { x, y } = parent_state.current_state;
-        let radius = x.hypot(y);
-        let angle = y.atan2(x);
-
-        // pi/2 wedges, no overlap
-        let in_bounds = match self.direction {
-            DpadDirection::North => {
-                radius >= Self::CENTER_ZONE && (FRAC_PI_4..=3.0 * FRAC_PI_4).contains(&angle)
-            }
-            DpadDirection::East => {
-                radius >= Self::CENTER_ZONE && (-FRAC_PI_4..=FRAC_PI_4).contains(&angle)
-            }
-            DpadDirection::South => {
-                radius >= Self::CENTER_ZONE && (-3.0 * FRAC_PI_4..=-FRAC_PI_4).contains(&angle)
-            }
-            // west section is disjoint with atan2
-            DpadDirection::West => {
-                radius >= Self::CENTER_ZONE
-                    && ((3.0 * FRAC_PI_4..=PI).contains(&angle)
-                        || (-PI..=-3.0 * FRAC_PI_4).contains(&angle))
-            }
-            DpadDirection::Center => radius < Self::CENTER_ZONE,
-        };
+        let in_bounds = self.in_bounds(parent_state.current_state);
 
         ret_state.current_state = in_bounds;
         if self
@@ -195,6 +218,10 @@ impl GrabBindingData {
 #[derive(Default)]
 pub(super) struct ToggleData {
     pub last_state: AtomicBool,
+    /// Whether the current press of the underlying button has already flipped the state.
+    /// This keeps the state from flipping multiple times if the app reads the action more
+    /// than once per sync.
+    press_handled: AtomicBool,
 }
 
 impl ToggleData {
@@ -213,9 +240,15 @@ impl ToggleData {
         }
 
         let s = self.last_state.load(Ordering::Relaxed);
-        let current_state = if state.changed_since_last_sync && state.current_state {
-            !s
+        let current_state = if state.current_state {
+            let already_handled = self.press_handled.swap(true, Ordering::Relaxed);
+            if already_handled {
+                s
+            } else {
+                !s
+            }
         } else {
+            self.press_handled.store(false, Ordering::Relaxed);
             s
         };
 
@@ -238,6 +271,401 @@ impl ToggleData {
     }
 }
 
+/// The time presses are measured with. Tests move it forward instead of sleeping.
+fn now() -> Instant {
+    #[cfg(test)]
+    let offset = tests::CLOCK_OFFSET.with(std::cell::Cell::get);
+    #[cfg(not(test))]
+    let offset = Duration::ZERO;
+    Instant::now() + offset
+}
+
+#[derive(Default)]
+struct HeldState {
+    /// Sync this state was last updated for.
+    packet_num: Option<u32>,
+    press_start: Option<Instant>,
+    output: bool,
+    changed: bool,
+}
+
+pub(super) struct HeldData {
+    delay: Duration,
+    /// If set, this binding is active for presses shorter than the delay instead of longer ones.
+    short_press: bool,
+    state: Mutex<HeldState>,
+}
+
+impl HeldData {
+    /// Seconds to hold the button before the held output activates.
+    pub const DEFAULT_DELAY: f32 = 0.5;
+
+    pub fn new(delay: Option<f32>, short_press: bool) -> Self {
+        Self {
+            delay: Duration::from_secs_f32(delay.unwrap_or(Self::DEFAULT_DELAY).max(0.0)),
+            short_press,
+            state: Default::default(),
+        }
+    }
+
+    fn state<G>(
+        &self,
+        extra_action: &ExtraActionData,
+        session: &xr::Session<G>,
+        subaction_path: xr::Path,
+        packet_num: u32,
+    ) -> xr::Result<Option<xr::ActionState<bool>>> {
+        let Some(action_to_read) = &extra_action.held_action else {
+            return Ok(None);
+        };
+        let state = action_to_read.state(session, subaction_path)?;
+        let mut held = self.state.lock().unwrap();
+        if !state.is_active {
+            *held = Default::default();
+            return Ok(None);
+        }
+
+        // Only advance once per sync, so the short press pulse is seen by every read of it.
+        if held.packet_num != Some(packet_num) {
+            held.packet_num = Some(packet_num);
+            let now = now();
+            let output = match (state.current_state, held.press_start) {
+                (true, _) => {
+                    let start = *held.press_start.get_or_insert(now);
+                    now.saturating_duration_since(start) >= self.delay && !self.short_press
+                }
+                // Whether a press was short is only known once it's released, so the short
+                // press output fires for a single sync then.
+                (false, Some(start)) => {
+                    held.press_start = None;
+                    self.short_press && now.saturating_duration_since(start) < self.delay
+                }
+                (false, None) => false,
+            };
+            held.changed = output != held.output;
+            held.output = output;
+        }
+
+        Ok(Some(xr::ActionState {
+            current_state: held.output,
+            changed_since_last_sync: held.changed,
+            last_change_time: state.last_change_time,
+            is_active: true,
+        }))
+    }
+}
+
+#[derive(Default)]
+struct RepeatState {
+    /// Sync this state was last updated for.
+    packet_num: Option<u32>,
+    press_start: Option<Instant>,
+    output: bool,
+    changed: bool,
+}
+
+pub(super) struct RepeatData {
+    /// Time between the starts of two presses. Each press lasts half of it.
+    period: Duration,
+    state: Mutex<RepeatState>,
+}
+
+impl RepeatData {
+    /// Presses per second while the button is held.
+    pub const DEFAULT_RATE: f32 = 10.0;
+
+    pub fn new(rate: Option<f32>) -> Self {
+        let rate = rate.unwrap_or(Self::DEFAULT_RATE).max(0.1);
+        Self {
+            period: Duration::from_secs_f32(1.0 / rate),
+            state: Default::default(),
+        }
+    }
+
+    fn state<G>(
+        &self,
+        extra_action: &ExtraActionData,
+        session: &xr::Session<G>,
+        subaction_path: xr::Path,
+        packet_num: u32,
+    ) -> xr::Result<Option<xr::ActionState<bool>>> {
+        let Some(action_to_read) = &extra_action.repeat_action else {
+            return Ok(None);
+        };
+        let state = action_to_read.state(session, subaction_path)?;
+        let mut repeat = self.state.lock().unwrap();
+        if !state.is_active {
+            *repeat = Default::default();
+            return Ok(None);
+        }
+
+        // Only advance once per sync, so every pulse is seen by apps that read the action
+        // more than once in between.
+        if repeat.packet_num != Some(packet_num) {
+            repeat.packet_num = Some(packet_num);
+            let output = if state.current_state {
+                let held = repeat
+                    .press_start
+                    .get_or_insert_with(Instant::now)
+                    .elapsed();
+                let half_periods = held.as_secs_f32() / (self.period.as_secs_f32() / 2.0);
+                (half_periods as u32) % 2 == 0
+            } else {
+                repeat.press_start = None;
+                false
+            };
+            repeat.changed = output != repeat.output;
+            repeat.output = output;
+        }
+
+        Ok(Some(xr::ActionState {
+            current_state: repeat.output,
+            changed_since_last_sync: repeat.changed,
+            last_change_time: state.last_change_time,
+            is_active: true,
+        }))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum DoublePressOutput {
+    /// Active while the second press of a double press is held.
+    Double,
+    /// Active for a single press. If `delayed` is set, the press is only reported once the
+    /// double press window has passed without a second press. Otherwise it is reported
+    /// immediately, and only the second press of a double press is suppressed.
+    Single { delayed: bool },
+}
+
+#[derive(Default, Clone, Copy)]
+enum DoublePressPhase {
+    #[default]
+    Idle,
+    FirstPress,
+    Released(Instant),
+    SecondPress,
+}
+
+#[derive(Default)]
+struct DoublePressState {
+    /// Sync this state was last updated for.
+    packet_num: Option<u32>,
+    phase: DoublePressPhase,
+    output: bool,
+    changed: bool,
+}
+
+pub(super) struct DoublePressData {
+    window: Duration,
+    output: DoublePressOutput,
+    state: Mutex<DoublePressState>,
+}
+
+impl DoublePressData {
+    /// Seconds after releasing the button in which a second press counts as a double press.
+    pub const DEFAULT_WINDOW: f32 = 0.25;
+
+    pub fn new(window: Option<f32>, output: DoublePressOutput) -> Self {
+        Self {
+            window: Duration::from_secs_f32(window.unwrap_or(Self::DEFAULT_WINDOW).max(0.0)),
+            output,
+            state: Default::default(),
+        }
+    }
+
+    fn state<G>(
+        &self,
+        extra_action: &ExtraActionData,
+        session: &xr::Session<G>,
+        subaction_path: xr::Path,
+        packet_num: u32,
+    ) -> xr::Result<Option<xr::ActionState<bool>>> {
+        let Some(action_to_read) = &extra_action.double_action else {
+            return Ok(None);
+        };
+        let state = action_to_read.state(session, subaction_path)?;
+        let mut double = self.state.lock().unwrap();
+        if !state.is_active {
+            *double = Default::default();
+            return Ok(None);
+        }
+
+        if double.packet_num != Some(packet_num) {
+            double.packet_num = Some(packet_num);
+            let pressed = state.current_state;
+
+            // Whether a lone press was completed by the window running out this sync.
+            let mut single_finished = false;
+            use DoublePressPhase::*;
+            double.phase = match (double.phase, pressed) {
+                (Idle, false) => Idle,
+                (Idle, true) | (FirstPress, true) => FirstPress,
+                (FirstPress, false) => Released(Instant::now()),
+                (Released(at), _) if at.elapsed() > self.window => {
+                    single_finished = true;
+                    if pressed {
+                        FirstPress
+                    } else {
+                        Idle
+                    }
+                }
+                (Released(_), true) | (SecondPress, true) => SecondPress,
+                (Released(at), false) => Released(at),
+                (SecondPress, false) => Idle,
+            };
+
+            let output = match self.output {
+                DoublePressOutput::Double => matches!(double.phase, SecondPress),
+                DoublePressOutput::Single { delayed: true } => single_finished,
+                DoublePressOutput::Single { delayed: false } => {
+                    matches!(double.phase, FirstPress)
+                }
+            };
+            double.changed = output != double.output;
+            double.output = output;
+        }
+
+        Ok(Some(xr::ActionState {
+            current_state: double.output,
+            changed_since_last_sync: double.changed,
+            last_change_time: state.last_change_time,
+            is_active: true,
+        }))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ScrollMode {
+    /// Scroll by whole notches, keeping any leftover movement for later.
+    Discrete,
+    /// Scroll continuously, smoothing out jitter in the input.
+    Smooth,
+}
+
+#[derive(Default)]
+struct ScrollState {
+    /// Sync this state was last updated for.
+    packet_num: Option<u32>,
+    /// When this state was last updated, to scale joystick scrolling by the time between syncs.
+    last_update: Option<Instant>,
+    last_position: Option<xr::Vector2f>,
+    remainder: xr::Vector2f,
+    output: xr::Vector2f,
+    changed: bool,
+}
+
+pub(super) struct ScrollData {
+    mode: ScrollMode,
+    multiplier: f32,
+    /// Trackpads scroll by swiping, joysticks scroll by how far they're pushed.
+    swipe: bool,
+    state: Mutex<ScrollState>,
+}
+
+impl ScrollData {
+    /// Distance moved per notch in discrete mode.
+    const NOTCH_SIZE: f32 = 0.1;
+    /// Distance scrolled per second with the joystick fully deflected.
+    const JOYSTICK_RATE: f32 = 1.8;
+    /// How much of the new scroll amount is mixed into the output each sync in smooth mode.
+    const SMOOTHING: f32 = 0.5;
+
+    pub fn new(mode: ScrollMode, multiplier: Option<f32>, swipe: bool) -> Self {
+        Self {
+            mode,
+            multiplier: multiplier.unwrap_or(1.0),
+            swipe,
+            state: Default::default(),
+        }
+    }
+
+    /// Returns the amount to scroll for the given sync. Repeated reads within the same sync
+    /// return the same value.
+    fn state<G>(
+        &self,
+        extra_action: &ExtraActionData,
+        session: &xr::Session<G>,
+        subaction_path: xr::Path,
+        packet_num: u32,
+    ) -> xr::Result<Option<xr::ActionState<xr::Vector2f>>> {
+        let Some(action_to_read) = &extra_action.vector2_action else {
+            return Ok(None);
+        };
+        let state = action_to_read.state(session, subaction_path)?;
+        let mut scroll = self.state.lock().unwrap();
+        if !state.is_active {
+            *scroll = Default::default();
+            return Ok(None);
+        }
+
+        if scroll.packet_num != Some(packet_num) {
+            scroll.packet_num = Some(packet_num);
+            let now = Instant::now();
+            let elapsed = scroll
+                .last_update
+                .replace(now)
+                .map(|last| (now - last).as_secs_f32())
+                .unwrap_or_default();
+            let position = state.current_state;
+            let amount = if self.swipe {
+                let touching = match &extra_action.touch_action {
+                    Some(touch) => touch.state(session, subaction_path)?.current_state,
+                    // Without a touch input, the best guess is that the trackpad reports the
+                    // origin when it isn't being touched.
+                    None => position.x != 0.0 || position.y != 0.0,
+                };
+                let last =
+                    std::mem::replace(&mut scroll.last_position, touching.then_some(position));
+                match last {
+                    Some(last) if touching => xr::Vector2f {
+                        x: position.x - last.x,
+                        y: position.y - last.y,
+                    },
+                    _ => Default::default(),
+                }
+            } else {
+                xr::Vector2f {
+                    x: position.x * Self::JOYSTICK_RATE * elapsed,
+                    y: position.y * Self::JOYSTICK_RATE * elapsed,
+                }
+            };
+            let amount = xr::Vector2f {
+                x: amount.x * self.multiplier,
+                y: amount.y * self.multiplier,
+            };
+
+            let output = match self.mode {
+                ScrollMode::Smooth => xr::Vector2f {
+                    x: scroll.output.x + (amount.x - scroll.output.x) * Self::SMOOTHING,
+                    y: scroll.output.y + (amount.y - scroll.output.y) * Self::SMOOTHING,
+                },
+                ScrollMode::Discrete => {
+                    let notches = |remainder: &mut f32, amount: f32| {
+                        *remainder += amount;
+                        let notches = (*remainder / Self::NOTCH_SIZE).trunc();
+                        *remainder -= notches * Self::NOTCH_SIZE;
+                        notches
+                    };
+                    let ScrollState { remainder, .. } = &mut *scroll;
+                    xr::Vector2f {
+                        x: notches(&mut remainder.x, amount.x),
+                        y: notches(&mut remainder.y, amount.y),
+                    }
+                }
+            };
+            scroll.changed = output != scroll.output;
+            scroll.output = output;
+        }
+
+        Ok(Some(xr::ActionState {
+            current_state: scroll.output,
+            changed_since_last_sync: scroll.changed,
+            last_change_time: state.last_change_time,
+            is_active: true,
+        }))
+    }
+}
+
 pub struct ThresholdBindingData {
     pub click_threshold: f32,
     pub release_threshold: f32,
@@ -309,14 +737,80 @@ impl ThresholdBindingData {
     }
 }
 
+/// A set of buttons that must all be held at once to activate an action.
+pub(super) struct ChordData {
+    /// The actions for each button in the chord, along with the hand to read them from.
+    inputs: Vec<(xr::Action<bool>, xr::Path)>,
+    last_state: AtomicBool,
+}
+
+impl ChordData {
+    pub fn new(inputs: Vec<(xr::Action<bool>, xr::Path)>) -> Self {
+        Self {
+            inputs,
+            last_state: false.into(),
+        }
+    }
+
+    /// Returns whether every button in the chord is held, along with the latest change time
+    /// among them. Returns None if any of the buttons is not active.
+    fn held<G>(&self, session: &xr::Session<G>) -> xr::Result<Option<(bool, xr::Time)>> {
+        let mut held = true;
+        let mut last_change_time = xr::Time::from_nanos(0);
+        for (action, hand) in &self.inputs {
+            let state = action.state(session, *hand)?;
+            if !state.is_active {
+                return Ok(None);
+            }
+            held &= state.current_state;
+            last_change_time = last_change_time.max(state.last_change_time);
+        }
+
+        Ok(Some((held, last_change_time)))
+    }
+
+    fn state<G>(&self, session: &xr::Session<G>) -> xr::Result<Option<xr::ActionState<bool>>> {
+        let Some((current_state, last_change_time)) = self.held(session)? else {
+            self.last_state.store(false, Ordering::Relaxed);
+            return Ok(None);
+        };
+
+        let changed_since_last_sync = self
+            .last_state
+            .compare_exchange(
+                !current_state,
+                current_state,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            )
+            .is_ok();
+
+        Ok(Some(xr::ActionState {
+            current_state,
+            changed_since_last_sync,
+            last_change_time,
+            is_active: true,
+        }))
+    }
+}
+
 pub enum BindingData {
     // For all cases where the action can be read directly, such as matching type or bool-to-float conversion,
     //  the xr::Action is read from ActionData
     // This can include actions where behavior is customized via OXR extensions
     Dpad(DpadData, xr::Path),
     Toggle(ToggleData, xr::Path),
+    Held(HeldData, xr::Path),
+    DoublePress(DoublePressData, xr::Path),
+    Repeat(RepeatData, xr::Path),
     Grab(GrabBindingData, xr::Path),
     Threshold(ThresholdBindingData, xr::Path),
+    Scroll(ScrollData, xr::Path),
+    Chord(Arc<ChordData>, xr::Path),
+    /// Suppresses an action bound to one of the buttons of a chord while the chord is held.
+    ChordMask(Arc<ChordData>, xr::Path),
+    /// Applies finger curl resistance when the (haptic) action is triggered.
+    ForceFeedback(xr::Path),
 }
 
 impl BindingData {
@@ -325,6 +819,7 @@ impl BindingData {
         session: &SessionData,
         extra_data: &ExtraActionData,
         subaction_path: xr::Path,
+        packet_num: u32,
     ) -> xr::Result<Option<xr::ActionState<bool>>> {
         assert_ne!(subaction_path, xr::Path::NULL);
         match self {
@@ -334,12 +829,48 @@ impl BindingData {
             BindingData::Toggle(toggle, x) if x == &subaction_path => {
                 toggle.state(extra_data, &session.session, subaction_path)
             }
+            BindingData::Held(held, x) if x == &subaction_path => {
+                held.state(extra_data, &session.session, subaction_path, packet_num)
+            }
+            BindingData::DoublePress(double, x) if x == &subaction_path => {
+                double.state(extra_data, &session.session, subaction_path, packet_num)
+            }
+            BindingData::Repeat(repeat, x) if x == &subaction_path => {
+                repeat.state(extra_data, &session.session, subaction_path, packet_num)
+            }
             BindingData::Grab(grab, x) if x == &subaction_path => {
                 grab.grabbed(extra_data, &session.session, subaction_path)
             }
             BindingData::Threshold(threshold, x) if x == &subaction_path => {
                 threshold.state(extra_data, &session.session, subaction_path)
             }
+            BindingData::Chord(chord, x) if x == &subaction_path => chord.state(&session.session),
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns true if this binding masks its action on the given hand.
+    pub fn masked(&self, session: &SessionData, subaction_path: xr::Path) -> xr::Result<bool> {
+        match self {
+            BindingData::ChordMask(chord, x) if x == &subaction_path => {
+                Ok(chord.held(&session.session)?.is_some_and(|(held, _)| held))
+            }
+            _ => Ok(false),
+        }
+    }
+
+    pub fn scroll_state(
+        &self,
+        session: &SessionData,
+        extra_data: &ExtraActionData,
+        subaction_path: xr::Path,
+        packet_num: u32,
+    ) -> xr::Result<Option<xr::ActionState<xr::Vector2f>>> {
+        assert_ne!(subaction_path, xr::Path::NULL);
+        match self {
+            BindingData::Scroll(scroll, x) if x == &subaction_path => {
+                scroll.state(extra_data, &session.session, subaction_path, packet_num)
+            }
             _ => Ok(None),
         }
     }
@@ -354,6 +885,16 @@ mod tests {
     use crate::input::InteractionProfile;
     use fakexr::UserPath::*;
     use openvr as vr;
+    use std::cell::Cell;
+
+    thread_local! {
+        /// How far [`now`] is ahead of the real time.
+        pub(super) static CLOCK_OFFSET: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+    }
+
+    fn advance_clock(by: Duration) {
+        CLOCK_OFFSET.with(|offset| offset.set(offset.get() + by));
+    }
 
     macro_rules! get_toggle_action {
         ($fixture:expr, $handle:expr, $toggle_data:ident) => {
@@ -365,6 +906,36 @@ mod tests {
         };
     }
 
+    macro_rules! get_held_action {
+        ($fixture:expr, $handle:expr, $held_data:ident) => {
+            let data = $fixture.input.openxr.session_data.get();
+            let actions = data.input_data.get_loaded_actions().unwrap();
+            let ExtraActionData { held_action, .. } = actions.try_get_extra($handle).unwrap();
+
+            let $held_data = held_action.as_ref().unwrap();
+        };
+    }
+
+    macro_rules! get_double_action {
+        ($fixture:expr, $handle:expr, $double_data:ident) => {
+            let data = $fixture.input.openxr.session_data.get();
+            let actions = data.input_data.get_loaded_actions().unwrap();
+            let ExtraActionData { double_action, .. } = actions.try_get_extra($handle).unwrap();
+
+            let $double_data = double_action.as_ref().unwrap();
+        };
+    }
+
+    macro_rules! get_repeat_action {
+        ($fixture:expr, $handle:expr, $repeat_data:ident) => {
+            let data = $fixture.input.openxr.session_data.get();
+            let actions = data.input_data.get_loaded_actions().unwrap();
+            let ExtraActionData { repeat_action, .. } = actions.try_get_extra($handle).unwrap();
+
+            let $repeat_data = repeat_action.as_ref().unwrap();
+        };
+    }
+
     macro_rules! get_dpad_action {
         ($fixture:expr, $handle:expr, $dpad_data:ident, $profile:ident) => {
             let data = $fixture.input.openxr.session_data.get();
@@ -392,6 +963,16 @@ mod tests {
         };
     }
 
+    macro_rules! get_analog_action {
+        ($fixture:expr, $handle:expr, $analog_data:ident) => {
+            let data = $fixture.input.openxr.session_data.get();
+            let actions = data.input_data.get_loaded_actions().unwrap();
+            let ExtraActionData { analog_action, .. } = actions.try_get_extra($handle).unwrap();
+
+            let $analog_data = analog_action.as_ref().unwrap();
+        };
+    }
+
     macro_rules! get_grab_action {
         ($fixture:expr, $handle:expr, $grab_data:ident) => {
             let data = $fixture.input.openxr.session_data.get();
@@ -462,6 +1043,48 @@ mod tests {
         assert!(state.bChanged);
     }
 
+    #[test]
+    fn dpad_input_overlap_and_deadzone() {
+        let f = Fixture::new();
+
+        let set1 = f.get_action_set_handle(c"/actions/set1");
+        let boolact = f.get_action_handle(c"/actions/set1/in/boolact");
+
+        f.load_actions(c"actions_dpad.json");
+        f.input.openxr.restart_session();
+
+        get_dpad_action!(f, boolact, dpad_data, ViveWands);
+
+        f.set_interaction_profile(&ViveWands, LeftHand);
+        fakexr::set_action_state(
+            dpad_data.click_or_touch.as_ref().unwrap().as_raw(),
+            fakexr::ActionState::Float(1.0),
+            LeftHand,
+        );
+
+        let check = |x, y, expected: bool| {
+            fakexr::set_action_state(
+                dpad_data.xy.as_raw(),
+                fakexr::ActionState::Vector2(x, y),
+                LeftHand,
+            );
+            f.sync(vr::VRActiveActionSet_t {
+                ulActionSet: set1,
+                ..Default::default()
+            });
+            let state = f.get_bool_state(boolact).unwrap();
+            assert_eq!(state.bState, expected, "({x}, {y})");
+        };
+
+        // Past the diagonal, but within the default overlap
+        check(0.5, 0.45, true);
+        // Within the center deadzone
+        check(0.0, 0.3, false);
+        check(0.0, 0.8, true);
+        // Opposite direction
+        check(0.0, -0.8, false);
+    }
+
     #[test]
     fn dpad_input_different_sets_have_different_actions() {
         let f = Fixture::new();
@@ -819,6 +1442,46 @@ mod tests {
         assert!(!state.bChanged);
     }
 
+    #[test]
+    fn toggle_button_multiple_reads_per_sync() {
+        let f = Fixture::new();
+        let set1 = f.get_action_set_handle(c"/actions/set1");
+        let boolact = f.get_action_handle(c"/actions/set1/in/boolact");
+        f.load_actions(c"actions_toggle.json");
+
+        get_toggle_action!(f, boolact, toggle_data);
+
+        f.set_interaction_profile(&Knuckles, LeftHand);
+        fakexr::set_action_state(toggle_data.as_raw(), true.into(), LeftHand);
+        f.sync(vr::VRActiveActionSet_t {
+            ulActionSet: set1,
+            ..Default::default()
+        });
+
+        for _ in 0..3 {
+            let state = f.get_bool_state(boolact).unwrap();
+            assert!(state.bActive);
+            assert!(state.bState);
+        }
+
+        fakexr::set_action_state(toggle_data.as_raw(), false.into(), LeftHand);
+        f.sync(vr::VRActiveActionSet_t {
+            ulActionSet: set1,
+            ..Default::default()
+        });
+        fakexr::set_action_state(toggle_data.as_raw(), true.into(), LeftHand);
+        f.sync(vr::VRActiveActionSet_t {
+            ulActionSet: set1,
+            ..Default::default()
+        });
+
+        for _ in 0..3 {
+            let state = f.get_bool_state(boolact).unwrap();
+            assert!(state.bActive);
+            assert!(!state.bState);
+        }
+    }
+
     #[test]
     fn toggle_button_per_hand() {
         let f = Fixture::new();
@@ -884,4 +1547,448 @@ mod tests {
         assert!(s_right.bState);
         assert!(s_right.bChanged);
     }
+
+    #[test]
+    fn held_button() {
+        let f = Fixture::new();
+        let set1 = f.get_action_set_handle(c"/actions/set1");
+        let held = f.get_action_handle(c"/actions/set1/in/boolact");
+        let click = f.get_action_handle(c"/actions/set1/in/boolact2");
+        f.load_actions(c"actions_held.json");
+
+        get_held_action!(f, held, held_data);
+        let held_raw = held_data.as_raw();
+        get_held_action!(f, click, click_data);
+        let click_raw = click_data.as_raw();
+
+        let sync = || {
+            f.sync(vr::VRActiveActionSet_t {
+                ulActionSet: set1,
+                ..Default::default()
+            })
+        };
+        let set_pressed = |pressed: bool| {
+            fakexr::set_action_state(held_raw, pressed.into(), LeftHand);
+            fakexr::set_action_state(click_raw, pressed.into(), LeftHand);
+        };
+
+        f.set_interaction_profile(&Knuckles, LeftHand);
+        set_pressed(true);
+        sync();
+
+        // Neither output knows yet what kind of press this is.
+        let s = f.get_bool_state(held).unwrap();
+        assert!(s.bActive);
+        assert!(!s.bState);
+        let s = f.get_bool_state(click).unwrap();
+        assert!(s.bActive);
+        assert!(!s.bState);
+        assert!(!s.bChanged);
+
+        // Held past the delay (100ms in the bindings): held fires, short press is suppressed
+        advance_clock(Duration::from_millis(150));
+        sync();
+
+        let s = f.get_bool_state(held).unwrap();
+        assert!(s.bState);
+        assert!(s.bChanged);
+        let s = f.get_bool_state(click).unwrap();
+        assert!(!s.bState);
+        assert!(!s.bChanged);
+
+        set_pressed(false);
+        sync();
+
+        let s = f.get_bool_state(held).unwrap();
+        assert!(!s.bState);
+        assert!(s.bChanged);
+        let s = f.get_bool_state(click).unwrap();
+        assert!(!s.bState);
+        assert!(!s.bChanged);
+
+        // A quick press only triggers the short press output, once it's released.
+        set_pressed(true);
+        sync();
+        assert!(!f.get_bool_state(held).unwrap().bState);
+        assert!(!f.get_bool_state(click).unwrap().bState);
+        set_pressed(false);
+        sync();
+        assert!(!f.get_bool_state(held).unwrap().bState);
+        // Every read within the sync sees the pulse.
+        for _ in 0..2 {
+            let s = f.get_bool_state(click).unwrap();
+            assert!(s.bState);
+            assert!(s.bChanged);
+        }
+        sync();
+        let s = f.get_bool_state(click).unwrap();
+        assert!(!s.bState);
+        assert!(s.bChanged);
+
+        // Presses are timed from the sync they start on, even if nothing reads them until
+        // they're released.
+        set_pressed(true);
+        sync();
+        advance_clock(Duration::from_millis(150));
+        set_pressed(false);
+        sync();
+        assert!(!f.get_bool_state(click).unwrap().bState);
+
+        set_pressed(true);
+        sync();
+        set_pressed(false);
+        sync();
+        assert!(f.get_bool_state(click).unwrap().bState);
+    }
+
+    #[test]
+    fn trigger_pull_thresholds() {
+        let f = Fixture::new();
+        let set1 = f.get_action_set_handle(c"/actions/set1");
+        let boolact = f.get_action_handle(c"/actions/set1/in/boolact");
+        f.load_actions(c"actions_trigger.json");
+
+        get_analog_action!(f, boolact, analog_data);
+        f.set_interaction_profile(&Knuckles, LeftHand);
+
+        let check = |value: f32, expected: bool, changed: bool, line| {
+            fakexr::set_action_state(
+                analog_data.as_raw(),
+                fakexr::ActionState::Float(value),
+                LeftHand,
+            );
+            f.sync(vr::VRActiveActionSet_t {
+                ulActionSet: set1,
+                ..Default::default()
+            });
+            let state = f.get_bool_state(boolact).unwrap();
+            assert!(state.bActive, "line {line}");
+            assert_eq!(state.bState, expected, "line {line}");
+            assert_eq!(state.bChanged, changed, "line {line}");
+        };
+
+        check(0.5, false, false, line!());
+        check(0.69, false, false, line!());
+        check(0.7, true, true, line!());
+        check(0.65, true, false, line!());
+        check(0.6, true, false, line!());
+        check(0.59, false, true, line!());
+    }
+
+    #[test]
+    fn grip_force_thresholds() {
+        let f = Fixture::new();
+        let set1 = f.get_action_set_handle(c"/actions/set1");
+        let boolact = f.get_action_handle(c"/actions/set1/in/boolact");
+        f.load_actions(c"actions_force.json");
+
+        get_analog_action!(f, boolact, analog_data);
+        f.set_interaction_profile(&Knuckles, LeftHand);
+
+        let check = |value: f32, expected: bool, changed: bool, line| {
+            fakexr::set_action_state(
+                analog_data.as_raw(),
+                fakexr::ActionState::Float(value),
+                LeftHand,
+            );
+            f.sync(vr::VRActiveActionSet_t {
+                ulActionSet: set1,
+                ..Default::default()
+            });
+            let state = f.get_bool_state(boolact).unwrap();
+            assert!(state.bActive, "line {line}");
+            assert_eq!(state.bState, expected, "line {line}");
+            assert_eq!(state.bChanged, changed, "line {line}");
+        };
+
+        check(0.5, false, false, line!());
+        check(0.7, true, true, line!());
+        check(0.6, true, false, line!());
+        check(0.59, false, true, line!());
+    }
+
+    #[test]
+    fn scroll_joystick_discrete() {
+        let f = Fixture::new();
+        let set1 = f.get_action_set_handle(c"/actions/set1");
+        let vec2act = f.get_action_handle(c"/actions/set1/in/vec2act");
+        f.load_actions(c"actions_scroll.json");
+
+        let scroll_raw = {
+            let data = f.input.openxr.session_data.get();
+            let actions = data.input_data.get_loaded_actions().unwrap();
+            let ExtraActionData { vector2_action, .. } = actions.try_get_extra(vec2act).unwrap();
+            vector2_action.as_ref().unwrap().as_raw()
+        };
+
+        f.set_interaction_profile(&Knuckles, LeftHand);
+        fakexr::set_action_state(
+            scroll_raw,
+            fakexr::ActionState::Vector2(0.0, 0.75),
+            LeftHand,
+        );
+
+        let sync_and_check = |expected_y: f32, line| {
+            f.sync(vr::VRActiveActionSet_t {
+                ulActionSet: set1,
+                ..Default::default()
+            });
+            let mut s = vr::InputAnalogActionData_t::default();
+            let err =
+                f.input
+                    .GetAnalogActionData(vec2act, &mut s, std::mem::size_of_val(&s) as u32, 0);
+            assert_eq!(err, vr::EVRInputError::None, "line {line}");
+            assert!(s.bActive, "line {line}");
+            assert_eq!(s.x, 0.0, "line {line}");
+            assert_eq!(s.y, expected_y, "line {line}");
+        };
+
+        // The scroll speed depends on the time between syncs, not how often they happen
+        sync_and_check(0.0, line!());
+        sync_and_check(0.0, line!());
+        sync_and_check(0.0, line!());
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        sync_and_check(1.0, line!());
+
+        fakexr::set_action_state(scroll_raw, fakexr::ActionState::Vector2(0.0, 0.0), LeftHand);
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        for _ in 0..10 {
+            sync_and_check(0.0, line!());
+        }
+    }
+
+    #[test]
+    fn scroll_trackpad_swipe() {
+        let f = Fixture::new();
+        let set1 = f.get_action_set_handle(c"/actions/set1");
+        let vec2act = f.get_action_handle(c"/actions/set1/in/vec2act");
+        f.load_actions(c"actions_scroll_trackpad.json");
+
+        let (scroll_raw, touch_raw) = {
+            let data = f.input.openxr.session_data.get();
+            let actions = data.input_data.get_loaded_actions().unwrap();
+            let ExtraActionData {
+                vector2_action,
+                touch_action,
+                ..
+            } = actions.try_get_extra(vec2act).unwrap();
+            (
+                vector2_action.as_ref().unwrap().as_raw(),
+                touch_action.as_ref().unwrap().as_raw(),
+            )
+        };
+
+        f.set_interaction_profile(&Knuckles, LeftHand);
+        let check = |touch: bool, y: f32, expected_y: f32, line| {
+            fakexr::set_action_state(touch_raw, touch.into(), LeftHand);
+            fakexr::set_action_state(scroll_raw, fakexr::ActionState::Vector2(0.0, y), LeftHand);
+            f.sync(vr::VRActiveActionSet_t {
+                ulActionSet: set1,
+                ..Default::default()
+            });
+            let mut s = vr::InputAnalogActionData_t::default();
+            let err =
+                f.input
+                    .GetAnalogActionData(vec2act, &mut s, std::mem::size_of_val(&s) as u32, 0);
+            assert_eq!(err, vr::EVRInputError::None, "line {line}");
+            assert!(s.bActive, "line {line}");
+            assert_eq!(s.y, expected_y, "line {line}");
+        };
+
+        check(false, 0.0, 0.0, line!());
+        // A swipe starting at the center of the trackpad still scrolls
+        check(true, 0.0, 0.0, line!());
+        check(true, 0.25, 2.0, line!());
+        // Lifting the finger doesn't scroll back to the origin
+        check(false, 0.0, 0.0, line!());
+    }
+
+    #[test]
+    fn chord() {
+        let f = Fixture::new();
+        let set1 = f.get_action_set_handle(c"/actions/set1");
+        let chord = f.get_action_handle(c"/actions/set1/in/boolact");
+        let button = f.get_action_handle(c"/actions/set1/in/boolact2");
+        f.load_actions(c"actions_chord.json");
+
+        let button_raw = f.get_action::<bool>(button);
+        let chord_raws: Vec<_> = {
+            let data = f.input.openxr.session_data.get();
+            let actions = data.input_data.get_loaded_actions().unwrap();
+            let path = f
+                .input
+                .openxr
+                .instance
+                .string_to_path(Knuckles.profile_path())
+                .unwrap();
+            let bindings = actions.try_get_bindings(chord, path).unwrap();
+            let [BindingData::Chord(chord_data, _)] = bindings.as_slice() else {
+                panic!("Expected a single chord binding");
+            };
+            chord_data
+                .inputs
+                .iter()
+                .map(|(action, _)| action.as_raw())
+                .collect()
+        };
+        assert_eq!(chord_raws.len(), 2);
+
+        f.set_interaction_profile(&Knuckles, LeftHand);
+        let check = |a: bool, b: bool, chord_state: bool, button_state: bool, line| {
+            fakexr::set_action_state(button_raw, a.into(), LeftHand);
+            fakexr::set_action_state(chord_raws[0], a.into(), LeftHand);
+            fakexr::set_action_state(chord_raws[1], b.into(), LeftHand);
+            f.sync(vr::VRActiveActionSet_t {
+                ulActionSet: set1,
+                ..Default::default()
+            });
+
+            let s = f.get_bool_state(chord).unwrap();
+            assert!(s.bActive, "chord active failed (line {line})");
+            assert_eq!(s.bState, chord_state, "chord state failed (line {line})");
+            let s = f.get_bool_state(button).unwrap();
+            assert_eq!(s.bState, button_state, "button state failed (line {line})");
+        };
+
+        check(false, false, false, false, line!());
+        check(true, false, false, true, line!());
+        // The button is masked while the chord is held
+        check(true, true, true, false, line!());
+        check(false, true, false, false, line!());
+    }
+
+    #[test]
+    fn chord_mask_changes_button() {
+        let f = Fixture::new();
+        let set1 = f.get_action_set_handle(c"/actions/set1");
+        let chord = f.get_action_handle(c"/actions/set1/in/boolact");
+        let button = f.get_action_handle(c"/actions/set1/in/boolact2");
+        f.load_actions(c"actions_chord.json");
+
+        let button_raw = f.get_action::<bool>(button);
+        let chord_raws: Vec<_> = {
+            let data = f.input.openxr.session_data.get();
+            let actions = data.input_data.get_loaded_actions().unwrap();
+            let path = f
+                .input
+                .openxr
+                .instance
+                .string_to_path(Knuckles.profile_path())
+                .unwrap();
+            let bindings = actions.try_get_bindings(chord, path).unwrap();
+            let [BindingData::Chord(chord_data, _)] = bindings.as_slice() else {
+                panic!("Expected a single chord binding");
+            };
+            chord_data
+                .inputs
+                .iter()
+                .map(|(action, _)| action.as_raw())
+                .collect()
+        };
+
+        f.set_interaction_profile(&Knuckles, LeftHand);
+        let check = |a: bool, b: bool, state: bool, changed: bool, line| {
+            fakexr::set_action_state(button_raw, a.into(), LeftHand);
+            fakexr::set_action_state(chord_raws[0], a.into(), LeftHand);
+            fakexr::set_action_state(chord_raws[1], b.into(), LeftHand);
+            f.sync(vr::VRActiveActionSet_t {
+                ulActionSet: set1,
+                ..Default::default()
+            });
+
+            let s = f.get_bool_state(button).unwrap();
+            assert_eq!(s.bState, state, "button state failed (line {line})");
+            assert_eq!(s.bChanged, changed, "button changed failed (line {line})");
+        };
+
+        check(true, false, true, true, line!());
+        // Completing the chord releases the button that was already pressed
+        check(true, true, false, true, line!());
+        check(true, true, false, false, line!());
+        // Releasing the chord while the button is still held presses it again
+        check(true, false, true, true, line!());
+        check(false, false, false, true, line!());
+    }
+
+    #[test]
+    fn double_press() {
+        let f = Fixture::new();
+        let set1 = f.get_action_set_handle(c"/actions/set1");
+        let double = f.get_action_handle(c"/actions/set1/in/boolact");
+        let click = f.get_action_handle(c"/actions/set1/in/boolact2");
+        f.load_actions(c"actions_double.json");
+
+        get_double_action!(f, double, double_data);
+        let double_raw = double_data.as_raw();
+        get_double_action!(f, click, click_data);
+        let click_raw = click_data.as_raw();
+
+        f.set_interaction_profile(&Knuckles, LeftHand);
+        let check = |pressed: bool, double_state: bool, click_state: bool, line| {
+            fakexr::set_action_state(double_raw, pressed.into(), LeftHand);
+            fakexr::set_action_state(click_raw, pressed.into(), LeftHand);
+            f.sync(vr::VRActiveActionSet_t {
+                ulActionSet: set1,
+                ..Default::default()
+            });
+
+            let s = f.get_bool_state(double).unwrap();
+            assert!(s.bActive, "double active failed (line {line})");
+            assert_eq!(s.bState, double_state, "double state failed (line {line})");
+            let s = f.get_bool_state(click).unwrap();
+            assert!(s.bActive, "click active failed (line {line})");
+            assert_eq!(s.bState, click_state, "click state failed (line {line})");
+        };
+
+        // Two presses within the window (100ms in the bindings) only activate the double output
+        check(true, false, false, line!());
+        check(false, false, false, line!());
+        check(true, true, false, line!());
+        check(false, false, false, line!());
+
+        // A lone press is reported as a click once the window has passed
+        check(true, false, false, line!());
+        check(false, false, false, line!());
+        std::thread::sleep(std::time::Duration::from_millis(150));
+        check(false, false, true, line!());
+        check(false, false, false, line!());
+    }
+
+    #[test]
+    fn auto_repeat() {
+        let f = Fixture::new();
+        let set1 = f.get_action_set_handle(c"/actions/set1");
+        let boolact = f.get_action_handle(c"/actions/set1/in/boolact");
+        f.load_actions(c"actions_repeat.json");
+
+        get_repeat_action!(f, boolact, repeat_data);
+        let repeat_raw = repeat_data.as_raw();
+
+        f.set_interaction_profile(&Knuckles, LeftHand);
+        let check = |pressed: bool, state: bool, changed: bool, line| {
+            fakexr::set_action_state(repeat_raw, pressed.into(), LeftHand);
+            f.sync(vr::VRActiveActionSet_t {
+                ulActionSet: set1,
+                ..Default::default()
+            });
+
+            let s = f.get_bool_state(boolact).unwrap();
+            assert!(s.bActive, "active failed (line {line})");
+            assert_eq!(s.bState, state, "state failed (line {line})");
+            assert_eq!(s.bChanged, changed, "changed failed (line {line})");
+            // Reading again in the same sync doesn't advance the pulses.
+            let s = f.get_bool_state(boolact).unwrap();
+            assert_eq!(s.bState, state, "second read failed (line {line})");
+        };
+
+        // Two presses per second in the bindings, so each press and release lasts 250ms.
+        check(true, true, true, line!());
+        check(true, true, false, line!());
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        check(true, false, true, line!());
+        std::thread::sleep(std::time::Duration::from_millis(250));
+        check(true, true, true, line!());
+        check(false, false, true, line!());
+        check(false, false, false, line!());
+    }
 }
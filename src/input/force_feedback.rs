@@ -0,0 +1,90 @@
+use crate::openxr_data::Hand;
+use log::{info, warn};
+use openxr as xr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Finger curl resistance through XR_MNDX_force_feedback_curl, for haptic actions bound to
+/// `/user/hand/<hand>/output/force_feedback`.
+pub struct ForceFeedback {
+    fp: xr::raw::ForceFeedbackCurlMNDX,
+    trackers: [xr::HandTracker; 2],
+    /// When to release the resistance on each hand, if it's currently applied.
+    release_at: [Mutex<Option<Instant>>; 2],
+}
+
+impl ForceFeedback {
+    pub fn new(
+        session: &xr::Session<xr::AnyGraphics>,
+        hand_tracking_supported: bool,
+    ) -> Option<Self> {
+        let fp = session.instance().exts().mndx_force_feedback_curl?;
+        if !hand_tracking_supported {
+            warn!("Force feedback is supported, but hand tracking isn't - it won't be available.");
+            return None;
+        }
+
+        let trackers = [xr::Hand::LEFT, xr::Hand::RIGHT].map(|hand| {
+            session
+                .create_hand_tracker(hand)
+                .inspect_err(|e| warn!("Failed to create hand tracker for force feedback: {e}"))
+                .ok()
+        });
+        let [Some(left), Some(right)] = trackers else {
+            return None;
+        };
+        info!("Force feedback available");
+
+        Some(Self {
+            fp,
+            trackers: [left, right],
+            release_at: Default::default(),
+        })
+    }
+
+    /// Applies resistance to every finger of the hand, from 0 (none) to 1 (fully locked).
+    pub fn apply(&self, hand: Hand, value: f32, duration: Duration) {
+        let idx = hand as usize - 1;
+        let value = if duration.is_zero() {
+            0.0
+        } else {
+            value.clamp(0.0, 1.0)
+        };
+        *self.release_at[idx].lock().unwrap() = (value > 0.0).then(|| Instant::now() + duration);
+        self.apply_curl(idx, value);
+    }
+
+    /// Releases any resistance whose duration has run out.
+    pub fn update(&self) {
+        for idx in 0..2 {
+            let mut release_at = self.release_at[idx].lock().unwrap();
+            if release_at.is_some_and(|at| at <= Instant::now()) {
+                *release_at = None;
+                self.apply_curl(idx, 0.0);
+            }
+        }
+    }
+
+    fn apply_curl(&self, idx: usize, value: f32) {
+        let mut locations = [
+            xr::sys::ForceFeedbackCurlLocationMNDX::THUMB_CURL,
+            xr::sys::ForceFeedbackCurlLocationMNDX::INDEX_CURL,
+            xr::sys::ForceFeedbackCurlLocationMNDX::MIDDLE_CURL,
+            xr::sys::ForceFeedbackCurlLocationMNDX::RING_CURL,
+            xr::sys::ForceFeedbackCurlLocationMNDX::LITTLE_CURL,
+        ]
+        .map(|location| xr::sys::ForceFeedbackCurlApplyLocationMNDX { location, value });
+        let info = xr::sys::ForceFeedbackCurlApplyLocationsMNDX {
+            ty: xr::sys::ForceFeedbackCurlApplyLocationsMNDX::TYPE,
+            next: std::ptr::null(),
+            location_count: locations.len() as u32,
+            locations: locations.as_mut_ptr(),
+        };
+
+        let result =
+            unsafe { (self.fp.apply_force_feedback_curl)(self.trackers[idx].as_raw(), &info) };
+        if result != xr::sys::Result::SUCCESS {
+            crate::warn_once!("Failed to apply force feedback: {result}");
+        }
+    }
+}
@@ -0,0 +1,300 @@
+use super::{ActionKey, Input};
+use crate::config::Config;
+use crate::openxr_data::{self, Hand, SessionData};
+use glam::Vec3;
+use log::{info, warn};
+use openvr as vr;
+use openxr as xr;
+use serde::Deserialize;
+use slotmap::{Key, KeyData};
+
+/// Most a finger can bend over all of its joints and still count as extended, in radians.
+const FINGER_EXTENDED: f32 = 0.7;
+/// Least a finger has to bend over all of its joints to count as curled, in radians.
+const FINGER_CURLED: f32 = 2.0;
+/// The thumb has one joint less and doesn't bend as far.
+const THUMB_EXTENDED: f32 = 0.5;
+/// How far up the thumb has to point for a thumbs up, as the cosine of its angle to vertical.
+const THUMB_UP: f32 = 0.7;
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Gesture {
+    /// Thumb extended and pointing up, other fingers curled.
+    ThumbsUp,
+    /// Thumb and index finger extended, other fingers curled.
+    FingerGun,
+    /// All fingers extended.
+    OpenPalm,
+}
+
+/// Presses a boolean action while a gesture is held.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct GestureBinding {
+    /// i.e. /actions/main/in/menu
+    pub action: String,
+    pub gesture: Gesture,
+    /// Either hand if missing.
+    #[serde(default)]
+    pub hand: Option<Hand>,
+}
+
+/// The gesture each hand is making, as of the last two action syncs.
+#[derive(Default)]
+pub(super) struct GestureState {
+    current: [Option<Gesture>; 2],
+    previous: [Option<Gesture>; 2],
+}
+
+impl GestureState {
+    /// Whether either hand is making the gesture.
+    pub fn is_held(&self, gesture: Gesture) -> bool {
+        self.current.contains(&Some(gesture))
+    }
+}
+
+pub(super) struct GestureTrackers {
+    trackers: [xr::HandTracker; 2],
+}
+
+impl GestureTrackers {
+    pub fn new(
+        session: &xr::Session<xr::AnyGraphics>,
+        hand_tracking_supported: bool,
+    ) -> Option<Self> {
+        let config = Config::get();
+        if config.gestures().next().is_none() && config.dashboard.gesture.is_none() {
+            return None;
+        }
+        if !hand_tracking_supported {
+            warn!("Gestures are configured, but hand tracking isn't supported - they won't be available.");
+            return None;
+        }
+
+        let trackers = [xr::Hand::LEFT, xr::Hand::RIGHT].map(|hand| {
+            session
+                .create_hand_tracker(hand)
+                .inspect_err(|e| warn!("Failed to create hand tracker for gestures: {e}"))
+                .ok()
+        });
+        let [Some(left), Some(right)] = trackers else {
+            return None;
+        };
+        info!("Gesture bindings available");
+
+        Some(Self {
+            trackers: [left, right],
+        })
+    }
+}
+
+impl<C: openxr_data::Compositor> Input<C> {
+    /// Recognizes the gesture each hand is making. Called on every action sync, so that gesture
+    /// changes line up with the rest of the action state.
+    pub(super) fn update_gestures(&self, data: &SessionData) {
+        let Some(trackers) = data
+            .input_data
+            .get_gesture_trackers(&data.session, self.openxr.hand_tracking_supported)
+        else {
+            return;
+        };
+
+        let time = self.openxr.display_time.get();
+        let space = data.tracking_space();
+        let current = trackers.trackers.each_ref().map(|tracker| {
+            space
+                .locate_hand_joints(tracker, time)
+                .inspect_err(|e| warn!("Failed to locate hand joints for gestures: {e}"))
+                .ok()
+                .flatten()
+                .and_then(|joints| detect(&joints))
+        });
+
+        let mut state = self.gestures.lock().unwrap();
+        state.previous = std::mem::replace(&mut state.current, current);
+    }
+
+    /// The state of a boolean action according to the gestures bound to it, if there are any.
+    pub(super) fn state_from_gestures(
+        &self,
+        action: vr::VRActionHandle_t,
+        restrict_to_device: vr::VRInputValueHandle_t,
+    ) -> Option<(xr::ActionState<bool>, vr::VRInputValueHandle_t)> {
+        let mut bindings = Config::get().gestures().peekable();
+        bindings.peek()?;
+
+        let action_map = self.action_map.read().unwrap();
+        let path = &action_map
+            .get(ActionKey::from(KeyData::from_ffi(action)))?
+            .path;
+        let state = self.gestures.lock().unwrap();
+        let hands = [
+            (Hand::Left, self.left_hand_key.data().as_ffi()),
+            (Hand::Right, self.right_hand_key.data().as_ffi()),
+        ];
+
+        let mut result: Option<(xr::ActionState<bool>, vr::VRInputValueHandle_t)> = None;
+        for binding in bindings.filter(|binding| binding.action.eq_ignore_ascii_case(path)) {
+            for (hand, handle) in hands {
+                if binding.hand.is_some_and(|h| h != hand)
+                    || (restrict_to_device != vr::k_ulInvalidInputValueHandle
+                        && restrict_to_device != handle)
+                {
+                    continue;
+                }
+                let idx = hand as usize - 1;
+                let held = state.current[idx] == Some(binding.gesture);
+                let changed = held != (state.previous[idx] == Some(binding.gesture));
+                // A held gesture wins, then one that was just released.
+                let better = result.as_ref().is_none_or(|(best, _)| {
+                    (held && !best.current_state)
+                        || (!best.current_state && changed && !best.changed_since_last_sync)
+                });
+                if better {
+                    result = Some((
+                        xr::ActionState {
+                            current_state: held,
+                            changed_since_last_sync: changed,
+                            last_change_time: xr::Time::from_nanos(0),
+                            is_active: true,
+                        },
+                        handle,
+                    ));
+                }
+            }
+        }
+        result
+    }
+}
+
+const THUMB: [xr::HandJoint; 4] = [
+    xr::HandJoint::THUMB_METACARPAL,
+    xr::HandJoint::THUMB_PROXIMAL,
+    xr::HandJoint::THUMB_DISTAL,
+    xr::HandJoint::THUMB_TIP,
+];
+
+macro_rules! finger {
+    ($finger:ident) => {
+        paste::paste! {
+            [
+                xr::HandJoint::[<$finger _METACARPAL>],
+                xr::HandJoint::[<$finger _PROXIMAL>],
+                xr::HandJoint::[<$finger _INTERMEDIATE>],
+                xr::HandJoint::[<$finger _DISTAL>],
+                xr::HandJoint::[<$finger _TIP>],
+            ]
+        }
+    };
+}
+
+const FINGERS: [[xr::HandJoint; 5]; 4] = [
+    finger!(INDEX),
+    finger!(MIDDLE),
+    finger!(RING),
+    finger!(LITTLE),
+];
+
+fn joint_position(joints: &[xr::HandJointLocation], joint: xr::HandJoint) -> Vec3 {
+    let p = joints[joint].pose.position;
+    Vec3::new(p.x, p.y, p.z)
+}
+
+/// Sum of the angles between consecutive segments of a chain of joints, in radians.
+fn bend(joints: &[xr::HandJointLocation], chain: &[xr::HandJoint]) -> f32 {
+    let positions: Vec<Vec3> = chain
+        .iter()
+        .map(|joint| joint_position(joints, *joint))
+        .collect();
+    positions
+        .windows(3)
+        .map(|w| (w[1] - w[0]).angle_between(w[2] - w[1]))
+        .sum()
+}
+
+/// Recognizes the gesture a hand is making from its joints, located in a space with +Y up.
+fn detect(joints: &[xr::HandJointLocation]) -> Option<Gesture> {
+    let valid = xr::SpaceLocationFlags::POSITION_VALID;
+    if !joints
+        .iter()
+        .all(|joint| joint.location_flags.contains(valid))
+    {
+        return None;
+    }
+
+    let thumb_extended = bend(joints, &THUMB) < THUMB_EXTENDED;
+    let [index, rest @ ..] = FINGERS.map(|finger| bend(joints, &finger));
+    let rest_curled = rest.iter().all(|bend| *bend > FINGER_CURLED);
+
+    if thumb_extended && index < FINGER_EXTENDED && rest.iter().all(|b| *b < FINGER_EXTENDED) {
+        Some(Gesture::OpenPalm)
+    } else if thumb_extended && index < FINGER_EXTENDED && rest_curled {
+        Some(Gesture::FingerGun)
+    } else if thumb_extended && index > FINGER_CURLED && rest_curled {
+        let thumb = joint_position(joints, xr::HandJoint::THUMB_TIP)
+            - joint_position(joints, xr::HandJoint::THUMB_PROXIMAL);
+        (thumb.normalize_or_zero().y > THUMB_UP).then_some(Gesture::ThumbsUp)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Quat;
+
+    const SEGMENT: f32 = 0.03;
+
+    /// A hand with its fingers pointing forward (-Z) and the thumb pointing `thumb_direction`.
+    /// Curled fingers bend downwards by 80 degrees at each joint.
+    fn hand(thumb_direction: Vec3, curled: [bool; 4]) -> xr::HandJointLocations {
+        let mut joints = [xr::HandJointLocation {
+            location_flags: xr::SpaceLocationFlags::POSITION_VALID,
+            pose: xr::Posef::IDENTITY,
+            radius: 0.01,
+        }; xr::HAND_JOINT_COUNT];
+        let mut place = |chain: &[xr::HandJoint], start: Vec3, direction: Vec3, bend: f32| {
+            let mut position = start;
+            let mut direction = direction;
+            for (idx, joint) in chain.iter().enumerate() {
+                let p = &mut joints[*joint].pose.position;
+                (p.x, p.y, p.z) = (position.x, position.y, position.z);
+                if idx > 0 {
+                    direction = Quat::from_rotation_x(-bend) * direction;
+                }
+                position += direction * SEGMENT;
+            }
+        };
+
+        place(&THUMB, Vec3::new(0.03, 0.0, 0.0), thumb_direction, 0.0);
+        for (idx, (finger, curled)) in FINGERS.iter().zip(curled).enumerate() {
+            let start = Vec3::new(-0.02 * idx as f32, 0.0, 0.0);
+            let bend = if curled { 80f32.to_radians() } else { 0.0 };
+            place(finger, start, Vec3::NEG_Z, bend);
+        }
+        joints
+    }
+
+    #[test]
+    fn detects_gestures() {
+        assert_eq!(detect(&hand(Vec3::Y, [true; 4])), Some(Gesture::ThumbsUp));
+        assert_eq!(
+            detect(&hand(Vec3::Y, [false, true, true, true])),
+            Some(Gesture::FingerGun)
+        );
+        assert_eq!(detect(&hand(Vec3::X, [false; 4])), Some(Gesture::OpenPalm));
+
+        // A fist with the thumb pointing sideways isn't a thumbs up.
+        assert_eq!(detect(&hand(Vec3::X, [true; 4])), None);
+        // Neither is a peace sign.
+        assert_eq!(detect(&hand(Vec3::Y, [false, false, true, true])), None);
+    }
+
+    #[test]
+    fn untracked_joints_detect_nothing() {
+        let mut joints = hand(Vec3::Y, [true; 4]);
+        joints[xr::HandJoint::WRIST].location_flags = xr::SpaceLocationFlags::EMPTY;
+        assert_eq!(detect(&joints), None);
+    }
+}
@@ -0,0 +1,100 @@
+use super::Input;
+use crate::openxr_data;
+use log::{debug, error, info};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Keeps track of the action manifest and bindings files that were loaded, so they can be
+/// reloaded when they're edited.
+pub(super) struct BindingsWatcher {
+    enabled: bool,
+    state: Mutex<WatchState>,
+}
+
+#[derive(Default)]
+struct WatchState {
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+    last_check: Option<Instant>,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl BindingsWatcher {
+    /// How often to check the watched files for changes.
+    const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+    pub fn new() -> Self {
+        Self {
+            enabled: std::env::var("XRIZER_HOT_RELOAD_BINDINGS").is_ok_and(|v| v != "0"),
+            state: Default::default(),
+        }
+    }
+
+    pub fn clear(&self) {
+        self.state.lock().unwrap().files.clear();
+    }
+
+    pub fn watch(&self, path: &Path) {
+        if !self.enabled {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        if state.files.iter().all(|(p, _)| p != path) {
+            debug!("watching {} for changes", path.display());
+            state.files.push((path.to_path_buf(), modified(path)));
+        }
+    }
+
+    /// Returns true if any of the watched files changed since the last time they were checked.
+    fn poll(&self) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let mut state = self.state.lock().unwrap();
+        if state
+            .last_check
+            .is_some_and(|t| t.elapsed() < Self::CHECK_INTERVAL)
+        {
+            return false;
+        }
+        state.last_check = Some(Instant::now());
+
+        let mut changed = false;
+        for (path, last_modified) in &mut state.files {
+            let current = modified(path);
+            if current != *last_modified {
+                info!("{} changed on disk", path.display());
+                *last_modified = current;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+impl<C: openxr_data::Compositor> Input<C> {
+    /// Reloads the action manifest and bindings if they were edited since they were loaded.
+    /// Bindings can only be suggested before a session attaches its action sets, so this
+    /// restarts the session, and must only be called between frames.
+    pub fn reload_bindings_if_changed(&self) {
+        if !self.bindings_watcher.poll() {
+            return;
+        }
+        let Some(path) = self.loaded_actions_path.get() else {
+            return;
+        };
+
+        // A broken manifest would fail to load after the session is already gone, so make sure
+        // it can still be parsed first. Broken bindings are skipped while loading anyway.
+        if let Err(e) = Self::parse_action_manifest(path) {
+            error!("Not reloading action manifest: {e:?}");
+            return;
+        }
+
+        info!("Reloading action manifest and bindings");
+        self.openxr.restart_session();
+    }
+}
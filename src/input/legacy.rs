@@ -1,6 +1,7 @@
-use super::{Input, Profiles};
+use super::{profiles::LegacyAxis, Input, Profiles, GAMEPAD_DEVICE_INDEX};
+use crate::config::Config;
 use crate::openxr_data::{self, Hand, OpenXrData, SessionData};
-use glam::Quat;
+use glam::{Mat4, Quat};
 use log::{debug, trace, warn};
 use openvr as vr;
 use openxr as xr;
@@ -8,17 +9,27 @@ use std::{
     ops::Deref,
     sync::{
         atomic::{AtomicBool, AtomicU32, Ordering},
-        RwLock, RwLockReadGuard,
+        Mutex, RwLock, RwLockReadGuard,
     },
 };
 
 #[derive(Default)]
 pub(super) struct LegacyState {
     packet_num: AtomicU32,
-    got_state_this_frame: [AtomicBool; 2],
+    /// Left hand, right hand, gamepad
+    got_state_this_frame: [AtomicBool; 3],
+    /// The dpad buttons last reported as touched and pressed, for each device.
+    dpad: [Mutex<(u64, u64)>; 3],
+    /// Whether the proximity sensor of the HMD was last reported as covered.
+    proximity: AtomicBool,
 }
 
 impl LegacyState {
+    /// The number of times actions have been synced.
+    pub fn packet_num(&self) -> u32 {
+        self.packet_num.load(Ordering::Relaxed)
+    }
+
     pub fn on_action_sync(&self) {
         self.packet_num.fetch_add(1, Ordering::Relaxed);
         for state in &self.got_state_this_frame {
@@ -39,6 +50,63 @@ macro_rules! button_mask_from_ids {
 }
 
 impl<C: openxr_data::Compositor> Input<C> {
+    /// Whether the menu button or gesture that summons the dashboard is being held.
+    pub fn dashboard_summon_held(&self) -> bool {
+        let config = &Config::get().dashboard;
+        if config
+            .gesture
+            .is_some_and(|gesture| self.gestures.lock().unwrap().is_held(gesture))
+        {
+            return true;
+        }
+        if !config.menu_button {
+            return false;
+        }
+
+        [Hand::Left, Hand::Right]
+            .into_iter()
+            .any(|hand| self.legacy_click(hand, |actions| &actions.app_menu))
+    }
+
+    /// Whether the trigger of a controller is clicked, for clicking on overlays.
+    pub fn trigger_held(&self, hand: Hand) -> bool {
+        self.legacy_click(hand, |actions| &actions.trigger_click)
+    }
+
+    fn legacy_click(
+        &self,
+        hand: Hand,
+        action: impl FnOnce(&LegacyActions) -> &xr::Action<bool>,
+    ) -> bool {
+        let data = self.openxr.session_data.get();
+        let Some(legacy) = data.input_data.legacy_actions.get() else {
+            return false;
+        };
+        let hand_path = match hand {
+            Hand::Left => self.openxr.left_hand.subaction_path,
+            Hand::Right => self.openxr.right_hand.subaction_path,
+        };
+        action(&legacy.actions)
+            .state(&data.session, hand_path)
+            .is_ok_and(|state| state.current_state)
+    }
+
+    /// Returns None if the controller doesn't report its battery level.
+    pub(super) fn get_battery_level(&self, hand: Hand) -> Option<f32> {
+        let data = self.openxr.session_data.get();
+        let legacy = data.input_data.legacy_actions.get()?;
+        let hand_path = match hand {
+            Hand::Left => self.openxr.left_hand.subaction_path,
+            Hand::Right => self.openxr.right_hand.subaction_path,
+        };
+        let state = legacy
+            .actions
+            .battery
+            .state(&data.session, hand_path)
+            .ok()?;
+        state.is_active.then_some(state.current_state)
+    }
+
     pub fn get_legacy_controller_state(
         &self,
         device_index: vr::TrackedDeviceIndex_t,
@@ -53,6 +121,12 @@ impl<C: openxr_data::Compositor> Input<C> {
             return false;
         }
 
+        let state = unsafe { state.as_mut() }.unwrap();
+        if device_index == vr::k_unTrackedDeviceIndex_Hmd {
+            self.get_hmd_state(state);
+            return true;
+        }
+
         let data = self.openxr.session_data.get();
         let Some(legacy) = data.input_data.legacy_actions.get() else {
             debug!("tried getting controller state, but legacy actions aren't ready");
@@ -60,26 +134,36 @@ impl<C: openxr_data::Compositor> Input<C> {
         };
         let actions = &legacy.actions;
 
-        let Ok(hand) = Hand::try_from(device_index) else {
-            debug!("requested controller state for invalid device index: {device_index}");
-            return false;
-        };
-
-        let hand_info = match hand {
-            Hand::Left => &self.openxr.left_hand,
-            Hand::Right => &self.openxr.right_hand,
+        let (hand_path, state_idx, profile_data) = if device_index == GAMEPAD_DEVICE_INDEX {
+            (
+                self.openxr.gamepad.subaction_path,
+                2,
+                self.get_gamepad_profile_data(),
+            )
+        } else {
+            let Ok(hand) = Hand::try_from(device_index) else {
+                debug!("requested controller state for invalid device index: {device_index}");
+                return false;
+            };
+            let hand_info = match hand {
+                Hand::Left => &self.openxr.left_hand,
+                Hand::Right => &self.openxr.right_hand,
+            };
+            (
+                hand_info.subaction_path,
+                hand as usize - 1,
+                self.get_profile_data(hand),
+            )
         };
-        let hand_path = hand_info.subaction_path;
 
         let data = self.openxr.session_data.get();
 
-        let state = unsafe { state.as_mut() }.unwrap();
         *state = Default::default();
 
         state.unPacketNum = self.legacy_state.packet_num.load(Ordering::Relaxed);
 
         // Only send the input event if we haven't already.
-        let mut events = self.legacy_state.got_state_this_frame[hand as usize - 1]
+        let mut events = self.legacy_state.got_state_this_frame[state_idx]
             .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
             .is_ok()
             .then(|| self.events.lock().unwrap());
@@ -121,40 +205,198 @@ impl<C: openxr_data::Compositor> Input<C> {
             };
 
         read_button(
-            vr::EVRButtonId::Axis0,
-            &actions.main_xy_click,
-            Some(&actions.main_xy_touch),
-        );
-        read_button(
-            vr::EVRButtonId::SteamVR_Trigger,
-            &actions.trigger_click,
-            None,
+            vr::EVRButtonId::ApplicationMenu,
+            &actions.app_menu,
+            Some(&actions.app_menu_touch),
         );
-        read_button(vr::EVRButtonId::ApplicationMenu, &actions.app_menu, None);
-        read_button(vr::EVRButtonId::A, &actions.a, None);
+        read_button(vr::EVRButtonId::A, &actions.a, Some(&actions.a_touch));
         read_button(vr::EVRButtonId::Grip, &actions.squeeze_click, None);
-        read_button(vr::EVRButtonId::Axis2, &actions.squeeze_click, None);
 
-        let j = actions.main_xy.state(&data.session, hand_path).unwrap();
-        state.rAxis[0] = vr::VRControllerAxis_t {
-            x: j.current_state.x,
-            y: j.current_state.y,
+        let read_f32 = |action: &xr::Action<f32>| {
+            action
+                .state(&data.session, hand_path)
+                .unwrap()
+                .current_state
         };
-
-        let t = actions.trigger.state(&data.session, hand_path).unwrap();
-        state.rAxis[1] = vr::VRControllerAxis_t {
-            x: t.current_state,
-            y: 0.0,
+        let read_xy = |action: &xr::Action<xr::Vector2f>| {
+            let state = action
+                .state(&data.session, hand_path)
+                .unwrap()
+                .current_state;
+            vr::VRControllerAxis_t {
+                x: state.x,
+                y: state.y,
+            }
         };
 
-        let s = actions.squeeze.state(&data.session, hand_path).unwrap();
-        state.rAxis[2] = vr::VRControllerAxis_t {
-            x: s.current_state,
-            y: 0.0,
-        };
+        let axes = profile_data
+            .map(|data| data.legacy_axes)
+            .unwrap_or([LegacyAxis::None; 5]);
+        const AXIS_BUTTONS: [vr::EVRButtonId; 5] = [
+            vr::EVRButtonId::Axis0,
+            vr::EVRButtonId::Axis1,
+            vr::EVRButtonId::Axis2,
+            vr::EVRButtonId::Axis3,
+            vr::EVRButtonId::Axis4,
+        ];
+        let main_axis = profile_data.map(|data| data.main_axis);
+        let mut axis_values = [vr::VRControllerAxis_t::default(); 5];
+        let mut trackpad = None;
+        for (idx, (axis, button)) in axes.into_iter().zip(AXIS_BUTTONS).enumerate() {
+            let (value, click, touch) = match axis {
+                LegacyAxis::None => continue,
+                LegacyAxis::Main => (
+                    read_xy(&actions.main_xy),
+                    &actions.main_xy_click,
+                    Some(&actions.main_xy_touch),
+                ),
+                LegacyAxis::Secondary(_) => (
+                    read_xy(&actions.secondary_xy),
+                    &actions.secondary_xy_click,
+                    Some(&actions.secondary_xy_touch),
+                ),
+                LegacyAxis::Trigger => (
+                    vr::VRControllerAxis_t {
+                        x: read_f32(&actions.trigger),
+                        y: 0.0,
+                    },
+                    &actions.trigger_click,
+                    Some(&actions.trigger_touch),
+                ),
+                LegacyAxis::Squeeze => (
+                    vr::VRControllerAxis_t {
+                        x: read_f32(&actions.squeeze),
+                        y: 0.0,
+                    },
+                    &actions.squeeze_click,
+                    None,
+                ),
+                LegacyAxis::ThumbrestStylus => {
+                    axis_values[idx] = vr::VRControllerAxis_t {
+                        x: read_f32(&actions.thumbrest_force),
+                        y: read_f32(&actions.stylus_force),
+                    };
+                    continue;
+                }
+                LegacyAxis::TriggerCurlSlide => {
+                    axis_values[idx] = vr::VRControllerAxis_t {
+                        x: read_f32(&actions.trigger_curl),
+                        y: read_f32(&actions.trigger_slide),
+                    };
+                    continue;
+                }
+            };
+            axis_values[idx] = value;
+            read_button(button, click, touch);
+            if trackpad.is_none()
+                && main_axis.is_some_and(|main_axis| {
+                    axis.axis_type(&main_axis) == vr::EVRControllerAxisType::TrackPad
+                })
+            {
+                trackpad = Some((value, click, touch));
+            }
+        }
+        state.rAxis = axis_values;
+
+        // Old SteamVR bindings often treat regions of the trackpad as a dpad, so report the
+        // region that's being touched or clicked as the matching dpad button.
+        if let Some((value, click, touch)) = trackpad {
+            let mask = dpad_direction(value).map_or(0, button_mask_from_id);
+            let touched =
+                touch.is_some_and(|a| a.state(&data.session, hand_path).unwrap().current_state);
+            let pressed = click.state(&data.session, hand_path).unwrap().current_state;
+            let touched = mask * touched as u64;
+            let pressed = mask * pressed as u64;
+            state.ulButtonTouched |= touched;
+            state.ulButtonPressed |= pressed;
+
+            if let Some(events) = &mut events {
+                let mut last = self.legacy_state.dpad[state_idx].lock().unwrap();
+                let (last_touched, last_pressed) = &mut *last;
+                for (current, last, on, off) in [
+                    (
+                        touched,
+                        last_touched,
+                        vr::EVREventType::ButtonTouch,
+                        vr::EVREventType::ButtonUntouch,
+                    ),
+                    (
+                        pressed,
+                        last_pressed,
+                        vr::EVREventType::ButtonPress,
+                        vr::EVREventType::ButtonUnpress,
+                    ),
+                ] {
+                    for id in DPAD_BUTTONS {
+                        let bit = button_mask_from_id(id);
+                        if (current ^ *last) & bit != 0 {
+                            events.push_back(super::InputEvent {
+                                ty: if current & bit != 0 { on } else { off },
+                                index: device_index,
+                                data: vr::VREvent_Controller_t { button: id as u32 },
+                            });
+                        }
+                    }
+                    *last = current;
+                }
+            }
+        }
 
         true
     }
+
+    /// The HMD has no buttons besides its proximity sensor, which is covered while the user is
+    /// present.
+    fn get_hmd_state(&self, state: &mut vr::VRControllerState_t) {
+        let worn = self.openxr.user_present();
+        *state = Default::default();
+        state.unPacketNum = self.legacy_state.packet_num.load(Ordering::Relaxed);
+        state.ulButtonPressed = button_mask_from_id(vr::EVRButtonId::ProximitySensor) * worn as u64;
+
+        if self.legacy_state.proximity.swap(worn, Ordering::Relaxed) != worn {
+            self.events.lock().unwrap().push_back(super::InputEvent {
+                ty: if worn {
+                    vr::EVREventType::ButtonPress
+                } else {
+                    vr::EVREventType::ButtonUnpress
+                },
+                index: vr::k_unTrackedDeviceIndex_Hmd,
+                data: vr::VREvent_Controller_t {
+                    button: vr::EVRButtonId::ProximitySensor as u32,
+                },
+            });
+        }
+    }
+}
+
+/// How far from the center of a trackpad a touch has to be to count as one of the dpad
+/// directions.
+const DPAD_DEADZONE: f32 = 0.4;
+
+const DPAD_BUTTONS: [vr::EVRButtonId; 4] = [
+    vr::EVRButtonId::DPad_Left,
+    vr::EVRButtonId::DPad_Up,
+    vr::EVRButtonId::DPad_Right,
+    vr::EVRButtonId::DPad_Down,
+];
+
+/// The dpad button for a position on a trackpad, if it's outside of the center.
+fn dpad_direction(value: vr::VRControllerAxis_t) -> Option<vr::EVRButtonId> {
+    let (x, y) = (value.x, value.y);
+    if x * x + y * y < DPAD_DEADZONE * DPAD_DEADZONE {
+        return None;
+    }
+    Some(if x.abs() > y.abs() {
+        if x < 0.0 {
+            vr::EVRButtonId::DPad_Left
+        } else {
+            vr::EVRButtonId::DPad_Right
+        }
+    } else if y > 0.0 {
+        vr::EVRButtonId::DPad_Up
+    } else {
+        vr::EVRButtonId::DPad_Down
+    })
 }
 
 macro_rules! legacy_actions_and_bindings {
@@ -182,8 +424,11 @@ legacy_actions_and_bindings! {
     grip_pose: xr::Action<xr::Posef>,
     aim_pose: xr::Action<xr::Posef>,
     app_menu: xr::Action<bool>,
+    app_menu_touch: xr::Action<bool>,
     a: xr::Action<bool>,
+    a_touch: xr::Action<bool>,
     trigger_click: xr::Action<bool>,
+    trigger_touch: xr::Action<bool>,
     squeeze_click: xr::Action<bool>,
     trigger: xr::Action<f32>,
     squeeze: xr::Action<f32>,
@@ -191,6 +436,16 @@ legacy_actions_and_bindings! {
     main_xy: xr::Action<xr::Vector2f>,
     main_xy_touch: xr::Action<bool>,
     main_xy_click: xr::Action<bool>,
+    // Controllers with both a stick and a trackpad
+    secondary_xy: xr::Action<xr::Vector2f>,
+    secondary_xy_touch: xr::Action<bool>,
+    secondary_xy_click: xr::Action<bool>,
+    battery: xr::Action<f32>,
+    // Extra analog inputs, only present on some controllers
+    thumbrest_force: xr::Action<f32>,
+    stylus_force: xr::Action<f32>,
+    trigger_curl: xr::Action<f32>,
+    trigger_slide: xr::Action<f32>,
 }
 
 pub(super) struct LegacyActionData {
@@ -198,12 +453,21 @@ pub(super) struct LegacyActionData {
     pub left_spaces: HandSpaces,
     pub right_spaces: HandSpaces,
     pub actions: LegacyActions,
+    /// Only created if XR_EXT_palm_pose is enabled.
+    pub palm_pose: Option<xr::Action<xr::Posef>>,
 }
 
 impl LegacyActionData {
-    pub fn new(instance: &xr::Instance, left_hand: xr::Path, right_hand: xr::Path) -> Self {
+    pub fn new(
+        instance: &xr::Instance,
+        left_hand: xr::Path,
+        right_hand: xr::Path,
+        gamepad: xr::Path,
+    ) -> Self {
         debug!("creating legacy actions");
         let leftright = [left_hand, right_hand];
+        // Gamepads have buttons and axes, but no poses.
+        let all = [left_hand, right_hand, gamepad];
         let create_spaces = |hand| {
             let hand_path = match hand {
                 Hand::Left => left_hand,
@@ -213,6 +477,7 @@ impl LegacyActionData {
                 hand,
                 hand_path,
                 raw: RwLock::new(None),
+                handgrip: RwLock::new(None),
             }
         };
 
@@ -231,33 +496,68 @@ impl LegacyActionData {
                 .create_action("aim-pose", "Aim Pose", &leftright)
                 .unwrap(),
             trigger_click: set
-                .create_action("trigger-click", "Trigger Click", &leftright)
+                .create_action("trigger-click", "Trigger Click", &all)
+                .unwrap(),
+            trigger_touch: set
+                .create_action("trigger-touch", "Trigger Touch", &all)
                 .unwrap(),
-            trigger: set.create_action("trigger", "Trigger", &leftright).unwrap(),
-            squeeze: set.create_action("squeeze", "Squeeze", &leftright).unwrap(),
+            trigger: set.create_action("trigger", "Trigger", &all).unwrap(),
+            squeeze: set.create_action("squeeze", "Squeeze", &all).unwrap(),
             app_menu: set
-                .create_action("app-menu", "Application Menu", &leftright)
+                .create_action("app-menu", "Application Menu", &all)
                 .unwrap(),
-            a: set.create_action("a", "A Button", &leftright).unwrap(),
-            squeeze_click: set
-                .create_action("grip-click", "Grip Click", &leftright)
+            app_menu_touch: set
+                .create_action("app-menu-touch", "Application Menu Touch", &all)
                 .unwrap(),
+            a: set.create_action("a", "A Button", &all).unwrap(),
+            a_touch: set
+                .create_action("a-touch", "A Button Touch", &all)
+                .unwrap(),
+            squeeze_click: set.create_action("grip-click", "Grip Click", &all).unwrap(),
             main_xy: set
-                .create_action("main-joystick", "Main Joystick/Trackpad", &leftright)
+                .create_action("main-joystick", "Main Joystick/Trackpad", &all)
                 .unwrap(),
             main_xy_click: set
-                .create_action("main-joystick-click", "Main Joystick Click", &leftright)
+                .create_action("main-joystick-click", "Main Joystick Click", &all)
                 .unwrap(),
             main_xy_touch: set
-                .create_action("main-joystick-touch", "Main Joystick Touch", &leftright)
+                .create_action("main-joystick-touch", "Main Joystick Touch", &all)
+                .unwrap(),
+            secondary_xy: set
+                .create_action("secondary-joystick", "Secondary Joystick/Trackpad", &all)
+                .unwrap(),
+            secondary_xy_click: set
+                .create_action("secondary-joystick-click", "Secondary Joystick Click", &all)
+                .unwrap(),
+            secondary_xy_touch: set
+                .create_action("secondary-joystick-touch", "Secondary Joystick Touch", &all)
+                .unwrap(),
+            battery: set.create_action("battery", "Battery", &all).unwrap(),
+            thumbrest_force: set
+                .create_action("thumbrest-force", "Thumbrest Force", &all)
+                .unwrap(),
+            stylus_force: set
+                .create_action("stylus-force", "Stylus Force", &all)
+                .unwrap(),
+            trigger_curl: set
+                .create_action("trigger-curl", "Trigger Curl", &all)
+                .unwrap(),
+            trigger_slide: set
+                .create_action("trigger-slide", "Trigger Slide", &all)
                 .unwrap(),
         };
 
+        let palm_pose = instance.exts().ext_palm_pose.is_some().then(|| {
+            set.create_action("palm-pose", "Palm Pose", &leftright)
+                .unwrap()
+        });
+
         Self {
             set,
             left_spaces,
             right_spaces,
             actions,
+            palm_pose,
         }
     }
 }
@@ -270,7 +570,10 @@ pub fn setup_legacy_bindings(
     debug!("setting up legacy bindings");
 
     let actions = &legacy.actions;
-    for profile in Profiles::get().profiles_iter() {
+    for profile in Profiles::get()
+        .profiles_iter()
+        .filter(|p| p.is_supported(instance.exts()))
+    {
         const fn constrain<F>(f: F) -> F
         where
             F: for<'a> Fn(&'a str) -> xr::Path,
@@ -279,11 +582,26 @@ pub fn setup_legacy_bindings(
         }
         let stp = constrain(|s| instance.string_to_path(s).unwrap());
         let bindings = profile.legacy_bindings(&stp);
+        // Profiles without poses (i.e. gamepads) can't have a palm pose either.
+        let palm_bindings = legacy
+            .palm_pose
+            .as_ref()
+            .filter(|_| !bindings.grip_pose.is_empty())
+            .map(|palm| {
+                stp.leftright("input/palm_ext/pose")
+                    .into_iter()
+                    .map(|path| xr::Binding::new(palm, path))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
         let profile = stp(profile.profile_path());
         instance
             .suggest_interaction_profile_bindings(
                 profile,
-                &bindings.binding_iter(actions).collect::<Vec<_>>(),
+                &bindings
+                    .binding_iter(actions)
+                    .chain(palm_bindings)
+                    .collect::<Vec<_>>(),
             )
             .unwrap();
     }
@@ -294,6 +612,25 @@ pub fn setup_legacy_bindings(
         .unwrap();
 }
 
+fn mat4_to_posef(offset: Mat4) -> xr::Posef {
+    let translation = offset.w_axis.truncate();
+    let rotation = Quat::from_mat4(&offset);
+
+    xr::Posef {
+        orientation: xr::Quaternionf {
+            x: rotation.x,
+            y: rotation.y,
+            z: rotation.z,
+            w: rotation.w,
+        },
+        position: xr::Vector3f {
+            x: translation.x,
+            y: translation.y,
+            z: translation.z,
+        },
+    }
+}
+
 pub(super) struct HandSpaces {
     hand: Hand,
     hand_path: xr::Path,
@@ -301,6 +638,9 @@ pub(super) struct HandSpaces {
     /// Based on the controller jsons in SteamVR, the "raw" pose
     /// This is stored as a space so we can locate hand joints relative to it for skeletal data.
     raw: RwLock<Option<xr::Space>>,
+
+    /// The "handgrip" pose, derived from the palm pose when XR_EXT_palm_pose is available.
+    handgrip: RwLock<Option<xr::Space>>,
 }
 
 pub(super) struct SpaceReadGuard<'a>(RwLockReadGuard<'a, Option<xr::Space>>);
@@ -337,23 +677,11 @@ impl HandSpaces {
                 return None;
             };
 
-            let offset = profile.offset_grip_pose(self.hand);
-            let translation = offset.w_axis.truncate();
-            let rotation = Quat::from_mat4(&offset);
-
-            let offset_pose = xr::Posef {
-                orientation: xr::Quaternionf {
-                    x: rotation.x,
-                    y: rotation.y,
-                    z: rotation.z,
-                    w: rotation.w,
-                },
-                position: xr::Vector3f {
-                    x: translation.x,
-                    y: translation.y,
-                    z: translation.z,
-                },
-            };
+            let user_offset = Config::get()
+                .pose_offsets(profile.profile_path(), self.hand)
+                .grip;
+            let offset_pose =
+                mat4_to_posef(profile.offset_grip_pose(self.hand) * user_offset.matrix());
 
             *self.raw.write().unwrap() = Some(
                 actions
@@ -366,13 +694,60 @@ impl HandSpaces {
         Some(SpaceReadGuard(self.raw.read().unwrap()))
     }
 
-    pub fn reset_raw(&self) {
+    /// Returns None if XR_EXT_palm_pose isn't enabled.
+    pub fn try_get_or_init_handgrip(
+        &self,
+        xr_data: &OpenXrData<impl crate::openxr_data::Compositor>,
+        session_data: &SessionData,
+        legacy: &LegacyActionData,
+    ) -> Option<SpaceReadGuard> {
+        let palm_pose = legacy.palm_pose.as_ref()?;
+        {
+            let handgrip = self.handgrip.read().unwrap();
+            if handgrip.is_some() {
+                return Some(SpaceReadGuard(handgrip));
+            }
+        }
+
+        {
+            let hand_profile = match self.hand {
+                Hand::Right => &xr_data.right_hand.profile,
+                Hand::Left => &xr_data.left_hand.profile,
+            };
+
+            let hand_profile = hand_profile.lock().unwrap();
+            let Some(profile) = hand_profile.as_ref() else {
+                trace!("no hand profile, no handgrip space will be created");
+                return None;
+            };
+            let user_offset = Config::get()
+                .pose_offsets(profile.profile_path(), self.hand)
+                .grip;
+
+            *self.handgrip.write().unwrap() = Some(
+                palm_pose
+                    .create_space(
+                        &session_data.session,
+                        self.hand_path,
+                        mat4_to_posef(profile.offset_palm_pose(self.hand) * user_offset.matrix()),
+                    )
+                    .unwrap(),
+            );
+        }
+
+        Some(SpaceReadGuard(self.handgrip.read().unwrap()))
+    }
+
+    pub fn reset_spaces(&self) {
         *self.raw.write().unwrap() = None;
+        *self.handgrip.write().unwrap() = None;
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::button_mask_from_id;
+    use crate::button_mask_from_ids;
     use crate::input::profiles::knuckles::Knuckles;
     use crate::input::tests::Fixture;
     use openvr as vr;
@@ -628,6 +1003,95 @@ mod tests {
         expect_no_event("RightHand".to_string());
     }
 
+    #[test]
+    fn trackpad_dpad() {
+        use fakexr::UserPath::*;
+        let f = Fixture::new();
+        f.input.openxr.restart_session();
+
+        f.set_interaction_profile(&Knuckles, LeftHand);
+        f.set_interaction_profile(&Knuckles, RightHand);
+        f.input.frame_start_update();
+        f.input.openxr.poll_events();
+        let (xy, click) = {
+            let data = f.input.openxr.session_data.get();
+            let actions = &data.input_data.legacy_actions.get().unwrap().actions;
+            (
+                actions.secondary_xy.as_raw(),
+                actions.secondary_xy_click.as_raw(),
+            )
+        };
+
+        let get_state = || {
+            let mut state = vr::VRControllerState_t::default();
+            assert!(f.input.get_legacy_controller_state(
+                1,
+                &mut state,
+                std::mem::size_of_val(&state) as u32
+            ));
+            state
+        };
+        let get_pressed = || {
+            let mut pressed = Vec::new();
+            let mut event = MyEvent::default();
+            while f.input.get_next_event(
+                std::mem::size_of_val(&event) as u32,
+                &mut event as *mut _ as *mut vr::VREvent_t,
+            ) {
+                let button = unsafe { event.data.controller }.button;
+                if event.ty == vr::EVREventType::ButtonPress as u32 {
+                    pressed.push((button, true));
+                } else if event.ty == vr::EVREventType::ButtonUnpress as u32 {
+                    pressed.push((button, false));
+                }
+            }
+            pressed
+        };
+
+        // Clicking the top of the trackpad presses dpad up.
+        fakexr::set_action_state(xy, fakexr::ActionState::Vector2(0.0, 0.8), LeftHand);
+        fakexr::set_action_state(click, true.into(), LeftHand);
+        f.input.frame_start_update();
+        let state = get_state();
+        assert_eq!(
+            { state.ulButtonPressed },
+            button_mask_from_ids!(vr::EVRButtonId::Axis3, vr::EVRButtonId::DPad_Up)
+        );
+        assert_eq!(
+            get_pressed(),
+            [
+                (vr::EVRButtonId::Axis3 as u32, true),
+                (vr::EVRButtonId::DPad_Up as u32, true)
+            ]
+        );
+
+        // Sliding to the left switches to dpad left.
+        fakexr::set_action_state(xy, fakexr::ActionState::Vector2(-0.8, 0.1), LeftHand);
+        f.input.frame_start_update();
+        let state = get_state();
+        assert_eq!(
+            { state.ulButtonPressed },
+            button_mask_from_ids!(vr::EVRButtonId::Axis3, vr::EVRButtonId::DPad_Left)
+        );
+        assert_eq!(
+            get_pressed(),
+            [
+                (vr::EVRButtonId::DPad_Left as u32, true),
+                (vr::EVRButtonId::DPad_Up as u32, false)
+            ]
+        );
+
+        // The center of the trackpad isn't part of the dpad.
+        fakexr::set_action_state(xy, fakexr::ActionState::Vector2(0.1, 0.1), LeftHand);
+        f.input.frame_start_update();
+        let state = get_state();
+        assert_eq!(
+            { state.ulButtonPressed },
+            button_mask_from_id(vr::EVRButtonId::Axis3)
+        );
+        assert_eq!(get_pressed(), [(vr::EVRButtonId::DPad_Left as u32, false)]);
+    }
+
     macro_rules! test_button {
         ($click:ident, $id:path $(| $other_id:path)*) => {
             paste::paste! {
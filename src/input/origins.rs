@@ -0,0 +1,253 @@
+use super::{ActionData, ActionSetKey, Input, InputSourceKey};
+use crate::openxr_data::{self, Hand};
+use log::debug;
+use openvr as vr;
+use openxr as xr;
+use slotmap::{Key, KeyData};
+use std::ffi::{CStr, CString};
+
+// EVRInputStringBits
+const STRING_HAND: i32 = 0x01;
+const STRING_CONTROLLER_TYPE: i32 = 0x02;
+const STRING_INPUT_SOURCE: i32 = 0x04;
+
+impl<C: openxr_data::Compositor> Input<C> {
+    /// The input sources the action is currently bound to, as input source handles.
+    pub(super) fn action_origins(
+        &self,
+        action: vr::VRActionHandle_t,
+    ) -> Result<Vec<vr::VRInputValueHandle_t>, vr::EVRInputError> {
+        let data = self.openxr.session_data.get();
+        let Some(loaded) = data.input_data.get_loaded_actions() else {
+            return Err(vr::EVRInputError::InvalidHandle);
+        };
+
+        let raw_action = match loaded.try_get_action(action)? {
+            // Poses and skeletons aren't bound to a particular input, so the hands are their origins.
+            ActionData::Pose => {
+                return Ok([
+                    (&self.openxr.left_hand, self.left_hand_key),
+                    (&self.openxr.right_hand, self.right_hand_key),
+                ]
+                .into_iter()
+                .filter(|(info, _)| {
+                    loaded
+                        .try_get_pose(action, info.profile_path.load())
+                        .is_ok()
+                })
+                .map(|(_, key)| key.0.as_ffi())
+                .collect());
+            }
+            ActionData::Skeleton { hand, .. } => {
+                let key = match hand {
+                    Hand::Left => self.left_hand_key,
+                    Hand::Right => self.right_hand_key,
+                };
+                return Ok(vec![key.0.as_ffi()]);
+            }
+            other => other.raw_action().unwrap(),
+        };
+
+        let sources = enumerate_bound_sources(&self.openxr.instance, &data.session, raw_action)
+            .map_err(|e| {
+                debug!("Couldn't enumerate bound sources: {e}");
+                vr::EVRInputError::InvalidHandle
+            })?;
+        Ok(sources
+            .into_iter()
+            .filter_map(|source| {
+                let path = self.openxr.instance.path_to_string(source).ok()?;
+                Some(self.input_source_handle(&CString::new(path).ok()?))
+            })
+            .collect())
+    }
+
+    /// Something like "Left Hand Index Controller Trigger", containing only the requested sections.
+    pub(super) fn origin_localized_name(&self, origin: &CStr, sections: i32) -> String {
+        let Ok(origin) = origin.to_str() else {
+            return String::new();
+        };
+        let (hand, user_path) = if origin.starts_with("/user/hand/left") {
+            (Some(Hand::Left), &self.openxr.left_hand)
+        } else if origin.starts_with("/user/hand/right") {
+            (Some(Hand::Right), &self.openxr.right_hand)
+        } else if origin.starts_with("/user/gamepad") {
+            (None, &self.openxr.gamepad)
+        } else {
+            return String::new();
+        };
+
+        // Only actual input sources have names the runtime knows about.
+        let component = origin
+            .strip_prefix(user_path.path_name())
+            .filter(|c| !c.is_empty());
+        if let Some(name) = component.and_then(|_| self.runtime_localized_name(origin, sections)) {
+            return name;
+        }
+
+        let mut parts = Vec::new();
+        if sections & STRING_HAND != 0 {
+            parts.extend(hand.map(|hand| match hand {
+                Hand::Left => "Left Hand".to_string(),
+                Hand::Right => "Right Hand".to_string(),
+            }));
+        }
+        if sections & STRING_CONTROLLER_TYPE != 0 {
+            let data = match hand {
+                Some(hand) => self.get_profile_data(hand),
+                None => self.get_gamepad_profile_data(),
+            };
+            parts.extend(data.map(|data| {
+                data.model
+                    .get(hand.unwrap_or(Hand::Left))
+                    .to_string_lossy()
+                    .into_owned()
+            }));
+        }
+        if sections & STRING_INPUT_SOURCE != 0 {
+            // i.e. /input/trigger/value -> Trigger
+            parts.extend(
+                component
+                    .and_then(|c| c.strip_prefix("/input/"))
+                    .and_then(|c| c.split('/').next())
+                    .map(|input| {
+                        input
+                            .split('_')
+                            .map(capitalize)
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    }),
+            );
+        }
+        parts.join(" ")
+    }
+
+    /// One line per action in the set (or just the given action) with what it's bound to, after a
+    /// line naming the set.
+    pub(super) fn bindings_view_lines(
+        &self,
+        set: vr::VRActionSetHandle_t,
+        action: Option<vr::VRActionHandle_t>,
+    ) -> Result<Vec<String>, vr::EVRInputError> {
+        let set_key = ActionSetKey::from(KeyData::from_ffi(set));
+        let Some(set_path) = self.set_map.read().unwrap().get(set_key).cloned() else {
+            return Err(vr::EVRInputError::InvalidHandle);
+        };
+        let prefix = format!("{set_path}/");
+        let mut actions: Vec<_> = self
+            .action_map
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(key, data)| match action {
+                Some(action) => key.data().as_ffi() == action,
+                None => data.path.starts_with(&prefix),
+            })
+            .map(|(key, data)| (key.data().as_ffi(), data.path.clone()))
+            .collect();
+        actions.sort_by(|(_, a), (_, b)| a.cmp(b));
+
+        let all_sections = STRING_HAND | STRING_CONTROLLER_TYPE | STRING_INPUT_SOURCE;
+        let mut lines = vec![set_path];
+        // Actions the application asked for a handle to but that aren't in the manifest fail here.
+        lines.extend(actions.into_iter().filter_map(|(handle, path)| {
+            let names: Vec<_> = self
+                .action_origins(handle)
+                .ok()?
+                .into_iter()
+                .filter_map(|origin| {
+                    let key = InputSourceKey::from(KeyData::from_ffi(origin));
+                    self.input_source_map.read().unwrap().get(key).cloned()
+                })
+                .map(|origin| self.origin_localized_name(&origin, all_sections))
+                .filter(|name| !name.is_empty())
+                .collect();
+            // i.e. /actions/main/in/jump -> jump
+            let name = path.rsplit('/').next().unwrap_or(&path);
+            Some(if names.is_empty() {
+                format!("    {name}: not bound")
+            } else {
+                format!("    {name}: {}", names.join(", "))
+            })
+        }));
+        Ok(lines)
+    }
+
+    fn runtime_localized_name(&self, source: &str, sections: i32) -> Option<String> {
+        let flags = [
+            (STRING_HAND, xr::InputSourceLocalizedNameFlags::USER_PATH),
+            (
+                STRING_CONTROLLER_TYPE,
+                xr::InputSourceLocalizedNameFlags::INTERACTION_PROFILE,
+            ),
+            (
+                STRING_INPUT_SOURCE,
+                xr::InputSourceLocalizedNameFlags::COMPONENT,
+            ),
+        ]
+        .into_iter()
+        .filter(|(bit, _)| sections & bit != 0)
+        .fold(xr::InputSourceLocalizedNameFlags::EMPTY, |flags, (_, f)| {
+            flags | f
+        });
+        if flags == xr::InputSourceLocalizedNameFlags::EMPTY {
+            return Some(String::new());
+        }
+
+        let path = self.openxr.instance.string_to_path(source).ok()?;
+        let data = self.openxr.session_data.get();
+        data.session
+            .input_source_localized_name(path, flags)
+            .inspect_err(|e| debug!("Couldn't get localized name for {source}: {e}"))
+            .ok()
+    }
+}
+
+pub(super) fn enumerate_bound_sources(
+    instance: &xr::Instance,
+    session: &xr::Session<xr::AnyGraphics>,
+    action: xr::sys::Action,
+) -> xr::Result<Vec<xr::Path>> {
+    let fp = instance.fp();
+    let info = xr::sys::BoundSourcesForActionEnumerateInfo {
+        ty: xr::sys::BoundSourcesForActionEnumerateInfo::TYPE,
+        next: std::ptr::null(),
+        action,
+    };
+    let mut count = 0;
+    let result = unsafe {
+        (fp.enumerate_bound_sources_for_action)(
+            session.as_raw(),
+            &info,
+            0,
+            &mut count,
+            std::ptr::null_mut(),
+        )
+    };
+    if result != xr::sys::Result::SUCCESS {
+        return Err(result);
+    }
+    let mut sources = vec![xr::Path::NULL; count as usize];
+    let result = unsafe {
+        (fp.enumerate_bound_sources_for_action)(
+            session.as_raw(),
+            &info,
+            count,
+            &mut count,
+            sources.as_mut_ptr(),
+        )
+    };
+    if result != xr::sys::Result::SUCCESS {
+        return Err(result);
+    }
+    sources.truncate(count as usize);
+    Ok(sources)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
@@ -0,0 +1,174 @@
+use glam::{Quat, Vec3};
+use openvr as vr;
+use openxr as xr;
+use serde::Deserialize;
+use std::f32::consts::TAU;
+
+/// Cutoff for smoothing the speed the filter's cutoff is derived from, in Hz.
+const DERIVATIVE_CUTOFF: f32 = 1.0;
+
+/// Settings for the one euro filter (https://gery.casiez.net/1euro/) applied to controller poses.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct PoseFilterConfig {
+    /// Cutoff frequency while the controller is still, in Hz. Lower values reduce jitter but add
+    /// lag.
+    #[serde(default = "default_min_cutoff")]
+    min_cutoff: f32,
+    /// How much the cutoff rises with the controller's speed, to reduce lag during fast movements.
+    #[serde(default = "default_beta")]
+    beta: f32,
+}
+
+fn default_min_cutoff() -> f32 {
+    1.0
+}
+
+fn default_beta() -> f32 {
+    0.5
+}
+
+/// How much of the new value to take for a cutoff frequency and time step.
+fn alpha(cutoff: f32, dt: f32) -> f32 {
+    let tau = 1.0 / (TAU * cutoff);
+    1.0 / (1.0 + tau / dt)
+}
+
+#[derive(Clone, Copy)]
+struct FilterState {
+    time: xr::Time,
+    position: Vec3,
+    orientation: Quat,
+    /// Filtered linear and angular speed.
+    speed: (f32, f32),
+    output: vr::TrackedDevicePose_t,
+}
+
+/// Smooths the poses of one controller in one tracking space.
+#[derive(Default)]
+pub(super) struct PoseFilter {
+    last: Option<FilterState>,
+}
+
+impl PoseFilter {
+    pub fn apply(
+        &mut self,
+        config: &PoseFilterConfig,
+        time: xr::Time,
+        mut pose: vr::TrackedDevicePose_t,
+    ) -> vr::TrackedDevicePose_t {
+        if !pose.bPoseIsValid {
+            self.last = None;
+            return pose;
+        }
+        let xr_pose = xr::Posef::from(pose.mDeviceToAbsoluteTracking);
+        let position = Vec3::new(xr_pose.position.x, xr_pose.position.y, xr_pose.position.z);
+        let orientation = Quat::from_xyzw(
+            xr_pose.orientation.x,
+            xr_pose.orientation.y,
+            xr_pose.orientation.z,
+            xr_pose.orientation.w,
+        );
+
+        let Some(last) = self.last else {
+            self.last = Some(FilterState {
+                time,
+                position,
+                orientation,
+                speed: (0.0, 0.0),
+                output: pose,
+            });
+            return pose;
+        };
+        let dt = (time.as_nanos() - last.time.as_nanos()) as f32 / 1e9;
+        if dt <= 0.0 {
+            // Already filtered for this time.
+            return last.output;
+        }
+
+        let linear_speed = position.distance(last.position) / dt;
+        let angular_speed = orientation.angle_between(last.orientation) / dt;
+        let derivative_alpha = alpha(DERIVATIVE_CUTOFF, dt);
+        let speed = (
+            last.speed.0 + (linear_speed - last.speed.0) * derivative_alpha,
+            last.speed.1 + (angular_speed - last.speed.1) * derivative_alpha,
+        );
+
+        let position_alpha = alpha(config.min_cutoff + config.beta * speed.0, dt);
+        let orientation_alpha = alpha(config.min_cutoff + config.beta * speed.1, dt);
+        let position = last.position.lerp(position, position_alpha);
+        let orientation = last.orientation.slerp(orientation, orientation_alpha);
+
+        pose.mDeviceToAbsoluteTracking = xr::Posef {
+            position: xr::Vector3f {
+                x: position.x,
+                y: position.y,
+                z: position.z,
+            },
+            orientation: xr::Quaternionf {
+                x: orientation.x,
+                y: orientation.y,
+                z: orientation.z,
+                w: orientation.w,
+            },
+        }
+        .into();
+        self.last = Some(FilterState {
+            time,
+            position,
+            orientation,
+            speed,
+            output: pose,
+        });
+        pose
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pose_at(x: f32) -> vr::TrackedDevicePose_t {
+        vr::TrackedDevicePose_t {
+            mDeviceToAbsoluteTracking: xr::Posef {
+                position: xr::Vector3f { x, y: 0.0, z: 0.0 },
+                orientation: xr::Quaternionf::IDENTITY,
+            }
+            .into(),
+            bPoseIsValid: true,
+            ..Default::default()
+        }
+    }
+
+    fn x_of(pose: vr::TrackedDevicePose_t) -> f32 {
+        xr::Posef::from(pose.mDeviceToAbsoluteTracking).position.x
+    }
+
+    #[test]
+    fn smooths_jitter() {
+        let config = PoseFilterConfig {
+            min_cutoff: default_min_cutoff(),
+            beta: 0.0,
+        };
+        let mut filter = PoseFilter::default();
+        let frame = 11_111_111;
+        assert_eq!(
+            x_of(filter.apply(&config, xr::Time::from_nanos(frame), pose_at(0.0))),
+            0.0
+        );
+
+        let jittered = x_of(filter.apply(&config, xr::Time::from_nanos(2 * frame), pose_at(0.01)));
+        assert!(jittered > 0.0 && jittered < 0.01, "{jittered}");
+        // Asking again for the same time gives the same pose.
+        assert_eq!(
+            x_of(filter.apply(&config, xr::Time::from_nanos(2 * frame), pose_at(0.01))),
+            jittered
+        );
+
+        // Holding still eventually converges on the real position.
+        let mut x = jittered;
+        for i in 3..300 {
+            x = x_of(filter.apply(&config, xr::Time::from_nanos(i * frame), pose_at(0.01)));
+        }
+        assert!((x - 0.01).abs() < 1e-4, "{x}");
+    }
+}
@@ -1,19 +1,31 @@
+pub mod hand_interaction;
+pub mod holographic_controller;
 pub mod knuckles;
 pub mod oculus_touch;
+pub mod pico;
 pub mod simple_controller;
+pub mod touch_pro;
 pub mod vive_controller;
+pub mod xbox_controller;
 
 use super::{
     action_manifest::ControllerType, legacy::LegacyBindings, skeletal::SkeletalInputBindings,
 };
+use crate::config::Config;
 use crate::openxr_data::Hand;
 use glam::Mat4;
+use hand_interaction::HandInteraction;
+use holographic_controller::HolographicController;
 use knuckles::Knuckles;
 use oculus_touch::Touch;
+use openvr as vr;
 use openxr as xr;
+use pico::{Pico4Controller, PicoNeo3Controller};
 use simple_controller::SimpleController;
 use std::ffi::CStr;
+use touch_pro::TouchPro;
 use vive_controller::ViveWands;
+use xbox_controller::XboxController;
 
 #[allow(private_interfaces)]
 pub trait InteractionProfile: Sync + Send {
@@ -25,7 +37,32 @@ pub trait InteractionProfile: Sync + Send {
     fn legacy_bindings(&self, string_to_path: &dyn StringToPath) -> LegacyBindings;
     /// Can be extracted from SteamVR rendermodel files, it is the inverse of the "grip" or "openxr_grip" value
     fn offset_grip_pose(&self, _: Hand) -> Mat4;
+    /// The transform from the palm pose (XR_EXT_palm_pose) to the "handgrip" pose.
+    fn offset_palm_pose(&self, _: Hand) -> Mat4;
+    /// The transform from the raw pose to the given pose component, also found in SteamVR
+    /// rendermodel files. Components a profile doesn't know about are treated as the raw pose.
+    fn offset_pose_component(&self, hand: Hand, component: PoseComponent) -> Mat4 {
+        match component {
+            PoseComponent::Handgrip => self.offset_grip_pose(hand).inverse(),
+            PoseComponent::Tip | PoseComponent::Base => Mat4::IDENTITY,
+        }
+    }
     fn skeletal_input_bindings(&self, string_to_path: &dyn StringToPath) -> SkeletalInputBindings;
+    /// Profiles that come from an extension can only be used if the extension is enabled.
+    fn is_supported(&self, _: &xr::InstanceExtensions) -> bool {
+        true
+    }
+}
+
+/// Pose components other than the raw pose that binding files can bind pose actions to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoseComponent {
+    /// The tip of the controller, where aiming rays should originate from.
+    Tip,
+    /// The bottom of the controller.
+    Base,
+    /// The center of the user's grip around the controller.
+    Handgrip,
 }
 
 pub enum Property<T> {
@@ -45,11 +82,48 @@ impl<T> Property<T> {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MainAxisType {
     Thumbstick,
     Trackpad,
 }
 
+/// What one of the five axes of the legacy controller state (VRControllerState_t::rAxis) reports.
+/// The Axis0-4 buttons are pressed and touched along with the input on their axis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LegacyAxis {
+    None,
+    /// The `main_xy` input, reported as a joystick or trackpad depending on the main axis type.
+    Main,
+    /// The `secondary_xy` input, for controllers with two thumbsticks or trackpads.
+    Secondary(MainAxisType),
+    Trigger,
+    Squeeze,
+    /// Thumbrest force on x, stylus force on y
+    ThumbrestStylus,
+    /// Trigger curl on x, trigger slide on y
+    TriggerCurlSlide,
+}
+
+impl LegacyAxis {
+    /// Corresponds to Prop_Axis<n>Type_Int32
+    pub fn axis_type(self, main_axis: &MainAxisType) -> vr::EVRControllerAxisType {
+        use vr::EVRControllerAxisType as T;
+        match (self, main_axis) {
+            (Self::None, _) => T::None,
+            (Self::Main, MainAxisType::Thumbstick)
+            | (Self::Secondary(MainAxisType::Thumbstick), _) => T::Joystick,
+            (Self::Main, MainAxisType::Trackpad) | (Self::Secondary(MainAxisType::Trackpad), _) => {
+                T::TrackPad
+            }
+            // The grip is recognized as such when reported as a trigger
+            (Self::Trigger | Self::Squeeze | Self::ThumbrestStylus | Self::TriggerCurlSlide, _) => {
+                T::Trigger
+            }
+        }
+    }
+}
+
 pub struct ProfileProperties {
     /// Corresponds to Prop_ModelNumber_String
     /// Can be pulled from a SteamVR System Report
@@ -69,6 +143,9 @@ pub struct ProfileProperties {
     pub tracking_system_name: &'static CStr,
     /// Corresponds to Prop_ManufacturerName_String
     pub manufacturer_name: &'static CStr,
+    /// The inputs reported on each axis of the legacy controller state, matching what SteamVR
+    /// reports for the controller so that pre-IVRInput games find them where they expect.
+    pub legacy_axes: [LegacyAxis; 5],
     /// Corresponds to Prop_SupportedButtons_Uint64
     /// Can be pulled from a SteamVR System Report
     pub legacy_buttons_mask: u64,
@@ -112,6 +189,15 @@ impl Profiles {
                 (ControllerType::ViveController, &ViveWands),
                 (ControllerType::Knuckles, &Knuckles),
                 (ControllerType::OculusTouch, &Touch),
+                (ControllerType::OculusTouch, &TouchPro),
+                (ControllerType::OculusTouch, &Pico4Controller),
+                (ControllerType::OculusTouch, &PicoNeo3Controller),
+                (
+                    ControllerType::HolographicController,
+                    &HolographicController,
+                ),
+                (ControllerType::Gamepad, &XboxController),
+                (ControllerType::ViveController, &HandInteraction),
                 (ControllerType::ViveController, &SimpleController),
             ],
         };
@@ -123,6 +209,34 @@ impl Profiles {
         self.list.iter().map(|(_, p)| *p)
     }
 
+    /// The controller type all controllers are reported as, along with the profile reported for it,
+    /// if controller emulation is configured.
+    pub(super) fn emulated(
+        &self,
+    ) -> Option<(&'static ControllerType, &'static dyn InteractionProfile)> {
+        let name = Config::get().emulated_controller()?;
+        let ty = ControllerType::from_name(name);
+        let emulated = self
+            .list
+            .iter()
+            .find(|(t, _)| *t == ty)
+            .map(|(t, p)| (t, *p));
+        if emulated.is_none() {
+            crate::warn_once!("Can't emulate unknown controller type {name}");
+        }
+        emulated
+    }
+
+    /// The controller type whose bindings are native to the given profile.
+    pub(super) fn controller_type(
+        &self,
+        profile: &dyn InteractionProfile,
+    ) -> Option<&'static ControllerType> {
+        self.list
+            .iter()
+            .find_map(|(ty, p)| (p.profile_path() == profile.profile_path()).then_some(ty))
+    }
+
     pub fn profile_from_name(&self, name: &str) -> Option<&'static dyn InteractionProfile> {
         self.list
             .iter()
@@ -0,0 +1,191 @@
+use super::{
+    InteractionProfile, LegacyAxis, MainAxisType, PathTranslation, ProfileProperties, Property,
+    SkeletalInputBindings, StringToPath,
+};
+use crate::button_mask_from_ids;
+use crate::input::legacy::button_mask_from_id;
+use crate::input::legacy::LegacyBindings;
+use crate::openxr_data::Hand;
+use glam::Mat4;
+use openvr::EVRButtonId::{Axis1, Axis2, Grip, System};
+use openxr as xr;
+
+/// Bare hands, through XR_EXT_hand_interaction. Runtimes switch to this profile when the
+/// controllers are put down and hands are tracked instead, so it's treated as a controller that
+/// pinches for the trigger and makes a fist for the grip.
+pub struct HandInteraction;
+
+impl InteractionProfile for HandInteraction {
+    fn properties(&self) -> &'static ProfileProperties {
+        static DEVICE_PROPERTIES: ProfileProperties = ProfileProperties {
+            model: Property::BothHands(c"Hand Tracking"),
+            openvr_controller_type: c"<unknown>",
+            render_model_name: Property::BothHands(c"generic_controller"),
+            main_axis: MainAxisType::Thumbstick,
+            registered_device_type: Property::PerHand {
+                left: c"xrizer/hand_interactionHAND-LEFT",
+                right: c"xrizer/hand_interactionHAND-RIGHT",
+            },
+            serial_number: Property::PerHand {
+                left: c"HAND-LEFT",
+                right: c"HAND-RIGHT",
+            },
+            tracking_system_name: c"xrizer",
+            manufacturer_name: c"xrizer",
+            legacy_axes: [
+                LegacyAxis::None,
+                LegacyAxis::Trigger,
+                LegacyAxis::Squeeze,
+                LegacyAxis::None,
+                LegacyAxis::None,
+            ],
+            legacy_buttons_mask: button_mask_from_ids!(System, Grip, Axis1, Axis2),
+        };
+        &DEVICE_PROPERTIES
+    }
+    fn profile_path(&self) -> &'static str {
+        "/interaction_profiles/ext/hand_interaction_ext"
+    }
+    fn translate_map(&self) -> &'static [PathTranslation] {
+        &[
+            PathTranslation {
+                from: "trigger/pull",
+                to: "pinch_ext/value",
+                stop: true,
+            },
+            PathTranslation {
+                from: "trigger/click",
+                to: "pinch_ext/value",
+                stop: true,
+            },
+            PathTranslation {
+                from: "trigger/value",
+                to: "pinch_ext/value",
+                stop: true,
+            },
+            PathTranslation {
+                from: "grip/pull",
+                to: "grasp_ext/value",
+                stop: true,
+            },
+            PathTranslation {
+                from: "grip/click",
+                to: "grasp_ext/value",
+                stop: true,
+            },
+            PathTranslation {
+                from: "grip/force",
+                to: "grasp_ext/value",
+                stop: true,
+            },
+            PathTranslation {
+                from: "grip/value",
+                to: "grasp_ext/value",
+                stop: true,
+            },
+        ]
+    }
+
+    fn legacy_bindings(&self, stp: &dyn StringToPath) -> LegacyBindings {
+        LegacyBindings {
+            grip_pose: stp.leftright("input/grip/pose"),
+            aim_pose: stp.leftright("input/aim/pose"),
+            trigger: stp.leftright("input/pinch_ext/value"),
+            trigger_click: stp.leftright("input/pinch_ext/value"),
+            trigger_touch: vec![],
+            app_menu: vec![],
+            app_menu_touch: vec![],
+            a: vec![],
+            a_touch: vec![],
+            squeeze: stp.leftright("input/grasp_ext/value"),
+            squeeze_click: stp.leftright("input/grasp_ext/value"),
+            main_xy: vec![],
+            main_xy_click: vec![],
+            main_xy_touch: vec![],
+            secondary_xy: vec![],
+            secondary_xy_click: vec![],
+            secondary_xy_touch: vec![],
+            battery: vec![],
+            thumbrest_force: vec![],
+            stylus_force: vec![],
+            trigger_curl: vec![],
+            trigger_slide: vec![],
+        }
+    }
+
+    fn skeletal_input_bindings(&self, stp: &dyn StringToPath) -> SkeletalInputBindings {
+        SkeletalInputBindings {
+            thumb_touch: Vec::new(),
+            index_touch: stp.leftright("input/pinch_ext/value"),
+            index_curl: stp.leftright("input/pinch_ext/value"),
+            rest_curl: stp.leftright("input/grasp_ext/value"),
+        }
+    }
+
+    fn legal_paths(&self) -> Box<[String]> {
+        [
+            "input/pinch_ext/value",
+            "input/pinch_ext/ready_ext",
+            "input/aim_activate_ext/value",
+            "input/aim_activate_ext/ready_ext",
+            "input/grasp_ext/value",
+            "input/grasp_ext/ready_ext",
+            "input/grip/pose",
+            "input/aim/pose",
+            "input/pinch_ext/pose",
+            "input/poke_ext/pose",
+        ]
+        .iter()
+        .flat_map(|s| {
+            [
+                format!("/user/hand/left/{s}"),
+                format!("/user/hand/right/{s}"),
+            ]
+        })
+        .collect()
+    }
+
+    // The grip pose of a tracked hand is already where a held controller's would be.
+    fn offset_grip_pose(&self, _: Hand) -> Mat4 {
+        Mat4::IDENTITY
+    }
+
+    fn offset_palm_pose(&self, _: Hand) -> Mat4 {
+        Mat4::IDENTITY
+    }
+
+    fn is_supported(&self, exts: &xr::InstanceExtensions) -> bool {
+        exts.ext_hand_interaction.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HandInteraction, InteractionProfile};
+    use crate::input::tests::Fixture;
+
+    #[test]
+    fn fallback_bindings() {
+        let f = Fixture::new();
+        f.load_actions(c"actions_fallback.json");
+
+        let path = HandInteraction.profile_path();
+        f.verify_bindings::<bool>(
+            path,
+            c"/actions/set1/in/boolact",
+            [
+                "/user/hand/left/input/pinch_ext/value".into(),
+                "/user/hand/right/input/pinch_ext/value".into(),
+            ],
+        );
+
+        f.verify_bindings::<f32>(
+            path,
+            c"/actions/set1/in/vec1act",
+            [
+                "/user/hand/left/input/pinch_ext/value".into(),
+                "/user/hand/right/input/pinch_ext/value".into(),
+            ],
+        );
+    }
+}
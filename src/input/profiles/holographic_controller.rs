@@ -0,0 +1,306 @@
+use super::{
+    InteractionProfile, LegacyAxis, MainAxisType, PathTranslation, PoseComponent,
+    ProfileProperties, Property, SkeletalInputBindings, StringToPath,
+};
+use crate::button_mask_from_ids;
+use crate::input::legacy::button_mask_from_id;
+use crate::input::legacy::LegacyBindings;
+use crate::openxr_data::Hand;
+use glam::{EulerRot, Mat4, Quat, Vec3};
+use openvr::EVRButtonId::{ApplicationMenu, Axis0, Axis1, Axis2, Grip, System};
+
+pub struct HolographicController;
+
+impl InteractionProfile for HolographicController {
+    fn properties(&self) -> &'static ProfileProperties {
+        static DEVICE_PROPERTIES: ProfileProperties = ProfileProperties {
+            model: Property::PerHand {
+                left: c"WindowsMR: 0x045E/0x065B/0/1",
+                right: c"WindowsMR: 0x045E/0x065B/0/2",
+            },
+            openvr_controller_type: c"holographic_controller",
+            render_model_name: Property::PerHand {
+                left: c"holographic_controller_left",
+                right: c"holographic_controller_right",
+            },
+            main_axis: MainAxisType::Trackpad,
+            registered_device_type: Property::PerHand {
+                left: c"holographic/MRSC00000001",
+                right: c"holographic/MRSC00000002",
+            },
+            serial_number: Property::PerHand {
+                left: c"MRSC00000001",
+                right: c"MRSC00000002",
+            },
+            tracking_system_name: c"holographic",
+            manufacturer_name: c"WindowsMR",
+            legacy_axes: [
+                LegacyAxis::Main,
+                LegacyAxis::Trigger,
+                LegacyAxis::Secondary(MainAxisType::Thumbstick),
+                LegacyAxis::None,
+                LegacyAxis::None,
+            ],
+            legacy_buttons_mask: button_mask_from_ids!(
+                System,
+                ApplicationMenu,
+                Grip,
+                Axis0,
+                Axis1,
+                Axis2
+            ),
+        };
+        &DEVICE_PROPERTIES
+    }
+    fn profile_path(&self) -> &'static str {
+        "/interaction_profiles/microsoft/motion_controller"
+    }
+    fn translate_map(&self) -> &'static [PathTranslation] {
+        &[
+            PathTranslation {
+                from: "grip",
+                to: "squeeze",
+                stop: true,
+            },
+            PathTranslation {
+                from: "trigger/pull",
+                to: "trigger/value",
+                stop: true,
+            },
+            PathTranslation {
+                from: "trigger/click",
+                to: "trigger/value",
+                stop: true,
+            },
+            PathTranslation {
+                from: "application_menu",
+                to: "menu",
+                stop: true,
+            },
+            PathTranslation {
+                from: "joystick",
+                to: "thumbstick",
+                stop: true,
+            },
+        ]
+    }
+
+    fn legal_paths(&self) -> Box<[String]> {
+        [
+            "input/menu/click",
+            "input/squeeze/click",
+            "input/trigger/value",
+            "input/thumbstick",
+            "input/thumbstick/x",
+            "input/thumbstick/y",
+            "input/thumbstick/click",
+            "input/trackpad",
+            "input/trackpad/x",
+            "input/trackpad/y",
+            "input/trackpad/click",
+            "input/trackpad/touch",
+            "input/grip/pose",
+            "input/aim/pose",
+            "output/haptic",
+        ]
+        .iter()
+        .flat_map(|s| {
+            [
+                format!("/user/hand/left/{s}"),
+                format!("/user/hand/right/{s}"),
+            ]
+        })
+        .collect()
+    }
+
+    fn legacy_bindings(&self, stp: &dyn StringToPath) -> LegacyBindings {
+        LegacyBindings {
+            grip_pose: stp.leftright("input/grip/pose"),
+            aim_pose: stp.leftright("input/aim/pose"),
+            trigger: stp.leftright("input/trigger/value"),
+            trigger_click: stp.leftright("input/trigger/value"),
+            trigger_touch: vec![],
+            app_menu: stp.leftright("input/menu/click"),
+            app_menu_touch: vec![],
+            a: vec![],
+            a_touch: vec![],
+            squeeze: stp.leftright("input/squeeze/click"),
+            squeeze_click: stp.leftright("input/squeeze/click"),
+            main_xy: stp.leftright("input/trackpad"),
+            main_xy_click: stp.leftright("input/trackpad/click"),
+            main_xy_touch: stp.leftright("input/trackpad/touch"),
+            secondary_xy: stp.leftright("input/thumbstick"),
+            secondary_xy_click: stp.leftright("input/thumbstick/click"),
+            secondary_xy_touch: vec![],
+            battery: vec![],
+            thumbrest_force: vec![],
+            stylus_force: vec![],
+            trigger_curl: vec![],
+            trigger_slide: vec![],
+        }
+    }
+
+    fn skeletal_input_bindings(&self, stp: &dyn StringToPath) -> SkeletalInputBindings {
+        SkeletalInputBindings {
+            thumb_touch: stp
+                .leftright("input/trackpad/touch")
+                .into_iter()
+                .chain(stp.leftright("input/thumbstick/click"))
+                .collect(),
+            index_touch: stp.leftright("input/trigger/value"),
+            index_curl: stp.leftright("input/trigger/value"),
+            rest_curl: stp.leftright("input/squeeze/click"),
+        }
+    }
+
+    fn offset_grip_pose(&self, hand: Hand) -> Mat4 {
+        match hand {
+            Hand::Left => Mat4::from_rotation_translation(
+                Quat::from_euler(
+                    EulerRot::XYZ,
+                    15.0_f32.to_radians(),
+                    0.0_f32.to_radians(),
+                    0.0_f32.to_radians(),
+                ),
+                Vec3::new(0.0, -0.005, 0.09),
+            )
+            .inverse(),
+            Hand::Right => Mat4::from_rotation_translation(
+                Quat::from_euler(
+                    EulerRot::XYZ,
+                    15.0_f32.to_radians(),
+                    0.0_f32.to_radians(),
+                    0.0_f32.to_radians(),
+                ),
+                Vec3::new(0.0, -0.005, 0.09),
+            )
+            .inverse(),
+        }
+    }
+
+    fn offset_palm_pose(&self, hand: Hand) -> Mat4 {
+        match hand {
+            Hand::Left => Mat4::from_translation(Vec3::new(0.022, 0.0, 0.0)),
+            Hand::Right => Mat4::from_translation(Vec3::new(-0.022, 0.0, 0.0)),
+        }
+    }
+
+    fn offset_pose_component(&self, hand: Hand, component: PoseComponent) -> Mat4 {
+        match component {
+            PoseComponent::Tip => Mat4::from_rotation_translation(
+                Quat::from_rotation_x(-30.0_f32.to_radians()),
+                Vec3::new(0.0, -0.045, -0.01),
+            ),
+            PoseComponent::Base => Mat4::from_translation(Vec3::new(0.0, -0.02, 0.15)),
+            PoseComponent::Handgrip => self.offset_grip_pose(hand).inverse(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HolographicController, InteractionProfile};
+    use crate::input::tests::Fixture;
+    use crate::openxr_data::Hand;
+    use fakexr::UserPath::*;
+    use openvr as vr;
+    use openxr as xr;
+
+    #[test]
+    fn verify_bindings() {
+        let f = Fixture::new();
+        f.load_actions(c"actions.json");
+
+        let path = HolographicController.profile_path();
+        f.verify_bindings::<bool>(
+            path,
+            c"/actions/set1/in/boolact",
+            [
+                "/user/hand/left/input/menu/click".into(),
+                "/user/hand/right/input/menu/click".into(),
+                "/user/hand/left/input/thumbstick/click".into(),
+                "/user/hand/right/input/trackpad/click".into(),
+            ],
+        );
+
+        f.verify_bindings::<f32>(
+            path,
+            c"/actions/set1/boolact_asfloat",
+            [
+                "/user/hand/left/input/trigger/value".into(),
+                "/user/hand/right/input/trigger/value".into(),
+            ],
+        );
+
+        f.verify_bindings::<f32>(
+            path,
+            c"/actions/set1/in/vec1act",
+            [
+                "/user/hand/left/input/trigger/value".into(),
+                "/user/hand/right/input/trigger/value".into(),
+            ],
+        );
+
+        f.verify_bindings::<xr::Vector2f>(
+            path,
+            c"/actions/set1/in/vec2act",
+            [
+                "/user/hand/left/input/thumbstick".into(),
+                "/user/hand/right/input/trackpad".into(),
+            ],
+        );
+
+        f.verify_bindings::<xr::Haptic>(
+            path,
+            c"/actions/set1/in/vib",
+            [
+                "/user/hand/left/output/haptic".into(),
+                "/user/hand/right/output/haptic".into(),
+            ],
+        );
+    }
+
+    #[test]
+    fn legacy_thumbstick() {
+        let f = Fixture::new();
+        f.input.openxr.restart_session();
+        f.set_interaction_profile(&HolographicController, LeftHand);
+        f.input.frame_start_update();
+        f.input.openxr.poll_events();
+
+        let data = f.input.openxr.session_data.get();
+        let actions = &data.input_data.legacy_actions.get().unwrap().actions;
+        fakexr::set_action_state(
+            actions.secondary_xy.as_raw(),
+            fakexr::ActionState::Vector2(0.5, -0.5),
+            LeftHand,
+        );
+        fakexr::set_action_state(
+            actions.secondary_xy_click.as_raw(),
+            fakexr::ActionState::Bool(true),
+            LeftHand,
+        );
+        f.input.frame_start_update();
+
+        // The thumbstick is on Axis2, like SteamVR reports it.
+        assert_eq!(
+            f.input.get_controller_int_tracked_property(
+                Hand::Left,
+                vr::ETrackedDeviceProperty::Axis2Type_Int32
+            ),
+            Some(vr::EVRControllerAxisType::Joystick as i32)
+        );
+        let mut state = vr::VRControllerState_t::default();
+        assert!(f.input.get_legacy_controller_state(
+            Hand::Left as vr::TrackedDeviceIndex_t,
+            &mut state,
+            std::mem::size_of_val(&state) as u32,
+        ));
+        assert_eq!(state.rAxis[2].x, 0.5);
+        assert_eq!(state.rAxis[2].y, -0.5);
+        assert_ne!(
+            state.ulButtonPressed & (1 << vr::EVRButtonId::Axis2 as u64),
+            0
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use super::{
-    InteractionProfile, MainAxisType, PathTranslation, ProfileProperties, Property,
-    SkeletalInputBindings, StringToPath,
+    InteractionProfile, LegacyAxis, MainAxisType, PathTranslation, PoseComponent,
+    ProfileProperties, Property, SkeletalInputBindings, StringToPath,
 };
 use crate::button_mask_from_ids;
 use crate::input::legacy::{button_mask_from_id, LegacyBindings};
@@ -37,6 +37,13 @@ impl InteractionProfile for Knuckles {
             },
             tracking_system_name: c"lighthouse",
             manufacturer_name: c"Valve",
+            legacy_axes: [
+                LegacyAxis::Main,
+                LegacyAxis::Trigger,
+                LegacyAxis::Squeeze,
+                LegacyAxis::Secondary(MainAxisType::Trackpad),
+                LegacyAxis::None,
+            ],
             legacy_buttons_mask: button_mask_from_ids!(
                 EVRButtonId::System,
                 EVRButtonId::ApplicationMenu,
@@ -44,7 +51,8 @@ impl InteractionProfile for Knuckles {
                 EVRButtonId::A,
                 EVRButtonId::Axis0,
                 EVRButtonId::Axis1,
-                EVRButtonId::Axis2
+                EVRButtonId::Axis2,
+                EVRButtonId::Axis3
             ),
         };
         &DEVICE_PROPERTIES
@@ -122,14 +130,25 @@ impl InteractionProfile for Knuckles {
             grip_pose: stp.leftright("input/grip/pose"),
             aim_pose: stp.leftright("input/aim/pose"),
             app_menu: stp.leftright("input/b/click"),
+            app_menu_touch: stp.leftright("input/b/touch"),
             a: stp.leftright("input/a/click"),
+            a_touch: stp.leftright("input/a/touch"),
             trigger: stp.leftright("input/trigger/value"),
             trigger_click: stp.leftright("input/trigger/click"),
+            trigger_touch: stp.leftright("input/trigger/touch"),
             squeeze: stp.leftright("input/squeeze/value"),
             squeeze_click: stp.leftright("input/squeeze/value"),
             main_xy: stp.leftright("input/thumbstick"),
             main_xy_click: stp.leftright("input/thumbstick/click"),
             main_xy_touch: stp.leftright("input/thumbstick/touch"),
+            secondary_xy: stp.leftright("input/trackpad"),
+            secondary_xy_click: stp.leftright("input/trackpad/force"),
+            secondary_xy_touch: stp.leftright("input/trackpad/touch"),
+            battery: vec![],
+            thumbrest_force: vec![],
+            stylus_force: vec![],
+            trigger_curl: vec![],
+            trigger_slide: vec![],
         }
     }
 
@@ -170,6 +189,37 @@ impl InteractionProfile for Knuckles {
             .inverse(),
         }
     }
+
+    fn offset_palm_pose(&self, hand: Hand) -> Mat4 {
+        match hand {
+            Hand::Left => Mat4::from_rotation_translation(
+                Quat::from_rotation_z(-3.0_f32.to_radians()),
+                Vec3::new(0.022, 0.0, 0.004),
+            ),
+            Hand::Right => Mat4::from_rotation_translation(
+                Quat::from_rotation_z(3.0_f32.to_radians()),
+                Vec3::new(-0.022, 0.0, 0.004),
+            ),
+        }
+    }
+
+    fn offset_pose_component(&self, hand: Hand, component: PoseComponent) -> Mat4 {
+        let side = match hand {
+            Hand::Left => 1.0,
+            Hand::Right => -1.0,
+        };
+        match component {
+            PoseComponent::Tip => Mat4::from_rotation_translation(
+                Quat::from_rotation_x(5.037_f32.to_radians()),
+                Vec3::new(0.016694 * side, -0.02522, 0.024687),
+            ),
+            PoseComponent::Base => Mat4::from_rotation_translation(
+                Quat::from_rotation_x(45.0_f32.to_radians()),
+                Vec3::new(0.0, -0.0503, 0.125),
+            ),
+            PoseComponent::Handgrip => self.offset_grip_pose(hand).inverse(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1,6 +1,6 @@
 use super::{
-    InteractionProfile, MainAxisType, PathTranslation, ProfileProperties, Property,
-    SkeletalInputBindings, StringToPath,
+    InteractionProfile, LegacyAxis, MainAxisType, PathTranslation, PoseComponent,
+    ProfileProperties, Property, SkeletalInputBindings, StringToPath,
 };
 use crate::button_mask_from_ids;
 use crate::input::legacy::button_mask_from_id;
@@ -34,6 +34,13 @@ impl InteractionProfile for Touch {
             tracking_system_name: c"oculus",
             manufacturer_name: c"Oculus",
             main_axis: MainAxisType::Thumbstick,
+            legacy_axes: [
+                LegacyAxis::Main,
+                LegacyAxis::Trigger,
+                LegacyAxis::Squeeze,
+                LegacyAxis::None,
+                LegacyAxis::None,
+            ],
             legacy_buttons_mask: button_mask_from_ids!(
                 System,
                 ApplicationMenu,
@@ -95,19 +102,36 @@ impl InteractionProfile for Touch {
             aim_pose: stp.leftright("input/aim/pose"),
             trigger: stp.leftright("input/trigger/value"),
             trigger_click: stp.leftright("input/trigger/value"),
+            trigger_touch: stp.leftright("input/trigger/touch"),
             app_menu: vec![
                 stp("/user/hand/left/input/y/click"),
                 stp("/user/hand/right/input/b/click"),
             ],
+            app_menu_touch: vec![
+                stp("/user/hand/left/input/y/touch"),
+                stp("/user/hand/right/input/b/touch"),
+            ],
             a: vec![
                 stp("/user/hand/left/input/x/click"),
                 stp("/user/hand/right/input/a/click"),
             ],
+            a_touch: vec![
+                stp("/user/hand/left/input/x/touch"),
+                stp("/user/hand/right/input/a/touch"),
+            ],
             squeeze_click: stp.leftright("input/squeeze/value"),
             squeeze: stp.leftright("input/squeeze/value"),
             main_xy: stp.leftright("input/thumbstick"),
             main_xy_click: stp.leftright("input/thumbstick/click"),
             main_xy_touch: stp.leftright("input/thumbstick/touch"),
+            secondary_xy: vec![],
+            secondary_xy_click: vec![],
+            secondary_xy_touch: vec![],
+            battery: vec![],
+            thumbrest_force: vec![],
+            stylus_force: vec![],
+            trigger_curl: vec![],
+            trigger_slide: vec![],
         }
     }
 
@@ -196,6 +220,31 @@ impl InteractionProfile for Touch {
             .inverse(),
         }
     }
+
+    fn offset_palm_pose(&self, hand: Hand) -> Mat4 {
+        match hand {
+            Hand::Left => Mat4::from_translation(Vec3::new(0.02, 0.0, 0.01)),
+            Hand::Right => Mat4::from_translation(Vec3::new(-0.02, 0.0, 0.01)),
+        }
+    }
+
+    fn offset_pose_component(&self, hand: Hand, component: PoseComponent) -> Mat4 {
+        let side = match hand {
+            Hand::Left => 1.0,
+            Hand::Right => -1.0,
+        };
+        match component {
+            PoseComponent::Tip => Mat4::from_rotation_translation(
+                Quat::from_rotation_x(-4.0_f32.to_radians()),
+                Vec3::new(0.006 * side, -0.018, -0.028),
+            ),
+            PoseComponent::Base => Mat4::from_rotation_translation(
+                Quat::from_rotation_x(35.0_f32.to_radians()),
+                Vec3::new(0.007 * side, -0.045, 0.117),
+            ),
+            PoseComponent::Handgrip => self.offset_grip_pose(hand).inverse(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -262,4 +311,30 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn closest_bindings() {
+        let f = Fixture::new();
+        f.load_actions(c"actions_closest.json");
+
+        // The knuckles bindings are a closer match than the wand ones.
+        let path = Touch.profile_path();
+        f.verify_bindings::<bool>(
+            path,
+            c"/actions/set1/in/boolact",
+            [
+                "/user/hand/left/input/trigger/value".into(),
+                "/user/hand/right/input/trigger/value".into(),
+                "/user/hand/left/input/menu/click".into(),
+            ],
+        );
+        f.verify_bindings::<f32>(
+            path,
+            c"/actions/set1/in/vec1act",
+            [
+                "/user/hand/left/input/trigger/value".into(),
+                "/user/hand/right/input/trigger/value".into(),
+            ],
+        );
+    }
 }
@@ -0,0 +1,403 @@
+use super::{
+    InteractionProfile, LegacyAxis, MainAxisType, PathTranslation, PoseComponent,
+    ProfileProperties, Property, SkeletalInputBindings, StringToPath,
+};
+use crate::button_mask_from_ids;
+use crate::input::legacy::button_mask_from_id;
+use crate::input::legacy::LegacyBindings;
+use crate::openxr_data::Hand;
+use glam::{EulerRot, Mat4, Quat, Vec3};
+use openvr::EVRButtonId::{ApplicationMenu, Axis0, Axis1, Axis2, Grip, System, A};
+use openxr as xr;
+
+/// The Pico 4 and Neo 3 controllers are laid out like Touch controllers, so games' Touch bindings
+/// are used for them. The only difference between the two is that the Neo 3 has a menu button on
+/// both controllers.
+pub struct Pico4Controller;
+pub struct PicoNeo3Controller;
+
+const TRANSLATE_MAP: &[PathTranslation] = &[
+    PathTranslation {
+        from: "trigger/pull",
+        to: "trigger/value",
+        stop: true,
+    },
+    PathTranslation {
+        from: "grip/pull",
+        to: "squeeze/value",
+        stop: true,
+    },
+    PathTranslation {
+        from: "grip",
+        to: "squeeze",
+        stop: true,
+    },
+    PathTranslation {
+        from: "application_menu",
+        to: "menu",
+        stop: true,
+    },
+    PathTranslation {
+        from: "joystick",
+        to: "thumbstick",
+        stop: true,
+    },
+];
+
+fn legal_paths(right_menu: bool) -> Box<[String]> {
+    let left_only = [
+        "input/x/click",
+        "input/x/touch",
+        "input/y/click",
+        "input/y/touch",
+        "input/menu/click",
+    ]
+    .iter()
+    .map(|p| format!("/user/hand/left/{p}"));
+    let right_only = [
+        "input/a/click",
+        "input/a/touch",
+        "input/b/click",
+        "input/b/touch",
+    ]
+    .iter()
+    .chain(right_menu.then_some(&"input/menu/click"))
+    .map(|p| format!("/user/hand/right/{p}"));
+
+    let both = [
+        "input/trigger/click",
+        "input/trigger/value",
+        "input/trigger/touch",
+        "input/squeeze/click",
+        "input/squeeze/value",
+        "input/thumbstick",
+        "input/thumbstick/x",
+        "input/thumbstick/y",
+        "input/thumbstick/click",
+        "input/thumbstick/touch",
+        "input/battery/value",
+        "input/grip/pose",
+        "input/aim/pose",
+        "output/haptic",
+    ]
+    .iter()
+    .flat_map(|p| {
+        [
+            format!("/user/hand/left/{p}"),
+            format!("/user/hand/right/{p}"),
+        ]
+    });
+
+    left_only.chain(right_only).chain(both).collect()
+}
+
+fn legacy_bindings(stp: &dyn StringToPath) -> LegacyBindings {
+    LegacyBindings {
+        grip_pose: stp.leftright("input/grip/pose"),
+        aim_pose: stp.leftright("input/aim/pose"),
+        trigger: stp.leftright("input/trigger/value"),
+        trigger_click: stp.leftright("input/trigger/click"),
+        trigger_touch: stp.leftright("input/trigger/touch"),
+        app_menu: vec![
+            stp("/user/hand/left/input/y/click"),
+            stp("/user/hand/right/input/b/click"),
+        ],
+        app_menu_touch: vec![
+            stp("/user/hand/left/input/y/touch"),
+            stp("/user/hand/right/input/b/touch"),
+        ],
+        a: vec![
+            stp("/user/hand/left/input/x/click"),
+            stp("/user/hand/right/input/a/click"),
+        ],
+        a_touch: vec![
+            stp("/user/hand/left/input/x/touch"),
+            stp("/user/hand/right/input/a/touch"),
+        ],
+        squeeze_click: stp.leftright("input/squeeze/click"),
+        squeeze: stp.leftright("input/squeeze/value"),
+        main_xy: stp.leftright("input/thumbstick"),
+        main_xy_click: stp.leftright("input/thumbstick/click"),
+        main_xy_touch: stp.leftright("input/thumbstick/touch"),
+        secondary_xy: vec![],
+        secondary_xy_click: vec![],
+        secondary_xy_touch: vec![],
+        battery: stp.leftright("input/battery/value"),
+        thumbrest_force: vec![],
+        stylus_force: vec![],
+        trigger_curl: vec![],
+        trigger_slide: vec![],
+    }
+}
+
+fn skeletal_input_bindings(stp: &dyn StringToPath) -> SkeletalInputBindings {
+    SkeletalInputBindings {
+        thumb_touch: stp
+            .leftright("input/thumbstick/touch")
+            .into_iter()
+            .chain(stp.left("input/x/touch"))
+            .chain(stp.left("input/y/touch"))
+            .chain(stp.right("input/a/touch"))
+            .chain(stp.right("input/b/touch"))
+            .collect(),
+        index_touch: stp.leftright("input/trigger/touch"),
+        index_curl: stp.leftright("input/trigger/value"),
+        rest_curl: stp.leftright("input/squeeze/value"),
+    }
+}
+
+fn offset_grip_pose(hand: Hand) -> Mat4 {
+    let side = match hand {
+        Hand::Left => 1.0,
+        Hand::Right => -1.0,
+    };
+    Mat4::from_rotation_translation(
+        Quat::from_euler(
+            EulerRot::XYZ,
+            18.0_f32.to_radians(),
+            0.0_f32.to_radians(),
+            0.0_f32.to_radians(),
+        ),
+        Vec3::new(0.006 * side, -0.004, 0.095),
+    )
+    .inverse()
+}
+
+fn offset_palm_pose(hand: Hand) -> Mat4 {
+    match hand {
+        Hand::Left => Mat4::from_translation(Vec3::new(0.02, 0.0, 0.01)),
+        Hand::Right => Mat4::from_translation(Vec3::new(-0.02, 0.0, 0.01)),
+    }
+}
+
+fn offset_pose_component(hand: Hand, component: PoseComponent) -> Mat4 {
+    match component {
+        PoseComponent::Tip => Mat4::from_rotation_translation(
+            Quat::from_rotation_x(-5.0_f32.to_radians()),
+            Vec3::new(0.0, -0.02, -0.03),
+        ),
+        PoseComponent::Base => Mat4::from_rotation_translation(
+            Quat::from_rotation_x(35.0_f32.to_radians()),
+            Vec3::new(0.0, -0.045, 0.11),
+        ),
+        PoseComponent::Handgrip => offset_grip_pose(hand).inverse(),
+    }
+}
+
+const LEGACY_BUTTONS_MASK: u64 =
+    button_mask_from_ids!(System, ApplicationMenu, Grip, A, Axis0, Axis1, Axis2);
+const LEGACY_AXES: [LegacyAxis; 5] = [
+    LegacyAxis::Main,
+    LegacyAxis::Trigger,
+    LegacyAxis::Squeeze,
+    LegacyAxis::None,
+    LegacyAxis::None,
+];
+
+impl InteractionProfile for Pico4Controller {
+    fn properties(&self) -> &'static ProfileProperties {
+        static DEVICE_PROPERTIES: ProfileProperties = ProfileProperties {
+            model: Property::PerHand {
+                left: c"PICO 4 (Left Controller)",
+                right: c"PICO 4 (Right Controller)",
+            },
+            openvr_controller_type: c"pico_controller",
+            render_model_name: Property::PerHand {
+                left: c"pico_4_controller_left",
+                right: c"pico_4_controller_right",
+            },
+            registered_device_type: Property::PerHand {
+                left: c"pico/PA7L10MGH0000000_Controller_Left",
+                right: c"pico/PA7L10MGH0000000_Controller_Right",
+            },
+            serial_number: Property::PerHand {
+                left: c"PA7L10MGH0000000_Controller_Left",
+                right: c"PA7L10MGH0000000_Controller_Right",
+            },
+            tracking_system_name: c"pico",
+            manufacturer_name: c"PICO",
+            main_axis: MainAxisType::Thumbstick,
+            legacy_axes: LEGACY_AXES,
+            legacy_buttons_mask: LEGACY_BUTTONS_MASK,
+        };
+        &DEVICE_PROPERTIES
+    }
+    fn profile_path(&self) -> &'static str {
+        "/interaction_profiles/bytedance/pico4_controller"
+    }
+    fn translate_map(&self) -> &'static [PathTranslation] {
+        TRANSLATE_MAP
+    }
+    fn legal_paths(&self) -> Box<[String]> {
+        legal_paths(false)
+    }
+    fn legacy_bindings(&self, stp: &dyn StringToPath) -> LegacyBindings {
+        legacy_bindings(stp)
+    }
+    fn skeletal_input_bindings(&self, stp: &dyn StringToPath) -> SkeletalInputBindings {
+        skeletal_input_bindings(stp)
+    }
+    fn offset_grip_pose(&self, hand: Hand) -> Mat4 {
+        offset_grip_pose(hand)
+    }
+    fn offset_palm_pose(&self, hand: Hand) -> Mat4 {
+        offset_palm_pose(hand)
+    }
+    fn offset_pose_component(&self, hand: Hand, component: PoseComponent) -> Mat4 {
+        offset_pose_component(hand, component)
+    }
+    fn is_supported(&self, exts: &xr::InstanceExtensions) -> bool {
+        exts.bd_controller_interaction.is_some()
+    }
+}
+
+impl InteractionProfile for PicoNeo3Controller {
+    fn properties(&self) -> &'static ProfileProperties {
+        static DEVICE_PROPERTIES: ProfileProperties = ProfileProperties {
+            model: Property::PerHand {
+                left: c"PICO Neo3 (Left Controller)",
+                right: c"PICO Neo3 (Right Controller)",
+            },
+            openvr_controller_type: c"pico_controller",
+            render_model_name: Property::PerHand {
+                left: c"pico_neo3_controller_left",
+                right: c"pico_neo3_controller_right",
+            },
+            registered_device_type: Property::PerHand {
+                left: c"pico/PA7H10MGH0000000_Controller_Left",
+                right: c"pico/PA7H10MGH0000000_Controller_Right",
+            },
+            serial_number: Property::PerHand {
+                left: c"PA7H10MGH0000000_Controller_Left",
+                right: c"PA7H10MGH0000000_Controller_Right",
+            },
+            tracking_system_name: c"pico",
+            manufacturer_name: c"PICO",
+            main_axis: MainAxisType::Thumbstick,
+            legacy_axes: LEGACY_AXES,
+            legacy_buttons_mask: LEGACY_BUTTONS_MASK,
+        };
+        &DEVICE_PROPERTIES
+    }
+    fn profile_path(&self) -> &'static str {
+        "/interaction_profiles/bytedance/pico_neo3_controller"
+    }
+    fn translate_map(&self) -> &'static [PathTranslation] {
+        TRANSLATE_MAP
+    }
+    fn legal_paths(&self) -> Box<[String]> {
+        legal_paths(true)
+    }
+    fn legacy_bindings(&self, stp: &dyn StringToPath) -> LegacyBindings {
+        legacy_bindings(stp)
+    }
+    fn skeletal_input_bindings(&self, stp: &dyn StringToPath) -> SkeletalInputBindings {
+        skeletal_input_bindings(stp)
+    }
+    fn offset_grip_pose(&self, hand: Hand) -> Mat4 {
+        offset_grip_pose(hand)
+    }
+    fn offset_palm_pose(&self, hand: Hand) -> Mat4 {
+        offset_palm_pose(hand)
+    }
+    fn offset_pose_component(&self, hand: Hand, component: PoseComponent) -> Mat4 {
+        offset_pose_component(hand, component)
+    }
+    fn is_supported(&self, exts: &xr::InstanceExtensions) -> bool {
+        exts.bd_controller_interaction.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Pico4Controller, PicoNeo3Controller};
+    use crate::input::{tests::Fixture, InteractionProfile};
+    use crate::openxr_data::Hand;
+    use fakexr::UserPath::*;
+    use openvr as vr;
+    use openxr as xr;
+
+    #[test]
+    fn verify_bindings() {
+        let f = Fixture::new();
+        f.load_actions(c"actions.json");
+
+        for path in [
+            Pico4Controller.profile_path(),
+            PicoNeo3Controller.profile_path(),
+        ] {
+            f.verify_bindings::<bool>(
+                path,
+                c"/actions/set1/in/boolact",
+                [
+                    "/user/hand/left/input/x/click".into(),
+                    "/user/hand/left/input/y/click".into(),
+                    "/user/hand/right/input/a/click".into(),
+                    "/user/hand/right/input/b/click".into(),
+                    "/user/hand/right/input/thumbstick/click".into(),
+                    "/user/hand/right/input/thumbstick/touch".into(),
+                    "/user/hand/left/input/menu/click".into(),
+                    // Unlike Touch controllers, these have click inputs for the trigger and grip
+                    "/user/hand/left/input/squeeze/click".into(),
+                    "/user/hand/right/input/squeeze/click".into(),
+                    "/user/hand/left/input/trigger/click".into(),
+                    "/user/hand/right/input/trigger/click".into(),
+                ],
+            );
+
+            f.verify_bindings::<xr::Vector2f>(
+                path,
+                c"/actions/set1/in/vec2act",
+                [
+                    "/user/hand/left/input/thumbstick".into(),
+                    "/user/hand/right/input/thumbstick".into(),
+                ],
+            );
+        }
+    }
+
+    #[test]
+    fn battery() {
+        let f = Fixture::new();
+        f.input.openxr.restart_session();
+        f.set_interaction_profile(&Pico4Controller, LeftHand);
+        f.input.frame_start_update();
+        f.input.openxr.poll_events();
+
+        let battery = f
+            .input
+            .openxr
+            .session_data
+            .get()
+            .input_data
+            .legacy_actions
+            .get()
+            .unwrap()
+            .actions
+            .battery
+            .as_raw();
+        fakexr::set_action_state(battery, fakexr::ActionState::Float(0.8), LeftHand);
+        f.input.frame_start_update();
+
+        let property = vr::ETrackedDeviceProperty::DeviceBatteryPercentage_Float;
+        assert_eq!(
+            f.input
+                .get_controller_float_tracked_property(Hand::Left, property),
+            Some(0.8)
+        );
+        assert_eq!(
+            f.input.get_controller_bool_tracked_property(
+                Hand::Left,
+                vr::ETrackedDeviceProperty::DeviceProvidesBatteryStatus_Bool
+            ),
+            Some(true)
+        );
+        assert_eq!(
+            f.input.get_controller_bool_tracked_property(
+                Hand::Left,
+                vr::ETrackedDeviceProperty::DeviceIsCharging_Bool
+            ),
+            None
+        );
+    }
+}
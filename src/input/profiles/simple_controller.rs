@@ -1,5 +1,5 @@
 use super::{
-    InteractionProfile, MainAxisType, PathTranslation, ProfileProperties, Property,
+    InteractionProfile, LegacyAxis, MainAxisType, PathTranslation, ProfileProperties, Property,
     SkeletalInputBindings, StringToPath,
 };
 use crate::button_mask_from_ids;
@@ -29,6 +29,13 @@ impl InteractionProfile for SimpleController {
             },
             tracking_system_name: c"lighthouse",
             manufacturer_name: c"HTC",
+            legacy_axes: [
+                LegacyAxis::Main,
+                LegacyAxis::Trigger,
+                LegacyAxis::Squeeze,
+                LegacyAxis::None,
+                LegacyAxis::None,
+            ],
             legacy_buttons_mask: button_mask_from_ids!(System, ApplicationMenu, Grip, Axis0, Axis1),
         };
         &DEVICE_PROPERTIES
@@ -38,6 +45,16 @@ impl InteractionProfile for SimpleController {
     }
     fn translate_map(&self) -> &'static [PathTranslation] {
         &[
+            PathTranslation {
+                from: "trigger/pull",
+                to: "select/click",
+                stop: true,
+            },
+            PathTranslation {
+                from: "trigger/value",
+                to: "select/click",
+                stop: true,
+            },
             PathTranslation {
                 from: "trigger",
                 to: "select",
@@ -57,13 +74,24 @@ impl InteractionProfile for SimpleController {
             aim_pose: stp.leftright("input/aim/pose"),
             trigger: stp.leftright("input/select/click"),
             trigger_click: stp.leftright("input/select/click"),
+            trigger_touch: vec![],
             app_menu: stp.leftright("input/menu/click"),
+            app_menu_touch: vec![],
             a: vec![],
+            a_touch: vec![],
             squeeze: stp.leftright("input/menu/click"),
             squeeze_click: stp.leftright("input/menu/click"),
             main_xy: vec![],
             main_xy_click: vec![],
             main_xy_touch: vec![],
+            secondary_xy: vec![],
+            secondary_xy_click: vec![],
+            secondary_xy_touch: vec![],
+            battery: vec![],
+            thumbrest_force: vec![],
+            stylus_force: vec![],
+            trigger_curl: vec![],
+            trigger_slide: vec![],
         }
     }
 
@@ -97,4 +125,41 @@ impl InteractionProfile for SimpleController {
     fn offset_grip_pose(&self, _: Hand) -> Mat4 {
         Mat4::IDENTITY
     }
+
+    fn offset_palm_pose(&self, _: Hand) -> Mat4 {
+        Mat4::IDENTITY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InteractionProfile, SimpleController};
+    use crate::input::tests::Fixture;
+
+    #[test]
+    fn fallback_bindings() {
+        let f = Fixture::new();
+        f.load_actions(c"actions_fallback.json");
+
+        let path = SimpleController.profile_path();
+        f.verify_bindings::<bool>(
+            path,
+            c"/actions/set1/in/boolact",
+            [
+                "/user/hand/left/input/select/click".into(),
+                "/user/hand/right/input/select/click".into(),
+                "/user/hand/left/input/menu/click".into(),
+                "/user/hand/right/input/menu/click".into(),
+            ],
+        );
+
+        f.verify_bindings::<f32>(
+            path,
+            c"/actions/set1/in/vec1act",
+            [
+                "/user/hand/left/input/select/click".into(),
+                "/user/hand/right/input/select/click".into(),
+            ],
+        );
+    }
 }
@@ -0,0 +1,325 @@
+use super::{
+    InteractionProfile, LegacyAxis, MainAxisType, PathTranslation, PoseComponent,
+    ProfileProperties, Property, SkeletalInputBindings, StringToPath,
+};
+use crate::button_mask_from_ids;
+use crate::input::legacy::button_mask_from_id;
+use crate::input::legacy::LegacyBindings;
+use crate::openxr_data::Hand;
+use glam::{EulerRot, Mat4, Quat, Vec3};
+use openvr::EVRButtonId::{ApplicationMenu, Axis0, Axis1, Axis2, Axis3, Axis4, Grip, System, A};
+use openxr as xr;
+
+/// The Quest Pro's controllers. These have the same layout as Touch controllers, with extra
+/// analog inputs for the thumbrest, stylus and trigger.
+pub struct TouchPro;
+
+impl InteractionProfile for TouchPro {
+    fn properties(&self) -> &'static ProfileProperties {
+        static DEVICE_PROPERTIES: ProfileProperties = ProfileProperties {
+            model: Property::PerHand {
+                left: c"Meta Quest Pro (Left Controller)",
+                right: c"Meta Quest Pro (Right Controller)",
+            },
+            openvr_controller_type: c"oculus_touch",
+            render_model_name: Property::PerHand {
+                left: c"meta_quest_pro_controller_left",
+                right: c"meta_quest_pro_controller_right",
+            },
+            registered_device_type: Property::PerHand {
+                left: c"oculus/230YC10000000_Controller_Left",
+                right: c"oculus/230YC10000000_Controller_Right",
+            },
+            serial_number: Property::PerHand {
+                left: c"230YC10000000_Controller_Left",
+                right: c"230YC10000000_Controller_Right",
+            },
+            tracking_system_name: c"oculus",
+            manufacturer_name: c"Oculus",
+            main_axis: MainAxisType::Thumbstick,
+            legacy_axes: [
+                LegacyAxis::Main,
+                LegacyAxis::Trigger,
+                LegacyAxis::Squeeze,
+                LegacyAxis::ThumbrestStylus,
+                LegacyAxis::TriggerCurlSlide,
+            ],
+            legacy_buttons_mask: button_mask_from_ids!(
+                System,
+                ApplicationMenu,
+                Grip,
+                A,
+                Axis0,
+                Axis1,
+                Axis2,
+                Axis3,
+                Axis4
+            ),
+        };
+        &DEVICE_PROPERTIES
+    }
+    fn profile_path(&self) -> &'static str {
+        "/interaction_profiles/facebook/touch_controller_pro"
+    }
+    fn translate_map(&self) -> &'static [PathTranslation] {
+        &[
+            PathTranslation {
+                from: "thumbrest/pull",
+                to: "thumbrest/force",
+                stop: true,
+            },
+            PathTranslation {
+                from: "stylus/pull",
+                to: "stylus_fb/force",
+                stop: true,
+            },
+            PathTranslation {
+                from: "trigger_curl/pull",
+                to: "trigger/curl_fb",
+                stop: true,
+            },
+            PathTranslation {
+                from: "trigger_slide/pull",
+                to: "trigger/slide_fb",
+                stop: true,
+            },
+            PathTranslation {
+                from: "trigger/click",
+                to: "trigger/value",
+                stop: true,
+            },
+            PathTranslation {
+                from: "grip/click",
+                to: "squeeze/value",
+                stop: true,
+            },
+            PathTranslation {
+                from: "grip/pull",
+                to: "squeeze/value",
+                stop: true,
+            },
+            PathTranslation {
+                from: "trigger/pull",
+                to: "trigger/value",
+                stop: true,
+            },
+            PathTranslation {
+                from: "trigger/click",
+                to: "trigger/value",
+                stop: true,
+            },
+            PathTranslation {
+                from: "application_menu",
+                to: "menu",
+                stop: true,
+            },
+            PathTranslation {
+                from: "joystick",
+                to: "thumbstick",
+                stop: true,
+            },
+        ]
+    }
+
+    fn legacy_bindings(&self, stp: &dyn StringToPath) -> LegacyBindings {
+        LegacyBindings {
+            grip_pose: stp.leftright("input/grip/pose"),
+            aim_pose: stp.leftright("input/aim/pose"),
+            trigger: stp.leftright("input/trigger/value"),
+            trigger_click: stp.leftright("input/trigger/value"),
+            trigger_touch: stp.leftright("input/trigger/touch"),
+            app_menu: vec![
+                stp("/user/hand/left/input/y/click"),
+                stp("/user/hand/right/input/b/click"),
+            ],
+            app_menu_touch: vec![
+                stp("/user/hand/left/input/y/touch"),
+                stp("/user/hand/right/input/b/touch"),
+            ],
+            a: vec![
+                stp("/user/hand/left/input/x/click"),
+                stp("/user/hand/right/input/a/click"),
+            ],
+            a_touch: vec![
+                stp("/user/hand/left/input/x/touch"),
+                stp("/user/hand/right/input/a/touch"),
+            ],
+            squeeze_click: stp.leftright("input/squeeze/value"),
+            squeeze: stp.leftright("input/squeeze/value"),
+            main_xy: stp.leftright("input/thumbstick"),
+            main_xy_click: stp.leftright("input/thumbstick/click"),
+            main_xy_touch: stp.leftright("input/thumbstick/touch"),
+            secondary_xy: vec![],
+            secondary_xy_click: vec![],
+            secondary_xy_touch: vec![],
+            battery: vec![],
+            thumbrest_force: stp.leftright("input/thumbrest/force"),
+            stylus_force: stp.leftright("input/stylus_fb/force"),
+            trigger_curl: stp.leftright("input/trigger/curl_fb"),
+            trigger_slide: stp.leftright("input/trigger/slide_fb"),
+        }
+    }
+
+    fn skeletal_input_bindings(&self, stp: &dyn StringToPath) -> SkeletalInputBindings {
+        SkeletalInputBindings {
+            thumb_touch: stp
+                .leftright("input/thumbstick/touch")
+                .into_iter()
+                .chain(stp.left("input/x/touch"))
+                .chain(stp.left("input/y/touch"))
+                .chain(stp.right("input/a/touch"))
+                .chain(stp.right("input/b/touch"))
+                .chain(stp.leftright("input/thumbrest/touch"))
+                .collect(),
+            index_touch: stp.leftright("input/trigger/touch"),
+            index_curl: stp.leftright("input/trigger/value"),
+            rest_curl: stp.leftright("input/squeeze/value"),
+        }
+    }
+
+    fn legal_paths(&self) -> Box<[String]> {
+        let left_only = [
+            "input/x/click",
+            "input/x/touch",
+            "input/y/click",
+            "input/y/touch",
+            "input/menu/click",
+        ]
+        .iter()
+        .map(|p| format!("/user/hand/left/{p}"));
+        let right_only = [
+            "input/a/click",
+            "input/a/touch",
+            "input/b/click",
+            "input/b/touch",
+        ]
+        .iter()
+        .map(|p| format!("/user/hand/right/{p}"));
+
+        let both = [
+            "input/squeeze/value",
+            "input/trigger/value",
+            "input/trigger/touch",
+            "input/thumbstick",
+            "input/thumbstick/x",
+            "input/thumbstick/y",
+            "input/thumbstick/click",
+            "input/thumbstick/touch",
+            "input/thumbrest/touch",
+            "input/thumbrest/force",
+            "input/stylus_fb/force",
+            "input/trigger/curl_fb",
+            "input/trigger/slide_fb",
+            "input/trigger/proximity_fb",
+            "input/thumb_fb/proximity_fb",
+            "input/grip/pose",
+            "input/aim/pose",
+            "output/haptic",
+            "output/haptic_trigger_fb",
+            "output/haptic_thumb_fb",
+        ]
+        .iter()
+        .flat_map(|p| {
+            [
+                format!("/user/hand/left/{p}"),
+                format!("/user/hand/right/{p}"),
+            ]
+        });
+
+        left_only.chain(right_only).chain(both).collect()
+    }
+
+    fn offset_grip_pose(&self, hand: Hand) -> Mat4 {
+        match hand {
+            Hand::Left => Mat4::from_rotation_translation(
+                Quat::from_euler(
+                    EulerRot::XYZ,
+                    20.6_f32.to_radians(),
+                    0.0_f32.to_radians(),
+                    0.0_f32.to_radians(),
+                ),
+                Vec3::new(0.007, -0.00182941, 0.1019482),
+            )
+            .inverse(),
+            Hand::Right => Mat4::from_rotation_translation(
+                Quat::from_euler(
+                    EulerRot::XYZ,
+                    20.6_f32.to_radians(),
+                    0.0_f32.to_radians(),
+                    0.0_f32.to_radians(),
+                ),
+                Vec3::new(-0.007, -0.00182941, 0.1019482),
+            )
+            .inverse(),
+        }
+    }
+
+    fn offset_palm_pose(&self, hand: Hand) -> Mat4 {
+        match hand {
+            Hand::Left => Mat4::from_translation(Vec3::new(0.02, 0.0, 0.01)),
+            Hand::Right => Mat4::from_translation(Vec3::new(-0.02, 0.0, 0.01)),
+        }
+    }
+
+    fn offset_pose_component(&self, hand: Hand, component: PoseComponent) -> Mat4 {
+        let side = match hand {
+            Hand::Left => 1.0,
+            Hand::Right => -1.0,
+        };
+        match component {
+            PoseComponent::Tip => Mat4::from_rotation_translation(
+                Quat::from_rotation_x(-4.0_f32.to_radians()),
+                Vec3::new(0.006 * side, -0.018, -0.028),
+            ),
+            PoseComponent::Base => Mat4::from_rotation_translation(
+                Quat::from_rotation_x(35.0_f32.to_radians()),
+                Vec3::new(0.007 * side, -0.045, 0.117),
+            ),
+            PoseComponent::Handgrip => self.offset_grip_pose(hand).inverse(),
+        }
+    }
+
+    fn is_supported(&self, exts: &xr::InstanceExtensions) -> bool {
+        exts.fb_touch_controller_pro.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InteractionProfile, TouchPro};
+    use crate::input::tests::Fixture;
+
+    #[test]
+    fn verify_bindings() {
+        let f = Fixture::new();
+        f.load_actions(c"actions_touch_pro.json");
+
+        let path = TouchPro.profile_path();
+        f.verify_bindings::<f32>(
+            path,
+            c"/actions/set1/in/thumbrest",
+            [
+                "/user/hand/left/input/thumbrest/force".into(),
+                "/user/hand/right/input/thumbrest/force".into(),
+            ],
+        );
+
+        f.verify_bindings::<f32>(
+            path,
+            c"/actions/set1/in/stylus",
+            ["/user/hand/right/input/stylus_fb/force".into()],
+        );
+
+        f.verify_bindings::<f32>(
+            path,
+            c"/actions/set1/in/triggercurl",
+            ["/user/hand/left/input/trigger/curl_fb".into()],
+        );
+
+        f.verify_bindings::<f32>(
+            path,
+            c"/actions/set1/in/triggerslide",
+            ["/user/hand/left/input/trigger/slide_fb".into()],
+        );
+    }
+}
@@ -1,12 +1,12 @@
 use super::{
-    InteractionProfile, MainAxisType, PathTranslation, ProfileProperties, Property,
-    SkeletalInputBindings, StringToPath,
+    InteractionProfile, LegacyAxis, MainAxisType, PathTranslation, PoseComponent,
+    ProfileProperties, Property, SkeletalInputBindings, StringToPath,
 };
 use crate::button_mask_from_ids;
 use crate::input::legacy::button_mask_from_id;
 use crate::input::legacy::LegacyBindings;
 use crate::openxr_data::Hand;
-use glam::Mat4;
+use glam::{Mat4, Quat, Vec3};
 use openvr::EVRButtonId::{ApplicationMenu, Axis0, Axis1, Grip, System};
 
 pub struct ViveWands;
@@ -28,6 +28,13 @@ impl InteractionProfile for ViveWands {
             },
             tracking_system_name: c"lighthouse",
             manufacturer_name: c"HTC",
+            legacy_axes: [
+                LegacyAxis::Main,
+                LegacyAxis::Trigger,
+                LegacyAxis::Squeeze,
+                LegacyAxis::None,
+                LegacyAxis::None,
+            ],
             legacy_buttons_mask: button_mask_from_ids!(System, ApplicationMenu, Grip, Axis0, Axis1),
         };
         &DEVICE_PROPERTIES
@@ -91,13 +98,24 @@ impl InteractionProfile for ViveWands {
             aim_pose: stp.leftright("input/aim/pose"),
             trigger: stp.leftright("input/trigger/value"),
             trigger_click: stp.leftright("input/trigger/click"),
+            trigger_touch: vec![],
             app_menu: stp.leftright("input/menu/click"),
+            app_menu_touch: vec![],
             a: vec![],
+            a_touch: vec![],
             squeeze: stp.leftright("input/squeeze/click"),
             squeeze_click: stp.leftright("input/squeeze/click"),
             main_xy: stp.leftright("input/trackpad"),
             main_xy_click: stp.leftright("input/trackpad/click"),
             main_xy_touch: stp.leftright("input/trackpad/touch"),
+            secondary_xy: vec![],
+            secondary_xy_click: vec![],
+            secondary_xy_touch: vec![],
+            battery: vec![],
+            thumbrest_force: vec![],
+            stylus_force: vec![],
+            trigger_curl: vec![],
+            trigger_slide: vec![],
         }
     }
 
@@ -117,6 +135,24 @@ impl InteractionProfile for ViveWands {
     fn offset_grip_pose(&self, _: Hand) -> Mat4 {
         Mat4::IDENTITY
     }
+
+    fn offset_palm_pose(&self, hand: Hand) -> Mat4 {
+        match hand {
+            Hand::Left => Mat4::from_translation(Vec3::new(0.025, 0.0, 0.0)),
+            Hand::Right => Mat4::from_translation(Vec3::new(-0.025, 0.0, 0.0)),
+        }
+    }
+
+    fn offset_pose_component(&self, _: Hand, component: PoseComponent) -> Mat4 {
+        match component {
+            PoseComponent::Tip => Mat4::from_rotation_translation(
+                Quat::from_rotation_x(5.0_f32.to_radians()),
+                Vec3::new(0.0, -0.075, 0.039),
+            ),
+            PoseComponent::Base => Mat4::from_translation(Vec3::new(0.0, -0.016, 0.181)),
+            PoseComponent::Handgrip => Mat4::IDENTITY,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,208 @@
+use super::{
+    InteractionProfile, LegacyAxis, MainAxisType, PathTranslation, ProfileProperties, Property,
+    SkeletalInputBindings, StringToPath,
+};
+use crate::button_mask_from_ids;
+use crate::input::legacy::button_mask_from_id;
+use crate::input::legacy::LegacyBindings;
+use crate::openxr_data::Hand;
+use glam::Mat4;
+use openvr::EVRButtonId::{ApplicationMenu, Axis0, Axis1, Axis2, Axis3, Grip, A};
+
+/// Gamepads, under /user/gamepad rather than either hand. Games bind these with the "gamepad"
+/// controller type, and see the gamepad as its own device for legacy input.
+pub struct XboxController;
+
+impl InteractionProfile for XboxController {
+    fn properties(&self) -> &'static ProfileProperties {
+        static DEVICE_PROPERTIES: ProfileProperties = ProfileProperties {
+            model: Property::BothHands(c"Xbox Controller"),
+            openvr_controller_type: c"gamepad",
+            render_model_name: Property::BothHands(c"generic_controller"),
+            main_axis: MainAxisType::Thumbstick,
+            registered_device_type: Property::BothHands(c"xrizer/gamepad"),
+            serial_number: Property::BothHands(c"GAMEPAD"),
+            tracking_system_name: c"xrizer",
+            manufacturer_name: c"Microsoft",
+            legacy_axes: [
+                LegacyAxis::Main,
+                LegacyAxis::Trigger,
+                LegacyAxis::Squeeze,
+                LegacyAxis::Secondary(MainAxisType::Thumbstick),
+                LegacyAxis::None,
+            ],
+            legacy_buttons_mask: button_mask_from_ids!(
+                ApplicationMenu,
+                Grip,
+                A,
+                Axis0,
+                Axis1,
+                Axis2,
+                Axis3
+            ),
+        };
+        &DEVICE_PROPERTIES
+    }
+    fn profile_path(&self) -> &'static str {
+        "/interaction_profiles/microsoft/xbox_controller"
+    }
+    fn translate_map(&self) -> &'static [PathTranslation] {
+        &[
+            PathTranslation {
+                from: "joystick",
+                to: "thumbstick",
+                stop: false,
+            },
+            PathTranslation {
+                from: "trigger_left/pull",
+                to: "trigger_left/value",
+                stop: true,
+            },
+            PathTranslation {
+                from: "trigger_left/click",
+                to: "trigger_left/value",
+                stop: true,
+            },
+            PathTranslation {
+                from: "trigger_right/pull",
+                to: "trigger_right/value",
+                stop: true,
+            },
+            PathTranslation {
+                from: "trigger_right/click",
+                to: "trigger_right/value",
+                stop: true,
+            },
+            PathTranslation {
+                from: "application_menu",
+                to: "menu",
+                stop: true,
+            },
+            PathTranslation {
+                from: "start",
+                to: "menu",
+                stop: true,
+            },
+            PathTranslation {
+                from: "back",
+                to: "view",
+                stop: true,
+            },
+        ]
+    }
+
+    fn legacy_bindings(&self, stp: &dyn StringToPath) -> LegacyBindings {
+        let gamepad = |path: &str| vec![stp(&format!("/user/gamepad/{path}"))];
+        LegacyBindings {
+            grip_pose: vec![],
+            aim_pose: vec![],
+            trigger: gamepad("input/trigger_right/value"),
+            trigger_click: gamepad("input/trigger_right/value"),
+            trigger_touch: vec![],
+            app_menu: gamepad("input/menu/click"),
+            app_menu_touch: vec![],
+            a: gamepad("input/a/click"),
+            a_touch: vec![],
+            squeeze: gamepad("input/trigger_left/value"),
+            squeeze_click: gamepad("input/trigger_left/value"),
+            main_xy: gamepad("input/thumbstick_left"),
+            main_xy_click: gamepad("input/thumbstick_left/click"),
+            main_xy_touch: vec![],
+            secondary_xy: gamepad("input/thumbstick_right"),
+            secondary_xy_click: gamepad("input/thumbstick_right/click"),
+            secondary_xy_touch: vec![],
+            battery: vec![],
+            thumbrest_force: vec![],
+            stylus_force: vec![],
+            trigger_curl: vec![],
+            trigger_slide: vec![],
+        }
+    }
+
+    fn skeletal_input_bindings(&self, _: &dyn StringToPath) -> SkeletalInputBindings {
+        SkeletalInputBindings {
+            thumb_touch: Vec::new(),
+            index_touch: Vec::new(),
+            index_curl: Vec::new(),
+            rest_curl: Vec::new(),
+        }
+    }
+
+    fn legal_paths(&self) -> Box<[String]> {
+        [
+            "input/menu/click",
+            "input/view/click",
+            "input/a/click",
+            "input/b/click",
+            "input/x/click",
+            "input/y/click",
+            "input/dpad_down/click",
+            "input/dpad_right/click",
+            "input/dpad_up/click",
+            "input/dpad_left/click",
+            "input/shoulder_left/click",
+            "input/shoulder_right/click",
+            "input/thumbstick_left/click",
+            "input/thumbstick_right/click",
+            "input/trigger_left/value",
+            "input/trigger_right/value",
+            "input/thumbstick_left",
+            "input/thumbstick_left/x",
+            "input/thumbstick_left/y",
+            "input/thumbstick_right",
+            "input/thumbstick_right/x",
+            "input/thumbstick_right/y",
+            "output/haptic_left",
+            "output/haptic_right",
+            "output/haptic_left_trigger",
+            "output/haptic_right_trigger",
+        ]
+        .iter()
+        .map(|s| format!("/user/gamepad/{s}"))
+        .collect()
+    }
+
+    // The gamepad isn't tracked, so it has no poses to offset.
+    fn offset_grip_pose(&self, _: Hand) -> Mat4 {
+        Mat4::IDENTITY
+    }
+
+    fn offset_palm_pose(&self, _: Hand) -> Mat4 {
+        Mat4::IDENTITY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InteractionProfile, XboxController};
+    use crate::input::tests::Fixture;
+    use openxr as xr;
+
+    #[test]
+    fn verify_bindings() {
+        let f = Fixture::new();
+        f.load_actions(c"actions_gamepad.json");
+
+        let path = XboxController.profile_path();
+        f.verify_bindings::<bool>(
+            path,
+            c"/actions/set1/in/boolact",
+            [
+                "/user/gamepad/input/a/click".into(),
+                "/user/gamepad/input/trigger_right/value".into(),
+            ],
+        );
+
+        f.verify_bindings::<f32>(
+            path,
+            c"/actions/set1/in/vec1act",
+            ["/user/gamepad/input/trigger_left/value".into()],
+        );
+
+        f.verify_bindings::<xr::Vector2f>(
+            path,
+            c"/actions/set1/in/vec2act",
+            ["/user/gamepad/input/thumbstick_left".into()],
+        );
+    }
+}
@@ -2,24 +2,90 @@
 mod gen;
 
 use super::Input;
+use crate::config::Config;
 use crate::openxr_data::{self, Hand, OpenXrData, SessionData};
 use glam::{Affine3A, Quat, Vec3};
 use log::debug;
 use openvr as vr;
 use openxr::{self as xr};
 use paste::paste;
+use serde::Deserialize;
 use std::cell::RefCell;
-use std::f32::consts::{FRAC_PI_2, PI};
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
 use std::time::Instant;
 use HandSkeletonBone::*;
 
 impl<C: openxr_data::Compositor> Input<C> {
-    /// Returns false if hand tracking data couldn't be generated for some reason.
+    pub(super) fn get_bones(
+        &self,
+        session_data: &SessionData,
+        space: vr::EVRSkeletalTransformSpace,
+        range: vr::EVRSkeletalMotionRange,
+        hand_tracker: Option<&xr::HandTracker>,
+        hand: Hand,
+        transforms: &mut [vr::VRBoneTransform_t],
+    ) {
+        if let Some(hand_tracker) = hand_tracker {
+            self.get_bones_from_hand_tracking(
+                &self.openxr,
+                session_data,
+                space,
+                range,
+                hand_tracker,
+                hand,
+                transforms,
+            )
+        } else {
+            self.get_estimated_bones(session_data, space, range, hand, transforms);
+        }
+    }
+
+    pub(super) fn get_summary_data(
+        &self,
+        session_data: &SessionData,
+        summary_type: vr::EVRSummaryType,
+        hand_tracker: Option<&xr::HandTracker>,
+        hand: Hand,
+    ) -> vr::VRSkeletalSummaryData_t {
+        let mut transforms = [vr::VRBoneTransform_t::default(); HandSkeletonBone::Count as usize];
+        self.get_bones(
+            session_data,
+            vr::EVRSkeletalTransformSpace::Model,
+            vr::EVRSkeletalMotionRange::WithoutController,
+            hand_tracker,
+            hand,
+            &mut transforms,
+        );
+        let bones: Vec<_> = transforms
+            .iter()
+            .copied()
+            .map(bone_transform_to_glam)
+            .collect();
+        let mut summary = summarize_bones(hand, &bones);
+
+        // Without hand tracking, the curls the skeleton is animated with come straight from
+        // the controller inputs, so just report those without the smoothing applied.
+        if summary_type == vr::EVRSummaryType::FromDevice && hand_tracker.is_none() {
+            let state = self.get_target_finger_state(session_data, hand);
+            summary.flFingerCurl = [
+                state.thumb,
+                state.index,
+                state.middle,
+                state.ring,
+                state.pinky,
+            ];
+        }
+
+        summary
+    }
+
+    /// Falls back to estimated bones if hand tracking data couldn't be generated for some reason.
     pub(super) fn get_bones_from_hand_tracking(
         &self,
         xr_data: &OpenXrData<C>,
         session_data: &SessionData,
         space: vr::EVRSkeletalTransformSpace,
+        range: vr::EVRSkeletalMotionRange,
         hand_tracker: &xr::HandTracker,
         hand: Hand,
         transforms: &mut [vr::VRBoneTransform_t],
@@ -33,15 +99,27 @@ impl<C: openxr_data::Compositor> Input<C> {
             Hand::Right => &legacy.right_spaces,
         }
         .try_get_or_init_raw(xr_data, session_data, &legacy.actions) else {
-            self.get_estimated_bones(session_data, space, hand, transforms);
+            self.get_estimated_bones(session_data, space, range, hand, transforms);
             return;
         };
 
         let Some(joints) = raw.locate_hand_joints(hand_tracker, display_time).unwrap() else {
-            self.get_estimated_bones(session_data, space, hand, transforms);
+            self.get_estimated_bones(session_data, space, range, hand, transforms);
             return;
         };
 
+        // Runtimes may report the hand as active while individual joints are untracked
+        // (i.e. while holding a controller), in which case the joint poses are garbage.
+        let valid =
+            xr::SpaceLocationFlags::POSITION_VALID | xr::SpaceLocationFlags::ORIENTATION_VALID;
+        if !joints
+            .iter()
+            .all(|joint| joint.location_flags.contains(valid))
+        {
+            self.get_estimated_bones(session_data, space, range, hand, transforms);
+            return;
+        }
+
         let mut joints: Box<[_]> = joints
             .into_iter()
             .map(|joint_location| {
@@ -142,6 +220,10 @@ impl<C: openxr_data::Compositor> Input<C> {
             xr_joint_to_vr_bone(&joints[joint], &mut transforms[bone as usize])
         }
 
+        if range == vr::EVRSkeletalMotionRange::WithController {
+            clamp_to_grip_limit(hand, transforms);
+        }
+
         // Convert back to model space if needed
         // it is unnecessary to convert back and forth, but it works and it's easy
         if space == vr::EVRSkeletalTransformSpace::Model {
@@ -156,13 +238,16 @@ impl<C: openxr_data::Compositor> Input<C> {
             }
         }
 
-        *self.skeletal_tracking_level.write().unwrap() = vr::EVRSkeletalTrackingLevel::Full;
+        *self.skeletal_tracking_level[hand as usize - 1]
+            .write()
+            .unwrap() = vr::EVRSkeletalTrackingLevel::Full;
     }
 
     pub(super) fn get_estimated_bones(
         &self,
         session_data: &SessionData,
         space: vr::EVRSkeletalTransformSpace,
+        range: vr::EVRSkeletalMotionRange,
         hand: Hand,
         transforms: &mut [vr::VRBoneTransform_t],
     ) {
@@ -171,6 +256,12 @@ impl<C: openxr_data::Compositor> Input<C> {
             Hand::Left => (&gen::left_hand::OPENHAND, &gen::left_hand::FIST),
             Hand::Right => (&gen::right_hand::OPENHAND, &gen::right_hand::FIST),
         };
+        // With a controller in hand, the fingers close around the controller instead of
+        // making a full fist.
+        let fist: &[vr::VRBoneTransform_t] = match range {
+            vr::EVRSkeletalMotionRange::WithController => grip_limit(hand),
+            vr::EVRSkeletalMotionRange::WithoutController => fist,
+        };
 
         const fn constrain<'a, F, G>(f: F) -> F
         where
@@ -191,23 +282,21 @@ impl<C: openxr_data::Compositor> Input<C> {
             }
         });
 
-        let bone_it = (0..HandSkeletonBone::Count as usize).map(|idx| {
-            let bone = unsafe { std::mem::transmute::<usize, HandSkeletonBone>(idx) };
+        let bone_it = HandSkeletonBone::ALL.into_iter().map(|bone| {
             let curl_state = finger_state.get_bone_state(bone);
 
             let map_fn = bone_transform_map(open, curl_state);
-            map_fn(idx)
+            map_fn(bone as usize)
         });
 
         finalize_transforms(bone_it, space, transforms);
-        *self.skeletal_tracking_level.write().unwrap() = vr::EVRSkeletalTrackingLevel::Estimated;
+        *self.skeletal_tracking_level[hand as usize - 1]
+            .write()
+            .unwrap() = vr::EVRSkeletalTrackingLevel::Estimated;
     }
 
-    fn get_finger_state(&self, session_data: &SessionData, hand: Hand) -> FingerState {
-        // Determines the speed at which fingers follow the input states
-        // This value seems to feel right for both analog inputs and binary ones (like vive wands)
-        const FINGER_SMOOTHING_SPEED: f32 = 24.0;
-
+    /// The finger state the estimated skeleton is moving towards, based on the controller inputs.
+    fn get_target_finger_state(&self, session_data: &SessionData, hand: Hand) -> FingerState {
         let actions = &session_data
             .input_data
             .estimated_skeleton_actions
@@ -240,35 +329,43 @@ impl<C: openxr_data::Compositor> Input<C> {
             .unwrap()
             .current_state;
 
+        let config = &Config::get().finger_smoothing;
         let index = index_curl.max(
             // Curl the index finger slightly on touch input
             if index_touch || index_curl > 0.0 {
-                0.3
+                config.index_touch_curl
             } else {
                 0.0
             },
         );
 
-        let mut state = self.estimated_finger_state[hand as usize - 1]
-            .lock()
-            .unwrap();
-
-        let current_time = std::time::Instant::now();
-
-        let target = FingerState {
+        FingerState {
             index,
             // Make other fingers curl with the index slightly to mimic how real human hands work
             middle: rest_curl.max(index / 2.0),
             ring: rest_curl.max(index / 4.0),
             pinky: rest_curl.max(index / 6.0),
-            thumb: if thumb_touch { 1.0 } else { 0.0 },
-            time: current_time,
-        };
+            thumb: if thumb_touch {
+                config.thumb_touch_curl
+            } else {
+                0.0
+            },
+            time: std::time::Instant::now(),
+        }
+    }
+
+    fn get_finger_state(&self, session_data: &SessionData, hand: Hand) -> FingerState {
+        let config = &Config::get().finger_smoothing;
+        let target = self.get_target_finger_state(session_data, hand);
+
+        let mut state = self.estimated_finger_state[hand as usize - 1]
+            .lock()
+            .unwrap();
 
-        let elapsed_time = current_time.duration_since(state.time).as_secs_f32();
-        let t = (elapsed_time * FINGER_SMOOTHING_SPEED).min(1.0);
+        let elapsed_time = target.time.duration_since(state.time).as_secs_f32();
+        let t = (elapsed_time * config.speed).min(1.0);
 
-        *state = state.lerp(&target, t);
+        *state = state.lerp(&target.ignore_below(state, config.deadband), t);
 
         *state
     }
@@ -302,6 +399,239 @@ impl<C: openxr_data::Compositor> Input<C> {
     }
 }
 
+fn grip_limit(hand: Hand) -> &'static [vr::VRBoneTransform_t] {
+    match hand {
+        Hand::Left => &gen::left_hand::GRIPLIMIT,
+        Hand::Right => &gen::right_hand::GRIPLIMIT,
+    }
+}
+
+/// Keeps finger bones (in parent space) from curling further than they could while holding
+/// the controller, as described by the grip limit reference pose.
+fn clamp_to_grip_limit(hand: Hand, transforms: &mut [vr::VRBoneTransform_t]) {
+    let open = match hand {
+        Hand::Left => &gen::left_hand::OPENHAND,
+        Hand::Right => &gen::right_hand::OPENHAND,
+    };
+
+    for bone in HandSkeletonBone::ALL
+        .into_iter()
+        .filter(|bone| !matches!(bone, Root | Wrist) && (*bone as usize) < AuxThumb as usize)
+    {
+        let idx = bone as usize;
+        let (_, open_rot) = bone_transform_to_glam(open[idx]);
+        let (_, limit_rot) = bone_transform_to_glam(grip_limit(hand)[idx]);
+        let (pos, rot) = bone_transform_to_glam(transforms[idx]);
+
+        let max_angle = open_rot.angle_between(limit_rot);
+        let angle = open_rot.angle_between(rot);
+        if angle > max_angle && angle > f32::EPSILON {
+            transforms[idx] = vr::VRBoneTransform_t {
+                position: pos.into(),
+                orientation: open_rot.slerp(rot, max_angle / angle).into(),
+            };
+        }
+    }
+}
+
+/// Finger bend is measured from the metacarpal to the tip of each finger, in the order
+/// SteamVR reports curls in (thumb, index, middle, ring, pinky).
+static CURL_CHAINS: [[HandSkeletonBone; 3]; 5] = [
+    [Thumb0, Thumb1, Thumb3],
+    [IndexFinger0, IndexFinger1, IndexFinger4],
+    [MiddleFinger0, MiddleFinger1, MiddleFinger4],
+    [RingFinger0, RingFinger1, RingFinger4],
+    [PinkyFinger0, PinkyFinger1, PinkyFinger4],
+];
+
+/// Angle between adjacent fingers at which splay is reported as fully spread.
+const MAX_FINGER_SPLAY: f32 = FRAC_PI_4;
+const MAX_THUMB_SPLAY: f32 = FRAC_PI_2;
+
+fn finger_bend(bones: &[(Vec3, Quat)], [base, mid, tip]: [HandSkeletonBone; 3]) -> f32 {
+    let proximal = bones[mid as usize].0 - bones[base as usize].0;
+    let distal = bones[tip as usize].0 - bones[mid as usize].0;
+    proximal.angle_between(distal)
+}
+
+fn finger_direction(bones: &[(Vec3, Quat)], [_, mid, tip]: [HandSkeletonBone; 3]) -> Vec3 {
+    (bones[tip as usize].0 - bones[mid as usize].0).normalize_or_zero()
+}
+
+/// Computes curl and splay from model space bones, with curl normalized between the open
+/// hand and fist reference poses.
+fn summarize_bones(hand: Hand, bones: &[(Vec3, Quat)]) -> vr::VRSkeletalSummaryData_t {
+    let (open, fist) = match hand {
+        Hand::Left => (&gen::left_hand::OPENHAND, &gen::left_hand::FIST),
+        Hand::Right => (&gen::right_hand::OPENHAND, &gen::right_hand::FIST),
+    };
+    let to_model = |pose: &[vr::VRBoneTransform_t]| -> Vec<(Vec3, Quat)> {
+        parent_to_model_space_bone_data(pose.iter().copied().map(bone_transform_to_glam)).collect()
+    };
+    let (open, fist) = (to_model(open), to_model(fist));
+
+    let curl = CURL_CHAINS.map(|chain| {
+        let open = finger_bend(&open, chain);
+        let fist = finger_bend(&fist, chain);
+        let current = finger_bend(bones, chain);
+        ((current - open) / (fist - open)).clamp(0.0, 1.0)
+    });
+
+    let splay = std::array::from_fn(|idx| {
+        let angle = finger_direction(bones, CURL_CHAINS[idx])
+            .angle_between(finger_direction(bones, CURL_CHAINS[idx + 1]));
+        let max = if idx == 0 {
+            MAX_THUMB_SPLAY
+        } else {
+            MAX_FINGER_SPLAY
+        };
+        (angle / max).clamp(0.0, 1.0)
+    });
+
+    vr::VRSkeletalSummaryData_t {
+        flFingerSplay: splay,
+        flFingerCurl: curl,
+    }
+}
+
+/// Layout of the compressed skeleton format handed out by GetSkeletalBoneDataCompressed.
+/// SteamVR doesn't document its format and apps are only supposed to pass the data back to
+/// DecompressSkeletalBoneData, so we use our own:
+/// - 4 byte magic
+/// - 1 byte hand
+/// - 4 byte little endian bitmask of bones that differ from the bind pose
+/// - for each bone in the mask: position as 3 i16s, followed by the rotation as a
+///   "smallest three" quaternion (index of the dropped component + 3 i16s)
+const COMPRESSED_MAGIC: [u8; 4] = *b"XRZS";
+const COMPRESSED_HEADER_SIZE: usize = COMPRESSED_MAGIC.len() + 1 + 4;
+const COMPRESSED_BONE_SIZE: usize = 3 * 2 + 1 + 3 * 2;
+/// Positions are stored in units of 0.1mm.
+const POSITION_SCALE: f32 = 10000.0;
+const ROTATION_SCALE: f32 = i16::MAX as f32 / std::f32::consts::FRAC_1_SQRT_2;
+
+#[derive(PartialEq)]
+struct QuantizedBone {
+    position: [i16; 3],
+    largest: u8,
+    rotation: [i16; 3],
+}
+
+impl QuantizedBone {
+    fn new((pos, rot): (Vec3, Quat)) -> Self {
+        let mut rot = rot.normalize().to_array();
+        let largest = (0..4)
+            .max_by(|a, b| rot[*a].abs().total_cmp(&rot[*b].abs()))
+            .unwrap();
+        // q and -q are the same rotation, so make sure the dropped component is positive
+        // so it can be recovered.
+        if rot[largest] < 0.0 {
+            rot.iter_mut().for_each(|c| *c = -*c);
+        }
+
+        let quantize = |v: f32, scale: f32| (v * scale).round().clamp(-32767.0, 32767.0) as i16;
+        let mut rest = (0..4).filter(|i| *i != largest).map(|i| rot[i]);
+        Self {
+            position: pos.to_array().map(|c| quantize(c, POSITION_SCALE)),
+            largest: largest as u8,
+            rotation: std::array::from_fn(|_| quantize(rest.next().unwrap(), ROTATION_SCALE)),
+        }
+    }
+
+    fn to_glam(&self) -> (Vec3, Quat) {
+        let pos = Vec3::from_array(self.position.map(|c| c as f32 / POSITION_SCALE));
+        let rest = self.rotation.map(|c| c as f32 / ROTATION_SCALE);
+        let largest = (1.0 - rest.iter().map(|c| c * c).sum::<f32>())
+            .max(0.0)
+            .sqrt();
+        let mut rest = rest.into_iter();
+        let rot: [f32; 4] = std::array::from_fn(|i| {
+            if i == self.largest as usize {
+                largest
+            } else {
+                rest.next().unwrap()
+            }
+        });
+
+        (pos, Quat::from_array(rot).normalize())
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        self.position
+            .iter()
+            .for_each(|c| out.extend_from_slice(&c.to_le_bytes()));
+        out.push(self.largest);
+        self.rotation
+            .iter()
+            .for_each(|c| out.extend_from_slice(&c.to_le_bytes()));
+    }
+
+    fn read(data: &[u8]) -> Option<Self> {
+        let data: &[u8; COMPRESSED_BONE_SIZE] =
+            data.get(..COMPRESSED_BONE_SIZE)?.try_into().ok()?;
+        let i16_at = |idx: usize| i16::from_le_bytes([data[idx], data[idx + 1]]);
+        let largest = data[6];
+        (largest < 4).then(|| Self {
+            position: [i16_at(0), i16_at(2), i16_at(4)],
+            largest,
+            rotation: [i16_at(7), i16_at(9), i16_at(11)],
+        })
+    }
+}
+
+fn bind_pose(hand: Hand) -> &'static [vr::VRBoneTransform_t] {
+    match hand {
+        Hand::Left => &gen::left_hand::BINDPOSE,
+        Hand::Right => &gen::right_hand::BINDPOSE,
+    }
+}
+
+/// Compresses parent space bone transforms.
+pub(super) fn compress_bones(hand: Hand, transforms: &[vr::VRBoneTransform_t]) -> Vec<u8> {
+    let mut mask = 0u32;
+    let mut bones = Vec::with_capacity(transforms.len() * COMPRESSED_BONE_SIZE);
+    for (idx, (transform, reference)) in transforms.iter().zip(bind_pose(hand)).enumerate() {
+        let bone = QuantizedBone::new(bone_transform_to_glam(*transform));
+        if bone != QuantizedBone::new(bone_transform_to_glam(*reference)) {
+            mask |= 1 << idx;
+            bone.write(&mut bones);
+        }
+    }
+
+    let mut ret = Vec::with_capacity(COMPRESSED_HEADER_SIZE + bones.len());
+    ret.extend_from_slice(&COMPRESSED_MAGIC);
+    ret.push(hand as u8);
+    ret.extend_from_slice(&mask.to_le_bytes());
+    ret.extend_from_slice(&bones);
+    ret
+}
+
+/// Returns None if the data wasn't produced by [`compress_bones`].
+pub(super) fn decompress_bones(
+    data: &[u8],
+    space: vr::EVRSkeletalTransformSpace,
+    transforms: &mut [vr::VRBoneTransform_t],
+) -> Option<()> {
+    if data.len() < COMPRESSED_HEADER_SIZE || data[..4] != COMPRESSED_MAGIC {
+        return None;
+    }
+    let hand = Hand::try_from(data[4] as u32).ok()?;
+    let mask = u32::from_le_bytes(data[5..9].try_into().unwrap());
+
+    let mut bone_data = &data[COMPRESSED_HEADER_SIZE..];
+    let mut bones = Vec::with_capacity(HandSkeletonBone::Count as usize);
+    for (idx, reference) in bind_pose(hand).iter().enumerate() {
+        if mask & (1 << idx) != 0 {
+            bones.push(QuantizedBone::read(bone_data)?.to_glam());
+            bone_data = &bone_data[COMPRESSED_BONE_SIZE..];
+        } else {
+            bones.push(QuantizedBone::new(bone_transform_to_glam(*reference)).to_glam());
+        }
+    }
+
+    finalize_transforms(bones.into_iter(), space, transforms);
+    Some(())
+}
+
 /// trait alias
 trait PoseIterator: Iterator<Item = (Vec3, Quat)> {}
 impl<T: Iterator<Item = (Vec3, Quat)>> PoseIterator for T {}
@@ -426,6 +756,33 @@ static AUX_BONES: &[(HandSkeletonBone, xr::HandJoint)] = &[
     (AuxPinkyFinger, xr::HandJoint::LITTLE_DISTAL),
 ];
 
+/// How the estimated skeleton's fingers follow the controller inputs.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct FingerSmoothingConfig {
+    /// How quickly fingers move towards the input state, per second. Lower values smooth out
+    /// noisy inputs more, but make fingers lag behind.
+    speed: f32,
+    /// Curl changes smaller than this are ignored, so fingers don't vibrate on noisy inputs.
+    deadband: f32,
+    /// The curl the index finger snaps to while it's touching the trigger.
+    index_touch_curl: f32,
+    /// The curl the thumb snaps to while it's touching a button.
+    thumb_touch_curl: f32,
+}
+
+impl Default for FingerSmoothingConfig {
+    fn default() -> Self {
+        Self {
+            // Seems to feel right for both analog inputs and binary ones (like vive wands)
+            speed: 24.0,
+            deadband: 0.0,
+            index_touch_curl: 0.3,
+            thumb_touch_curl: 1.0,
+        }
+    }
+}
+
 #[derive(Copy, Clone)]
 pub(super) struct FingerState {
     index: f32,
@@ -448,6 +805,26 @@ impl FingerState {
         }
     }
 
+    /// Keeps the curl of each finger at its current value unless the target has moved more
+    /// than `deadband` away from it.
+    fn ignore_below(&self, current: &Self, deadband: f32) -> Self {
+        let pick = |target: f32, current: f32| {
+            if (target - current).abs() < deadband {
+                current
+            } else {
+                target
+            }
+        };
+        Self {
+            index: pick(self.index, current.index),
+            middle: pick(self.middle, current.middle),
+            ring: pick(self.ring, current.ring),
+            pinky: pick(self.pinky, current.pinky),
+            thumb: pick(self.thumb, current.thumb),
+            time: self.time,
+        }
+    }
+
     fn lerp(&self, target: &Self, amount: f32) -> Self {
         Self {
             index: self.index + (target.index - self.index) * amount,
@@ -495,8 +872,10 @@ impl FingerState {
     }
 }
 
+/// Bones of the OpenVR hand skeleton. It ends at the wrist, so there are no forearm or elbow
+/// bones to fill in - the wrist and auxiliary bones are what games can use as IK hints.
 #[repr(usize)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub(super) enum HandSkeletonBone {
     Root = 0,
     Wrist,
@@ -532,6 +911,117 @@ pub(super) enum HandSkeletonBone {
     Count,
 }
 
+impl HandSkeletonBone {
+    pub const ALL: [HandSkeletonBone; HandSkeletonBone::Count as usize] = [
+        Root,
+        Wrist,
+        Thumb0,
+        Thumb1,
+        Thumb2,
+        Thumb3,
+        IndexFinger0,
+        IndexFinger1,
+        IndexFinger2,
+        IndexFinger3,
+        IndexFinger4,
+        MiddleFinger0,
+        MiddleFinger1,
+        MiddleFinger2,
+        MiddleFinger3,
+        MiddleFinger4,
+        RingFinger0,
+        RingFinger1,
+        RingFinger2,
+        RingFinger3,
+        RingFinger4,
+        PinkyFinger0,
+        PinkyFinger1,
+        PinkyFinger2,
+        PinkyFinger3,
+        PinkyFinger4,
+        AuxThumb,
+        AuxIndexFinger,
+        AuxMiddleFinger,
+        AuxRingFinger,
+        AuxPinkyFinger,
+    ];
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    /// The parent of this bone in the SteamVR hand skeleton, or None for the root.
+    /// https://github.com/ValveSoftware/openvr/wiki/Hand-Skeleton#bone-structure
+    pub fn parent(self) -> Option<Self> {
+        match self {
+            Root | Count => None,
+            Wrist => Some(Root),
+            Thumb0 | IndexFinger0 | MiddleFinger0 | RingFinger0 | PinkyFinger0 => Some(Wrist),
+            AuxThumb | AuxIndexFinger | AuxMiddleFinger | AuxRingFinger | AuxPinkyFinger => {
+                Some(Root)
+            }
+            // Every other bone is the child of the bone right before it.
+            other => Self::from_index(other as usize - 1),
+        }
+    }
+
+    /// The name SteamVR uses for this bone, without the hand suffix.
+    fn base_name(self) -> &'static str {
+        match self {
+            Root => "root",
+            Wrist => "wrist",
+            Thumb0 => "finger_thumb_0",
+            Thumb1 => "finger_thumb_1",
+            Thumb2 => "finger_thumb_2",
+            Thumb3 => "finger_thumb",
+            IndexFinger0 => "finger_index_meta",
+            IndexFinger1 => "finger_index_0",
+            IndexFinger2 => "finger_index_1",
+            IndexFinger3 => "finger_index_2",
+            IndexFinger4 => "finger_index",
+            MiddleFinger0 => "finger_middle_meta",
+            MiddleFinger1 => "finger_middle_0",
+            MiddleFinger2 => "finger_middle_1",
+            MiddleFinger3 => "finger_middle_2",
+            MiddleFinger4 => "finger_middle",
+            RingFinger0 => "finger_ring_meta",
+            RingFinger1 => "finger_ring_0",
+            RingFinger2 => "finger_ring_1",
+            RingFinger3 => "finger_ring_2",
+            RingFinger4 => "finger_ring",
+            PinkyFinger0 => "finger_pinky_meta",
+            PinkyFinger1 => "finger_pinky_0",
+            PinkyFinger2 => "finger_pinky_1",
+            PinkyFinger3 => "finger_pinky_2",
+            PinkyFinger4 => "finger_pinky",
+            AuxThumb => "finger_thumb",
+            AuxIndexFinger => "finger_index",
+            AuxMiddleFinger => "finger_middle",
+            AuxRingFinger => "finger_ring",
+            AuxPinkyFinger => "finger_pinky",
+            Count => unreachable!(),
+        }
+    }
+
+    pub fn name(self, hand: Hand) -> String {
+        let suffix = match hand {
+            Hand::Left => "l",
+            Hand::Right => "r",
+        };
+        let base = self.base_name();
+        match self {
+            Root => "Root".to_string(),
+            Thumb3 | IndexFinger4 | MiddleFinger4 | RingFinger4 | PinkyFinger4 => {
+                format!("{base}_{suffix}_end")
+            }
+            AuxThumb | AuxIndexFinger | AuxMiddleFinger | AuxRingFinger | AuxPinkyFinger => {
+                format!("{base}_{suffix}_aux")
+            }
+            _ => format!("{base}_{suffix}"),
+        }
+    }
+}
+
 macro_rules! skeletal_input_actions {
     ($($field:ident: $ty:ty),+$(,)?) => {
         pub struct SkeletalInputActions {
@@ -551,6 +1041,22 @@ macro_rules! skeletal_input_actions {
             }
         }
     }
+
+    #[test]
+    fn finger_deadband() {
+        let mut current = FingerState::new();
+        current.index = 0.5;
+        let mut target = FingerState::new();
+        target.index = 0.52;
+        target.middle = 0.2;
+
+        let held = target.ignore_below(&current, 0.05);
+        assert_eq!(held.index, 0.5);
+        assert_eq!(held.middle, 0.2);
+
+        let moved = target.ignore_below(&current, 0.0);
+        assert_eq!(moved.index, 0.52);
+    }
 }
 
 skeletal_input_actions! {
@@ -596,3 +1102,50 @@ impl SkeletalInputActionData {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grip_limit_clamps_fist() {
+        for (hand, open, fist) in [
+            (Hand::Left, &gen::left_hand::OPENHAND, &gen::left_hand::FIST),
+            (
+                Hand::Right,
+                &gen::right_hand::OPENHAND,
+                &gen::right_hand::FIST,
+            ),
+        ] {
+            let mut transforms = *fist;
+            clamp_to_grip_limit(hand, &mut transforms);
+
+            for bone in &HandSkeletonBone::ALL[Thumb0 as usize..AuxThumb as usize] {
+                let idx = *bone as usize;
+                let (_, open_rot) = bone_transform_to_glam(open[idx]);
+                let (_, limit_rot) = bone_transform_to_glam(grip_limit(hand)[idx]);
+                let (_, rot) = bone_transform_to_glam(transforms[idx]);
+                assert!(
+                    open_rot.angle_between(rot) <= open_rot.angle_between(limit_rot) + 0.001,
+                    "{bone:?} curls past the grip limit"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn finger_deadband() {
+        let mut current = FingerState::new();
+        current.index = 0.5;
+        let mut target = FingerState::new();
+        target.index = 0.52;
+        target.middle = 0.2;
+
+        let held = target.ignore_below(&current, 0.05);
+        assert_eq!(held.index, 0.5);
+        assert_eq!(held.middle, 0.2);
+
+        let moved = target.ignore_below(&current, 0.0);
+        assert_eq!(moved.index, 0.52);
+    }
+}
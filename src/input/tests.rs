@@ -1,9 +1,9 @@
 use super::{
     profiles::{
         knuckles::Knuckles, oculus_touch::Touch, simple_controller::SimpleController,
-        vive_controller::ViveWands,
+        vive_controller::ViveWands, PoseComponent,
     },
-    ActionData, Input, InteractionProfile,
+    ActionData, BindingData, Input, InteractionProfile,
 };
 use crate::{
     graphics_backends::GraphicsBackend,
@@ -11,7 +11,7 @@ use crate::{
     vr::{self, IVRInput010_Interface},
 };
 use fakexr::UserPath::*;
-use glam::{Mat4, Quat};
+use glam::{Mat4, Quat, Vec3};
 use openxr as xr;
 use std::collections::HashSet;
 use std::f32::consts::FRAC_PI_4;
@@ -30,6 +30,7 @@ impl std::fmt::Debug for ActionData {
             ActionData::Bool(_) => f.write_str("InputAction::Bool"),
             ActionData::Vector1 { .. } => f.write_str("InputAction::Float"),
             ActionData::Vector2 { .. } => f.write_str("InputAction::Vector2"),
+            ActionData::Vector3 { .. } => f.write_str("InputAction::Vector3"),
             ActionData::Pose { .. } => f.write_str("InputAction::Pose"),
             ActionData::Skeleton { .. } => f.write_str("InputAction::Skeleton"),
             ActionData::Haptic(_) => f.write_str("InputAction::Haptic"),
@@ -86,7 +87,7 @@ macro_rules! impl_action_type {
 
 impl_action_type!(bool, "boolean", ActionData::Bool(a) => a.as_raw());
 impl_action_type!(f32, "vector1", ActionData::Vector1 { action, .. } => action.as_raw());
-impl_action_type!(xr::Vector2f, "vector2", ActionData::Vector2{ action, .. } => action.as_raw());
+impl_action_type!(xr::Vector2f, "vector2", ActionData::Vector2{ action, .. } | ActionData::Vector3 { action, .. } => action.as_raw());
 impl_action_type!(xr::Haptic, "haptic", ActionData::Haptic(a) => a.as_raw());
 //impl_action_type!(xr::Posef, "pose", ActionData::Pose { action, .. } => action.as_raw());
 
@@ -195,12 +196,16 @@ impl Fixture {
         src
     }
 
-    pub fn sync(&self, mut active: vr::VRActiveActionSet_t) {
+    pub fn sync(&self, active: vr::VRActiveActionSet_t) {
+        self.sync_sets(&mut [active]);
+    }
+
+    pub fn sync_sets(&self, active: &mut [vr::VRActiveActionSet_t]) {
         assert_eq!(
             self.input.UpdateActionState(
-                &mut active,
+                active.as_mut_ptr(),
                 std::mem::size_of::<vr::VRActiveActionSet_t>() as u32,
-                1
+                active.len() as u32
             ),
             vr::EVRInputError::None
         );
@@ -608,6 +613,45 @@ fn pose_action_no_restrict() {
     }
 }
 
+#[test]
+fn pose_velocity() {
+    let f = Fixture::new();
+
+    let set1 = f.get_action_set_handle(c"/actions/set1");
+    let posel = f.get_action_handle(c"/actions/set1/in/posel");
+
+    f.load_actions(c"actions.json");
+    f.set_interaction_profile(&SimpleController, LeftHand);
+    let session = f.input.openxr.session_data.get().session.as_raw();
+    let linear = xr::Vector3f {
+        x: 1.0,
+        y: -0.5,
+        z: 2.0,
+    };
+    let angular = xr::Vector3f {
+        x: 0.0,
+        y: 3.0,
+        z: 0.0,
+    };
+    fakexr::set_velocity(session, LeftHand, linear, angular);
+
+    f.sync(vr::VRActiveActionSet_t {
+        ulActionSet: set1,
+        ..Default::default()
+    });
+
+    let action_pose = f.get_pose(posel, 0).unwrap().pose;
+    let controller_pose = f
+        .input
+        .get_controller_pose(super::Hand::Left, Some(vr::ETrackingUniverseOrigin::Seated))
+        .unwrap();
+    for pose in [action_pose, controller_pose] {
+        assert!(pose.bPoseIsValid);
+        assert_eq!(pose.vVelocity.v, [linear.x, linear.y, linear.z]);
+        assert_eq!(pose.vAngularVelocity.v, [angular.x, angular.y, angular.z]);
+    }
+}
+
 #[test]
 fn raw_pose_switch_profile() {
     let f = Fixture::new();
@@ -699,6 +743,64 @@ fn raw_pose_switch_profile() {
     }
 }
 
+#[test]
+fn tip_pose_component() {
+    let f = Fixture::new();
+
+    let set1 = f.get_action_set_handle(c"/actions/set1");
+    let posel = f.get_action_handle(c"/actions/set1/in/posel");
+    let poser = f.get_action_handle(c"/actions/set1/in/poser");
+
+    f.load_actions(c"actions_pose_tip.json");
+    f.set_interaction_profile(&ViveWands, LeftHand);
+    f.set_interaction_profile(&ViveWands, RightHand);
+    let session = f.input.openxr.session_data.get().session.as_raw();
+    let grip = xr::Posef {
+        position: xr::Vector3f {
+            x: 0.1,
+            y: 0.2,
+            z: 0.3,
+        },
+        orientation: xr::Quaternionf::IDENTITY,
+    };
+    fakexr::set_grip(session, LeftHand, grip);
+    fakexr::set_grip(session, RightHand, grip);
+
+    f.sync(vr::VRActiveActionSet_t {
+        ulActionSet: set1,
+        ..Default::default()
+    });
+
+    let tip = Mat4::from_translation(Vec3::new(0.1, 0.2, 0.3))
+        * ViveWands.offset_pose_component(Hand::Left, PoseComponent::Tip);
+    let (_, tip_rot, tip_pos) = tip.to_scale_rotation_translation();
+
+    let actual = f.get_pose(posel, 0).unwrap();
+    assert!(actual.bActive);
+    assert!(actual.pose.bPoseIsValid);
+    let actual: xr::Posef = actual.pose.mDeviceToAbsoluteTracking.into();
+    let actual_pos = Vec3::new(actual.position.x, actual.position.y, actual.position.z);
+    let actual_rot = Quat::from_xyzw(
+        actual.orientation.x,
+        actual.orientation.y,
+        actual.orientation.z,
+        actual.orientation.w,
+    );
+    assert!(
+        actual_pos.abs_diff_eq(tip_pos, 1e-5),
+        "expected: {tip_pos}\nactual: {actual_pos}"
+    );
+    assert!(
+        actual_rot.abs_diff_eq(tip_rot, 1e-5),
+        "expected: {tip_rot}\nactual: {actual_rot}"
+    );
+
+    // Raw is unaffected by the tip offset
+    let actual = f.get_pose(poser, 0).unwrap();
+    assert!(actual.bActive);
+    compare_pose(grip, actual.pose.mDeviceToAbsoluteTracking.into());
+}
+
 #[test]
 fn cased_actions() {
     let f = Fixture::new();
@@ -747,6 +849,60 @@ fn cased_actions() {
     assert!(pose.pose.bPoseIsValid);
 }
 
+#[test]
+fn vector3_action() {
+    let f = Fixture::new();
+    let set1 = f.get_action_set_handle(c"/actions/set1");
+    let vec3act = f.get_action_handle(c"/actions/set1/in/Vec3Act");
+    f.load_actions(c"actions_vector3.json");
+
+    f.verify_bindings::<xr::Vector2f>(
+        ViveWands.profile_path(),
+        c"/actions/set1/in/Vec3Act",
+        ["/user/hand/left/input/trackpad".into()],
+    );
+
+    f.set_interaction_profile(&ViveWands, LeftHand);
+    fakexr::set_action_state(
+        f.get_action::<xr::Vector2f>(vec3act),
+        fakexr::ActionState::Vector2(0.5, -0.25),
+        LeftHand,
+    );
+    f.sync(vr::VRActiveActionSet_t {
+        ulActionSet: set1,
+        ..Default::default()
+    });
+
+    let mut s = vr::InputAnalogActionData_t::default();
+    let ret = f
+        .input
+        .GetAnalogActionData(vec3act, &mut s, std::mem::size_of_val(&s) as u32, 0);
+    assert_eq!(ret, vr::EVRInputError::None);
+    assert!(s.bActive);
+    assert_eq!(s.x, 0.5);
+    assert_eq!(s.y, -0.25);
+    assert_eq!(s.z, 0.0);
+    assert_eq!(s.deltaX, 0.5);
+    assert_eq!(s.deltaY, -0.25);
+}
+
+#[test]
+fn skeleton_action_without_skeleton_path() {
+    let f = Fixture::new();
+    let hand_right = f.get_action_handle(c"/actions/set1/in/HandRight");
+    let other = f.get_action_handle(c"/actions/set1/in/SkelOther");
+    f.load_actions(c"actions_skeleton_implicit.json");
+
+    let data = f.input.openxr.session_data.get();
+    let actions = data.input_data.get_loaded_actions().unwrap();
+    for handle in [hand_right, other] {
+        let Ok(ActionData::Skeleton { hand, .. }) = actions.try_get_action(handle) else {
+            panic!("Expected skeleton action");
+        };
+        assert_eq!(*hand, Hand::Right);
+    }
+}
+
 #[test]
 fn digital_action_initalize_on_failure() {
     let f = Fixture::new();
@@ -888,3 +1044,354 @@ fn detect_controller_after_manifest_load() {
     frame();
     assert!(f.input.openxr.left_hand.connected());
 }
+
+#[test]
+fn skeleton_bone_names_and_hierarchy() {
+    let f = Fixture::new();
+    let skel_l = f.get_action_handle(c"/actions/set1/in/skellyl");
+    let skel_r = f.get_action_handle(c"/actions/set1/in/skellyr");
+    f.load_actions(c"actions.json");
+
+    let bone_name = |handle, index| {
+        let mut name = [0 as std::ffi::c_char; 64];
+        assert_eq!(
+            f.input
+                .GetBoneName(handle, index, name.as_mut_ptr(), name.len() as u32),
+            vr::EVRInputError::None
+        );
+        unsafe { CStr::from_ptr(name.as_ptr()) }.to_owned()
+    };
+
+    assert_eq!(bone_name(skel_l, 0), c"Root");
+    assert_eq!(bone_name(skel_l, 1), c"wrist_l");
+    assert_eq!(bone_name(skel_r, 1), c"wrist_r");
+    assert_eq!(bone_name(skel_r, 6), c"finger_index_meta_r");
+    assert_eq!(bone_name(skel_r, 10), c"finger_index_r_end");
+    assert_eq!(bone_name(skel_l, 26), c"finger_thumb_l_aux");
+
+    let mut name = [0 as std::ffi::c_char; 64];
+    assert_eq!(
+        f.input
+            .GetBoneName(skel_l, 31, name.as_mut_ptr(), name.len() as u32),
+        vr::EVRInputError::InvalidBoneIndex
+    );
+    assert_eq!(
+        f.input.GetBoneName(skel_l, 1, name.as_mut_ptr(), 2),
+        vr::EVRInputError::BufferTooSmall
+    );
+
+    let mut parents = [0; 31];
+    assert_eq!(
+        f.input
+            .GetBoneHierarchy(skel_l, parents.as_mut_ptr(), parents.len() as u32),
+        vr::EVRInputError::None
+    );
+    assert_eq!(
+        parents,
+        [
+            -1, 0, 1, 2, 3, 4, 1, 6, 7, 8, 9, 1, 11, 12, 13, 14, 1, 16, 17, 18, 19, 1, 21, 22, 23,
+            24, 0, 0, 0, 0, 0
+        ]
+    );
+}
+
+#[test]
+fn skeletal_wrist_and_aux_bones_are_posed() {
+    let f = Fixture::new();
+    let set1 = f.get_action_set_handle(c"/actions/set1");
+    let skel = f.get_action_handle(c"/actions/set1/in/skellyr");
+    f.load_actions(c"actions.json");
+    f.sync(vr::VRActiveActionSet_t {
+        ulActionSet: set1,
+        ..Default::default()
+    });
+
+    // OpenVR's hand skeleton stops at the wrist, so the wrist and the auxiliary finger bones
+    // are all games have to go on for IK - none of them should be left at identity.
+    for space in [
+        vr::EVRSkeletalTransformSpace::Parent,
+        vr::EVRSkeletalTransformSpace::Model,
+    ] {
+        let mut transforms = [vr::VRBoneTransform_t::default(); 31];
+        assert_eq!(
+            f.input.GetSkeletalBoneData(
+                skel,
+                space,
+                vr::EVRSkeletalMotionRange::WithoutController,
+                transforms.as_mut_ptr(),
+                transforms.len() as u32,
+            ),
+            vr::EVRInputError::None
+        );
+        for idx in std::iter::once(1).chain(26..31) {
+            let bone = transforms[idx];
+            assert_ne!(
+                Vec3::from_slice(&bone.position.v[..3]),
+                Vec3::ZERO,
+                "bone {idx} ({space:?}) has no position"
+            );
+        }
+    }
+}
+
+#[test]
+fn skeletal_bone_data_compression_round_trip() {
+    let f = Fixture::new();
+    let set1 = f.get_action_set_handle(c"/actions/set1");
+    let skel = f.get_action_handle(c"/actions/set1/in/skellyr");
+    f.load_actions(c"actions.json");
+    f.sync(vr::VRActiveActionSet_t {
+        ulActionSet: set1,
+        ..Default::default()
+    });
+
+    let mut required = 0;
+    assert_eq!(
+        f.input.GetSkeletalBoneDataCompressed(
+            skel,
+            vr::EVRSkeletalMotionRange::WithoutController,
+            std::ptr::null_mut(),
+            0,
+            &mut required,
+        ),
+        vr::EVRInputError::BufferTooSmall
+    );
+    assert!(required > 0);
+
+    let mut compressed = vec![0u8; required as usize];
+    assert_eq!(
+        f.input.GetSkeletalBoneDataCompressed(
+            skel,
+            vr::EVRSkeletalMotionRange::WithoutController,
+            compressed.as_mut_ptr() as _,
+            compressed.len() as u32,
+            &mut required,
+        ),
+        vr::EVRInputError::None
+    );
+
+    for space in [
+        vr::EVRSkeletalTransformSpace::Parent,
+        vr::EVRSkeletalTransformSpace::Model,
+    ] {
+        let mut expected = [vr::VRBoneTransform_t::default(); 31];
+        assert_eq!(
+            f.input.GetSkeletalBoneData(
+                skel,
+                space,
+                vr::EVRSkeletalMotionRange::WithoutController,
+                expected.as_mut_ptr(),
+                expected.len() as u32,
+            ),
+            vr::EVRInputError::None
+        );
+
+        let mut actual = [vr::VRBoneTransform_t::default(); 31];
+        assert_eq!(
+            f.input.DecompressSkeletalBoneData(
+                compressed.as_ptr() as _,
+                compressed.len() as u32,
+                space,
+                actual.as_mut_ptr(),
+                actual.len() as u32,
+            ),
+            vr::EVRInputError::None
+        );
+
+        for (idx, (e, a)) in expected.iter().zip(&actual).enumerate() {
+            let to_glam = |t: &vr::VRBoneTransform_t| {
+                let rot = t.orientation;
+                (
+                    Vec3::from_slice(&t.position.v[..3]),
+                    Quat::from_xyzw(rot.x, rot.y, rot.z, rot.w),
+                )
+            };
+            let ((epos, erot), (apos, arot)) = (to_glam(e), to_glam(a));
+            assert!(
+                epos.abs_diff_eq(apos, 0.001),
+                "bone {idx} position ({space:?}): expected {epos:?}, got {apos:?}"
+            );
+            assert!(
+                erot.dot(arot).abs() > 0.9999,
+                "bone {idx} rotation ({space:?}): expected {erot:?}, got {arot:?}"
+            );
+        }
+    }
+
+    let garbage = [0u8; 16];
+    let mut transforms = [vr::VRBoneTransform_t::default(); 31];
+    assert_eq!(
+        f.input.DecompressSkeletalBoneData(
+            garbage.as_ptr() as _,
+            garbage.len() as u32,
+            vr::EVRSkeletalTransformSpace::Parent,
+            transforms.as_mut_ptr(),
+            transforms.len() as u32,
+        ),
+        vr::EVRInputError::InvalidCompressedData
+    );
+}
+
+#[test]
+fn skeletal_summary_open_hand() {
+    let f = Fixture::new();
+    let set1 = f.get_action_set_handle(c"/actions/set1");
+    let skel = f.get_action_handle(c"/actions/set1/in/skellyl");
+    f.load_actions(c"actions.json");
+    f.sync(vr::VRActiveActionSet_t {
+        ulActionSet: set1,
+        ..Default::default()
+    });
+
+    for ty in [
+        vr::EVRSummaryType::FromAnimation,
+        vr::EVRSummaryType::FromDevice,
+    ] {
+        let mut summary = vr::VRSkeletalSummaryData_t::default();
+        assert_eq!(
+            f.input.GetSkeletalSummaryData(skel, ty, &mut summary),
+            vr::EVRInputError::None
+        );
+        for curl in summary.flFingerCurl {
+            assert!(curl.abs() < 0.01, "{ty:?}: {:?}", summary.flFingerCurl);
+        }
+        for splay in summary.flFingerSplay {
+            assert!(
+                (0.0..=1.0).contains(&splay),
+                "{ty:?}: {:?}",
+                summary.flFingerSplay
+            );
+        }
+    }
+}
+
+#[test]
+fn force_feedback_binding() {
+    let f = Fixture::new();
+    f.load_actions(c"actions_force_feedback.json");
+
+    let path = Knuckles.profile_path();
+    f.verify_bindings::<xr::Haptic>(
+        path,
+        c"/actions/set1/in/resist",
+        ["/user/hand/left/output/haptic".into()],
+    );
+
+    let resist = f.get_action_handle(c"/actions/set1/in/resist");
+    let right = f.input.openxr.right_hand.subaction_path;
+    {
+        let data = f.input.openxr.session_data.get();
+        let loaded = data.input_data.get_loaded_actions().unwrap();
+        let profile = f.input.openxr.instance.string_to_path(path).unwrap();
+        let bindings = loaded.try_get_bindings(resist, profile).unwrap();
+        assert!(
+            matches!(bindings.as_slice(), [BindingData::ForceFeedback(p)] if *p == right),
+            "{}",
+            bindings.len()
+        );
+    }
+
+    // The fake runtime doesn't support force feedback, so this should just be ignored.
+    f.set_interaction_profile(&Knuckles, RightHand);
+    let right_hand = f.get_input_source_handle(c"/user/hand/right");
+    assert_eq!(
+        f.input
+            .TriggerHapticVibrationAction(resist, 0.0, 1.0, 0.0, 1.0, right_hand),
+        vr::EVRInputError::None
+    );
+}
+
+#[test]
+fn action_origin_names() {
+    let f = Fixture::new();
+    let set1 = f.get_action_set_handle(c"/actions/set1");
+    let boolact = f.get_action_handle(c"/actions/set1/in/boolact");
+    f.load_actions(c"actions.json");
+
+    let frame = || {
+        f.input.openxr.poll_events();
+        f.input.frame_start_update();
+    };
+    f.set_interaction_profile(&Knuckles, LeftHand);
+    frame();
+    frame();
+    assert!(f.input.openxr.left_hand.connected());
+
+    let mut origins = [vr::k_ulInvalidInputValueHandle; 16];
+    assert_eq!(
+        f.input
+            .GetActionOrigins(set1, boolact, origins.as_mut_ptr(), origins.len() as u32),
+        vr::EVRInputError::None
+    );
+    let left_a = f.get_input_source_handle(c"/user/hand/left/input/a/click");
+    let right_a = f.get_input_source_handle(c"/user/hand/right/input/a/click");
+    assert!(origins.contains(&left_a));
+    // The right hand isn't connected.
+    assert!(!origins.contains(&right_a));
+
+    let name = |origin, sections| {
+        let mut name = [0 as std::ffi::c_char; 128];
+        assert_eq!(
+            f.input
+                .GetOriginLocalizedName(origin, name.as_mut_ptr(), name.len() as u32, sections),
+            vr::EVRInputError::None
+        );
+        unsafe { CStr::from_ptr(name.as_ptr()) }.to_owned()
+    };
+
+    // Input sources are named by the runtime...
+    assert_eq!(name(left_a, -1), c"Left Hand Fake Controller a/click");
+    assert_eq!(name(left_a, 0x04), c"a/click");
+
+    // ...while the devices themselves are named after the hand and the controller.
+    let left = f.get_input_source_handle(c"/user/hand/left");
+    assert_eq!(name(left, 0x01), c"Left Hand");
+    assert_eq!(name(left, -1), c"Left Hand Knuckles Left");
+}
+
+#[test]
+fn show_action_origins() {
+    let f = Fixture::new();
+    let set1 = f.get_action_set_handle(c"/actions/set1");
+    let boolact = f.get_action_handle(c"/actions/set1/in/boolact");
+    f.load_actions(c"actions.json");
+
+    let frame = || {
+        f.input.openxr.poll_events();
+        f.input.frame_start_update();
+    };
+    f.set_interaction_profile(&Knuckles, LeftHand);
+    frame();
+    frame();
+
+    assert_eq!(
+        f.input.ShowActionOrigins(set1, boolact),
+        vr::EVRInputError::None
+    );
+    let lines = f.input.take_bindings_view().unwrap();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], "/actions/set1");
+    assert!(lines[1].trim_start().starts_with("boolact: "));
+    assert!(lines[1].contains("Left Hand Fake Controller a/click"));
+    // It's only shown once.
+    assert!(f.input.take_bindings_view().is_none());
+
+    let mut sets = [vr::VRActiveActionSet_t {
+        ulActionSet: set1,
+        ..Default::default()
+    }];
+    assert_eq!(
+        f.input.ShowBindingsForActionSet(
+            sets.as_mut_ptr(),
+            std::mem::size_of::<vr::VRActiveActionSet_t>() as u32,
+            1,
+            vr::k_ulInvalidInputValueHandle,
+        ),
+        vr::EVRInputError::None
+    );
+    let lines = f.input.take_bindings_view().unwrap();
+    assert_eq!(lines[0], "/actions/set1");
+    assert!(lines
+        .iter()
+        .any(|line| line.trim_start().starts_with("boolact: ")));
+}
@@ -3,13 +3,23 @@
 mod applications;
 mod chaperone;
 mod clientcore;
+mod color_gain;
 mod compositor;
+mod config;
+mod dynamic_resolution;
+mod face_tracking;
+mod foveation;
 mod graphics_backends;
 mod input;
+mod mirror;
 mod misc_unknown;
+mod notifications;
 mod openxr_data;
+mod osc;
+mod osc_trackers;
 mod overlay;
 mod overlayview;
+mod passthrough;
 mod rendermodels;
 mod screenshots;
 mod settings;
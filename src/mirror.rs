@@ -0,0 +1,193 @@
+//! A desktop window mirroring what the game submits to the headset, like SteamVR's VR View.
+//! Useful for streaming and for watching someone else play, since OpenXR runtimes generally don't
+//! have a mirror of their own.
+
+use crate::config::Config;
+use egui_miniquad::EguiMq;
+use log::{error, info};
+use miniquad::{
+    conf::{Conf, LinuxBackend, Platform},
+    EventHandler, GlContext, PassAction, RenderingBackend,
+};
+use openvr as vr;
+use openxr as xr;
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, Once, OnceLock};
+
+#[derive(Deserialize)]
+pub struct MirrorConfig {
+    /// Which eyes to show.
+    #[serde(default)]
+    pub eyes: MirrorEyes,
+}
+
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MirrorEyes {
+    Left,
+    Right,
+    /// Side by side.
+    #[default]
+    Both,
+}
+
+impl MirrorEyes {
+    pub fn eyes(self) -> &'static [vr::EVREye] {
+        match self {
+            Self::Left => &[vr::EVREye::Left],
+            Self::Right => &[vr::EVREye::Right],
+            Self::Both => &[vr::EVREye::Left, vr::EVREye::Right],
+        }
+    }
+}
+
+/// An eye of the swapchain image to copy to the mirror.
+#[derive(Copy, Clone, Debug)]
+pub struct MirrorEye {
+    pub eye: vr::EVREye,
+    pub extent: xr::Extent2Di,
+    pub flip_vertically: bool,
+    pub flip_horizontally: bool,
+}
+
+/// Tightly packed, top-down sRGB RGBA8 pixels.
+pub struct MirrorImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+pub struct Mirror {
+    eyes: MirrorEyes,
+    /// The latest image, taken by the window when it draws.
+    image: Mutex<Option<MirrorImage>>,
+    open: AtomicBool,
+}
+
+impl Mirror {
+    /// Returns the mirror if it's enabled, opening its window on first use. Returns None once the
+    /// window has been closed.
+    pub fn get() -> Option<&'static Self> {
+        static MIRROR: OnceLock<Option<Mirror>> = OnceLock::new();
+        static WINDOW: Once = Once::new();
+
+        let mirror = MIRROR
+            .get_or_init(|| {
+                Config::get().mirror_window.as_ref().map(|config| Mirror {
+                    eyes: config.eyes,
+                    image: Mutex::default(),
+                    open: true.into(),
+                })
+            })
+            .as_ref()?;
+        WINDOW.call_once(|| mirror.open_window());
+        mirror.open.load(Ordering::Relaxed).then_some(mirror)
+    }
+
+    pub fn eyes(&self) -> MirrorEyes {
+        self.eyes
+    }
+
+    /// Replaces the image shown in the window.
+    pub fn show(&self, image: MirrorImage) {
+        *self.image.lock().unwrap() = Some(image);
+    }
+
+    fn open_window(&'static self) {
+        info!("Opening mirror window ({:?})", self.eyes);
+        let (width, height) = match self.eyes {
+            MirrorEyes::Both => (1280, 640),
+            MirrorEyes::Left | MirrorEyes::Right => (720, 720),
+        };
+        let spawned = std::thread::Builder::new()
+            .name("xrizer mirror".into())
+            .spawn(move || {
+                let r = std::panic::catch_unwind(|| {
+                    miniquad::start(
+                        Conf {
+                            window_title: "xrizer mirror".to_string(),
+                            window_width: width,
+                            window_height: height,
+                            window_resizable: true,
+                            platform: Platform {
+                                linux_backend: LinuxBackend::WaylandWithX11Fallback,
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        },
+                        move || Box::new(MirrorWindow::new(self)),
+                    )
+                });
+                if let Err(e) = r {
+                    error!("Mirror window panicked: {e:?}");
+                }
+                info!("Mirror window closed");
+                self.open.store(false, Ordering::Relaxed);
+            });
+        if let Err(e) = spawned {
+            error!("Failed to spawn mirror window thread: {e}");
+            self.open.store(false, Ordering::Relaxed);
+        }
+    }
+}
+
+struct MirrorWindow {
+    egui_mq: EguiMq,
+    mq: GlContext,
+    mirror: &'static Mirror,
+    texture: Option<egui::TextureHandle>,
+}
+
+impl MirrorWindow {
+    fn new(mirror: &'static Mirror) -> Self {
+        let mut mq = GlContext::new();
+        let egui_mq = EguiMq::new(&mut mq);
+        Self {
+            egui_mq,
+            mq,
+            mirror,
+            texture: None,
+        }
+    }
+}
+
+impl EventHandler for MirrorWindow {
+    fn update(&mut self) {}
+    fn draw(&mut self) {
+        self.mq
+            .begin_default_pass(PassAction::clear_color(0.0, 0.0, 0.0, 1.0));
+        self.mq.end_render_pass();
+
+        let mut image = self.mirror.image.lock().unwrap().take();
+        let texture = &mut self.texture;
+        self.egui_mq.run(&mut self.mq, |_, ctx| {
+            if let Some(image) = image.take() {
+                let image = egui::ColorImage::from_rgba_unmultiplied(
+                    [image.width as usize, image.height as usize],
+                    &image.pixels,
+                );
+                match texture {
+                    Some(texture) => texture.set(image, egui::TextureOptions::LINEAR),
+                    None => {
+                        *texture =
+                            Some(ctx.load_texture("mirror", image, egui::TextureOptions::LINEAR));
+                    }
+                }
+            }
+
+            egui::CentralPanel::default()
+                .frame(egui::Frame::none())
+                .show(ctx, |ui| {
+                    if let Some(texture) = texture {
+                        ui.centered_and_justified(|ui| {
+                            let size = ui.available_size();
+                            ui.add(egui::Image::new(&*texture).fit_to_exact_size(size));
+                        });
+                    }
+                });
+        });
+        self.egui_mq.draw(&mut self.mq);
+        self.mq.commit_frame();
+    }
+}
@@ -0,0 +1,66 @@
+use crate::{
+    clientcore::{Injected, Injector},
+    overlay::OverlayMan,
+};
+use openvr as vr;
+use std::ffi::{c_char, CStr};
+
+#[derive(macros::InterfaceImpl)]
+#[interface = "IVRNotifications"]
+#[versions(002)]
+pub struct Notifications {
+    vtables: Vtables,
+    overlays: Injected<OverlayMan>,
+}
+
+impl Notifications {
+    pub fn new(injector: &Injector) -> Self {
+        Self {
+            vtables: Default::default(),
+            overlays: injector.inject(),
+        }
+    }
+}
+
+impl vr::IVRNotifications002_Interface for Notifications {
+    fn CreateNotification(
+        &self,
+        overlay_handle: vr::VROverlayHandle_t,
+        user_value: u64,
+        ty: vr::EVRNotificationType,
+        text: *const c_char,
+        _style: vr::EVRNotificationStyle,
+        image: *const vr::NotificationBitmap_t,
+        id: *mut vr::VRNotificationId,
+    ) -> vr::EVRNotificationError {
+        if id.is_null() {
+            return vr::EVRNotificationError::InvalidNotificationId;
+        }
+        // Without an overlay there's nothing that could have created the notification.
+        let Some(overlays) = self.overlays.get() else {
+            return vr::EVRNotificationError::InvalidOverlayHandle;
+        };
+        let text = if text.is_null() {
+            Default::default()
+        } else {
+            unsafe { CStr::from_ptr(text) }.to_string_lossy()
+        };
+        let image = unsafe { image.as_ref() };
+        match overlays.create_notification(overlay_handle, user_value, ty, &text, image) {
+            Ok(new_id) => {
+                unsafe { id.write(new_id) };
+                vr::EVRNotificationError::OK
+            }
+            Err(e) => e,
+        }
+    }
+
+    fn RemoveNotification(&self, id: vr::VRNotificationId) -> vr::EVRNotificationError {
+        self.overlays
+            .get()
+            .ok_or(vr::EVRNotificationError::InvalidNotificationId)
+            .and_then(|overlays| overlays.remove_notification(id))
+            .err()
+            .unwrap_or(vr::EVRNotificationError::OK)
+    }
+}
@@ -1,13 +1,19 @@
 use crate::{
     clientcore::{Injected, Injector},
+    config::Config,
+    face_tracking::FaceTracker,
+    foveation::Foveation,
     graphics_backends::{supported_apis_enum, GraphicsBackend, VulkanData},
     input::{InteractionProfile, Profiles},
+    passthrough::Passthrough,
 };
 use derive_more::{Deref, From, TryInto};
 use glam::f32::{Quat, Vec3};
 use log::{info, warn};
 use openvr as vr;
 use openxr as xr;
+use serde::Deserialize;
+use std::collections::VecDeque;
 use std::mem::ManuallyDrop;
 use std::sync::{
     atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering},
@@ -37,7 +43,34 @@ pub struct OpenXrData<C: Compositor> {
     pub display_time: AtomicXrTime,
     pub left_hand: HandInfo,
     pub right_hand: HandInfo,
+    /// Not a hand, but gamepads are tracked the same way.
+    pub gamepad: HandInfo,
     pub enabled_extensions: xr::ExtensionSet,
+    /// Whether the system can actually provide XR_EXT_hand_tracking joint data.
+    pub hand_tracking_supported: bool,
+    /// Whether the system can actually detect if the headset is worn through XR_EXT_user_presence.
+    user_presence_supported: bool,
+    /// Stereo, or quad views if enabled and supported. The first two views are always the eyes.
+    pub view_configuration: xr::ViewConfigurationType,
+    /// Overlay apps only draw overlays, so xrizer has to run the frame loop for them.
+    pub overlay_app: bool,
+
+    /// Whether the game is shown in the headset, rather than the runtime or another app.
+    scene_focus: AtomicBool,
+    /// Whether the game gets input, rather than a system menu shown over it.
+    input_focus: AtomicBool,
+    /// Whether the headset is being worn, from XR_EXT_user_presence if the runtime supports it,
+    /// and otherwise from the session stopping and starting again.
+    user_present: AtomicBool,
+    /// Set once the runtime wants the session to end.
+    quitting: AtomicBool,
+    /// The runtime stopped the session, which hasn't been ended yet.
+    stopping: AtomicBool,
+    /// The session was ended after being stopped, and waits for the runtime to be ready again.
+    standby: AtomicBool,
+    /// State changes are expected while the session is being ended by us.
+    ending_session: AtomicBool,
+    focus_events: Mutex<VecDeque<FocusEvent>>,
 
     /// should only be externally accessed for testing
     pub(crate) input: Injected<crate::input::Input<C>>,
@@ -53,6 +86,13 @@ impl<C: Compositor> Drop for OpenXrData<C> {
     }
 }
 
+/// A change in the game's focus, to be sent as an OpenVR event.
+#[derive(Clone, Copy)]
+pub struct FocusEvent {
+    pub ty: vr::EVREventType,
+    pub process: vr::VREvent_Process_t,
+}
+
 #[derive(Debug)]
 #[allow(dead_code)] // Results aren't used, but they're printed
 #[allow(clippy::enum_variant_names)]
@@ -86,11 +126,31 @@ impl<C: Compositor> OpenXrData<C> {
         exts.khr_vulkan_enable = supported_exts.khr_vulkan_enable;
         exts.khr_opengl_enable = supported_exts.khr_opengl_enable;
         exts.ext_hand_tracking = supported_exts.ext_hand_tracking;
+        exts.ext_palm_pose = supported_exts.ext_palm_pose;
+        exts.ext_hand_interaction = supported_exts.ext_hand_interaction;
+        exts.bd_controller_interaction = supported_exts.bd_controller_interaction;
+        exts.fb_touch_controller_pro = supported_exts.fb_touch_controller_pro;
+        exts.mndx_force_feedback_curl = supported_exts.mndx_force_feedback_curl;
+        exts.fb_face_tracking =
+            supported_exts.fb_face_tracking && Config::get().face_tracking.is_some();
         exts.khr_visibility_mask = supported_exts.khr_visibility_mask;
         exts.khr_composition_layer_cylinder = supported_exts.khr_composition_layer_cylinder;
         exts.khr_composition_layer_equirect2 = supported_exts.khr_composition_layer_equirect2;
+        exts.khr_composition_layer_cube = supported_exts.khr_composition_layer_cube;
         exts.khr_composition_layer_color_scale_bias =
             supported_exts.khr_composition_layer_color_scale_bias;
+        exts.khr_composition_layer_depth =
+            supported_exts.khr_composition_layer_depth && Config::get().submit_depth();
+        exts.varjo_quad_views = supported_exts.varjo_quad_views && Config::get().quad_views();
+        // Games can turn passthrough on at runtime, so it's enabled even if the config doesn't.
+        exts.fb_passthrough = supported_exts.fb_passthrough;
+        exts.ext_user_presence = supported_exts.ext_user_presence;
+        exts.khr_convert_timespec_time = supported_exts.khr_convert_timespec_time;
+        if Config::get().foveation.is_some() {
+            exts.fb_foveation = supported_exts.fb_foveation;
+            exts.fb_foveation_configuration = supported_exts.fb_foveation_configuration;
+            exts.fb_swapchain_update_state = supported_exts.fb_swapchain_update_state;
+        }
 
         let instance = entry
             .create_instance(
@@ -108,10 +168,37 @@ impl<C: Compositor> OpenXrData<C> {
             .system(xr::FormFactor::HEAD_MOUNTED_DISPLAY)
             .map_err(InitError::SystemCreationFailed)?;
 
+        let hand_tracking_supported = exts.ext_hand_tracking
+            && instance
+                .supports_hand_tracking(system_id)
+                .inspect_err(|e| warn!("Failed to query hand tracking support: {e}"))
+                .unwrap_or(false);
+        info!("Hand tracking supported: {hand_tracking_supported}");
+
+        let user_presence_supported = exts.ext_user_presence
+            && supports_user_presence(&instance, system_id)
+                .inspect_err(|e| warn!("Failed to query user presence support: {e}"))
+                .unwrap_or(false);
+        info!("User presence supported: {user_presence_supported}");
+
+        let view_configuration = if exts.varjo_quad_views
+            && instance
+                .enumerate_view_configurations(system_id)
+                .inspect_err(|e| warn!("Failed to enumerate view configurations: {e}"))
+                .is_ok_and(|configs| {
+                    configs.contains(&xr::ViewConfigurationType::PRIMARY_QUAD_VARJO)
+                }) {
+            info!("Using quad views");
+            xr::ViewConfigurationType::PRIMARY_QUAD_VARJO
+        } else {
+            xr::ViewConfigurationType::PRIMARY_STEREO
+        };
+
         let session_data = SessionReadGuard(RwLock::new(ManuallyDrop::new(
             SessionData::new(
                 &instance,
                 system_id,
+                view_configuration,
                 vr::ETrackingUniverseOrigin::Standing,
                 None,
             )?
@@ -120,6 +207,7 @@ impl<C: Compositor> OpenXrData<C> {
 
         let left_hand = HandInfo::new(&instance, "/user/hand/left");
         let right_hand = HandInfo::new(&instance, "/user/hand/right");
+        let gamepad = HandInfo::new(&instance, "/user/gamepad");
 
         Ok(Self {
             _entry: entry,
@@ -129,7 +217,20 @@ impl<C: Compositor> OpenXrData<C> {
             display_time: AtomicXrTime(1.into()),
             left_hand,
             right_hand,
+            gamepad,
             enabled_extensions: exts,
+            hand_tracking_supported,
+            user_presence_supported,
+            view_configuration,
+            overlay_app: false,
+            scene_focus: AtomicBool::new(true),
+            input_focus: AtomicBool::new(true),
+            user_present: AtomicBool::new(true),
+            quitting: AtomicBool::new(false),
+            stopping: AtomicBool::new(false),
+            standby: AtomicBool::new(false),
+            ending_session: AtomicBool::new(false),
+            focus_events: Mutex::default(),
             input: injector.inject(),
             compositor: injector.inject(),
         })
@@ -140,12 +241,16 @@ impl<C: Compositor> OpenXrData<C> {
         while let Some(event) = self.instance.poll_event(&mut buf).unwrap() {
             match event {
                 xr::Event::SessionStateChanged(event) => {
-                    self.session_data.0.write().unwrap().state = event.state();
+                    let old_state = std::mem::replace(
+                        &mut self.session_data.0.write().unwrap().state,
+                        event.state(),
+                    );
                     info!("OpenXR session state changed: {:?}", event.state());
+                    self.session_state_changed(old_state, event.state());
                 }
                 xr::Event::InteractionProfileChanged(_) => {
                     let session = self.session_data.get();
-                    for info in [&self.left_hand, &self.right_hand] {
+                    for info in [&self.left_hand, &self.right_hand, &self.gamepad] {
                         let profile_path = session
                             .session
                             .current_interaction_profile(info.subaction_path)
@@ -165,13 +270,17 @@ impl<C: Compositor> OpenXrData<C> {
 
                         *info.profile.lock().unwrap() = Profiles::get().profile_from_name(&profile);
 
-                        session.input_data.interaction_profile_changed();
-
                         info!(
                             "{} interaction profile changed: {}",
                             info.path_name, profile
                         );
                     }
+                    session.input_data.interaction_profile_changed();
+                }
+                xr::Event::UserPresenceChangedEXT(event) => {
+                    info!("User presence changed: {}", event.is_user_present());
+                    self.user_present
+                        .store(event.is_user_present(), Ordering::Relaxed);
                 }
                 _ => {
                     info!("unknown event");
@@ -180,6 +289,177 @@ impl<C: Compositor> OpenXrData<C> {
         }
     }
 
+    /// Translates session state changes into the focus and lifecycle changes OpenVR apps are told
+    /// about.
+    fn session_state_changed(&self, old: xr::SessionState, new: xr::SessionState) {
+        if self.ending_session.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let pid = std::process::id();
+        let mut events = self.focus_events.lock().unwrap();
+        let mut push = |ty, pid, old_pid| {
+            events.push_back(FocusEvent {
+                ty,
+                process: vr::VREvent_Process_t {
+                    pid,
+                    oldPid: old_pid,
+                    bForced: false,
+                    bConnectionLost: false,
+                },
+            })
+        };
+
+        // The runtime or another app took over the headset, or gave it back. Other processes
+        // are reported as 0, since OpenXR doesn't say who they are.
+        let visible =
+            |state| matches!(state, xr::SessionState::VISIBLE | xr::SessionState::FOCUSED);
+        if visible(old) != visible(new)
+            && self.scene_focus.swap(visible(new), Ordering::Relaxed) != visible(new)
+        {
+            info!(
+                "Scene focus {}",
+                if visible(new) { "gained" } else { "lost" }
+            );
+            let (pid, old_pid) = if visible(new) { (pid, 0) } else { (0, pid) };
+            push(vr::EVREventType::SceneApplicationChanged, pid, old_pid);
+            push(vr::EVREventType::SceneApplicationStateChanged, 0, 0);
+        }
+
+        // A system menu was opened over the game, or closed.
+        let focused = new == xr::SessionState::FOCUSED;
+        if (old == xr::SessionState::FOCUSED) != focused
+            && self.input_focus.swap(focused, Ordering::Relaxed) != focused
+        {
+            info!("Input focus {}", if focused { "gained" } else { "lost" });
+            let (ty, pid, old_pid) = if focused {
+                (vr::EVREventType::InputFocusReleased, pid, 0)
+            } else {
+                (vr::EVREventType::InputFocusCaptured, 0, pid)
+            };
+            push(ty, pid, old_pid);
+            push(vr::EVREventType::InputFocusChanged, pid, old_pid);
+        }
+
+        match new {
+            // i.e. the headset was taken off. The session has to be ended before it can be started
+            // again, which the compositor does in between frames.
+            xr::SessionState::STOPPING => {
+                info!("Session is stopping");
+                self.stopping.store(true, Ordering::Relaxed);
+                push(vr::EVREventType::EnterStandbyMode, 0, 0);
+                if !self.user_presence_supported {
+                    self.user_present.store(false, Ordering::Relaxed);
+                }
+            }
+            xr::SessionState::READY if self.standby.load(Ordering::Relaxed) => {
+                match self
+                    .session_data
+                    .get()
+                    .session
+                    .begin(self.view_configuration)
+                {
+                    Ok(_) => {
+                        info!("Session is running again, leaving standby");
+                        self.standby.store(false, Ordering::Relaxed);
+                        push(vr::EVREventType::LeaveStandbyMode, 0, 0);
+                        if !self.user_presence_supported {
+                            self.user_present.store(true, Ordering::Relaxed);
+                        }
+                    }
+                    Err(e) => warn!("Failed to begin session after standby: {e}"),
+                }
+            }
+            xr::SessionState::EXITING | xr::SessionState::LOSS_PENDING
+                if !self.quitting.swap(true, Ordering::Relaxed) =>
+            {
+                info!("Runtime is ending the session, asking the app to quit");
+                push(vr::EVREventType::SceneApplicationStateChanged, 0, 0);
+                push(vr::EVREventType::Quit, pid, 0);
+            }
+            _ => {}
+        }
+    }
+
+    /// Ends the session if the runtime is stopping it. Returns whether it was ended, in which case
+    /// no frames can be submitted until the session is running again.
+    pub fn end_stopped_session(&self) -> bool {
+        if !self.stopping.swap(false, Ordering::Relaxed) {
+            return false;
+        }
+        if let Err(e) = self.session_data.get().session.end() {
+            warn!("Failed to end stopped session: {e}");
+        }
+        self.standby.store(true, Ordering::Relaxed);
+        info!("Session ended, entering standby");
+        true
+    }
+
+    pub fn in_standby(&self) -> bool {
+        self.standby.load(Ordering::Relaxed)
+    }
+
+    pub fn pop_focus_event(&self) -> Option<FocusEvent> {
+        self.focus_events.lock().unwrap().pop_front()
+    }
+
+    pub fn has_scene_focus(&self) -> bool {
+        self.scene_focus.load(Ordering::Relaxed)
+    }
+
+    pub fn has_input_focus(&self) -> bool {
+        self.input_focus.load(Ordering::Relaxed)
+    }
+
+    pub fn user_present(&self) -> bool {
+        self.user_present.load(Ordering::Relaxed)
+    }
+
+    pub fn scene_application_state(&self) -> vr::EVRSceneApplicationState {
+        if self.quitting.load(Ordering::Relaxed) {
+            vr::EVRSceneApplicationState::Quitting
+        } else if self.has_scene_focus() {
+            vr::EVRSceneApplicationState::Running
+        } else {
+            vr::EVRSceneApplicationState::Waiting
+        }
+    }
+
+    /// Asks the runtime to end the session, which tells the app to quit once it does.
+    pub fn request_quit(&self) {
+        if let Err(e) = self.session_data.get().session.request_exit() {
+            warn!("Failed to request session exit: {e}");
+        }
+    }
+
+    /// The current time on the runtime's clock, if it supports XR_KHR_convert_timespec_time.
+    pub fn now(&self) -> Option<xr::Time> {
+        let convert = self.instance.exts().khr_convert_timespec_time?;
+        let mut timespec = std::mem::MaybeUninit::<libc::timespec>::uninit();
+        if unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, timespec.as_mut_ptr()) } != 0 {
+            warn!(
+                "Failed to get the current time: {}",
+                std::io::Error::last_os_error()
+            );
+            return None;
+        }
+
+        let mut time = xr::Time::from_nanos(0);
+        let result = unsafe {
+            (convert.convert_timespec_time_to_time)(
+                self.instance.as_raw(),
+                timespec.as_ptr().cast(),
+                &mut time,
+            )
+        };
+        if result != xr::sys::Result::SUCCESS {
+            warn!("Failed to convert the current time: {result}");
+            return None;
+        }
+
+        Some(time)
+    }
+
     pub fn restart_session(&self) {
         self.end_session();
         let mut session_guard = self.session_data.0.write().unwrap();
@@ -195,9 +475,14 @@ impl<C: Compositor> OpenXrData<C> {
         // We need to destroy the old session before creating the new one.
         let _ = unsafe { ManuallyDrop::take(&mut *session_guard) };
 
-        let (session, waiter, stream) =
-            SessionData::new(&self.instance, self.system_id, origin, Some(&info))
-                .expect("Failed to initalize new session");
+        let (session, waiter, stream) = SessionData::new(
+            &self.instance,
+            self.system_id,
+            self.view_configuration,
+            origin,
+            Some(&info),
+        )
+        .expect("Failed to initalize new session");
 
         comp.post_session_restart(&session, waiter, stream);
 
@@ -276,6 +561,12 @@ impl<C: Compositor> OpenXrData<C> {
     }
 
     fn end_session(&self) {
+        self.stopping.store(false, Ordering::Relaxed);
+        if self.standby.swap(false, Ordering::Relaxed) {
+            // Already ended, and can be destroyed as is.
+            return;
+        }
+        self.ending_session.store(true, Ordering::Relaxed);
         self.session_data.get().session.request_exit().unwrap();
         let mut state = self.session_data.get().state;
         while state != xr::SessionState::STOPPING {
@@ -287,11 +578,32 @@ impl<C: Compositor> OpenXrData<C> {
             self.poll_events();
             state = self.session_data.get().state;
         }
+        self.ending_session.store(false, Ordering::Relaxed);
     }
 }
 
 pub struct AtomicXrTime(AtomicI64);
 
+fn supports_user_presence(instance: &xr::Instance, system: xr::SystemId) -> xr::Result<bool> {
+    let mut presence = xr::sys::SystemUserPresencePropertiesEXT {
+        ty: xr::sys::SystemUserPresencePropertiesEXT::TYPE,
+        next: std::ptr::null_mut(),
+        supports_user_presence: xr::sys::FALSE,
+    };
+    let mut properties = xr::sys::SystemProperties {
+        ty: xr::sys::SystemProperties::TYPE,
+        next: &mut presence as *mut _ as *mut _,
+        ..unsafe { std::mem::zeroed() }
+    };
+    let result = unsafe {
+        (instance.fp().get_system_properties)(instance.as_raw(), system, &mut properties)
+    };
+    if result != xr::sys::Result::SUCCESS {
+        return Err(result);
+    }
+    Ok(presence.supports_user_presence.into())
+}
+
 impl AtomicXrTime {
     #[inline]
     pub fn set(&self, time: xr::Time) {
@@ -347,10 +659,14 @@ pub struct SessionData {
     stage_space_reference: xr::Space,
     stage_space_adjusted: xr::Space,
     pub current_origin: vr::ETrackingUniverseOrigin,
+    pub view_configuration: xr::ViewConfigurationType,
 
     pub input_data: crate::input::InputSessionData,
     pub comp_data: crate::compositor::CompositorSessionData,
     pub overlay_data: crate::overlay::OverlaySessionData,
+    pub face_tracker: Option<FaceTracker>,
+    pub foveation: Option<Foveation>,
+    pub passthrough: Passthrough,
     /// OpenXR requires graphics information before creating a session, but OpenVR clients don't
     /// have to provide that information until they actually submit a frame. Yet, we need some
     /// information only available behind a session (i.e., calling xrLocateViews for
@@ -376,6 +692,7 @@ impl SessionData {
     fn new(
         instance: &xr::Instance,
         system_id: xr::SystemId,
+        view_configuration: xr::ViewConfigurationType,
         current_origin: vr::ETrackingUniverseOrigin,
         create_info: Option<&SessionCreateInfo>,
     ) -> Result<(Self, xr::FrameWaiter, FrameStream), SessionCreationError> {
@@ -465,10 +782,14 @@ impl SessionData {
             xr::SessionState::READY
         );
         session
-            .begin(xr::ViewConfigurationType::PRIMARY_STEREO)
+            .begin(view_configuration)
             .map_err(SessionCreationError::BeginSessionFailed)?;
         info!("Began OpenXR session.");
 
+        let face_tracker = FaceTracker::new(&session);
+        let foveation = Foveation::new(&session);
+        let passthrough = Passthrough::new(&session, system_id, view_configuration);
+
         Ok((
             SessionData {
                 temp_vulkan,
@@ -483,7 +804,11 @@ impl SessionData {
                 input_data: Default::default(),
                 comp_data: Default::default(),
                 overlay_data: Default::default(),
+                face_tracker,
+                foveation,
+                passthrough,
                 current_origin,
+                view_configuration,
             },
             waiter,
             stream,
@@ -509,12 +834,11 @@ impl SessionData {
             .create_swapchain(info)
     }
 
-    pub fn check_format<G: GraphicsBackend>(&self, info: &mut xr::SwapchainCreateInfo<G::Api>)
+    fn swapchain_formats<G: GraphicsBackend>(&self) -> &[<G::Api as xr::Graphics>::Format]
     where
         for<'a> &'a GraphicalSession: TryInto<&'a Session<G::Api>, Error: std::fmt::Display>,
-        <G::Api as xr::Graphics>::Format: PartialEq,
     {
-        let formats = &(&self.session_graphics)
+        &(&self.session_graphics)
             .try_into()
             .unwrap_or_else(|e| {
                 panic!(
@@ -522,10 +846,30 @@ impl SessionData {
                     std::any::type_name::<G>()
                 )
             })
-            .swapchain_formats;
+            .swapchain_formats
+    }
+
+    pub fn supports_format<G: GraphicsBackend>(
+        &self,
+        format: <G::Api as xr::Graphics>::Format,
+    ) -> bool
+    where
+        for<'a> &'a GraphicalSession: TryInto<&'a Session<G::Api>, Error: std::fmt::Display>,
+        <G::Api as xr::Graphics>::Format: PartialEq,
+    {
+        self.swapchain_formats::<G>().contains(&format)
+    }
 
-        if !formats.contains(&info.format) {
-            let new_format = formats[0];
+    pub fn check_format<G: GraphicsBackend>(&self, info: &mut xr::SwapchainCreateInfo<G::Api>)
+    where
+        for<'a> &'a GraphicalSession: TryInto<&'a Session<G::Api>, Error: std::fmt::Display>,
+        <G::Api as xr::Graphics>::Format: PartialEq,
+    {
+        if !self.supports_format::<G>(info.format) {
+            let new_format = G::fallback_formats(info.format)
+                .into_iter()
+                .find(|format| self.supports_format::<G>(*format))
+                .unwrap_or(self.swapchain_formats::<G>()[0]);
             warn!(
                 "Requested to init swapchain with unsupported format {:?} - instead using {:?}",
                 G::to_nice_format(info.format),
@@ -544,7 +888,8 @@ impl SessionData {
         match origin {
             vr::ETrackingUniverseOrigin::Seated => &self.local_space_adjusted,
             vr::ETrackingUniverseOrigin::Standing => &self.stage_space_adjusted,
-            vr::ETrackingUniverseOrigin::RawAndUncalibrated => unreachable!(),
+            // The stage without any recentering applied.
+            vr::ETrackingUniverseOrigin::RawAndUncalibrated => &self.stage_space_reference,
         }
     }
 
@@ -599,6 +944,11 @@ impl HandInfo {
         self.connected.load(Ordering::Relaxed)
     }
 
+    #[inline]
+    pub fn path_name(&self) -> &'static str {
+        self.path_name
+    }
+
     fn new(instance: &xr::Instance, path_name: &'static str) -> Self {
         Self {
             path_name,
@@ -611,7 +961,8 @@ impl HandInfo {
 }
 
 #[repr(u32)]
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Hand {
     Left = 1,
     Right,
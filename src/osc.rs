@@ -0,0 +1,169 @@
+//! Just enough of [OSC](https://opensoundcontrol.stanford.edu/spec-1_0.html) for talking to
+//! VRChat style tools: messages with float and int arguments, and bundles of them.
+
+#[derive(Debug, PartialEq)]
+pub struct Message<'a> {
+    pub address: &'a str,
+    /// Only float and int arguments are supported, ints are converted to floats.
+    pub args: Vec<f32>,
+}
+
+#[derive(Debug)]
+pub struct ParseError(&'static str);
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+/// Reads a null terminated string, padded to a multiple of 4 bytes.
+fn read_string<'a>(data: &mut &'a [u8]) -> Result<&'a str, ParseError> {
+    let len = data
+        .iter()
+        .position(|b| *b == 0)
+        .ok_or(ParseError("unterminated string"))?;
+    let s = std::str::from_utf8(&data[..len]).map_err(|_| ParseError("invalid string"))?;
+    let padded = (len + 4) & !3;
+    *data = data.get(padded..).ok_or(ParseError("truncated string"))?;
+    Ok(s)
+}
+
+fn read_u32(data: &mut &[u8]) -> Result<u32, ParseError> {
+    let (bytes, rest) = data
+        .split_first_chunk::<4>()
+        .ok_or(ParseError("truncated argument"))?;
+    *data = rest;
+    Ok(u32::from_be_bytes(*bytes))
+}
+
+/// Parses a message or a bundle, appending every message in it to `messages`.
+pub fn parse_packet<'a>(
+    mut data: &'a [u8],
+    messages: &mut Vec<Message<'a>>,
+) -> Result<(), ParseError> {
+    if let Some(mut bundle) = data.strip_prefix(b"#bundle\0") {
+        // Skip the time tag, we apply everything as soon as it arrives.
+        bundle = bundle.get(8..).ok_or(ParseError("truncated bundle"))?;
+        while !bundle.is_empty() {
+            let size = read_u32(&mut bundle)? as usize;
+            let element = bundle
+                .get(..size)
+                .ok_or(ParseError("truncated bundle element"))?;
+            parse_packet(element, messages)?;
+            bundle = &bundle[size..];
+        }
+        return Ok(());
+    }
+
+    let address = read_string(&mut data)?;
+    let tags = read_string(&mut data)?
+        .strip_prefix(',')
+        .ok_or(ParseError("missing type tags"))?;
+    let args = tags
+        .chars()
+        .map(|tag| match tag {
+            'f' => read_u32(&mut data).map(f32::from_bits),
+            'i' => read_u32(&mut data).map(|i| i as i32 as f32),
+            _ => Err(ParseError("unsupported argument type")),
+        })
+        .collect::<Result<_, _>>()?;
+
+    messages.push(Message { address, args });
+    Ok(())
+}
+
+fn write_string(buf: &mut Vec<u8>, s: &str) {
+    buf.extend_from_slice(s.as_bytes());
+    buf.resize(buf.len() + 4 - s.len() % 4, 0);
+}
+
+/// Appends a message with float arguments to `buf`.
+pub fn write_message(buf: &mut Vec<u8>, address: &str, args: &[f32]) {
+    write_string(buf, address);
+    write_string(buf, &format!(",{}", "f".repeat(args.len())));
+    for arg in args {
+        buf.extend_from_slice(&arg.to_be_bytes());
+    }
+}
+
+/// Builds a bundle out of messages, to be applied immediately.
+pub struct BundleWriter {
+    buf: Vec<u8>,
+}
+
+impl BundleWriter {
+    pub fn new() -> Self {
+        let mut buf = b"#bundle\0".to_vec();
+        // The special "immediately" time tag
+        buf.extend_from_slice(&1_u64.to_be_bytes());
+        Self { buf }
+    }
+
+    pub fn message(&mut self, address: &str, args: &[f32]) {
+        let size_offset = self.buf.len();
+        self.buf.extend_from_slice(&[0; 4]);
+        write_message(&mut self.buf, address, args);
+        let size = (self.buf.len() - size_offset - 4) as u32;
+        self.buf[size_offset..size_offset + 4].copy_from_slice(&size.to_be_bytes());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buf.len() == 16
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+impl Default for BundleWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_message() {
+        let mut data = Vec::new();
+        write_message(&mut data, "/tracking/trackers/1/position", &[1.0, 2.0, 3.0]);
+        assert_eq!(data.len() % 4, 0);
+
+        let mut messages = Vec::new();
+        parse_packet(&data, &mut messages).unwrap();
+        assert_eq!(
+            messages,
+            [Message {
+                address: "/tracking/trackers/1/position",
+                args: vec![1.0, 2.0, 3.0]
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_bundle() {
+        let mut bundle = BundleWriter::new();
+        assert!(bundle.is_empty());
+        bundle.message("/tracking/trackers/2/rotation", &[0.0, 90.0, 0.0]);
+        bundle.message("/tracking/trackers/2/battery", &[0.5]);
+        assert!(!bundle.is_empty());
+        let data = bundle.finish();
+
+        let mut messages = Vec::new();
+        parse_packet(&data, &mut messages).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].address, "/tracking/trackers/2/rotation");
+        assert_eq!(messages[1].args, [0.5]);
+    }
+
+    #[test]
+    fn parse_truncated() {
+        let mut data = Vec::new();
+        write_message(&mut data, "/tracking/trackers/1/position", &[1.0, 2.0, 3.0]);
+        assert!(parse_packet(&data[..data.len() - 2], &mut Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,365 @@
+//! Virtual trackers fed over OSC, using the tracker protocol SlimeVR (and others) use to send full
+//! body tracking to VRChat. This is for runtimes that have no way of exposing trackers themselves.
+//!
+//! Trackers are sent on `/tracking/trackers/<n>/position` and `/tracking/trackers/<n>/rotation`,
+//! with `n` from 1 to 8. Positions are in meters and rotations are euler angles in degrees, both in
+//! Unity's left handed coordinate space. Additionally `/tracking/trackers/<n>/battery` is accepted,
+//! with a battery level from 0 to 1.
+//!
+//! Haptic pulses for a tracker are sent back to whoever sent the tracker data, on
+//! `/tracking/trackers/<n>/haptic` with the amplitude (0 to 1), duration in seconds and frequency
+//! in Hz (0 if unspecified).
+
+use crate::config::Config;
+use crate::openxr_data::SessionData;
+use crate::osc::{self, Message};
+use glam::{EulerRot, Quat, Vec3};
+use log::{debug, error, info, trace};
+use openvr as vr;
+use openxr as xr;
+use serde::Deserialize;
+use std::ffi::CStr;
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex, OnceLock,
+};
+use std::time::{Duration, Instant};
+
+pub const MAX_TRACKERS: usize = 8;
+/// Device index of the first tracker. The HMD and the controllers come before it.
+pub const FIRST_TRACKER_INDEX: vr::TrackedDeviceIndex_t = 3;
+/// Trackers that haven't been updated for this long are reported as disconnected.
+const TRACKER_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Deserialize)]
+pub struct OscTrackersConfig {
+    #[serde(default = "default_port")]
+    port: u16,
+}
+
+fn default_port() -> u16 {
+    // Not 9000, since that's what VRChat itself listens on.
+    9002
+}
+
+#[derive(Copy, Clone)]
+struct TrackerState {
+    position: Vec3,
+    rotation: Quat,
+    battery: Option<f32>,
+    last_update: Instant,
+}
+
+pub struct OscTrackers {
+    trackers: Mutex<[Option<TrackerState>; MAX_TRACKERS]>,
+    last_connected: [AtomicBool; MAX_TRACKERS],
+    socket: UdpSocket,
+    /// Where the last tracker packet came from, which haptics are sent to.
+    sender: Mutex<Option<SocketAddr>>,
+}
+
+impl OscTrackers {
+    /// Returns None if OSC trackers aren't enabled in the config.
+    pub fn get() -> Option<&'static Self> {
+        static TRACKERS: OnceLock<Option<&'static OscTrackers>> = OnceLock::new();
+        *TRACKERS.get_or_init(|| {
+            let config = Config::get().osc_trackers.as_ref()?;
+            let socket = UdpSocket::bind(("127.0.0.1", config.port))
+                .inspect_err(|e| error!("Couldn't listen for OSC trackers: {e}"))
+                .ok()?;
+            info!("Listening for OSC trackers on port {}", config.port);
+
+            let send_socket = socket
+                .try_clone()
+                .inspect_err(|e| error!("Couldn't clone OSC tracker socket: {e}"))
+                .ok()?;
+            let trackers: &'static Self = Box::leak(Box::new(Self {
+                trackers: Mutex::default(),
+                last_connected: Default::default(),
+                socket: send_socket,
+                sender: Mutex::default(),
+            }));
+            std::thread::Builder::new()
+                .name("xrizer-osc-trackers".into())
+                .spawn(move || trackers.receive(socket))
+                .inspect_err(|e| error!("Couldn't spawn OSC tracker thread: {e}"))
+                .ok()?;
+            Some(trackers)
+        })
+    }
+
+    fn receive(&self, socket: UdpSocket) {
+        let mut buf = [0; 4096];
+        loop {
+            let len = match socket.recv_from(&mut buf) {
+                Ok((len, sender)) => {
+                    *self.sender.lock().unwrap() = Some(sender);
+                    len
+                }
+                Err(e) => {
+                    error!("Failed to receive OSC packet, stopping: {e}");
+                    return;
+                }
+            };
+            let mut messages = Vec::new();
+            if let Err(e) = osc::parse_packet(&buf[..len], &mut messages) {
+                trace!("Ignoring malformed OSC packet: {e}");
+                continue;
+            }
+            let now = Instant::now();
+            let mut trackers = self.trackers.lock().unwrap();
+            for message in messages {
+                Self::apply_message(&mut trackers, message, now);
+            }
+        }
+    }
+
+    fn apply_message(
+        trackers: &mut [Option<TrackerState>; MAX_TRACKERS],
+        message: Message,
+        now: Instant,
+    ) {
+        let Some(rest) = message.address.strip_prefix("/tracking/trackers/") else {
+            return;
+        };
+        // The "head" tracker is only used by VRChat for aligning the other trackers.
+        let Some((Ok(id @ 1..=MAX_TRACKERS), field)) = rest
+            .split_once('/')
+            .map(|(id, field)| (id.parse::<usize>(), field))
+        else {
+            return;
+        };
+
+        let tracker = trackers[id - 1].get_or_insert(TrackerState {
+            position: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            battery: None,
+            last_update: now,
+        });
+        match (field, message.args.as_slice()) {
+            ("position", &[x, y, z]) => {
+                tracker.position = Vec3::new(x, y, -z);
+                tracker.last_update = now;
+            }
+            ("rotation", &[x, y, z]) => {
+                // Unity applies euler angles in ZXY order. Going from a left handed to a right
+                // handed space flips the direction of the X and Y rotations.
+                tracker.rotation = Quat::from_euler(
+                    EulerRot::YXZ,
+                    -y.to_radians(),
+                    -x.to_radians(),
+                    z.to_radians(),
+                );
+                tracker.last_update = now;
+            }
+            ("battery", &[level]) => tracker.battery = Some(level.clamp(0.0, 1.0)),
+            _ => debug!("Unhandled OSC tracker message: {}", message.address),
+        }
+    }
+
+    fn tracker(&self, index: vr::TrackedDeviceIndex_t) -> Option<TrackerState> {
+        let id = index.checked_sub(FIRST_TRACKER_INDEX)? as usize;
+        self.trackers
+            .lock()
+            .unwrap()
+            .get(id)
+            .copied()
+            .flatten()
+            .filter(|tracker| tracker.last_update.elapsed() < TRACKER_TIMEOUT)
+    }
+
+    pub fn is_tracker_index(index: vr::TrackedDeviceIndex_t) -> bool {
+        (FIRST_TRACKER_INDEX..FIRST_TRACKER_INDEX + MAX_TRACKERS as u32).contains(&index)
+    }
+
+    pub fn connected(&self, index: vr::TrackedDeviceIndex_t) -> bool {
+        self.tracker(index).is_some()
+    }
+
+    pub fn battery(&self, index: vr::TrackedDeviceIndex_t) -> Option<f32> {
+        self.tracker(index)?.battery
+    }
+
+    /// The tracker with the given device path (`/devices/xrizer/<serial number>`), which can be
+    /// used as an input source to send haptics to it.
+    pub fn index_for_device_path(path: &CStr) -> Option<vr::TrackedDeviceIndex_t> {
+        let serial = path.to_str().ok()?.strip_prefix("/devices/xrizer/")?;
+        (FIRST_TRACKER_INDEX..FIRST_TRACKER_INDEX + MAX_TRACKERS as u32).find(|index| {
+            Self::string_property(*index, vr::ETrackedDeviceProperty::SerialNumber_String)
+                .is_some_and(|s| s.to_bytes().eq_ignore_ascii_case(serial.as_bytes()))
+        })
+    }
+
+    pub fn string_property(
+        index: vr::TrackedDeviceIndex_t,
+        prop: vr::ETrackedDeviceProperty,
+    ) -> Option<&'static CStr> {
+        const SERIALS: [&CStr; MAX_TRACKERS] = [
+            c"OSC-TRACKER-1",
+            c"OSC-TRACKER-2",
+            c"OSC-TRACKER-3",
+            c"OSC-TRACKER-4",
+            c"OSC-TRACKER-5",
+            c"OSC-TRACKER-6",
+            c"OSC-TRACKER-7",
+            c"OSC-TRACKER-8",
+        ];
+        match prop {
+            vr::ETrackedDeviceProperty::SerialNumber_String => {
+                Some(SERIALS[(index - FIRST_TRACKER_INDEX) as usize])
+            }
+            vr::ETrackedDeviceProperty::ModelNumber_String => Some(c"OSC Tracker"),
+            vr::ETrackedDeviceProperty::ManufacturerName_String => Some(c"xrizer"),
+            vr::ETrackedDeviceProperty::TrackingSystemName_String => Some(c"osc"),
+            vr::ETrackedDeviceProperty::ControllerType_String => Some(c"vive_tracker"),
+            vr::ETrackedDeviceProperty::RenderModelName_String => Some(c"{htc}vr_tracker_vive_1_0"),
+            _ => None,
+        }
+    }
+
+    /// Sends a haptic pulse for a tracker to the program sending the tracker data.
+    pub fn trigger_haptic(
+        &self,
+        index: vr::TrackedDeviceIndex_t,
+        amplitude: f32,
+        duration_seconds: f32,
+        frequency: f32,
+    ) {
+        if self.tracker(index).is_none() {
+            return;
+        }
+        let Some(sender) = *self.sender.lock().unwrap() else {
+            return;
+        };
+        let id = index - FIRST_TRACKER_INDEX + 1;
+        let mut buf = Vec::new();
+        osc::write_message(
+            &mut buf,
+            &format!("/tracking/trackers/{id}/haptic"),
+            &[
+                amplitude.clamp(0.0, 1.0),
+                duration_seconds.max(0.0),
+                frequency,
+            ],
+        );
+        if let Err(e) = self.socket.send_to(&buf, sender) {
+            debug!("Couldn't send OSC tracker haptic: {e}");
+        }
+    }
+
+    /// Returns the index and new state of a tracker that connected or disconnected since the last
+    /// call, if any.
+    pub fn poll_connection_change(&self) -> Option<(vr::TrackedDeviceIndex_t, bool)> {
+        (0..MAX_TRACKERS).find_map(|id| {
+            let index = FIRST_TRACKER_INDEX + id as u32;
+            let current = self.connected(index);
+            self.last_connected[id]
+                .compare_exchange(!current, current, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+                .then_some((index, current))
+        })
+    }
+
+    pub fn get_pose(
+        &self,
+        index: vr::TrackedDeviceIndex_t,
+        session_data: &SessionData,
+        display_time: xr::Time,
+        origin: vr::ETrackingUniverseOrigin,
+    ) -> vr::TrackedDevicePose_t {
+        let Some(tracker) = self.tracker(index) else {
+            return Default::default();
+        };
+
+        // Trackers are sent relative to the floor, so relate the stage to the requested origin.
+        let stage = match session_data
+            .get_space_from_type(xr::ReferenceSpaceType::STAGE)
+            .locate(session_data.get_space_for_origin(origin), display_time)
+        {
+            Ok(stage) => stage,
+            Err(e) => {
+                error!("Failed to locate the stage for OSC tracker {index}: {e}");
+                return Default::default();
+            }
+        };
+        let stage_pose = stage.pose;
+        let stage_rotation = Quat::from_xyzw(
+            stage_pose.orientation.x,
+            stage_pose.orientation.y,
+            stage_pose.orientation.z,
+            stage_pose.orientation.w,
+        );
+        let stage_position = Vec3::new(
+            stage_pose.position.x,
+            stage_pose.position.y,
+            stage_pose.position.z,
+        );
+
+        let rotation = stage_rotation * tracker.rotation;
+        let position = stage_position + stage_rotation * tracker.position;
+        vr::TrackedDevicePose_t {
+            mDeviceToAbsoluteTracking: xr::Posef {
+                orientation: xr::Quaternionf {
+                    x: rotation.x,
+                    y: rotation.y,
+                    z: rotation.z,
+                    w: rotation.w,
+                },
+                position: xr::Vector3f {
+                    x: position.x,
+                    y: position.y,
+                    z: position.z,
+                },
+            }
+            .into(),
+            vVelocity: Default::default(),
+            vAngularVelocity: Default::default(),
+            eTrackingResult: vr::ETrackingResult::Running_OK,
+            bPoseIsValid: true,
+            bDeviceIsConnected: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unity_to_openvr_space() {
+        let mut trackers = [None; MAX_TRACKERS];
+        let now = Instant::now();
+        for (address, args) in [
+            ("/tracking/trackers/3/position", vec![1.0, 2.0, 3.0]),
+            // Turned 90 degrees right in Unity
+            ("/tracking/trackers/3/rotation", vec![0.0, 90.0, 0.0]),
+            ("/tracking/trackers/head/position", vec![0.0, 1.7, 0.0]),
+        ] {
+            OscTrackers::apply_message(&mut trackers, Message { address, args }, now);
+        }
+
+        assert!(trackers[..2].iter().all(Option::is_none));
+        let tracker = trackers[2].unwrap();
+        assert_eq!(tracker.position, Vec3::new(1.0, 2.0, -3.0));
+        // Forward (-Z in OpenVR) should now point right (+X).
+        assert!((tracker.rotation * Vec3::NEG_Z).abs_diff_eq(Vec3::X, 1e-6));
+    }
+
+    #[test]
+    fn device_paths() {
+        assert_eq!(
+            OscTrackers::index_for_device_path(c"/devices/xrizer/OSC-TRACKER-2"),
+            Some(FIRST_TRACKER_INDEX + 1)
+        );
+        assert_eq!(
+            OscTrackers::index_for_device_path(c"/devices/xrizer/osc-tracker-8"),
+            Some(FIRST_TRACKER_INDEX + 7)
+        );
+        assert_eq!(
+            OscTrackers::index_for_device_path(c"/devices/xrizer/OSC-TRACKER-9"),
+            None
+        );
+        assert_eq!(OscTrackers::index_for_device_path(c"/user/hand/left"), None);
+    }
+}
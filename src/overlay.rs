@@ -1,20 +1,51 @@
 use crate::{
+    clientcore::{Injected, Injector},
     compositor::{is_usable_swapchain, Compositor},
-    graphics_backends::{supported_apis_enum, GraphicsBackend, SupportedBackend},
-    openxr_data::{GraphicalSession, OpenXrData, Session, SessionData},
+    config::Config,
+    graphics_backends::{
+        supported_apis_enum, GlData, GlInterop, GraphicsBackend, SupportedBackend,
+    },
+    input::Input,
+    openxr_data::{GraphicalSession, Hand, OpenXrData, Session, SessionData},
+    osc_trackers::OscTrackers,
 };
-use glam::{vec3, Quat, Vec3};
-use log::{debug, trace};
+use glam::{vec3, Quat, Vec2, Vec3};
+use log::{debug, error, info, trace};
 use openvr as vr;
+use openvr::IVRCompositor028_Interface;
 use openxr as xr;
 use slotmap::{new_key_type, Key, KeyData, SecondaryMap, SlotMap};
 use std::f32::consts::{FRAC_1_SQRT_2, PI};
 use std::ffi::{c_char, c_void, CStr, CString};
-use std::sync::{Arc, Mutex, RwLock};
-use std::{collections::HashMap, ops::Deref};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, RwLock, Weak};
+use std::time::{Duration, Instant};
+use std::{
+    collections::{HashMap, VecDeque},
+    ops::Deref,
+};
+
+mod dashboard;
+mod keyboard;
+mod notifications;
+mod pointer;
+mod text;
+
+pub use dashboard::DashboardConfig;
 
 // OpenVR overlays are allowed to use ≥ 0
 pub const SKYBOX_Z_ORDER: i64 = -1;
+/// How long the binding list from ShowBindingsForActionSet stays up.
+const BINDINGS_VIEW_DURATION: Duration = Duration::from_secs(10);
+/// Above every other overlay.
+const CURSOR_Z_ORDER: i64 = i64::MAX;
+const CURSOR_WIDTH: f32 = 0.015;
+const KEYBOARD_WIDTH: f32 = 0.7;
+/// Notifications go over everything but the cursor.
+const NOTIFICATION_Z_ORDER: i64 = CURSOR_Z_ORDER - 1;
+const NOTIFICATION_WIDTH: f32 = 0.4;
+/// Events beyond this are dropped if the app doesn't poll them.
+const MAX_QUEUED_EVENTS: usize = 256;
 
 #[derive(macros::InterfaceImpl)]
 #[interface = "IVROverlay"]
@@ -25,21 +56,330 @@ pub struct OverlayMan {
     overlays: RwLock<SlotMap<OverlayKey, Overlay>>,
     key_to_overlay: RwLock<HashMap<CString, OverlayKey>>,
     skybox: RwLock<Vec<OverlayKey>>,
+    /// The binding list overlay and when to hide it.
+    bindings_view: Mutex<Option<(OverlayKey, Instant)>>,
+    compositor: Injected<Compositor>,
+    /// Only started for overlay apps.
+    frame_loop: Mutex<Option<OverlayFrameLoop>>,
+    /// Counts composited frames, for WaitFrameSync.
+    frame_count: Mutex<u64>,
+    frame_composited: Condvar,
+    /// Locked before the overlays.
+    dashboard: Mutex<dashboard::Dashboard>,
+    /// Locked before the overlays.
+    pointer: Mutex<pointer::Pointer>,
+    /// The dot showing where the pointer hits an overlay.
+    cursor: Mutex<Option<OverlayKey>>,
+    /// Locked before the overlays.
+    keyboard: Mutex<keyboard::Keyboard>,
+    /// Keyboard events for the scene application, sent through IVRSystem.
+    keyboard_events: Mutex<VecDeque<(vr::EVREventType, vr::VREvent_Keyboard_t)>>,
+    /// Locked before the overlays.
+    notifications: Mutex<notifications::Notifications>,
 }
 
 impl OverlayMan {
-    pub fn new(openxr: Arc<OpenXrData<Compositor>>) -> Self {
+    pub fn new(openxr: Arc<OpenXrData<Compositor>>, injector: &Injector) -> Self {
         Self {
             vtables: Vtables::default(),
             openxr,
             overlays: Default::default(),
             key_to_overlay: Default::default(),
             skybox: Default::default(),
+            bindings_view: Default::default(),
+            compositor: injector.inject(),
+            frame_loop: Default::default(),
+            frame_count: Default::default(),
+            frame_composited: Default::default(),
+            dashboard: Default::default(),
+            pointer: Default::default(),
+            cursor: Default::default(),
+            keyboard: Default::default(),
+            keyboard_events: Default::default(),
+            notifications: Default::default(),
+        }
+    }
+
+    /// Points the primary controller at overlays that take mouse input, and sends them mouse
+    /// events. Called once per frame.
+    pub fn update_pointer(&self, session: &SessionData, input: &Input<Compositor>) {
+        let wants_pointer = |overlay: &Overlay| {
+            overlay.visible
+                && overlay.rect.is_some()
+                && overlay.input_method == vr::VROverlayInputMethod::Mouse
+        };
+        let cursor = *self.cursor.lock().unwrap();
+        // The cursor is created the first time it's needed.
+        let backend =
+            if cursor.is_none() && self.overlays.read().unwrap().values().any(wants_pointer) {
+                self.new_pixels_backend(None, session)
+            } else {
+                None
+            };
+
+        let triggers = [Hand::Left, Hand::Right].map(|hand| input.trigger_held(hand));
+        let mut pointer = self.pointer.lock().unwrap();
+        pointer.select_hand(triggers);
+        let aims = [
+            vr::ETrackingUniverseOrigin::Seated,
+            vr::ETrackingUniverseOrigin::Standing,
+        ]
+        .map(|origin| {
+            input
+                .get_aim_pose(pointer.hand, origin)
+                .filter(|pose| pose.bPoseIsValid)
+                .map(|pose| pointer::aim_ray(pose.mDeviceToAbsoluteTracking.into()))
+        });
+
+        let devices = self.locate_devices(session);
+        let mut overlays = self.overlays.write().unwrap();
+        let hit = overlays
+            .iter()
+            .filter(|(_, overlay)| wants_pointer(overlay))
+            .filter_map(|(key, overlay)| {
+                let (origin, pose) = overlay.placement(session.current_origin, &devices)?;
+                let (source, direction) = match origin {
+                    vr::ETrackingUniverseOrigin::Seated => aims[0],
+                    vr::ETrackingUniverseOrigin::Standing => aims[1],
+                    vr::ETrackingUniverseOrigin::RawAndUncalibrated => None,
+                }?;
+                let hit =
+                    pointer::intersect(pose, overlay.width, overlay.height(), source, direction)?;
+                Some((key, origin, pose, hit))
+            })
+            .min_by(|(.., a), (.., b)| a.distance.total_cmp(&b.distance));
+
+        let device = pointer.hand as vr::TrackedDeviceIndex_t;
+        for (key, event, uv) in pointer.update(hit.map(|(key, .., hit)| (key, hit.uv)), triggers) {
+            let Some(overlay) = overlays.get_mut(key) else {
+                continue;
+            };
+            let focus = |ty| {
+                let overlay = vr::VREvent_Overlay_t {
+                    overlayHandle: key.data().as_ffi(),
+                    ..Default::default()
+                };
+                (ty, vr::VREvent_Data_t { overlay })
+            };
+            // Mouse coordinates are scaled by the mouse scale, from the bottom left.
+            let mouse = |ty, button| {
+                let mouse = vr::VREvent_Mouse_t {
+                    x: uv.x * overlay.mouse_scale.v[0],
+                    y: uv.y * overlay.mouse_scale.v[1],
+                    button,
+                    cursorIndex: 0,
+                };
+                (ty, vr::VREvent_Data_t { mouse })
+            };
+            let left = vr::EVRMouseButton::Left as u32;
+            let (ty, data) = match event {
+                pointer::MouseEvent::FocusEnter => focus(vr::EVREventType::FocusEnter),
+                pointer::MouseEvent::FocusLeave => focus(vr::EVREventType::FocusLeave),
+                pointer::MouseEvent::Move => mouse(vr::EVREventType::MouseMove, 0),
+                pointer::MouseEvent::ButtonDown => mouse(vr::EVREventType::MouseButtonDown, left),
+                pointer::MouseEvent::ButtonUp => mouse(vr::EVREventType::MouseButtonUp, left),
+            };
+            trace!("overlay {:?} pointer event {ty:?}", overlay.name);
+            overlay.push_event(ty, device, data);
+        }
+
+        // Show where the pointer hits.
+        let cursor = match cursor {
+            Some(key) => Some(key),
+            None if backend.is_some() => {
+                let name = CString::new("__xrizer_cursor").unwrap();
+                let key = overlays.insert(Overlay::new(name.clone(), name));
+                let overlay = overlays.get_mut(key).unwrap();
+                let (pixels, extent) = pointer::cursor_image();
+                match overlay.set_pixels(key, session, &pixels, extent, backend) {
+                    Ok(()) => {
+                        overlay.width = CURSOR_WIDTH;
+                        overlay.z_order = CURSOR_Z_ORDER;
+                        *self.cursor.lock().unwrap() = Some(key);
+                        Some(key)
+                    }
+                    Err(e) => {
+                        debug!("Couldn't create overlay cursor: {e:?}");
+                        overlays.remove(key);
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+        if let Some(cursor) = cursor.and_then(|key| overlays.get_mut(key)) {
+            cursor.visible = hit.is_some();
+            if let Some((_, origin, pose, hit)) = hit {
+                // Slightly in front, so it doesn't fight with the overlay.
+                let position = hit.point + hit.normal * 0.001;
+                let pose = xr::Posef {
+                    orientation: pose.orientation,
+                    position: xr::Vector3f {
+                        x: position.x,
+                        y: position.y,
+                        z: position.z,
+                    },
+                };
+                cursor.transform = Some(OverlayTransform::Absolute(origin, pose.into()));
+            }
+        }
+    }
+
+    /// Toggles the dashboard once the summon button or gesture has been held long enough, and
+    /// keeps the dashboard overlays in place. Called once per frame.
+    pub fn update_dashboard(&self, session: &SessionData, summon_held: bool) {
+        let hold = Duration::from_secs_f32(Config::get().dashboard.hold_seconds);
+        let mut dashboard = self.dashboard.lock().unwrap();
+        if dashboard.update_summon(summon_held, Instant::now(), hold) && !dashboard.tabs.is_empty()
+        {
+            let visible = !dashboard.visible;
+            debug!("dashboard summoned, visible: {visible}");
+            self.set_dashboard_visible(&mut dashboard, session, visible);
+        }
+        if dashboard.visible {
+            self.update_thumbnails(&mut dashboard, session);
+        }
+        dashboard.layout(&mut self.overlays.write().unwrap());
+    }
+
+    /// Draws the names of dashboard overlays whose thumbnail the app left empty, and switches to
+    /// the overlay whose thumbnail was clicked.
+    fn update_thumbnails(&self, dashboard: &mut dashboard::Dashboard, session: &SessionData) {
+        let unnamed: Vec<_> = {
+            let overlays = self.overlays.read().unwrap();
+            dashboard
+                .tabs
+                .iter()
+                .filter_map(|tab| {
+                    let thumbnail = overlays.get(tab.thumbnail)?;
+                    thumbnail
+                        .rect
+                        .is_none()
+                        .then(|| (tab.thumbnail, thumbnail.name.to_string_lossy().into_owned()))
+                })
+                .collect()
+        };
+        let unnamed: Vec<_> = unnamed
+            .into_iter()
+            .map(|(key, name)| {
+                let backend = self.new_pixels_backend(Some(key), session);
+                (key, dashboard::name_thumbnail(&name), backend)
+            })
+            .collect();
+
+        let mut overlays = self.overlays.write().unwrap();
+        for (key, image, backend) in unnamed {
+            let Some(thumbnail) = overlays.get_mut(key) else {
+                continue;
+            };
+            if let Err(e) = thumbnail.set_pixels(key, session, &image.pixels, image.extent, backend)
+            {
+                debug!("Couldn't draw thumbnail for {:?}: {e:?}", thumbnail.name);
+            }
+        }
+
+        // Thumbnails are xrizer's to click on, so their events don't go to the app.
+        let mut clicked = None;
+        for tab in &dashboard.tabs {
+            let Some(thumbnail) = overlays.get_mut(tab.thumbnail) else {
+                continue;
+            };
+            if thumbnail
+                .events
+                .drain(..)
+                .any(|event| event.eventType == vr::EVREventType::MouseButtonDown as u32)
+            {
+                clicked = Some(tab.main);
+            }
+        }
+        if let Some(main) = clicked.filter(|main| dashboard.active != Some(*main)) {
+            debug!(
+                "switching dashboard to {:?}",
+                overlays.get(main).map(|o| &o.name)
+            );
+            dashboard.active = Some(main);
         }
     }
 
+    pub fn poll_dashboard_event(&self) -> Option<vr::EVREventType> {
+        self.dashboard.lock().unwrap().events.pop_front()
+    }
+
+    fn set_dashboard_visible(
+        &self,
+        dashboard: &mut dashboard::Dashboard,
+        session: &SessionData,
+        visible: bool,
+    ) {
+        let hmd = session
+            .get_space_from_type(xr::ReferenceSpaceType::VIEW)
+            .locate(session.tracking_space(), self.openxr.display_time.get())
+            .unwrap()
+            .pose;
+        dashboard.set_visible(
+            visible,
+            session.current_origin,
+            hmd,
+            &mut self.overlays.write().unwrap(),
+        );
+    }
+
+    /// Overlay apps never submit frames, so the session is started with the device of their first
+    /// overlay texture and frames are ended on a thread of our own.
+    fn start_overlay_frames(&self, texture: &vr::Texture_t) {
+        let mut frame_loop = self.frame_loop.lock().unwrap();
+        if frame_loop.is_some() {
+            return;
+        }
+
+        let compositor = self
+            .compositor
+            .force(|injector| Compositor::new(self.openxr.clone(), injector));
+        compositor.initialize_overlay_session(texture);
+        *frame_loop = OverlayFrameLoop::spawn(Arc::downgrade(&compositor));
+    }
+
+    pub fn stop_overlay_frames(&self) {
+        self.frame_loop.lock().unwrap().take();
+    }
+
+    fn get_overlay_string(
+        &self,
+        handle: vr::VROverlayHandle_t,
+        value: *mut c_char,
+        size: u32,
+        error: *mut vr::EVROverlayError,
+        get: impl FnOnce(&Overlay) -> &CStr,
+    ) -> u32 {
+        let set_error = |e| {
+            if let Some(error) = unsafe { error.as_mut() } {
+                *error = e;
+            }
+        };
+        let overlays = self.overlays.read().unwrap();
+        let Some(overlay) = overlays.get(OverlayKey::from(KeyData::from_ffi(handle))) else {
+            set_error(vr::EVROverlayError::UnknownOverlay);
+            return 0;
+        };
+
+        let data = get(overlay).to_bytes_with_nul();
+        let buf = if !value.is_null() && size > 0 {
+            unsafe { std::slice::from_raw_parts_mut(value as *mut u8, size as usize) }
+        } else {
+            &mut []
+        };
+        if buf.len() < data.len() {
+            set_error(vr::EVROverlayError::ArrayTooSmall);
+        } else {
+            buf[..data.len()].copy_from_slice(data);
+            set_error(vr::EVROverlayError::None);
+        }
+
+        data.len() as u32
+    }
+
     pub fn set_skybox(&self, session: &SessionData, textures: &[vr::Texture_t]) {
-        // We don't yet follow HMD position, so the skybox needs to be
+        // We don't yet follow HMD position, so the box skybox needs to be
         // big enough so that the user never leaves it
         const SKYBOX_SIZE: f32 = 500.0;
 
@@ -50,18 +390,38 @@ impl OverlayMan {
 
         match textures.len() {
             1..=2 => {
-                // only single equirect supported for now, ignore any 2nd one
-                let name = CString::new("__xrizer_skybox").unwrap();
-                let key = overlays.insert(Overlay::new(name.clone(), name));
-                let overlay = overlays.get_mut(key).unwrap();
-                overlay.set_texture(key, session, *textures.first().unwrap());
-                overlay.visible = true;
-                overlay.width = SKYBOX_SIZE; // for equirect this becomes radius
-                overlay.kind = OverlayKind::Sphere;
-                overlay.z_order = SKYBOX_Z_ORDER;
-                skybox.push(key);
+                // A second texture makes the skybox stereo - one texture per eye
+                let eyes: &[_] = if textures.len() == 2 {
+                    &[xr::EyeVisibility::LEFT, xr::EyeVisibility::RIGHT]
+                } else {
+                    &[xr::EyeVisibility::BOTH]
+                };
+                for (idx, (texture, eye)) in textures.iter().zip(eyes).enumerate() {
+                    let name = CString::new(format!("__xrizer_skybox_{}", idx)).unwrap();
+                    let key = overlays.insert(Overlay::new(name.clone(), name));
+                    let overlay = overlays.get_mut(key).unwrap();
+                    overlay.set_texture(key, session, *texture);
+                    overlay.visible = true;
+                    overlay.kind = OverlayKind::Sphere { eye: *eye };
+                    overlay.z_order = SKYBOX_Z_ORDER;
+                    skybox.push(key);
+                }
             }
             6 => {
+                if self.openxr.enabled_extensions.khr_composition_layer_cube {
+                    let name = CString::new("__xrizer_skybox_cube").unwrap();
+                    let key = overlays.insert(Overlay::new(name.clone(), name));
+                    let overlay = overlays.get_mut(key).unwrap();
+                    if overlay.set_cube_textures(key, session, textures.try_into().unwrap()) {
+                        overlay.visible = true;
+                        overlay.kind = OverlayKind::Cube;
+                        overlay.z_order = SKYBOX_Z_ORDER;
+                        skybox.push(key);
+                        return;
+                    }
+                    overlays.remove(key);
+                }
+
                 for (idx, texture) in textures.iter().enumerate() {
                     // 6 quads forming a cursed box
                     let name = CString::new(format!("__xrizer_skybox_{}", idx)).unwrap();
@@ -101,7 +461,7 @@ impl OverlayMan {
                         },
                     ];
 
-                    overlay.transform = Some((
+                    overlay.transform = Some(OverlayTransform::Absolute(
                         vr::ETrackingUniverseOrigin::Standing,
                         QUAD_POSES[idx].into(),
                     ));
@@ -113,6 +473,408 @@ impl OverlayMan {
         }
     }
 
+    /// The backend an overlay needs for set_pixels, if it doesn't have one yet. This has to be
+    /// called before locking the overlays, because the compositor locks them while holding the
+    /// backend. Returns None if the game hasn't submitted a frame yet.
+    fn new_pixels_backend(
+        &self,
+        key: Option<OverlayKey>,
+        session: &SessionData,
+    ) -> Option<SupportedBackend> {
+        let has_backend = key.is_some_and(|key| {
+            self.overlays
+                .read()
+                .unwrap()
+                .get(key)
+                .is_some_and(|overlay| overlay.compositor.is_some())
+        });
+        (!has_backend)
+            .then(|| session.comp_data.new_overlay_backend())
+            .flatten()
+    }
+
+    /// Sets the content of an overlay to tightly packed sRGB RGBA8 pixels, top row first.
+    fn set_overlay_pixels(
+        &self,
+        handle: vr::VROverlayHandle_t,
+        pixels: &[u8],
+        extent: xr::Extent2Di,
+    ) -> vr::EVROverlayError {
+        let key = OverlayKey::from(KeyData::from_ffi(handle));
+        let session_data = self.openxr.session_data.get();
+        let backend = self.new_pixels_backend(Some(key), &session_data);
+        get_overlay!(self, handle, mut overlay);
+        match overlay.set_pixels(key, &session_data, pixels, extent, backend) {
+            Ok(()) => {
+                debug!(
+                    "set overlay pixels for {:?} ({}x{})",
+                    overlay.name, extent.width, extent.height
+                );
+                vr::EVROverlayError::None
+            }
+            Err(e) => e,
+        }
+    }
+
+    /// Locates the devices that visible overlays are attached to. Called before locking the
+    /// overlays for writing, since controller poses come from the input system.
+    fn locate_devices(&self, session: &SessionData) -> DevicePoses {
+        let mut devices: Vec<_> = self
+            .overlays
+            .read()
+            .unwrap()
+            .values()
+            .filter(|overlay| overlay.visible)
+            .filter_map(Overlay::tracked_device)
+            .collect();
+        if devices.is_empty() {
+            return DevicePoses::new();
+        }
+        devices.sort_unstable();
+        devices.dedup();
+
+        let time = self.openxr.display_time.get();
+        let origin = session.current_origin;
+        let input = self.openxr.input.get();
+        devices
+            .into_iter()
+            .filter_map(|device| {
+                if device == vr::k_unTrackedDeviceIndex_Hmd {
+                    let location = session
+                        .view_space
+                        .locate(session.tracking_space(), time)
+                        .ok()?;
+                    let valid = xr::SpaceLocationFlags::ORIENTATION_VALID
+                        | xr::SpaceLocationFlags::POSITION_VALID;
+                    return location
+                        .location_flags
+                        .contains(valid)
+                        .then_some((device, location.pose));
+                }
+                let pose = match Hand::try_from(device) {
+                    Ok(hand) => input.as_ref()?.get_controller_pose(hand, Some(origin))?,
+                    Err(_) => OscTrackers::get()?.get_pose(device, session, time, origin),
+                };
+                pose.bPoseIsValid
+                    .then(|| (device, pose.mDeviceToAbsoluteTracking.into()))
+            })
+            .collect()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn show_keyboard(
+        &self,
+        target: Option<OverlayKey>,
+        mode: vr::EGamepadTextInputMode,
+        line_mode: vr::EGamepadTextInputLineMode,
+        flags: u32,
+        description: *const c_char,
+        max_chars: u32,
+        existing_text: *const c_char,
+        user_value: u64,
+    ) -> vr::EVROverlayError {
+        let to_string = |s: *const c_char| {
+            if s.is_null() {
+                String::new()
+            } else {
+                unsafe { CStr::from_ptr(s) }.to_string_lossy().into_owned()
+            }
+        };
+        let input = keyboard::KeyboardInput::new(
+            target,
+            mode,
+            line_mode,
+            flags,
+            to_string(description),
+            max_chars,
+            to_string(existing_text),
+            user_value,
+        );
+
+        let session = self.openxr.session_data.get();
+        let mut keyboard = self.keyboard.lock().unwrap();
+        if keyboard.input.is_some() {
+            return vr::EVROverlayError::KeyboardAlreadyInUse;
+        }
+        let backend = self.new_pixels_backend(keyboard.overlay, &session);
+        let mut overlays = self.overlays.write().unwrap();
+        let key = *keyboard.overlay.get_or_insert_with(|| {
+            let name = CString::new("__xrizer_keyboard").unwrap();
+            let mut overlay = Overlay::new(name.clone(), name);
+            overlay.input_method = vr::VROverlayInputMethod::Mouse;
+            overlay.width = KEYBOARD_WIDTH;
+            overlays.insert(overlay)
+        });
+        let overlay = overlays.get_mut(key).unwrap();
+        let image = input.image();
+        if let Err(e) = overlay.set_pixels(key, &session, &image.pixels, image.extent, backend) {
+            debug!("Couldn't show keyboard: {e:?}");
+            return e;
+        }
+
+        let (origin, transform) = keyboard.transform.unwrap_or_else(|| {
+            // A bit below where the user is looking, within reach.
+            let hmd = session
+                .get_space_from_type(xr::ReferenceSpaceType::VIEW)
+                .locate(session.tracking_space(), self.openxr.display_time.get())
+                .unwrap()
+                .pose;
+            let offset = xr::Posef {
+                orientation: xr::Quaternionf::IDENTITY,
+                position: xr::Vector3f {
+                    x: 0.0,
+                    y: -0.3,
+                    z: -0.7,
+                },
+            };
+            (session.current_origin, compose(hmd, offset).into())
+        });
+        overlay.transform = Some(OverlayTransform::Absolute(origin, transform));
+        overlay.visible = true;
+        keyboard.input = Some(input);
+        vr::EVROverlayError::None
+    }
+
+    /// Types the keys the pointer clicked on the keyboard. Called once per frame, after the
+    /// pointer is updated.
+    pub fn update_keyboard(&self, session: &SessionData) {
+        let mut keyboard = self.keyboard.lock().unwrap();
+        let keyboard = &mut *keyboard;
+        let (Some(key), Some(input)) = (keyboard.overlay, keyboard.input.as_mut()) else {
+            return;
+        };
+        let mut overlays = self.overlays.write().unwrap();
+        let Some(overlay) = overlays.get_mut(key) else {
+            return;
+        };
+        let clicks: Vec<_> = overlay
+            .events
+            .drain(..)
+            .filter(|event| event.eventType == vr::EVREventType::MouseButtonDown as u32)
+            .map(|event| {
+                let mouse = unsafe { event.data.mouse };
+                Vec2::new(mouse.x, mouse.y)
+            })
+            .collect();
+        if clicks.is_empty() {
+            return;
+        }
+
+        let mut closed = false;
+        for pressed in clicks.into_iter().filter_map(keyboard::key_at) {
+            for event in input.press(pressed) {
+                closed |= event == keyboard::KeyboardEvent::Closed;
+                self.send_keyboard_event(&mut overlays, input, event);
+            }
+            if closed {
+                break;
+            }
+        }
+
+        let overlay = overlays.get_mut(key).unwrap();
+        if closed {
+            overlay.visible = false;
+            keyboard.last_text = keyboard.input.take().unwrap().text;
+            return;
+        }
+        let image = input.image();
+        if let Err(e) = overlay.set_pixels(key, session, &image.pixels, image.extent, None) {
+            debug!("Couldn't update keyboard: {e:?}");
+        }
+    }
+
+    fn send_keyboard_event(
+        &self,
+        overlays: &mut SlotMap<OverlayKey, Overlay>,
+        input: &keyboard::KeyboardInput,
+        event: keyboard::KeyboardEvent,
+    ) {
+        let (ty, text) = match event {
+            keyboard::KeyboardEvent::Char(text) => (vr::EVREventType::KeyboardCharInput, text),
+            keyboard::KeyboardEvent::Done => (vr::EVREventType::KeyboardDone, String::new()),
+            keyboard::KeyboardEvent::Closed => (vr::EVREventType::KeyboardClosed, String::new()),
+        };
+        let handle = input
+            .target
+            .map_or(vr::k_ulOverlayHandleInvalid, |key| key.data().as_ffi());
+        let data = keyboard::event_data(&text, input.user_value, handle);
+        debug!("sending keyboard event {ty:?} for overlay {handle}");
+        match input.target.and_then(|key| overlays.get_mut(key)) {
+            Some(target) => target.push_event(
+                ty,
+                vr::k_unTrackedDeviceIndexInvalid,
+                vr::VREvent_Data_t { keyboard: data },
+            ),
+            None => self.keyboard_events.lock().unwrap().push_back((ty, data)),
+        }
+    }
+
+    pub fn poll_keyboard_event(&self) -> Option<(vr::EVREventType, vr::VREvent_Keyboard_t)> {
+        self.keyboard_events.lock().unwrap().pop_front()
+    }
+
+    /// Queues a notification for an overlay. Its toast is rendered right away, and shown once the
+    /// notifications before it are out of the way.
+    pub fn create_notification(
+        &self,
+        handle: vr::VROverlayHandle_t,
+        user_value: u64,
+        ty: vr::EVRNotificationType,
+        text: &str,
+        image: Option<&vr::NotificationBitmap_t>,
+    ) -> Result<vr::VRNotificationId, vr::EVRNotificationError> {
+        let target = OverlayKey::from(KeyData::from_ffi(handle));
+        let icon = image.and_then(notifications::Icon::from_bitmap);
+        let image = notifications::render(text, icon.as_ref());
+
+        let session = self.openxr.session_data.get();
+        let backend = self.new_pixels_backend(None, &session);
+        let mut notifications = self.notifications.lock().unwrap();
+        let mut overlays = self.overlays.write().unwrap();
+        if !overlays.contains_key(target) {
+            return Err(vr::EVRNotificationError::InvalidOverlayHandle);
+        }
+        let id = notifications
+            .add(target, user_value, ty)
+            .map_err(|e| match e {
+                notifications::NotificationError::QueueFull => {
+                    vr::EVRNotificationError::NotificationQueueFull
+                }
+                notifications::NotificationError::UserValueExists => {
+                    vr::EVRNotificationError::SystemWithUserValueAlreadyExists
+                }
+            })?;
+
+        let name = CString::new(format!("__xrizer_notification_{id}")).unwrap();
+        let key = overlays.insert(Overlay::new(name.clone(), name));
+        let toast = overlays.get_mut(key).unwrap();
+        if let Err(e) = toast.set_pixels(key, &session, &image.pixels, image.extent, backend) {
+            debug!("Couldn't show notification: {e:?}");
+            overlays.remove(key);
+            notifications.remove(id);
+            return Err(vr::EVRNotificationError::ServiceUnavailable);
+        }
+        toast.width = NOTIFICATION_WIDTH;
+        toast.z_order = NOTIFICATION_Z_ORDER;
+        notifications.queue.last_mut().unwrap().toast = Some(key);
+        debug!("created notification {id}: {text:?}");
+        Ok(id)
+    }
+
+    pub fn remove_notification(
+        &self,
+        id: vr::VRNotificationId,
+    ) -> Result<(), vr::EVRNotificationError> {
+        let removed = self
+            .notifications
+            .lock()
+            .unwrap()
+            .remove(id)
+            .ok_or(vr::EVRNotificationError::InvalidNotificationId)?;
+        self.remove_toasts(
+            &self.openxr.session_data.get(),
+            &mut self.overlays.write().unwrap(),
+            [removed],
+        );
+        Ok(())
+    }
+
+    fn remove_toasts(
+        &self,
+        session: &SessionData,
+        overlays: &mut SlotMap<OverlayKey, Overlay>,
+        removed: impl IntoIterator<Item = notifications::Notification>,
+    ) {
+        for key in removed
+            .into_iter()
+            .filter_map(|notification| notification.toast)
+        {
+            overlays.remove(key);
+            session.overlay_data.remove_swapchain(key);
+        }
+    }
+
+    /// Takes down expired notifications, and stacks the shown ones below the center of the
+    /// user's view.
+    fn update_notifications(
+        &self,
+        session: &SessionData,
+        notifications: &mut notifications::Notifications,
+        overlays: &mut SlotMap<OverlayKey, Overlay>,
+    ) {
+        let expired = notifications.update(Instant::now());
+        self.remove_toasts(session, overlays, expired);
+
+        let shown = notifications.queue.iter().filter(|n| n.shown);
+        for (idx, notification) in shown.enumerate() {
+            let Some(toast) = notification.toast.and_then(|key| overlays.get_mut(key)) else {
+                continue;
+            };
+            let y = -0.2 - idx as f32 * (toast.height() + 0.02);
+            let pose = xr::Posef {
+                orientation: xr::Quaternionf::IDENTITY,
+                position: xr::Vector3f { x: 0.0, y, z: -1.0 },
+            };
+            toast.transform = Some(OverlayTransform::TrackedDeviceRelative(
+                vr::k_unTrackedDeviceIndex_Hmd,
+                pose.into(),
+            ));
+            toast.visible = true;
+        }
+    }
+
+    /// Shows the lines on a panel in front of the user for a while.
+    pub fn show_bindings_view(&self, session: &SessionData, lines: &[String]) {
+        let image = text::render_lines(lines);
+
+        let existing = self.bindings_view.lock().unwrap().map(|(key, _)| key);
+        let backend = self.new_pixels_backend(existing, session);
+        let mut overlays = self.overlays.write().unwrap();
+        let mut view = self.bindings_view.lock().unwrap();
+        let key = match *view {
+            Some((key, _)) => key,
+            None => {
+                let name = CString::new("__xrizer_bindings").unwrap();
+                overlays.insert(Overlay::new(name.clone(), name))
+            }
+        };
+        *view = Some((key, Instant::now() + BINDINGS_VIEW_DURATION));
+        let overlay = overlays.get_mut(key).unwrap();
+        if let Err(e) = overlay.set_pixels(key, session, &image.pixels, image.extent, backend) {
+            debug!("Couldn't show bindings: {e:?}");
+            return;
+        }
+
+        // Put it a meter in front of wherever the user is looking.
+        let hmd = session
+            .get_space_from_type(xr::ReferenceSpaceType::VIEW)
+            .locate(session.tracking_space(), self.openxr.display_time.get())
+            .unwrap()
+            .pose;
+        let rotation = Quat::from_xyzw(
+            hmd.orientation.x,
+            hmd.orientation.y,
+            hmd.orientation.z,
+            hmd.orientation.w,
+        );
+        let position =
+            vec3(hmd.position.x, hmd.position.y, hmd.position.z) + rotation.mul_vec3(Vec3::NEG_Z);
+        let pose = xr::Posef {
+            orientation: hmd.orientation,
+            position: xr::Vector3f {
+                x: position.x,
+                y: position.y,
+                z: position.z,
+            },
+        };
+        overlay.transform = Some(OverlayTransform::Absolute(
+            session.current_origin,
+            pose.into(),
+        ));
+        overlay.width = 0.8;
+        overlay.visible = true;
+    }
+
     pub fn clear_skybox(&self) {
         let mut overlays = self.overlays.write().unwrap();
         self.skybox.write().unwrap().drain(..).for_each(|key| {
@@ -128,7 +890,19 @@ impl OverlayMan {
     where
         for<'b> &'b AnySwapchainMap: TryInto<&'b SwapchainMap<G>, Error: std::fmt::Display>,
     {
+        *self.frame_count.lock().unwrap() += 1;
+        self.frame_composited.notify_all();
+
+        let devices = self.locate_devices(session);
+        let mut notifications = self.notifications.lock().unwrap();
         let mut overlays = self.overlays.write().unwrap();
+        if let Some((key, hide_at)) = *self.bindings_view.lock().unwrap() {
+            if let Some(overlay) = overlays.get_mut(key).filter(|_| hide_at <= Instant::now()) {
+                overlay.visible = false;
+            }
+        }
+        self.update_notifications(session, &mut notifications, &mut overlays);
+        drop(notifications);
         let swapchains = session.overlay_data.swapchains.lock().unwrap();
         let Some(swapchains) = swapchains.as_ref() else {
             return Vec::new();
@@ -140,6 +914,21 @@ impl OverlayMan {
             )
         });
 
+        // For sorting overlays with the same sort order back to front.
+        let time = self.openxr.display_time.get();
+        let hmd_positions = [
+            vr::ETrackingUniverseOrigin::Seated,
+            vr::ETrackingUniverseOrigin::Standing,
+            vr::ETrackingUniverseOrigin::RawAndUncalibrated,
+        ]
+        .map(|origin| {
+            session
+                .view_space
+                .locate(session.get_space_for_origin(origin), time)
+                .ok()
+                .map(|location| location.pose.position)
+        });
+
         let mut layers = Vec::with_capacity(overlays.len());
         for (key, overlay) in overlays.iter_mut() {
             if !overlay.visible {
@@ -153,31 +942,23 @@ impl OverlayMan {
             };
 
             let SwapchainData { swapchain, .. } = swapchains.get(key).unwrap();
-            let space = session.get_space_for_origin(
-                overlay
-                    .transform
-                    .as_ref()
-                    .map(|(o, _)| *o)
-                    .unwrap_or(session.current_origin),
-            );
+            let Some((origin, pose)) = overlay.placement(session.current_origin, &devices) else {
+                continue;
+            };
+            let space = session.get_space_for_origin(origin);
+            let distance = hmd_positions[origin as usize].map_or(0.0, |hmd| {
+                vec3(pose.position.x, pose.position.y, pose.position.z)
+                    .distance(vec3(hmd.x, hmd.y, hmd.z))
+            });
+            let order = (overlay.stack_order(), distance);
 
             trace!("overlay rect: {:#?}", rect);
 
-            let pose = overlay
-                .transform
-                .as_ref()
-                .map(|(_, t)| (*t).into())
-                .unwrap_or(xr::Posef {
-                    position: xr::Vector3f {
-                        x: 0.0,
-                        y: 0.0,
-                        z: -0.5,
-                    },
-                    orientation: xr::Quaternionf::IDENTITY,
-                });
-
             macro_rules! layer_init {
-                ($ty:ident) => {{
+                ($ty:ident) => {
+                    layer_init!($ty, rect)
+                };
+                ($ty:ident, $rect:expr) => {{
                     $ty::new()
                         .space(space)
                         .layer_flags(
@@ -189,7 +970,7 @@ impl OverlayMan {
                             xr::SwapchainSubImage::new()
                                 .image_array_index(vr::EVREye::Left as u32)
                                 .swapchain(swapchain)
-                                .image_rect(rect),
+                                .image_rect($rect),
                         )
                 }};
             }
@@ -211,21 +992,89 @@ impl OverlayMan {
                 }}
             }
 
+            // Covers the whole sphere around the user with the rect.
+            macro_rules! push_equirect {
+                ($eye:expr, $rect:expr) => {{
+                    const HORIZONTAL_RAD: f32 = 2.0 * PI;
+                    const VERTICAL_RAD_HIGH: f32 = 0.5 * PI;
+                    const VERTICAL_RAD_LOW: f32 = -0.5 * PI;
+
+                    use xr::CompositionLayerEquirect2KHR;
+                    // A radius of 0 is an infinite sphere
+                    let layer = layer_init!(CompositionLayerEquirect2KHR, $rect)
+                        .eye_visibility($eye)
+                        .radius(0.0)
+                        .central_horizontal_angle(HORIZONTAL_RAD)
+                        .upper_vertical_angle(VERTICAL_RAD_HIGH)
+                        .lower_vertical_angle(VERTICAL_RAD_LOW)
+                        .pose(pose);
+
+                    let layer = lifetime_extend!(CompositionLayerEquirect2KHR, layer);
+                    let mut layer = OverlayLayer::from(OverlayLayerInner::Equirect2(layer));
+                    overlay
+                        .color_scale()
+                        .iter()
+                        .for_each(|c| layer.set_color_scale(*c));
+                    layers.push((order, layer));
+                }};
+            }
+
             match overlay.kind {
+                // Panoramas are only drawn as such if the runtime can, otherwise they stay flat.
+                OverlayKind::Quad
+                    if overlay.has_flag(vr::VROverlayFlags::StereoPanorama)
+                        && self
+                            .openxr
+                            .enabled_extensions
+                            .khr_composition_layer_equirect2 =>
+                {
+                    // Left eye above the right one
+                    let half = xr::Extent2Di {
+                        width: rect.extent.width,
+                        height: rect.extent.height / 2,
+                    };
+                    let top = xr::Rect2Di {
+                        offset: rect.offset,
+                        extent: half,
+                    };
+                    let bottom = xr::Rect2Di {
+                        offset: xr::Offset2Di {
+                            x: rect.offset.x,
+                            y: rect.offset.y + half.height,
+                        },
+                        extent: half,
+                    };
+                    push_equirect!(xr::EyeVisibility::LEFT, top);
+                    push_equirect!(xr::EyeVisibility::RIGHT, bottom);
+                }
+                OverlayKind::Quad
+                    if overlay.has_flag(vr::VROverlayFlags::Panorama)
+                        && self
+                            .openxr
+                            .enabled_extensions
+                            .khr_composition_layer_equirect2 =>
+                {
+                    push_equirect!(xr::EyeVisibility::BOTH, rect);
+                }
                 OverlayKind::Quad => {
                     use xr::CompositionLayerQuad;
-                    let layer = layer_init!(CompositionLayerQuad)
-                        .pose(pose)
-                        .size(xr::Extent2Df {
-                            width: overlay.width,
-                            height: rect.extent.height as f32 * overlay.width
-                                / rect.extent.width as f32,
-                        });
-
-                    let layer = lifetime_extend!(CompositionLayerQuad, layer);
-                    let mut layer = OverlayLayer::from(OverlayLayerInner::Quad(layer));
-                    overlay.alpha.iter().for_each(|a| layer.set_alpha(*a));
-                    layers.push((overlay.z_order, layer));
+                    for (eye, rect) in overlay.eye_rects(rect) {
+                        let layer = layer_init!(CompositionLayerQuad, rect)
+                            .eye_visibility(eye)
+                            .pose(pose)
+                            .size(xr::Extent2Df {
+                                width: overlay.width,
+                                height: overlay.height(),
+                            });
+
+                        let layer = lifetime_extend!(CompositionLayerQuad, layer);
+                        let mut layer = OverlayLayer::from(OverlayLayerInner::Quad(layer));
+                        overlay
+                            .color_scale()
+                            .iter()
+                            .for_each(|c| layer.set_color_scale(*c));
+                        layers.push((order, layer));
+                    }
                 }
                 // SetOverlayCurvature checks for khr_composition_layer_cylinder
                 OverlayKind::Curved { curvature } => {
@@ -242,48 +1091,63 @@ impl OverlayMan {
                     let angle = 2.0 * (overlay.width / (2.0 * radius));
 
                     use xr::CompositionLayerCylinderKHR;
-                    let layer = layer_init!(CompositionLayerCylinderKHR)
-                        .radius(radius)
-                        .central_angle(angle)
-                        .aspect_ratio(rect.extent.height as f32 / rect.extent.width as f32)
-                        .pose(xr::Posef {
-                            orientation: pose.orientation,
-                            position: xr::Vector3f {
-                                x: center.x,
-                                y: center.y,
-                                z: center.z,
-                            },
-                        });
-
-                    let layer = lifetime_extend!(CompositionLayerCylinderKHR, layer);
-                    let mut layer = OverlayLayer::from(OverlayLayerInner::Cylinder(layer));
-                    overlay.alpha.iter().for_each(|a| layer.set_alpha(*a));
-                    layers.push((overlay.z_order, layer));
+                    for (eye, rect) in overlay.eye_rects(rect) {
+                        let layer = layer_init!(CompositionLayerCylinderKHR, rect)
+                            .eye_visibility(eye)
+                            .radius(radius)
+                            .central_angle(angle)
+                            .aspect_ratio(overlay.width / overlay.height())
+                            .pose(xr::Posef {
+                                orientation: pose.orientation,
+                                position: xr::Vector3f {
+                                    x: center.x,
+                                    y: center.y,
+                                    z: center.z,
+                                },
+                            });
+
+                        let layer = lifetime_extend!(CompositionLayerCylinderKHR, layer);
+                        let mut layer = OverlayLayer::from(OverlayLayerInner::Cylinder(layer));
+                        overlay
+                            .color_scale()
+                            .iter()
+                            .for_each(|c| layer.set_color_scale(*c));
+                        layers.push((order, layer));
+                    }
                 }
                 // SetSkyboxOverride checks for khr_composition_layer_equirect2
-                OverlayKind::Sphere => {
-                    const HORIZONTAL_RAD: f32 = 2.0 * PI;
-                    const VERTICAL_RAD_HIGH: f32 = 0.5 * PI;
-                    const VERTICAL_RAD_LOW: f32 = -0.5 * PI;
-
-                    use xr::CompositionLayerEquirect2KHR;
-                    let layer = layer_init!(CompositionLayerEquirect2KHR)
-                        .radius(overlay.width)
-                        .central_horizontal_angle(HORIZONTAL_RAD)
-                        .upper_vertical_angle(VERTICAL_RAD_HIGH)
-                        .lower_vertical_angle(VERTICAL_RAD_LOW)
-                        .pose(pose);
-
-                    let layer = lifetime_extend!(CompositionLayerEquirect2KHR, layer);
-                    let mut layer = OverlayLayer::from(OverlayLayerInner::Equirect2(layer));
-                    overlay.alpha.iter().for_each(|a| layer.set_alpha(*a));
-                    layers.push((overlay.z_order, layer));
+                OverlayKind::Sphere { eye } => {
+                    push_equirect!(eye, rect);
+                }
+                // set_skybox checks for khr_composition_layer_cube
+                OverlayKind::Cube => {
+                    use xr::CompositionLayerCubeKHR;
+                    let layer = CompositionLayerCubeKHR::new()
+                        .space(space)
+                        .layer_flags(
+                            xr::CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA
+                                | xr::CompositionLayerFlags::UNPREMULTIPLIED_ALPHA,
+                        )
+                        .eye_visibility(xr::EyeVisibility::BOTH)
+                        .swapchain(swapchain)
+                        .image_array_index(0)
+                        .orientation(pose.orientation);
+
+                    let layer = lifetime_extend!(CompositionLayerCubeKHR, layer);
+                    let mut layer = OverlayLayer::from(OverlayLayerInner::Cube(layer));
+                    overlay
+                        .color_scale()
+                        .iter()
+                        .for_each(|c| layer.set_color_scale(*c));
+                    layers.push((order, layer));
                 }
             }
         }
 
-        // Sort by z_order asc
-        layers.sort_by(|a, b| a.0.cmp(&b.0));
+        // Bottom to top, with farther overlays first among equals.
+        layers.sort_by(|((a, a_distance), _), ((b, b_distance), _)| {
+            a.cmp(b).then(b_distance.total_cmp(a_distance))
+        });
 
         let sorted_layers: Vec<OverlayLayer<_>> = layers.into_iter().map(|(_, l)| l).collect();
 
@@ -299,11 +1163,11 @@ pub struct OverlayLayer<'a, G: xr::Graphics> {
 }
 
 impl<G: xr::Graphics> OverlayLayer<'_, G> {
-    pub fn set_alpha(&mut self, alpha: f32) {
+    pub fn set_color_scale(&mut self, color_scale: xr::Color4f) {
         // only one instance is stored, so this would cause segfault due to UAF
         debug_assert!(
             self.color_bias_khr.is_none(),
-            "attempted to set_alpha on the same CompositorLayer twice!"
+            "attempted to set_color_scale on the same CompositorLayer twice!"
         );
 
         self.color_bias_khr = {
@@ -311,10 +1175,7 @@ impl<G: xr::Graphics> OverlayLayer<'_, G> {
                 ty: xr::StructureType::COMPOSITION_LAYER_COLOR_SCALE_BIAS_KHR,
                 next: std::ptr::null(),
                 color_bias: Default::default(),
-                color_scale: xr::Color4f {
-                    a: alpha,
-                    ..Default::default()
-                },
+                color_scale,
             });
 
             let payload_ptr = payload.as_mut() as *mut _ as *mut xr::sys::BaseInStructure;
@@ -349,6 +1210,12 @@ impl<G: xr::Graphics> OverlayLayer<'_, G> {
                 raw.next = item as *const _;
                 OverlayLayerInner::Equirect2(xr::CompositionLayerEquirect2KHR::from_raw(raw))
             }
+            OverlayLayerInner::Cube(cube) => {
+                let mut raw = cube.into_raw();
+                new_elem.next = raw.next as _;
+                raw.next = item as *const _;
+                OverlayLayerInner::Cube(xr::CompositionLayerCubeKHR::from_raw(raw))
+            }
         });
     }
 }
@@ -373,8 +1240,10 @@ pub enum OverlayLayerInner<'a, G: xr::Graphics> {
     Quad(xr::CompositionLayerQuad<'a, G>),
     // Curved overlays
     Cylinder(xr::CompositionLayerCylinderKHR<'a, G>),
-    // Skybox
+    // Skybox and panoramas
     Equirect2(xr::CompositionLayerEquirect2KHR<'a, G>),
+    // Box skybox
+    Cube(xr::CompositionLayerCubeKHR<'a, G>),
 }
 
 impl<'a, G: xr::Graphics> Deref for OverlayLayerInner<'a, G> {
@@ -384,6 +1253,7 @@ impl<'a, G: xr::Graphics> Deref for OverlayLayerInner<'a, G> {
             OverlayLayerInner::Quad(quad) => quad.deref(),
             OverlayLayerInner::Cylinder(cylinder) => cylinder.deref(),
             OverlayLayerInner::Equirect2(equirect2) => equirect2.deref(),
+            OverlayLayerInner::Cube(cube) => cube.deref(),
         }
     }
 }
@@ -406,10 +1276,118 @@ pub struct OverlaySessionData {
     swapchains: Mutex<Option<AnySwapchainMap>>,
 }
 
+impl OverlaySessionData {
+    fn remove_swapchain(&self, key: OverlayKey) {
+        #[macros::any_graphics(AnySwapchainMap)]
+        fn remove<G: xr::Graphics>(map: &mut SwapchainMap<G>, key: OverlayKey) {
+            map.remove(key);
+        }
+
+        if let Some(map) = self.swapchains.lock().unwrap().as_mut() {
+            map.with_any_graphics_mut::<remove>(key);
+        }
+    }
+}
+
+/// Ends frames for overlay apps, by calling WaitGetPoses the way a game would.
+struct OverlayFrameLoop {
+    stop: Arc<AtomicBool>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl OverlayFrameLoop {
+    fn spawn(compositor: Weak<Compositor>) -> Option<Self> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let thread = std::thread::Builder::new()
+            .name("xrizer-overlays".into())
+            .spawn(move || {
+                while !thread_stop.load(Ordering::Relaxed) {
+                    let Some(compositor) = compositor.upgrade() else {
+                        break;
+                    };
+                    compositor.WaitGetPoses(std::ptr::null_mut(), 0, std::ptr::null_mut(), 0);
+                }
+            });
+        match thread {
+            Ok(thread) => {
+                info!("Compositing overlays on a separate thread");
+                Some(Self {
+                    stop,
+                    thread: Some(thread),
+                })
+            }
+            Err(e) => {
+                error!("Couldn't spawn overlay frame thread: {e}");
+                None
+            }
+        }
+    }
+}
+
+impl Drop for OverlayFrameLoop {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum OverlayTransform {
+    Absolute(vr::ETrackingUniverseOrigin, vr::HmdMatrix34_t),
+    /// Follows a tracked device, such as a controller for wrist-mounted overlays.
+    TrackedDeviceRelative(vr::TrackedDeviceIndex_t, vr::HmdMatrix34_t),
+}
+
+/// Poses of the tracked devices that overlays are attached to, in the current origin.
+type DevicePoses = HashMap<vr::TrackedDeviceIndex_t, xr::Posef>;
+
+/// `child`, given relative to `parent`, in the space `parent` is in.
+fn compose(parent: xr::Posef, child: xr::Posef) -> xr::Posef {
+    let parent_rotation = Quat::from_xyzw(
+        parent.orientation.x,
+        parent.orientation.y,
+        parent.orientation.z,
+        parent.orientation.w,
+    );
+    let parent_position = vec3(parent.position.x, parent.position.y, parent.position.z);
+    let rotation = Quat::from_xyzw(
+        child.orientation.x,
+        child.orientation.y,
+        child.orientation.z,
+        child.orientation.w,
+    );
+    let position = vec3(child.position.x, child.position.y, child.position.z);
+    let rotation = parent_rotation * rotation;
+    let position = parent_position + parent_rotation.mul_vec3(position);
+    xr::Posef {
+        orientation: xr::Quaternionf {
+            x: rotation.x,
+            y: rotation.y,
+            z: rotation.z,
+            w: rotation.w,
+        },
+        position: xr::Vector3f {
+            x: position.x,
+            y: position.y,
+            z: position.z,
+        },
+    }
+}
+
 enum OverlayKind {
     Quad,
-    Curved { curvature: f32 },
-    Sphere,
+    Curved {
+        curvature: f32,
+    },
+    /// Infinitely far away, so it doesn't need to follow the HMD.
+    Sphere {
+        eye: xr::EyeVisibility,
+    },
+    /// Also infinitely far away, with a face per cube map layer.
+    Cube,
 }
 
 struct Overlay {
@@ -417,14 +1395,29 @@ struct Overlay {
     name: CString,
     /// Only allowed to be Some if KHR_composition_layer_color_scale_bias is active
     alpha: Option<f32>,
+    /// Tint multiplied into the texture. Like alpha, only Some if the color scale extension is
+    /// active.
+    color: Option<[f32; 3]>,
+    /// Width over height of a texel.
+    texel_aspect: f32,
     width: f32,
     visible: bool,
     kind: OverlayKind,
+    /// VROverlayFlags bits
+    flags: u32,
+    rendering_pid: u32,
+    /// Created by the scene application, as opposed to an overlay app or xrizer itself.
+    scene_app: bool,
     z_order: i64,
     bounds: vr::VRTextureBounds_t,
-    transform: Option<(vr::ETrackingUniverseOrigin, vr::HmdMatrix34_t)>,
+    transform: Option<OverlayTransform>,
     compositor: Option<SupportedBackend>,
     rect: Option<xr::Rect2Di>,
+    input_method: vr::VROverlayInputMethod,
+    mouse_scale: vr::HmdVector2_t,
+    events: VecDeque<vr::VREvent_t>,
+    /// Copies GL textures for a Vulkan compositor.
+    gl_interop: Option<GlInterop>,
 }
 
 impl Overlay {
@@ -433,9 +1426,14 @@ impl Overlay {
             key,
             name,
             alpha: None,
+            color: None,
+            texel_aspect: 1.0,
             width: 1.0,
             visible: false,
             kind: OverlayKind::Quad,
+            flags: 0,
+            rendering_pid: 0,
+            scene_app: false,
             z_order: 0,
             bounds: vr::VRTextureBounds_t {
                 uMin: 0.0,
@@ -446,18 +1444,273 @@ impl Overlay {
             transform: None,
             compositor: None,
             rect: None,
+            input_method: vr::VROverlayInputMethod::None,
+            mouse_scale: vr::HmdVector2_t { v: [1.0, 1.0] },
+            events: VecDeque::new(),
+            gl_interop: None,
+        }
+    }
+
+    /// The origin the overlay is placed in and its pose there. Overlays without a transform sit
+    /// half a meter in front of the current origin. Returns None if the overlay is attached to a
+    /// device that isn't being tracked.
+    fn placement(
+        &self,
+        current_origin: vr::ETrackingUniverseOrigin,
+        devices: &DevicePoses,
+    ) -> Option<(vr::ETrackingUniverseOrigin, xr::Posef)> {
+        match self.transform {
+            None => Some((
+                current_origin,
+                xr::Posef {
+                    position: xr::Vector3f {
+                        x: 0.0,
+                        y: 0.0,
+                        z: -0.5,
+                    },
+                    orientation: xr::Quaternionf::IDENTITY,
+                },
+            )),
+            Some(OverlayTransform::Absolute(origin, transform)) => Some((origin, transform.into())),
+            Some(OverlayTransform::TrackedDeviceRelative(device, transform)) => {
+                let device = devices.get(&device)?;
+                Some((current_origin, compose(*device, transform.into())))
+            }
+        }
+    }
+
+    fn tracked_device(&self) -> Option<vr::TrackedDeviceIndex_t> {
+        match self.transform {
+            Some(OverlayTransform::TrackedDeviceRelative(device, _)) => Some(device),
+            _ => None,
+        }
+    }
+
+    fn push_event(
+        &mut self,
+        ty: vr::EVREventType,
+        device: vr::TrackedDeviceIndex_t,
+        data: vr::VREvent_Data_t,
+    ) {
+        if self.events.len() == MAX_QUEUED_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(vr::VREvent_t {
+            eventType: ty as u32,
+            trackedDeviceIndex: device,
+            eventAgeSeconds: 0.0,
+            data,
+        });
+    }
+
+    /// Shows or hides the overlay, letting it know if that changed.
+    fn set_visible(&mut self, key: OverlayKey, visible: bool) {
+        if visible == self.visible {
+            return;
+        }
+        self.visible = visible;
+        let ty = if visible {
+            vr::EVREventType::OverlayShown
+        } else {
+            vr::EVREventType::OverlayHidden
+        };
+        let overlay = vr::VREvent_Overlay_t {
+            overlayHandle: key.data().as_ffi(),
+            ..Default::default()
+        };
+        self.push_event(
+            ty,
+            vr::k_unTrackedDeviceIndexInvalid,
+            vr::VREvent_Data_t { overlay },
+        );
+    }
+
+    fn has_flag(&self, flag: vr::VROverlayFlags) -> bool {
+        self.flags & flag as u32 != 0
+    }
+
+    /// Overlays are stacked from the lowest of these up: the skybox, then the scene application's
+    /// overlays, then those of other applications and xrizer's own, each by their sort order.
+    fn stack_order(&self) -> (bool, bool, i64) {
+        let skybox = self.z_order == SKYBOX_Z_ORDER;
+        let over_scene_overlays =
+            !self.scene_app || self.has_flag(vr::VROverlayFlags::SortWithNonSceneOverlays);
+        (!skybox, over_scene_overlays, self.z_order)
+    }
+
+    /// In meters, following the aspect ratio of what each eye sees of the texture.
+    fn height(&self) -> f32 {
+        self.rect.map_or(self.width, |rect| {
+            let extent = self.eye_rects(rect)[0].1.extent;
+            extent.height as f32 * self.width / (extent.width as f32 * self.texel_aspect)
+        })
+    }
+
+    /// The parts of the texture each eye sees. Side-by-side stereo overlays show each eye one
+    /// half of the texture.
+    fn eye_rects(&self, rect: xr::Rect2Di) -> Vec<(xr::EyeVisibility, xr::Rect2Di)> {
+        let parallel = self.has_flag(vr::VROverlayFlags::SideBySide_Parallel);
+        if !parallel && !self.has_flag(vr::VROverlayFlags::SideBySide_Crossed) {
+            return vec![(xr::EyeVisibility::BOTH, rect)];
+        }
+        let half = xr::Extent2Di {
+            width: rect.extent.width / 2,
+            height: rect.extent.height,
+        };
+        let left = xr::Rect2Di {
+            offset: rect.offset,
+            extent: half,
+        };
+        let right = xr::Rect2Di {
+            offset: xr::Offset2Di {
+                x: rect.offset.x + half.width,
+                y: rect.offset.y,
+            },
+            extent: half,
+        };
+        if parallel {
+            vec![
+                (xr::EyeVisibility::LEFT, left),
+                (xr::EyeVisibility::RIGHT, right),
+            ]
+        } else {
+            // Crossed: the left eye's image is on the right.
+            vec![
+                (xr::EyeVisibility::LEFT, right),
+                (xr::EyeVisibility::RIGHT, left),
+            ]
+        }
+    }
+
+    /// What to scale the texture's colors by, if not left as is.
+    fn color_scale(&self) -> Option<xr::Color4f> {
+        if self.alpha.is_none() && self.color.is_none() {
+            return None;
+        }
+        let [r, g, b] = self.color.unwrap_or([1.0; 3]);
+        Some(xr::Color4f {
+            r,
+            g,
+            b,
+            a: self.alpha.unwrap_or(1.0),
+        })
+    }
+
+    pub fn set_texture(
+        &mut self,
+        key: OverlayKey,
+        session_data: &SessionData,
+        texture: vr::Texture_t,
+    ) {
+        let backend = self
+            .compositor
+            .get_or_insert_with(|| SupportedBackend::new(&texture, self.bounds));
+
+        #[macros::any_graphics(SupportedBackend)]
+        fn create_swapchain_map<G: GraphicsBackend>(_: &G) -> AnySwapchainMap
+        where
+            AnySwapchainMap: From<SwapchainMap<G::Api>>,
+        {
+            SwapchainMap::<G::Api>::default().into()
+        }
+
+        let mut swapchains = session_data.overlay_data.swapchains.lock().unwrap();
+        let swapchains =
+            swapchains.get_or_insert_with(|| backend.with_any_graphics::<create_swapchain_map>(()));
+
+        #[macros::any_graphics(SupportedBackend)]
+        fn set_swapchain_texture<G: GraphicsBackend>(
+            backend: &mut G,
+            session_data: &SessionData,
+            overlay: &mut Overlay,
+            map: &mut AnySwapchainMap,
+            key: OverlayKey,
+            texture: vr::Texture_t,
+        ) -> xr::Extent2Di
+        where
+            for<'a> &'a mut SwapchainMap<G::Api>:
+                TryFrom<&'a mut AnySwapchainMap, Error: std::fmt::Display>,
+            for<'a> &'a GraphicalSession: TryInto<&'a Session<G::Api>, Error: std::fmt::Display>,
+            <G::Api as xr::Graphics>::Format: Eq,
+        {
+            let map: &mut SwapchainMap<G::Api> = map.try_into().unwrap_or_else(|e| {
+                panic!(
+                    "Received different texture type for overlay than current ({}) - {e}",
+                    std::any::type_name::<G::Api>()
+                );
+            });
+            let b_texture = G::get_texture(&texture);
+            let tex_swapchain_info = backend.swapchain_info_for_texture(
+                b_texture,
+                overlay.bounds,
+                texture.eColorSpace,
+                vr::EVRSubmitFlags::Default,
+            );
+            let mut create_swapchain = || {
+                let mut info = backend.swapchain_info_for_texture(
+                    b_texture,
+                    overlay.bounds,
+                    texture.eColorSpace,
+                    vr::EVRSubmitFlags::Default,
+                );
+                let initial_format = info.format;
+                session_data.check_format::<G>(&mut info);
+                let swapchain = session_data.create_swapchain(&info).unwrap();
+                let images = swapchain
+                    .enumerate_images()
+                    .expect("Couldn't enumerate swapchain images");
+                backend.store_swapchain_images(images, info.format);
+                SwapchainData {
+                    swapchain,
+                    info,
+                    initial_format,
+                }
+            };
+            let swapchain = {
+                let data = map
+                    .entry(key)
+                    .unwrap()
+                    .or_insert_with(&mut create_swapchain);
+                if !is_usable_swapchain(&data.info, data.initial_format, &tex_swapchain_info) {
+                    *data = create_swapchain();
+                }
+                &mut data.swapchain
+            };
+            let idx = swapchain.acquire_image().unwrap();
+            swapchain.wait_image(xr::Duration::INFINITE).unwrap();
+
+            let extent = backend.copy_overlay_to_swapchain(b_texture, overlay.bounds, idx as usize);
+            swapchain.release_image().unwrap();
+
+            extent
         }
+
+        let mut backend = self.compositor.take().unwrap();
+        let extent = backend.with_any_graphics_mut::<set_swapchain_texture>((
+            session_data,
+            self,
+            swapchains,
+            key,
+            texture,
+        ));
+        self.compositor = Some(backend);
+        self.rect = Some(xr::Rect2Di {
+            extent,
+            offset: xr::Offset2Di::default(),
+        });
     }
 
-    pub fn set_texture(
+    /// Copies skybox textures, in OpenVR's front, back, left, right, top, bottom order, to a cube
+    /// map. Returns false if the backend can't make one.
+    pub fn set_cube_textures(
         &mut self,
         key: OverlayKey,
         session_data: &SessionData,
-        texture: vr::Texture_t,
-    ) {
+        textures: &[vr::Texture_t; 6],
+    ) -> bool {
         let backend = self
             .compositor
-            .get_or_insert_with(|| SupportedBackend::new(&texture, self.bounds));
+            .get_or_insert_with(|| SupportedBackend::new(&textures[0], self.bounds));
 
         #[macros::any_graphics(SupportedBackend)]
         fn create_swapchain_map<G: GraphicsBackend>(_: &G) -> AnySwapchainMap
@@ -472,14 +1725,13 @@ impl Overlay {
             swapchains.get_or_insert_with(|| backend.with_any_graphics::<create_swapchain_map>(()));
 
         #[macros::any_graphics(SupportedBackend)]
-        fn set_swapchain_texture<G: GraphicsBackend>(
+        fn set_swapchain_cube<G: GraphicsBackend>(
             backend: &mut G,
             session_data: &SessionData,
-            overlay: &mut Overlay,
             map: &mut AnySwapchainMap,
             key: OverlayKey,
-            texture: vr::Texture_t,
-        ) -> xr::Extent2Di
+            textures: &[vr::Texture_t; 6],
+        ) -> Option<xr::Extent2Di>
         where
             for<'a> &'a mut SwapchainMap<G::Api>:
                 TryFrom<&'a mut AnySwapchainMap, Error: std::fmt::Display>,
@@ -492,15 +1744,14 @@ impl Overlay {
                     std::any::type_name::<G::Api>()
                 );
             });
-            let b_texture = G::get_texture(&texture);
-            let tex_swapchain_info =
-                backend.swapchain_info_for_texture(b_texture, overlay.bounds, texture.eColorSpace);
+            // Cube map layers are +X, -X, +Y, -Y, +Z, -Z, and the front of the skybox is -Z.
+            let faces = [3, 2, 4, 5, 1, 0].map(|idx| G::get_texture(&textures[idx]));
+            let color_space = textures[0].eColorSpace;
+            let cube_swapchain_info = backend.swapchain_info_for_cube(faces[0], color_space)?;
             let mut create_swapchain = || {
-                let mut info = backend.swapchain_info_for_texture(
-                    b_texture,
-                    overlay.bounds,
-                    texture.eColorSpace,
-                );
+                let mut info = backend
+                    .swapchain_info_for_cube(faces[0], color_space)
+                    .unwrap();
                 let initial_format = info.format;
                 session_data.check_format::<G>(&mut info);
                 let swapchain = session_data.create_swapchain(&info).unwrap();
@@ -519,34 +1770,180 @@ impl Overlay {
                     .entry(key)
                     .unwrap()
                     .or_insert_with(&mut create_swapchain);
-                if !is_usable_swapchain(&data.info, data.initial_format, &tex_swapchain_info) {
+                if !is_usable_swapchain(&data.info, data.initial_format, &cube_swapchain_info) {
                     *data = create_swapchain();
                 }
                 &mut data.swapchain
             };
             let idx = swapchain.acquire_image().unwrap();
             swapchain.wait_image(xr::Duration::INFINITE).unwrap();
-
-            let extent = backend.copy_overlay_to_swapchain(b_texture, overlay.bounds, idx as usize);
+            backend.copy_textures_to_cube(faces, idx as usize);
             swapchain.release_image().unwrap();
 
-            extent
+            Some(xr::Extent2Di {
+                width: cube_swapchain_info.width as i32,
+                height: cube_swapchain_info.height as i32,
+            })
         }
 
         let mut backend = self.compositor.take().unwrap();
-        let extent = backend.with_any_graphics_mut::<set_swapchain_texture>((
+        let extent = backend.with_any_graphics_mut::<set_swapchain_cube>((
             session_data,
-            self,
             swapchains,
             key,
-            texture,
+            textures,
         ));
         self.compositor = Some(backend);
+        let Some(extent) = extent else {
+            return false;
+        };
+        self.rect = Some(xr::Rect2Di {
+            extent,
+            offset: xr::Offset2Di::default(),
+        });
+        true
+    }
+
+    /// Like set_texture, but with sRGB RGBA8 pixels from the CPU. Overlays without a backend yet
+    /// need one from OverlayMan::new_pixels_backend.
+    pub fn set_pixels(
+        &mut self,
+        key: OverlayKey,
+        session_data: &SessionData,
+        pixels: &[u8],
+        extent: xr::Extent2Di,
+        new_backend: Option<SupportedBackend>,
+    ) -> Result<(), vr::EVROverlayError> {
+        if self.compositor.is_none() {
+            self.compositor = Some(new_backend.ok_or(vr::EVROverlayError::RequestFailed)?);
+        }
+        let backend = self.compositor.as_mut().unwrap();
+        // Textures are cropped when they're copied, pixels are cropped up front.
+        let cropped = crop_to_bounds(pixels, extent, self.bounds);
+        let (pixels, extent) = cropped
+            .as_ref()
+            .map_or((pixels, extent), |(pixels, extent)| (pixels, *extent));
+
+        #[macros::any_graphics(SupportedBackend)]
+        fn create_swapchain_map<G: GraphicsBackend>(_: &G) -> AnySwapchainMap
+        where
+            AnySwapchainMap: From<SwapchainMap<G::Api>>,
+        {
+            SwapchainMap::<G::Api>::default().into()
+        }
+
+        let mut swapchains = session_data.overlay_data.swapchains.lock().unwrap();
+        let swapchains =
+            swapchains.get_or_insert_with(|| backend.with_any_graphics::<create_swapchain_map>(()));
+
+        #[macros::any_graphics(SupportedBackend)]
+        fn set_swapchain_pixels<G: GraphicsBackend>(
+            backend: &mut G,
+            session_data: &SessionData,
+            map: &mut AnySwapchainMap,
+            key: OverlayKey,
+            pixels: &[u8],
+            extent: xr::Extent2Di,
+        ) where
+            for<'a> &'a mut SwapchainMap<G::Api>:
+                TryFrom<&'a mut AnySwapchainMap, Error: std::fmt::Display>,
+            for<'a> &'a GraphicalSession: TryInto<&'a Session<G::Api>, Error: std::fmt::Display>,
+            <G::Api as xr::Graphics>::Format: Eq,
+        {
+            let map: &mut SwapchainMap<G::Api> = map.try_into().unwrap_or_else(|e| {
+                panic!(
+                    "Received different graphics API for overlay than current ({}) - {e}",
+                    std::any::type_name::<G::Api>()
+                );
+            });
+            let pixels_swapchain_info = backend.swapchain_info_for_pixels(extent);
+            let mut create_swapchain = || {
+                let mut info = backend.swapchain_info_for_pixels(extent);
+                let initial_format = info.format;
+                session_data.check_format::<G>(&mut info);
+                let swapchain = session_data.create_swapchain(&info).unwrap();
+                let images = swapchain
+                    .enumerate_images()
+                    .expect("Couldn't enumerate swapchain images");
+                backend.store_swapchain_images(images, info.format);
+                SwapchainData {
+                    swapchain,
+                    info,
+                    initial_format,
+                }
+            };
+            let swapchain = {
+                let data = map
+                    .entry(key)
+                    .unwrap()
+                    .or_insert_with(&mut create_swapchain);
+                if !is_usable_swapchain(&data.info, data.initial_format, &pixels_swapchain_info) {
+                    *data = create_swapchain();
+                }
+                &mut data.swapchain
+            };
+            let idx = swapchain.acquire_image().unwrap();
+            swapchain.wait_image(xr::Duration::INFINITE).unwrap();
+            backend.copy_pixels_to_swapchain(pixels, extent, idx as usize);
+            swapchain.release_image().unwrap();
+        }
+
+        backend.with_any_graphics_mut::<set_swapchain_pixels>((
+            session_data,
+            swapchains,
+            key,
+            pixels,
+            extent,
+        ));
         self.rect = Some(xr::Rect2Di {
             extent,
             offset: xr::Offset2Di::default(),
         });
+        Ok(())
+    }
+}
+
+/// Crops tightly packed RGBA8 pixels, top row first, to the part inside the texture bounds.
+/// Bounds with a min larger than the max flip the image. Returns None for the full image.
+fn crop_to_bounds(
+    pixels: &[u8],
+    extent: xr::Extent2Di,
+    bounds: vr::VRTextureBounds_t,
+) -> Option<(Vec<u8>, xr::Extent2Di)> {
+    let vr::VRTextureBounds_t {
+        uMin: u_min,
+        vMin: v_min,
+        uMax: u_max,
+        vMax: v_max,
+    } = bounds;
+    if (u_min, v_min, u_max, v_max) == (0.0, 0.0, 1.0, 1.0) {
+        return None;
+    }
+    let span = |min: f32, max: f32, size: i32| {
+        let to_px = |t: f32| (t.clamp(0.0, 1.0) * size as f32).round() as i32;
+        let start = to_px(min.min(max)).min(size - 1);
+        let end = to_px(min.max(max)).max(start + 1);
+        let mut span: Vec<_> = (start..end).collect();
+        if min > max {
+            span.reverse();
+        }
+        span
+    };
+    let columns = span(u_min, u_max, extent.width);
+    let rows = span(v_min, v_max, extent.height);
+
+    let mut cropped = Vec::with_capacity(columns.len() * rows.len() * 4);
+    for y in &rows {
+        for x in &columns {
+            let idx = (y * extent.width + x) as usize * 4;
+            cropped.extend_from_slice(&pixels[idx..idx + 4]);
+        }
     }
+    let extent = xr::Extent2Di {
+        width: columns.len() as i32,
+        height: rows.len() as i32,
+    };
+    Some((cropped, extent))
 }
 
 macro_rules! get_overlay {
@@ -577,10 +1974,21 @@ impl vr::IVROverlay027_Interface for OverlayMan {
         if handle.is_null() {
             return vr::EVROverlayError::InvalidParameter;
         }
+        if key.to_bytes().len() >= vr::k_unVROverlayMaxKeyLength as usize {
+            return vr::EVROverlayError::KeyTooLong;
+        }
+        if name.to_bytes().len() >= vr::k_unVROverlayMaxNameLength as usize {
+            return vr::EVROverlayError::NameTooLong;
+        }
 
         let mut overlays = self.overlays.write().unwrap();
-        let ret_key = overlays.insert(Overlay::new(key.into(), name.into()));
         let mut key_to_overlay = self.key_to_overlay.write().unwrap();
+        if key_to_overlay.contains_key(key) {
+            return vr::EVROverlayError::KeyInUse;
+        }
+        let mut overlay = Overlay::new(key.into(), name.into());
+        overlay.scene_app = !self.openxr.overlay_app;
+        let ret_key = overlays.insert(overlay);
         key_to_overlay.insert(key.into(), ret_key);
 
         unsafe {
@@ -615,7 +2023,7 @@ impl vr::IVROverlay027_Interface for OverlayMan {
         get_overlay!(self, handle, mut overlay);
 
         debug!("showing overlay {:?}", overlay.name);
-        overlay.visible = true;
+        overlay.set_visible(OverlayKey::from(KeyData::from_ffi(handle)), true);
         vr::EVROverlayError::None
     }
 
@@ -623,7 +2031,7 @@ impl vr::IVROverlay027_Interface for OverlayMan {
         get_overlay!(self, handle, mut overlay);
 
         debug!("hiding overlay {:?}", overlay.name);
-        overlay.visible = false;
+        overlay.set_visible(OverlayKey::from(KeyData::from_ffi(handle)), false);
         vr::EVROverlayError::None
     }
 
@@ -669,16 +2077,56 @@ impl vr::IVROverlay027_Interface for OverlayMan {
         handle: vr::VROverlayHandle_t,
         texture: *const vr::Texture_t,
     ) -> vr::EVROverlayError {
+        if self.openxr.overlay_app && !texture.is_null() {
+            // Must happen before the overlay is locked, since this may restart the session.
+            self.start_overlay_frames(unsafe { &*texture });
+        }
+        let key = OverlayKey::from(KeyData::from_ffi(handle));
+        let session_data = self.openxr.session_data.get();
+        let is_gl = unsafe { texture.as_ref() }
+            .is_some_and(|texture| texture.eType == vr::ETextureType::OpenGL);
+        let backend = is_gl
+            .then(|| self.new_pixels_backend(Some(key), &session_data))
+            .flatten();
         get_overlay!(self, handle, mut overlay);
         if texture.is_null() {
-            vr::EVROverlayError::InvalidParameter
+            return vr::EVROverlayError::InvalidParameter;
+        }
+        let texture = unsafe { texture.read() };
+        if is_gl {
+            // GL textures can only go straight into a GL session's swapchains; anything else gets
+            // a copy through Vulkan.
+            if let Some(backend @ SupportedBackend::Vulkan(_)) = backend {
+                overlay.compositor.get_or_insert(backend);
+            }
+            if let (None, Some(SupportedBackend::Vulkan(vk))) =
+                (&overlay.gl_interop, &overlay.compositor)
+            {
+                overlay.gl_interop = Some(GlInterop::new(vk));
+            }
+        }
+        if let Some(mut interop) = overlay.gl_interop.take() {
+            // GL rows go bottom up, unlike Vulkan's.
+            let bounds = overlay.bounds;
+            overlay.bounds = vr::VRTextureBounds_t {
+                vMin: bounds.vMax,
+                vMax: bounds.vMin,
+                ..bounds
+            };
+            let gl_texture = GlData::get_texture(&texture);
+            let copied = interop.copy(gl_texture, texture.eColorSpace, |texture| {
+                overlay.set_texture(key, &session_data, *texture)
+            });
+            overlay.bounds = bounds;
+            overlay.gl_interop = Some(interop);
+            if copied.is_none() {
+                return vr::EVROverlayError::InvalidTexture;
+            }
         } else {
-            let texture = unsafe { texture.read() };
-            let key = OverlayKey::from(KeyData::from_ffi(handle));
-            overlay.set_texture(key, &self.openxr.session_data.get(), texture);
-            debug!("set overlay texture for {:?}", overlay.name);
-            vr::EVROverlayError::None
+            overlay.set_texture(key, &session_data, texture);
         }
+        debug!("set overlay texture for {:?}", overlay.name);
+        vr::EVROverlayError::None
     }
 
     fn CloseMessageOverlay(&self) {
@@ -696,89 +2144,208 @@ impl vr::IVROverlay027_Interface for OverlayMan {
         todo!()
     }
     fn SetKeyboardPositionForOverlay(&self, _: vr::VROverlayHandle_t, _: vr::HmdRect2_t) {
-        todo!()
+        // The keyboard doesn't move out of the way of anything.
+        crate::warn_unimplemented!("SetKeyboardPositionForOverlay");
     }
     fn SetKeyboardTransformAbsolute(
         &self,
-        _: vr::ETrackingUniverseOrigin,
-        _: *const vr::HmdMatrix34_t,
+        origin: vr::ETrackingUniverseOrigin,
+        transform: *const vr::HmdMatrix34_t,
     ) {
-        todo!()
+        if transform.is_null() {
+            return;
+        }
+        let transform = unsafe { transform.read() };
+        let mut keyboard = self.keyboard.lock().unwrap();
+        keyboard.transform = Some((origin, transform));
+        // Move it if it's already shown.
+        if let Some(key) = keyboard.overlay.filter(|_| keyboard.input.is_some()) {
+            if let Some(overlay) = self.overlays.write().unwrap().get_mut(key) {
+                overlay.transform = Some(OverlayTransform::Absolute(origin, transform));
+            }
+        }
     }
     fn HideKeyboard(&self) {
-        todo!()
+        let mut keyboard = self.keyboard.lock().unwrap();
+        let Some(input) = keyboard.input.take() else {
+            return;
+        };
+        keyboard.last_text = input.text;
+        if let Some(key) = keyboard.overlay {
+            if let Some(overlay) = self.overlays.write().unwrap().get_mut(key) {
+                overlay.visible = false;
+            }
+        }
     }
-    fn GetKeyboardText(&self, _: *mut c_char, _: u32) -> u32 {
-        todo!()
+    fn GetKeyboardText(&self, text: *mut c_char, size: u32) -> u32 {
+        let keyboard = self.keyboard.lock().unwrap();
+        let current = keyboard.input.as_ref().map(|input| &input.text);
+        let data = CString::new(current.unwrap_or(&keyboard.last_text).replace('\0', "")).unwrap();
+        let data = data.as_bytes_with_nul();
+        if !text.is_null() && size > 0 {
+            let buf = unsafe { std::slice::from_raw_parts_mut(text as *mut u8, size as usize) };
+            let len = data.len().min(buf.len() - 1);
+            buf[..len].copy_from_slice(&data[..len]);
+            buf[len] = 0;
+        }
+        data.len() as u32
     }
     fn ShowKeyboardForOverlay(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: vr::EGamepadTextInputMode,
-        _: vr::EGamepadTextInputLineMode,
-        _: u32,
-        _: *const c_char,
-        _: u32,
-        _: *const c_char,
-        _: u64,
+        handle: vr::VROverlayHandle_t,
+        mode: vr::EGamepadTextInputMode,
+        line_mode: vr::EGamepadTextInputLineMode,
+        flags: u32,
+        description: *const c_char,
+        max_chars: u32,
+        existing_text: *const c_char,
+        user_value: u64,
     ) -> vr::EVROverlayError {
-        todo!()
+        let key = OverlayKey::from(KeyData::from_ffi(handle));
+        if !self.overlays.read().unwrap().contains_key(key) {
+            return vr::EVROverlayError::UnknownOverlay;
+        }
+        self.show_keyboard(
+            Some(key),
+            mode,
+            line_mode,
+            flags,
+            description,
+            max_chars,
+            existing_text,
+            user_value,
+        )
     }
     fn ShowKeyboard(
         &self,
-        _: vr::EGamepadTextInputMode,
-        _: vr::EGamepadTextInputLineMode,
-        _: u32,
-        _: *const c_char,
-        _: u32,
-        _: *const c_char,
-        _: u64,
+        mode: vr::EGamepadTextInputMode,
+        line_mode: vr::EGamepadTextInputLineMode,
+        flags: u32,
+        description: *const c_char,
+        max_chars: u32,
+        existing_text: *const c_char,
+        user_value: u64,
     ) -> vr::EVROverlayError {
-        crate::warn_unimplemented!("ShowKeyboard");
-        vr::EVROverlayError::RequestFailed
+        self.show_keyboard(
+            None,
+            mode,
+            line_mode,
+            flags,
+            description,
+            max_chars,
+            existing_text,
+            user_value,
+        )
     }
     fn GetPrimaryDashboardDevice(&self) -> vr::TrackedDeviceIndex_t {
-        todo!()
-    }
-    fn ShowDashboard(&self, _: *const c_char) {
-        todo!()
+        self.pointer.lock().unwrap().hand as vr::TrackedDeviceIndex_t
+    }
+    fn ShowDashboard(&self, overlay_to_show: *const c_char) {
+        let mut dashboard = self.dashboard.lock().unwrap();
+        if !overlay_to_show.is_null() {
+            let key = unsafe { CStr::from_ptr(overlay_to_show) };
+            let overlay = self.key_to_overlay.read().unwrap().get(key).copied();
+            match overlay.filter(|overlay| dashboard.tab(*overlay).is_some()) {
+                Some(overlay) => dashboard.active = Some(overlay),
+                None => debug!("ShowDashboard: {key:?} isn't a dashboard overlay"),
+            }
+        }
+        if dashboard.tabs.is_empty() {
+            debug!("ShowDashboard: no dashboard overlays to show");
+            return;
+        }
+        self.set_dashboard_visible(&mut dashboard, &self.openxr.session_data.get(), true);
+        dashboard.layout(&mut self.overlays.write().unwrap());
     }
     fn GetDashboardOverlaySceneProcess(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: *mut u32,
+        handle: vr::VROverlayHandle_t,
+        pid: *mut u32,
     ) -> vr::EVROverlayError {
-        todo!()
+        let dashboard = self.dashboard.lock().unwrap();
+        let Some(tab) = dashboard.tab(OverlayKey::from(KeyData::from_ffi(handle))) else {
+            return vr::EVROverlayError::UnknownOverlay;
+        };
+        let Some(pid) = (unsafe { pid.as_mut() }) else {
+            return vr::EVROverlayError::InvalidParameter;
+        };
+        *pid = tab.scene_process;
+        vr::EVROverlayError::None
     }
     fn SetDashboardOverlaySceneProcess(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: u32,
+        handle: vr::VROverlayHandle_t,
+        pid: u32,
     ) -> vr::EVROverlayError {
-        todo!()
+        let key = OverlayKey::from(KeyData::from_ffi(handle));
+        let mut dashboard = self.dashboard.lock().unwrap();
+        let Some(tab) = dashboard.tabs.iter_mut().find(|tab| tab.main == key) else {
+            return vr::EVROverlayError::UnknownOverlay;
+        };
+        // There's only ever one scene process.
+        tab.scene_process = pid;
+        vr::EVROverlayError::None
     }
-    fn IsActiveDashboardOverlay(&self, _: vr::VROverlayHandle_t) -> bool {
-        todo!()
+    fn IsActiveDashboardOverlay(&self, handle: vr::VROverlayHandle_t) -> bool {
+        let dashboard = self.dashboard.lock().unwrap();
+        dashboard.visible && dashboard.active == Some(OverlayKey::from(KeyData::from_ffi(handle)))
     }
     fn IsDashboardVisible(&self) -> bool {
-        false
+        self.dashboard.lock().unwrap().visible
     }
     fn CreateDashboardOverlay(
         &self,
-        _: *const c_char,
-        _: *const c_char,
-        _: *mut vr::VROverlayHandle_t,
-        _: *mut vr::VROverlayHandle_t,
+        key: *const c_char,
+        name: *const c_char,
+        main_handle: *mut vr::VROverlayHandle_t,
+        thumbnail_handle: *mut vr::VROverlayHandle_t,
     ) -> vr::EVROverlayError {
-        todo!()
+        if thumbnail_handle.is_null() {
+            return vr::EVROverlayError::InvalidParameter;
+        }
+        let ret = self.CreateOverlay(key, name, main_handle);
+        if ret != vr::EVROverlayError::None {
+            return ret;
+        }
+
+        let main = OverlayKey::from(KeyData::from_ffi(unsafe { main_handle.read() }));
+        let mut dashboard = self.dashboard.lock().unwrap();
+        let mut overlays = self.overlays.write().unwrap();
+        // Thumbnails can't be found by their key.
+        let mut thumbnail = Overlay::new(overlays[main].key.clone(), overlays[main].name.clone());
+        // So they can be clicked on to switch to their overlay.
+        thumbnail.input_method = vr::VROverlayInputMethod::Mouse;
+        let thumbnail = overlays.insert(thumbnail);
+        dashboard.tabs.push(dashboard::DashboardTab {
+            main,
+            thumbnail,
+            scene_process: 0,
+        });
+        unsafe {
+            thumbnail_handle.write(thumbnail.data().as_ffi());
+        }
+
+        debug!("created dashboard overlay {:?}", overlays[main].name);
+        vr::EVROverlayError::None
     }
     fn GetOverlayTextureSize(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: *mut u32,
-        _: *mut u32,
+        handle: vr::VROverlayHandle_t,
+        width: *mut u32,
+        height: *mut u32,
     ) -> vr::EVROverlayError {
-        todo!()
+        get_overlay!(self, handle, overlay);
+        if width.is_null() || height.is_null() {
+            return vr::EVROverlayError::InvalidParameter;
+        }
+        let Some(rect) = overlay.rect else {
+            return vr::EVROverlayError::InvalidTexture;
+        };
+        unsafe {
+            *width = rect.extent.width as u32;
+            *height = rect.extent.height as u32;
+        }
+        vr::EVROverlayError::None
     }
     fn ReleaseNativeOverlayHandle(
         &self,
@@ -803,23 +2370,65 @@ impl vr::IVROverlay027_Interface for OverlayMan {
     }
     fn SetOverlayFromFile(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: *const c_char,
+        handle: vr::VROverlayHandle_t,
+        path: *const c_char,
     ) -> vr::EVROverlayError {
-        todo!()
+        if path.is_null() {
+            return vr::EVROverlayError::InvalidParameter;
+        }
+        let path = unsafe { CStr::from_ptr(path) };
+        let Ok(path) = path.to_str() else {
+            return vr::EVROverlayError::InvalidParameter;
+        };
+        let image = match image::open(path) {
+            Ok(image) => image.into_rgba8(),
+            Err(e) => {
+                error!("Couldn't load overlay image {path:?}: {e}");
+                return vr::EVROverlayError::UnableToLoadFile;
+            }
+        };
+        let extent = xr::Extent2Di {
+            width: image.width() as i32,
+            height: image.height() as i32,
+        };
+        self.set_overlay_pixels(handle, image.as_raw(), extent)
     }
     fn SetOverlayRaw(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: *mut c_void,
-        _: u32,
-        _: u32,
-        _: u32,
+        handle: vr::VROverlayHandle_t,
+        buffer: *mut c_void,
+        width: u32,
+        height: u32,
+        bytes_per_pixel: u32,
     ) -> vr::EVROverlayError {
-        todo!()
+        if buffer.is_null() || width == 0 || height == 0 || !(1..=4).contains(&bytes_per_pixel) {
+            return vr::EVROverlayError::InvalidParameter;
+        }
+        let bpp = bytes_per_pixel as usize;
+        let len = width as usize * height as usize * bpp;
+        let raw = unsafe { std::slice::from_raw_parts(buffer.cast::<u8>(), len) };
+        let pixels: Vec<u8> = raw
+            .chunks_exact(bpp)
+            .flat_map(|px| match *px {
+                [l] => [l, l, l, 255],
+                [l, a] => [l, l, l, a],
+                [r, g, b] => [r, g, b, 255],
+                [r, g, b, a] => [r, g, b, a],
+                _ => unreachable!(),
+            })
+            .collect();
+
+        let extent = xr::Extent2Di {
+            width: width as i32,
+            height: height as i32,
+        };
+        self.set_overlay_pixels(handle, &pixels, extent)
     }
-    fn ClearOverlayTexture(&self, _: vr::VROverlayHandle_t) -> vr::EVROverlayError {
-        todo!()
+    fn ClearOverlayTexture(&self, handle: vr::VROverlayHandle_t) -> vr::EVROverlayError {
+        get_overlay!(self, handle, mut overlay);
+        debug!("clearing overlay texture for {:?}", overlay.name);
+        overlay.rect = None;
+        vr::EVROverlayError::None
     }
     fn ClearOverlayCursorPositionOverride(&self, _: vr::VROverlayHandle_t) -> vr::EVROverlayError {
         todo!()
@@ -856,55 +2465,152 @@ impl vr::IVROverlay027_Interface for OverlayMan {
     ) -> vr::EVROverlayError {
         todo!()
     }
-    fn IsHoverTargetOverlay(&self, _: vr::VROverlayHandle_t) -> bool {
-        todo!()
+    fn IsHoverTargetOverlay(&self, handle: vr::VROverlayHandle_t) -> bool {
+        let key = OverlayKey::from(KeyData::from_ffi(handle));
+        self.pointer
+            .lock()
+            .unwrap()
+            .hover
+            .is_some_and(|(hovered, _)| hovered == key)
     }
     fn ComputeOverlayIntersection(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: *const vr::VROverlayIntersectionParams_t,
-        _: *mut vr::VROverlayIntersectionResults_t,
+        handle: vr::VROverlayHandle_t,
+        params: *const vr::VROverlayIntersectionParams_t,
+        results: *mut vr::VROverlayIntersectionResults_t,
     ) -> bool {
-        todo!()
+        if params.is_null() || results.is_null() {
+            return false;
+        }
+        let params = unsafe { params.read() };
+        let session = self.openxr.session_data.get();
+        let devices = self.locate_devices(&session);
+        let overlays = self.overlays.read().unwrap();
+        let Some(overlay) = overlays.get(OverlayKey::from(KeyData::from_ffi(handle))) else {
+            return false;
+        };
+        let Some((origin, mut pose)) = overlay.placement(session.current_origin, &devices) else {
+            return false;
+        };
+        if origin != params.eOrigin {
+            let Ok(location) = session.get_space_for_origin(origin).locate(
+                session.get_space_for_origin(params.eOrigin),
+                self.openxr.display_time.get(),
+            ) else {
+                return false;
+            };
+            pose = compose(location.pose, pose);
+        }
+
+        let Some(hit) = pointer::intersect(
+            pose,
+            overlay.width,
+            overlay.height(),
+            Vec3::from_array(params.vSource.v),
+            Vec3::from_array(params.vDirection.v),
+        ) else {
+            return false;
+        };
+        unsafe {
+            results.write(vr::VROverlayIntersectionResults_t {
+                vPoint: vr::HmdVector3_t {
+                    v: hit.point.to_array(),
+                },
+                vNormal: vr::HmdVector3_t {
+                    v: hit.normal.to_array(),
+                },
+                vUVs: vr::HmdVector2_t {
+                    v: hit.uv.to_array(),
+                },
+                fDistance: hit.distance,
+            });
+        }
+        true
     }
     fn SetOverlayMouseScale(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: *const vr::HmdVector2_t,
+        handle: vr::VROverlayHandle_t,
+        scale: *const vr::HmdVector2_t,
     ) -> vr::EVROverlayError {
-        todo!()
+        get_overlay!(self, handle, mut overlay);
+        overlay.mouse_scale = if scale.is_null() {
+            vr::HmdVector2_t { v: [1.0, 1.0] }
+        } else {
+            unsafe { scale.read() }
+        };
+        vr::EVROverlayError::None
     }
     fn GetOverlayMouseScale(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: *mut vr::HmdVector2_t,
+        handle: vr::VROverlayHandle_t,
+        scale: *mut vr::HmdVector2_t,
     ) -> vr::EVROverlayError {
-        todo!()
+        get_overlay!(self, handle, overlay);
+        if scale.is_null() {
+            return vr::EVROverlayError::InvalidParameter;
+        }
+        unsafe { *scale = overlay.mouse_scale };
+        vr::EVROverlayError::None
     }
     fn SetOverlayInputMethod(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: vr::VROverlayInputMethod,
+        handle: vr::VROverlayHandle_t,
+        method: vr::VROverlayInputMethod,
     ) -> vr::EVROverlayError {
-        todo!()
+        get_overlay!(self, handle, mut overlay);
+        debug!("set overlay {:?} input method to {method:?}", overlay.name);
+        overlay.input_method = method;
+        vr::EVROverlayError::None
     }
     fn GetOverlayInputMethod(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: *mut vr::VROverlayInputMethod,
+        handle: vr::VROverlayHandle_t,
+        method: *mut vr::VROverlayInputMethod,
     ) -> vr::EVROverlayError {
-        todo!()
+        get_overlay!(self, handle, overlay);
+        if method.is_null() {
+            return vr::EVROverlayError::InvalidParameter;
+        }
+        unsafe { *method = overlay.input_method };
+        vr::EVROverlayError::None
     }
     fn PollNextOverlayEvent(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: *mut vr::VREvent_t,
-        _: u32,
+        handle: vr::VROverlayHandle_t,
+        event: *mut vr::VREvent_t,
+        size: u32,
     ) -> bool {
-        todo!()
-    }
-    fn WaitFrameSync(&self, _: u32) -> vr::EVROverlayError {
-        todo!()
+        if event.is_null() {
+            return false;
+        }
+        let mut overlays = self.overlays.write().unwrap();
+        let Some(next) = overlays
+            .get_mut(OverlayKey::from(KeyData::from_ffi(handle)))
+            .and_then(|overlay| overlay.events.pop_front())
+        else {
+            return false;
+        };
+        // Older apps pass smaller event structs.
+        let size = (size as usize).min(std::mem::size_of::<vr::VREvent_t>());
+        unsafe {
+            std::ptr::copy_nonoverlapping((&raw const next).cast::<u8>(), event.cast::<u8>(), size);
+        }
+        true
+    }
+    fn WaitFrameSync(&self, timeout_ms: u32) -> vr::EVROverlayError {
+        let count = self.frame_count.lock().unwrap();
+        let current = *count;
+        let (_, result) = self
+            .frame_composited
+            .wait_timeout_while(count, Duration::from_millis(timeout_ms.into()), |count| {
+                *count == current
+            })
+            .unwrap();
+        if result.timed_out() {
+            vr::EVROverlayError::TimedOut
+        } else {
+            vr::EVROverlayError::None
+        }
     }
     fn GetTransformForOverlayCoordinates(
         &self,
@@ -915,8 +2621,12 @@ impl vr::IVROverlay027_Interface for OverlayMan {
     ) -> vr::EVROverlayError {
         todo!()
     }
-    fn IsOverlayVisible(&self, _: vr::VROverlayHandle_t) -> bool {
-        todo!()
+    fn IsOverlayVisible(&self, handle: vr::VROverlayHandle_t) -> bool {
+        self.overlays
+            .read()
+            .unwrap()
+            .get(OverlayKey::from(KeyData::from_ffi(handle)))
+            .is_some_and(|overlay| overlay.visible)
     }
     fn SetOverlayTransformProjection(
         &self,
@@ -961,28 +2671,60 @@ impl vr::IVROverlay027_Interface for OverlayMan {
     }
     fn GetOverlayTransformTrackedDeviceRelative(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: *mut vr::TrackedDeviceIndex_t,
-        _: *mut vr::HmdMatrix34_t,
+        handle: vr::VROverlayHandle_t,
+        device: *mut vr::TrackedDeviceIndex_t,
+        transform: *mut vr::HmdMatrix34_t,
     ) -> vr::EVROverlayError {
-        todo!()
+        get_overlay!(self, handle, overlay);
+        if device.is_null() || transform.is_null() {
+            return vr::EVROverlayError::InvalidParameter;
+        }
+        let Some(OverlayTransform::TrackedDeviceRelative(d, t)) = overlay.transform else {
+            return vr::EVROverlayError::WrongTransformType;
+        };
+        unsafe {
+            *device = d;
+            *transform = t;
+        }
+        vr::EVROverlayError::None
     }
     fn SetOverlayTransformTrackedDeviceRelative(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: vr::TrackedDeviceIndex_t,
-        _: *const vr::HmdMatrix34_t,
+        handle: vr::VROverlayHandle_t,
+        device: vr::TrackedDeviceIndex_t,
+        transform: *const vr::HmdMatrix34_t,
     ) -> vr::EVROverlayError {
-        crate::warn_unimplemented!("SetOverlayTransformTrackedDeviceRelative");
+        get_overlay!(self, handle, mut overlay);
+        if transform.is_null() || device >= vr::k_unMaxTrackedDeviceCount {
+            return vr::EVROverlayError::InvalidParameter;
+        }
+        overlay.transform = Some(OverlayTransform::TrackedDeviceRelative(device, unsafe {
+            transform.read()
+        }));
+        debug!(
+            "attached overlay {:?} to tracked device {device}",
+            overlay.name
+        );
         vr::EVROverlayError::None
     }
     fn GetOverlayTransformAbsolute(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: *mut vr::ETrackingUniverseOrigin,
-        _: *mut vr::HmdMatrix34_t,
+        handle: vr::VROverlayHandle_t,
+        origin: *mut vr::ETrackingUniverseOrigin,
+        transform: *mut vr::HmdMatrix34_t,
     ) -> vr::EVROverlayError {
-        todo!()
+        get_overlay!(self, handle, overlay);
+        if origin.is_null() || transform.is_null() {
+            return vr::EVROverlayError::InvalidParameter;
+        }
+        let Some(OverlayTransform::Absolute(o, t)) = overlay.transform else {
+            return vr::EVROverlayError::WrongTransformType;
+        };
+        unsafe {
+            *origin = o;
+            *transform = t;
+        }
+        vr::EVROverlayError::None
     }
     fn SetOverlayTransformAbsolute(
         &self,
@@ -994,7 +2736,9 @@ impl vr::IVROverlay027_Interface for OverlayMan {
         if transform.is_null() {
             vr::EVROverlayError::InvalidParameter
         } else {
-            overlay.transform = Some((origin, unsafe { transform.read() }));
+            overlay.transform = Some(OverlayTransform::Absolute(origin, unsafe {
+                transform.read()
+            }));
             debug!(
                 "set overlay transform origin to {origin:?} for {:?}",
                 overlay.name
@@ -1004,10 +2748,22 @@ impl vr::IVROverlay027_Interface for OverlayMan {
     }
     fn GetOverlayTransformType(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: *mut vr::VROverlayTransformType,
+        handle: vr::VROverlayHandle_t,
+        transform_type: *mut vr::VROverlayTransformType,
     ) -> vr::EVROverlayError {
-        todo!()
+        get_overlay!(self, handle, overlay);
+        if transform_type.is_null() {
+            return vr::EVROverlayError::InvalidParameter;
+        }
+        unsafe {
+            *transform_type = match overlay.transform {
+                None | Some(OverlayTransform::Absolute(..)) => vr::VROverlayTransformType::Absolute,
+                Some(OverlayTransform::TrackedDeviceRelative(..)) => {
+                    vr::VROverlayTransformType::TrackedDeviceRelative
+                }
+            }
+        };
+        vr::EVROverlayError::None
     }
     fn GetOverlayTextureBounds(
         &self,
@@ -1125,11 +2881,29 @@ impl vr::IVROverlay027_Interface for OverlayMan {
         overlay.z_order = value as _;
         vr::EVROverlayError::None
     }
-    fn GetOverlayTexelAspect(&self, _: vr::VROverlayHandle_t, _: *mut f32) -> vr::EVROverlayError {
-        todo!()
+    fn GetOverlayTexelAspect(
+        &self,
+        handle: vr::VROverlayHandle_t,
+        value: *mut f32,
+    ) -> vr::EVROverlayError {
+        get_overlay!(self, handle, overlay);
+        if value.is_null() {
+            return vr::EVROverlayError::InvalidParameter;
+        }
+        unsafe { *value = overlay.texel_aspect };
+        vr::EVROverlayError::None
     }
-    fn SetOverlayTexelAspect(&self, _: vr::VROverlayHandle_t, _: f32) -> vr::EVROverlayError {
-        crate::warn_unimplemented!("SetOverlayTexelAspect");
+    fn SetOverlayTexelAspect(
+        &self,
+        handle: vr::VROverlayHandle_t,
+        value: f32,
+    ) -> vr::EVROverlayError {
+        get_overlay!(self, handle, mut overlay);
+        if !(value.is_finite() && value > 0.0) {
+            return vr::EVROverlayError::InvalidParameter;
+        }
+        debug!("overlay {:?} texel aspect {value}", overlay.name);
+        overlay.texel_aspect = value;
         vr::EVROverlayError::None
     }
     fn GetOverlayAlpha(
@@ -1144,49 +2918,139 @@ impl vr::IVROverlay027_Interface for OverlayMan {
 
     fn GetOverlayColor(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: *mut f32,
-        _: *mut f32,
-        _: *mut f32,
+        handle: vr::VROverlayHandle_t,
+        red: *mut f32,
+        green: *mut f32,
+        blue: *mut f32,
     ) -> vr::EVROverlayError {
-        todo!()
+        get_overlay!(self, handle, overlay);
+        if red.is_null() || green.is_null() || blue.is_null() {
+            return vr::EVROverlayError::InvalidParameter;
+        }
+        let [r, g, b] = overlay.color.unwrap_or([1.0; 3]);
+        unsafe {
+            *red = r;
+            *green = g;
+            *blue = b;
+        }
+        vr::EVROverlayError::None
     }
     fn SetOverlayColor(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: f32,
-        _: f32,
-        _: f32,
+        handle: vr::VROverlayHandle_t,
+        red: f32,
+        green: f32,
+        blue: f32,
     ) -> vr::EVROverlayError {
-        todo!()
+        get_overlay!(self, handle, mut overlay);
+        if !self
+            .openxr
+            .enabled_extensions
+            .khr_composition_layer_color_scale_bias
+        {
+            crate::warn_once!("Cannot SetOverlayColor on {:?}: Runtime does not support KHR_composition_layer_color_scale_bias", overlay.name);
+            return vr::EVROverlayError::None;
+        }
+
+        debug!(
+            "overlay {:?} color ({red:.2}, {green:.2}, {blue:.2})",
+            overlay.name
+        );
+        let color = [red, green, blue];
+        overlay.color = (color != [1.0; 3]).then_some(color);
+        vr::EVROverlayError::None
     }
-    fn GetOverlayFlags(&self, _: vr::VROverlayHandle_t, _: *mut u32) -> vr::EVROverlayError {
-        todo!()
+    fn GetOverlayFlags(
+        &self,
+        handle: vr::VROverlayHandle_t,
+        flags: *mut u32,
+    ) -> vr::EVROverlayError {
+        get_overlay!(self, handle, overlay);
+        if flags.is_null() {
+            return vr::EVROverlayError::InvalidParameter;
+        }
+        unsafe { *flags = overlay.flags };
+        vr::EVROverlayError::None
     }
     fn GetOverlayFlag(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: vr::VROverlayFlags,
-        _: *mut bool,
+        handle: vr::VROverlayHandle_t,
+        flag: vr::VROverlayFlags,
+        enabled: *mut bool,
     ) -> vr::EVROverlayError {
-        todo!()
+        get_overlay!(self, handle, overlay);
+        if enabled.is_null() {
+            return vr::EVROverlayError::InvalidParameter;
+        }
+        unsafe { *enabled = overlay.has_flag(flag) };
+        vr::EVROverlayError::None
     }
     fn SetOverlayFlag(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: vr::VROverlayFlags,
-        _: bool,
+        handle: vr::VROverlayHandle_t,
+        flag: vr::VROverlayFlags,
+        enabled: bool,
     ) -> vr::EVROverlayError {
-        todo!()
+        get_overlay!(self, handle, mut overlay);
+        debug!("overlay {:?} flag {flag:?} → {enabled}", overlay.name);
+        if enabled {
+            overlay.flags |= flag as u32;
+        } else {
+            overlay.flags &= !(flag as u32);
+        }
+        vr::EVROverlayError::None
     }
-    fn GetOverlayRenderingPid(&self, _: vr::VROverlayHandle_t) -> u32 {
-        todo!()
+    fn GetOverlayRenderingPid(&self, handle: vr::VROverlayHandle_t) -> u32 {
+        self.overlays
+            .read()
+            .unwrap()
+            .get(OverlayKey::from(KeyData::from_ffi(handle)))
+            .map_or(0, |overlay| overlay.rendering_pid)
     }
-    fn SetOverlayRenderingPid(&self, _: vr::VROverlayHandle_t, _: u32) -> vr::EVROverlayError {
-        todo!()
+    fn SetOverlayRenderingPid(
+        &self,
+        handle: vr::VROverlayHandle_t,
+        pid: u32,
+    ) -> vr::EVROverlayError {
+        get_overlay!(self, handle, mut overlay);
+        overlay.rendering_pid = pid;
+        vr::EVROverlayError::None
     }
-    fn GetOverlayErrorNameFromEnum(&self, _: vr::EVROverlayError) -> *const c_char {
-        todo!()
+    fn GetOverlayErrorNameFromEnum(&self, error: vr::EVROverlayError) -> *const c_char {
+        #[allow(unreachable_patterns)]
+        let name: &'static CStr = match error {
+            vr::EVROverlayError::None => c"VROverlayError_None",
+            vr::EVROverlayError::UnknownOverlay => c"VROverlayError_UnknownOverlay",
+            vr::EVROverlayError::InvalidHandle => c"VROverlayError_InvalidHandle",
+            vr::EVROverlayError::PermissionDenied => c"VROverlayError_PermissionDenied",
+            vr::EVROverlayError::OverlayLimitExceeded => c"VROverlayError_OverlayLimitExceeded",
+            vr::EVROverlayError::WrongVisibilityType => c"VROverlayError_WrongVisibilityType",
+            vr::EVROverlayError::KeyTooLong => c"VROverlayError_KeyTooLong",
+            vr::EVROverlayError::NameTooLong => c"VROverlayError_NameTooLong",
+            vr::EVROverlayError::KeyInUse => c"VROverlayError_KeyInUse",
+            vr::EVROverlayError::WrongTransformType => c"VROverlayError_WrongTransformType",
+            vr::EVROverlayError::InvalidTrackedDevice => c"VROverlayError_InvalidTrackedDevice",
+            vr::EVROverlayError::InvalidParameter => c"VROverlayError_InvalidParameter",
+            vr::EVROverlayError::ThumbnailCantBeDestroyed => {
+                c"VROverlayError_ThumbnailCantBeDestroyed"
+            }
+            vr::EVROverlayError::ArrayTooSmall => c"VROverlayError_ArrayTooSmall",
+            vr::EVROverlayError::RequestFailed => c"VROverlayError_RequestFailed",
+            vr::EVROverlayError::InvalidTexture => c"VROverlayError_InvalidTexture",
+            vr::EVROverlayError::UnableToLoadFile => c"VROverlayError_UnableToLoadFile",
+            vr::EVROverlayError::KeyboardAlreadyInUse => c"VROverlayError_KeyboardAlreadyInUse",
+            vr::EVROverlayError::NoNeighbor => c"VROverlayError_NoNeighbor",
+            vr::EVROverlayError::TooManyMaskPrimitives => c"VROverlayError_TooManyMaskPrimitives",
+            vr::EVROverlayError::BadMaskPrimitive => c"VROverlayError_BadMaskPrimitive",
+            vr::EVROverlayError::TextureAlreadyLocked => c"VROverlayError_TextureAlreadyLocked",
+            vr::EVROverlayError::TextureLockCapacityReached => {
+                c"VROverlayError_TextureLockCapacityReached"
+            }
+            vr::EVROverlayError::TextureNotLocked => c"VROverlayError_TextureNotLocked",
+            vr::EVROverlayError::TimedOut => c"VROverlayError_TimedOut",
+            _ => c"Unknown",
+        };
+        name.as_ptr()
     }
     fn GetOverlayImageData(
         &self,
@@ -1198,35 +3062,81 @@ impl vr::IVROverlay027_Interface for OverlayMan {
     ) -> vr::EVROverlayError {
         todo!()
     }
-    fn SetOverlayName(&self, _: vr::VROverlayHandle_t, _: *const c_char) -> vr::EVROverlayError {
-        todo!()
+    fn SetOverlayName(
+        &self,
+        handle: vr::VROverlayHandle_t,
+        name: *const c_char,
+    ) -> vr::EVROverlayError {
+        get_overlay!(self, handle, mut overlay);
+        if name.is_null() {
+            return vr::EVROverlayError::InvalidParameter;
+        }
+        let name = unsafe { CStr::from_ptr(name) };
+        if name.to_bytes().len() >= vr::k_unVROverlayMaxNameLength as usize {
+            return vr::EVROverlayError::NameTooLong;
+        }
+        debug!("renaming overlay {:?} to {name:?}", overlay.name);
+        overlay.name = name.into();
+        vr::EVROverlayError::None
     }
     fn GetOverlayName(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: *mut c_char,
-        _: u32,
-        _: *mut vr::EVROverlayError,
+        handle: vr::VROverlayHandle_t,
+        value: *mut c_char,
+        size: u32,
+        error: *mut vr::EVROverlayError,
     ) -> u32 {
-        todo!()
+        self.get_overlay_string(handle, value, size, error, |overlay| {
+            overlay.name.as_c_str()
+        })
     }
     fn GetOverlayKey(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: *mut c_char,
-        _: u32,
-        _: *mut vr::EVROverlayError,
+        handle: vr::VROverlayHandle_t,
+        value: *mut c_char,
+        size: u32,
+        error: *mut vr::EVROverlayError,
     ) -> u32 {
-        todo!()
+        self.get_overlay_string(handle, value, size, error, |overlay| overlay.key.as_c_str())
     }
     fn DestroyOverlay(&self, handle: vr::VROverlayHandle_t) -> vr::EVROverlayError {
         let key = OverlayKey::from(KeyData::from_ffi(handle));
 
-        let mut overlays = self.overlays.write().unwrap();
-        if let Some(overlay) = overlays.remove(key) {
-            let mut map = self.key_to_overlay.write().unwrap();
-            map.remove(&overlay.key);
+        let mut dashboard = self.dashboard.lock().unwrap();
+        if dashboard.tabs.iter().any(|tab| tab.thumbnail == key) {
+            return vr::EVROverlayError::ThumbnailCantBeDestroyed;
+        }
+        let Some(overlay) = self.overlays.write().unwrap().remove(key) else {
+            return vr::EVROverlayError::UnknownOverlay;
+        };
+        self.key_to_overlay.write().unwrap().remove(&overlay.key);
+
+        // Dashboard overlays take their thumbnail with them.
+        let mut removed = vec![key];
+        if let Some(idx) = dashboard.tabs.iter().position(|tab| tab.main == key) {
+            let tab = dashboard.tabs.remove(idx);
+            self.overlays.write().unwrap().remove(tab.thumbnail);
+            removed.push(tab.thumbnail);
+            if dashboard.active == Some(key) {
+                dashboard.active = dashboard.tabs.first().map(|tab| tab.main);
+            }
+            if dashboard.tabs.is_empty() {
+                dashboard.visible = false;
+                dashboard
+                    .events
+                    .push_back(vr::EVREventType::DashboardDeactivated);
+            }
+        }
+        drop(dashboard);
+
+        let session = self.openxr.session_data.get();
+        for key in removed {
+            session.overlay_data.remove_swapchain(key);
         }
+        // Notifications go away with their overlay.
+        let notifications = self.notifications.lock().unwrap().remove_for(key);
+        self.remove_toasts(&session, &mut self.overlays.write().unwrap(), notifications);
+        debug!("destroyed overlay {:?}", overlay.name);
         vr::EVROverlayError::None
     }
 }
@@ -1253,38 +3163,65 @@ impl vr::IVROverlay025On027 for OverlayMan {
 impl vr::IVROverlay021On024 for OverlayMan {
     fn ShowKeyboardForOverlay(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: vr::EGamepadTextInputMode,
-        _: vr::EGamepadTextInputLineMode,
-        _: *const c_char,
-        _: u32,
-        _: *const c_char,
-        _: bool,
-        _: u64,
+        handle: vr::VROverlayHandle_t,
+        mode: vr::EGamepadTextInputMode,
+        line_mode: vr::EGamepadTextInputLineMode,
+        description: *const c_char,
+        max_chars: u32,
+        existing_text: *const c_char,
+        minimal: bool,
+        user_value: u64,
     ) -> vr::EVROverlayError {
-        todo!()
+        <Self as vr::IVROverlay027_Interface>::ShowKeyboardForOverlay(
+            self,
+            handle,
+            mode,
+            line_mode,
+            minimal as u32 * vr::EKeyboardFlags::Minimal as u32,
+            description,
+            max_chars,
+            existing_text,
+            user_value,
+        )
     }
     fn ShowKeyboard(
         &self,
-        _: vr::EGamepadTextInputMode,
-        _: vr::EGamepadTextInputLineMode,
-        _: *const c_char,
-        _: u32,
-        _: *const c_char,
-        _: bool,
-        _: u64,
+        mode: vr::EGamepadTextInputMode,
+        line_mode: vr::EGamepadTextInputLineMode,
+        description: *const c_char,
+        max_chars: u32,
+        existing_text: *const c_char,
+        minimal: bool,
+        user_value: u64,
     ) -> vr::EVROverlayError {
-        todo!()
+        <Self as vr::IVROverlay027_Interface>::ShowKeyboard(
+            self,
+            mode,
+            line_mode,
+            minimal as u32 * vr::EKeyboardFlags::Minimal as u32,
+            description,
+            max_chars,
+            existing_text,
+            user_value,
+        )
     }
+    #[inline]
     fn SetOverlayRaw(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: *mut c_void,
-        _: u32,
-        _: u32,
-        _: u32,
+        handle: vr::VROverlayHandle_t,
+        buffer: *mut c_void,
+        width: u32,
+        height: u32,
+        bytes_per_pixel: u32,
     ) -> vr::EVROverlayError {
-        todo!()
+        <Self as vr::IVROverlay027_Interface>::SetOverlayRaw(
+            self,
+            handle,
+            buffer,
+            width,
+            height,
+            bytes_per_pixel,
+        )
     }
     fn GetOverlayDualAnalogTransform(
         &self,
@@ -1393,9 +3330,158 @@ impl vr::IVROverlay018On019 for OverlayMan {
 impl vr::IVROverlay016On018 for OverlayMan {
     fn HandleControllerOverlayInteractionAsMouse(
         &self,
-        _: vr::VROverlayHandle_t,
-        _: vr::TrackedDeviceIndex_t,
+        handle: vr::VROverlayHandle_t,
+        device: vr::TrackedDeviceIndex_t,
     ) -> bool {
-        todo!()
+        // The pointer already sends mouse events to overlays that take them, so this only reports
+        // whether the controller is pointing at the overlay.
+        let pointer = self.pointer.lock().unwrap();
+        pointer.hand as vr::TrackedDeviceIndex_t == device
+            && pointer
+                .hover
+                .is_some_and(|(hovered, _)| hovered == OverlayKey::from(KeyData::from_ffi(handle)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn side_by_side_overlays_split_per_eye() {
+        let mut overlay = Overlay::new(c"key".into(), c"name".into());
+        let rect = xr::Rect2Di {
+            offset: xr::Offset2Di::default(),
+            extent: xr::Extent2Di {
+                width: 200,
+                height: 50,
+            },
+        };
+        overlay.rect = Some(rect);
+        assert_eq!(overlay.eye_rects(rect).len(), 1);
+        assert_eq!(overlay.height(), 0.25);
+
+        overlay.flags = vr::VROverlayFlags::SideBySide_Crossed as u32;
+        let eyes = overlay.eye_rects(rect);
+        assert_eq!(eyes[0].0, xr::EyeVisibility::LEFT);
+        assert_eq!(eyes[0].1.offset.x, 100);
+        assert_eq!(eyes[1].1.offset.x, 0);
+        // Each eye sees a square.
+        assert_eq!(overlay.height(), 0.5);
+    }
+
+    #[test]
+    fn scene_overlays_stack_below_others() {
+        let overlay = |scene_app, z_order, flags: u32| {
+            let mut overlay = Overlay::new(c"key".into(), c"name".into());
+            overlay.scene_app = scene_app;
+            overlay.z_order = z_order;
+            overlay.flags = flags;
+            overlay.stack_order()
+        };
+        let skybox = overlay(false, SKYBOX_Z_ORDER, 0);
+        let scene_hud = overlay(true, 100, 0);
+        let other = overlay(false, 0, 0);
+        let sorted_with_others =
+            overlay(true, 1, vr::VROverlayFlags::SortWithNonSceneOverlays as u32);
+
+        assert!(skybox < scene_hud);
+        assert!(scene_hud < other);
+        assert!(other < sorted_with_others);
+    }
+
+    #[test]
+    fn visibility_changes_are_queued() {
+        let key = OverlayKey::from(KeyData::from_ffi(1));
+        let mut overlay = Overlay::new(c"key".into(), c"name".into());
+        overlay.set_visible(key, true);
+        overlay.set_visible(key, true);
+        overlay.set_visible(key, false);
+
+        let events: Vec<_> = overlay.events.iter().map(|e| e.eventType).collect();
+        assert_eq!(
+            events,
+            [
+                vr::EVREventType::OverlayShown as u32,
+                vr::EVREventType::OverlayHidden as u32
+            ]
+        );
+        assert_eq!(
+            unsafe { overlay.events[0].data.overlay.overlayHandle },
+            key.data().as_ffi()
+        );
+    }
+
+    #[test]
+    fn crops_pixels_to_bounds() {
+        // 2x2: red, green / blue, white
+        #[rustfmt::skip]
+        let pixels = [
+            255, 0, 0, 255,     0, 255, 0, 255,
+            0, 0, 255, 255,     255, 255, 255, 255,
+        ];
+        let extent = xr::Extent2Di {
+            width: 2,
+            height: 2,
+        };
+        let bounds = |u_min, v_min, u_max, v_max| vr::VRTextureBounds_t {
+            uMin: u_min,
+            vMin: v_min,
+            uMax: u_max,
+            vMax: v_max,
+        };
+
+        assert!(crop_to_bounds(&pixels, extent, bounds(0.0, 0.0, 1.0, 1.0)).is_none());
+
+        // Right column only.
+        let (cropped, cropped_extent) =
+            crop_to_bounds(&pixels, extent, bounds(0.5, 0.0, 1.0, 1.0)).unwrap();
+        assert_eq!(cropped, [0, 255, 0, 255, 255, 255, 255, 255]);
+        assert_eq!((cropped_extent.width, cropped_extent.height), (1, 2));
+
+        // Flipped vertically, as GL apps do.
+        let (cropped, _) = crop_to_bounds(&pixels, extent, bounds(0.0, 1.0, 1.0, 0.0)).unwrap();
+        assert_eq!(cropped[..8], pixels[8..]);
+        assert_eq!(cropped[8..], pixels[..8]);
+    }
+
+    #[test]
+    fn device_relative_overlay_follows_device() {
+        let mut overlay = Overlay::new(c"key".into(), c"name".into());
+        let offset = xr::Posef {
+            orientation: xr::Quaternionf::IDENTITY,
+            position: xr::Vector3f {
+                x: 0.1,
+                y: 0.0,
+                z: 0.0,
+            },
+        };
+        overlay.transform = Some(OverlayTransform::TrackedDeviceRelative(1, offset.into()));
+        let origin = vr::ETrackingUniverseOrigin::Standing;
+
+        // Not placed while the device isn't tracked.
+        assert!(overlay.placement(origin, &DevicePoses::new()).is_none());
+
+        // The offset is applied in the device's frame: turned left, its +X points along -Z.
+        let rotation = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+        let device = xr::Posef {
+            orientation: xr::Quaternionf {
+                x: rotation.x,
+                y: rotation.y,
+                z: rotation.z,
+                w: rotation.w,
+            },
+            position: xr::Vector3f {
+                x: 1.0,
+                y: 1.0,
+                z: 0.0,
+            },
+        };
+        let (placed_origin, pose) = overlay
+            .placement(origin, &DevicePoses::from([(1, device)]))
+            .unwrap();
+        assert_eq!(placed_origin, origin);
+        let position = vec3(pose.position.x, pose.position.y, pose.position.z);
+        assert!(position.abs_diff_eq(vec3(1.0, 1.0, -0.1), 1e-5));
     }
 }
@@ -0,0 +1,270 @@
+use super::text::{self, Label, TextImage};
+use super::{Overlay, OverlayKey, OverlayTransform};
+use crate::input::Gesture;
+use glam::{Quat, Vec3};
+use openvr as vr;
+use openxr as xr;
+use serde::Deserialize;
+use slotmap::SlotMap;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How far in front of the user the dashboard opens, in meters.
+const DISTANCE: f32 = 1.5;
+const THUMBNAIL_WIDTH: f32 = 0.25;
+const THUMBNAIL_SPACING: f32 = 0.05;
+/// Space between the bottom of the active overlay and the row of thumbnails, in meters.
+const THUMBNAIL_GAP: f32 = 0.1;
+/// Size of the thumbnails xrizer draws for overlays that don't have one, in pixels.
+const NAME_THUMBNAIL_SIZE: (usize, usize) = (320, 180);
+const NAME_THUMBNAIL_FILL: [u8; 4] = [40, 40, 46, 255];
+
+/// How the user summons xrizer's dashboard.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct DashboardConfig {
+    /// Toggle the dashboard by holding the menu button of either controller.
+    pub menu_button: bool,
+    /// Toggle the dashboard by holding this gesture with either hand.
+    pub gesture: Option<Gesture>,
+    /// How long the button or gesture has to be held, in seconds.
+    pub hold_seconds: f32,
+}
+
+impl Default for DashboardConfig {
+    fn default() -> Self {
+        Self {
+            menu_button: true,
+            gesture: None,
+            hold_seconds: 1.0,
+        }
+    }
+}
+
+/// An overlay registered through CreateDashboardOverlay.
+pub(super) struct DashboardTab {
+    pub main: OverlayKey,
+    pub thumbnail: OverlayKey,
+    pub scene_process: u32,
+}
+
+/// A stand-in for the SteamVR dashboard: the active dashboard overlay is shown in front of the
+/// user, with the thumbnails of all of them in a row below it.
+#[derive(Default)]
+pub(super) struct Dashboard {
+    pub tabs: Vec<DashboardTab>,
+    pub active: Option<OverlayKey>,
+    pub visible: bool,
+    /// Where the dashboard was opened, so it stays put while it's shown.
+    pose: Option<(vr::ETrackingUniverseOrigin, xr::Posef)>,
+    held_since: Option<Instant>,
+    /// Whether the current hold already toggled the dashboard, so holding on doesn't toggle it
+    /// again.
+    held_toggled: bool,
+    pub events: VecDeque<vr::EVREventType>,
+}
+
+impl Dashboard {
+    /// Tracks the summon button or gesture, returning true when it has just been held long enough
+    /// to toggle the dashboard.
+    pub fn update_summon(&mut self, held: bool, now: Instant, hold: Duration) -> bool {
+        if !held {
+            self.held_since = None;
+            self.held_toggled = false;
+            return false;
+        }
+        let since = *self.held_since.get_or_insert(now);
+        if !self.held_toggled && now - since >= hold {
+            self.held_toggled = true;
+            return true;
+        }
+        false
+    }
+
+    pub fn tab(&self, main: OverlayKey) -> Option<&DashboardTab> {
+        self.tabs.iter().find(|tab| tab.main == main)
+    }
+
+    /// Shows or hides the dashboard. `hmd` is the pose of the headset in `origin`, which the
+    /// dashboard is placed in front of when it's opened.
+    pub fn set_visible(
+        &mut self,
+        visible: bool,
+        origin: vr::ETrackingUniverseOrigin,
+        hmd: xr::Posef,
+        overlays: &mut SlotMap<OverlayKey, Overlay>,
+    ) {
+        if visible == self.visible {
+            return;
+        }
+        self.visible = visible;
+        if visible {
+            self.pose = Some((origin, pose_in_front(hmd)));
+            if self.active.is_none() {
+                self.active = self.tabs.first().map(|tab| tab.main);
+            }
+            self.events.push_back(vr::EVREventType::DashboardActivated);
+        } else {
+            self.events
+                .push_back(vr::EVREventType::DashboardDeactivated);
+        }
+        self.layout(overlays);
+    }
+
+    /// Positions the dashboard overlays, and shows only the ones that should be visible.
+    pub fn layout(&self, overlays: &mut SlotMap<OverlayKey, Overlay>) {
+        let pose = self.pose.filter(|_| self.visible);
+        let active = self.active.filter(|_| pose.is_some());
+        for tab in &self.tabs {
+            if let Some(main) = overlays.get_mut(tab.main) {
+                if active != Some(tab.main) {
+                    main.set_visible(tab.main, false);
+                }
+            }
+            if let Some(thumbnail) = overlays.get_mut(tab.thumbnail) {
+                thumbnail.visible = false;
+            }
+        }
+        let Some((origin, pose)) = pose else {
+            return;
+        };
+        let rotation = Quat::from_xyzw(
+            pose.orientation.x,
+            pose.orientation.y,
+            pose.orientation.z,
+            pose.orientation.w,
+        );
+        let position = Vec3::new(pose.position.x, pose.position.y, pose.position.z);
+        let place = |overlay: &mut Overlay, offset: Vec3| {
+            overlay.transform = Some(OverlayTransform::Absolute(
+                origin,
+                to_posef(rotation, position + rotation.mul_vec3(offset)).into(),
+            ));
+        };
+
+        let mut thumbnails_y = -THUMBNAIL_GAP;
+        if let Some((key, main)) = active.and_then(|key| Some((key, overlays.get_mut(key)?))) {
+            thumbnails_y -= main.height() / 2.0;
+            place(main, Vec3::ZERO);
+            main.set_visible(key, true);
+        }
+
+        let row_width =
+            self.tabs.len() as f32 * (THUMBNAIL_WIDTH + THUMBNAIL_SPACING) - THUMBNAIL_SPACING;
+        for (idx, tab) in self.tabs.iter().enumerate() {
+            let Some(thumbnail) = overlays.get_mut(tab.thumbnail) else {
+                continue;
+            };
+            thumbnail.width = THUMBNAIL_WIDTH;
+            let x = -row_width / 2.0
+                + THUMBNAIL_WIDTH / 2.0
+                + idx as f32 * (THUMBNAIL_WIDTH + THUMBNAIL_SPACING);
+            let y = thumbnails_y - thumbnail.height() / 2.0;
+            place(thumbnail, Vec3::new(x, y, 0.0));
+            // Thumbnails don't get told when they're shown.
+            thumbnail.visible = true;
+        }
+    }
+}
+
+/// A stand-in thumbnail showing the name of a dashboard overlay.
+pub(super) fn name_thumbnail(name: &str) -> TextImage {
+    let (width, height) = NAME_THUMBNAIL_SIZE;
+    text::render_labels(
+        width,
+        height,
+        &[Label {
+            text: name.to_string(),
+            rect: [0, 0, width, height],
+            fill: NAME_THUMBNAIL_FILL,
+            centered: true,
+        }],
+    )
+}
+
+/// A pose facing the user, in front of the headset but level with the floor.
+fn pose_in_front(hmd: xr::Posef) -> xr::Posef {
+    let orientation = Quat::from_xyzw(
+        hmd.orientation.x,
+        hmd.orientation.y,
+        hmd.orientation.z,
+        hmd.orientation.w,
+    );
+    let forward = orientation.mul_vec3(Vec3::NEG_Z);
+    let yaw = Quat::from_rotation_y((-forward.x).atan2(-forward.z));
+    let position = Vec3::new(hmd.position.x, hmd.position.y, hmd.position.z)
+        + yaw.mul_vec3(Vec3::new(0.0, 0.0, -DISTANCE));
+    to_posef(yaw, position)
+}
+
+fn to_posef(rotation: Quat, position: Vec3) -> xr::Posef {
+    xr::Posef {
+        orientation: xr::Quaternionf {
+            x: rotation.x,
+            y: rotation.y,
+            z: rotation.z,
+            w: rotation.w,
+        },
+        position: xr::Vector3f {
+            x: position.x,
+            y: position.y,
+            z: position.z,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summon_toggles_once_per_hold() {
+        let mut dashboard = Dashboard::default();
+        let hold = Duration::from_secs(1);
+        let start = Instant::now();
+
+        assert!(!dashboard.update_summon(true, start, hold));
+        assert!(!dashboard.update_summon(true, start + hold / 2, hold));
+        assert!(dashboard.update_summon(true, start + hold, hold));
+        assert!(!dashboard.update_summon(true, start + hold * 3, hold));
+
+        // Letting go starts the hold over.
+        assert!(!dashboard.update_summon(false, start + hold * 4, hold));
+        assert!(!dashboard.update_summon(true, start + hold * 5, hold));
+        assert!(dashboard.update_summon(true, start + hold * 6, hold));
+    }
+
+    #[test]
+    fn opens_level_in_front_of_user() {
+        let hmd = to_posef(
+            Quat::from_rotation_y(std::f32::consts::FRAC_PI_2) * Quat::from_rotation_x(-0.5),
+            Vec3::new(1.0, 1.7, 0.0),
+        );
+        let pose = pose_in_front(hmd);
+        let rotation = Quat::from_xyzw(
+            pose.orientation.x,
+            pose.orientation.y,
+            pose.orientation.z,
+            pose.orientation.w,
+        );
+
+        // Turned left, so it's on the -X side, at eye height and facing back at the user.
+        let position = Vec3::new(pose.position.x, pose.position.y, pose.position.z);
+        assert!(position.abs_diff_eq(Vec3::new(1.0 - DISTANCE, 1.7, 0.0), 1e-5));
+        assert!(rotation.mul_vec3(Vec3::Z).abs_diff_eq(Vec3::X, 1e-5));
+    }
+
+    #[test]
+    fn name_thumbnail_shows_name() {
+        let image = name_thumbnail("Overlay");
+        let (width, height) = NAME_THUMBNAIL_SIZE;
+        assert_eq!(
+            (image.extent.width, image.extent.height),
+            (width as i32, height as i32)
+        );
+        assert!(image
+            .pixels
+            .chunks_exact(4)
+            .any(|px| px[..3] != NAME_THUMBNAIL_FILL[..3]));
+    }
+}
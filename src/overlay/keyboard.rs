@@ -0,0 +1,339 @@
+use super::text::{self, Label, TextImage};
+use super::OverlayKey;
+use glam::Vec2;
+use openvr as vr;
+
+/// Size of a one unit wide key, in pixels.
+const KEY_SIZE: usize = 64;
+/// Space around each key, in pixels.
+const KEY_GAP: usize = 4;
+/// Every row is this many keys wide.
+const ROW_UNITS: f32 = 11.0;
+/// The text field takes up the first row.
+const ROWS: usize = 6;
+const WIDTH: usize = (ROW_UNITS as usize) * KEY_SIZE;
+const HEIGHT: usize = ROWS * KEY_SIZE;
+
+const KEY_FILL: [u8; 4] = [60, 60, 68, 255];
+const SPECIAL_KEY_FILL: [u8; 4] = [40, 40, 46, 255];
+const ACTIVE_KEY_FILL: [u8; 4] = [70, 110, 180, 255];
+const FIELD_FILL: [u8; 4] = [10, 10, 12, 255];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum Key {
+    /// Typed without and with shift.
+    Char(char, char),
+    Backspace,
+    Enter,
+    Shift,
+    Space,
+    Done,
+    Close,
+}
+
+impl Key {
+    fn label(self, shift: bool) -> String {
+        match self {
+            Key::Char(c, _) if !shift => c.into(),
+            Key::Char(_, c) => c.into(),
+            Key::Backspace => "⌫".into(),
+            Key::Enter => "⏎".into(),
+            Key::Shift => "⇧".into(),
+            Key::Space => String::new(),
+            Key::Done => "Done".into(),
+            Key::Close => "Close".into(),
+        }
+    }
+}
+
+/// The keys of each row, with their width in units.
+fn layout() -> [Vec<(Key, f32)>; ROWS - 1] {
+    let chars = |plain: &str, shifted: &str| -> Vec<(Key, f32)> {
+        plain
+            .chars()
+            .zip(shifted.chars())
+            .map(|(plain, shifted)| (Key::Char(plain, shifted), 1.0))
+            .collect()
+    };
+    let row = |mut keys: Vec<(Key, f32)>, extra: &[(Key, f32)]| {
+        keys.extend_from_slice(extra);
+        keys
+    };
+    [
+        row(chars("1234567890", "!@#$%^&*()"), &[(Key::Backspace, 1.0)]),
+        chars("qwertyuiop-", "QWERTYUIOP_"),
+        row(chars("asdfghjkl'", "ASDFGHJKL\""), &[(Key::Enter, 1.0)]),
+        row(vec![(Key::Shift, 1.0)], &chars("zxcvbnm,./", "ZXCVBNM<>?")),
+        vec![(Key::Close, 2.0), (Key::Space, 7.0), (Key::Done, 2.0)],
+    ]
+}
+
+/// Where each key is drawn, as x, y, width, height in pixels from the top left.
+fn key_rects() -> impl Iterator<Item = (Key, [usize; 4])> {
+    layout().into_iter().enumerate().flat_map(|(row, keys)| {
+        let mut x = 0.0;
+        keys.into_iter().map(move |(key, units)| {
+            let rect = [
+                (x * KEY_SIZE as f32) as usize,
+                (row + 1) * KEY_SIZE,
+                (units * KEY_SIZE as f32) as usize,
+                KEY_SIZE,
+            ];
+            x += units;
+            (key, rect)
+        })
+    })
+}
+
+/// The key at a point on the keyboard overlay, in mouse coordinates from its bottom left.
+pub(super) fn key_at(uv: Vec2) -> Option<Key> {
+    let x = (uv.x * WIDTH as f32) as usize;
+    let y = ((1.0 - uv.y) * HEIGHT as f32) as usize;
+    key_rects()
+        .find(|(_, [left, top, width, height])| {
+            (*left..left + width).contains(&x) && (*top..top + height).contains(&y)
+        })
+        .map(|(key, _)| key)
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) enum KeyboardEvent {
+    Char(String),
+    Done,
+    Closed,
+}
+
+/// What ShowKeyboard or ShowKeyboardForOverlay asked for.
+pub(super) struct KeyboardInput {
+    /// The overlay the keyboard was shown for, if not for the scene application.
+    pub target: Option<OverlayKey>,
+    pub user_value: u64,
+    pub text: String,
+    description: String,
+    password: bool,
+    multiline: bool,
+    /// Send each key as it's typed, instead of only the finished text.
+    minimal: bool,
+    /// 0 for no limit.
+    max_chars: usize,
+    shift: bool,
+}
+
+impl KeyboardInput {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        target: Option<OverlayKey>,
+        mode: vr::EGamepadTextInputMode,
+        line_mode: vr::EGamepadTextInputLineMode,
+        flags: u32,
+        description: String,
+        max_chars: u32,
+        text: String,
+        user_value: u64,
+    ) -> Self {
+        Self {
+            target,
+            user_value,
+            text,
+            description,
+            password: mode == vr::EGamepadTextInputMode::Password,
+            multiline: line_mode == vr::EGamepadTextInputLineMode::MultipleLines,
+            minimal: flags & vr::EKeyboardFlags::Minimal as u32 != 0,
+            max_chars: max_chars as usize,
+            shift: false,
+        }
+    }
+
+    /// Types a key, returning the events that should be sent to the app.
+    pub fn press(&mut self, key: Key) -> Vec<KeyboardEvent> {
+        let typed = match key {
+            Key::Char(plain, shifted) => {
+                let c = if self.shift { shifted } else { plain };
+                self.shift = false;
+                Some(c)
+            }
+            Key::Space => Some(' '),
+            Key::Enter if self.multiline => Some('\n'),
+            Key::Enter | Key::Done => return vec![KeyboardEvent::Done, KeyboardEvent::Closed],
+            Key::Close => return vec![KeyboardEvent::Closed],
+            Key::Shift => {
+                self.shift = !self.shift;
+                None
+            }
+            Key::Backspace => {
+                if self.text.pop().is_some() && self.minimal {
+                    return vec![KeyboardEvent::Char("\x08".into())];
+                }
+                None
+            }
+        };
+
+        match typed {
+            Some(c) if self.max_chars == 0 || self.text.chars().count() < self.max_chars => {
+                self.text.push(c);
+                if self.minimal {
+                    vec![KeyboardEvent::Char(c.into())]
+                } else {
+                    Vec::new()
+                }
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn image(&self) -> TextImage {
+        let field = if self.text.is_empty() {
+            self.description.clone()
+        } else if self.password {
+            "•".repeat(self.text.chars().count())
+        } else {
+            // Only the last line fits.
+            self.text.lines().last().unwrap_or_default().to_string()
+        };
+        let mut labels = vec![Label {
+            text: field,
+            rect: [
+                KEY_GAP,
+                KEY_GAP,
+                WIDTH - 2 * KEY_GAP,
+                KEY_SIZE - 2 * KEY_GAP,
+            ],
+            fill: FIELD_FILL,
+            centered: false,
+        }];
+        labels.extend(key_rects().map(|(key, [x, y, width, height])| {
+            let fill = match key {
+                Key::Shift if self.shift => ACTIVE_KEY_FILL,
+                Key::Done => ACTIVE_KEY_FILL,
+                Key::Char(..) | Key::Space => KEY_FILL,
+                _ => SPECIAL_KEY_FILL,
+            };
+            Label {
+                text: key.label(self.shift),
+                rect: [
+                    x + KEY_GAP,
+                    y + KEY_GAP,
+                    width - 2 * KEY_GAP,
+                    height - 2 * KEY_GAP,
+                ],
+                fill,
+                centered: true,
+            }
+        }));
+        text::render_labels(WIDTH, HEIGHT, &labels)
+    }
+}
+
+/// The keyboard xrizer shows in place of the SteamVR one.
+#[derive(Default)]
+pub(super) struct Keyboard {
+    /// Kept around between uses.
+    pub overlay: Option<OverlayKey>,
+    /// Set while the keyboard is shown.
+    pub input: Option<KeyboardInput>,
+    /// From SetKeyboardTransformAbsolute, used instead of placing the keyboard in front of the user.
+    pub transform: Option<(vr::ETrackingUniverseOrigin, vr::HmdMatrix34_t)>,
+    /// What was typed the last time, since apps get the text after the keyboard closes.
+    pub last_text: String,
+}
+
+/// Packs the typed text into a keyboard event, which holds up to 7 bytes of UTF-8.
+pub(super) fn event_data(
+    input: &str,
+    user_value: u64,
+    overlay: vr::VROverlayHandle_t,
+) -> vr::VREvent_Keyboard_t {
+    let mut new_input = [0; 8];
+    let mut len = 0;
+    for c in input.chars() {
+        if len + c.len_utf8() > 7 {
+            break;
+        }
+        len += c.len_utf8();
+    }
+    for (dst, src) in new_input.iter_mut().zip(&input.as_bytes()[..len]) {
+        *dst = *src as _;
+    }
+    vr::VREvent_Keyboard_t {
+        cNewInput: new_input,
+        uUserValue: user_value,
+        overlayHandle: overlay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(minimal: bool, max_chars: u32) -> KeyboardInput {
+        KeyboardInput::new(
+            None,
+            vr::EGamepadTextInputMode::Normal,
+            vr::EGamepadTextInputLineMode::SingleLine,
+            if minimal {
+                vr::EKeyboardFlags::Minimal as u32
+            } else {
+                0
+            },
+            "Name".into(),
+            max_chars,
+            String::new(),
+            0,
+        )
+    }
+
+    #[test]
+    fn types_and_finishes_text() {
+        let mut input = input(false, 0);
+        assert!(input.press(Key::Shift).is_empty());
+        assert!(input.press(Key::Char('a', 'A')).is_empty());
+        assert!(input.press(Key::Char('b', 'B')).is_empty());
+        input.press(Key::Space);
+        input.press(Key::Char('c', 'C'));
+        input.press(Key::Backspace);
+        assert_eq!(input.text, "Ab ");
+
+        // Single line, so enter finishes.
+        assert_eq!(
+            input.press(Key::Enter),
+            [KeyboardEvent::Done, KeyboardEvent::Closed]
+        );
+    }
+
+    #[test]
+    fn minimal_mode_sends_each_key() {
+        let mut input = input(true, 2);
+        assert_eq!(
+            input.press(Key::Char('a', 'A')),
+            [KeyboardEvent::Char("a".into())]
+        );
+        input.press(Key::Char('b', 'B'));
+        // Over the limit.
+        assert!(input.press(Key::Char('c', 'C')).is_empty());
+        assert_eq!(
+            input.press(Key::Backspace),
+            [KeyboardEvent::Char("\x08".into())]
+        );
+        assert_eq!(input.text, "a");
+    }
+
+    #[test]
+    fn finds_keys_under_pointer() {
+        // Top left key, below the text field.
+        let uv = Vec2::new(0.5 / ROW_UNITS, 1.0 - 1.5 / ROWS as f32);
+        assert_eq!(key_at(uv), Some(Key::Char('1', '!')));
+        // Middle of the bottom row.
+        assert_eq!(key_at(Vec2::new(0.5, 0.5 / ROWS as f32)), Some(Key::Space));
+        // The text field.
+        assert_eq!(key_at(Vec2::new(0.5, 0.99)), None);
+    }
+
+    #[test]
+    fn truncates_event_input() {
+        let data = event_data("ééééé", 7, 3);
+        // Three two byte characters fit in the seven bytes.
+        assert_eq!(data.cNewInput[..7].iter().filter(|&&b| b != 0).count(), 6);
+        assert_eq!(data.cNewInput[7], 0);
+        assert_eq!(data.uUserValue, 7);
+    }
+}
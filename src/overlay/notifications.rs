@@ -0,0 +1,271 @@
+use super::text::{self, Label, TextImage};
+use super::OverlayKey;
+use openvr as vr;
+use std::time::{Duration, Instant};
+
+/// How long transient notifications stay up.
+const TRANSIENT_DURATION: Duration = Duration::from_secs(5);
+/// Notifications shown at once; the rest wait their turn.
+const MAX_SHOWN: usize = 3;
+/// Notifications waiting to be shown beyond this are refused.
+const MAX_QUEUED: usize = 32;
+
+const WIDTH: usize = 480;
+const HEIGHT: usize = 96;
+const ICON_PADDING: usize = 16;
+const TEXT_FILL: [u8; 4] = [20, 20, 24, 230];
+
+/// An image given with CreateNotification.
+pub(super) struct Icon {
+    /// RGBA8, top row first.
+    pixels: Vec<u8>,
+    width: usize,
+    height: usize,
+}
+
+impl Icon {
+    /// Copies an RGB or RGBA image. Returns None for anything else.
+    pub fn from_bitmap(bitmap: &vr::NotificationBitmap_t) -> Option<Self> {
+        let width = usize::try_from(bitmap.m_nWidth).ok()?;
+        let height = usize::try_from(bitmap.m_nHeight).ok()?;
+        let bytes_per_pixel = match bitmap.m_nBytesPerPixel {
+            bpp @ (3 | 4) => bpp as usize,
+            _ => return None,
+        };
+        if bitmap.m_pImageData.is_null() || width == 0 || height == 0 {
+            return None;
+        }
+        let data = unsafe {
+            std::slice::from_raw_parts(
+                bitmap.m_pImageData as *const u8,
+                width * height * bytes_per_pixel,
+            )
+        };
+        let pixels = data
+            .chunks_exact(bytes_per_pixel)
+            .flat_map(|px| [px[0], px[1], px[2], px.get(3).copied().unwrap_or(255)])
+            .collect();
+        Some(Self {
+            pixels,
+            width,
+            height,
+        })
+    }
+}
+
+pub(super) struct Notification {
+    pub id: vr::VRNotificationId,
+    /// The overlay that created the notification.
+    pub target: OverlayKey,
+    pub user_value: u64,
+    pub ty: vr::EVRNotificationType,
+    /// The overlay showing the toast.
+    pub toast: Option<OverlayKey>,
+    /// Set once its turn has come.
+    pub shown: bool,
+    /// When a shown transient notification goes away.
+    expires: Option<Instant>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) enum NotificationError {
+    QueueFull,
+    UserValueExists,
+}
+
+/// Notifications from CreateNotification, shown as toasts below the center of the user's view
+/// in the order they were created.
+#[derive(Default)]
+pub(super) struct Notifications {
+    next_id: vr::VRNotificationId,
+    /// Shown ones first.
+    pub queue: Vec<Notification>,
+}
+
+impl Notifications {
+    pub fn add(
+        &mut self,
+        target: OverlayKey,
+        user_value: u64,
+        ty: vr::EVRNotificationType,
+    ) -> Result<vr::VRNotificationId, NotificationError> {
+        if self.queue.len() >= MAX_QUEUED {
+            return Err(NotificationError::QueueFull);
+        }
+        // Only one of these is allowed per user value, so apps can post them idempotently.
+        if ty == vr::EVRNotificationType::Transient_SystemWithUserValue
+            && self
+                .queue
+                .iter()
+                .any(|n| n.ty == ty && n.user_value == user_value)
+        {
+            return Err(NotificationError::UserValueExists);
+        }
+
+        self.next_id += 1;
+        self.queue.push(Notification {
+            id: self.next_id,
+            target,
+            user_value,
+            ty,
+            toast: None,
+            shown: false,
+            expires: None,
+        });
+        Ok(self.next_id)
+    }
+
+    pub fn remove(&mut self, id: vr::VRNotificationId) -> Option<Notification> {
+        let idx = self.queue.iter().position(|n| n.id == id)?;
+        Some(self.queue.remove(idx))
+    }
+
+    /// Drops the notifications of a destroyed overlay.
+    pub fn remove_for(&mut self, target: OverlayKey) -> Vec<Notification> {
+        let (removed, kept) = std::mem::take(&mut self.queue)
+            .into_iter()
+            .partition(|n| n.target == target);
+        self.queue = kept;
+        removed
+    }
+
+    /// Drops expired notifications and starts showing queued ones in their place. Returns the
+    /// dropped notifications.
+    pub fn update(&mut self, now: Instant) -> Vec<Notification> {
+        let (expired, kept) = std::mem::take(&mut self.queue)
+            .into_iter()
+            .partition(|n| n.expires.is_some_and(|expires| expires <= now));
+        self.queue = kept;
+
+        for notification in self.queue.iter_mut().take(MAX_SHOWN) {
+            if !notification.shown {
+                notification.shown = true;
+                if notification.ty != vr::EVRNotificationType::Persistent {
+                    notification.expires = Some(now + TRANSIENT_DURATION);
+                }
+            }
+        }
+        expired
+    }
+}
+
+/// The toast: the icon on the left, and up to two lines of text next to it.
+pub(super) fn render(text: &str, icon: Option<&Icon>) -> TextImage {
+    let text_x = if icon.is_some() { HEIGHT } else { 0 };
+    let mut image = text::render_labels(
+        WIDTH,
+        HEIGHT,
+        &[Label {
+            text: text.lines().take(2).collect::<Vec<_>>().join("\n"),
+            rect: [text_x, 0, WIDTH - text_x, HEIGHT],
+            fill: TEXT_FILL,
+            centered: false,
+        }],
+    );
+    if let Some(icon) = icon {
+        draw_icon(&mut image.pixels, icon);
+    }
+    image
+}
+
+/// Scales the icon into the square at the left of the toast.
+fn draw_icon(pixels: &mut [u8], icon: &Icon) {
+    let size = HEIGHT - 2 * ICON_PADDING;
+    if icon.width == 0 || icon.height == 0 {
+        return;
+    }
+    for y in 0..size {
+        for x in 0..size {
+            let src_x = x * icon.width / size;
+            let src_y = y * icon.height / size;
+            let src = &icon.pixels[(src_y * icon.width + src_x) * 4..][..4];
+            let dst = ((y + ICON_PADDING) * WIDTH + x + ICON_PADDING) * 4;
+            let alpha = src[3] as u32;
+            for channel in 0..3 {
+                let under = pixels[dst + channel] as u32;
+                pixels[dst + channel] =
+                    ((src[channel] as u32 * alpha + under * (255 - alpha)) / 255) as u8;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slotmap::KeyData;
+
+    fn target() -> OverlayKey {
+        OverlayKey::from(KeyData::from_ffi(1))
+    }
+
+    #[test]
+    fn shows_queued_notifications_in_turn() {
+        let mut notifications = Notifications::default();
+        let ids: Vec<_> = (0..MAX_SHOWN + 1)
+            .map(|idx| {
+                notifications
+                    .add(target(), idx as u64, vr::EVRNotificationType::Transient)
+                    .unwrap()
+            })
+            .collect();
+
+        let start = Instant::now();
+        assert!(notifications.update(start).is_empty());
+        assert!(notifications.queue[..MAX_SHOWN].iter().all(|n| n.shown));
+        assert!(!notifications.queue[MAX_SHOWN].shown);
+
+        // Removing one lets the last one in.
+        assert_eq!(notifications.remove(ids[0]).unwrap().id, ids[0]);
+        assert!(notifications.update(start).is_empty());
+        assert!(notifications.queue.iter().all(|n| n.shown));
+
+        let expired = notifications.update(start + TRANSIENT_DURATION);
+        assert_eq!(expired.len(), MAX_SHOWN);
+        assert!(notifications.queue.is_empty());
+    }
+
+    #[test]
+    fn persistent_notifications_stay() {
+        let mut notifications = Notifications::default();
+        notifications
+            .add(target(), 0, vr::EVRNotificationType::Persistent)
+            .unwrap();
+        let start = Instant::now();
+        notifications.update(start);
+        assert!(notifications
+            .update(start + TRANSIENT_DURATION * 10)
+            .is_empty());
+        assert_eq!(notifications.remove_for(target()).len(), 1);
+    }
+
+    #[test]
+    fn one_system_notification_per_user_value() {
+        let mut notifications = Notifications::default();
+        let ty = vr::EVRNotificationType::Transient_SystemWithUserValue;
+        assert!(notifications.add(target(), 7, ty).is_ok());
+        assert_eq!(
+            notifications.add(target(), 7, ty),
+            Err(NotificationError::UserValueExists)
+        );
+        assert!(notifications.add(target(), 8, ty).is_ok());
+    }
+
+    #[test]
+    fn draws_icon() {
+        let red = [255, 0, 0];
+        let mut data = red.repeat(4);
+        let bitmap = vr::NotificationBitmap_t {
+            m_pImageData: data.as_mut_ptr() as *mut _,
+            m_nWidth: 2,
+            m_nHeight: 2,
+            m_nBytesPerPixel: 3,
+        };
+        let icon = Icon::from_bitmap(&bitmap).unwrap();
+        assert_eq!(icon.pixels, [255, 0, 0, 255].repeat(4));
+
+        let image = render("hi", Some(&icon));
+        let center = (HEIGHT / 2 * WIDTH + HEIGHT / 2) * 4;
+        assert_eq!(image.pixels[center..center + 3], red);
+    }
+}
@@ -0,0 +1,277 @@
+use super::OverlayKey;
+use crate::openxr_data::Hand;
+use glam::{Quat, Vec2, Vec3};
+use openxr as xr;
+
+/// Diameter of the cursor dot, in pixels.
+const CURSOR_SIZE: i32 = 32;
+
+/// Where a ray hit an overlay.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct Intersection {
+    pub point: Vec3,
+    pub normal: Vec3,
+    /// From the bottom left of the overlay, like mouse coordinates.
+    pub uv: Vec2,
+    pub distance: f32,
+}
+
+/// Intersects a ray with a flat overlay of the given size, centered on `pose` and facing +Z.
+pub(super) fn intersect(
+    pose: xr::Posef,
+    width: f32,
+    height: f32,
+    source: Vec3,
+    direction: Vec3,
+) -> Option<Intersection> {
+    let (rotation, center) = to_glam(pose);
+    let direction = direction.try_normalize()?;
+    let normal = rotation.mul_vec3(Vec3::Z);
+    let facing = direction.dot(normal);
+    if facing.abs() < 1e-6 {
+        return None;
+    }
+    let distance = (center - source).dot(normal) / facing;
+    if distance < 0.0 {
+        return None;
+    }
+
+    let point = source + direction * distance;
+    let local = rotation.inverse().mul_vec3(point - center);
+    if local.x.abs() > width / 2.0 || local.y.abs() > height / 2.0 {
+        return None;
+    }
+    Some(Intersection {
+        point,
+        normal,
+        uv: Vec2::new(local.x / width + 0.5, local.y / height + 0.5),
+        distance,
+    })
+}
+
+/// The ray a controller points along, from its aim pose.
+pub(super) fn aim_ray(pose: xr::Posef) -> (Vec3, Vec3) {
+    let (rotation, position) = to_glam(pose);
+    (position, rotation.mul_vec3(Vec3::NEG_Z))
+}
+
+fn to_glam(pose: xr::Posef) -> (Quat, Vec3) {
+    let o = pose.orientation;
+    let p = pose.position;
+    (
+        Quat::from_xyzw(o.x, o.y, o.z, o.w),
+        Vec3::new(p.x, p.y, p.z),
+    )
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum MouseEvent {
+    FocusEnter,
+    FocusLeave,
+    Move,
+    ButtonDown,
+    ButtonUp,
+}
+
+/// Tracks the laser pointer of the primary controller across overlays that take mouse input.
+pub(super) struct Pointer {
+    /// The controller pointing at overlays, which is the last one to pull its trigger.
+    pub hand: Hand,
+    /// The overlay being pointed at, and where.
+    pub hover: Option<(OverlayKey, Vec2)>,
+    /// Whether the hovered overlay was sent a button press that hasn't been released yet.
+    pressed: bool,
+    /// Trigger state of each hand as of the last update.
+    triggers: [bool; 2],
+}
+
+impl Default for Pointer {
+    fn default() -> Self {
+        Self {
+            hand: Hand::Right,
+            hover: None,
+            pressed: false,
+            triggers: [false; 2],
+        }
+    }
+}
+
+impl Pointer {
+    /// Hands the pointer to a controller that just pulled its trigger.
+    pub fn select_hand(&mut self, triggers: [bool; 2]) {
+        for (hand, idx) in [(Hand::Left, 0), (Hand::Right, 1)] {
+            if triggers[idx] && !self.triggers[idx] && hand != self.hand {
+                self.hand = hand;
+            }
+        }
+    }
+
+    /// Moves the pointer to where the primary controller is pointing, returning the mouse events
+    /// that causes.
+    pub fn update(
+        &mut self,
+        target: Option<(OverlayKey, Vec2)>,
+        triggers: [bool; 2],
+    ) -> Vec<(OverlayKey, MouseEvent, Vec2)> {
+        let idx = self.hand as usize - 1;
+        let (held, was_held) = (triggers[idx], self.triggers[idx]);
+        self.triggers = triggers;
+
+        let mut events = Vec::new();
+        if let Some((key, uv)) = self.hover {
+            if target.is_none_or(|(target, _)| target != key) {
+                if self.pressed {
+                    events.push((key, MouseEvent::ButtonUp, uv));
+                    self.pressed = false;
+                }
+                events.push((key, MouseEvent::FocusLeave, uv));
+            }
+        }
+        if let Some((key, uv)) = target {
+            if self.hover.is_none_or(|(hovered, _)| hovered != key) {
+                events.push((key, MouseEvent::FocusEnter, uv));
+            }
+            if self.hover != target {
+                events.push((key, MouseEvent::Move, uv));
+            }
+            if held && !was_held {
+                events.push((key, MouseEvent::ButtonDown, uv));
+                self.pressed = true;
+            } else if !held && self.pressed {
+                events.push((key, MouseEvent::ButtonUp, uv));
+                self.pressed = false;
+            }
+        }
+        self.hover = target;
+        events
+    }
+}
+
+/// A white dot with a dark outline, as tightly packed sRGB RGBA8 pixels.
+pub(super) fn cursor_image() -> (Vec<u8>, xr::Extent2Di) {
+    let radius = CURSOR_SIZE as f32 / 2.0;
+    let mut pixels = Vec::with_capacity((CURSOR_SIZE * CURSOR_SIZE * 4) as usize);
+    for y in 0..CURSOR_SIZE {
+        for x in 0..CURSOR_SIZE {
+            let offset = Vec2::new(x as f32 + 0.5, y as f32 + 0.5) - Vec2::splat(radius);
+            let distance = offset.length();
+            let color = if distance < radius * 0.6 { 255 } else { 32 };
+            let alpha = ((radius - distance).clamp(0.0, 1.0) * 255.0) as u8;
+            pixels.extend_from_slice(&[color, color, color, alpha]);
+        }
+    }
+    (
+        pixels,
+        xr::Extent2Di {
+            width: CURSOR_SIZE,
+            height: CURSOR_SIZE,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slotmap::KeyData;
+
+    fn pose(rotation: Quat, position: Vec3) -> xr::Posef {
+        xr::Posef {
+            orientation: xr::Quaternionf {
+                x: rotation.x,
+                y: rotation.y,
+                z: rotation.z,
+                w: rotation.w,
+            },
+            position: xr::Vector3f {
+                x: position.x,
+                y: position.y,
+                z: position.z,
+            },
+        }
+    }
+
+    #[test]
+    fn intersects_overlay() {
+        // 2x1 overlay two meters ahead, facing the user.
+        let overlay = pose(Quat::IDENTITY, Vec3::new(0.0, 1.0, -2.0));
+        let hit = intersect(
+            overlay,
+            2.0,
+            1.0,
+            Vec3::new(0.5, 1.25, 0.0),
+            Vec3::NEG_Z * 3.0,
+        )
+        .unwrap();
+        assert!(hit.point.abs_diff_eq(Vec3::new(0.5, 1.25, -2.0), 1e-5));
+        assert!(hit.normal.abs_diff_eq(Vec3::Z, 1e-5));
+        assert!(hit.uv.abs_diff_eq(Vec2::new(0.75, 0.75), 1e-5));
+        assert!((hit.distance - 2.0).abs() < 1e-5);
+
+        // Pointing past it, away from it, or along it.
+        let miss = |source, direction| intersect(overlay, 2.0, 1.0, source, direction);
+        assert_eq!(miss(Vec3::new(1.5, 1.0, 0.0), Vec3::NEG_Z), None);
+        assert_eq!(miss(Vec3::new(0.0, 1.0, 0.0), Vec3::Z), None);
+        assert_eq!(miss(Vec3::new(0.0, 1.0, 0.0), Vec3::X), None);
+    }
+
+    #[test]
+    fn intersects_rotated_overlay() {
+        // Off to the right, facing left.
+        let overlay = pose(
+            Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+            Vec3::new(-1.0, 0.0, 0.0),
+        );
+        let hit = intersect(overlay, 1.0, 1.0, Vec3::ZERO, Vec3::NEG_X).unwrap();
+        assert!(hit.uv.abs_diff_eq(Vec2::splat(0.5), 1e-5));
+        assert!((hit.distance - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn clicks_follow_primary_trigger() {
+        let a = OverlayKey::from(KeyData::from_ffi(1));
+        let b = OverlayKey::from(KeyData::from_ffi(2));
+        let uv = Vec2::splat(0.5);
+        let mut pointer = Pointer::default();
+
+        assert_eq!(
+            pointer.update(Some((a, uv)), [false, false]),
+            [(a, MouseEvent::FocusEnter, uv), (a, MouseEvent::Move, uv)]
+        );
+        assert_eq!(pointer.update(Some((a, uv)), [false, false]), []);
+        assert_eq!(
+            pointer.update(Some((a, uv)), [false, true]),
+            [(a, MouseEvent::ButtonDown, uv)]
+        );
+
+        // Moving to another overlay releases the button on the first one.
+        assert_eq!(
+            pointer.update(Some((b, uv)), [false, true]),
+            [
+                (a, MouseEvent::ButtonUp, uv),
+                (a, MouseEvent::FocusLeave, uv),
+                (b, MouseEvent::FocusEnter, uv),
+                (b, MouseEvent::Move, uv)
+            ]
+        );
+        assert_eq!(pointer.update(Some((b, uv)), [false, false]), []);
+
+        // The other hand takes over by pulling its trigger.
+        let triggers = [true, false];
+        pointer.select_hand(triggers);
+        assert_eq!(pointer.hand, Hand::Left);
+        assert_eq!(
+            pointer.update(Some((b, uv)), triggers),
+            [(b, MouseEvent::ButtonDown, uv)]
+        );
+        assert_eq!(
+            pointer.update(Some((b, uv)), [false, false]),
+            [(b, MouseEvent::ButtonUp, uv)]
+        );
+
+        // Pointing away.
+        assert_eq!(
+            pointer.update(None, [false, false]),
+            [(b, MouseEvent::FocusLeave, uv)]
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use egui::epaint::{text::FontDefinitions, Color32, FontId, FontImage, Fonts, Galley};
+use openxr as xr;
+
+const PADDING: usize = 24;
+const BACKGROUND: [u8; 4] = [20, 20, 24, 230];
+const FONT_SIZE: f32 = 24.0;
+
+pub struct TextImage {
+    /// sRGB RGBA8, top row first.
+    pub pixels: Vec<u8>,
+    pub extent: xr::Extent2Di,
+}
+
+/// Draws white text lines onto a dark panel, which is sized to fit them.
+pub fn render_lines(lines: &[String]) -> TextImage {
+    let fonts = Fonts::new(1.0, 2048, FontDefinitions::default());
+    let font = FontId::monospace(FONT_SIZE);
+    let line_height = fonts.row_height(&font).ceil() as usize;
+    let galleys: Vec<_> = lines
+        .iter()
+        .map(|line| fonts.layout_no_wrap(line.clone(), font.clone(), Color32::WHITE))
+        .collect();
+    // The atlas is only complete once everything has been laid out.
+    let atlas = fonts.image();
+
+    let text_width = galleys
+        .iter()
+        .map(|galley| galley.size().x.ceil() as usize)
+        .max()
+        .unwrap_or(0);
+    let width = text_width + 2 * PADDING;
+    let height = line_height * lines.len() + 2 * PADDING;
+    let mut pixels = BACKGROUND.repeat(width * height);
+    for (idx, galley) in galleys.iter().enumerate() {
+        draw_galley(
+            &mut pixels,
+            width,
+            &atlas,
+            galley,
+            (PADDING, PADDING + idx * line_height),
+        );
+    }
+
+    TextImage {
+        pixels,
+        extent: xr::Extent2Di {
+            width: width as i32,
+            height: height as i32,
+        },
+    }
+}
+
+/// A line of text on a filled rectangle.
+pub struct Label {
+    pub text: String,
+    /// x, y, width, height in pixels, from the top left.
+    pub rect: [usize; 4],
+    pub fill: [u8; 4],
+    /// Centered in the rectangle, or else at its left.
+    pub centered: bool,
+}
+
+/// Draws labels onto a dark panel of the given size.
+pub fn render_labels(width: usize, height: usize, labels: &[Label]) -> TextImage {
+    let fonts = Fonts::new(1.0, 2048, FontDefinitions::default());
+    let font = FontId::proportional(FONT_SIZE);
+    let galleys: Vec<_> = labels
+        .iter()
+        .map(|label| fonts.layout_no_wrap(label.text.clone(), font.clone(), Color32::WHITE))
+        .collect();
+    let atlas = fonts.image();
+
+    let mut pixels = BACKGROUND.repeat(width * height);
+    for (label, galley) in labels.iter().zip(&galleys) {
+        let [x, y, w, h] = label.rect;
+        for row in y..(y + h).min(height) {
+            let start = (row * width + x) * 4;
+            let end = (row * width + (x + w).min(width)) * 4;
+            for px in pixels[start..end].chunks_exact_mut(4) {
+                px.copy_from_slice(&label.fill);
+            }
+        }
+        let size = galley.size();
+        let text_x = if label.centered {
+            x + (w.saturating_sub(size.x.ceil() as usize)) / 2
+        } else {
+            x + PADDING / 2
+        };
+        let text_y = y + (h.saturating_sub(size.y.ceil() as usize)) / 2;
+        draw_galley(&mut pixels, width, &atlas, galley, (text_x, text_y));
+    }
+
+    TextImage {
+        pixels,
+        extent: xr::Extent2Di {
+            width: width as i32,
+            height: height as i32,
+        },
+    }
+}
+
+fn draw_galley(
+    pixels: &mut [u8],
+    width: usize,
+    atlas: &FontImage,
+    galley: &Galley,
+    (x, y): (usize, usize),
+) {
+    let height = pixels.len() / 4 / width;
+    for glyph in galley.rows.iter().flat_map(|row| &row.glyphs) {
+        let uv = glyph.uv_rect;
+        if uv.is_nothing() {
+            continue;
+        }
+        let left = (x as f32 + glyph.pos.x + uv.offset.x).round() as isize;
+        let top = (y as f32 + glyph.pos.y + uv.offset.y).round() as isize;
+        for (dy, ty) in (uv.min[1]..uv.max[1]).enumerate() {
+            for (dx, tx) in (uv.min[0]..uv.max[0]).enumerate() {
+                let (px, py) = (left + dx as isize, top + dy as isize);
+                if px < 0 || py < 0 || px as usize >= width || py as usize >= height {
+                    continue;
+                }
+                let coverage = atlas.pixels[ty as usize * atlas.size[0] + tx as usize];
+                let idx = (py as usize * width + px as usize) * 4;
+                for channel in &mut pixels[idx..idx + 3] {
+                    *channel = (*channel as f32 + (255.0 - *channel as f32) * coverage) as u8;
+                }
+                pixels[idx + 3] = pixels[idx + 3].max((coverage * 255.0) as u8);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panel_fits_text() {
+        let short = render_lines(&["a".into()]);
+        let long = render_lines(&["a".into(), "a much longer line".into()]);
+        assert!(long.extent.width > short.extent.width);
+        assert!(long.extent.height > short.extent.height);
+        assert_eq!(
+            long.pixels.len(),
+            (long.extent.width * long.extent.height * 4) as usize
+        );
+        // Some of the text should have been drawn over the background.
+        assert!(long
+            .pixels
+            .chunks_exact(4)
+            .any(|px| px[..3] != BACKGROUND[..3]));
+    }
+}
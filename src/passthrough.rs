@@ -0,0 +1,196 @@
+//! Shows the real world behind the game on headsets with cameras or see-through displays. Games
+//! opt in by rendering a transparent background, and it's turned on by the config or by the game
+//! itself through the xrizer/passthrough setting.
+
+use crate::config::Config;
+use log::{error, info};
+use openxr as xr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+fn enabled() -> &'static AtomicBool {
+    static ENABLED: OnceLock<AtomicBool> = OnceLock::new();
+    ENABLED.get_or_init(|| AtomicBool::new(Config::get().passthrough()))
+}
+
+pub fn is_enabled() -> bool {
+    enabled().load(Ordering::Relaxed)
+}
+
+pub fn set_enabled(value: bool) {
+    if enabled().swap(value, Ordering::Relaxed) != value {
+        info!("Passthrough {}", if value { "enabled" } else { "disabled" });
+    }
+}
+
+/// How a frame is composited with the real world.
+pub struct PassthroughFrame {
+    pub blend_mode: xr::EnvironmentBlendMode,
+    /// Has to be submitted below all other layers.
+    pub layer: Option<xr::sys::CompositionLayerPassthroughFB>,
+    /// Whether the projection layer should be blended with what's behind it using its alpha.
+    pub blend_alpha: bool,
+}
+
+pub struct Passthrough {
+    /// For frames without passthrough - see-through headsets may not support opaque.
+    opaque_mode: xr::EnvironmentBlendMode,
+    alpha_blend: bool,
+    additive: bool,
+    /// Only created if the runtime can't alpha blend by itself.
+    fb: Option<FbPassthrough>,
+}
+
+impl Passthrough {
+    pub fn new(
+        session: &xr::Session<xr::AnyGraphics>,
+        system_id: xr::SystemId,
+        view_configuration: xr::ViewConfigurationType,
+    ) -> Self {
+        let modes = session
+            .instance()
+            .enumerate_environment_blend_modes(system_id, view_configuration)
+            .unwrap_or_else(|e| {
+                error!("Failed to enumerate environment blend modes: {e}");
+                Vec::new()
+            });
+        let opaque_mode = match modes.first() {
+            Some(&mode) if !modes.contains(&xr::EnvironmentBlendMode::OPAQUE) => mode,
+            _ => xr::EnvironmentBlendMode::OPAQUE,
+        };
+        let alpha_blend = modes.contains(&xr::EnvironmentBlendMode::ALPHA_BLEND);
+        let additive = modes.contains(&xr::EnvironmentBlendMode::ADDITIVE);
+        let fb = if alpha_blend {
+            None
+        } else {
+            FbPassthrough::new(session)
+        };
+
+        Self {
+            opaque_mode,
+            alpha_blend,
+            additive,
+            fb,
+        }
+    }
+
+    pub fn frame(&self) -> PassthroughFrame {
+        let enabled = is_enabled();
+        let fb_running = self.fb.as_ref().is_some_and(|fb| fb.set_running(enabled));
+
+        let (blend_mode, layer, blend_alpha) = if !enabled {
+            (self.opaque_mode, None, false)
+        } else if self.alpha_blend {
+            (xr::EnvironmentBlendMode::ALPHA_BLEND, None, true)
+        } else if fb_running {
+            let layer = self.fb.as_ref().map(FbPassthrough::layer);
+            (self.opaque_mode, layer, true)
+        } else if self.additive {
+            (xr::EnvironmentBlendMode::ADDITIVE, None, false)
+        } else {
+            crate::warn_once!("Passthrough is enabled, but the runtime doesn't support it");
+            (self.opaque_mode, None, false)
+        };
+
+        PassthroughFrame {
+            blend_mode,
+            layer,
+            blend_alpha,
+        }
+    }
+}
+
+/// Camera passthrough through XR_FB_passthrough, drawn as a layer below the game.
+struct FbPassthrough {
+    // Keeps the session alive for as long as the passthrough exists.
+    _session: xr::Session<xr::AnyGraphics>,
+    fp: xr::raw::PassthroughFB,
+    passthrough: xr::sys::PassthroughFB,
+    layer: xr::sys::PassthroughLayerFB,
+    running: AtomicBool,
+}
+
+impl FbPassthrough {
+    fn new(session: &xr::Session<xr::AnyGraphics>) -> Option<Self> {
+        let fp = session.instance().exts().fb_passthrough?;
+
+        // The cameras only run while passthrough is enabled.
+        let create_info = xr::sys::PassthroughCreateInfoFB {
+            ty: xr::sys::PassthroughCreateInfoFB::TYPE,
+            next: std::ptr::null(),
+            flags: xr::sys::PassthroughFlagsFB::EMPTY,
+        };
+        let mut passthrough = xr::sys::PassthroughFB::NULL;
+        let result =
+            unsafe { (fp.create_passthrough)(session.as_raw(), &create_info, &mut passthrough) };
+        if result != xr::sys::Result::SUCCESS {
+            error!("Failed to create passthrough: {result}");
+            return None;
+        }
+
+        let layer_info = xr::sys::PassthroughLayerCreateInfoFB {
+            ty: xr::sys::PassthroughLayerCreateInfoFB::TYPE,
+            next: std::ptr::null(),
+            passthrough,
+            flags: xr::sys::PassthroughFlagsFB::IS_RUNNING_AT_CREATION,
+            purpose: xr::sys::PassthroughLayerPurposeFB::RECONSTRUCTION,
+        };
+        let mut layer = xr::sys::PassthroughLayerFB::NULL;
+        let result =
+            unsafe { (fp.create_passthrough_layer)(session.as_raw(), &layer_info, &mut layer) };
+        if result != xr::sys::Result::SUCCESS {
+            error!("Failed to create passthrough layer: {result}");
+            unsafe { (fp.destroy_passthrough)(passthrough) };
+            return None;
+        }
+        info!("Created XR_FB_passthrough layer");
+
+        Some(Self {
+            _session: session.clone(),
+            fp,
+            passthrough,
+            layer,
+            running: AtomicBool::new(false),
+        })
+    }
+
+    /// Starts or pauses the cameras. Returns whether they're running.
+    fn set_running(&self, running: bool) -> bool {
+        if self.running.load(Ordering::Relaxed) == running {
+            return running;
+        }
+
+        let result = unsafe {
+            if running {
+                (self.fp.passthrough_start)(self.passthrough)
+            } else {
+                (self.fp.passthrough_pause)(self.passthrough)
+            }
+        };
+        if result != xr::sys::Result::SUCCESS {
+            crate::warn_once!("Failed to start or pause passthrough: {result}");
+            return !running;
+        }
+        self.running.store(running, Ordering::Relaxed);
+        running
+    }
+
+    fn layer(&self) -> xr::sys::CompositionLayerPassthroughFB {
+        xr::sys::CompositionLayerPassthroughFB {
+            ty: xr::sys::CompositionLayerPassthroughFB::TYPE,
+            next: std::ptr::null(),
+            flags: xr::CompositionLayerFlags::EMPTY,
+            space: xr::sys::Space::NULL,
+            layer_handle: self.layer,
+        }
+    }
+}
+
+impl Drop for FbPassthrough {
+    fn drop(&mut self) {
+        unsafe {
+            (self.fp.destroy_passthrough_layer)(self.layer);
+            (self.fp.destroy_passthrough)(self.passthrough);
+        }
+    }
+}
@@ -1,68 +1,348 @@
+use crate::{
+    clientcore::{Injected, Injector},
+    compositor::Compositor,
+    mirror::MirrorImage,
+};
+use log::{debug, error, info, warn};
 use openvr as vr;
+use openxr as xr;
+use std::collections::{HashMap, VecDeque};
+use std::ffi::{c_char, CStr, CString};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
 
-#[derive(Default, macros::InterfaceImpl)]
+#[derive(macros::InterfaceImpl)]
 #[interface = "IVRScreenshots"]
 #[versions(001)]
 pub struct Screenshots {
     vtables: Vtables,
+    compositor: Injected<Compositor>,
+    next_handle: AtomicU32,
+    screenshots: Mutex<HashMap<vr::ScreenshotHandle_t, Screenshot>>,
+    /// Types the app takes screenshots of itself.
+    hooked_types: Mutex<Vec<vr::EVRScreenshotType>>,
+    events: Arc<Mutex<VecDeque<ScreenshotEvent>>>,
+    /// Set while a screenshot is waiting for a frame or being written.
+    taking: Arc<AtomicBool>,
+}
+
+struct Screenshot {
+    ty: vr::EVRScreenshotType,
+    preview_filename: CString,
+    vr_filename: CString,
+}
+
+pub struct ScreenshotEvent {
+    pub ty: vr::EVREventType,
+    pub data: vr::VREvent_Screenshot_t,
+}
+
+impl Screenshots {
+    pub fn new(injector: &Injector) -> Self {
+        Self {
+            vtables: Default::default(),
+            compositor: injector.inject(),
+            next_handle: 1.into(),
+            screenshots: Mutex::default(),
+            hooked_types: Mutex::default(),
+            events: Arc::default(),
+            taking: Arc::default(),
+        }
+    }
+
+    pub fn poll_event(&self) -> Option<ScreenshotEvent> {
+        self.events.lock().unwrap().pop_front()
+    }
+
+    fn add_screenshot(
+        &self,
+        ty: vr::EVRScreenshotType,
+        preview_filename: *const c_char,
+        vr_filename: *const c_char,
+    ) -> vr::ScreenshotHandle_t {
+        let filename = |name: *const c_char| {
+            (!name.is_null())
+                .then(|| unsafe { CStr::from_ptr(name) }.to_owned())
+                .unwrap_or_default()
+        };
+        let handle = self.next_handle.fetch_add(1, Ordering::Relaxed);
+        self.screenshots.lock().unwrap().insert(
+            handle,
+            Screenshot {
+                ty,
+                preview_filename: filename(preview_filename),
+                vr_filename: filename(vr_filename),
+            },
+        );
+        handle
+    }
+
+    /// Takes the screenshot from the next frame the game submits.
+    fn take_screenshot(&self, handle: vr::ScreenshotHandle_t) -> vr::EVRScreenshotError {
+        if self.taking.swap(true, Ordering::Relaxed) {
+            return vr::EVRScreenshotError::ScreenshotAlreadyInProgress;
+        }
+
+        let (ty, preview_path, vr_path) = {
+            let screenshots = self.screenshots.lock().unwrap();
+            let screenshot = &screenshots[&handle];
+            (
+                screenshot.ty,
+                png_path(&screenshot.preview_filename),
+                png_path(&screenshot.vr_filename),
+            )
+        };
+        let events = self.events.clone();
+        // Also released if the frame controller goes away before the screenshot is taken.
+        let taking = TakingGuard(self.taking.clone());
+        let callback = Box::new(move |image: Option<(MirrorImage, xr::Extent2Di)>| {
+            // Writing the files can take a while, so don't hold up the frame.
+            std::thread::spawn(move || {
+                let result = match image {
+                    Some((image, left_extent)) => {
+                        write_screenshot(&image, left_extent, ty, &preview_path, &vr_path)
+                    }
+                    None => Err(std::io::Error::other("the frame couldn't be read back")),
+                };
+                let event_type = match result {
+                    Ok(()) => {
+                        info!("Saved screenshot to {}", preview_path.display());
+                        vr::EVREventType::ScreenshotTaken
+                    }
+                    Err(e) => {
+                        error!("Failed to take screenshot: {e}");
+                        vr::EVREventType::ScreenshotFailed
+                    }
+                };
+                events.lock().unwrap().push_back(ScreenshotEvent {
+                    ty: event_type,
+                    data: vr::VREvent_Screenshot_t {
+                        handle,
+                        type_: ty as u32,
+                    },
+                });
+                drop(taking);
+            });
+        });
+
+        let requested = self
+            .compositor
+            .get()
+            .is_some_and(|compositor| compositor.request_screenshot(callback));
+        if !requested {
+            warn!("Can't take a screenshot without frames being submitted");
+            return vr::EVRScreenshotError::RequestFailed;
+        }
+        vr::EVRScreenshotError::None
+    }
+}
+
+struct TakingGuard(Arc<AtomicBool>);
+
+impl Drop for TakingGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Screenshot filenames are given without an extension.
+fn png_path(filename: &CStr) -> PathBuf {
+    let mut path = filename.to_string_lossy().into_owned();
+    path.push_str(".png");
+    path.into()
+}
+
+/// Writes the left eye to the preview image, and for stereo screenshots both eyes side by side
+/// to the VR image.
+fn write_screenshot(
+    image: &MirrorImage,
+    left_extent: xr::Extent2Di,
+    ty: vr::EVRScreenshotType,
+    preview_path: &Path,
+    vr_path: &Path,
+) -> std::io::Result<()> {
+    let (width, height) = (left_extent.width as usize, left_extent.height as usize);
+    let row_len = image.width as usize * 4;
+    let preview: Vec<u8> = image
+        .pixels
+        .chunks_exact(row_len)
+        .take(height)
+        .flat_map(|row| &row[..width * 4])
+        .copied()
+        .collect();
+    write_png(preview_path, width as u32, height as u32, &preview)?;
+
+    if ty == vr::EVRScreenshotType::Stereo {
+        write_png(vr_path, image.width, image.height, &image.pixels)?;
+    }
+    Ok(())
+}
+
+/// Writes sRGB RGBA8 pixels to a PNG.
+fn write_png(path: &Path, width: u32, height: u32, pixels: &[u8]) -> std::io::Result<()> {
+    image::save_buffer(path, pixels, width, height, image::ColorType::Rgba8)
+        .map_err(std::io::Error::other)
 }
 
 impl vr::IVRScreenshots001_Interface for Screenshots {
     fn SubmitScreenshot(
         &self,
         _: vr::ScreenshotHandle_t,
-        _: vr::EVRScreenshotType,
-        _: *const std::os::raw::c_char,
-        _: *const std::os::raw::c_char,
+        ty: vr::EVRScreenshotType,
+        preview_filename: *const c_char,
+        _: *const c_char,
     ) -> vr::EVRScreenshotError {
-        todo!()
+        // There's no screenshot library to submit it to, it's already saved where the app put it.
+        if !preview_filename.is_null() {
+            info!("Game submitted {ty:?} screenshot {:?}", unsafe {
+                CStr::from_ptr(preview_filename)
+            });
+        }
+        vr::EVRScreenshotError::None
     }
     fn TakeStereoScreenshot(
         &self,
-        _: *mut vr::ScreenshotHandle_t,
-        _: *const std::os::raw::c_char,
-        _: *const std::os::raw::c_char,
+        handle: *mut vr::ScreenshotHandle_t,
+        preview_filename: *const c_char,
+        vr_filename: *const c_char,
     ) -> vr::EVRScreenshotError {
-        todo!()
+        if handle.is_null() || preview_filename.is_null() || vr_filename.is_null() {
+            return vr::EVRScreenshotError::RequestFailed;
+        }
+
+        let new_handle =
+            self.add_screenshot(vr::EVRScreenshotType::Stereo, preview_filename, vr_filename);
+        unsafe { handle.write(new_handle) };
+        self.take_screenshot(new_handle)
     }
     fn UpdateScreenshotProgress(
         &self,
-        _: vr::ScreenshotHandle_t,
-        _: f32,
+        handle: vr::ScreenshotHandle_t,
+        progress: f32,
     ) -> vr::EVRScreenshotError {
-        todo!()
+        if !self.screenshots.lock().unwrap().contains_key(&handle) {
+            return vr::EVRScreenshotError::NotFound;
+        }
+        debug!("screenshot {handle} progress: {progress}");
+        vr::EVRScreenshotError::None
     }
     fn GetScreenshotPropertyFilename(
         &self,
-        _: vr::ScreenshotHandle_t,
-        _: vr::EVRScreenshotPropertyFilenames,
-        _: *mut std::os::raw::c_char,
-        _: u32,
-        _: *mut vr::EVRScreenshotError,
+        handle: vr::ScreenshotHandle_t,
+        filename_type: vr::EVRScreenshotPropertyFilenames,
+        filename: *mut c_char,
+        size: u32,
+        error: *mut vr::EVRScreenshotError,
     ) -> u32 {
-        todo!()
+        let set_error = |e| {
+            if let Some(error) = unsafe { error.as_mut() } {
+                *error = e;
+            }
+        };
+
+        let screenshots = self.screenshots.lock().unwrap();
+        let Some(screenshot) = screenshots.get(&handle) else {
+            set_error(vr::EVRScreenshotError::NotFound);
+            return 0;
+        };
+        let data = match filename_type {
+            vr::EVRScreenshotPropertyFilenames::Preview => &screenshot.preview_filename,
+            vr::EVRScreenshotPropertyFilenames::VR => &screenshot.vr_filename,
+        }
+        .to_bytes_with_nul();
+
+        if (size as usize) < data.len() || filename.is_null() {
+            set_error(vr::EVRScreenshotError::BufferTooSmall);
+        } else {
+            let buf = unsafe { std::slice::from_raw_parts_mut(filename.cast::<u8>(), data.len()) };
+            buf.copy_from_slice(data);
+            set_error(vr::EVRScreenshotError::None);
+        }
+        data.len() as u32
     }
     fn GetScreenshotPropertyType(
         &self,
-        _: vr::ScreenshotHandle_t,
-        _: *mut vr::EVRScreenshotError,
+        handle: vr::ScreenshotHandle_t,
+        error: *mut vr::EVRScreenshotError,
     ) -> vr::EVRScreenshotType {
-        todo!()
+        let ty = self.screenshots.lock().unwrap().get(&handle).map(|s| s.ty);
+        if let Some(error) = unsafe { error.as_mut() } {
+            *error = if ty.is_some() {
+                vr::EVRScreenshotError::None
+            } else {
+                vr::EVRScreenshotError::NotFound
+            };
+        }
+        ty.unwrap_or(vr::EVRScreenshotType::None)
     }
     fn HookScreenshot(
         &self,
-        _: *const vr::EVRScreenshotType,
-        _: std::os::raw::c_int,
+        types: *const vr::EVRScreenshotType,
+        count: std::os::raw::c_int,
     ) -> vr::EVRScreenshotError {
+        let types: &[_] = if types.is_null() || count <= 0 {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(types, count as usize) }
+        };
+        debug!("game hooked screenshots: {types:?}");
+        *self.hooked_types.lock().unwrap() = types.to_vec();
         vr::EVRScreenshotError::None
     }
     fn RequestScreenshot(
         &self,
-        _: *mut vr::ScreenshotHandle_t,
-        _: vr::EVRScreenshotType,
-        _: *const std::os::raw::c_char,
-        _: *const std::os::raw::c_char,
+        handle: *mut vr::ScreenshotHandle_t,
+        ty: vr::EVRScreenshotType,
+        preview_filename: *const c_char,
+        vr_filename: *const c_char,
     ) -> vr::EVRScreenshotError {
-        todo!()
+        if handle.is_null() || preview_filename.is_null() {
+            return vr::EVRScreenshotError::RequestFailed;
+        }
+
+        // Games that hooked this type want to take the screenshot themselves.
+        let hooked = self.hooked_types.lock().unwrap().contains(&ty);
+        let supported = match ty {
+            vr::EVRScreenshotType::Mono => true,
+            vr::EVRScreenshotType::Stereo => !vr_filename.is_null(),
+            _ => false,
+        };
+        if !hooked && !supported {
+            warn!("Can't take {ty:?} screenshot");
+            return vr::EVRScreenshotError::RequestFailed;
+        }
+
+        let new_handle = self.add_screenshot(ty, preview_filename, vr_filename);
+        unsafe { handle.write(new_handle) };
+        if hooked {
+            self.events.lock().unwrap().push_back(ScreenshotEvent {
+                ty: vr::EVREventType::RequestScreenshot,
+                data: vr::VREvent_Screenshot_t {
+                    handle: new_handle,
+                    type_: ty as u32,
+                },
+            });
+            return vr::EVRScreenshotError::None;
+        }
+        self.take_screenshot(new_handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn png_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("xrizer-screenshot-{}.png", std::process::id()));
+        let pixels = [255, 0, 0, 255, 0, 255, 0, 128];
+        write_png(&path, 2, 1, &pixels).unwrap();
+
+        let image = image::open(&path).unwrap().into_rgba8();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(image.dimensions(), (2, 1));
+        assert_eq!(image.as_raw(), &pixels);
     }
 }
@@ -1,9 +1,14 @@
+use crate::{color_gain, passthrough};
 use log::debug;
 use openvr as vr;
 use openvr::EVRSettingsError;
 use std::ffi::CStr;
 use std::os::raw::c_char;
 
+/// Settings that games can use to control xrizer itself.
+const XRIZER_SECTION: &str = "xrizer";
+const PASSTHROUGH_KEY: &str = "passthrough";
+
 #[derive(Default, macros::InterfaceImpl)]
 #[interface = "IVRSettings"]
 #[versions(003)]
@@ -37,6 +42,9 @@ impl vr::IVRSettings003_Interface for Settings {
         let section = unsafe { CStr::from_ptr(section) }.to_string_lossy();
         let key = unsafe { CStr::from_ptr(settings_key) }.to_string_lossy();
         debug!("Setting bool on {section}/{key} to {value}");
+        if section == XRIZER_SECTION && key == PASSTHROUGH_KEY {
+            passthrough::set_enabled(value);
+        }
         unsafe {
             *error = EVRSettingsError::None;
         }
@@ -67,6 +75,7 @@ impl vr::IVRSettings003_Interface for Settings {
         let section = unsafe { CStr::from_ptr(section) }.to_string_lossy();
         let key = unsafe { CStr::from_ptr(settings_key) }.to_string_lossy();
         debug!("Setting float on {section}/{key} to {value}");
+        color_gain::set_setting(&section, &key, value);
         unsafe {
             *error = EVRSettingsError::None;
         }
@@ -100,7 +109,7 @@ impl vr::IVRSettings003_Interface for Settings {
             *error = EVRSettingsError::None;
         }
         debug!("Getting bool on {section}/{key}");
-        false
+        section == XRIZER_SECTION && key == PASSTHROUGH_KEY && passthrough::is_enabled()
     }
 
     fn GetInt32(
@@ -130,7 +139,7 @@ impl vr::IVRSettings003_Interface for Settings {
             *error = EVRSettingsError::None;
         }
         debug!("Getting float on {section}/{key}");
-        0.0
+        color_gain::get_setting(&section, &key).unwrap_or(0.0)
     }
 
     fn GetString(
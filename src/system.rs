@@ -1,29 +1,65 @@
 use crate::{
     clientcore::{Injected, Injector},
-    input::Input,
-    openxr_data::{Hand, RealOpenXrData, SessionData},
+    config::Config,
+    dynamic_resolution::DynamicResolution,
+    input::{Input, GAMEPAD_DEVICE_INDEX},
+    openxr_data::{FocusEvent, Hand, RealOpenXrData, SessionData},
+    osc_trackers::{OscTrackers, FIRST_TRACKER_INDEX, MAX_TRACKERS},
+    overlay::OverlayMan,
+    screenshots::{ScreenshotEvent, Screenshots},
     tracy_span,
 };
 use glam::{Mat3, Quat, Vec3};
-use log::{debug, trace, warn};
+use log::{debug, info, trace, warn};
 use openvr as vr;
 use openxr as xr;
+use std::collections::VecDeque;
 use std::ffi::CStr;
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
+    atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
     Arc, Mutex,
 };
 
+/// What the application was last told about a controller.
 #[derive(Default)]
-struct ConnectedHands {
-    left: AtomicBool,
-    right: AtomicBool,
+struct KnownDevice {
+    connected: AtomicBool,
+    profile: AtomicU64,
+    battery: KnownBattery,
+}
+
+/// The battery level the application was last told about, in whole percent so it isn't told
+/// about every tiny fluctuation.
+struct KnownBattery(AtomicU32);
+
+impl Default for KnownBattery {
+    fn default() -> Self {
+        Self(AtomicU32::new(u32::MAX))
+    }
+}
+
+impl KnownBattery {
+    /// Returns true if the level changed enough to tell the application about it.
+    fn update(&self, level: Option<f32>) -> bool {
+        let percent = level.map_or(u32::MAX, |level| {
+            (level.clamp(0.0, 1.0) * 100.0).round() as u32
+        });
+        self.0.swap(percent, Ordering::Relaxed) != percent
+    }
+}
+
+struct DeviceEvent {
+    ty: vr::EVREventType,
+    index: vr::TrackedDeviceIndex_t,
+    property: Option<vr::ETrackedDeviceProperty>,
 }
 
 #[derive(Copy, Clone)]
 pub struct ViewData {
     pub flags: xr::ViewStateFlags,
     pub views: [xr::View; 2],
+    /// The inner views of each eye, with quad views.
+    pub insets: Option<[xr::View; 2]>,
 }
 
 #[derive(Default)]
@@ -48,20 +84,23 @@ impl ViewCache {
         };
 
         *data.get_or_insert_with(|| {
-            let (flags, views) = session
+            let (flags, mut views) = session
                 .session
                 .locate_views(
-                    xr::ViewConfigurationType::PRIMARY_STEREO,
+                    session.view_configuration,
                     display_time,
                     session.get_space_from_type(ty),
                 )
                 .expect("Couldn't locate views");
 
+            let insets = (views.len() == 4).then(|| [views[2], views[3]]);
+            views.truncate(2);
             ViewData {
                 flags,
                 views: views
                     .try_into()
                     .unwrap_or_else(|v: Vec<xr::View>| panic!("Expected 2 views, got {}", v.len())),
+                insets,
             }
         })
     }
@@ -73,8 +112,15 @@ impl ViewCache {
 pub struct System {
     openxr: Arc<RealOpenXrData>, // We don't need to test session restarting.
     input: Injected<Input<crate::compositor::Compositor>>,
+    screenshots: Injected<Screenshots>,
+    overlays: Injected<OverlayMan>,
     vtables: Vtables,
-    last_connected_hands: ConnectedHands,
+    /// Left hand, right hand and gamepad.
+    known_devices: [KnownDevice; 3],
+    tracker_batteries: [KnownBattery; MAX_TRACKERS],
+    /// Whether the application was last told the headset is being worn.
+    user_present: AtomicBool,
+    device_events: Mutex<VecDeque<DeviceEvent>>,
     views: Mutex<ViewCache>,
 }
 
@@ -87,8 +133,13 @@ impl System {
         Self {
             openxr,
             input: injector.inject(),
+            screenshots: injector.inject(),
+            overlays: injector.inject(),
             vtables: Default::default(),
-            last_connected_hands: Default::default(),
+            known_devices: Default::default(),
+            tracker_batteries: Default::default(),
+            user_present: AtomicBool::new(true),
+            device_events: Mutex::default(),
             views: Mutex::default(),
         }
     }
@@ -106,6 +157,111 @@ impl System {
         );
     }
 
+    /// Queues events for controllers that were connected, disconnected or swapped for another
+    /// kind of controller since the last call.
+    fn queue_device_changes(&self) {
+        let devices = [
+            (&self.openxr.left_hand, Hand::Left as u32),
+            (&self.openxr.right_hand, Hand::Right as u32),
+            (&self.openxr.gamepad, GAMEPAD_DEVICE_INDEX),
+        ];
+        let mut events = self.device_events.lock().unwrap();
+        for ((info, index), known) in devices.into_iter().zip(&self.known_devices) {
+            let connected = info.connected();
+            let profile = info.profile_path.load().into_raw();
+            let was_connected = known.connected.swap(connected, Ordering::Relaxed);
+            let old_profile = known.profile.swap(profile, Ordering::Relaxed);
+
+            if connected != was_connected {
+                debug!(
+                    "sending {} {}connected",
+                    info.path_name(),
+                    if connected { "" } else { "not " }
+                );
+                events.push_back(DeviceEvent {
+                    ty: if connected {
+                        vr::EVREventType::TrackedDeviceActivated
+                    } else {
+                        vr::EVREventType::TrackedDeviceDeactivated
+                    },
+                    index,
+                    property: None,
+                });
+            } else if connected && profile != old_profile {
+                // The controller was swapped for a different one, so it's effectively a new device
+                // with different properties.
+                debug!("sending {} changed controller", info.path_name());
+                events.push_back(DeviceEvent {
+                    ty: vr::EVREventType::TrackedDeviceActivated,
+                    index,
+                    property: None,
+                });
+                events.extend(
+                    [
+                        vr::ETrackedDeviceProperty::ControllerType_String,
+                        vr::ETrackedDeviceProperty::ModelNumber_String,
+                        vr::ETrackedDeviceProperty::RenderModelName_String,
+                    ]
+                    .map(|property| DeviceEvent {
+                        ty: vr::EVREventType::PropertyChanged,
+                        index,
+                        property: Some(property),
+                    }),
+                );
+            }
+        }
+        self.queue_battery_changes(&mut events);
+
+        // Taking the headset off is sent as the end of user interaction.
+        let present = self.openxr.user_present();
+        if self.user_present.swap(present, Ordering::Relaxed) != present {
+            debug!("sending headset {}worn", if present { "" } else { "not " });
+            events.push_back(DeviceEvent {
+                ty: if present {
+                    vr::EVREventType::TrackedDeviceUserInteractionStarted
+                } else {
+                    vr::EVREventType::TrackedDeviceUserInteractionEnded
+                },
+                index: vr::k_unTrackedDeviceIndex_Hmd,
+                property: None,
+            });
+        }
+    }
+
+    /// Queues property changes for controllers and trackers whose battery level changed.
+    fn queue_battery_changes(&self, events: &mut VecDeque<DeviceEvent>) {
+        let property = vr::ETrackedDeviceProperty::DeviceBatteryPercentage_Float;
+        let input = self.input.get();
+        let controllers = [Hand::Left, Hand::Right].map(|hand| {
+            let level = input
+                .as_ref()
+                .and_then(|input| input.get_controller_float_tracked_property(hand, property));
+            (hand as u32, level)
+        });
+        let trackers = OscTrackers::get();
+        let trackers = (0..MAX_TRACKERS as u32).map(|idx| {
+            let index = FIRST_TRACKER_INDEX + idx;
+            (index, trackers.and_then(|trackers| trackers.battery(index)))
+        });
+
+        // Skips the gamepad, which doesn't report its battery.
+        let known = self.known_devices[..controllers.len()]
+            .iter()
+            .map(|device| &device.battery)
+            .chain(&self.tracker_batteries);
+        for ((index, level), known) in controllers.into_iter().chain(trackers).zip(known) {
+            // Devices losing their battery level are disconnected, which the app hears about
+            // anyway.
+            if known.update(level) && level.is_some() {
+                events.push_back(DeviceEvent {
+                    ty: vr::EVREventType::PropertyChanged,
+                    index,
+                    property: Some(property),
+                });
+            }
+        }
+    }
+
     pub fn get_views(&self, ty: xr::ReferenceSpaceType) -> ViewData {
         tracy_span!();
         let session = self.openxr.session_data.get();
@@ -121,16 +277,35 @@ impl vr::IVRSystem022_Interface for System {
             .instance
             .enumerate_view_configuration_views(
                 self.openxr.system_id,
-                xr::ViewConfigurationType::PRIMARY_STEREO,
+                self.openxr.view_configuration,
             )
             .unwrap();
 
+        // The scale applies to the pixel count, so each side grows by its square root.
+        let dynamic_scale = DynamicResolution::get().map_or(1.0, DynamicResolution::scale);
+        let scale = (Config::get().supersample_scale() * dynamic_scale)
+            .max(0.0)
+            .sqrt();
+        let scaled = |recommended: u32, max: u32| {
+            ((recommended as f32 * scale).round() as u32).clamp(1, max.max(1))
+        };
+
         if !width.is_null() {
-            unsafe { *width = views[0].recommended_image_rect_width };
+            unsafe {
+                *width = scaled(
+                    views[0].recommended_image_rect_width,
+                    views[0].max_image_rect_width,
+                )
+            };
         }
 
         if !height.is_null() {
-            unsafe { *height = views[0].recommended_image_rect_height };
+            unsafe {
+                *height = scaled(
+                    views[0].recommended_image_rect_height,
+                    views[0].max_image_rect_height,
+                )
+            };
         }
     }
     fn GetProjectionMatrix(&self, eye: vr::EVREye, near_z: f32, far_z: f32) -> vr::HmdMatrix44_t {
@@ -221,7 +396,7 @@ impl vr::IVRSystem022_Interface for System {
         todo!()
     }
     fn AcknowledgeQuit_Exiting(&self) {
-        todo!()
+        info!("App is exiting");
     }
     fn PerformFirmwareUpdate(&self, _: vr::TrackedDeviceIndex_t) -> vr::EVRFirmwareError {
         todo!()
@@ -230,13 +405,13 @@ impl vr::IVRSystem022_Interface for System {
         false
     }
     fn ShouldApplicationPause(&self) -> bool {
-        false
+        !self.openxr.has_scene_focus() || !self.openxr.has_input_focus()
     }
     fn IsSteamVRDrawingControllers(&self) -> bool {
         todo!()
     }
     fn IsInputAvailable(&self) -> bool {
-        true
+        self.openxr.has_input_focus()
     }
     fn GetControllerAxisTypeNameFromEnum(
         &self,
@@ -247,7 +422,18 @@ impl vr::IVRSystem022_Interface for System {
     fn GetButtonIdNameFromEnum(&self, _: vr::EVRButtonId) -> *const std::os::raw::c_char {
         todo!()
     }
-    fn TriggerHapticPulse(&self, _: vr::TrackedDeviceIndex_t, _: u32, _: std::os::raw::c_ushort) {
+    fn TriggerHapticPulse(
+        &self,
+        device_index: vr::TrackedDeviceIndex_t,
+        _: u32,
+        duration_micros: std::os::raw::c_ushort,
+    ) {
+        if OscTrackers::is_tracker_index(device_index) {
+            if let Some(trackers) = OscTrackers::get() {
+                trackers.trigger_haptic(device_index, 1.0, duration_micros as f32 / 1e6, 0.0);
+            }
+            return;
+        }
         crate::warn_unimplemented!("TriggerHapticPulse");
     }
     fn GetControllerStateWithPose(
@@ -260,11 +446,15 @@ impl vr::IVRSystem022_Interface for System {
     ) -> bool {
         if self.GetControllerState(device_index, state, state_size) {
             unsafe {
-                *pose.as_mut().unwrap() = self
-                    .input
-                    .get()
-                    .unwrap()
-                    .get_controller_pose(Hand::try_from(device_index).unwrap(), Some(origin))
+                // Gamepads aren't tracked, so they don't have a pose.
+                *pose.as_mut().unwrap() = Hand::try_from(device_index)
+                    .ok()
+                    .and_then(|hand| {
+                        self.input
+                            .get()
+                            .unwrap()
+                            .get_controller_pose(hand, Some(origin))
+                    })
                     .unwrap_or_default();
             }
             true
@@ -305,11 +495,7 @@ impl vr::IVRSystem022_Interface for System {
         let session_data = self.openxr.session_data.get();
         let mask = session_data
             .session
-            .get_visibility_mask_khr(
-                xr::ViewConfigurationType::PRIMARY_STEREO,
-                eye as u32,
-                mask_ty,
-            )
+            .get_visibility_mask_khr(session_data.view_configuration, eye as u32, mask_ty)
             .unwrap();
 
         trace!("openxr mask: {:#?} {:#?}", mask.indices, mask.vertices);
@@ -357,50 +543,148 @@ impl vr::IVRSystem022_Interface for System {
         size: u32,
         pose: *mut vr::TrackedDevicePose_t,
     ) -> bool {
-        for (current, prev, hand) in [
-            (
-                self.openxr.left_hand.connected(),
-                &self.last_connected_hands.left,
-                Hand::Left,
-            ),
-            (
-                self.openxr.right_hand.connected(),
-                &self.last_connected_hands.right,
-                Hand::Right,
-            ),
-        ] {
-            if prev
-                .compare_exchange(!current, current, Ordering::Relaxed, Ordering::Relaxed)
-                .is_ok()
-            {
-                debug!(
-                    "sending {hand:?} {}connected",
-                    if current { "" } else { "not " }
-                );
-
-                // Since the VREvent_t struct can be a variable size, it seems a little dangerous to
-                // create a reference to it, so we'll just operate through pointers.
-                // The eventType, trackedDeviceIndex, and eventAgeSeconds fields have always existed.
-                unsafe {
-                    (&raw mut (*event).eventType).write(if current {
-                        vr::EVREventType::TrackedDeviceActivated as u32
-                    } else {
-                        vr::EVREventType::TrackedDeviceDeactivated as u32
+        self.queue_device_changes();
+        let device_event = self.device_events.lock().unwrap().pop_front();
+        if let Some(DeviceEvent {
+            ty,
+            index,
+            property,
+        }) = device_event
+        {
+            const MIN_PROPERTY_EVENT_SIZE: usize = std::mem::offset_of!(vr::VREvent_t, data)
+                + std::mem::size_of::<vr::VREvent_Property_t>();
+            // Since the VREvent_t struct can be a variable size, it seems a little dangerous to
+            // create a reference to it, so we'll just operate through pointers.
+            // The eventType, trackedDeviceIndex, and eventAgeSeconds fields have always existed.
+            unsafe {
+                (&raw mut (*event).eventType).write(ty as u32);
+                (&raw mut (*event).trackedDeviceIndex).write(index);
+                (&raw mut (*event).eventAgeSeconds).write(0.0);
+                if let Some(prop) = property.filter(|_| size as usize >= MIN_PROPERTY_EVENT_SIZE) {
+                    (&raw mut (*event).data.property).write(vr::VREvent_Property_t {
+                        container: vr::k_ulInvalidPropertyContainer,
+                        prop,
                     });
+                }
+                if !pose.is_null() {
+                    pose.write(
+                        Hand::try_from(index)
+                            .ok()
+                            .and_then(|hand| {
+                                self.input
+                                    .force(|_| Input::new(self.openxr.clone()))
+                                    .get_controller_pose(hand, Some(origin))
+                            })
+                            .unwrap_or_default(),
+                    );
+                }
+            }
+            return true;
+        }
 
-                    (&raw mut (*event).trackedDeviceIndex).write(hand as u32);
-                    (&raw mut (*event).eventAgeSeconds).write(0.0);
-                    if !pose.is_null() {
-                        pose.write(
-                            self.input
-                                .force(|_| Input::new(self.openxr.clone()))
-                                .get_controller_pose(hand, Some(origin))
-                                .unwrap_or_default(),
-                        );
-                    }
+        if let Some(FocusEvent { ty, process }) = self.openxr.pop_focus_event() {
+            const MIN_PROCESS_EVENT_SIZE: usize = std::mem::offset_of!(vr::VREvent_t, data)
+                + std::mem::size_of::<vr::VREvent_Process_t>();
+            debug!("sending focus event {ty:?}");
+            unsafe {
+                (&raw mut (*event).eventType).write(ty as u32);
+                (&raw mut (*event).trackedDeviceIndex).write(vr::k_unTrackedDeviceIndexInvalid);
+                (&raw mut (*event).eventAgeSeconds).write(0.0);
+                if size as usize >= MIN_PROCESS_EVENT_SIZE {
+                    (&raw mut (*event).data.process).write(process);
+                }
+                if !pose.is_null() {
+                    pose.write(Default::default());
+                }
+            }
+            return true;
+        }
+
+        if let Some((index, connected)) =
+            OscTrackers::get().and_then(OscTrackers::poll_connection_change)
+        {
+            debug!(
+                "sending tracker {index} {}connected",
+                if connected { "" } else { "not " }
+            );
+            unsafe {
+                (&raw mut (*event).eventType).write(if connected {
+                    vr::EVREventType::TrackedDeviceActivated as u32
+                } else {
+                    vr::EVREventType::TrackedDeviceDeactivated as u32
+                });
+                (&raw mut (*event).trackedDeviceIndex).write(index);
+                (&raw mut (*event).eventAgeSeconds).write(0.0);
+                if !pose.is_null() {
+                    let data = self.openxr.session_data.get();
+                    pose.write(OscTrackers::get().unwrap().get_pose(
+                        index,
+                        &data,
+                        self.openxr.display_time.get(),
+                        origin,
+                    ));
+                }
+            }
+            return true;
+        }
+
+        if DynamicResolution::get().is_some_and(DynamicResolution::take_changed) {
+            // Like SteamVR sends when the resolution slider moves, so games re-query
+            // GetRecommendedRenderTargetSize.
+            unsafe {
+                (&raw mut (*event).eventType)
+                    .write(vr::EVREventType::SteamVRSectionSettingChanged as u32);
+                (&raw mut (*event).trackedDeviceIndex).write(vr::k_unTrackedDeviceIndex_Hmd);
+                (&raw mut (*event).eventAgeSeconds).write(0.0);
+            }
+            return true;
+        }
+
+        if let Some(ScreenshotEvent { ty, data }) =
+            self.screenshots.get().and_then(|s| s.poll_event())
+        {
+            const MIN_SCREENSHOT_EVENT_SIZE: usize = std::mem::offset_of!(vr::VREvent_t, data)
+                + std::mem::size_of::<vr::VREvent_Screenshot_t>();
+            unsafe {
+                (&raw mut (*event).eventType).write(ty as u32);
+                (&raw mut (*event).trackedDeviceIndex).write(vr::k_unTrackedDeviceIndex_Hmd);
+                (&raw mut (*event).eventAgeSeconds).write(0.0);
+                if size as usize >= MIN_SCREENSHOT_EVENT_SIZE {
+                    (&raw mut (*event).data.screenshot).write(data);
+                }
+            }
+            return true;
+        }
+
+        if let Some(ty) = self.overlays.get().and_then(|o| o.poll_dashboard_event()) {
+            debug!("sending dashboard event {ty:?}");
+            unsafe {
+                (&raw mut (*event).eventType).write(ty as u32);
+                (&raw mut (*event).trackedDeviceIndex).write(vr::k_unTrackedDeviceIndexInvalid);
+                (&raw mut (*event).eventAgeSeconds).write(0.0);
+                if !pose.is_null() {
+                    pose.write(Default::default());
+                }
+            }
+            return true;
+        }
+
+        if let Some((ty, keyboard)) = self.overlays.get().and_then(|o| o.poll_keyboard_event()) {
+            const MIN_KEYBOARD_EVENT_SIZE: usize = std::mem::offset_of!(vr::VREvent_t, data)
+                + std::mem::size_of::<vr::VREvent_Keyboard_t>();
+            debug!("sending keyboard event {ty:?}");
+            unsafe {
+                (&raw mut (*event).eventType).write(ty as u32);
+                (&raw mut (*event).trackedDeviceIndex).write(vr::k_unTrackedDeviceIndexInvalid);
+                (&raw mut (*event).eventAgeSeconds).write(0.0);
+                if size as usize >= MIN_KEYBOARD_EVENT_SIZE {
+                    (&raw mut (*event).data.keyboard).write(keyboard);
+                }
+                if !pose.is_null() {
+                    pose.write(Default::default());
                 }
-                return true;
             }
+            return true;
         }
 
         self.input.get().is_some_and(|input| {
@@ -409,9 +693,10 @@ impl vr::IVRSystem022_Interface for System {
                 unsafe {
                     let index = (&raw const (*event).trackedDeviceIndex).read();
                     pose.write(
-                        input
-                            .get_controller_pose(Hand::try_from(index).unwrap(), None)
-                            .unwrap(),
+                        Hand::try_from(index)
+                            .ok()
+                            .and_then(|hand| input.get_controller_pose(hand, None))
+                            .unwrap_or_default(),
                     );
                 }
             }
@@ -474,6 +759,11 @@ impl vr::IVRSystem022_Interface for System {
             x if Hand::try_from(x).is_ok() => self.input.get().and_then(|i| {
                 i.get_controller_string_tracked_property(Hand::try_from(x).unwrap(), prop)
             }),
+            x if OscTrackers::is_tracker_index(x) => OscTrackers::string_property(x, prop),
+            GAMEPAD_DEVICE_INDEX => self
+                .input
+                .get()
+                .and_then(|i| i.get_gamepad_string_tracked_property(prop)),
             _ => None,
         };
 
@@ -535,6 +825,10 @@ impl vr::IVRSystem022_Interface for System {
             x if Hand::try_from(x).is_ok() => self.input.get().and_then(|input| {
                 input.get_controller_uint_tracked_property(Hand::try_from(x).unwrap(), prop)
             }),
+            GAMEPAD_DEVICE_INDEX => self
+                .input
+                .get()
+                .and_then(|input| input.get_gamepad_uint_tracked_property(prop)),
             _ => None,
         }
         .unwrap_or_else(|| {
@@ -564,6 +858,10 @@ impl vr::IVRSystem022_Interface for System {
             x if Hand::try_from(x).is_ok() => self.input.get().and_then(|input| {
                 input.get_controller_int_tracked_property(Hand::try_from(x).unwrap(), prop)
             }),
+            GAMEPAD_DEVICE_INDEX => self
+                .input
+                .get()
+                .and_then(|input| input.get_gamepad_int_tracked_property(prop)),
             _ => None,
         }
         .unwrap_or_else(|| {
@@ -580,6 +878,33 @@ impl vr::IVRSystem022_Interface for System {
         error: *mut vr::ETrackedPropertyError,
     ) -> f32 {
         debug!(target: log_tags::TRACKED_PROP, "requesting float property: {prop:?} ({device_index})");
+        if let Ok(hand) = Hand::try_from(device_index) {
+            let value = self
+                .input
+                .get()
+                .and_then(|input| input.get_controller_float_tracked_property(hand, prop));
+            if let Some(error) = unsafe { error.as_mut() } {
+                *error = match value {
+                    Some(_) => vr::ETrackedPropertyError::Success,
+                    None => vr::ETrackedPropertyError::UnknownProperty,
+                };
+            }
+            return value.unwrap_or_default();
+        }
+
+        if OscTrackers::is_tracker_index(device_index) {
+            let value = (prop == vr::ETrackedDeviceProperty::DeviceBatteryPercentage_Float)
+                .then(|| OscTrackers::get()?.battery(device_index))
+                .flatten();
+            if let Some(error) = unsafe { error.as_mut() } {
+                *error = match value {
+                    Some(_) => vr::ETrackedPropertyError::Success,
+                    None => vr::ETrackedPropertyError::UnknownProperty,
+                };
+            }
+            return value.unwrap_or_default();
+        }
+
         if device_index != vr::k_unTrackedDeviceIndex_Hmd {
             if let Some(error) = unsafe { error.as_mut() } {
                 *error = vr::ETrackedPropertyError::UnknownProperty;
@@ -608,10 +933,37 @@ impl vr::IVRSystem022_Interface for System {
         err: *mut vr::ETrackedPropertyError,
     ) -> bool {
         debug!(target: log_tags::TRACKED_PROP, "requesting bool property: {prop:?} ({device_index})");
+        let value = match Hand::try_from(device_index) {
+            Ok(hand) => self
+                .input
+                .get()
+                .and_then(|input| input.get_controller_bool_tracked_property(hand, prop)),
+            Err(_) if device_index == vr::k_unTrackedDeviceIndex_Hmd => match prop {
+                // The legacy proximity button follows user presence.
+                vr::ETrackedDeviceProperty::ContainsProximitySensor_Bool => Some(true),
+                // No OpenXR extension reports the headset's battery.
+                vr::ETrackedDeviceProperty::DeviceProvidesBatteryStatus_Bool => Some(false),
+                _ => None,
+            },
+            Err(_) if OscTrackers::is_tracker_index(device_index) => {
+                let has_battery =
+                    || OscTrackers::get().is_some_and(|t| t.battery(device_index).is_some());
+                match prop {
+                    vr::ETrackedDeviceProperty::DeviceProvidesBatteryStatus_Bool => {
+                        Some(has_battery())
+                    }
+                    _ => None,
+                }
+            }
+            Err(_) => None,
+        };
         if let Some(err) = unsafe { err.as_mut() } {
-            *err = vr::ETrackedPropertyError::UnknownProperty;
+            *err = match value {
+                Some(_) => vr::ETrackedPropertyError::Success,
+                None => vr::ETrackedPropertyError::UnknownProperty,
+            };
         }
-        false
+        value.unwrap_or(false)
     }
 
     fn IsTrackedDeviceConnected(&self, device_index: vr::TrackedDeviceIndex_t) -> bool {
@@ -621,6 +973,10 @@ impl vr::IVRSystem022_Interface for System {
                 Hand::Left => self.openxr.left_hand.connected(),
                 Hand::Right => self.openxr.right_hand.connected(),
             },
+            x if OscTrackers::is_tracker_index(x) => {
+                OscTrackers::get().is_some_and(|trackers| trackers.connected(x))
+            }
+            GAMEPAD_DEVICE_INDEX => self.openxr.gamepad.connected(),
             _ => false,
         }
     }
@@ -635,6 +991,12 @@ impl vr::IVRSystem022_Interface for System {
                     vr::ETrackedDeviceClass::Invalid
                 }
             }
+            x if OscTrackers::is_tracker_index(x) && self.IsTrackedDeviceConnected(x) => {
+                vr::ETrackedDeviceClass::GenericTracker
+            }
+            GAMEPAD_DEVICE_INDEX if self.IsTrackedDeviceConnected(GAMEPAD_DEVICE_INDEX) => {
+                vr::ETrackedDeviceClass::Controller
+            }
             _ => vr::ETrackedDeviceClass::Invalid,
         }
     }
@@ -647,6 +1009,8 @@ impl vr::IVRSystem022_Interface for System {
                 Hand::Left => vr::ETrackedControllerRole::LeftHand,
                 Hand::Right => vr::ETrackedControllerRole::RightHand,
             },
+            // SteamVR reports gamepads as controllers that aren't held in either hand.
+            GAMEPAD_DEVICE_INDEX => vr::ETrackedControllerRole::OptOut,
             _ => vr::ETrackedControllerRole::Invalid,
         }
     }
@@ -685,7 +1049,13 @@ impl vr::IVRSystem022_Interface for System {
         device_index: vr::TrackedDeviceIndex_t,
     ) -> vr::EDeviceActivityLevel {
         match device_index {
-            vr::k_unTrackedDeviceIndex_Hmd => vr::EDeviceActivityLevel::UserInteraction,
+            vr::k_unTrackedDeviceIndex_Hmd => {
+                if self.openxr.user_present() {
+                    vr::EDeviceActivityLevel::UserInteraction
+                } else {
+                    vr::EDeviceActivityLevel::Standby
+                }
+            }
             x if Hand::try_from(x).is_ok() => {
                 if self.IsTrackedDeviceConnected(x) {
                     vr::EDeviceActivityLevel::UserInteraction
@@ -693,6 +1063,11 @@ impl vr::IVRSystem022_Interface for System {
                     vr::EDeviceActivityLevel::Unknown
                 }
             }
+            x if (OscTrackers::is_tracker_index(x) || x == GAMEPAD_DEVICE_INDEX)
+                && self.IsTrackedDeviceConnected(x) =>
+            {
+                vr::EDeviceActivityLevel::UserInteraction
+            }
             _ => vr::EDeviceActivityLevel::Unknown,
         }
     }
@@ -786,7 +1161,7 @@ impl vr::IVRSystem019On020 for System {
 
 impl vr::IVRSystem017On019 for System {
     fn IsInputFocusCapturedByAnotherProcess(&self) -> bool {
-        false
+        !self.openxr.has_input_focus()
     }
     fn ReleaseInputFocus(&self) {}
     fn CaptureInputFocus(&self) -> bool {
@@ -861,4 +1236,135 @@ mod tests {
         test_prop(vr::ETrackedDeviceProperty::ManufacturerName_String);
         test_prop(vr::ETrackedDeviceProperty::ControllerType_String);
     }
+
+    #[test]
+    fn battery_changes_in_whole_percent() {
+        let known = KnownBattery::default();
+        assert!(known.update(Some(0.8)));
+        assert!(!known.update(Some(0.801)));
+        assert!(known.update(Some(0.79)));
+        assert!(known.update(None));
+        assert!(!known.update(None));
+    }
+
+    #[test]
+    fn headset_removal_ends_user_interaction() {
+        let xr = Arc::new(RealOpenXrData::new(&Injector::default()).unwrap());
+        let injector = Injector::default();
+        let system = System::new(xr.clone(), &injector);
+        let session = xr.session_data.get().session.as_raw();
+
+        let poll = |state| {
+            fakexr::set_session_state(session, state);
+            xr.poll_events();
+            let mut events = Vec::new();
+            let mut event: vr::VREvent_t = unsafe { std::mem::zeroed() };
+            while system.PollNextEvent(&mut event, std::mem::size_of_val(&event) as u32) {
+                events.push((event.eventType, event.trackedDeviceIndex));
+            }
+            events
+        };
+        let interaction = |ty: vr::EVREventType| (ty as u32, vr::k_unTrackedDeviceIndex_Hmd);
+
+        assert!(system.GetBoolTrackedDeviceProperty(
+            vr::k_unTrackedDeviceIndex_Hmd,
+            vr::ETrackedDeviceProperty::ContainsProximitySensor_Bool,
+            std::ptr::null_mut()
+        ));
+        poll(xr::SessionState::FOCUSED);
+
+        let events = poll(xr::SessionState::STOPPING);
+        assert!(events.contains(&interaction(
+            vr::EVREventType::TrackedDeviceUserInteractionEnded
+        )));
+        assert_eq!(
+            system.GetTrackedDeviceActivityLevel(vr::k_unTrackedDeviceIndex_Hmd),
+            vr::EDeviceActivityLevel::Standby
+        );
+
+        assert!(xr.end_stopped_session());
+        let events = poll(xr::SessionState::READY);
+        assert!(events.contains(&interaction(
+            vr::EVREventType::TrackedDeviceUserInteractionStarted
+        )));
+        assert_eq!(
+            system.GetTrackedDeviceActivityLevel(vr::k_unTrackedDeviceIndex_Hmd),
+            vr::EDeviceActivityLevel::UserInteraction
+        );
+    }
+
+    #[test]
+    fn focus_events() {
+        let xr = Arc::new(RealOpenXrData::new(&Injector::default()).unwrap());
+        let injector = Injector::default();
+        let system = System::new(xr.clone(), &injector);
+        let session = xr.session_data.get().session.as_raw();
+
+        let poll = |state| {
+            fakexr::set_session_state(session, state);
+            xr.poll_events();
+            let mut events = Vec::new();
+            let mut event: vr::VREvent_t = unsafe { std::mem::zeroed() };
+            while system.PollNextEvent(&mut event, std::mem::size_of_val(&event) as u32) {
+                events.push((event.eventType, unsafe { event.data.process.pid }));
+            }
+            events
+        };
+        let pid = std::process::id();
+
+        // Starting up doesn't change focus.
+        assert!(poll(xr::SessionState::VISIBLE).is_empty());
+        assert!(poll(xr::SessionState::FOCUSED).is_empty());
+        assert!(system.IsInputAvailable());
+        assert!(!system.ShouldApplicationPause());
+
+        assert_eq!(
+            poll(xr::SessionState::VISIBLE),
+            [
+                (vr::EVREventType::InputFocusCaptured as u32, 0),
+                (vr::EVREventType::InputFocusChanged as u32, 0)
+            ]
+        );
+        assert!(!system.IsInputAvailable());
+        assert!(system.ShouldApplicationPause());
+
+        assert_eq!(
+            poll(xr::SessionState::SYNCHRONIZED),
+            [
+                (vr::EVREventType::SceneApplicationChanged as u32, 0),
+                (vr::EVREventType::SceneApplicationStateChanged as u32, 0)
+            ]
+        );
+        assert_eq!(
+            xr.scene_application_state(),
+            vr::EVRSceneApplicationState::Waiting
+        );
+
+        poll(xr::SessionState::VISIBLE);
+        assert_eq!(
+            poll(xr::SessionState::FOCUSED),
+            [
+                (vr::EVREventType::InputFocusReleased as u32, pid),
+                (vr::EVREventType::InputFocusChanged as u32, pid)
+            ]
+        );
+        assert!(!system.ShouldApplicationPause());
+
+        let events = poll(xr::SessionState::STOPPING);
+        assert_eq!(
+            events.last(),
+            Some(&(vr::EVREventType::EnterStandbyMode as u32, 0))
+        );
+        assert_eq!(
+            poll(xr::SessionState::EXITING),
+            [
+                (vr::EVREventType::SceneApplicationStateChanged as u32, 0),
+                (vr::EVREventType::Quit as u32, pid)
+            ]
+        );
+        assert_eq!(
+            xr.scene_application_state(),
+            vr::EVRSceneApplicationState::Quitting
+        );
+    }
 }
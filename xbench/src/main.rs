@@ -0,0 +1,582 @@
+use ash::vk::{self, Handle};
+use libloading::{Library, Symbol};
+use openvr as vr;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const FORMAT: vk::Format = vk::Format::R8G8B8A8_SRGB;
+
+/// Calls a function through an OpenVR interface's vtable, the way a game would.
+macro_rules! call {
+    ($iface:expr, $func:ident($($arg:expr),* $(,)?)) => {{
+        let iface = $iface;
+        unsafe { ((*(*iface).vtable_).$func)(iface, $($arg),*) }
+    }};
+}
+
+fn fail(msg: impl std::fmt::Display) -> ! {
+    eprintln!("{msg}");
+    std::process::exit(1);
+}
+
+struct Args {
+    lib: PathBuf,
+    duration: Duration,
+}
+
+impl Args {
+    fn parse() -> Self {
+        let mut lib = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../target/release/libxrizer.so"
+        ));
+        let mut duration = Duration::from_secs(30);
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .unwrap_or_else(|| fail(format!("{arg} needs a value")))
+            };
+            match arg.as_str() {
+                "--lib" => lib = value().into(),
+                "--seconds" => {
+                    let secs = value()
+                        .parse()
+                        .unwrap_or_else(|e| fail(format!("Invalid number of seconds: {e}")));
+                    duration = Duration::from_secs_f64(secs);
+                }
+                _ => fail(format!(
+                    "Unknown argument {arg}\nUsage: xbench [--lib <path to libxrizer.so>] [--seconds <n>]"
+                )),
+            }
+        }
+        Self { lib, duration }
+    }
+}
+
+struct Runtime {
+    _lib: Library,
+    core: *mut vr::IVRClientCore003,
+    system: *mut vr::IVRSystem022,
+    compositor: *mut vr::IVRCompositor028,
+}
+
+impl Runtime {
+    fn load(path: &Path) -> Self {
+        let lib = unsafe { Library::new(path) }
+            .unwrap_or_else(|e| fail(format!("Couldn't load {}: {e}", path.display())));
+        let factory: Symbol<unsafe extern "C" fn(*const c_char, *mut i32) -> *mut c_void> =
+            unsafe { lib.get(b"VRClientCoreFactory\0") }.unwrap_or_else(|e| {
+                fail(format!("{} isn't an OpenVR runtime: {e}", path.display()))
+            });
+        let core: *mut vr::IVRClientCore003 =
+            unsafe { factory(c"IVRClientCore_003".as_ptr(), std::ptr::null_mut()) }.cast();
+        if core.is_null() {
+            fail("Couldn't get IVRClientCore");
+        }
+
+        let err = call!(
+            core,
+            IVRClientCore_Init(vr::EVRApplicationType::Scene, std::ptr::null())
+        );
+        if err != vr::EVRInitError::None {
+            fail(format!("Couldn't initialize the runtime: {err:?}"));
+        }
+
+        let get_interface = |name: &CStr| {
+            let mut err = vr::EVRInitError::None;
+            let iface = call!(
+                core,
+                IVRClientCore_GetGenericInterface(name.as_ptr(), &mut err)
+            );
+            if iface.is_null() || err != vr::EVRInitError::None {
+                fail(format!("Couldn't get {name:?}: {err:?}"));
+            }
+            iface
+        };
+
+        Self {
+            system: get_interface(c"IVRSystem_022").cast(),
+            compositor: get_interface(c"IVRCompositor_028").cast(),
+            core,
+            _lib: lib,
+        }
+    }
+
+    /// Reads a space separated list from one of the Get*ExtensionsRequired functions.
+    fn extension_list(get: impl Fn(*mut c_char, u32) -> u32) -> Vec<CString> {
+        let size = get(std::ptr::null_mut(), 0);
+        if size == 0 {
+            return Vec::new();
+        }
+        let mut buf = vec![0u8; size as usize];
+        get(buf.as_mut_ptr().cast(), size);
+        CStr::from_bytes_until_nul(&buf)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .split_ascii_whitespace()
+            .map(|ext| CString::new(ext).unwrap())
+            .collect()
+    }
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        call!(self.core, IVRClientCore_Cleanup());
+    }
+}
+
+struct Gpu {
+    _entry: ash::Entry,
+    instance: ash::Instance,
+    physical_device: vk::PhysicalDevice,
+    device: ash::Device,
+    queue: vk::Queue,
+    queue_family_index: u32,
+}
+
+impl Gpu {
+    fn new(runtime: &Runtime) -> Self {
+        let entry = unsafe { ash::Entry::load() }
+            .unwrap_or_else(|e| fail(format!("Couldn't load Vulkan: {e}")));
+
+        let inst_exts = Runtime::extension_list(|buf, size| {
+            call!(
+                runtime.compositor,
+                IVRCompositor_GetVulkanInstanceExtensionsRequired(buf, size)
+            )
+        });
+        let inst_exts: Vec<*const c_char> = inst_exts.iter().map(|ext| ext.as_ptr()).collect();
+        let instance = unsafe {
+            entry.create_instance(
+                &vk::InstanceCreateInfo::default()
+                    .application_info(
+                        &vk::ApplicationInfo::default()
+                            .api_version(vk::API_VERSION_1_1)
+                            .application_name(c"xrizer benchmark"),
+                    )
+                    .enabled_extension_names(&inst_exts),
+                None,
+            )
+        }
+        .unwrap_or_else(|e| fail(format!("Couldn't create Vulkan instance: {e}")));
+
+        let mut physical_device = 0;
+        call!(
+            runtime.system,
+            IVRSystem_GetOutputDevice(
+                &mut physical_device,
+                vr::ETextureType::Vulkan,
+                instance.handle().as_raw() as _,
+            )
+        );
+        let physical_device = vk::PhysicalDevice::from_raw(physical_device);
+        if physical_device == vk::PhysicalDevice::null() {
+            fail("The runtime didn't pick a Vulkan device");
+        }
+
+        let dev_exts = Runtime::extension_list(|buf, size| {
+            call!(
+                runtime.compositor,
+                IVRCompositor_GetVulkanDeviceExtensionsRequired(
+                    physical_device.as_raw() as _,
+                    buf,
+                    size
+                )
+            )
+        });
+        let dev_exts: Vec<*const c_char> = dev_exts.iter().map(|ext| ext.as_ptr()).collect();
+
+        let queue_family_index =
+            unsafe { instance.get_physical_device_queue_family_properties(physical_device) }
+                .into_iter()
+                .position(|family| family.queue_flags.contains(vk::QueueFlags::GRAPHICS))
+                .unwrap_or_else(|| fail("No graphics queue")) as u32;
+
+        let device = unsafe {
+            instance.create_device(
+                physical_device,
+                &vk::DeviceCreateInfo::default()
+                    .queue_create_infos(std::slice::from_ref(
+                        &vk::DeviceQueueCreateInfo::default()
+                            .queue_family_index(queue_family_index)
+                            .queue_priorities(&[1.0]),
+                    ))
+                    .enabled_extension_names(&dev_exts),
+                None,
+            )
+        }
+        .unwrap_or_else(|e| fail(format!("Couldn't create Vulkan device: {e}")));
+        let queue = unsafe { device.get_device_queue(queue_family_index, 0) };
+
+        Self {
+            _entry: entry,
+            instance,
+            physical_device,
+            device,
+            queue,
+            queue_family_index,
+        }
+    }
+
+    fn create_eye_image(&self, extent: vk::Extent2D) -> (vk::Image, vk::DeviceMemory) {
+        let image = unsafe {
+            self.device.create_image(
+                &vk::ImageCreateInfo::default()
+                    .image_type(vk::ImageType::TYPE_2D)
+                    .format(FORMAT)
+                    .extent(vk::Extent3D {
+                        width: extent.width,
+                        height: extent.height,
+                        depth: 1,
+                    })
+                    .mip_levels(1)
+                    .array_layers(1)
+                    .samples(vk::SampleCountFlags::TYPE_1)
+                    .tiling(vk::ImageTiling::OPTIMAL)
+                    .usage(
+                        vk::ImageUsageFlags::TRANSFER_DST
+                            | vk::ImageUsageFlags::TRANSFER_SRC
+                            | vk::ImageUsageFlags::SAMPLED,
+                    )
+                    .initial_layout(vk::ImageLayout::UNDEFINED),
+                None,
+            )
+        }
+        .unwrap();
+
+        let reqs = unsafe { self.device.get_image_memory_requirements(image) };
+        let props = unsafe {
+            self.instance
+                .get_physical_device_memory_properties(self.physical_device)
+        };
+        let memory_type_index = (0..props.memory_type_count)
+            .find(|&idx| {
+                reqs.memory_type_bits & (1 << idx) != 0
+                    && props.memory_types[idx as usize]
+                        .property_flags
+                        .contains(vk::MemoryPropertyFlags::DEVICE_LOCAL)
+            })
+            .unwrap_or_else(|| fail("No device local memory for eye images"));
+        let memory = unsafe {
+            self.device.allocate_memory(
+                &vk::MemoryAllocateInfo::default()
+                    .allocation_size(reqs.size)
+                    .memory_type_index(memory_type_index),
+                None,
+            )
+        }
+        .unwrap();
+        unsafe { self.device.bind_image_memory(image, memory, 0) }.unwrap();
+
+        (image, memory)
+    }
+
+    /// Fills the eye images with a colour that cycles over time, and leaves them in the layout
+    /// Submit expects.
+    fn record_scene(&self, buf: vk::CommandBuffer, images: &[vk::Image], frame: u64) {
+        let range = vk::ImageSubresourceRange {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            layer_count: 1,
+        };
+        unsafe {
+            self.device
+                .begin_command_buffer(
+                    buf,
+                    &vk::CommandBufferBeginInfo::default()
+                        .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT),
+                )
+                .unwrap();
+
+            for (eye, &image) in images.iter().enumerate() {
+                let t = frame as f32 / 90.0 + eye as f32 * 0.5;
+                let color = [
+                    0.5 + 0.5 * t.sin(),
+                    0.5 + 0.5 * (t + 2.1).sin(),
+                    0.5 + 0.5 * (t + 4.2).sin(),
+                    1.0,
+                ];
+
+                // The runtime may still be copying last frame's contents.
+                self.device.cmd_pipeline_barrier(
+                    buf,
+                    vk::PipelineStageFlags::ALL_COMMANDS,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &[vk::ImageMemoryBarrier::default()
+                        .src_access_mask(vk::AccessFlags::empty())
+                        .dst_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+                        .old_layout(vk::ImageLayout::UNDEFINED)
+                        .new_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                        .image(image)
+                        .subresource_range(range)],
+                );
+                self.device.cmd_clear_color_image(
+                    buf,
+                    image,
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    &vk::ClearColorValue { float32: color },
+                    &[range],
+                );
+                self.device.cmd_pipeline_barrier(
+                    buf,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::PipelineStageFlags::ALL_COMMANDS,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &[vk::ImageMemoryBarrier::default()
+                        .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+                        .dst_access_mask(vk::AccessFlags::TRANSFER_READ)
+                        .old_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                        .new_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+                        .image(image)
+                        .subresource_range(range)],
+                );
+            }
+
+            self.device.end_command_buffer(buf).unwrap();
+        }
+    }
+}
+
+impl Drop for Gpu {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.destroy_device(None);
+            self.instance.destroy_instance(None);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Samples(Vec<Duration>);
+
+impl Samples {
+    fn avg(&self) -> Duration {
+        self.0.iter().sum::<Duration>() / self.0.len().max(1) as u32
+    }
+
+    fn percentile(&self, p: f64) -> Duration {
+        let mut sorted = self.0.clone();
+        sorted.sort();
+        let idx = ((sorted.len() as f64 * p) as usize).min(sorted.len().saturating_sub(1));
+        sorted.get(idx).copied().unwrap_or_default()
+    }
+
+    fn max(&self) -> Duration {
+        self.0.iter().max().copied().unwrap_or_default()
+    }
+}
+
+fn ms(d: Duration) -> String {
+    format!("{:.2} ms", d.as_secs_f64() * 1000.0)
+}
+
+fn main() {
+    let args = Args::parse();
+    let runtime = Runtime::load(&args.lib);
+    let gpu = Gpu::new(&runtime);
+
+    let (mut width, mut height) = (0, 0);
+    call!(
+        runtime.system,
+        IVRSystem_GetRecommendedRenderTargetSize(&mut width, &mut height)
+    );
+    let mut prop_err = vr::ETrackedPropertyError::Success;
+    let refresh_rate = call!(
+        runtime.system,
+        IVRSystem_GetFloatTrackedDeviceProperty(
+            vr::k_unTrackedDeviceIndex_Hmd,
+            vr::ETrackedDeviceProperty::DisplayFrequency_Float,
+            &mut prop_err,
+        )
+    );
+    let extent = vk::Extent2D { width, height };
+    let eyes = [gpu.create_eye_image(extent), gpu.create_eye_image(extent)];
+    let images = eyes.map(|(image, _)| image);
+
+    let pool = unsafe {
+        gpu.device.create_command_pool(
+            &vk::CommandPoolCreateInfo::default()
+                .flags(vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER)
+                .queue_family_index(gpu.queue_family_index),
+            None,
+        )
+    }
+    .unwrap();
+    let buf = unsafe {
+        gpu.device.allocate_command_buffers(
+            &vk::CommandBufferAllocateInfo::default()
+                .command_pool(pool)
+                .command_buffer_count(1),
+        )
+    }
+    .unwrap()[0];
+    let fence = unsafe {
+        gpu.device.create_fence(
+            &vk::FenceCreateInfo::default().flags(vk::FenceCreateFlags::SIGNALED),
+            None,
+        )
+    }
+    .unwrap();
+
+    println!(
+        "Rendering {width}x{height} per eye at {refresh_rate} Hz for {:?}",
+        args.duration
+    );
+
+    let mut poses = [vr::TrackedDevicePose_t::default(); vr::k_unMaxTrackedDeviceCount as usize];
+    let mut frame_times = Samples::default();
+    let mut wait_times = Samples::default();
+    let mut submit_times = Samples::default();
+    let mut frame = 0;
+    let start = Instant::now();
+    let mut last_frame: Option<Instant> = None;
+
+    while start.elapsed() < args.duration {
+        let wait_start = Instant::now();
+        let err = call!(
+            runtime.compositor,
+            IVRCompositor_WaitGetPoses(
+                poses.as_mut_ptr(),
+                poses.len() as u32,
+                std::ptr::null_mut(),
+                0,
+            )
+        );
+        let now = Instant::now();
+        if err != vr::EVRCompositorError::None {
+            // The session isn't running yet, or is paused.
+            std::thread::sleep(Duration::from_millis(10));
+            continue;
+        }
+        wait_times.0.push(now - wait_start);
+        if let Some(last) = last_frame.replace(now) {
+            frame_times.0.push(now - last);
+        }
+
+        unsafe {
+            gpu.device
+                .wait_for_fences(&[fence], true, u64::MAX)
+                .unwrap();
+            gpu.device.reset_fences(&[fence]).unwrap();
+        }
+        gpu.record_scene(buf, &images, frame);
+        unsafe {
+            gpu.device
+                .queue_submit(
+                    gpu.queue,
+                    &[vk::SubmitInfo::default().command_buffers(&[buf])],
+                    fence,
+                )
+                .unwrap();
+        }
+
+        let submit_start = Instant::now();
+        for (eye, image) in [vr::EVREye::Left, vr::EVREye::Right]
+            .into_iter()
+            .zip(images)
+        {
+            let mut data = vr::VRVulkanTextureData_t {
+                m_nImage: image.as_raw(),
+                m_pDevice: gpu.device.handle().as_raw() as _,
+                m_pPhysicalDevice: gpu.physical_device.as_raw() as _,
+                m_pInstance: gpu.instance.handle().as_raw() as _,
+                m_pQueue: gpu.queue.as_raw() as _,
+                m_nQueueFamilyIndex: gpu.queue_family_index,
+                m_nWidth: width,
+                m_nHeight: height,
+                m_nFormat: FORMAT.as_raw() as _,
+                m_nSampleCount: 1,
+            };
+            let texture = vr::Texture_t {
+                handle: (&raw mut data).cast(),
+                eType: vr::ETextureType::Vulkan,
+                eColorSpace: vr::EColorSpace::Auto,
+            };
+            let err = call!(
+                runtime.compositor,
+                IVRCompositor_Submit(eye, &texture, std::ptr::null(), vr::EVRSubmitFlags::Default,)
+            );
+            if err != vr::EVRCompositorError::None {
+                eprintln!("Submit for {eye:?} failed: {err:?}");
+            }
+        }
+        submit_times.0.push(submit_start.elapsed());
+        frame += 1;
+    }
+
+    let mut stats = vr::Compositor_CumulativeStats::default();
+    call!(
+        runtime.compositor,
+        IVRCompositor_GetCumulativeStats(
+            &mut stats,
+            std::mem::size_of::<vr::Compositor_CumulativeStats>() as u32,
+        )
+    );
+
+    let elapsed = start.elapsed();
+    let budget = Duration::from_secs_f32(1.0 / refresh_rate);
+    let missed = frame_times
+        .0
+        .iter()
+        .filter(|&&time| time > budget.mul_f32(1.5))
+        .count();
+    println!();
+    println!(
+        "Frames:          {frame} in {:.1} s ({:.1} fps, budget {})",
+        elapsed.as_secs_f64(),
+        frame as f64 / elapsed.as_secs_f64(),
+        ms(budget)
+    );
+    println!(
+        "Frame time:      avg {}, p50 {}, p99 {}, max {}",
+        ms(frame_times.avg()),
+        ms(frame_times.percentile(0.5)),
+        ms(frame_times.percentile(0.99)),
+        ms(frame_times.max())
+    );
+    println!("Missed frames:   {missed}");
+    println!(
+        "WaitGetPoses:    avg {}, max {}",
+        ms(wait_times.avg()),
+        ms(wait_times.max())
+    );
+    println!(
+        "Submit (2 eyes): avg {}, p99 {}, max {}",
+        ms(submit_times.avg()),
+        ms(submit_times.percentile(0.99)),
+        ms(submit_times.max())
+    );
+    let submits = stats.m_nNumFrameSubmits.max(1) as f64;
+    println!(
+        "Compositor:      {} presents, {} dropped, {} reprojected, avg cpu {:.2} ms, avg gpu {:.2} ms",
+        stats.m_nNumFramePresents,
+        stats.m_nNumDroppedFrames,
+        stats.m_nNumReprojectedFrames,
+        stats.m_flSumCompositorCPUTimeMS / submits,
+        stats.m_flSumCompositorGPUTimeMS / submits,
+    );
+
+    unsafe {
+        gpu.device.device_wait_idle().unwrap();
+    }
+    // The runtime has to let go of the images before they're destroyed.
+    drop(runtime);
+    unsafe {
+        gpu.device.destroy_fence(fence, None);
+        gpu.device.destroy_command_pool(pool, None);
+        for (image, memory) in eyes {
+            gpu.device.destroy_image(image, None);
+            gpu.device.free_memory(memory, None);
+        }
+    }
+}